@@ -0,0 +1,258 @@
+//! Derive macros for `ambara-plugin-sdk`.
+//!
+//! Do not depend on this crate directly; use the re-exports from
+//! `ambara_plugin_sdk` instead, since the generated code refers to items by
+//! their `::ambara_plugin_sdk` paths.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Expr, Fields, LitStr, Path};
+
+/// Implement `Plugin` for a struct and export its `ambara_plugin_vtable`.
+///
+/// ```rust,ignore
+/// #[derive(Default, AmbaraPlugin)]
+/// #[plugin(filters(Invert, Brightness), init = Self::configure)]
+/// struct MyPlugin { /* ... */ }
+/// ```
+///
+/// Supported `#[plugin(...)]` keys:
+///
+/// * `filters(A, B, ...)` — filter types (each `Default + PluginFilter`).
+/// * `init = path` — `fn(&mut Self, &serde_json::Value) -> Result<(), String>`.
+/// * `health_check = path` — `fn(&Self) -> bool`.
+///
+/// Only one type per plugin crate may derive `AmbaraPlugin`, because the
+/// vtable symbol name is fixed.
+#[proc_macro_derive(AmbaraPlugin, attributes(plugin))]
+pub fn derive_ambara_plugin(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_plugin(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `FilterParams` for a struct of typed parameters.
+///
+/// ```rust,ignore
+/// #[derive(FilterParams)]
+/// struct BrightnessParams {
+///     #[param(default = 0.0, min = -1.0, max = 1.0, description = "Offset")]
+///     amount: f64,
+/// }
+/// ```
+///
+/// Supported `#[param(...)]` keys: `name`, `display_name`, `description`,
+/// `group`, `default`, `min`, `max`. Fields without a `default` use
+/// `Default::default()`.
+#[proc_macro_derive(FilterParams, attributes(param))]
+pub fn derive_filter_params(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_params(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_plugin(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "AmbaraPlugin cannot be derived for generic types",
+        ));
+    }
+
+    let mut filters: Vec<Path> = Vec::new();
+    let mut init: Option<Expr> = None;
+    let mut health_check: Option<Expr> = None;
+
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("plugin")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("filters") {
+                meta.parse_nested_meta(|filter| {
+                    filters.push(filter.path);
+                    Ok(())
+                })
+            } else if meta.path.is_ident("init") {
+                init = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("health_check") {
+                health_check = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `filters`, `init` or `health_check`"))
+            }
+        })?;
+    }
+
+    let name = &input.ident;
+    let init_fn = init.map(|path| {
+        quote! {
+            fn init(
+                &mut self,
+                config: &::ambara_plugin_sdk::serde_json::Value,
+            ) -> ::core::result::Result<(), ::std::string::String> {
+                (#path)(self, config)
+            }
+        }
+    });
+    let health_fn = health_check.map(|path| {
+        quote! {
+            fn health_check(&self) -> bool {
+                (#path)(self)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl ::ambara_plugin_sdk::Plugin for #name {
+            fn filters(
+                &self,
+            ) -> ::std::vec::Vec<::std::boxed::Box<dyn ::ambara_plugin_sdk::PluginFilter>> {
+                ::std::vec![
+                    #( ::std::boxed::Box::new(<#filters as ::core::default::Default>::default()) ),*
+                ]
+            }
+
+            #init_fn
+            #health_fn
+        }
+
+        ::ambara_plugin_sdk::export_plugin!(#name);
+    })
+}
+
+#[derive(Default)]
+struct ParamAttrs {
+    name: Option<LitStr>,
+    display_name: Option<LitStr>,
+    description: Option<LitStr>,
+    group: Option<LitStr>,
+    default: Option<Expr>,
+    min: Option<Expr>,
+    max: Option<Expr>,
+}
+
+fn parse_param_attrs(field: &syn::Field) -> syn::Result<ParamAttrs> {
+    let mut attrs = ParamAttrs::default();
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("param")) {
+        attr.parse_nested_meta(|meta| {
+            let key = meta
+                .path
+                .get_ident()
+                .map(ToString::to_string)
+                .unwrap_or_default();
+            match key.as_str() {
+                "name" => attrs.name = Some(meta.value()?.parse()?),
+                "display_name" => attrs.display_name = Some(meta.value()?.parse()?),
+                "description" => attrs.description = Some(meta.value()?.parse()?),
+                "group" => attrs.group = Some(meta.value()?.parse()?),
+                "default" => attrs.default = Some(meta.value()?.parse()?),
+                "min" => attrs.min = Some(meta.value()?.parse()?),
+                "max" => attrs.max = Some(meta.value()?.parse()?),
+                _ => {
+                    return Err(meta.error(
+                        "expected one of `name`, `display_name`, `description`, \
+                         `group`, `default`, `min`, `max`",
+                    ))
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(attrs)
+}
+
+fn expand_params(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FilterParams can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FilterParams requires named fields",
+        ));
+    };
+
+    let sdk = quote!(::ambara_plugin_sdk);
+    let mut definitions = Vec::new();
+    let mut extractions = Vec::new();
+
+    for field in &fields.named {
+        let attrs = parse_param_attrs(field)?;
+        let ident = field.ident.as_ref().expect("named field");
+        let ty = &field.ty;
+        let param_name = attrs
+            .name
+            .as_ref()
+            .map_or_else(|| ident.to_string(), LitStr::value);
+
+        let default = match &attrs.default {
+            Some(expr) => quote!(::core::convert::Into::<#ty>::into(#expr)),
+            None => quote!(<#ty as ::core::default::Default>::default()),
+        };
+
+        let mut builder = quote! {
+            #sdk::ambara::core::port::ParameterDefinition::new(
+                #param_name,
+                <#ty as #sdk::ParamType>::port_type(),
+                <#ty as #sdk::ParamType>::into_value(#default),
+            )
+        };
+        if let Some(display_name) = &attrs.display_name {
+            builder = quote!(#builder.with_display_name(#display_name));
+        }
+        if let Some(description) = &attrs.description {
+            builder = quote!(#builder.with_description(#description));
+        }
+        if let Some(group) = &attrs.group {
+            builder = quote!(#builder.with_group(#group));
+        }
+        builder = match (&attrs.min, &attrs.max) {
+            (Some(min), Some(max)) => {
+                quote!(#builder.with_range((#min) as f64, (#max) as f64))
+            }
+            (Some(min), None) => quote! {
+                #builder.with_constraint(
+                    #sdk::ambara::core::port::Constraint::MinValue((#min) as f64)
+                )
+            },
+            (None, Some(max)) => quote! {
+                #builder.with_constraint(
+                    #sdk::ambara::core::port::Constraint::MaxValue((#max) as f64)
+                )
+            },
+            (None, None) => builder,
+        };
+        definitions.push(builder);
+
+        extractions.push(quote! {
+            #ident: #sdk::params::extract::<#ty>(params, #param_name, || #default)?
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #sdk::FilterParams for #name #ty_generics #where_clause {
+            fn definitions() -> ::std::vec::Vec<#sdk::ambara::core::port::ParameterDefinition> {
+                ::std::vec![ #( #definitions ),* ]
+            }
+
+            fn from_parameters(
+                params: &::std::collections::HashMap<
+                    ::std::string::String,
+                    #sdk::ambara::core::types::Value,
+                >,
+            ) -> ::core::result::Result<Self, ::std::string::String> {
+                ::core::result::Result::Ok(Self {
+                    #( #extractions ),*
+                })
+            }
+        }
+    })
+}
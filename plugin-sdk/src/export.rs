@@ -0,0 +1,550 @@
+//! FFI glue behind `export_plugin!`.
+//!
+//! [`vtable`] builds a [`PluginVTable`] whose entry points are monomorphised
+//! for a [`Plugin`] type. Each entry point only converts raw pointers into
+//! slices and wraps the call in `catch_unwind`; the actual work happens in
+//! the safe methods of [`PluginInstance`], which can be unit tested without
+//! going through the ABI.
+
+use crate::filter::{Plugin, PluginFilter};
+use crate::with_default_parameters;
+
+use ambara::core::context::{ExecutionContext, ValidationContext};
+use ambara::core::error::NodeId;
+use ambara::core::node::NodeMetadata;
+use ambara::plugins::api::{AbiResult, PluginHandle, PluginVTable, HOST_ABI_VERSION};
+use ambara::plugins::marshal;
+
+use indexmap::IndexMap;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Build the vtable for plugin type `P`.
+#[must_use]
+pub const fn vtable<P: Plugin>() -> PluginVTable {
+    PluginVTable {
+        abi_version: HOST_ABI_VERSION,
+        plugin_create: plugin_create::<P>,
+        plugin_destroy: plugin_destroy::<P>,
+        plugin_init: plugin_init::<P>,
+        filter_count: filter_count::<P>,
+        filter_id_at: filter_id_at::<P>,
+        filter_metadata_json: filter_metadata_json::<P>,
+        filter_execute: filter_execute::<P>,
+        filter_validate: filter_validate::<P>,
+        plugin_health_check: plugin_health_check::<P>,
+    }
+}
+
+/// One exported filter with its cached ABI representations.
+struct ExportedFilter {
+    c_id: CString,
+    metadata: NodeMetadata,
+    metadata_json: String,
+    filter: Box<dyn PluginFilter>,
+}
+
+/// The object behind a [`PluginHandle`].
+pub struct PluginInstance<P: Plugin> {
+    plugin: P,
+    filters: IndexMap<String, ExportedFilter>,
+    panicked: AtomicBool,
+}
+
+impl<P: Plugin> Default for PluginInstance<P> {
+    fn default() -> Self {
+        Self {
+            plugin: P::default(),
+            filters: IndexMap::new(),
+            panicked: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<P: Plugin> PluginInstance<P> {
+    /// Run [`Plugin::init`] and build the filter table.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the configuration is not JSON, `init` fails, or
+    /// two filters share an ID.
+    pub fn init(&mut self, config_json: &[u8]) -> Result<(), String> {
+        let config: serde_json::Value = if config_json.is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_slice(config_json).map_err(|e| format!("invalid config: {e}"))?
+        };
+        self.plugin.init(&config)?;
+
+        let mut filters = IndexMap::new();
+        for filter in self.plugin.filters() {
+            let metadata = filter.metadata();
+            let c_id = CString::new(metadata.id.clone())
+                .map_err(|_| format!("filter ID '{}' contains a NUL byte", metadata.id))?;
+            let metadata_json = serde_json::to_string(&metadata).map_err(|e| e.to_string())?;
+            let id = metadata.id.clone();
+            let exported = ExportedFilter {
+                c_id,
+                metadata,
+                metadata_json,
+                filter,
+            };
+            if filters.insert(id.clone(), exported).is_some() {
+                return Err(format!("duplicate filter ID '{id}'"));
+            }
+        }
+        self.filters = filters;
+        Ok(())
+    }
+
+    /// Number of exported filters.
+    #[must_use]
+    pub fn filter_count(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Metadata JSON for `filter_id`.
+    #[must_use]
+    pub fn metadata_json(&self, filter_id: &str) -> Option<&str> {
+        self.filters.get(filter_id).map(|f| f.metadata_json.as_str())
+    }
+
+    /// Validate a node; returns the error messages (empty when valid).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AbiResult::ErrInvalidInput`] for an unknown filter or
+    /// undecodable JSON.
+    pub fn validate(
+        &self,
+        filter_id: &str,
+        inputs_json: &[u8],
+        params_json: &[u8],
+    ) -> Result<Vec<String>, AbiResult> {
+        let exported = self.filters.get(filter_id).ok_or(AbiResult::ErrInvalidInput)?;
+        let inputs = marshal::map_from_json(inputs_json).map_err(|_| AbiResult::ErrInvalidInput)?;
+        let params = marshal::map_from_json(params_json).map_err(|_| AbiResult::ErrInvalidInput)?;
+        let params = with_default_parameters(&exported.metadata, params);
+
+        let mut errors: Vec<String> = exported
+            .metadata
+            .parameters
+            .iter()
+            .filter_map(|def| def.validate(params.get(&def.name)?).err())
+            .collect();
+
+        let mut ctx = ValidationContext::new(NodeId::new());
+        for (name, value) in inputs {
+            ctx.add_input(name, value);
+        }
+        for (name, value) in params {
+            ctx.add_parameter(name, value);
+        }
+        if let Err(e) = exported.filter.validate(&ctx) {
+            errors.push(e.to_string());
+        }
+        Ok(errors)
+    }
+
+    /// Execute a node; returns the output map as JSON.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AbiResult::ErrInvalidInput`] for an unknown filter or
+    /// undecodable JSON, and [`AbiResult::ErrExecution`] if the filter fails.
+    pub fn execute(
+        &self,
+        filter_id: &str,
+        inputs_json: &[u8],
+        params_json: &[u8],
+    ) -> Result<String, AbiResult> {
+        let exported = self.filters.get(filter_id).ok_or(AbiResult::ErrInvalidInput)?;
+        let inputs = marshal::map_from_json(inputs_json).map_err(|_| AbiResult::ErrInvalidInput)?;
+        let params = marshal::map_from_json(params_json).map_err(|_| AbiResult::ErrInvalidInput)?;
+        let params = with_default_parameters(&exported.metadata, params);
+
+        let mut ctx = ExecutionContext::new(NodeId::new());
+        for (name, value) in inputs {
+            ctx.add_input(name, value);
+        }
+        for (name, value) in params {
+            ctx.add_parameter(name, value);
+        }
+        exported
+            .filter
+            .execute(&mut ctx)
+            .map_err(|_| AbiResult::ErrExecution)?;
+
+        let outputs = ctx.take_outputs();
+        Ok(marshal::map_to_json(
+            outputs.iter().map(|(k, v)| (k.as_str(), v)),
+        ))
+    }
+
+    /// Whether the plugin is healthy: it never panicked and
+    /// [`Plugin::health_check`] passes.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        !self.panicked.load(Ordering::Relaxed) && self.plugin.health_check()
+    }
+
+    fn mark_panicked(&self) {
+        self.panicked.store(true, Ordering::Relaxed);
+    }
+}
+
+// ============================================================================
+// Pointer helpers
+// ============================================================================
+
+/// # Safety
+///
+/// `handle` must be null or a pointer returned by [`plugin_create`] for `P`.
+unsafe fn instance<'a, P: Plugin>(handle: *const PluginHandle) -> Option<&'a PluginInstance<P>> {
+    handle.cast::<PluginInstance<P>>().as_ref()
+}
+
+/// # Safety
+///
+/// `ptr` must be null or valid for reads of `len` bytes.
+unsafe fn bytes<'a>(ptr: *const u8, len: usize) -> &'a [u8] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null or a valid NUL-terminated string.
+unsafe fn c_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        None
+    } else {
+        CStr::from_ptr(ptr).to_str().ok()
+    }
+}
+
+/// Copy `data` into the host buffer, failing if it does not fit.
+///
+/// # Safety
+///
+/// `out` must be null or valid for writes of `capacity` bytes.
+unsafe fn write_out(data: &[u8], out: *mut u8, capacity: usize) -> Option<usize> {
+    if out.is_null() || data.len() > capacity {
+        return None;
+    }
+    std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    Some(data.len())
+}
+
+/// Run `f`, converting a panic into `on_panic` and poisoning the instance.
+fn guard<P: Plugin, R>(handle: *const PluginHandle, on_panic: R, f: impl FnOnce() -> R) -> R {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => {
+            // SAFETY: the host passes the handle it got from plugin_create.
+            if let Some(instance) = unsafe { instance::<P>(handle) } {
+                instance.mark_panicked();
+            }
+            on_panic
+        }
+    }
+}
+
+// ============================================================================
+// Entry points
+// ============================================================================
+
+unsafe extern "C" fn plugin_create<P: Plugin>() -> *mut PluginHandle {
+    catch_unwind(|| Box::into_raw(Box::<PluginInstance<P>>::default()).cast::<PluginHandle>())
+        .unwrap_or(std::ptr::null_mut())
+}
+
+unsafe extern "C" fn plugin_destroy<P: Plugin>(handle: *mut PluginHandle) {
+    if handle.is_null() {
+        return;
+    }
+    // A panicking Drop must not unwind into the host; leaking is the only
+    // safe option left at that point.
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        drop(Box::from_raw(handle.cast::<PluginInstance<P>>()));
+    }));
+}
+
+unsafe extern "C" fn plugin_init<P: Plugin>(
+    handle: *mut PluginHandle,
+    config_json: *const u8,
+    config_len: usize,
+) -> AbiResult {
+    guard::<P, _>(handle, AbiResult::ErrInit, || {
+        let Some(instance) = handle.cast::<PluginInstance<P>>().as_mut() else {
+            return AbiResult::ErrInit;
+        };
+        match instance.init(bytes(config_json, config_len)) {
+            Ok(()) => AbiResult::Ok,
+            Err(_) => AbiResult::ErrInit,
+        }
+    })
+}
+
+unsafe extern "C" fn filter_count<P: Plugin>(handle: *const PluginHandle) -> usize {
+    guard::<P, _>(handle, 0, || {
+        instance::<P>(handle).map_or(0, PluginInstance::filter_count)
+    })
+}
+
+unsafe extern "C" fn filter_id_at<P: Plugin>(
+    handle: *const PluginHandle,
+    index: usize,
+) -> *const c_char {
+    guard::<P, _>(handle, std::ptr::null(), || {
+        instance::<P>(handle)
+            .and_then(|i| i.filters.get_index(index))
+            .map_or(std::ptr::null(), |(_, f)| f.c_id.as_ptr())
+    })
+}
+
+unsafe extern "C" fn filter_metadata_json<P: Plugin>(
+    handle: *const PluginHandle,
+    filter_id: *const c_char,
+    out_buf: *mut u8,
+    out_buf_len: usize,
+) -> usize {
+    guard::<P, _>(handle, 0, || {
+        instance::<P>(handle)
+            .zip(c_str(filter_id))
+            .and_then(|(i, id)| i.metadata_json(id))
+            .and_then(|json| write_out(json.as_bytes(), out_buf, out_buf_len))
+            .unwrap_or(0)
+    })
+}
+
+unsafe extern "C" fn filter_execute<P: Plugin>(
+    handle: *mut PluginHandle,
+    filter_id: *const c_char,
+    inputs_json: *const u8,
+    inputs_len: usize,
+    params_json: *const u8,
+    params_len: usize,
+    out_buf: *mut u8,
+    out_buf_len: usize,
+    out_written: *mut usize,
+) -> AbiResult {
+    guard::<P, _>(handle, AbiResult::ErrExecution, || {
+        let (Some(instance), Some(id)) = (instance::<P>(handle), c_str(filter_id)) else {
+            return AbiResult::ErrInvalidInput;
+        };
+        let output = match instance.execute(
+            id,
+            bytes(inputs_json, inputs_len),
+            bytes(params_json, params_len),
+        ) {
+            Ok(output) => output,
+            Err(code) => return code,
+        };
+        match write_out(output.as_bytes(), out_buf, out_buf_len) {
+            Some(written) => {
+                if let Some(out) = out_written.as_mut() {
+                    *out = written;
+                }
+                AbiResult::Ok
+            }
+            None => AbiResult::ErrExecution,
+        }
+    })
+}
+
+unsafe extern "C" fn filter_validate<P: Plugin>(
+    handle: *const PluginHandle,
+    filter_id: *const c_char,
+    inputs_json: *const u8,
+    inputs_len: usize,
+    params_json: *const u8,
+    params_len: usize,
+    out_buf: *mut u8,
+    out_buf_len: usize,
+    out_written: *mut usize,
+) -> AbiResult {
+    guard::<P, _>(handle, AbiResult::ErrUnknown, || {
+        let (Some(instance), Some(id)) = (instance::<P>(handle), c_str(filter_id)) else {
+            return AbiResult::ErrInvalidInput;
+        };
+        let errors = match instance.validate(
+            id,
+            bytes(inputs_json, inputs_len),
+            bytes(params_json, params_len),
+        ) {
+            Ok(errors) => errors,
+            Err(code) => return code,
+        };
+        let json = serde_json::to_string(&errors).unwrap_or_else(|_| "[]".to_string());
+        match write_out(json.as_bytes(), out_buf, out_buf_len) {
+            Some(written) => {
+                if let Some(out) = out_written.as_mut() {
+                    *out = written;
+                }
+                AbiResult::Ok
+            }
+            None => AbiResult::ErrUnknown,
+        }
+    })
+}
+
+unsafe extern "C" fn plugin_health_check<P: Plugin>(handle: *const PluginHandle) -> AbiResult {
+    guard::<P, _>(handle, AbiResult::ErrUnknown, || {
+        match instance::<P>(handle) {
+            Some(instance) if instance.is_healthy() => AbiResult::Ok,
+            _ => AbiResult::ErrUnknown,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ambara::core::error::ExecutionError;
+    use ambara::core::port::{ParameterDefinition, PortDefinition};
+    use ambara::core::types::{PortType, Value};
+
+    #[derive(Default)]
+    struct Double;
+
+    impl PluginFilter for Double {
+        fn metadata(&self) -> NodeMetadata {
+            NodeMetadata::builder("test.double", "Double")
+                .input(PortDefinition::input("x", PortType::Float))
+                .output(PortDefinition::output("x", PortType::Float))
+                .parameter(
+                    ParameterDefinition::new("factor", PortType::Float, Value::Float(2.0))
+                        .with_range(0.0, 10.0),
+                )
+                .build()
+        }
+
+        fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+            let x = ctx.get_input("x")?.as_float().unwrap_or_default();
+            if x < 0.0 {
+                panic!("negative input");
+            }
+            let factor = ctx.get_float("factor")?;
+            ctx.set_output("x", Value::Float(x * factor))
+        }
+    }
+
+    #[derive(Default)]
+    struct TestPlugin;
+
+    impl Plugin for TestPlugin {
+        fn filters(&self) -> Vec<Box<dyn PluginFilter>> {
+            vec![Box::new(Double)]
+        }
+    }
+
+    static VTABLE: PluginVTable = vtable::<TestPlugin>();
+
+    fn create() -> *mut PluginHandle {
+        unsafe {
+            let handle = (VTABLE.plugin_create)();
+            assert_eq!((VTABLE.plugin_init)(handle, b"{}".as_ptr(), 2), AbiResult::Ok);
+            handle
+        }
+    }
+
+    fn execute(handle: *mut PluginHandle, x: f64) -> (AbiResult, String) {
+        let id = CString::new("test.double").unwrap();
+        let inputs = format!(r#"{{"x":{{"type":"Float","data":{x}}}}}"#);
+        let mut out = vec![0u8; 1024];
+        let mut written = 0usize;
+        let result = unsafe {
+            (VTABLE.filter_execute)(
+                handle,
+                id.as_ptr(),
+                inputs.as_ptr(),
+                inputs.len(),
+                std::ptr::null(),
+                0,
+                out.as_mut_ptr(),
+                out.len(),
+                &mut written,
+            )
+        };
+        (result, String::from_utf8_lossy(&out[..written]).into_owned())
+    }
+
+    #[test]
+    fn exports_filter_ids_and_metadata() {
+        let handle = create();
+        unsafe {
+            assert_eq!((VTABLE.filter_count)(handle), 1);
+            let id = CStr::from_ptr((VTABLE.filter_id_at)(handle, 0));
+            assert_eq!(id.to_str().unwrap(), "test.double");
+            assert!((VTABLE.filter_id_at)(handle, 1).is_null());
+
+            let mut buf = vec![0u8; 4096];
+            let n = (VTABLE.filter_metadata_json)(handle, id.as_ptr(), buf.as_mut_ptr(), buf.len());
+            let meta: NodeMetadata = serde_json::from_slice(&buf[..n]).unwrap();
+            assert_eq!(meta.id, "test.double");
+
+            let mut tiny = [0u8; 4];
+            assert_eq!(
+                (VTABLE.filter_metadata_json)(handle, id.as_ptr(), tiny.as_mut_ptr(), tiny.len()),
+                0
+            );
+            (VTABLE.plugin_destroy)(handle);
+        }
+    }
+
+    #[test]
+    fn execute_fills_default_parameters() {
+        let handle = create();
+        let (result, output) = execute(handle, 1.5);
+        assert_eq!(result, AbiResult::Ok);
+        let outputs = marshal::map_from_json(output.as_bytes()).unwrap();
+        assert_eq!(outputs["x"], Value::Float(3.0));
+        unsafe { (VTABLE.plugin_destroy)(handle) };
+    }
+
+    #[test]
+    fn panics_are_contained_and_mark_plugin_unhealthy() {
+        let handle = create();
+        unsafe {
+            assert_eq!((VTABLE.plugin_health_check)(handle), AbiResult::Ok);
+        }
+        let (result, _) = execute(handle, -1.0);
+        assert_eq!(result, AbiResult::ErrExecution);
+        unsafe {
+            assert_eq!((VTABLE.plugin_health_check)(handle), AbiResult::ErrUnknown);
+            (VTABLE.plugin_destroy)(handle);
+        }
+    }
+
+    #[test]
+    fn validate_checks_parameter_constraints() {
+        let handle = create();
+        let instance = unsafe { instance::<TestPlugin>(handle) }.unwrap();
+        let params = br#"{"factor":{"type":"Float","data":99.0}}"#;
+        let errors = instance.validate("test.double", b"{}", params).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(instance.validate("test.double", b"{}", b"{}").unwrap().is_empty());
+        assert_eq!(
+            instance.validate("test.missing", b"{}", b"{}"),
+            Err(AbiResult::ErrInvalidInput)
+        );
+        unsafe { (VTABLE.plugin_destroy)(handle) };
+    }
+
+    #[test]
+    fn null_handles_are_rejected() {
+        unsafe {
+            assert_eq!((VTABLE.filter_count)(std::ptr::null()), 0);
+            assert_eq!(
+                (VTABLE.plugin_health_check)(std::ptr::null()),
+                AbiResult::ErrUnknown
+            );
+            (VTABLE.plugin_destroy)(std::ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Safe traits implemented by plugin authors.
+
+use ambara::core::context::{ExecutionContext, ValidationContext};
+use ambara::core::error::{ExecutionError, ValidationError};
+use ambara::core::node::NodeMetadata;
+
+/// A filter provided by a plugin.
+///
+/// This mirrors [`ambara::core::node::FilterNode`] minus `clone_box`: the SDK
+/// owns one instance per filter for the lifetime of the plugin, and calls it
+/// from whichever host thread executes the node.
+///
+/// Parameters declared in [`PluginFilter::metadata`] are validated against
+/// their constraints and filled with defaults before `validate`/`execute`
+/// run, so implementations can read them unconditionally.
+pub trait PluginFilter: Send + Sync {
+    /// Describe the filter's ports and parameters.
+    ///
+    /// Called once when the plugin is initialised; the result is cached.
+    fn metadata(&self) -> NodeMetadata;
+
+    /// Validate inputs and parameters before execution.
+    ///
+    /// # Errors
+    ///
+    /// Return a [`ValidationError`] describing why the node cannot run.
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        let _ = ctx;
+        Ok(())
+    }
+
+    /// Execute the filter, writing results with `ctx.set_output`.
+    ///
+    /// # Errors
+    ///
+    /// Return an [`ExecutionError`] if processing fails.
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError>;
+}
+
+/// A plugin: a set of filters plus lifecycle hooks.
+///
+/// Usually implemented with `#[derive(AmbaraPlugin)]`. The instance is
+/// created with [`Default`], then [`Plugin::init`] runs with the host
+/// configuration, and only then is [`Plugin::filters`] called — so the filter
+/// list may depend on configuration.
+pub trait Plugin: Default + Send + Sync + 'static {
+    /// Instantiate every filter this plugin provides.
+    fn filters(&self) -> Vec<Box<dyn PluginFilter>>;
+
+    /// Apply host configuration (manifest `[plugin.config]` merged with the
+    /// host's settings, plus `granted_capabilities`).
+    ///
+    /// Entries declared in `[plugin.config_schema]` have already been
+    /// validated by the host and converted to their declared types.
+    ///
+    /// # Errors
+    ///
+    /// Return a message to abort loading the plugin.
+    fn init(&mut self, config: &serde_json::Value) -> Result<(), String> {
+        let _ = config;
+        Ok(())
+    }
+
+    /// Report whether the plugin can keep serving requests.
+    fn health_check(&self) -> bool {
+        true
+    }
+}
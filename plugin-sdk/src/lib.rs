@@ -0,0 +1,111 @@
+//! # Ambara Plugin SDK
+//!
+//! Safe building blocks for writing Ambara plugins without touching the C ABI
+//! in [`ambara::plugins::api`].
+//!
+//! A plugin author implements [`PluginFilter`] (the plugin-side counterpart
+//! of [`ambara::core::node::FilterNode`]) for each filter and lists them on a
+//! plugin type deriving [`AmbaraPlugin`]. The derive generates the
+//! `ambara_plugin_vtable` export, and the SDK provides everything behind it:
+//!
+//! - `catch_unwind` around every `extern "C"` entry point; a panicking filter
+//!   returns an error code and marks the plugin unhealthy.
+//! - JSON (de)serialisation of inputs, parameters and outputs, including
+//!   images, via [`ambara::plugins::marshal`].
+//! - Bounds-checked writes into host-provided buffers.
+//! - Metadata export and default-parameter filling from [`NodeMetadata`].
+//!
+//! ## Example
+//!
+//! ```rust,ignore
+//! use ambara_plugin_sdk::prelude::*;
+//!
+//! #[derive(FilterParams)]
+//! struct GainParams {
+//!     #[param(default = 1.0, min = 0.0, max = 4.0)]
+//!     gain: f64,
+//! }
+//!
+//! #[derive(Default)]
+//! struct Gain;
+//!
+//! impl PluginFilter for Gain {
+//!     fn metadata(&self) -> NodeMetadata {
+//!         NodeMetadata::builder("example.gain", "Gain")
+//!             .input(PortDefinition::input("value", PortType::Float))
+//!             .output(PortDefinition::output("value", PortType::Float))
+//!             .parameters(GainParams::definitions())
+//!             .build()
+//!     }
+//!
+//!     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+//!         let params = GainParams::from_context(ctx)?;
+//!         let value = ctx.get_input("value")?.as_float().unwrap_or_default();
+//!         ctx.set_output("value", Value::Float(value * params.gain))
+//!     }
+//! }
+//!
+//! #[derive(Default, AmbaraPlugin)]
+//! #[plugin(filters(Gain))]
+//! struct ExamplePlugin;
+//! ```
+//!
+//! The crate must be built as a `cdylib` and shipped next to an
+//! `ambara-plugin.toml` manifest. See `plugins/sdk_example` for a complete
+//! plugin, including tests that load it through the host with
+//! [`testing::PluginHarness`] (behind the `testing` feature).
+
+#![warn(missing_docs)]
+
+pub mod export;
+pub mod filter;
+pub mod params;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use ambara;
+pub use ambara_plugin_sdk_macros::{AmbaraPlugin, FilterParams};
+pub use serde_json;
+
+pub use filter::{Plugin, PluginFilter};
+pub use params::{FilterParams, ParamType};
+
+use ambara::core::node::NodeMetadata;
+
+/// Export `$plugin` as this library's `ambara_plugin_vtable`.
+///
+/// Generated by `#[derive(AmbaraPlugin)]`; invoke it directly only when
+/// implementing [`Plugin`] by hand. Use it at most once per crate.
+#[macro_export]
+macro_rules! export_plugin {
+    ($plugin:ty) => {
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static ambara_plugin_vtable: $crate::ambara::plugins::api::PluginVTable =
+            $crate::export::vtable::<$plugin>();
+    };
+}
+
+/// Common imports for plugin crates.
+pub mod prelude {
+    pub use crate::{AmbaraPlugin, FilterParams, ParamType, Plugin, PluginFilter};
+    pub use ambara::core::context::{ExecutionContext, ValidationContext};
+    pub use ambara::core::error::{ExecutionError, ValidationError};
+    pub use ambara::core::node::{Category, NodeMetadata};
+    pub use ambara::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
+    pub use ambara::core::types::{ImageValue, PortType, Value};
+}
+
+/// Fill in defaults for any parameter declared in `metadata` but missing
+/// from `params`.
+fn with_default_parameters(
+    metadata: &NodeMetadata,
+    mut params: std::collections::HashMap<String, ambara::core::types::Value>,
+) -> std::collections::HashMap<String, ambara::core::types::Value> {
+    for def in &metadata.parameters {
+        params
+            .entry(def.name.clone())
+            .or_insert_with(|| def.default_value.clone());
+    }
+    params
+}
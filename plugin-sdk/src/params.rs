@@ -0,0 +1,201 @@
+//! Typed parameter extraction.
+//!
+//! [`FilterParams`] is normally derived: each field becomes a
+//! [`ParameterDefinition`] and is read back from the node's parameter map
+//! with its [`ParamType`] conversion.
+
+use ambara::core::context::ExecutionContext;
+use ambara::core::error::ExecutionError;
+use ambara::core::port::ParameterDefinition;
+use ambara::core::types::{Color, PortType, Value};
+
+use std::collections::HashMap;
+
+/// A Rust type that can be stored in a filter parameter.
+pub trait ParamType: Sized {
+    /// The port type advertised in the parameter definition.
+    fn port_type() -> PortType;
+    /// Convert into a [`Value`].
+    fn into_value(self) -> Value;
+    /// Convert from a [`Value`], returning `None` on a type mismatch.
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+impl ParamType for bool {
+    fn port_type() -> PortType {
+        PortType::Boolean
+    }
+    fn into_value(self) -> Value {
+        Value::Boolean(self)
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl ParamType for i64 {
+    fn port_type() -> PortType {
+        PortType::Integer
+    }
+    fn into_value(self) -> Value {
+        Value::Integer(self)
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_integer()
+    }
+}
+
+impl ParamType for i32 {
+    fn port_type() -> PortType {
+        PortType::Integer
+    }
+    fn into_value(self) -> Value {
+        Value::Integer(i64::from(self))
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_integer().and_then(|v| i32::try_from(v).ok())
+    }
+}
+
+impl ParamType for u32 {
+    fn port_type() -> PortType {
+        PortType::Integer
+    }
+    fn into_value(self) -> Value {
+        Value::Integer(i64::from(self))
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_integer().and_then(|v| u32::try_from(v).ok())
+    }
+}
+
+impl ParamType for f64 {
+    fn port_type() -> PortType {
+        PortType::Float
+    }
+    fn into_value(self) -> Value {
+        Value::Float(self)
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_float()
+    }
+}
+
+impl ParamType for f32 {
+    fn port_type() -> PortType {
+        PortType::Float
+    }
+    fn into_value(self) -> Value {
+        Value::Float(f64::from(self))
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_float().map(|v| v as f32)
+    }
+}
+
+impl ParamType for String {
+    fn port_type() -> PortType {
+        PortType::String
+    }
+    fn into_value(self) -> Value {
+        Value::String(self)
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_string().map(ToString::to_string)
+    }
+}
+
+impl ParamType for Color {
+    fn port_type() -> PortType {
+        PortType::Color
+    }
+    fn into_value(self) -> Value {
+        Value::Color(self)
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_color()
+    }
+}
+
+impl ParamType for (f64, f64) {
+    fn port_type() -> PortType {
+        PortType::Vector2
+    }
+    fn into_value(self) -> Value {
+        Value::Vector2(self.0, self.1)
+    }
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_vector2()
+    }
+}
+
+/// A struct of typed filter parameters. Derive with `#[derive(FilterParams)]`.
+pub trait FilterParams: Sized {
+    /// Parameter definitions to include in the filter's metadata.
+    fn definitions() -> Vec<ParameterDefinition>;
+
+    /// Read the parameters from a parameter map.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if a parameter has the wrong type.
+    fn from_parameters(params: &HashMap<String, Value>) -> Result<Self, String>;
+
+    /// Read the parameters from an execution context.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExecutionError::Other`] if a parameter has the wrong type.
+    fn from_context(ctx: &ExecutionContext) -> Result<Self, ExecutionError> {
+        Self::from_parameters(ctx.parameters()).map_err(ExecutionError::Other)
+    }
+}
+
+/// Read one parameter, falling back to `default` when it is absent.
+///
+/// Used by the `FilterParams` derive.
+///
+/// # Errors
+///
+/// Returns a message if the stored value has the wrong type.
+pub fn extract<T: ParamType>(
+    params: &HashMap<String, Value>,
+    name: &str,
+    default: impl FnOnce() -> T,
+) -> Result<T, String> {
+    match params.get(name) {
+        None | Some(Value::None) => Ok(default()),
+        Some(value) => T::from_value(value).ok_or_else(|| {
+            format!(
+                "Parameter '{name}' expected {}, got {}",
+                T::port_type(),
+                value.get_type()
+            )
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_uses_default_when_missing() {
+        let params = HashMap::new();
+        assert_eq!(extract(&params, "x", || 2.5f64), Ok(2.5));
+    }
+
+    #[test]
+    fn extract_rejects_wrong_type() {
+        let mut params = HashMap::new();
+        params.insert("x".to_string(), Value::String("no".to_string()));
+        let err = extract::<i64>(&params, "x", || 0).unwrap_err();
+        assert!(err.contains("'x'"));
+    }
+
+    #[test]
+    fn narrow_integers_reject_out_of_range() {
+        assert_eq!(u32::from_value(&Value::Integer(-1)), None);
+        assert_eq!(i32::from_value(&Value::Integer(7)), Some(7));
+    }
+}
@@ -0,0 +1,155 @@
+//! Test harness that loads a built plugin through the real host loader.
+//!
+//! Unit tests of [`crate::PluginFilter`] implementations exercise plugin code
+//! in-process, but miss everything between the host and the library:
+//! symbol export, ABI version, manifest compatibility and JSON marshalling.
+//! [`PluginHarness`] closes that gap by copying the compiled `cdylib` and its
+//! manifest into a temporary plugin directory and loading it with
+//! [`LoadedPlugin::load`], exactly as [`ambara::plugins::PluginRegistry`]
+//! would.
+//!
+//! ```rust,ignore
+//! // tests/load.rs in a plugin crate with crate-type = ["cdylib", "rlib"]
+//! use ambara_plugin_sdk::testing::PluginHarness;
+//!
+//! #[test]
+//! fn loads_through_host() {
+//!     let mut harness = PluginHarness::load(
+//!         PluginHarness::cdylib_path("my_plugin"),
+//!         concat!(env!("CARGO_MANIFEST_DIR"), "/ambara-plugin.toml"),
+//!     )
+//!     .unwrap();
+//!     assert!(harness.filter_ids().contains(&"my.filter".to_string()));
+//! }
+//! ```
+
+use ambara::core::error::PluginError;
+use ambara::core::node::NodeMetadata;
+use ambara::core::types::Value;
+use ambara::plugins::health::HealthReport;
+use ambara::plugins::loader::LoadedPlugin;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A plugin loaded from a temporary plugin directory.
+pub struct PluginHarness {
+    // Declared first so the library is unloaded before the directory goes.
+    plugin: LoadedPlugin,
+    _dir: tempfile::TempDir,
+}
+
+impl PluginHarness {
+    /// Load `library` with `manifest` and an empty host configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Io`] if the files cannot be staged, or any
+    /// error [`LoadedPlugin::load`] reports.
+    pub fn load(library: impl AsRef<Path>, manifest: impl AsRef<Path>) -> Result<Self, PluginError> {
+        Self::load_with_config(library, manifest, &serde_json::json!({}))
+    }
+
+    /// Load `library` with `manifest`, passing `config` to `plugin_init`.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`PluginHarness::load`].
+    pub fn load_with_config(
+        library: impl AsRef<Path>,
+        manifest: impl AsRef<Path>,
+        config: &serde_json::Value,
+    ) -> Result<Self, PluginError> {
+        let library = library.as_ref();
+        let io = |e: std::io::Error| PluginError::Io {
+            message: e.to_string(),
+        };
+
+        let dir = tempfile::tempdir().map_err(io)?;
+        let file_name = library.file_name().ok_or_else(|| PluginError::Io {
+            message: format!("{} is not a file", library.display()),
+        })?;
+        let staged = dir.path().join(file_name);
+        std::fs::copy(library, &staged).map_err(io)?;
+        std::fs::copy(manifest, dir.path().join("ambara-plugin.toml")).map_err(io)?;
+
+        let plugin = LoadedPlugin::load(&staged, config)?;
+        Ok(Self { plugin, _dir: dir })
+    }
+
+    /// Locate the `cdylib` Cargo built for `crate_name` in the current
+    /// profile's target directory.
+    ///
+    /// Works from integration tests of the plugin crate itself, where Cargo
+    /// builds the library (including its `cdylib`) before the test binary.
+    #[must_use]
+    pub fn cdylib_path(crate_name: &str) -> PathBuf {
+        let file_name = format!(
+            "{}{}{}",
+            std::env::consts::DLL_PREFIX,
+            crate_name.replace('-', "_"),
+            std::env::consts::DLL_SUFFIX
+        );
+        // The test binary lives in target/<profile>/deps/.
+        let deps = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        let profile = deps.parent().map(Path::to_path_buf).unwrap_or_default();
+        [profile.join(&file_name), deps.join(&file_name)]
+            .into_iter()
+            .find(|p| p.exists())
+            .unwrap_or_else(|| profile.join(file_name))
+    }
+
+    /// The loaded plugin.
+    #[must_use]
+    pub fn plugin(&self) -> &LoadedPlugin {
+        &self.plugin
+    }
+
+    /// IDs of the filters the plugin exports.
+    #[must_use]
+    pub fn filter_ids(&self) -> Vec<String> {
+        self.plugin.filter_ids()
+    }
+
+    /// Metadata the plugin reports for `filter_id`.
+    ///
+    /// # Errors
+    ///
+    /// See [`LoadedPlugin::filter_metadata`].
+    pub fn metadata(&self, filter_id: &str) -> Result<NodeMetadata, PluginError> {
+        self.plugin.filter_metadata(filter_id)
+    }
+
+    /// Execute `filter_id` through the ABI.
+    ///
+    /// # Errors
+    ///
+    /// See [`LoadedPlugin::execute_filter`].
+    pub fn execute(
+        &mut self,
+        filter_id: &str,
+        inputs: &[(&str, Value)],
+        params: &[(&str, Value)],
+    ) -> Result<HashMap<String, Value>, PluginError> {
+        self.plugin.execute_filter(filter_id, inputs, params)
+    }
+
+    /// Validate `filter_id` through the ABI.
+    #[must_use]
+    pub fn validate(
+        &self,
+        filter_id: &str,
+        inputs: &[(&str, Value)],
+        params: &[(&str, Value)],
+    ) -> Vec<String> {
+        self.plugin.validate_filter(filter_id, inputs, params)
+    }
+
+    /// Run the plugin's health check.
+    pub fn health_check(&mut self) -> HealthReport {
+        self.plugin.health_check()
+    }
+}
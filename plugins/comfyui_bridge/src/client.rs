@@ -0,0 +1,389 @@
+//! Blocking client for the ComfyUI HTTP API.
+//!
+//! Only the endpoints the bridge needs are covered: `/object_info` for
+//! discovery, `/prompt` + `/history/{id}` to run a prompt, `/view` to fetch
+//! the resulting images, `/upload/image` to send Ambara images to the
+//! server, and `/system_stats` as a liveness probe.
+
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Errors talking to a ComfyUI server.
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// The request could not be sent or the response not read.
+    #[error("Request to {url} failed: {message}")]
+    Request { url: String, message: String },
+
+    /// The server answered with a non-success status.
+    #[error("{url} returned HTTP {status}: {body}")]
+    Status {
+        url: String,
+        status: u16,
+        body: String,
+    },
+
+    /// The server answered with something the bridge does not understand.
+    #[error("Unexpected ComfyUI response: {0}")]
+    Protocol(String),
+
+    /// ComfyUI reported an error while running the prompt.
+    #[error("ComfyUI failed to execute the prompt: {0}")]
+    Execution(String),
+
+    /// The prompt did not finish in time.
+    #[error("ComfyUI did not finish within {secs} seconds")]
+    Timeout { secs: u64 },
+}
+
+/// Location of an image produced by a prompt, as listed in `/history`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageRef {
+    /// File name on the server.
+    pub filename: String,
+    /// Sub-folder, usually empty.
+    pub subfolder: String,
+    /// `output`, `temp` or `input`.
+    pub folder_type: String,
+}
+
+/// A ComfyUI server.
+#[derive(Debug, Clone)]
+pub struct ComfyClient {
+    base_url: String,
+    http: reqwest::blocking::Client,
+    timeout: Duration,
+    poll_interval: Duration,
+}
+
+/// Longest error body quoted in [`ClientError::Status`].
+const MAX_ERROR_BODY: usize = 512;
+
+impl ComfyClient {
+    /// Create a client for `base_url`. `timeout` bounds a whole prompt run;
+    /// individual requests use the same limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Request`] if the HTTP client cannot be built.
+    pub fn new(base_url: &str, timeout: Duration) -> Result<Self, ClientError> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let http = reqwest::blocking::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| ClientError::Request {
+                url: base_url.clone(),
+                message: e.to_string(),
+            })?;
+        Ok(Self {
+            base_url,
+            http,
+            timeout,
+            poll_interval: Duration::from_millis(500),
+        })
+    }
+
+    /// Override how often `/history` is polled while a prompt runs.
+    #[must_use]
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// The server's base URL, without a trailing slash.
+    #[must_use]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Fetch `/object_info`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClientError`] if the request fails or the body is not JSON.
+    pub fn object_info(&self) -> Result<serde_json::Value, ClientError> {
+        self.get_json("/object_info")
+    }
+
+    /// Probe `/system_stats` with a short timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClientError`] if the server does not answer successfully.
+    pub fn ping(&self) -> Result<(), ClientError> {
+        let url = self.url("/system_stats");
+        let response = self
+            .http
+            .get(&url)
+            .timeout(Duration::from_secs(2))
+            .send()
+            .map_err(|e| request_error(&url, &e))?;
+        check_status(&url, response).map(drop)
+    }
+
+    /// Queue an API-format prompt and return its `prompt_id`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Status`] with ComfyUI's validation message if
+    /// the server rejects the prompt.
+    pub fn queue_prompt(&self, prompt: &serde_json::Value) -> Result<String, ClientError> {
+        let url = self.url("/prompt");
+        let response = self
+            .http
+            .post(&url)
+            .json(&serde_json::json!({ "prompt": prompt }))
+            .send()
+            .map_err(|e| request_error(&url, &e))?;
+        let body: serde_json::Value = check_status(&url, response)?
+            .json()
+            .map_err(|e| request_error(&url, &e))?;
+        body.get("prompt_id")
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| ClientError::Protocol("/prompt response has no prompt_id".to_string()))
+    }
+
+    /// Poll `/history/{prompt_id}` until the prompt has finished and return
+    /// its history entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::Execution`] if ComfyUI reports an error, and
+    /// [`ClientError::Timeout`] if the prompt does not finish in time.
+    pub fn wait_for(&self, prompt_id: &str) -> Result<serde_json::Value, ClientError> {
+        let deadline = Instant::now() + self.timeout;
+        let path = format!("/history/{prompt_id}");
+        loop {
+            let history = self.get_json(&path)?;
+            if let Some(entry) = history.get(prompt_id) {
+                let status = entry.get("status");
+                if status
+                    .and_then(|s| s.get("status_str"))
+                    .and_then(serde_json::Value::as_str)
+                    == Some("error")
+                {
+                    return Err(ClientError::Execution(execution_error_message(status)));
+                }
+                return Ok(entry.clone());
+            }
+            if Instant::now() + self.poll_interval > deadline {
+                return Err(ClientError::Timeout {
+                    secs: self.timeout.as_secs(),
+                });
+            }
+            std::thread::sleep(self.poll_interval);
+        }
+    }
+
+    /// Download an image listed in a history entry.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClientError`] if the download fails.
+    pub fn view(&self, image: &ImageRef) -> Result<Vec<u8>, ClientError> {
+        let url = self.url("/view");
+        let response = self
+            .http
+            .get(&url)
+            .query(&[
+                ("filename", image.filename.as_str()),
+                ("subfolder", image.subfolder.as_str()),
+                ("type", image.folder_type.as_str()),
+            ])
+            .send()
+            .map_err(|e| request_error(&url, &e))?;
+        let bytes = check_status(&url, response)?
+            .bytes()
+            .map_err(|e| request_error(&url, &e))?;
+        Ok(bytes.to_vec())
+    }
+
+    /// Upload a PNG to the server's input folder and return the value a
+    /// `LoadImage` node expects for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`ClientError`] if the upload fails.
+    pub fn upload_image(&self, png: &[u8], filename: &str) -> Result<String, ClientError> {
+        let url = self.url("/upload/image");
+        let boundary = format!("ambara-{}", uuid::Uuid::new_v4().simple());
+        let response = self
+            .http
+            .post(&url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(multipart_body(&boundary, filename, png))
+            .send()
+            .map_err(|e| request_error(&url, &e))?;
+        let body: serde_json::Value = check_status(&url, response)?
+            .json()
+            .map_err(|e| request_error(&url, &e))?;
+        let name = body
+            .get("name")
+            .and_then(serde_json::Value::as_str)
+            .ok_or_else(|| ClientError::Protocol("/upload/image response has no name".into()))?;
+        Ok(match body.get("subfolder").and_then(serde_json::Value::as_str) {
+            Some(subfolder) if !subfolder.is_empty() => format!("{subfolder}/{name}"),
+            _ => name.to_string(),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    fn get_json(&self, path: &str) -> Result<serde_json::Value, ClientError> {
+        let url = self.url(path);
+        let response = self.http.get(&url).send().map_err(|e| request_error(&url, &e))?;
+        check_status(&url, response)?
+            .json()
+            .map_err(|e| request_error(&url, &e))
+    }
+}
+
+/// Images listed for `node_id` in a history entry's `outputs`.
+#[must_use]
+pub fn output_images(history: &serde_json::Value, node_id: &str) -> Vec<ImageRef> {
+    let text = |image: &serde_json::Value, key: &str, default: &str| {
+        image
+            .get(key)
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or(default)
+            .to_string()
+    };
+    history
+        .get("outputs")
+        .and_then(|o| o.get(node_id))
+        .and_then(|o| o.get("images"))
+        .and_then(serde_json::Value::as_array)
+        .map(|images| {
+            images
+                .iter()
+                .filter_map(|image| {
+                    Some(ImageRef {
+                        filename: image.get("filename")?.as_str()?.to_string(),
+                        subfolder: text(image, "subfolder", ""),
+                        folder_type: text(image, "type", "output"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn request_error(url: &str, error: &reqwest::Error) -> ClientError {
+    ClientError::Request {
+        url: url.to_string(),
+        message: error.to_string(),
+    }
+}
+
+fn check_status(
+    url: &str,
+    response: reqwest::blocking::Response,
+) -> Result<reqwest::blocking::Response, ClientError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let mut body = response.text().unwrap_or_default();
+    if body.len() > MAX_ERROR_BODY {
+        let mut end = MAX_ERROR_BODY;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+    }
+    Err(ClientError::Status {
+        url: url.to_string(),
+        status: status.as_u16(),
+        body,
+    })
+}
+
+/// Summarise the `execution_error` messages of a failed history entry.
+fn execution_error_message(status: Option<&serde_json::Value>) -> String {
+    let messages: Vec<String> = status
+        .and_then(|s| s.get("messages"))
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|m| m.get(0).and_then(serde_json::Value::as_str) == Some("execution_error"))
+        .filter_map(|m| {
+            let detail = m.get(1)?;
+            let node = detail.get("node_type").and_then(serde_json::Value::as_str)?;
+            let message = detail
+                .get("exception_message")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("unknown error");
+            Some(format!("{node}: {}", message.trim()))
+        })
+        .collect();
+    if messages.is_empty() {
+        "unknown error".to_string()
+    } else {
+        messages.join("; ")
+    }
+}
+
+fn multipart_body(boundary: &str, filename: &str, png: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(png.len() + 512);
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"image\"; \
+             filename=\"{filename}\"\r\nContent-Type: image/png\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(png);
+    body.extend_from_slice(
+        format!(
+            "\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"overwrite\"\r\n\r\n\
+             true\r\n--{boundary}--\r\n"
+        )
+        .as_bytes(),
+    );
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn output_images_reads_history_entry() {
+        let history = serde_json::json!({
+            "outputs": {
+                "9": {"images": [
+                    {"filename": "a.png", "subfolder": "", "type": "temp"},
+                    {"subfolder": "missing filename"}
+                ]}
+            }
+        });
+        assert_eq!(
+            output_images(&history, "9"),
+            [ImageRef {
+                filename: "a.png".into(),
+                subfolder: String::new(),
+                folder_type: "temp".into(),
+            }]
+        );
+        assert!(output_images(&history, "3").is_empty());
+    }
+
+    #[test]
+    fn execution_errors_name_the_failing_node() {
+        let status = serde_json::json!({
+            "status_str": "error",
+            "messages": [
+                ["execution_start", {}],
+                ["execution_error", {"node_type": "KSampler", "exception_message": "CUDA OOM\n"}]
+            ]
+        });
+        assert_eq!(execution_error_message(Some(&status)), "KSampler: CUDA OOM");
+        assert_eq!(execution_error_message(None), "unknown error");
+    }
+}
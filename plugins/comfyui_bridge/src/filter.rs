@@ -0,0 +1,336 @@
+//! Ambara filters backed by ComfyUI node classes.
+//!
+//! ComfyUI values such as `MODEL` or `LATENT` live on the server and cannot
+//! be brought into Ambara. Instead, a [`ComfyNodeFilter`] whose outputs are
+//! all such values does not contact the server at all: each output carries a
+//! [`ComfyLink`], the API-format prompt fragment that would produce it.
+//! Downstream ComfyUI filters merge the fragments of their inputs and add
+//! their own node. Only a node that yields images (or is a ComfyUI output
+//! node such as `SaveImage`) submits the accumulated prompt, once, and
+//! downloads the results. A checkpoint → CLIP encode → KSampler → VAE decode
+//! chain therefore runs as a single ComfyUI prompt.
+//!
+//! Images flowing *into* a ComfyUI filter are uploaded with `/upload/image`
+//! and fed through a `LoadImage` node.
+
+use crate::client::{output_images, ClientError, ComfyClient};
+use crate::schema::{self, InputKind, NodeClass, IMAGE_TYPE, OUTPUT_NODE_IMAGE};
+
+use ambara::core::context::ExecutionContext;
+use ambara::core::error::ExecutionError;
+use ambara::core::node::NodeMetadata;
+use ambara::core::types::{ImageValue, Value};
+use ambara_plugin_sdk::PluginFilter;
+
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::sync::Arc;
+
+type Prompt = serde_json::Map<String, serde_json::Value>;
+
+/// A reference to one output slot of a node in a not-yet-submitted prompt.
+///
+/// Travels between Ambara nodes as a JSON string on `Custom` ports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComfyLink {
+    /// Base URL of the server the prompt targets.
+    pub server: String,
+    /// API-format prompt holding the producing node and all its ancestors.
+    pub prompt: Prompt,
+    /// Prompt ID of the producing node.
+    pub node: String,
+    /// Output slot on the producing node.
+    pub slot: usize,
+}
+
+impl ComfyLink {
+    /// Wrap the link for an Ambara port.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        Value::String(serde_json::to_string(self).unwrap_or_default())
+    }
+
+    /// Read a link produced by [`ComfyLink::to_value`].
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if `value` is not a ComfyUI link.
+    pub fn from_value(value: &Value) -> Result<Self, String> {
+        value
+            .as_string()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .ok_or_else(|| "expected the output of a ComfyUI node".to_string())
+    }
+}
+
+/// An Ambara filter generated from a ComfyUI node class.
+pub struct ComfyNodeFilter {
+    class: NodeClass,
+    client: Arc<ComfyClient>,
+}
+
+impl ComfyNodeFilter {
+    /// Expose `class` of the server behind `client`.
+    #[must_use]
+    pub fn new(class: NodeClass, client: Arc<ComfyClient>) -> Self {
+        Self { class, client }
+    }
+
+    /// Build this node's prompt: the merged fragments of its inputs plus the
+    /// node itself. Returns the prompt and the node's prompt ID.
+    fn build_prompt(&self, ctx: &ExecutionContext) -> Result<(Prompt, String), String> {
+        let mut prompt = Prompt::new();
+        let mut inputs = serde_json::Map::new();
+        for input in &self.class.inputs {
+            let value = if input.is_port() {
+                match ctx.inputs().get(&input.name) {
+                    Some(Value::None) | None if input.optional => continue,
+                    Some(Value::None) | None => {
+                        return Err(format!("input '{}' is not connected", input.name))
+                    }
+                    Some(value) => value,
+                }
+            } else {
+                ctx.get_parameter(&input.name).map_err(|e| e.to_string())?
+            };
+
+            let literal = match &input.kind {
+                InputKind::Link(comfy_type) if comfy_type == IMAGE_TYPE => {
+                    let image = value
+                        .as_image()
+                        .ok_or_else(|| format!("input '{}' is not an image", input.name))?;
+                    let loader = self.upload(image).map_err(|e| e.to_string())?;
+                    let id = node_id(&loader);
+                    prompt.insert(id.clone(), loader);
+                    serde_json::json!([id, 0])
+                }
+                InputKind::Link(_) => {
+                    let link = ComfyLink::from_value(value)
+                        .map_err(|e| format!("input '{}': {e}", input.name))?;
+                    if link.server != self.client.base_url() {
+                        return Err(format!(
+                            "input '{}' comes from ComfyUI server {}, not {}",
+                            input.name,
+                            link.server,
+                            self.client.base_url()
+                        ));
+                    }
+                    prompt.extend(link.prompt);
+                    serde_json::json!([link.node, link.slot])
+                }
+                kind => schema::widget_literal(kind, value),
+            };
+            inputs.insert(input.name.clone(), literal);
+        }
+
+        let node = serde_json::json!({ "class_type": self.class.name, "inputs": inputs });
+        let id = node_id(&node);
+        prompt.insert(id.clone(), node);
+        Ok((prompt, id))
+    }
+
+    /// Upload `image` and return a `LoadImage` node reading it.
+    fn upload(&self, image: &ImageValue) -> Result<serde_json::Value, ClientError> {
+        let pixels = image
+            .get_image()
+            .ok_or_else(|| ClientError::Protocol("input image is not loaded".to_string()))?;
+        let mut png = Vec::new();
+        pixels
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .map_err(|e| ClientError::Protocol(e.to_string()))?;
+        let filename = format!("ambara_{:016x}.png", fnv1a(&png));
+        let name = self.client.upload_image(&png, &filename)?;
+        Ok(serde_json::json!({ "class_type": "LoadImage", "inputs": { "image": name } }))
+    }
+
+    /// Run `prompt` and download the image for each `(port, node)` pair.
+    fn submit(
+        &self,
+        prompt: Prompt,
+        images: &[(String, String)],
+        ctx: &mut ExecutionContext,
+    ) -> Result<(), String> {
+        let prompt_id = self
+            .client
+            .queue_prompt(&serde_json::Value::Object(prompt))
+            .map_err(|e| e.to_string())?;
+        let history = self.client.wait_for(&prompt_id).map_err(|e| e.to_string())?;
+
+        for (port, node) in images {
+            let Some(image) = output_images(&history, node).into_iter().next() else {
+                if port == OUTPUT_NODE_IMAGE {
+                    ctx.set_output(port.as_str(), Value::None)
+                        .map_err(|e| e.to_string())?;
+                    continue;
+                }
+                return Err(format!("ComfyUI returned no image for output '{port}'"));
+            };
+            let bytes = self.client.view(&image).map_err(|e| e.to_string())?;
+            let decoded = image::load_from_memory(&bytes)
+                .map_err(|e| format!("failed to decode {}: {e}", image.filename))?;
+            ctx.set_output_image(port.as_str(), ImageValue::new(decoded))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl PluginFilter for ComfyNodeFilter {
+    fn metadata(&self) -> NodeMetadata {
+        self.class.metadata()
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let ambara_node = ctx.node_id;
+        let fail = |error: String| ExecutionError::NodeExecution {
+            node_id: ambara_node,
+            error: format!("{}: {error}", self.class.name),
+        };
+
+        let (mut prompt, id) = self.build_prompt(ctx).map_err(fail)?;
+        let mut images = Vec::new();
+        for (slot, output) in self.class.outputs.iter().enumerate() {
+            if output.comfy_type == IMAGE_TYPE {
+                let preview = serde_json::json!({
+                    "class_type": "PreviewImage",
+                    "inputs": { "images": [id, slot] },
+                });
+                let preview_id = node_id(&preview);
+                prompt.insert(preview_id.clone(), preview);
+                images.push((output.port.clone(), preview_id));
+            } else {
+                let link = ComfyLink {
+                    server: self.client.base_url().to_string(),
+                    prompt: prompt.clone(),
+                    node: id.clone(),
+                    slot,
+                };
+                ctx.set_output(output.port.as_str(), link.to_value())?;
+            }
+        }
+        if self.class.has_output_node_image() {
+            images.push((OUTPUT_NODE_IMAGE.to_string(), id));
+        }
+
+        if self.class.needs_submission() {
+            self.submit(prompt, &images, ctx).map_err(fail)?;
+        }
+        Ok(())
+    }
+}
+
+/// Content-derived prompt ID: identical nodes (the same checkpoint loader
+/// reached through two branches) collapse into one.
+fn node_id(node: &serde_json::Value) -> String {
+    format!("{:016x}", fnv1a(node.to_string().as_bytes()))
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `DefaultHasher`.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ambara::core::error::NodeId;
+    use std::time::Duration;
+
+    fn filter(name: &str, spec: serde_json::Value) -> ComfyNodeFilter {
+        // Nothing listens on port 9; only tests that never submit use this.
+        let client = ComfyClient::new("http://127.0.0.1:9", Duration::from_secs(1)).unwrap();
+        ComfyNodeFilter::new(NodeClass::parse(name, &spec).unwrap(), Arc::new(client))
+    }
+
+    fn loader() -> ComfyNodeFilter {
+        filter(
+            "CheckpointLoaderSimple",
+            serde_json::json!({
+                "input": {"required": {"ckpt_name": [["sd15.safetensors"], {}]}},
+                "output": ["MODEL", "CLIP", "VAE"],
+                "output_name": ["MODEL", "CLIP", "VAE"]
+            }),
+        )
+    }
+
+    fn run(filter: &ComfyNodeFilter, inputs: &[(&str, Value)], params: &[(&str, Value)]) -> Result<ExecutionContext, ExecutionError> {
+        let mut ctx = ExecutionContext::new(NodeId::new());
+        for (name, value) in inputs {
+            ctx.add_input(*name, value.clone());
+        }
+        for (name, value) in params {
+            ctx.add_parameter(*name, value.clone());
+        }
+        filter.execute(&mut ctx).map(|()| ctx)
+    }
+
+    #[test]
+    fn non_image_outputs_are_links_without_contacting_the_server() {
+        let ctx = run(
+            &loader(),
+            &[],
+            &[("ckpt_name", Value::String("sd15.safetensors".into()))],
+        )
+        .unwrap();
+        let clip = ComfyLink::from_value(&ctx.outputs()["clip"]).unwrap();
+        assert_eq!(clip.slot, 1);
+        assert_eq!(clip.server, "http://127.0.0.1:9");
+        assert_eq!(
+            clip.prompt[&clip.node],
+            serde_json::json!({
+                "class_type": "CheckpointLoaderSimple",
+                "inputs": {"ckpt_name": "sd15.safetensors"}
+            })
+        );
+    }
+
+    #[test]
+    fn downstream_nodes_merge_upstream_fragments() {
+        let params = [("ckpt_name", Value::String("sd15.safetensors".into()))];
+        let upstream = run(&loader(), &[], &params).unwrap();
+        let encode = filter(
+            "CLIPTextEncode",
+            serde_json::json!({
+                "input": {"required": {
+                    "text": ["STRING", {"multiline": true}],
+                    "clip": ["CLIP"]
+                }},
+                "output": ["CONDITIONING"]
+            }),
+        );
+        let ctx = run(
+            &encode,
+            &[("clip", upstream.outputs()["clip"].clone())],
+            &[("text", Value::String("a cat".into()))],
+        )
+        .unwrap();
+        let cond = ComfyLink::from_value(&ctx.outputs()["conditioning"]).unwrap();
+        assert_eq!(cond.prompt.len(), 2);
+        let clip = ComfyLink::from_value(&upstream.outputs()["clip"]).unwrap();
+        assert_eq!(
+            cond.prompt[&cond.node]["inputs"]["clip"],
+            serde_json::json!([clip.node, 1])
+        );
+
+        // Identical upstream nodes collapse to one prompt entry.
+        let again = run(&loader(), &[], &params).unwrap();
+        assert_eq!(again.outputs()["model"], upstream.outputs()["model"]);
+    }
+
+    #[test]
+    fn required_links_must_be_connected() {
+        let encode = filter(
+            "CLIPTextEncode",
+            serde_json::json!({
+                "input": {"required": {"clip": ["CLIP"]}},
+                "output": ["CONDITIONING"]
+            }),
+        );
+        let err = run(&encode, &[], &[]).unwrap_err();
+        assert!(err.to_string().contains("'clip' is not connected"));
+        let err = run(&encode, &[("clip", Value::String("nope".into()))], &[]).unwrap_err();
+        assert!(err.to_string().contains("output of a ComfyUI node"));
+    }
+}
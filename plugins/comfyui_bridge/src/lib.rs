@@ -1,102 +1,109 @@
-//! ComfyUI bridge plugin (scaffold).
+//! ComfyUI bridge plugin.
 //!
-//! This crate is intentionally a minimal placeholder so the workspace can
-//! compile while full ComfyUI bridge functionality is implemented.
+//! At initialisation the plugin fetches `/object_info` from the configured
+//! ComfyUI server and exports one Ambara filter per node class
+//! (`comfyui.<ClassName>`), with metadata generated by [`schema`]. How the
+//! filters turn into ComfyUI prompts is described in [`filter`].
+//!
+//! Configuration (`[plugin.config]` or the host's plugin settings):
+//!
+//! - `comfyui_url`: server base URL, default `http://127.0.0.1:8188`.
+//! - `timeout_secs`: limit for one prompt run, default 300.
+//!
+//! The health check probes `/system_stats`, so the plugin is quarantined
+//! while the server is unreachable and released when it comes back.
 
-use ambara::plugins::api::{AbiResult, PluginHandle, PluginVTable, HOST_ABI_VERSION};
-use std::ffi::c_char;
-use std::ptr;
+pub mod client;
+pub mod filter;
+pub mod schema;
 
-#[repr(C)]
-struct StubPlugin;
+pub use client::{ClientError, ComfyClient};
+pub use filter::{ComfyLink, ComfyNodeFilter};
+pub use schema::NodeClass;
 
-unsafe extern "C" fn plugin_create() -> *mut PluginHandle {
-    let boxed = Box::new(StubPlugin);
-    Box::into_raw(boxed) as *mut PluginHandle
-}
+use ambara_plugin_sdk::{export_plugin, Plugin, PluginFilter};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
 
-unsafe extern "C" fn plugin_destroy(handle: *mut PluginHandle) {
-    if !handle.is_null() {
-        let _ = Box::from_raw(handle as *mut StubPlugin);
-    }
-}
+/// Server used when `comfyui_url` is not configured.
+pub const DEFAULT_URL: &str = "http://127.0.0.1:8188";
 
-unsafe extern "C" fn plugin_init(
-    _handle: *mut PluginHandle,
-    _config_json: *const u8,
-    _config_len: usize,
-) -> AbiResult {
-    AbiResult::Ok
-}
-
-unsafe extern "C" fn filter_count(_handle: *const PluginHandle) -> usize {
-    0
-}
+/// Prompt timeout used when `timeout_secs` is not configured.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 300;
 
-unsafe extern "C" fn filter_id_at(_handle: *const PluginHandle, _index: usize) -> *const c_char {
-    ptr::null()
+/// The plugin entry point.
+#[derive(Default)]
+pub struct ComfyBridgePlugin {
+    client: Option<Arc<ComfyClient>>,
+    classes: Vec<NodeClass>,
 }
 
-unsafe extern "C" fn filter_metadata_json(
-    _handle: *const PluginHandle,
-    _filter_id: *const c_char,
-    _output_buf: *mut u8,
-    _output_capacity: usize,
-) -> usize {
-    0
+impl ComfyBridgePlugin {
+    /// The node classes discovered at initialisation.
+    #[must_use]
+    pub fn classes(&self) -> &[NodeClass] {
+        &self.classes
+    }
 }
 
-unsafe extern "C" fn filter_execute(
-    _handle: *mut PluginHandle,
-    _filter_id: *const c_char,
-    _inputs_json: *const u8,
-    _inputs_len: usize,
-    _params_json: *const u8,
-    _params_len: usize,
-    _output_buf: *mut u8,
-    _output_capacity: usize,
-    _output_len: *mut usize,
-) -> AbiResult {
-    AbiResult::ErrNotSupported
-}
+impl Plugin for ComfyBridgePlugin {
+    fn init(&mut self, config: &serde_json::Value) -> Result<(), String> {
+        let url = config
+            .get("comfyui_url")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or(DEFAULT_URL);
+        let timeout = config
+            .get("timeout_secs")
+            .and_then(serde_json::Value::as_u64)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let client =
+            ComfyClient::new(url, Duration::from_secs(timeout)).map_err(|e| e.to_string())?;
+        let info = client.object_info().map_err(|e| e.to_string())?;
+
+        let mut seen = HashSet::new();
+        self.classes = schema::parse_object_info(&info)?
+            .into_iter()
+            .filter(|class| {
+                let unique = seen.insert(class.filter_id());
+                if !unique {
+                    log::warn!(
+                        "Skipping ComfyUI node class '{}': filter ID {} is taken",
+                        class.name,
+                        class.filter_id()
+                    );
+                }
+                unique
+            })
+            .collect();
+        self.client = Some(Arc::new(client));
+        Ok(())
+    }
 
-unsafe extern "C" fn filter_validate(
-    _handle: *const PluginHandle,
-    _filter_id: *const c_char,
-    _inputs_json: *const u8,
-    _inputs_len: usize,
-    _params_json: *const u8,
-    _params_len: usize,
-    _output_buf: *mut u8,
-    _output_capacity: usize,
-    _output_len: *mut usize,
-) -> AbiResult {
-    AbiResult::Ok
-}
+    fn filters(&self) -> Vec<Box<dyn PluginFilter>> {
+        let Some(client) = &self.client else {
+            return Vec::new();
+        };
+        self.classes
+            .iter()
+            .map(|class| {
+                Box::new(ComfyNodeFilter::new(class.clone(), Arc::clone(client)))
+                    as Box<dyn PluginFilter>
+            })
+            .collect()
+    }
 
-unsafe extern "C" fn plugin_health_check(
-    _handle: *const PluginHandle,
-) -> AbiResult {
-    AbiResult::Ok
+    fn health_check(&self) -> bool {
+        self.client.as_ref().is_some_and(|client| client.ping().is_ok())
+    }
 }
 
-#[no_mangle]
-pub static ambara_plugin_vtable: PluginVTable = PluginVTable {
-    abi_version: HOST_ABI_VERSION,
-    plugin_create,
-    plugin_destroy,
-    plugin_init,
-    filter_count,
-    filter_id_at,
-    filter_metadata_json,
-    filter_execute,
-    filter_validate,
-    plugin_health_check,
-};
+export_plugin!(ComfyBridgePlugin);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ambara::plugins::api::HOST_ABI_VERSION;
 
     #[test]
     fn vtable_advertises_host_abi_version() {
@@ -115,28 +122,23 @@ mod tests {
     #[test]
     fn destroy_null_handle_is_safe_noop() {
         unsafe {
-            (ambara_plugin_vtable.plugin_destroy)(ptr::null_mut());
+            (ambara_plugin_vtable.plugin_destroy)(std::ptr::null_mut());
         }
     }
 
     #[test]
-    fn stub_reports_zero_filters_and_healthy_status() {
-        unsafe {
-            let handle = (ambara_plugin_vtable.plugin_create)();
-            assert_eq!(
-                (ambara_plugin_vtable.filter_count)(handle),
-                0,
-                "scaffold must advertise zero filters"
-            );
-            assert!(
-                (ambara_plugin_vtable.filter_id_at)(handle, 0).is_null(),
-                "filter_id_at on an empty plugin must return null"
-            );
-            assert!(matches!(
-                (ambara_plugin_vtable.plugin_health_check)(handle),
-                AbiResult::Ok
-            ));
-            (ambara_plugin_vtable.plugin_destroy)(handle);
-        }
+    fn uninitialised_plugin_has_no_filters_and_is_unhealthy() {
+        let plugin = ComfyBridgePlugin::default();
+        assert!(plugin.filters().is_empty());
+        assert!(!plugin.health_check());
+    }
+
+    #[test]
+    fn unreachable_server_fails_init() {
+        let mut plugin = ComfyBridgePlugin::default();
+        let err = plugin
+            .init(&serde_json::json!({"comfyui_url": "http://127.0.0.1:9", "timeout_secs": 1}))
+            .unwrap_err();
+        assert!(err.contains("/object_info"), "{err}");
     }
 }
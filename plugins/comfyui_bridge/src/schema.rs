@@ -0,0 +1,630 @@
+//! Translation of ComfyUI's `/object_info` into Ambara node metadata.
+//!
+//! `/object_info` describes every node class a ComfyUI server knows:
+//!
+//! ```json
+//! "KSampler": {
+//!     "input": {
+//!         "required": {
+//!             "model": ["MODEL"],
+//!             "seed": ["INT", {"default": 0, "min": 0, "max": 18446744073709551615}],
+//!             "sampler_name": [["euler", "dpmpp_2m"], {}]
+//!         },
+//!         "optional": {}
+//!     },
+//!     "input_order": {"required": ["model", "seed", "sampler_name"]},
+//!     "output": ["LATENT"],
+//!     "output_name": ["LATENT"],
+//!     "display_name": "KSampler",
+//!     "category": "sampling",
+//!     "output_node": false
+//! }
+//! ```
+//!
+//! Widget inputs (`INT`, `FLOAT`, `STRING`, `BOOLEAN` and option lists)
+//! become Ambara parameters; everything else becomes an input port. `IMAGE`
+//! maps to [`PortType::Image`], every other ComfyUI type (`MODEL`, `CLIP`,
+//! `VAE`, `LATENT`, `CONDITIONING`, `MASK`, ...) to [`PortType::Custom`] with
+//! the ComfyUI type name, so only matching ComfyUI sockets can be connected.
+
+use ambara::core::node::{Category, NodeMetadata};
+use ambara::core::port::{ParameterDefinition, PortDefinition, UiHint};
+use ambara::core::types::{PortType, Value};
+
+/// Prefix of every filter ID this plugin exports.
+pub const FILTER_PREFIX: &str = "comfyui.";
+
+/// The ComfyUI type exchanged as Ambara images.
+pub const IMAGE_TYPE: &str = "IMAGE";
+
+/// Name of the extra image output added to output nodes without one.
+pub const OUTPUT_NODE_IMAGE: &str = "image";
+
+/// One node class from `/object_info`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeClass {
+    /// ComfyUI class name (`class_type` in API prompts).
+    pub name: String,
+    /// Human-readable name.
+    pub display_name: String,
+    /// Description, possibly empty.
+    pub description: String,
+    /// Slash-separated ComfyUI menu category.
+    pub category: String,
+    /// Whether ComfyUI treats the node as a workflow output.
+    pub output_node: bool,
+    /// Inputs in ComfyUI's declared order, required before optional.
+    pub inputs: Vec<InputSpec>,
+    /// Outputs in slot order.
+    pub outputs: Vec<OutputSpec>,
+}
+
+/// One input of a [`NodeClass`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputSpec {
+    /// Input name, shared by the ComfyUI input and the Ambara port/parameter.
+    pub name: String,
+    /// What the input accepts.
+    pub kind: InputKind,
+    /// Whether the input is listed under `optional`.
+    pub optional: bool,
+    /// Whether a widget input is declared with `forceInput` and must be
+    /// connected rather than edited.
+    pub force_input: bool,
+    /// The `tooltip`, if any.
+    pub tooltip: String,
+}
+
+/// The type of an [`InputSpec`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputKind {
+    /// `INT` widget.
+    Int {
+        /// Default value.
+        default: i64,
+        /// Lower bound, if declared.
+        min: Option<f64>,
+        /// Upper bound, if declared.
+        max: Option<f64>,
+    },
+    /// `FLOAT` widget.
+    Float {
+        /// Default value.
+        default: f64,
+        /// Lower bound, if declared.
+        min: Option<f64>,
+        /// Upper bound, if declared.
+        max: Option<f64>,
+    },
+    /// `STRING` widget.
+    String {
+        /// Default value.
+        default: String,
+        /// Whether the widget is a multi-line text box.
+        multiline: bool,
+    },
+    /// `BOOLEAN` widget.
+    Boolean {
+        /// Default value.
+        default: bool,
+    },
+    /// A fixed list of options (checkpoint names, samplers, ...).
+    Combo {
+        /// The options, in server order.
+        options: Vec<String>,
+        /// Default option; the first one unless declared.
+        default: String,
+    },
+    /// A socket carrying a ComfyUI type produced by another node.
+    Link(String),
+}
+
+/// One output slot of a [`NodeClass`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputSpec {
+    /// Ambara output port name, unique within the node.
+    pub port: String,
+    /// ComfyUI type of the slot.
+    pub comfy_type: String,
+}
+
+impl InputKind {
+    /// Whether the input is edited as a parameter (as opposed to a socket).
+    #[must_use]
+    pub fn is_widget(&self) -> bool {
+        !matches!(self, InputKind::Link(_))
+    }
+
+    /// The Ambara type of the value this input carries.
+    #[must_use]
+    pub fn port_type(&self) -> PortType {
+        match self {
+            InputKind::Int { .. } => PortType::Integer,
+            InputKind::Float { .. } => PortType::Float,
+            InputKind::String { .. } | InputKind::Combo { .. } => PortType::String,
+            InputKind::Boolean { .. } => PortType::Boolean,
+            InputKind::Link(comfy_type) => port_type_for(comfy_type),
+        }
+    }
+
+    fn default_value(&self) -> Value {
+        match self {
+            InputKind::Int { default, .. } => Value::Integer(*default),
+            InputKind::Float { default, .. } => Value::Float(*default),
+            InputKind::String { default, .. } | InputKind::Combo { default, .. } => {
+                Value::String(default.clone())
+            }
+            InputKind::Boolean { default } => Value::Boolean(*default),
+            InputKind::Link(_) => Value::None,
+        }
+    }
+}
+
+impl InputSpec {
+    /// Whether the input is an Ambara input port rather than a parameter.
+    #[must_use]
+    pub fn is_port(&self) -> bool {
+        self.force_input || !self.kind.is_widget()
+    }
+}
+
+/// Ambara port type for a ComfyUI socket type.
+#[must_use]
+pub fn port_type_for(comfy_type: &str) -> PortType {
+    if comfy_type == IMAGE_TYPE {
+        PortType::Image
+    } else {
+        PortType::Custom(comfy_type.to_string())
+    }
+}
+
+/// Parse a whole `/object_info` response.
+///
+/// Classes whose description cannot be understood are skipped with a
+/// warning rather than failing the whole plugin.
+///
+/// # Errors
+///
+/// Returns a message if `info` is not a JSON object.
+pub fn parse_object_info(info: &serde_json::Value) -> Result<Vec<NodeClass>, String> {
+    let classes = info
+        .as_object()
+        .ok_or_else(|| "/object_info did not return a JSON object".to_string())?;
+    Ok(classes
+        .iter()
+        .filter_map(|(name, spec)| match NodeClass::parse(name, spec) {
+            Ok(class) => Some(class),
+            Err(e) => {
+                log::warn!("Skipping ComfyUI node class '{name}': {e}");
+                None
+            }
+        })
+        .collect())
+}
+
+impl NodeClass {
+    /// Parse one class entry of `/object_info`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the entry or one of its inputs is malformed.
+    pub fn parse(name: &str, spec: &serde_json::Value) -> Result<Self, String> {
+        let text = |key: &str| {
+            spec.get(key)
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let mut inputs = Vec::new();
+        for (group, optional) in [("required", false), ("optional", true)] {
+            let Some(entries) = spec
+                .get("input")
+                .and_then(|i| i.get(group))
+                .and_then(serde_json::Value::as_object)
+            else {
+                continue;
+            };
+            // serde_json sorts object keys; `input_order` restores the
+            // server's order where available.
+            let order: Vec<&str> = spec
+                .get("input_order")
+                .and_then(|o| o.get(group))
+                .and_then(serde_json::Value::as_array)
+                .map(|names| names.iter().filter_map(serde_json::Value::as_str).collect())
+                .unwrap_or_else(|| entries.keys().map(String::as_str).collect());
+            for input_name in order {
+                let Some(entry) = entries.get(input_name) else {
+                    continue;
+                };
+                inputs.push(InputSpec::parse(input_name, entry, optional)?);
+            }
+        }
+
+        let types: Vec<String> = spec
+            .get("output")
+            .and_then(serde_json::Value::as_array)
+            .map(|types| {
+                types
+                    .iter()
+                    .map(|t| t.as_str().unwrap_or("*").to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let names: Vec<&str> = spec
+            .get("output_name")
+            .and_then(serde_json::Value::as_array)
+            .map(|names| names.iter().filter_map(serde_json::Value::as_str).collect())
+            .unwrap_or_default();
+        let mut outputs: Vec<OutputSpec> = Vec::with_capacity(types.len());
+        for (slot, comfy_type) in types.into_iter().enumerate() {
+            let base = names.get(slot).copied().unwrap_or(&comfy_type).to_lowercase();
+            let mut port = base.clone();
+            let mut n = 2;
+            while outputs.iter().any(|o| o.port == port) {
+                port = format!("{base}_{n}");
+                n += 1;
+            }
+            outputs.push(OutputSpec { port, comfy_type });
+        }
+
+        let display_name = text("display_name");
+        Ok(Self {
+            name: name.to_string(),
+            display_name: if display_name.is_empty() {
+                name.to_string()
+            } else {
+                display_name
+            },
+            description: text("description"),
+            category: text("category"),
+            output_node: spec
+                .get("output_node")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false),
+            inputs,
+            outputs,
+        })
+    }
+
+    /// The Ambara filter ID for this class.
+    ///
+    /// Characters outside `[A-Za-z0-9_]` (custom node packs use spaces and
+    /// punctuation) are replaced with `_`.
+    #[must_use]
+    pub fn filter_id(&self) -> String {
+        let sanitized: String = self
+            .name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' })
+            .collect();
+        format!("{FILTER_PREFIX}{sanitized}")
+    }
+
+    /// Whether executing the node requires running a prompt on the server:
+    /// it produces images, or it is an output node.
+    #[must_use]
+    pub fn needs_submission(&self) -> bool {
+        self.output_node || self.outputs.iter().any(|o| o.comfy_type == IMAGE_TYPE)
+    }
+
+    /// Whether an output node gets the extra [`OUTPUT_NODE_IMAGE`] port.
+    #[must_use]
+    pub fn has_output_node_image(&self) -> bool {
+        self.output_node && !self.outputs.iter().any(|o| o.port == OUTPUT_NODE_IMAGE)
+    }
+
+    /// Generate the Ambara metadata for this class.
+    #[must_use]
+    pub fn metadata(&self) -> NodeMetadata {
+        let mut description = self.description.clone();
+        if description.is_empty() {
+            description = format!("ComfyUI node {}", self.name);
+        }
+        let mut builder = NodeMetadata::builder(self.filter_id(), &self.display_name)
+            .category(if self.output_node {
+                Category::Output
+            } else {
+                Category::Api
+            })
+            .description(description)
+            .tag("comfyui")
+            .tags(self.category.split('/').filter(|s| !s.is_empty()))
+            .non_deterministic();
+
+        for input in &self.inputs {
+            if input.is_port() {
+                let mut port = PortDefinition::input(&input.name, input.kind.port_type())
+                    .with_description(&input.tooltip);
+                if input.optional {
+                    port = port.optional();
+                }
+                builder = builder.input(port);
+            } else {
+                builder = builder.parameter(input.parameter());
+            }
+        }
+        for output in &self.outputs {
+            builder = builder.output(PortDefinition::output(
+                &output.port,
+                port_type_for(&output.comfy_type),
+            ));
+        }
+        if self.has_output_node_image() {
+            builder = builder.output(
+                PortDefinition::output(OUTPUT_NODE_IMAGE, PortType::Image)
+                    .with_description("First image saved by this node"),
+            );
+        }
+        builder.build()
+    }
+}
+
+impl InputSpec {
+    fn parse(name: &str, entry: &serde_json::Value, optional: bool) -> Result<Self, String> {
+        let entry = entry
+            .as_array()
+            .ok_or_else(|| format!("input '{name}' is not a list"))?;
+        let head = entry
+            .first()
+            .ok_or_else(|| format!("input '{name}' is empty"))?;
+        let options = entry.get(1).cloned().unwrap_or_else(|| serde_json::json!({}));
+        let number = |key: &str| options.get(key).and_then(serde_json::Value::as_f64);
+        let flag = |key: &str| {
+            options
+                .get(key)
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false)
+        };
+        let combo = |list: &[serde_json::Value]| {
+            let options: Vec<String> = list
+                .iter()
+                .map(|o| match o {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+            let default = options.first().cloned().unwrap_or_default();
+            InputKind::Combo { options, default }
+        };
+
+        let mut kind = match head {
+            serde_json::Value::Array(list) => combo(list),
+            serde_json::Value::String(t) => match t.as_str() {
+                "INT" => InputKind::Int {
+                    default: number("default").map_or(0, clamp_i64),
+                    min: number("min"),
+                    max: number("max"),
+                },
+                "FLOAT" => InputKind::Float {
+                    default: number("default").unwrap_or(0.0),
+                    min: number("min"),
+                    max: number("max"),
+                },
+                "STRING" => InputKind::String {
+                    default: options
+                        .get("default")
+                        .and_then(serde_json::Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                    multiline: flag("multiline"),
+                },
+                "BOOLEAN" => InputKind::Boolean {
+                    default: flag("default"),
+                },
+                "COMBO" => combo(
+                    options
+                        .get("options")
+                        .and_then(serde_json::Value::as_array)
+                        .map_or(&[][..], Vec::as_slice),
+                ),
+                other => InputKind::Link(other.to_string()),
+            },
+            _ => return Err(format!("input '{name}' has an unknown type")),
+        };
+        if let InputKind::Combo { options: list, default } = &mut kind {
+            if let Some(declared) = options.get("default").and_then(serde_json::Value::as_str) {
+                if list.iter().any(|o| o == declared) {
+                    *default = declared.to_string();
+                }
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            kind,
+            optional,
+            force_input: flag("forceInput"),
+            tooltip: options
+                .get("tooltip")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+
+    fn parameter(&self) -> ParameterDefinition {
+        let mut def =
+            ParameterDefinition::new(&self.name, self.kind.port_type(), self.kind.default_value())
+                .with_description(&self.tooltip);
+        match &self.kind {
+            InputKind::Int { min, max, .. } | InputKind::Float { min, max, .. } => {
+                if let (Some(min), Some(max)) = (min, max) {
+                    def = def.with_range(*min, *max);
+                }
+            }
+            InputKind::String { multiline, .. } => {
+                def = def.with_ui_hint(UiHint::TextInput {
+                    multiline: *multiline,
+                    placeholder: None,
+                });
+            }
+            InputKind::Boolean { .. } => def = def.with_ui_hint(UiHint::Checkbox),
+            InputKind::Combo { options, .. } => {
+                def = def.with_ui_hint(UiHint::Dropdown {
+                    options: options.clone(),
+                });
+            }
+            InputKind::Link(_) => {}
+        }
+        if self.optional {
+            def = def.with_group("Optional");
+        }
+        def
+    }
+}
+
+/// Convert an Ambara parameter or primitive input into the literal ComfyUI
+/// expects for a widget input.
+#[must_use]
+pub fn widget_literal(kind: &InputKind, value: &Value) -> serde_json::Value {
+    match (kind, value) {
+        (InputKind::Int { .. }, Value::Float(f)) => serde_json::json!(clamp_i64(*f)),
+        (InputKind::Float { .. }, Value::Integer(i)) => serde_json::json!(*i as f64),
+        (_, Value::Integer(i)) => serde_json::json!(i),
+        (_, Value::Float(f)) => serde_json::json!(f),
+        (_, Value::String(s)) => serde_json::json!(s),
+        (_, Value::Boolean(b)) => serde_json::json!(b),
+        _ => serde_json::Value::Null,
+    }
+}
+
+fn clamp_i64(value: f64) -> i64 {
+    // Saturating cast: seeds are declared with a u64 maximum.
+    value as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ksampler() -> serde_json::Value {
+        serde_json::json!({
+            "input": {
+                "required": {
+                    "model": ["MODEL", {"tooltip": "The model"}],
+                    "seed": ["INT", {"default": 0, "min": 0, "max": 18446744073709551615u64}],
+                    "cfg": ["FLOAT", {"default": 8.0, "min": 0.0, "max": 100.0}],
+                    "sampler_name": [["euler", "dpmpp_2m"], {}],
+                    "latent_image": ["LATENT"]
+                },
+                "optional": {
+                    "note": ["STRING", {"multiline": true, "default": "hi"}]
+                }
+            },
+            "input_order": {
+                "required": ["model", "seed", "cfg", "sampler_name", "latent_image"],
+                "optional": ["note"]
+            },
+            "output": ["LATENT"],
+            "output_name": ["LATENT"],
+            "display_name": "KSampler",
+            "category": "sampling",
+            "output_node": false
+        })
+    }
+
+    mod parsing {
+        use super::*;
+
+        #[test]
+        fn widgets_become_parameters_and_links_become_ports() {
+            let class = NodeClass::parse("KSampler", &ksampler()).unwrap();
+            let meta = class.metadata();
+            assert_eq!(meta.id, "comfyui.KSampler");
+            assert_eq!(meta.input_names(), ["model", "latent_image"]);
+            assert_eq!(meta.inputs[0].port_type, PortType::Custom("MODEL".into()));
+            assert_eq!(meta.parameter_names(), ["seed", "cfg", "sampler_name", "note"]);
+            assert_eq!(meta.outputs[0].name, "latent");
+            assert_eq!(meta.outputs[0].port_type, PortType::Custom("LATENT".into()));
+
+            let sampler = meta.get_parameter("sampler_name").unwrap();
+            assert_eq!(sampler.default_value, Value::String("euler".into()));
+            assert!(matches!(&sampler.ui_hint, UiHint::Dropdown { options } if options.len() == 2));
+            assert!(meta.get_parameter("cfg").unwrap().validate(&Value::Float(101.0)).is_err());
+            assert_eq!(meta.get_parameter("seed").unwrap().default_value, Value::Integer(0));
+            assert!(!class.needs_submission());
+        }
+
+        #[test]
+        fn images_map_to_image_ports_and_output_nodes_submit() {
+            let class = NodeClass::parse(
+                "SaveImage",
+                &serde_json::json!({
+                    "input": {"required": {
+                        "images": ["IMAGE"],
+                        "filename_prefix": ["STRING", {"default": "ComfyUI"}]
+                    }},
+                    "output": [],
+                    "output_node": true,
+                    "category": "image"
+                }),
+            )
+            .unwrap();
+            let meta = class.metadata();
+            assert_eq!(meta.inputs[0].port_type, PortType::Image);
+            assert_eq!(meta.output_names(), [OUTPUT_NODE_IMAGE]);
+            assert_eq!(meta.category, Category::Output);
+            assert!(class.needs_submission());
+        }
+
+        #[test]
+        fn duplicate_output_names_and_odd_class_names_are_made_unique() {
+            let class = NodeClass::parse(
+                "Split Image (pack)",
+                &serde_json::json!({
+                    "input": {"required": {"image": ["IMAGE"]}},
+                    "output": ["IMAGE", "IMAGE", "MASK"],
+                }),
+            )
+            .unwrap();
+            assert_eq!(class.filter_id(), "comfyui.Split_Image__pack_");
+            let ports: Vec<&str> = class.outputs.iter().map(|o| o.port.as_str()).collect();
+            assert_eq!(ports, ["image", "image_2", "mask"]);
+        }
+
+        #[test]
+        fn malformed_classes_are_skipped() {
+            let info = serde_json::json!({
+                "KSampler": ksampler(),
+                "Broken": {"input": {"required": {"x": 5}}}
+            });
+            let classes = parse_object_info(&info).unwrap();
+            assert_eq!(classes.len(), 1);
+            assert!(parse_object_info(&serde_json::json!([])).is_err());
+        }
+
+        #[test]
+        fn combo_spelled_as_type_with_options() {
+            let class = NodeClass::parse(
+                "Loader",
+                &serde_json::json!({
+                    "input": {"required": {
+                        "name": ["COMBO", {"options": ["a", "b"], "default": "b"}],
+                        "count": ["INT", {"default": 3, "forceInput": true}]
+                    }},
+                    "output": ["MODEL"]
+                }),
+            )
+            .unwrap();
+            let meta = class.metadata();
+            assert_eq!(
+                meta.get_parameter("name").unwrap().default_value,
+                Value::String("b".into())
+            );
+            assert_eq!(meta.get_input("count").unwrap().port_type, PortType::Integer);
+        }
+    }
+
+    #[test]
+    fn widget_literals_follow_declared_type() {
+        let int = InputKind::Int { default: 0, min: None, max: None };
+        let float = InputKind::Float { default: 0.0, min: None, max: None };
+        assert_eq!(widget_literal(&int, &Value::Float(3.0)), serde_json::json!(3));
+        assert_eq!(widget_literal(&float, &Value::Integer(2)), serde_json::json!(2.0));
+        assert_eq!(
+            widget_literal(&InputKind::Boolean { default: false }, &Value::Boolean(true)),
+            serde_json::json!(true)
+        );
+    }
+}
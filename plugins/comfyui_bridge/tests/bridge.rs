@@ -0,0 +1,395 @@
+//! Loads the built plugin against a mock ComfyUI server.
+//!
+//! The mock serves a recorded `/object_info` (`tests/fixtures`) and answers
+//! `/prompt`, `/history`, `/view` and `/upload/image` the way ComfyUI does,
+//! recording every request so tests can assert on the exact prompt sent.
+
+use ambara::core::types::{ImageValue, PortType, Value};
+use ambara_plugin_sdk::testing::PluginHarness;
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+const MANIFEST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/ambara-plugin.toml");
+const OBJECT_INFO: &str = include_str!("fixtures/object_info.json");
+
+#[derive(Debug, Clone)]
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+#[derive(Default)]
+struct State {
+    requests: Vec<Request>,
+    prompts: Vec<serde_json::Value>,
+    reject_prompts: bool,
+}
+
+/// A minimal HTTP/1.1 server speaking just enough of the ComfyUI API.
+struct MockComfy {
+    url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockComfy {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(State::default()));
+        let shared = Arc::clone(&state);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = serve(stream, &shared);
+            }
+        });
+        Self { url, state }
+    }
+
+    fn requests(&self, method: &str, prefix: &str) -> Vec<Request> {
+        let state = self.state.lock().unwrap();
+        state
+            .requests
+            .iter()
+            .filter(|r| r.method == method && r.path.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    fn prompts(&self) -> Vec<serde_json::Value> {
+        self.state.lock().unwrap().prompts.clone()
+    }
+
+    fn harness(&self) -> PluginHarness {
+        PluginHarness::load_with_config(
+            PluginHarness::cdylib_path("comfyui_bridge"),
+            MANIFEST,
+            &serde_json::json!({ "comfyui_url": self.url, "timeout_secs": 10 }),
+        )
+        .expect("comfyui_bridge should load against the mock server")
+    }
+}
+
+fn serve(stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let request = Request { method, path, body };
+    let (status, content_type, response) = respond(&request, state);
+    state.lock().unwrap().requests.push(request);
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status} OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        response.len()
+    )?;
+    stream.write_all(&response)?;
+    stream.flush()
+}
+
+fn respond(request: &Request, state: &Mutex<State>) -> (u16, &'static str, Vec<u8>) {
+    let json = |value: serde_json::Value| (200, "application/json", value.to_string().into_bytes());
+    let path = request.path.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("GET", "/object_info") => (200, "application/json", OBJECT_INFO.as_bytes().to_vec()),
+        ("GET", "/system_stats") => json(serde_json::json!({ "system": {} })),
+        ("POST", "/prompt") => {
+            let mut state = state.lock().unwrap();
+            if state.reject_prompts {
+                return (
+                    400,
+                    "application/json",
+                    br#"{"error": {"type": "prompt_outputs_failed_validation"}}"#.to_vec(),
+                );
+            }
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            state.prompts.push(body["prompt"].clone());
+            json(serde_json::json!({
+                "prompt_id": format!("prompt-{}", state.prompts.len()),
+                "number": state.prompts.len(),
+                "node_errors": {}
+            }))
+        }
+        ("GET", history) if history.starts_with("/history/") => {
+            let state = state.lock().unwrap();
+            let id = &history["/history/".len()..];
+            let index: usize = id.trim_start_matches("prompt-").parse().unwrap();
+            let prompt = &state.prompts[index - 1];
+            let outputs: serde_json::Map<String, serde_json::Value> = prompt
+                .as_object()
+                .unwrap()
+                .iter()
+                .filter(|(_, node)| {
+                    matches!(node["class_type"].as_str(), Some("PreviewImage" | "SaveImage"))
+                })
+                .map(|(node_id, _)| {
+                    (
+                        node_id.clone(),
+                        serde_json::json!({ "images": [
+                            { "filename": format!("{node_id}.png"), "subfolder": "", "type": "temp" }
+                        ]}),
+                    )
+                })
+                .collect();
+            json(serde_json::json!({
+                id: { "outputs": outputs, "status": { "status_str": "success", "completed": true } }
+            }))
+        }
+        ("GET", "/view") => (200, "image/png", png(Rgba([200, 10, 10, 255]))),
+        ("POST", "/upload/image") => json(serde_json::json!({
+            "name": "ambara_upload.png", "subfolder": "ambara", "type": "input"
+        })),
+        _ => (404, "text/plain", b"not found".to_vec()),
+    }
+}
+
+fn png(color: Rgba<u8>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(3, 2, color))
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .unwrap();
+    bytes
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+/// Resolve a `[node_id, slot]` link in `prompt` to the linked class type.
+fn linked_class<'a>(prompt: &'a serde_json::Value, link: &serde_json::Value) -> (&'a str, u64) {
+    let node = link[0].as_str().expect("link should name a node");
+    let class = prompt[node]["class_type"].as_str().expect("linked node should exist");
+    (class, link[1].as_u64().unwrap())
+}
+
+#[test]
+fn discovers_filters_from_object_info() {
+    let server = MockComfy::start();
+    let harness = server.harness();
+    let ids = harness.filter_ids();
+    assert_eq!(ids.len(), 7);
+    assert!(ids.contains(&"comfyui.KSampler".to_string()));
+
+    let sampler = harness.metadata("comfyui.KSampler").unwrap();
+    assert_eq!(sampler.name, "KSampler");
+    assert_eq!(
+        sampler.input_names(),
+        ["model", "positive", "negative", "latent_image"]
+    );
+    assert_eq!(sampler.inputs[0].port_type, PortType::Custom("MODEL".into()));
+    assert_eq!(sampler.inputs[1].port_type, PortType::Custom("CONDITIONING".into()));
+    assert_eq!(sampler.outputs[0].port_type, PortType::Custom("LATENT".into()));
+    assert_eq!(
+        sampler.parameter_names(),
+        ["seed", "steps", "cfg", "sampler_name", "scheduler", "denoise"]
+    );
+
+    let decode = harness.metadata("comfyui.VAEDecode").unwrap();
+    assert_eq!(decode.outputs[0].port_type, PortType::Image);
+    assert_eq!(server.requests("GET", "/object_info").len(), 1);
+}
+
+#[test]
+fn text_to_image_chain_runs_as_one_prompt() {
+    let server = MockComfy::start();
+    let mut harness = server.harness();
+
+    let checkpoint = harness
+        .execute(
+            "comfyui.CheckpointLoaderSimple",
+            &[],
+            &[("ckpt_name", string("v1-5-pruned-emaonly.safetensors"))],
+        )
+        .unwrap();
+    let positive = harness
+        .execute(
+            "comfyui.CLIPTextEncode",
+            &[("clip", checkpoint["clip"].clone())],
+            &[("text", string("a lighthouse at dusk"))],
+        )
+        .unwrap();
+    let negative = harness
+        .execute(
+            "comfyui.CLIPTextEncode",
+            &[("clip", checkpoint["clip"].clone())],
+            &[("text", string("blurry"))],
+        )
+        .unwrap();
+    let latent = harness
+        .execute(
+            "comfyui.EmptyLatentImage",
+            &[],
+            &[("width", Value::Integer(768))],
+        )
+        .unwrap();
+    let sampled = harness
+        .execute(
+            "comfyui.KSampler",
+            &[
+                ("model", checkpoint["model"].clone()),
+                ("positive", positive["conditioning"].clone()),
+                ("negative", negative["conditioning"].clone()),
+                ("latent_image", latent["latent"].clone()),
+            ],
+            &[("seed", Value::Integer(42)), ("steps", Value::Integer(12))],
+        )
+        .unwrap();
+    assert!(server.prompts().is_empty(), "only image outputs submit");
+
+    let decoded = harness
+        .execute(
+            "comfyui.VAEDecode",
+            &[
+                ("samples", sampled["latent"].clone()),
+                ("vae", checkpoint["vae"].clone()),
+            ],
+            &[],
+        )
+        .unwrap();
+
+    let prompts = server.prompts();
+    assert_eq!(prompts.len(), 1);
+    let prompt = &prompts[0];
+    // Checkpoint, two encoders, latent, sampler, decode and the preview;
+    // the checkpoint loader reached through four links appears once.
+    assert_eq!(prompt.as_object().unwrap().len(), 7);
+
+    let (_, preview) = prompt
+        .as_object()
+        .unwrap()
+        .iter()
+        .find(|(_, node)| node["class_type"] == "PreviewImage")
+        .unwrap();
+    assert_eq!(linked_class(prompt, &preview["inputs"]["images"]), ("VAEDecode", 0));
+    let decode = &prompt[preview["inputs"]["images"][0].as_str().unwrap()];
+    assert_eq!(linked_class(prompt, &decode["inputs"]["vae"]), ("CheckpointLoaderSimple", 2));
+
+    let sampler = &prompt[decode["inputs"]["samples"][0].as_str().unwrap()];
+    assert_eq!(sampler["class_type"], "KSampler");
+    let inputs = &sampler["inputs"];
+    assert_eq!(inputs["seed"], 42);
+    assert_eq!(inputs["steps"], 12);
+    assert_eq!(inputs["cfg"], 8.0);
+    assert_eq!(inputs["sampler_name"], "euler");
+    assert_eq!(inputs["scheduler"], "normal");
+    assert_eq!(inputs["denoise"], 1.0);
+    assert_eq!(linked_class(prompt, &inputs["model"]), ("CheckpointLoaderSimple", 0));
+    assert_eq!(linked_class(prompt, &inputs["latent_image"]), ("EmptyLatentImage", 0));
+    let positive = &prompt[inputs["positive"][0].as_str().unwrap()];
+    assert_eq!(
+        positive["inputs"],
+        serde_json::json!({
+            "text": "a lighthouse at dusk",
+            "clip": [inputs["model"][0].clone(), 1]
+        })
+    );
+    assert_eq!(
+        prompt[inputs["negative"][0].as_str().unwrap()]["inputs"]["text"],
+        "blurry"
+    );
+    assert_eq!(
+        prompt[inputs["latent_image"][0].as_str().unwrap()]["inputs"],
+        serde_json::json!({ "width": 768, "height": 512, "batch_size": 1 })
+    );
+    assert_eq!(
+        prompt[inputs["model"][0].as_str().unwrap()]["inputs"]["ckpt_name"],
+        "v1-5-pruned-emaonly.safetensors"
+    );
+
+    let image = decoded["image"].as_image().unwrap().get_image().unwrap().to_rgba8();
+    assert_eq!(image.dimensions(), (3, 2));
+    assert_eq!(image.get_pixel(0, 0), &Rgba([200, 10, 10, 255]));
+    let views = server.requests("GET", "/view");
+    assert_eq!(views.len(), 1);
+    assert!(views[0].path.contains("type=temp"));
+}
+
+#[test]
+fn image_inputs_are_uploaded_and_loaded() {
+    let server = MockComfy::start();
+    let mut harness = server.harness();
+    let input = Value::Image(ImageValue::new(DynamicImage::ImageRgba8(
+        RgbaImage::from_pixel(4, 4, Rgba([1, 2, 3, 255])),
+    )));
+
+    let saved = harness
+        .execute(
+            "comfyui.SaveImage",
+            &[("images", input)],
+            &[("filename_prefix", string("ambara"))],
+        )
+        .unwrap();
+    assert!(saved["image"].as_image().is_some());
+
+    let uploads = server.requests("POST", "/upload/image");
+    assert_eq!(uploads.len(), 1);
+    let body = &uploads[0].body;
+    let text = String::from_utf8_lossy(body);
+    assert!(text.contains(r#"name="image"; filename="ambara_"#));
+    assert!(body.windows(4).any(|w| w == b"\x89PNG"));
+
+    let prompt = &server.prompts()[0];
+    assert_eq!(prompt.as_object().unwrap().len(), 2);
+    let (_, save) = prompt
+        .as_object()
+        .unwrap()
+        .iter()
+        .find(|(_, node)| node["class_type"] == "SaveImage")
+        .unwrap();
+    assert_eq!(save["inputs"]["filename_prefix"], "ambara");
+    let loader = &prompt[save["inputs"]["images"][0].as_str().unwrap()];
+    assert_eq!(
+        loader,
+        &serde_json::json!({
+            "class_type": "LoadImage",
+            "inputs": { "image": "ambara/ambara_upload.png" }
+        })
+    );
+}
+
+#[test]
+fn rejected_prompts_fail_the_node() {
+    let server = MockComfy::start();
+    let mut harness = server.harness();
+    server.state.lock().unwrap().reject_prompts = true;
+    let input = Value::Image(ImageValue::new(DynamicImage::ImageRgba8(
+        RgbaImage::from_pixel(1, 1, Rgba([0, 0, 0, 255])),
+    )));
+    assert!(harness
+        .execute("comfyui.ImageInvert", &[("image", input)], &[])
+        .is_err());
+    assert_eq!(server.requests("POST", "/prompt").len(), 1);
+}
+
+#[test]
+fn health_follows_server_reachability() {
+    let server = MockComfy::start();
+    let mut harness = server.harness();
+    assert!(harness.health_check().healthy);
+    assert!(!server.requests("GET", "/system_stats").is_empty());
+}
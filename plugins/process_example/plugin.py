@@ -0,0 +1,141 @@
+#!/usr/bin/env python3
+"""Example Ambara process plugin.
+
+Speaks the JSON-lines protocol documented in `src/plugins/process.rs`:
+one JSON request per line on stdin, one response per line on stdout.
+Images arrive as raw RGBA8 files (`image_transport = "shared_memory"`).
+"""
+
+import json
+import os
+import sys
+import uuid
+
+
+def port(name, kind, direction):
+    return {
+        "name": name,
+        "display_name": name.capitalize(),
+        "port_type": {"kind": kind},
+        "direction": direction,
+        "default_value": None,
+        "optional": False,
+        "description": "",
+        "constraints": [],
+    }
+
+
+def metadata(filter_id, name, category, inputs, outputs, parameters):
+    return {
+        "id": filter_id,
+        "name": name,
+        "category": category,
+        "description": name,
+        "version": "0.1.0",
+        "author": "Ambara Contributors",
+        "inputs": inputs,
+        "outputs": outputs,
+        "parameters": parameters,
+        "tags": ["process"],
+        "color": None,
+        "supports_progress": False,
+        "deterministic": True,
+    }
+
+
+FILTERS = [
+    metadata(
+        "process_example.invert",
+        "Invert (Process)",
+        "color",
+        [port("image", "Image", "input")],
+        [port("image", "Image", "output")],
+        [],
+    ),
+    metadata(
+        "process_example.scale",
+        "Scale (Process)",
+        "math",
+        [port("value", "Float", "input")],
+        [port("value", "Float", "output")],
+        [
+            {
+                "name": "factor",
+                "display_name": "Factor",
+                "param_type": {"kind": "Float"},
+                "default_value": {"type": "Float", "data": 1.0},
+                "description": "Multiplier (must not be negative)",
+                "constraints": [],
+                "ui_hint": {"widget": "Default"},
+                "group": None,
+            }
+        ],
+    ),
+]
+
+scratch_dir = os.environ.get("AMBARA_SCRATCH_DIR", ".")
+
+
+def invert(inputs, params):
+    image = inputs["image"]["data"]
+    with open(image["data_ref"]["value"], "rb") as f:
+        pixels = bytearray(f.read())
+    for i in range(0, len(pixels), 4):
+        pixels[i] = 255 - pixels[i]
+        pixels[i + 1] = 255 - pixels[i + 1]
+        pixels[i + 2] = 255 - pixels[i + 2]
+    out_path = os.path.join(scratch_dir, uuid.uuid4().hex + ".rgba")
+    with open(out_path, "wb") as f:
+        f.write(pixels)
+    result = {
+        "metadata": image["metadata"],
+        "data_ref": {"ref_type": "Temporary", "value": out_path},
+    }
+    return {"image": {"type": "Image", "data": result}}
+
+
+def factor_of(params):
+    return params.get("factor", {"data": 1.0})["data"]
+
+
+def scale(inputs, params):
+    value = inputs["value"]["data"]
+    return {"value": {"type": "Float", "data": value * factor_of(params)}}
+
+
+EXECUTE = {"process_example.invert": invert, "process_example.scale": scale}
+
+
+def handle(method, params):
+    if method == "handshake":
+        return {"protocol_version": 1, "filters": FILTERS}
+    if method == "health":
+        return {"healthy": True}
+    if method == "validate":
+        errors = []
+        if params["filter_id"] == "process_example.scale" and factor_of(params["params"]) < 0:
+            errors.append("factor must not be negative")
+        return {"errors": errors}
+    if method == "execute":
+        run = EXECUTE.get(params["filter_id"])
+        if run is None:
+            raise ValueError("unknown filter " + params["filter_id"])
+        return {"outputs": run(params["inputs"], params["params"])}
+    raise ValueError("unknown method " + method)
+
+
+def main():
+    for line in sys.stdin:
+        request = json.loads(line)
+        if request["method"] == "shutdown":
+            break
+        try:
+            response = {"id": request["id"], "result": handle(request["method"], request["params"])}
+        except Exception as e:  # reported to the host as a failed request
+            response = {"id": request["id"], "error": {"message": str(e)}}
+        sys.stdout.write(json.dumps(response) + "\n")
+        sys.stdout.flush()
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,110 @@
+//! Example Ambara plugin written with `ambara-plugin-sdk`.
+//!
+//! Exports two filters: an image inverter and a numeric gain whose
+//! parameters are declared with `#[derive(FilterParams)]`. The crate doubles
+//! as the SDK's end-to-end test: `tests/load.rs` loads the built `cdylib`
+//! through the host loader.
+
+use ambara_plugin_sdk::prelude::*;
+
+/// Inverts the RGB channels of an image, keeping alpha.
+#[derive(Default)]
+pub struct Invert;
+
+impl PluginFilter for Invert {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("sdk_example.invert", "Invert (SDK)")
+            .category(Category::Color)
+            .description("Invert image colors")
+            .input(PortDefinition::input("image", PortType::Image))
+            .output(PortDefinition::output("image", PortType::Image))
+            .build()
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let input = ctx.get_input_image("image")?;
+        let Some(image) = input.get_image() else {
+            return Err(ExecutionError::ImageProcessing(
+                "input image is not loaded".to_string(),
+            ));
+        };
+        let mut inverted = image.clone();
+        inverted.invert();
+        let output = input.with_image(inverted);
+        ctx.set_output_image("image", output)
+    }
+}
+
+/// Parameters of [`Gain`].
+#[derive(FilterParams)]
+pub struct GainParams {
+    /// Multiplier applied to the input.
+    #[param(default = 1.0, min = 0.0, max = 10.0, description = "Multiplier")]
+    pub gain: f64,
+    /// Constant added after the multiplication.
+    #[param(default = 0, description = "Offset added after scaling")]
+    pub offset: i64,
+}
+
+/// Computes `value * gain + offset`.
+#[derive(Default)]
+pub struct Gain;
+
+impl PluginFilter for Gain {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("sdk_example.gain", "Gain (SDK)")
+            .category(Category::Math)
+            .input(PortDefinition::input("value", PortType::Float))
+            .output(PortDefinition::output("value", PortType::Float))
+            .parameters(GainParams::definitions())
+            .build()
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let params = GainParams::from_context(ctx)?;
+        let value = ctx.get_input("value")?.as_float().unwrap_or_default();
+        ctx.set_output(
+            "value",
+            Value::Float(value * params.gain + params.offset as f64),
+        )
+    }
+}
+
+/// The plugin entry point.
+#[derive(Default, AmbaraPlugin)]
+#[plugin(filters(Invert, Gain), init = Self::configure)]
+pub struct SdkExamplePlugin {
+    configured: bool,
+}
+
+impl SdkExamplePlugin {
+    fn configure(&mut self, config: &ambara_plugin_sdk::serde_json::Value) -> Result<(), String> {
+        if config.get("reject").is_some() {
+            return Err("configuration rejected".to_string());
+        }
+        self.configured = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_params_derive_definitions() {
+        let defs = GainParams::definitions();
+        assert_eq!(defs.len(), 2);
+        assert_eq!(defs[0].name, "gain");
+        assert_eq!(defs[0].default_value, Value::Float(1.0));
+        assert!(defs[0].validate(&Value::Float(11.0)).is_err());
+        assert_eq!(defs[1].param_type, PortType::Integer);
+    }
+
+    #[test]
+    fn plugin_lists_both_filters() {
+        let plugin = SdkExamplePlugin::default();
+        let ids: Vec<String> = plugin.filters().iter().map(|f| f.metadata().id).collect();
+        assert_eq!(ids, ["sdk_example.invert", "sdk_example.gain"]);
+    }
+}
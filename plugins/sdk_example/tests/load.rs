@@ -0,0 +1,130 @@
+//! Loads the built plugin through the host loader.
+
+use ambara::core::types::{ImageValue, Value};
+use ambara::core::error::PluginError;
+use ambara_plugin_sdk::testing::PluginHarness;
+use image::{DynamicImage, Rgba, RgbaImage};
+
+const MANIFEST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/ambara-plugin.toml");
+
+fn harness() -> PluginHarness {
+    PluginHarness::load(PluginHarness::cdylib_path("sdk_example"), MANIFEST)
+        .expect("sdk_example should load through LoadedPlugin")
+}
+
+#[test]
+fn exports_filters_and_metadata() {
+    let harness = harness();
+    assert_eq!(
+        harness.filter_ids(),
+        ["sdk_example.invert", "sdk_example.gain"]
+    );
+    let meta = harness.metadata("sdk_example.gain").unwrap();
+    assert_eq!(meta.parameters.len(), 2);
+}
+
+#[test]
+fn executes_with_default_and_explicit_parameters() {
+    let mut harness = harness();
+    let out = harness
+        .execute("sdk_example.gain", &[("value", Value::Float(2.0))], &[])
+        .unwrap();
+    assert_eq!(out["value"], Value::Float(2.0));
+
+    let out = harness
+        .execute(
+            "sdk_example.gain",
+            &[("value", Value::Float(2.0))],
+            &[("gain", Value::Float(3.0)), ("offset", Value::Integer(1))],
+        )
+        .unwrap();
+    assert_eq!(out["value"], Value::Float(7.0));
+}
+
+#[test]
+fn images_cross_the_abi() {
+    let mut harness = harness();
+    let img = RgbaImage::from_pixel(2, 2, Rgba([10, 20, 30, 255]));
+    let input = Value::Image(ImageValue::new(DynamicImage::ImageRgba8(img)));
+
+    let out = harness
+        .execute("sdk_example.invert", &[("image", input)], &[])
+        .unwrap();
+    let image = out["image"].as_image().unwrap().get_image().unwrap().to_rgba8();
+    assert_eq!(image.get_pixel(1, 1), &Rgba([245, 235, 225, 255]));
+}
+
+#[test]
+fn validation_reports_constraint_violations() {
+    let harness = harness();
+    let errors = harness.validate("sdk_example.gain", &[], &[("gain", Value::Float(50.0))]);
+    assert_eq!(errors.len(), 1, "{errors:?}");
+}
+
+#[test]
+fn failing_init_is_reported() {
+    let result = PluginHarness::load_with_config(
+        PluginHarness::cdylib_path("sdk_example"),
+        MANIFEST,
+        &ambara_plugin_sdk::serde_json::json!({ "reject": true }),
+    );
+    assert!(matches!(result, Err(PluginError::PluginInitFailed { .. })));
+}
+
+#[test]
+fn reports_healthy() {
+    let mut harness = harness();
+    assert!(harness.health_check().healthy);
+}
+
+#[test]
+fn quarantined_plugin_fails_fast_and_warns() {
+    use ambara::core::context::ExecutionContext;
+    use ambara::filters::registry::FilterRegistry;
+    use ambara::graph::structure::{GraphNode, ProcessingGraph};
+    use ambara::plugins::registry::{PluginRegistry, PluginSystemConfig};
+    use ambara::validation::stages::{CustomValidation, ValidationStage};
+
+    // The registry expects the manifest next to the library.
+    let dir = tempfile::tempdir().unwrap();
+    let source = PluginHarness::cdylib_path("sdk_example");
+    let library = dir.path().join(source.file_name().unwrap());
+    std::fs::copy(&source, &library).unwrap();
+    std::fs::copy(MANIFEST, dir.path().join("ambara-plugin.toml")).unwrap();
+
+    let mut plugins = PluginRegistry::new(dir.path(), PluginSystemConfig::default());
+    let plugin_id = plugins.load_plugin(&library).unwrap();
+    let mut filters = FilterRegistry::new();
+    plugins
+        .register_plugin_in_filter_registry(&plugin_id, &mut filters)
+        .unwrap();
+
+    assert!(plugins.health_check_all()[0].is_healthy());
+    let history = plugins.health_history(&plugin_id).unwrap();
+    assert_eq!(history.total_checks, 1);
+    assert!(history.last_latency_ms.is_some());
+
+    let mut graph = ProcessingGraph::new();
+    let node = GraphNode::new(filters.create("sdk_example.gain").unwrap());
+    assert!(node.filter.unavailable_reason().is_none());
+    let node_id = graph.add_node(node);
+
+    plugins.quarantine_plugin(&plugin_id, "operator request").unwrap();
+    assert_eq!(plugins.quarantined_plugins(), std::slice::from_ref(&plugin_id));
+
+    let warnings = CustomValidation.validate(&graph).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].message.contains("quarantined"), "{warnings:?}");
+
+    let filter = &graph.get_node(node_id).unwrap().filter;
+    let mut ctx = ExecutionContext::new(node_id);
+    let err = filter.execute(&mut ctx).unwrap_err();
+    assert!(err.to_string().contains("operator request"), "{err}");
+
+    // Passing checks do not lift a manual quarantine.
+    plugins.health_check_all();
+    plugins.health_check_all();
+    assert!(plugins.health_history(&plugin_id).unwrap().quarantine.is_some());
+    assert!(plugins.release_quarantine(&plugin_id).unwrap());
+    assert!(CustomValidation.validate(&graph).unwrap().is_empty());
+}
@@ -168,6 +168,8 @@ mod tests {
             height,
             format: ImageFormat::Png,
             has_alpha: true,
+            grayscale: false,
+            bit_depth: 8,
         };
         ImageValue::from_metadata(metadata, PathBuf::from("test.png"))
     }
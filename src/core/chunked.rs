@@ -477,17 +477,12 @@ impl FileImageSource {
             format!("Failed to open image '{}': {}", path.display(), e)
         ))?;
         
-        let metadata = ImageMetadata {
-            width: image.width(),
-            height: image.height(),
-            format: ImageFormat::from_path(&path),
-            has_alpha: matches!(
-                image,
-                DynamicImage::ImageRgba8(_) | DynamicImage::ImageRgba16(_) | 
-                DynamicImage::ImageRgba32F(_) | DynamicImage::ImageLumaA8(_) | 
-                DynamicImage::ImageLumaA16(_)
-            ),
-        };
+        let metadata = ImageMetadata::new(
+            image.width(),
+            image.height(),
+            ImageFormat::from_path(&path),
+            image.color(),
+        );
         
         Ok(Self {
             path,
@@ -541,17 +536,12 @@ pub struct MemoryImageSource {
 impl MemoryImageSource {
     /// Create from an existing DynamicImage.
     pub fn new(image: DynamicImage) -> Self {
-        let metadata = ImageMetadata {
-            width: image.width(),
-            height: image.height(),
-            format: ImageFormat::Unknown,
-            has_alpha: matches!(
-                image,
-                DynamicImage::ImageRgba8(_) | DynamicImage::ImageRgba16(_) | 
-                DynamicImage::ImageRgba32F(_) | DynamicImage::ImageLumaA8(_) | 
-                DynamicImage::ImageLumaA16(_)
-            ),
-        };
+        let metadata = ImageMetadata::new(
+            image.width(),
+            image.height(),
+            ImageFormat::Unknown,
+            image.color(),
+        );
         
         Self {
             image: Arc::new(image),
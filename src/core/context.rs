@@ -4,8 +4,10 @@
 //! node validation and execution. They encapsulate the data flow.
 
 use crate::core::error::{ExecutionError, NodeId, ValidationError};
-use crate::core::types::{Color, ImageValue, Value};
-use std::collections::HashMap;
+use crate::core::types::{Color, ImageMetadata, ImageValue, PortType, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /// Context provided during node validation.
 ///
@@ -17,6 +19,9 @@ pub struct ValidationContext {
     pub node_id: NodeId,
     /// Input values or metadata.
     inputs: HashMap<String, Value>,
+    /// Inputs whose value was inferred from upstream nodes rather than
+    /// made up from the port type.
+    inferred: HashSet<String>,
     /// Parameter values.
     parameters: HashMap<String, Value>,
 }
@@ -27,13 +32,24 @@ impl ValidationContext {
         Self {
             node_id,
             inputs: HashMap::new(),
+            inferred: HashSet::new(),
             parameters: HashMap::new(),
         }
     }
 
     /// Add an input value to the context.
     pub fn add_input(&mut self, name: impl Into<String>, value: Value) {
-        self.inputs.insert(name.into(), value);
+        let name = name.into();
+        self.inferred.remove(&name);
+        self.inputs.insert(name, value);
+    }
+
+    /// Add an input whose value was inferred from the upstream node (see
+    /// [`FilterNode::infer_outputs`](crate::core::node::FilterNode::infer_outputs)).
+    pub fn add_inferred_input(&mut self, name: impl Into<String>, value: Value) {
+        let name = name.into();
+        self.inferred.insert(name.clone());
+        self.inputs.insert(name, value);
     }
 
     /// Add a parameter value to the context.
@@ -78,6 +94,24 @@ impl ValidationContext {
         self.inputs.contains_key(name)
     }
 
+    /// Get an input only if its value was inferred, not a placeholder.
+    ///
+    /// Checks against input dimensions or lengths should use this, since
+    /// placeholders say nothing about the real data.
+    pub fn inferred_input(&self, name: &str) -> Option<&Value> {
+        self.inferred
+            .contains(name)
+            .then(|| self.inputs.get(name))
+            .flatten()
+    }
+
+    /// Get the inferred metadata of an image input.
+    pub fn inferred_image(&self, name: &str) -> Option<&ImageMetadata> {
+        self.inferred_input(name)
+            .and_then(Value::as_image)
+            .map(|image| &image.metadata)
+    }
+
     // ========================================================================
     // Parameter Getters
     // ========================================================================
@@ -147,6 +181,48 @@ impl ValidationContext {
     }
 }
 
+/// Receives a node's progress (0.0 to 1.0) and an optional status message.
+pub type ProgressSink = Arc<dyn Fn(f32, Option<&str>) + Send + Sync>;
+
+/// Connects an [`ExecutionContext`] to the run that owns it.
+///
+/// Progress reported by the node is forwarded to the sink, and cancelling
+/// the run is visible to the node through the shared flag. Both are
+/// optional; a context created on its own has neither.
+#[derive(Clone, Default)]
+pub struct ExecutionHooks {
+    progress: Option<ProgressSink>,
+    cancelled: Option<Arc<AtomicBool>>,
+}
+
+impl ExecutionHooks {
+    /// Create hooks that forward nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forward progress reports to `sink`.
+    pub fn with_progress(mut self, sink: ProgressSink) -> Self {
+        self.progress = Some(sink);
+        self
+    }
+
+    /// Observe cancellation through `flag`.
+    pub fn with_cancellation(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.cancelled = Some(flag);
+        self
+    }
+}
+
+impl std::fmt::Debug for ExecutionHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExecutionHooks")
+            .field("progress", &self.progress.as_ref().map(|_| "<sink>"))
+            .field("cancelled", &self.cancelled)
+            .finish()
+    }
+}
+
 /// Context provided during node execution.
 ///
 /// ExecutionContext contains actual data values and allows nodes to
@@ -157,6 +233,8 @@ pub struct ExecutionContext {
     pub node_id: NodeId,
     /// Input values.
     inputs: HashMap<String, Value>,
+    /// Types generic inputs were bound to.
+    input_types: HashMap<String, PortType>,
     /// Parameter values.
     parameters: HashMap<String, Value>,
     /// Output values set by the node.
@@ -171,6 +249,8 @@ pub struct ExecutionContext {
     auto_chunk: bool,
     /// Preferred tile size for chunked processing.
     tile_size: (u32, u32),
+    /// Progress and cancellation shared with the running graph.
+    hooks: ExecutionHooks,
 }
 
 impl ExecutionContext {
@@ -179,6 +259,7 @@ impl ExecutionContext {
         Self {
             node_id,
             inputs: HashMap::new(),
+            input_types: HashMap::new(),
             parameters: HashMap::new(),
             outputs: HashMap::new(),
             progress: 0.0,
@@ -186,6 +267,7 @@ impl ExecutionContext {
             memory_limit: 500 * 1024 * 1024, // 500MB default
             auto_chunk: true,
             tile_size: (512, 512),
+            hooks: ExecutionHooks::default(),
         }
     }
 
@@ -199,6 +281,7 @@ impl ExecutionContext {
         Self {
             node_id,
             inputs: HashMap::new(),
+            input_types: HashMap::new(),
             parameters: HashMap::new(),
             outputs: HashMap::new(),
             progress: 0.0,
@@ -206,9 +289,16 @@ impl ExecutionContext {
             memory_limit,
             auto_chunk,
             tile_size,
+            hooks: ExecutionHooks::default(),
         }
     }
 
+    /// Attach the progress and cancellation hooks of the running graph.
+    pub fn with_hooks(mut self, hooks: ExecutionHooks) -> Self {
+        self.hooks = hooks;
+        self
+    }
+
     /// Add an input value to the context.
     pub fn add_input(&mut self, name: impl Into<String>, value: Value) {
         self.inputs.insert(name.into(), value);
@@ -251,6 +341,25 @@ impl ExecutionContext {
         })
     }
 
+    /// Record the type a generic input's type variables were bound to.
+    pub fn add_input_type(&mut self, name: impl Into<String>, port_type: PortType) {
+        self.input_types.insert(name.into(), port_type);
+    }
+
+    /// Type of an input value.
+    ///
+    /// Generic inputs report the type their variables were bound to
+    /// ([`ProcessingGraph::resolved_input_type`](crate::graph::structure::ProcessingGraph::resolved_input_type)),
+    /// so an empty array is still an `Array<Image>` rather than `Array<Any>`.
+    /// Other inputs report [`Value::get_type`].
+    pub fn get_input_type(&self, name: &str) -> Option<PortType> {
+        let value = self.inputs.get(name)?;
+        match self.input_types.get(name) {
+            Some(bound) if is_concrete(bound) => Some(bound.clone()),
+            _ => Some(value.get_type()),
+        }
+    }
+
     /// Get an input as an image.
     pub fn get_input_image(&self, name: &str) -> Result<&ImageValue, ExecutionError> {
         self.get_input(name)?
@@ -450,6 +559,17 @@ impl ExecutionContext {
     /// Set the current progress (0.0 to 1.0).
     pub fn set_progress(&mut self, progress: f32) {
         self.progress = progress.clamp(0.0, 1.0);
+        if let Some(sink) = &self.hooks.progress {
+            sink(self.progress, None);
+        }
+    }
+
+    /// Set the current progress with a status message, e.g. "step 3/20".
+    pub fn report_progress(&mut self, progress: f32, message: &str) {
+        self.progress = progress.clamp(0.0, 1.0);
+        if let Some(sink) = &self.hooks.progress {
+            sink(self.progress, Some(message));
+        }
     }
 
     /// Get the current progress.
@@ -462,14 +582,20 @@ impl ExecutionContext {
         self.cancelled = true;
     }
 
-    /// Check if execution should be cancelled.
+    /// Check if execution should be cancelled, either through
+    /// [`cancel`](Self::cancel) or by the running graph.
     pub fn is_cancelled(&self) -> bool {
         self.cancelled
+            || self
+                .hooks
+                .cancelled
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed))
     }
 
     /// Check cancellation and return error if cancelled.
     pub fn check_cancelled(&self) -> Result<(), ExecutionError> {
-        if self.cancelled {
+        if self.is_cancelled() {
             Err(ExecutionError::Cancelled)
         } else {
             Ok(())
@@ -491,6 +617,15 @@ impl From<ValidationContext> for ExecutionContext {
     }
 }
 
+/// Whether a type names no variables and no `Any`.
+fn is_concrete(port_type: &PortType) -> bool {
+    match port_type {
+        PortType::Any | PortType::Var(_) => false,
+        PortType::Array(inner) | PortType::Map(inner) => is_concrete(inner),
+        _ => true,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -550,4 +685,29 @@ mod tests {
         assert!(ctx.is_cancelled());
         assert!(ctx.check_cancelled().is_err());
     }
+
+    #[test]
+    fn test_execution_context_hooks() {
+        let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&reports);
+        let flag = Arc::new(AtomicBool::new(false));
+        let hooks = ExecutionHooks::new()
+            .with_progress(Arc::new(move |progress, message: Option<&str>| {
+                sink.lock().unwrap().push((progress, message.map(str::to_string)));
+            }))
+            .with_cancellation(Arc::clone(&flag));
+        let mut ctx = ExecutionContext::new(NodeId::new()).with_hooks(hooks);
+
+        ctx.set_progress(0.25);
+        ctx.report_progress(2.0, "step 20/20");
+        assert_eq!(
+            *reports.lock().unwrap(),
+            vec![(0.25, None), (1.0, Some("step 20/20".to_string()))]
+        );
+
+        assert!(!ctx.is_cancelled());
+        flag.store(true, Ordering::Relaxed);
+        assert!(ctx.is_cancelled());
+        assert!(matches!(ctx.check_cancelled(), Err(ExecutionError::Cancelled)));
+    }
 }
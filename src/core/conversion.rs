@@ -0,0 +1,398 @@
+//! Implicit conversions between port types.
+//!
+//! A [`ConversionRegistry`] declares which values may flow into a port of
+//! another type and how they are converted. The
+//! [`FilterRegistry`](crate::filters::registry::FilterRegistry) owns one,
+//! which starts with the built-in conversions and accepts more through
+//! [`FilterRegistry::register_conversion`](crate::filters::registry::FilterRegistry::register_conversion).
+//! Each graph carries the registry it was built with
+//! ([`ProcessingGraph::conversions`](crate::graph::structure::ProcessingGraph::conversions)):
+//! connections are allowed between types it converts between, the execution
+//! engine converts values as it gathers a node's inputs, and type validation
+//! warns about conversions that lose information.
+//!
+//! Image inputs can also ask for a channel layout ([`ImageLayout`]) with
+//! [`PortDefinition::with_image_layout`]; images in another layout are
+//! converted the same way.
+
+use crate::core::port::PortDefinition;
+use crate::core::types::{ImageMetadata, ImageValue, PortType, Value};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+/// Whether a conversion keeps all information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Fidelity {
+    /// Every value converts exactly.
+    Lossless,
+    /// Information may be lost (rounding, dropped channels).
+    Lossy,
+}
+
+/// An implicit conversion from one port type to another.
+#[derive(Debug, Clone)]
+pub struct Conversion {
+    /// Type the conversion accepts.
+    pub from: PortType,
+    /// Type the conversion produces.
+    pub to: PortType,
+    /// Whether information may be lost.
+    pub fidelity: Fidelity,
+    /// What the conversion does, shown in validation warnings.
+    pub description: String,
+    convert: fn(Value) -> Value,
+}
+
+impl Conversion {
+    /// Declare a conversion. `convert` is only called with values of type
+    /// `from`.
+    pub fn new(
+        from: PortType,
+        to: PortType,
+        fidelity: Fidelity,
+        description: impl Into<String>,
+        convert: fn(Value) -> Value,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            fidelity,
+            description: description.into(),
+            convert,
+        }
+    }
+
+    /// Convert a value of type `from`.
+    pub fn apply(&self, value: Value) -> Value {
+        (self.convert)(value)
+    }
+
+    /// Whether the conversion may lose information.
+    pub fn is_lossy(&self) -> bool {
+        self.fidelity == Fidelity::Lossy
+    }
+}
+
+/// Declared implicit conversions.
+#[derive(Debug, Clone, Default)]
+pub struct ConversionRegistry {
+    conversions: Vec<Conversion>,
+}
+
+impl ConversionRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry with the built-in conversions.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(Conversion::new(
+            PortType::Integer,
+            PortType::Float,
+            Fidelity::Lossless,
+            "Integer to Float",
+            |value| match value {
+                Value::Integer(i) => Value::Float(i as f64),
+                other => other,
+            },
+        ));
+        registry.register(Conversion::new(
+            PortType::Float,
+            PortType::Integer,
+            Fidelity::Lossy,
+            "Float to Integer rounds to the nearest integer",
+            |value| match value {
+                Value::Float(f) => Value::Integer(f.round() as i64),
+                other => other,
+            },
+        ));
+        registry.register(Conversion::new(
+            PortType::Boolean,
+            PortType::Integer,
+            Fidelity::Lossless,
+            "Boolean to Integer (false = 0, true = 1)",
+            |value| match value {
+                Value::Boolean(b) => Value::Integer(i64::from(b)),
+                other => other,
+            },
+        ));
+        registry.register(Conversion::new(
+            PortType::Color,
+            PortType::Vector3,
+            Fidelity::Lossy,
+            "Color to Vector3 keeps RGB in 0-1 and drops alpha",
+            |value| match value {
+                Value::Color(c) => Value::Vector3(
+                    f64::from(c.r) / 255.0,
+                    f64::from(c.g) / 255.0,
+                    f64::from(c.b) / 255.0,
+                ),
+                other => other,
+            },
+        ));
+        registry.register(Conversion::new(
+            PortType::Image,
+            PortType::Array(Box::new(PortType::Image)),
+            Fidelity::Lossless,
+            "Image to a one-element Array<Image>",
+            |value| match value {
+                Value::Image(_) => Value::Array(vec![value]),
+                other => other,
+            },
+        ));
+        registry
+    }
+
+    /// The built-in conversions, shared by graphs that were not given a
+    /// registry.
+    pub fn builtin() -> Arc<Self> {
+        static BUILTIN: OnceLock<Arc<ConversionRegistry>> = OnceLock::new();
+        Arc::clone(BUILTIN.get_or_init(|| Arc::new(Self::with_builtins())))
+    }
+
+    /// Register a conversion, replacing any between the same types.
+    pub fn register(&mut self, conversion: Conversion) {
+        self.conversions
+            .retain(|c| c.from != conversion.from || c.to != conversion.to);
+        self.conversions.push(conversion);
+    }
+
+    /// The conversion declared from `from` to `to`.
+    pub fn find(&self, from: &PortType, to: &PortType) -> Option<&Conversion> {
+        self.conversions
+            .iter()
+            .find(|c| c.from == *from && c.to == *to)
+    }
+
+    /// All declared conversions.
+    pub fn conversions(&self) -> impl Iterator<Item = &Conversion> {
+        self.conversions.iter()
+    }
+
+    /// How values of type `from` reach a port of type `to`, or `None` if
+    /// they cannot. Equal types, `Any` and type variables are lossless;
+    /// arrays and maps convert element-wise.
+    pub fn fidelity(&self, from: &PortType, to: &PortType) -> Option<Fidelity> {
+        match (from, to) {
+            (PortType::Any, _) | (_, PortType::Any) => Some(Fidelity::Lossless),
+            (PortType::Var(_), _) | (_, PortType::Var(_)) => Some(Fidelity::Lossless),
+            (a, b) if a == b => Some(Fidelity::Lossless),
+            (a, b) => match self.find(a, b) {
+                Some(conversion) => Some(conversion.fidelity),
+                None => match (a, b) {
+                    (PortType::Array(a), PortType::Array(b))
+                    | (PortType::Map(a), PortType::Map(b)) => self.fidelity(a, b),
+                    _ => None,
+                },
+            },
+        }
+    }
+
+    /// Convert `value` for a port of type `to`. Values that already have the
+    /// type, or that no conversion applies to, are returned unchanged.
+    pub fn convert(&self, value: Value, to: &PortType) -> Value {
+        match (to, value) {
+            (PortType::Any | PortType::Var(_), value) => value,
+            (PortType::Array(inner), Value::Array(items)) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.convert(item, inner))
+                    .collect(),
+            ),
+            (PortType::Map(inner), Value::Map(entries)) => Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, item)| (key, self.convert(item, inner)))
+                    .collect(),
+            ),
+            (to, value) => match self.find(&value.get_type(), to) {
+                Some(conversion) => conversion.apply(value),
+                None => value,
+            },
+        }
+    }
+
+    /// Convert `value` for an input port: to the port's type, then to the
+    /// image layout it asks for.
+    pub fn convert_input(&self, value: Value, port: &PortDefinition) -> Value {
+        let value = self.convert(value, &port.port_type);
+        match port.image_layout {
+            Some(layout) => layout.convert(value),
+            None => value,
+        }
+    }
+}
+
+/// Channel layout an image input asks for with
+/// [`PortDefinition::with_image_layout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageLayout {
+    /// A single luminance channel.
+    Grayscale,
+    /// Red, green, blue and alpha channels.
+    Rgba,
+}
+
+impl ImageLayout {
+    /// How an image described by `metadata` converts to this layout, or
+    /// `None` if it is already in it. Dropping colour or alpha is lossy;
+    /// adding channels is not.
+    pub fn conversion(self, metadata: &ImageMetadata) -> Option<Fidelity> {
+        match self {
+            ImageLayout::Grayscale if metadata.grayscale && !metadata.has_alpha => None,
+            ImageLayout::Grayscale => Some(Fidelity::Lossy),
+            ImageLayout::Rgba if !metadata.grayscale && metadata.has_alpha => None,
+            ImageLayout::Rgba => Some(Fidelity::Lossless),
+        }
+    }
+
+    /// Convert an image (or each image of an array) to this layout, keeping
+    /// its bit depth. Images whose pixels are not loaded are left as they
+    /// are.
+    pub fn convert(self, value: Value) -> Value {
+        match value {
+            Value::Image(image) => Value::Image(self.convert_image(image)),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().map(|item| self.convert(item)).collect())
+            }
+            other => other,
+        }
+    }
+
+    fn convert_image(self, image: ImageValue) -> ImageValue {
+        if self.conversion(&image.metadata).is_none() {
+            return image;
+        }
+        let Some(pixels) = image.get_image() else {
+            return image;
+        };
+        let converted = match (self, image.metadata.bit_depth) {
+            (ImageLayout::Grayscale, 8) => DynamicImage::ImageLuma8(pixels.to_luma8()),
+            (ImageLayout::Grayscale, _) => DynamicImage::ImageLuma16(pixels.to_luma16()),
+            (ImageLayout::Rgba, 8) => DynamicImage::ImageRgba8(pixels.to_rgba8()),
+            (ImageLayout::Rgba, 16) => DynamicImage::ImageRgba16(pixels.to_rgba16()),
+            (ImageLayout::Rgba, _) => DynamicImage::ImageRgba32F(pixels.to_rgba32f()),
+        };
+        image.with_image(converted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::types::Color;
+
+    #[test]
+    fn builtin_conversions() {
+        let registry = ConversionRegistry::builtin();
+        assert_eq!(
+            registry.convert(Value::Integer(3), &PortType::Float),
+            Value::Float(3.0)
+        );
+        assert_eq!(
+            registry.convert(Value::Float(2.6), &PortType::Integer),
+            Value::Integer(3)
+        );
+        assert_eq!(
+            registry.convert(Value::Boolean(true), &PortType::Integer),
+            Value::Integer(1)
+        );
+        assert_eq!(
+            registry.convert(Value::Color(Color::new(255, 0, 51, 10)), &PortType::Vector3),
+            Value::Vector3(1.0, 0.0, 0.2)
+        );
+        assert_eq!(
+            registry.convert(Value::Array(vec![Value::Integer(1)]), &PortType::Array(Box::new(PortType::Float))),
+            Value::Array(vec![Value::Float(1.0)])
+        );
+
+        let image = Value::Image(ImageValue::new(DynamicImage::new_rgb8(2, 2)));
+        let images = PortType::Array(Box::new(PortType::Image));
+        assert_eq!(registry.convert(image.clone(), &images), Value::Array(vec![image]));
+
+        // Values no conversion applies to are left for the node to reject
+        assert_eq!(
+            registry.convert(Value::String("a".into()), &PortType::Integer),
+            Value::String("a".into())
+        );
+    }
+
+    #[test]
+    fn fidelity_of_type_pairs() {
+        let registry = ConversionRegistry::builtin();
+        let array_of = |inner: PortType| PortType::Array(Box::new(inner));
+        assert_eq!(registry.fidelity(&PortType::Integer, &PortType::Float), Some(Fidelity::Lossless));
+        assert_eq!(registry.fidelity(&PortType::Float, &PortType::Integer), Some(Fidelity::Lossy));
+        assert_eq!(registry.fidelity(&PortType::Color, &PortType::Vector3), Some(Fidelity::Lossy));
+        assert_eq!(
+            registry.fidelity(&array_of(PortType::Float), &array_of(PortType::Integer)),
+            Some(Fidelity::Lossy)
+        );
+        assert_eq!(registry.fidelity(&PortType::Image, &array_of(PortType::Image)), Some(Fidelity::Lossless));
+        assert_eq!(registry.fidelity(&PortType::Integer, &PortType::Boolean), None);
+        assert_eq!(registry.fidelity(&PortType::Vector3, &PortType::Color), None);
+    }
+
+    #[test]
+    fn registered_conversions_replace_builtins() {
+        let mut registry = ConversionRegistry::with_builtins();
+        registry.register(Conversion::new(
+            PortType::Float,
+            PortType::Integer,
+            Fidelity::Lossy,
+            "Float to Integer truncates",
+            |value| match value {
+                Value::Float(f) => Value::Integer(f as i64),
+                other => other,
+            },
+        ));
+        assert_eq!(
+            registry.convert(Value::Float(2.6), &PortType::Integer),
+            Value::Integer(2)
+        );
+        assert_eq!(
+            registry
+                .conversions()
+                .filter(|c| c.from == PortType::Float && c.to == PortType::Integer)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn image_layouts() {
+        let gray = PortDefinition::input("mask", PortType::Image)
+            .with_image_layout(ImageLayout::Grayscale);
+        let rgba = PortDefinition::input("overlay", PortType::Image)
+            .with_image_layout(ImageLayout::Rgba);
+        let registry = ConversionRegistry::builtin();
+
+        let rgb = ImageValue::new(DynamicImage::new_rgb16(4, 2));
+        assert_eq!(ImageLayout::Grayscale.conversion(&rgb.metadata), Some(Fidelity::Lossy));
+        assert_eq!(ImageLayout::Rgba.conversion(&rgb.metadata), Some(Fidelity::Lossless));
+
+        let converted = registry.convert_input(Value::Image(rgb.clone()), &gray);
+        let pixels = converted.as_image().unwrap().get_image().unwrap();
+        assert_eq!(pixels.color(), image::ColorType::L16);
+        assert!(converted.as_image().unwrap().metadata.grayscale);
+
+        let converted = registry.convert_input(Value::Image(rgb), &rgba);
+        let pixels = converted.as_image().unwrap().get_image().unwrap();
+        assert_eq!(pixels.color(), image::ColorType::Rgba16);
+        assert_eq!((pixels.width(), pixels.height()), (4, 2));
+
+        let luma = ImageValue::new(DynamicImage::new_luma8(1, 1));
+        assert_eq!(ImageLayout::Grayscale.conversion(&luma.metadata), None);
+
+        // Requiring alpha is a check, not a conversion
+        let checked = PortDefinition::input("overlay", PortType::Image)
+            .with_constraint(crate::core::port::Constraint::ImageRequiresAlpha);
+        let opaque = Value::Image(ImageValue::new(DynamicImage::new_rgb8(1, 1)));
+        assert_eq!(registry.convert_input(opaque.clone(), &checked), opaque);
+    }
+}
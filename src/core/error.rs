@@ -106,6 +106,12 @@ pub enum GraphError {
     #[error("Port '{port}' not found on node {node_id}")]
     PortNotFound { node_id: NodeId, port: String },
 
+    #[error("Parameter '{parameter}' not found on node {node_id}")]
+    ParameterNotFound { node_id: NodeId, parameter: String },
+
+    #[error("Parameter '{parameter}' on node {node_id} is not promoted to an input")]
+    ParameterNotPromoted { node_id: NodeId, parameter: String },
+
     #[error("Cycle detected in graph involving nodes: {nodes:?}")]
     CycleDetected { nodes: Vec<NodeId> },
 
@@ -162,6 +168,13 @@ pub enum ValidationError {
     #[error("Unreachable node: {0}")]
     UnreachableNode(NodeId),
 
+    #[error("Lint '{rule}': {message}")]
+    Lint {
+        rule: String,
+        node_ids: Vec<NodeId>,
+        message: String,
+    },
+
     #[error("{0}")]
     Other(String),
 }
@@ -178,6 +191,13 @@ pub enum ExecutionError {
     #[error("Missing parameter '{parameter}' for node {node_id}")]
     MissingParameter { node_id: NodeId, parameter: String },
 
+    #[error("Invalid value for parameter '{parameter}' on node {node_id}: {error}")]
+    InvalidParameter {
+        node_id: NodeId,
+        parameter: String,
+        error: String,
+    },
+
     #[error("Output '{port}' was not set by node {node_id}")]
     OutputNotSet { node_id: NodeId, port: String },
 
@@ -281,6 +301,72 @@ pub enum PluginError {
     /// I/O error when scanning or reading plugin files.
     #[error("I/O error while loading plugin: {message}")]
     Io { message: String },
+
+    /// The signature policy requires a signature but `ambara-plugin.sig` is absent.
+    #[error("Plugin library at {path} is not signed")]
+    SignatureMissing { path: PathBuf },
+
+    /// The signature file is malformed or the Ed25519 signature does not verify.
+    #[error("Invalid signature for plugin library at {path}: {reason}")]
+    SignatureInvalid { path: PathBuf, reason: String },
+
+    /// A signed file was modified after signing.
+    #[error("Integrity check failed for plugin library at {path}: {file} does not match its signed hash")]
+    IntegrityMismatch { path: PathBuf, file: String },
+
+    /// The signature is valid but the signing key is not in the trust store.
+    #[error("Plugin library at {path} is signed by untrusted key {public_key}")]
+    UntrustedPublisher { path: PathBuf, public_key: String },
+
+    /// Two plugin directories declare the same plugin ID.
+    #[error("Plugin '{plugin_id}' at {path} duplicates an earlier plugin with the same ID")]
+    DuplicatePlugin { plugin_id: String, path: PathBuf },
+
+    /// A plugin requires another plugin that is not available.
+    #[error("Plugin '{plugin_id}' requires plugin '{dependency}' {requirement}, which is not available")]
+    MissingDependency {
+        plugin_id: String,
+        dependency: String,
+        requirement: String,
+    },
+
+    /// A required plugin is available, but not in a matching version.
+    #[error(
+        "Plugin '{plugin_id}' requires plugin '{dependency}' {requirement}, found {found}"
+    )]
+    DependencyVersionMismatch {
+        plugin_id: String,
+        dependency: String,
+        requirement: String,
+        found: String,
+    },
+
+    /// Plugins depend on each other in a cycle.
+    #[error("Plugin dependency cycle among: {plugins:?}")]
+    DependencyCycle { plugins: Vec<String> },
+
+    /// A plugin provides a filter ID that is already owned elsewhere.
+    #[error("Plugin '{plugin_id}' provides filter '{filter_id}', which is already provided by '{owner}'")]
+    FilterIdConflict {
+        plugin_id: String,
+        filter_id: String,
+        owner: String,
+    },
+
+    /// A configuration value does not match the plugin's config schema.
+    #[error("Invalid configuration for plugin '{plugin_id}', key '{key}': {reason}")]
+    InvalidConfig {
+        plugin_id: String,
+        key: String,
+        reason: String,
+    },
+
+    /// A plugin cannot be unloaded while other loaded plugins depend on it.
+    #[error("Plugin '{plugin_id}' is required by loaded plugins: {dependents:?}")]
+    PluginRequired {
+        plugin_id: String,
+        dependents: Vec<String>,
+    },
 }
 
 /// Errors during batch processing.
@@ -348,6 +434,7 @@ impl ValidationError {
             | ValidationError::CustomValidation { node_id, .. }
             | ValidationError::ResourceNotFound { node_id, .. }
             | ValidationError::UnreachableNode(node_id) => vec![*node_id],
+            ValidationError::Lint { node_ids, .. } => node_ids.clone(),
             _ => vec![],
         }
     }
@@ -360,6 +447,7 @@ impl ExecutionError {
             ExecutionError::NodeExecution { node_id, .. }
             | ExecutionError::MissingInput { node_id, .. }
             | ExecutionError::MissingParameter { node_id, .. }
+            | ExecutionError::InvalidParameter { node_id, .. }
             | ExecutionError::OutputNotSet { node_id, .. }
             | ExecutionError::ScriptError { node_id, .. } => Some(*node_id),
             _ => None,
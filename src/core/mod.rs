@@ -10,6 +10,7 @@
 //! - Batch processing support
 //! - GPU acceleration infrastructure
 //! - Chunked/tiled processing for large images
+//! - Implicit conversions between port types
 
 pub mod types;
 pub mod port;
@@ -19,15 +20,17 @@ pub mod node;
 pub mod batch;
 pub mod gpu;
 pub mod chunked;
+pub mod conversion;
 
 // Re-export commonly used types
 pub use types::{Value, PortType, ImageValue, ImageFormat, Color, ImageMetadata};
 pub use port::{PortDefinition, PortDirection, Constraint};
 pub use error::{AmbaraError, GraphError, ValidationError, ExecutionError};
-pub use context::{ValidationContext, ExecutionContext};
+pub use context::{ValidationContext, ExecutionContext, ExecutionHooks};
 pub use node::{FilterNode, NodeMetadata, Category};
 pub use batch::{BatchContext, BatchMode, BatchSize, BatchAware};
 pub use gpu::{GpuDevice, GpuAccelerated, GpuBackend, GpuPool};
+pub use conversion::{Conversion, ConversionRegistry, Fidelity, ImageLayout};
 pub use chunked::{
     ProcessingConfig, SpatialExtent, TileRegion, TileIterator,
     MemoryTracker, ChunkedImageSource, ChunkedImageSink,
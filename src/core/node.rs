@@ -5,10 +5,11 @@
 //! and execution (processing).
 
 use crate::core::context::{ExecutionContext, ValidationContext};
-use crate::core::error::{ExecutionError, ValidationError};
+use crate::core::error::{ExecutionError, ValidationError, ValidationWarning};
 use crate::core::port::{ParameterDefinition, PortDefinition};
-use crate::core::types::Color;
+use crate::core::types::{Color, Value};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Category for organizing filters in the UI.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -254,6 +255,12 @@ impl NodeMetadataBuilder {
         self
     }
 
+    /// Add multiple parameters.
+    pub fn parameters(mut self, params: impl IntoIterator<Item = ParameterDefinition>) -> Self {
+        self.parameters.extend(params);
+        self
+    }
+
     /// Add a tag.
     pub fn tag(mut self, tag: impl Into<String>) -> Self {
         self.tags.push(tag.into());
@@ -381,6 +388,23 @@ pub trait FilterNode: Send + Sync {
     /// the actual pixel data. This allows validation without loading images.
     fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError>;
 
+    /// Describe the outputs from the inputs and parameters, without
+    /// processing anything.
+    ///
+    /// Called during validation in topological order. Inputs whose upstream
+    /// node described them are available through
+    /// [`ValidationContext::inferred_input`]; the others hold placeholders.
+    /// Images are returned as [`ImageValue::from_metadata`] and arrays with
+    /// one entry per element. Outputs left out are unknown, and downstream
+    /// nodes see placeholders for them.
+    ///
+    /// Default implementation infers nothing.
+    ///
+    /// [`ImageValue::from_metadata`]: crate::core::types::ImageValue::from_metadata
+    fn infer_outputs(&self, _ctx: &ValidationContext) -> HashMap<String, Value> {
+        HashMap::new()
+    }
+
     /// Execute the node.
     ///
     /// Called once per image (or once per batch for batch-aware nodes).
@@ -402,6 +426,44 @@ pub trait FilterNode: Send + Sync {
     /// Required for graph cloning and parallel execution.
     fn clone_box(&self) -> Box<dyn FilterNode>;
 
+    /// Check parameters against external resources, such as the models a
+    /// server offers.
+    ///
+    /// Called by resource validation with the node's parameters (no input
+    /// data). Errors fail validation; warnings report checks that could not
+    /// be made, e.g. because a server is unreachable.
+    ///
+    /// Default implementation checks nothing.
+    fn validate_resources(
+        &self,
+        _ctx: &ValidationContext,
+    ) -> Result<Vec<ValidationWarning>, ValidationError> {
+        Ok(Vec::new())
+    }
+
+    /// Why this node cannot run right now, if it is temporarily unavailable.
+    ///
+    /// Plugin filters return a reason while their plugin is quarantined after
+    /// failing health checks. Validation reports it as a warning and
+    /// execution of the node fails.
+    ///
+    /// Default implementation returns `None` (always available).
+    fn unavailable_reason(&self) -> Option<String> {
+        None
+    }
+
+    /// Steps that upgrade saved nodes of this filter from older versions.
+    ///
+    /// Filters that change their parameters or ports in a way older graphs
+    /// cannot load bump [`NodeMetadata::version`] and return a step from the
+    /// previous version here. The [`FilterRegistry`](crate::filters::registry::FilterRegistry)
+    /// collects them at registration and runs them when a graph is loaded.
+    ///
+    /// Default implementation returns no steps.
+    fn migrations(&self) -> Vec<crate::graph::serialization::migration::FilterMigration> {
+        Vec::new()
+    }
+
     /// Get the spatial extent required by this filter for chunked processing.
     ///
     /// Spatial filters (blur, sharpen, edge detection, convolution) need
@@ -450,8 +512,8 @@ impl FilterNode for PassthroughNode {
         NodeMetadata::builder("passthrough", "Passthrough")
             .category(Category::Utility)
             .description("Passes the input through unchanged")
-            .input(PortDefinition::input("input", PortType::Any))
-            .output(PortDefinition::output("output", PortType::Any))
+            .input(PortDefinition::input("input", PortType::Var("T".to_string())))
+            .output(PortDefinition::output("output", PortType::Var("T".to_string())))
             .build()
     }
 
@@ -460,6 +522,12 @@ impl FilterNode for PassthroughNode {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        ctx.inferred_input("input")
+            .map(|value| HashMap::from([("output".to_string(), value.clone())]))
+            .unwrap_or_default()
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.take_input("input")?;
         ctx.set_output("output", value)?;
@@ -3,6 +3,7 @@
 //! Ports define the interface of a node - what data it accepts and produces.
 //! Each port has a type and optional constraints for validation.
 
+use crate::core::conversion::ImageLayout;
 use crate::core::types::{ImageFormat, PortType, Value};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -37,6 +38,9 @@ pub struct PortDefinition {
     pub description: String,
     /// Constraints that values must satisfy
     pub constraints: Vec<Constraint>,
+    /// Channel layout images are converted to before reaching this input
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_layout: Option<ImageLayout>,
 }
 
 /// UI hints for parameter display.
@@ -216,6 +220,7 @@ impl PortDefinition {
             optional: false,
             description: String::new(),
             constraints: Vec::new(),
+            image_layout: None,
         }
     }
 
@@ -231,6 +236,7 @@ impl PortDefinition {
             optional: false,
             description: String::new(),
             constraints: Vec::new(),
+            image_layout: None,
         }
     }
 
@@ -282,6 +288,12 @@ impl PortDefinition {
         self
     }
 
+    /// Convert images to a channel layout before they reach this input.
+    pub fn with_image_layout(mut self, layout: ImageLayout) -> Self {
+        self.image_layout = Some(layout);
+        self
+    }
+
     /// Convert snake_case name to Title Case display name.
     fn name_to_display(name: &str) -> String {
         name.split('_')
@@ -6,9 +6,10 @@
 //! - Serialization: serde handles enums natively
 //! - Type safety: Exhaustive matching catches missing cases at compile time
 
-use image::DynamicImage;
+use crate::core::conversion::{ConversionRegistry, Fidelity};
+use image::{ColorType, DynamicImage};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -79,6 +80,30 @@ pub struct ImageMetadata {
     pub format: ImageFormat,
     /// Whether the image has an alpha channel
     pub has_alpha: bool,
+    /// Whether the image has a single luminance channel (plus alpha, if any)
+    #[serde(default)]
+    pub grayscale: bool,
+    /// Bits per channel (8, 16 or 32)
+    #[serde(default = "default_bit_depth")]
+    pub bit_depth: u8,
+}
+
+fn default_bit_depth() -> u8 {
+    8
+}
+
+impl ImageMetadata {
+    /// Describe an image from its dimensions and pixel layout.
+    pub fn new(width: u32, height: u32, format: ImageFormat, color: ColorType) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            has_alpha: color.has_alpha(),
+            grayscale: !color.has_color(),
+            bit_depth: (color.bits_per_pixel() / u16::from(color.channel_count())) as u8,
+        }
+    }
 }
 
 /// Reference to image data for serialization and lazy loading.
@@ -145,6 +170,26 @@ pub enum PortType {
     /// treats two `Custom` ports as compatible only when their inner strings
     /// are equal.
     Custom(String),
+    /// Type variable (e.g. `T`), for generic filters.
+    ///
+    /// All ports of one node that mention the same variable carry the same
+    /// concrete type, resolved from the node's connections (see
+    /// [`TypeBindings`]). `T` in and `T` out forwards the input type;
+    /// `Array<T>` in and `T` out takes the element type. An unresolved
+    /// variable connects like [`PortType::Any`].
+    Var(String),
+}
+
+/// Concrete types bound to one node's type variables.
+///
+/// Inputs bind variables from the type arriving on the connection
+/// ([`TypeBindings::bind_input`]); outputs from the input they feed
+/// ([`TypeBindings::bind_output`]). Type variables of the other node are
+/// treated as unknown. When two inputs bind a variable to `Integer` and
+/// `Float`, it widens to `Float`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypeBindings {
+    bound: BTreeMap<String, PortType>,
 }
 
 // ============================================================================
@@ -313,8 +358,8 @@ impl PortType {
     /// Check if a value matches this port type.
     pub fn matches(&self, value: &Value) -> bool {
         match (self, value) {
-            // Any accepts everything
-            (PortType::Any, _) => true,
+            // Any (and an unresolved type variable) accepts everything
+            (PortType::Any, _) | (PortType::Var(_), _) => true,
             // Direct matches
             (PortType::Image, Value::Image(_)) => true,
             (PortType::Integer, Value::Integer(_)) => true,
@@ -339,19 +384,13 @@ impl PortType {
 
     /// Check if this type is compatible with another (for connections).
     ///
-    /// This is used when validating if two ports can be connected.
+    /// This is used when validating if two ports can be connected. Besides
+    /// equal types, `Any` and unresolved type variables, values may flow
+    /// through any conversion declared in
+    /// [`ConversionRegistry::builtin`](crate::core::conversion::ConversionRegistry::builtin),
+    /// such as Integer to Float.
     pub fn compatible_with(&self, other: &PortType) -> bool {
-        match (self, other) {
-            // Any is compatible with everything
-            (PortType::Any, _) | (_, PortType::Any) => true,
-            // Integer output can connect to Float input (implicit conversion)
-            (PortType::Integer, PortType::Float) => true,
-            // Container covariance
-            (PortType::Array(a), PortType::Array(b)) => a.compatible_with(b),
-            (PortType::Map(a), PortType::Map(b)) => a.compatible_with(b),
-            // Direct match
-            (a, b) => a == b,
-        }
+        ConversionRegistry::builtin().fidelity(self, other).is_some()
     }
 
     /// Get a human-readable name for this type.
@@ -369,10 +408,134 @@ impl PortType {
             PortType::Map(inner) => format!("Map<String, {}>", inner.display_name()),
             PortType::Any => "Any".to_string(),
             PortType::Custom(name) => name.clone(),
+            PortType::Var(name) => name.clone(),
+        }
+    }
+
+    /// Whether this type mentions a type variable.
+    pub fn is_generic(&self) -> bool {
+        match self {
+            PortType::Var(_) => true,
+            PortType::Array(inner) | PortType::Map(inner) => inner.is_generic(),
+            _ => false,
+        }
+    }
+
+    /// Replace type variables with `Any`.
+    ///
+    /// Used for types coming from another node, whose variables mean
+    /// nothing to this one.
+    fn erase_vars(&self) -> PortType {
+        match self {
+            PortType::Var(_) => PortType::Any,
+            PortType::Array(inner) => PortType::Array(Box::new(inner.erase_vars())),
+            PortType::Map(inner) => PortType::Map(Box::new(inner.erase_vars())),
+            other => other.clone(),
+        }
+    }
+}
+
+impl TypeBindings {
+    /// Create empty bindings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The type bound to a variable.
+    pub fn get(&self, var: &str) -> Option<&PortType> {
+        self.bound.get(var)
+    }
+
+    /// Whether no variable is bound.
+    pub fn is_empty(&self) -> bool {
+        self.bound.is_empty()
+    }
+
+    /// Substitute bound variables in `port_type`. Unbound ones are kept.
+    pub fn resolve(&self, port_type: &PortType) -> PortType {
+        match port_type {
+            PortType::Var(name) => self
+                .bound
+                .get(name)
+                .cloned()
+                .unwrap_or_else(|| port_type.clone()),
+            PortType::Array(inner) => PortType::Array(Box::new(self.resolve(inner))),
+            PortType::Map(inner) => PortType::Map(Box::new(self.resolve(inner))),
+            other => other.clone(),
+        }
+    }
+
+    /// Bind the variables in `declared`, an input port type, so a value of
+    /// type `incoming` can flow into it through `conversions`.
+    ///
+    /// Returns `false` (leaving earlier bindings in place) if `incoming`
+    /// cannot connect to the port given what is already bound.
+    pub fn bind_input(
+        &mut self,
+        declared: &PortType,
+        incoming: &PortType,
+        conversions: &ConversionRegistry,
+    ) -> bool {
+        self.unify(declared, &incoming.erase_vars(), true, conversions)
+    }
+
+    /// Bind the variables in `declared`, an output port type, so its value
+    /// can flow into an input of type `target`.
+    pub fn bind_output(
+        &mut self,
+        declared: &PortType,
+        target: &PortType,
+        conversions: &ConversionRegistry,
+    ) -> bool {
+        self.unify(declared, &target.erase_vars(), false, conversions)
+    }
+
+    /// Unify our declared type with the other side's (variable-free) type.
+    /// `inbound` says whether values flow from `theirs` into `ours`.
+    fn unify(
+        &mut self,
+        ours: &PortType,
+        theirs: &PortType,
+        inbound: bool,
+        conversions: &ConversionRegistry,
+    ) -> bool {
+        let flows = |ours: &PortType, theirs: &PortType| {
+            if inbound {
+                conversions.fidelity(theirs, ours).is_some()
+            } else {
+                conversions.fidelity(ours, theirs).is_some()
+            }
+        };
+        match (ours, theirs) {
+            (_, PortType::Any) | (PortType::Any, _) => true,
+            (PortType::Var(name), _) => match self.bound.get(name) {
+                None => {
+                    self.bound.insert(name.clone(), theirs.clone());
+                    true
+                }
+                Some(bound) if inbound && widens(bound, theirs, conversions) => {
+                    // Widen, e.g. Integer to Float
+                    if *bound != *theirs {
+                        self.bound.insert(name.clone(), theirs.clone());
+                    }
+                    true
+                }
+                Some(bound) => flows(bound, theirs),
+            },
+            (PortType::Array(a), PortType::Array(b)) | (PortType::Map(a), PortType::Map(b)) => {
+                self.unify(a, b, inbound, conversions)
+            }
+            _ => flows(ours, theirs),
         }
     }
 }
 
+/// Whether a variable bound to `bound` may be rebound to `wider`: values of
+/// `bound` convert to it without loss.
+fn widens(bound: &PortType, wider: &PortType, conversions: &ConversionRegistry) -> bool {
+    conversions.fidelity(bound, wider) == Some(Fidelity::Lossless)
+}
+
 impl fmt::Display for PortType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.display_name())
@@ -386,24 +549,13 @@ impl fmt::Display for PortType {
 impl ImageValue {
     /// Create a new ImageValue from a DynamicImage.
     pub fn new(image: DynamicImage) -> Self {
-        let width = image.width();
-        let height = image.height();
-        let has_alpha = matches!(
-            image,
-            DynamicImage::ImageRgba8(_)
-                | DynamicImage::ImageRgba16(_)
-                | DynamicImage::ImageRgba32F(_)
-                | DynamicImage::ImageLumaA8(_)
-                | DynamicImage::ImageLumaA16(_)
-        );
-
         Self {
-            metadata: ImageMetadata {
-                width,
-                height,
-                format: ImageFormat::Unknown,
-                has_alpha,
-            },
+            metadata: ImageMetadata::new(
+                image.width(),
+                image.height(),
+                ImageFormat::Unknown,
+                image.color(),
+            ),
             data: Some(Arc::new(image)),
             data_ref: ImageDataRef::InMemory,
         }
@@ -413,24 +565,14 @@ impl ImageValue {
     pub fn from_path(path: impl Into<PathBuf>) -> Result<Self, image::ImageError> {
         let path = path.into();
         let image = image::open(&path)?;
-        let width = image.width();
-        let height = image.height();
-        let has_alpha = matches!(
-            image,
-            DynamicImage::ImageRgba8(_)
-                | DynamicImage::ImageRgba16(_)
-                | DynamicImage::ImageRgba32F(_)
-                | DynamicImage::ImageLumaA8(_)
-                | DynamicImage::ImageLumaA16(_)
-        );
 
         Ok(Self {
-            metadata: ImageMetadata {
-                width,
-                height,
-                format: ImageFormat::from_path(&path),
-                has_alpha,
-            },
+            metadata: ImageMetadata::new(
+                image.width(),
+                image.height(),
+                ImageFormat::from_path(&path),
+                image.color(),
+            ),
             data: Some(Arc::new(image)),
             data_ref: ImageDataRef::FilePath(path),
         })
@@ -482,24 +624,13 @@ impl ImageValue {
     ///
     /// Useful for filter operations that transform the image.
     pub fn with_image(&self, image: DynamicImage) -> Self {
-        let width = image.width();
-        let height = image.height();
-        let has_alpha = matches!(
-            image,
-            DynamicImage::ImageRgba8(_)
-                | DynamicImage::ImageRgba16(_)
-                | DynamicImage::ImageRgba32F(_)
-                | DynamicImage::ImageLumaA8(_)
-                | DynamicImage::ImageLumaA16(_)
-        );
-
         Self {
-            metadata: ImageMetadata {
-                width,
-                height,
-                format: self.metadata.format,
-                has_alpha,
-            },
+            metadata: ImageMetadata::new(
+                image.width(),
+                image.height(),
+                self.metadata.format,
+                image.color(),
+            ),
             data: Some(Arc::new(image)),
             data_ref: ImageDataRef::InMemory,
         }
@@ -514,6 +645,8 @@ impl Default for ImageValue {
                 height: 0,
                 format: ImageFormat::Unknown,
                 has_alpha: false,
+                grayscale: false,
+                bit_depth: 8,
             },
             data: None,
             data_ref: ImageDataRef::InMemory,
@@ -732,4 +865,29 @@ mod tests {
             PortType::Array(Box::new(PortType::Integer))
         );
     }
+
+    #[test]
+    fn test_type_bindings() {
+        let conversions = ConversionRegistry::with_builtins();
+        let t = || PortType::Var("T".to_string());
+        let array_of = |inner: PortType| PortType::Array(Box::new(inner));
+
+        let mut bindings = TypeBindings::new();
+        assert!(bindings.bind_input(&array_of(t()), &array_of(PortType::Image), &conversions));
+        assert_eq!(bindings.resolve(&t()), PortType::Image);
+        assert!(!bindings.bind_input(&t(), &PortType::String, &conversions));
+        assert!(!bindings.bind_input(&array_of(t()), &PortType::Image, &conversions));
+
+        // Integer widens to Float; Float does not narrow back
+        let mut bindings = TypeBindings::new();
+        assert!(bindings.bind_input(&t(), &PortType::Integer, &conversions));
+        assert!(bindings.bind_input(&t(), &PortType::Float, &conversions));
+        assert!(bindings.bind_input(&t(), &PortType::Integer, &conversions));
+        assert_eq!(bindings.get("T"), Some(&PortType::Float));
+
+        // Another node's variables are unknown, not ours
+        let mut bindings = TypeBindings::new();
+        assert!(bindings.bind_input(&t(), &array_of(PortType::Var("T".to_string())), &conversions));
+        assert_eq!(bindings.resolve(&t()), array_of(PortType::Any));
+    }
 }
@@ -2,13 +2,16 @@
 //!
 //! The engine executes validated filter graphs.
 
-use crate::core::context::ExecutionContext;
+use crate::core::context::{ExecutionContext, ExecutionHooks};
 use crate::core::error::{AmbaraError, ExecutionError, NodeId};
 use crate::core::types::Value;
 use crate::execution::cache::{CacheKey, ResultCache, SharedCache};
-use crate::execution::progress::{ProgressCallback, ProgressTracker, ProgressUpdate, SkipReason};
+use crate::execution::progress::{
+    CancellationToken, ProgressCallback, ProgressTracker, ProgressUpdate, SkipReason,
+};
 use crate::graph::structure::ProcessingGraph;
 use crate::graph::topology::TopologyAnalyzer;
+use crate::graph::typing::TypeResolver;
 use rayon::prelude::*;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -31,6 +34,8 @@ pub struct ExecutionOptions {
     pub skip_disabled: bool,
     /// Progress callback.
     pub progress_callback: Option<Arc<ProgressCallback>>,
+    /// Token for cancelling the run from another thread.
+    pub cancellation: Option<CancellationToken>,
     /// Memory limit in bytes for chunked processing.
     /// When processing large images, this limits peak memory usage.
     /// Default is 500 MB.
@@ -54,6 +59,7 @@ impl std::fmt::Debug for ExecutionOptions {
             .field("node_timeout", &self.node_timeout)
             .field("skip_disabled", &self.skip_disabled)
             .field("progress_callback", &self.progress_callback.as_ref().map(|_| "<callback>"))
+            .field("cancellation", &self.cancellation)
             .field("memory_limit", &self.memory_limit)
             .field("auto_chunk", &self.auto_chunk)
             .field("tile_size", &self.tile_size)
@@ -71,6 +77,7 @@ impl Default for ExecutionOptions {
             node_timeout: None,
             skip_disabled: true,
             progress_callback: None,
+            cancellation: None,
             memory_limit: crate::core::chunked::DEFAULT_MEMORY_LIMIT,
             auto_chunk: true,
             tile_size: (512, 512),
@@ -123,6 +130,15 @@ impl ExecutionOptions {
         self
     }
 
+    /// Cancel the run when `token` is cancelled.
+    ///
+    /// Cancellation is checked between nodes and by nodes that poll
+    /// [`ExecutionContext::is_cancelled`] while they run.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
     /// Set memory limit in bytes for chunked processing.
     pub fn with_memory_limit(mut self, limit: usize) -> Self {
         self.memory_limit = limit;
@@ -226,6 +242,9 @@ impl ExecutionEngine {
             let callback = callback.clone();
             tracker = tracker.with_callback(Box::new(move |update| callback(update)));
         }
+        if let Some(token) = &options.cancellation {
+            tracker = tracker.with_cancellation(token.clone());
+        }
         tracker.start();
 
         // Storage for outputs
@@ -337,6 +356,27 @@ impl ExecutionEngine {
         })
     }
 
+    /// Hooks forwarding a node's progress to the run's callback and the
+    /// run's cancellation to the node.
+    fn hooks_for(
+        node_id: NodeId,
+        options: &ExecutionOptions,
+        tracker: &ProgressTracker,
+    ) -> ExecutionHooks {
+        let mut hooks = ExecutionHooks::new().with_cancellation(tracker.cancellation().flag());
+        if let Some(callback) = &options.progress_callback {
+            let callback = Arc::clone(callback);
+            hooks = hooks.with_progress(Arc::new(move |progress, message: Option<&str>| {
+                callback(ProgressUpdate::NodeProgress {
+                    node_id,
+                    progress,
+                    message: message.map(str::to_string),
+                })
+            }));
+        }
+        hooks
+    }
+
     /// Execute a single node.
     #[allow(clippy::type_complexity)]
     fn execute_node(
@@ -383,21 +423,56 @@ impl ExecutionEngine {
             options.memory_limit,
             options.auto_chunk,
             options.tile_size,
-        );
-        
-        // Add inputs
+        )
+        .with_hooks(Self::hooks_for(node_id, options, tracker));
+
+        // Add inputs; promoted parameters are routed to the parameter map below
+        let metadata = node.filter.metadata();
         for (name, value) in &inputs {
+            if node.is_promoted(name) && metadata.get_input(name).is_none() {
+                continue;
+            }
             ctx.add_input(name.clone(), value.clone());
         }
 
-        // Add parameters (with defaults)
-        let metadata = node.filter.metadata();
+        // Generic inputs carry the types their variables were bound to
+        if metadata.inputs.iter().any(|input| input.port_type.is_generic()) {
+            let mut resolver = TypeResolver::new(graph);
+            for input in metadata.inputs.iter().filter(|input| input.port_type.is_generic()) {
+                if let Some(port_type) = resolver.input_type(node_id, &input.name) {
+                    ctx.add_input_type(input.name.clone(), port_type);
+                }
+            }
+        }
+
+        // Add parameters (connected value, then stored value, then default)
         for param_def in &metadata.parameters {
-            let value = node
-                .parameters
+            let connected = inputs
                 .get(&param_def.name)
-                .cloned()
-                .unwrap_or_else(|| param_def.default_value.clone());
+                .filter(|_| node.is_promoted(&param_def.name));
+            let value = match connected {
+                Some(value) => {
+                    let value = value.clone();
+                    // Stored values are checked before execution; connected
+                    // ones only exist now, so check them against the
+                    // parameter's type and constraints here.
+                    param_def.validate(&value).map_err(|error| {
+                        let error = ExecutionError::InvalidParameter {
+                            node_id,
+                            parameter: param_def.name.clone(),
+                            error,
+                        };
+                        tracker.report_error(Some(node_id), error.to_string());
+                        (node_id, error)
+                    })?;
+                    value
+                }
+                None => node
+                    .parameters
+                    .get(&param_def.name)
+                    .cloned()
+                    .unwrap_or_else(|| param_def.default_value.clone()),
+            };
             ctx.add_parameter(param_def.name.clone(), value);
         }
 
@@ -443,17 +518,24 @@ impl ExecutionEngine {
         upstream_outputs: &HashMap<NodeId, HashMap<String, Value>>,
     ) -> HashMap<String, Value> {
         let mut inputs = HashMap::new();
+        let node = graph.get_node(node_id).ok();
+        let conversions = graph.conversions();
 
         for conn in graph.connections_to(node_id) {
             if let Some(upstream_output) = upstream_outputs.get(&conn.from.node_id) {
                 if let Some(value) = upstream_output.get(&conn.from.port_name) {
-                    inputs.insert(conn.to.port_name.clone(), value.clone());
+                    // Apply implicit conversions to the input's type and layout
+                    let value = match node.and_then(|node| node.input_port(&conn.to.port_name)) {
+                        Some(port) => conversions.convert_input(value.clone(), &port),
+                        None => value.clone(),
+                    };
+                    inputs.insert(conn.to.port_name.clone(), value);
                 }
             }
         }
 
         // Add default values for unconnected optional inputs
-        if let Ok(node) = graph.get_node(node_id) {
+        if let Some(node) = node {
             let metadata = node.filter.metadata();
             for input in &metadata.inputs {
                 if !inputs.contains_key(&input.name) {
@@ -546,4 +628,345 @@ mod tests {
         assert!(!options.stop_on_error);
         assert_eq!(options.node_timeout, Some(Duration::from_secs(30)));
     }
+
+    /// Reports three steps, cancelling `token` after the second.
+    #[derive(Debug, Clone)]
+    struct SteppingNode {
+        token: CancellationToken,
+    }
+
+    impl crate::core::node::FilterNode for SteppingNode {
+        fn metadata(&self) -> crate::core::node::NodeMetadata {
+            crate::core::node::NodeMetadata::builder("stepping", "Stepping").build()
+        }
+
+        fn validate(
+            &self,
+            _ctx: &crate::core::context::ValidationContext,
+        ) -> Result<(), crate::core::error::ValidationError> {
+            Ok(())
+        }
+
+        fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+            for step in 1..=3 {
+                ctx.check_cancelled()?;
+                ctx.report_progress(step as f32 / 3.0, &format!("step {step}/3"));
+                if step == 2 {
+                    self.token.cancel();
+                }
+            }
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn crate::core::node::FilterNode> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn test_node_progress_and_cancellation_reach_the_node() {
+        let engine = ExecutionEngine::new();
+        let token = CancellationToken::new();
+        let mut graph = ProcessingGraph::new();
+        let node_id = graph.add_node(GraphNode::new(Box::new(SteppingNode {
+            token: token.clone(),
+        })));
+
+        let messages = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&messages);
+        let options = ExecutionOptions::new()
+            .with_cancellation(token)
+            .with_progress(move |update| {
+                if let ProgressUpdate::NodeProgress { node_id: id, message, .. } = update {
+                    assert_eq!(id, node_id);
+                    seen.lock().push(message.unwrap_or_default());
+                }
+            });
+
+        let result = engine.execute(&graph, Some(options));
+        assert!(matches!(
+            result,
+            Err(AmbaraError::Execution(ExecutionError::Cancelled))
+        ));
+        assert_eq!(*messages.lock(), vec!["step 1/3", "step 2/3"]);
+    }
+
+    mod promoted_parameters {
+        use super::*;
+        use crate::core::context::ValidationContext;
+        use crate::core::error::ValidationError;
+        use crate::core::node::{FilterNode, NodeMetadata};
+        use crate::core::port::{ParameterDefinition, PortDefinition};
+        use crate::core::types::PortType;
+
+        /// Emits a fixed value on `value`.
+        #[derive(Debug, Clone)]
+        struct Emit(Value);
+
+        impl FilterNode for Emit {
+            fn metadata(&self) -> NodeMetadata {
+                NodeMetadata::builder("emit", "Emit")
+                    .output(PortDefinition::output("value", self.0.get_type()))
+                    .build()
+            }
+
+            fn validate(&self, _ctx: &ValidationContext) -> Result<(), ValidationError> {
+                Ok(())
+            }
+
+            fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+                ctx.set_output("value", self.0.clone())
+            }
+
+            fn clone_box(&self) -> Box<dyn FilterNode> {
+                Box::new(self.clone())
+            }
+        }
+
+        /// Doubles its `factor` parameter (range 0..=4).
+        #[derive(Debug, Clone)]
+        struct Double;
+
+        impl FilterNode for Double {
+            fn metadata(&self) -> NodeMetadata {
+                NodeMetadata::builder("double", "Double")
+                    .output(PortDefinition::output("result", PortType::Float))
+                    .parameter(
+                        ParameterDefinition::new("factor", PortType::Float, Value::Float(1.0))
+                            .with_range(0.0, 4.0),
+                    )
+                    .build()
+            }
+
+            fn validate(&self, _ctx: &ValidationContext) -> Result<(), ValidationError> {
+                Ok(())
+            }
+
+            fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+                let factor = match ctx.get_parameter("factor")? {
+                    Value::Float(f) => *f,
+                    other => panic!("factor should arrive as a float, got {other:?}"),
+                };
+                ctx.set_output("result", Value::Float(factor * 2.0))
+            }
+
+            fn clone_box(&self) -> Box<dyn FilterNode> {
+                Box::new(self.clone())
+            }
+        }
+
+        fn run(upstream: Option<Value>) -> Result<ExecutionResult, AmbaraError> {
+            let mut graph = ProcessingGraph::new();
+            let double = graph.add_node(
+                GraphNode::new(Box::new(Double))
+                    .with_parameter("factor", Value::Float(1.5))
+                    .with_promoted_parameter("factor"),
+            );
+            if let Some(value) = upstream {
+                let emit = graph.add_node(GraphNode::new(Box::new(Emit(value))));
+                graph.connect(emit, "value", double, "factor").unwrap();
+            }
+            let result = ExecutionEngine::new().execute(&graph, None)?;
+            assert_eq!(result.errors.len(), 0);
+            Ok(result)
+        }
+
+        fn doubled(result: &ExecutionResult) -> Value {
+            result
+                .all_outputs
+                .values()
+                .find_map(|outputs| outputs.get("result").cloned())
+                .unwrap()
+        }
+
+        #[test]
+        fn connected_value_overrides_stored_parameter() {
+            let result = run(Some(Value::Float(3.0))).unwrap();
+            assert_eq!(doubled(&result), Value::Float(6.0));
+        }
+
+        #[test]
+        fn integer_upstream_is_widened_to_float() {
+            let result = run(Some(Value::Integer(2))).unwrap();
+            assert_eq!(doubled(&result), Value::Float(4.0));
+        }
+
+        #[test]
+        fn unconnected_promoted_parameter_uses_stored_value() {
+            let result = run(None).unwrap();
+            assert_eq!(doubled(&result), Value::Float(3.0));
+        }
+
+        #[test]
+        fn out_of_range_value_fails_at_execution() {
+            let err = run(Some(Value::Float(9.0))).unwrap_err();
+            match err {
+                AmbaraError::Execution(ExecutionError::InvalidParameter { parameter, .. }) => {
+                    assert_eq!(parameter, "factor");
+                }
+                other => panic!("expected InvalidParameter, got {other:?}"),
+            }
+        }
+    }
+
+    mod implicit_conversions {
+        use super::*;
+        use crate::core::context::ValidationContext;
+        use crate::core::error::ValidationError;
+        use crate::core::node::{FilterNode, NodeMetadata};
+        use crate::core::port::PortDefinition;
+        use crate::core::types::{Color, PortType};
+        use crate::filters::registry::FilterRegistry;
+
+        /// Passes on the `value` input it receives, declared with a type.
+        #[derive(Debug, Clone)]
+        struct Receive(PortType);
+
+        impl FilterNode for Receive {
+            fn metadata(&self) -> NodeMetadata {
+                NodeMetadata::builder("receive", "Receive")
+                    .input(PortDefinition::input("value", self.0.clone()))
+                    .output(PortDefinition::output("received", self.0.clone()))
+                    .build()
+            }
+
+            fn validate(&self, _ctx: &ValidationContext) -> Result<(), ValidationError> {
+                Ok(())
+            }
+
+            fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+                let value = ctx.get_input("value")?.clone();
+                ctx.set_output("received", value)
+            }
+
+            fn clone_box(&self) -> Box<dyn FilterNode> {
+                Box::new(self.clone())
+            }
+        }
+
+        fn receive(source: &str, parameter: Value, port_type: PortType) -> Value {
+            let registry = FilterRegistry::with_builtins();
+            let mut graph = ProcessingGraph::new();
+            let source = graph.add_node(
+                GraphNode::new(registry.create(source).unwrap()).with_parameter("value", parameter),
+            );
+            let receive = graph.add_node(GraphNode::new(Box::new(Receive(port_type))));
+            graph.connect(source, "value", receive, "value").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            result.all_outputs[&receive]["received"].clone()
+        }
+
+        #[test]
+        fn inputs_are_converted_to_the_port_type() {
+            assert_eq!(
+                receive("boolean_constant", Value::Boolean(true), PortType::Integer),
+                Value::Integer(1)
+            );
+            assert_eq!(
+                receive("float_constant", Value::Float(-1.5), PortType::Integer),
+                Value::Integer(-2)
+            );
+            assert_eq!(
+                receive("color_constant", Value::Color(Color::WHITE), PortType::Vector3),
+                Value::Vector3(1.0, 1.0, 1.0)
+            );
+        }
+    }
+
+    mod generic_inputs {
+        use super::*;
+        use crate::core::context::ValidationContext;
+        use crate::core::error::ValidationError;
+        use crate::core::node::{FilterNode, NodeMetadata};
+        use crate::core::port::PortDefinition;
+        use crate::core::types::PortType;
+        use crate::filters::registry::FilterRegistry;
+
+        /// Emits an empty `Array<Image>`.
+        #[derive(Debug, Clone)]
+        struct NoImages;
+
+        impl FilterNode for NoImages {
+            fn metadata(&self) -> NodeMetadata {
+                NodeMetadata::builder("no_images", "No Images")
+                    .output(PortDefinition::output("images", PortType::Array(Box::new(PortType::Image))))
+                    .build()
+            }
+
+            fn validate(&self, _ctx: &ValidationContext) -> Result<(), ValidationError> {
+                Ok(())
+            }
+
+            fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+                ctx.set_output("images", Value::Array(Vec::new()))
+            }
+
+            fn clone_box(&self) -> Box<dyn FilterNode> {
+                Box::new(self.clone())
+            }
+        }
+
+        #[test]
+        fn empty_arrays_report_the_bound_element_type() {
+            let registry = FilterRegistry::with_builtins();
+            let mut graph = ProcessingGraph::new();
+            let source = graph.add_node(GraphNode::new(Box::new(NoImages)));
+            let display = graph.add_node(GraphNode::new(registry.create("value_display").unwrap()));
+            graph.connect(source, "images", display, "value").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert_eq!(
+                result.all_outputs[&display]["type"],
+                Value::String("Array<Image>".to_string())
+            );
+        }
+    }
+
+    mod registered_conversions {
+        use super::*;
+        use crate::core::conversion::{Conversion, Fidelity};
+        use crate::core::types::PortType;
+        use crate::filters::registry::FilterRegistry;
+
+        fn parse_float(value: Value) -> Value {
+            match value {
+                Value::String(text) => Value::Float(text.trim().parse().unwrap_or(0.0)),
+                other => other,
+            }
+        }
+
+        #[test]
+        fn graphs_connect_and_execute_with_the_registry_conversions() {
+            let mut registry = FilterRegistry::with_builtins();
+            let text = |registry: &FilterRegistry| {
+                GraphNode::new(registry.create("string_constant").unwrap())
+                    .with_parameter("value", Value::String(" 2.5".to_string()))
+            };
+            let mut builtin = ProcessingGraph::new();
+            let source = builtin.add_node(text(&registry));
+            let equal = builtin.add_node(GraphNode::new(registry.create("equal").unwrap()));
+            assert!(builtin.connect(source, "value", equal, "a").is_err());
+
+            registry.register_conversion(Conversion::new(
+                PortType::String,
+                PortType::Float,
+                Fidelity::Lossy,
+                "text is parsed as a number",
+                parse_float,
+            ));
+            let mut graph = ProcessingGraph::new().with_conversions(registry.conversions());
+            let source = graph.add_node(text(&registry));
+            let number = graph.add_node(
+                GraphNode::new(registry.create("float_constant").unwrap())
+                    .with_parameter("value", Value::Float(2.5)),
+            );
+            let equal = graph.add_node(GraphNode::new(registry.create("equal").unwrap()));
+            graph.connect(source, "value", equal, "a").unwrap();
+            graph.connect(number, "value", equal, "b").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert_eq!(result.all_outputs[&equal]["result"], Value::Boolean(true));
+        }
+    }
 }
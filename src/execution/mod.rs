@@ -8,4 +8,4 @@ pub mod progress;
 
 pub use engine::{ExecutionEngine, ExecutionResult, ExecutionOptions};
 pub use cache::ResultCache;
-pub use progress::{ProgressTracker, ProgressUpdate};
+pub use progress::{CancellationToken, ProgressTracker, ProgressUpdate};
@@ -31,6 +31,13 @@ pub enum ProgressUpdate {
         node_id: NodeId,
         reason: SkipReason,
     },
+    /// Progress reported by a node while it runs.
+    NodeProgress {
+        node_id: NodeId,
+        /// Fraction of the node's work done (0.0 to 1.0).
+        progress: f32,
+        message: Option<String>,
+    },
     /// Overall progress percentage.
     Progress {
         percent: f32,
@@ -66,6 +73,37 @@ pub enum SkipReason {
 /// Callback type for progress updates.
 pub type ProgressCallback = Box<dyn Fn(ProgressUpdate) + Send + Sync>;
 
+/// A handle for cancelling a running graph from another thread.
+///
+/// Clones share the same flag. Pass one to
+/// [`ExecutionOptions::with_cancellation`](super::ExecutionOptions::with_cancellation)
+/// and call [`cancel`](Self::cancel) on another; nodes observe it through
+/// [`ExecutionContext::is_cancelled`](crate::core::context::ExecutionContext::is_cancelled).
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if cancellation was requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// The shared flag, for [`ExecutionHooks`](crate::core::context::ExecutionHooks).
+    pub(crate) fn flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.0)
+    }
+}
+
 /// Tracks execution progress and allows cancellation.
 pub struct ProgressTracker {
     /// Total number of nodes to process.
@@ -75,7 +113,7 @@ pub struct ProgressTracker {
     /// Number of nodes skipped.
     skipped_nodes: AtomicU64,
     /// Whether execution is cancelled.
-    cancelled: AtomicBool,
+    cancelled: CancellationToken,
     /// Start time.
     start_time: Option<Instant>,
     /// Progress callback.
@@ -91,7 +129,7 @@ impl ProgressTracker {
             total_nodes,
             completed_nodes: AtomicU64::new(0),
             skipped_nodes: AtomicU64::new(0),
-            cancelled: AtomicBool::new(false),
+            cancelled: CancellationToken::new(),
             start_time: None,
             callback: None,
             node_times: parking_lot::Mutex::new(Vec::new()),
@@ -109,6 +147,17 @@ impl ProgressTracker {
         self
     }
 
+    /// Share cancellation with `token`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancelled = token;
+        self
+    }
+
+    /// The token cancelling this run.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancelled
+    }
+
     /// Start tracking.
     pub fn start(&mut self) {
         self.start_time = Some(Instant::now());
@@ -146,6 +195,15 @@ impl ProgressTracker {
         self.send_progress_update();
     }
 
+    /// Report progress within a running node.
+    pub fn node_progress(&self, node_id: NodeId, progress: f32, message: Option<String>) {
+        self.send_update(ProgressUpdate::NodeProgress {
+            node_id,
+            progress,
+            message,
+        });
+    }
+
     /// Report that a node was skipped.
     pub fn node_skipped(&self, node_id: NodeId, reason: SkipReason) {
         self.skipped_nodes.fetch_add(1, Ordering::Relaxed);
@@ -154,12 +212,12 @@ impl ProgressTracker {
 
     /// Check if execution should be cancelled.
     pub fn is_cancelled(&self) -> bool {
-        self.cancelled.load(Ordering::Relaxed)
+        self.cancelled.is_cancelled()
     }
 
     /// Request cancellation.
     pub fn cancel(&self) {
-        self.cancelled.store(true, Ordering::Relaxed);
+        self.cancelled.cancel();
         self.send_update(ProgressUpdate::Cancelled);
     }
 
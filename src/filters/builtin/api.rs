@@ -9,6 +9,7 @@ use crate::core::error::{ExecutionError, ValidationError};
 use crate::core::node::{Category, FilterNode, NodeMetadata};
 use crate::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
 use crate::core::types::{ImageValue, PortType, Value};
+use crate::filters::http::HttpClient;
 use crate::filters::registry::FilterRegistry;
 use std::io::Cursor;
 use std::io::Read;
@@ -81,7 +82,7 @@ impl FilterNode for HttpImageFetch {
         let url = ctx.get_string("url")?;
         let timeout = ctx.get_integer("timeout_secs").unwrap_or(30) as u64;
 
-        let resp = ureq::agent()
+        let resp = HttpClient::global()
             .get(url)
             .timeout(std::time::Duration::from_secs(timeout))
             .call()
@@ -137,17 +138,32 @@ impl FilterNode for HttpImageFetch {
 /// Generate an image using a Stable Diffusion API endpoint.
 ///
 /// Supports any API that follows the Automatic1111/ComfyUI-style text-to-image
-/// interface (POST with prompt, returns base64-encoded image).
+/// interface (POST with prompt, returns base64-encoded image). With an
+/// `init_image` (and optionally a `mask`) it calls the matching Automatic1111
+/// `img2img` endpoint instead.
 #[derive(Debug, Clone)]
 pub struct StableDiffusionGenerate;
 
 impl FilterNode for StableDiffusionGenerate {
     fn metadata(&self) -> NodeMetadata {
         NodeMetadata::builder("stable_diffusion_generate", "Stable Diffusion Generate")
-            .description("Generate an image from text using a Stable Diffusion API (local or remote)")
+            .description(
+                "Generate an image from text using a Stable Diffusion API (local or remote). \
+                 Connect an init image (and optionally an inpainting mask) for img2img.",
+            )
             .category(Category::Api)
             .author("Ambara")
             .version("1.0.0")
+            .input(
+                PortDefinition::input("init_image", PortType::Image)
+                    .with_description("Image to start from (img2img); output keeps its size")
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("mask", PortType::Image)
+                    .with_description("Inpainting mask for the init image; white areas are repainted")
+                    .optional(),
+            )
             .output(
                 PortDefinition::output("image", PortType::Image)
                     .with_description("The generated image"),
@@ -158,7 +174,10 @@ impl FilterNode for StableDiffusionGenerate {
                     PortType::String,
                     Value::String("http://127.0.0.1:7860/sdapi/v1/txt2img".to_string()),
                 )
-                .with_description("Stable Diffusion API endpoint URL")
+                .with_description(
+                    "Stable Diffusion API endpoint URL; a txt2img URL is switched to img2img \
+                     when an init image is connected",
+                )
                 .with_constraint(Constraint::NotEmpty),
             )
             .parameter(
@@ -239,6 +258,23 @@ impl FilterNode for StableDiffusionGenerate {
                 .with_description("API request timeout in seconds")
                 .with_constraint(Constraint::Range { min: 10.0, max: 600.0 }),
             )
+            .parameter(
+                ParameterDefinition::new(
+                    "denoising_strength",
+                    PortType::Float,
+                    Value::Float(0.75),
+                )
+                .with_description("How much of the init image to repaint (img2img only)")
+                .with_constraint(Constraint::Range { min: 0.0, max: 1.0 })
+                .with_ui_hint(UiHint::Slider {
+                    logarithmic: false,
+                }),
+            )
+            .parameter(
+                ParameterDefinition::new("mask_blur", PortType::Integer, Value::Integer(4))
+                    .with_description("Blur radius for the inpainting mask edge")
+                    .with_constraint(Constraint::Range { min: 0.0, max: 64.0 }),
+            )
             .build()
     }
 
@@ -273,7 +309,7 @@ impl FilterNode for StableDiffusionGenerate {
         let seed = ctx.get_integer("seed").unwrap_or(-1);
         let timeout = ctx.get_integer("timeout_secs").unwrap_or(120) as u64;
 
-        let body = serde_json::json!({
+        let mut body = serde_json::json!({
             "prompt": prompt,
             "negative_prompt": negative_prompt,
             "width": width,
@@ -283,8 +319,60 @@ impl FilterNode for StableDiffusionGenerate {
             "seed": seed,
         });
 
-        let resp = ureq::agent()
-            .post(api_url)
+        use base64::Engine;
+        let encode_image = |img: &ImageValue| -> Result<String, ExecutionError> {
+            let rgba = img
+                .get_image()
+                .ok_or_else(|| ExecutionError::NodeExecution {
+                    node_id: ctx.node_id,
+                    error: "Input image data is not loaded".to_string(),
+                })?
+                .to_rgba8();
+            let mut png_bytes = Vec::new();
+            let encoder = image::codecs::png::PngEncoder::new(Cursor::new(&mut png_bytes));
+            use image::ImageEncoder;
+            encoder
+                .write_image(
+                    rgba.as_raw(),
+                    rgba.width(),
+                    rgba.height(),
+                    image::ExtendedColorType::Rgba8,
+                )
+                .map_err(|e| ExecutionError::NodeExecution {
+                    node_id: ctx.node_id,
+                    error: format!("Failed to encode image: {}", e),
+                })?;
+            Ok(base64::engine::general_purpose::STANDARD.encode(&png_bytes))
+        };
+
+        // img2img: same request plus the init image, which also fixes the size
+        let mut api_url = api_url.to_string();
+        match (ctx.get_input_image_optional("init_image"), ctx.get_input_image_optional("mask")) {
+            (Some(init), mask) => {
+                if let Some(base) = api_url.strip_suffix("/txt2img") {
+                    api_url = format!("{}/img2img", base);
+                }
+                body["init_images"] = serde_json::json!([encode_image(init)?]);
+                body["denoising_strength"] =
+                    serde_json::json!(ctx.get_float("denoising_strength").unwrap_or(0.75));
+                body["width"] = serde_json::json!(init.metadata.width);
+                body["height"] = serde_json::json!(init.metadata.height);
+                if let Some(mask) = mask {
+                    body["mask"] = serde_json::json!(encode_image(mask)?);
+                    body["mask_blur"] = serde_json::json!(ctx.get_integer("mask_blur").unwrap_or(4));
+                }
+            }
+            (None, Some(_)) => {
+                return Err(ExecutionError::NodeExecution {
+                    node_id: ctx.node_id,
+                    error: "A mask needs an init image to inpaint".to_string(),
+                });
+            }
+            (None, None) => {}
+        }
+
+        let resp = HttpClient::global()
+            .post(&api_url)
             .timeout(std::time::Duration::from_secs(timeout))
             .send_json(&body)
             .map_err(|e| ExecutionError::NodeExecution {
@@ -315,7 +403,6 @@ impl FilterNode for StableDiffusionGenerate {
                 error: "No images in API response".to_string(),
             })?;
 
-        use base64::Engine;
         let decoded = base64::engine::general_purpose::STANDARD
             .decode(b64)
             .map_err(|e| ExecutionError::NodeExecution {
@@ -446,7 +533,7 @@ impl FilterNode for ImageClassify {
             "image": b64,
         });
 
-        let resp = ureq::agent()
+        let resp = HttpClient::global()
             .post(api_url)
             .timeout(std::time::Duration::from_secs(timeout))
             .send_json(&body)
@@ -633,7 +720,7 @@ impl FilterNode for ModelInference {
             }
         }
 
-        let resp = ureq::agent()
+        let resp = HttpClient::global()
             .post(api_url)
             .timeout(std::time::Duration::from_secs(timeout))
             .send_json(&body)
@@ -807,7 +894,7 @@ impl FilterNode for StyleTransfer {
             "strength": strength,
         });
 
-        let resp = ureq::agent()
+        let resp = HttpClient::global()
             .post(api_url)
             .timeout(std::time::Duration::from_secs(timeout))
             .send_json(&body)
@@ -859,3 +946,111 @@ impl FilterNode for StyleTransfer {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::error::NodeId;
+    use base64::Engine;
+    use crate::filters::test_server::{serve_once, Request};
+    use image::{DynamicImage, Rgba, RgbaImage};
+    use std::sync::mpsc;
+
+    /// Answer one request with a generated 4×4 image.
+    fn stub_server() -> (String, mpsc::Receiver<Request>) {
+        let generated = png(4, 4, [0, 0, 255, 255]);
+        let response = serde_json::json!({
+            "images": [base64::engine::general_purpose::STANDARD.encode(generated)]
+        });
+        serve_once("application/json", response.to_string().into_bytes())
+    }
+
+    fn json_body(request: &Request) -> serde_json::Value {
+        serde_json::from_slice(&request.body).unwrap()
+    }
+
+    fn png(width: u32, height: u32, color: [u8; 4]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(width, height, Rgba(color)))
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn image(width: u32, height: u32, color: [u8; 4]) -> Value {
+        Value::Image(ImageValue::new(DynamicImage::ImageRgba8(
+            RgbaImage::from_pixel(width, height, Rgba(color)),
+        )))
+    }
+
+    /// A context with `filter`'s default parameters and `api_url` set.
+    fn context(filter: &dyn FilterNode, api_url: &str) -> ExecutionContext {
+        let mut ctx = ExecutionContext::new(NodeId::new());
+        for param in filter.metadata().parameters {
+            ctx.add_parameter(param.name, param.default_value);
+        }
+        ctx.add_parameter("api_url", Value::String(api_url.to_string()));
+        ctx
+    }
+
+    fn decode(b64: &serde_json::Value) -> RgbaImage {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(b64.as_str().unwrap())
+            .unwrap();
+        image::load_from_memory(&bytes).unwrap().to_rgba8()
+    }
+
+    mod stable_diffusion {
+        use super::*;
+
+        #[test]
+        fn text_prompts_call_txt2img() {
+            let (url, requests) = stub_server();
+            let filter = StableDiffusionGenerate;
+            let mut ctx = context(&filter, &format!("{url}/sdapi/v1/txt2img"));
+            filter.execute(&mut ctx).unwrap();
+
+            let request = requests.recv().unwrap();
+            assert_eq!(request.path(), "/sdapi/v1/txt2img");
+            let body = json_body(&request);
+            assert_eq!((body["width"].as_u64(), body["height"].as_u64()), (Some(512), Some(512)));
+            assert!(body.get("init_images").is_none());
+            let outputs = ctx.take_outputs();
+            let generated = outputs["image"].as_image().unwrap();
+            assert_eq!((generated.metadata.width, generated.metadata.height), (4, 4));
+        }
+
+        #[test]
+        fn init_image_and_mask_call_img2img() {
+            let (url, requests) = stub_server();
+            let filter = StableDiffusionGenerate;
+            let mut ctx = context(&filter, &format!("{url}/sdapi/v1/txt2img"));
+            ctx.add_parameter("denoising_strength", Value::Float(0.4));
+            ctx.add_input("init_image", image(3, 2, [200, 10, 10, 255]));
+            ctx.add_input("mask", image(3, 2, [255, 255, 255, 255]));
+            filter.execute(&mut ctx).unwrap();
+
+            let request = requests.recv().unwrap();
+            assert_eq!(request.path(), "/sdapi/v1/img2img");
+            let body = json_body(&request);
+            assert_eq!(body["denoising_strength"], 0.4);
+            assert_eq!(body["mask_blur"], 4);
+            assert_eq!((body["width"].as_u64(), body["height"].as_u64()), (Some(3), Some(2)));
+            let init = body["init_images"].as_array().unwrap();
+            assert_eq!(init.len(), 1);
+            let init = decode(&init[0]);
+            assert_eq!(init.dimensions(), (3, 2));
+            assert!(init.pixels().all(|p| p.0 == [200, 10, 10, 255]));
+            assert!(decode(&body["mask"]).pixels().all(|p| p.0 == [255, 255, 255, 255]));
+        }
+
+        #[test]
+        fn mask_without_init_image_is_rejected() {
+            let filter = StableDiffusionGenerate;
+            let mut ctx = context(&filter, "http://127.0.0.1:9/sdapi/v1/txt2img");
+            ctx.add_input("mask", image(3, 2, [255, 255, 255, 255]));
+            let err = filter.execute(&mut ctx).unwrap_err();
+            assert!(err.to_string().contains("init image"), "{err}");
+        }
+    }
+}
@@ -9,6 +9,7 @@ use crate::core::node::{Category, FilterNode, NodeMetadata};
 use crate::core::port::{ParameterDefinition, PortDefinition};
 use crate::core::types::{PortType, Value};
 use crate::filters::registry::FilterRegistry;
+use std::collections::HashMap;
 
 /// Register array processing filters.
 pub fn register(registry: &mut FilterRegistry) {
@@ -32,11 +33,11 @@ impl FilterNode for ArrayMap {
             .author("Ambara")
             .version("1.0.0")
             .input(
-                PortDefinition::input("images", PortType::Any)
+                PortDefinition::input("images", PortType::Var("T".to_string()))
                     .with_description("Image or array of images")
             )
             .output(
-                PortDefinition::output("images", PortType::Any)
+                PortDefinition::output("images", PortType::Var("T".to_string()))
                     .with_description("Processed image(s)")
             )
             .output(
@@ -264,6 +265,30 @@ impl FilterNode for ArraySlice {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        let Some(Value::Array(images)) = ctx.inferred_input("images") else {
+            return HashMap::new();
+        };
+        let start = ctx.get_integer("start").unwrap_or(0) as usize;
+        let end_param = ctx.get_integer("end").unwrap_or(-1);
+        let end = if end_param < 0 {
+            images.len()
+        } else {
+            (end_param as usize).min(images.len())
+        };
+        let sliced: Vec<Value> = images
+            .iter()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .cloned()
+            .collect();
+        let count = sliced.len() as i64;
+        HashMap::from([
+            ("images".to_string(), Value::Array(sliced)),
+            ("count".to_string(), Value::Integer(count)),
+        ])
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let input = ctx.get_input("images")?;
         let start = ctx.get_integer("start").unwrap_or(0) as usize;
@@ -0,0 +1,348 @@
+//! Discovery of the models and options a ComfyUI server offers.
+//!
+//! Checkpoint, LoRA, upscale and ControlNet names come from the server's
+//! `/models/{folder}` listing where it has one, otherwise from the option
+//! lists of the matching loader classes in `/object_info/{class}`. Sampler
+//! and scheduler names always come from `/object_info/KSampler`.
+//!
+//! [`DiscoveryCache`] keeps the result per server URL until it is
+//! [refreshed](DiscoveryCache::refresh). The lists turn the name parameters
+//! of the `comfy_*` filters into dropdowns ([`ServerOptions::dropdowns`]) and
+//! let validation reject names the server does not have.
+
+use crate::core::node::NodeMetadata;
+use crate::core::port::UiHint;
+use crate::filters::http::{HttpClient, HttpError};
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Timeout for each discovery request; validation waits for it.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a failed lookup is reused before the server is asked again.
+pub const RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// A list of names the server offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionKind {
+    Checkpoints,
+    Loras,
+    UpscaleModels,
+    ControlNets,
+    Samplers,
+    Schedulers,
+}
+
+impl OptionKind {
+    /// All kinds, in discovery order.
+    pub const ALL: [OptionKind; 6] = [
+        OptionKind::Checkpoints,
+        OptionKind::Loras,
+        OptionKind::UpscaleModels,
+        OptionKind::ControlNets,
+        OptionKind::Samplers,
+        OptionKind::Schedulers,
+    ];
+
+    /// The `/models/{folder}` listing, for model files.
+    fn folder(self) -> Option<&'static str> {
+        match self {
+            OptionKind::Checkpoints => Some("checkpoints"),
+            OptionKind::Loras => Some("loras"),
+            OptionKind::UpscaleModels => Some("upscale_models"),
+            OptionKind::ControlNets => Some("controlnet"),
+            OptionKind::Samplers | OptionKind::Schedulers => None,
+        }
+    }
+
+    /// The class and required input in `/object_info` listing the names.
+    fn object_info_input(self) -> (&'static str, &'static str) {
+        match self {
+            OptionKind::Checkpoints => ("CheckpointLoaderSimple", "ckpt_name"),
+            OptionKind::Loras => ("LoraLoader", "lora_name"),
+            OptionKind::UpscaleModels => ("UpscaleModelLoader", "model_name"),
+            OptionKind::ControlNets => ("ControlNetLoader", "control_net_name"),
+            OptionKind::Samplers => ("KSampler", "sampler_name"),
+            OptionKind::Schedulers => ("KSampler", "scheduler"),
+        }
+    }
+}
+
+/// Filter parameters that name something on the server.
+pub const DISCOVERED_PARAMETERS: &[(&str, &str, OptionKind)] = &[
+    ("comfy_checkpoint_loader", "checkpoint_name", OptionKind::Checkpoints),
+    ("comfy_lora_loader", "lora_name", OptionKind::Loras),
+    ("comfy_ksampler", "sampler_name", OptionKind::Samplers),
+    ("comfy_ksampler", "scheduler", OptionKind::Schedulers),
+    ("comfy_image_upscale", "upscale_model", OptionKind::UpscaleModels),
+    ("comfy_controlnet_apply", "controlnet_name", OptionKind::ControlNets),
+];
+
+/// The discovered parameters of `filter_id`.
+pub fn parameters_of(filter_id: &str) -> impl Iterator<Item = (&'static str, OptionKind)> + '_ {
+    DISCOVERED_PARAMETERS
+        .iter()
+        .filter(move |(id, _, _)| *id == filter_id)
+        .map(|(_, parameter, kind)| (*parameter, *kind))
+}
+
+/// A dropdown for one filter parameter.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterDropdown {
+    pub filter_id: String,
+    pub parameter: String,
+    pub ui_hint: UiHint,
+}
+
+/// The names one server offers.
+///
+/// Kinds the server does not list (for example a server without the
+/// upscale model loader) are absent rather than empty, so their
+/// parameters are left unchecked.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ServerOptions {
+    lists: BTreeMap<OptionKind, Vec<String>>,
+}
+
+impl ServerOptions {
+    /// Ask `base_url` for its names.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the server cannot be reached.
+    pub fn fetch(base_url: &str) -> Result<Self, String> {
+        let base_url = base_url.trim_end_matches('/');
+        let get = |path: String| -> Result<Option<serde_json::Value>, String> {
+            // No retries: validation waits for this, and failures are cached.
+            let request = HttpClient::global()
+                .get(&format!("{}{}", base_url, path))
+                .timeout(REQUEST_TIMEOUT)
+                .retries(0);
+            match request.call() {
+                Ok(resp) => resp
+                    .into_json()
+                    .map(Some)
+                    .map_err(|e| format!("invalid response from {}: {}", path, e)),
+                // The endpoint or class does not exist on this server.
+                Err(HttpError::Status { .. }) => Ok(None),
+                Err(e) => Err(e.to_string()),
+            }
+        };
+
+        let mut object_info: HashMap<&str, Option<serde_json::Value>> = HashMap::new();
+        let mut lists = BTreeMap::new();
+        for kind in OptionKind::ALL {
+            if let Some(folder) = kind.folder() {
+                if let Some(names) = get(format!("/models/{}", folder))?.as_ref().and_then(names) {
+                    lists.insert(kind, names);
+                    continue;
+                }
+            }
+            let (class, input) = kind.object_info_input();
+            if !object_info.contains_key(class) {
+                object_info.insert(class, get(format!("/object_info/{}", class))?);
+            }
+            let spec = object_info[class]
+                .as_ref()
+                .map(|info| &info[class]["input"]["required"][input]);
+            if let Some(names) = spec.and_then(combo_options) {
+                lists.insert(kind, names);
+            }
+        }
+        Ok(Self { lists })
+    }
+
+    /// The names of `kind`, if the server lists them.
+    pub fn get(&self, kind: OptionKind) -> Option<&[String]> {
+        self.lists.get(&kind).map(Vec::as_slice)
+    }
+
+    /// Dropdowns for every discovered parameter the server has names for.
+    pub fn dropdowns(&self) -> Vec<ParameterDropdown> {
+        DISCOVERED_PARAMETERS
+            .iter()
+            .filter_map(|(filter_id, parameter, kind)| {
+                Some(ParameterDropdown {
+                    filter_id: filter_id.to_string(),
+                    parameter: parameter.to_string(),
+                    ui_hint: UiHint::Dropdown {
+                        options: self.get(*kind)?.to_vec(),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Turn the discovered parameters of `metadata` into dropdowns.
+    pub fn apply_to(&self, metadata: &mut NodeMetadata) {
+        for (parameter, kind) in parameters_of(&metadata.id) {
+            let Some(options) = self.get(kind) else {
+                continue;
+            };
+            if let Some(definition) = metadata.parameters.iter_mut().find(|p| p.name == parameter) {
+                definition.ui_hint = UiHint::Dropdown {
+                    options: options.to_vec(),
+                };
+            }
+        }
+    }
+}
+
+/// A `/models/{folder}` listing: a JSON array of file names.
+fn names(listing: &serde_json::Value) -> Option<Vec<String>> {
+    listing
+        .as_array()?
+        .iter()
+        .map(|name| name.as_str().map(str::to_string))
+        .collect()
+}
+
+/// The options of a combo input spec: `[[options...], {...}]`, or
+/// `["COMBO", {"options": [...]}]` on newer servers.
+fn combo_options(spec: &serde_json::Value) -> Option<Vec<String>> {
+    match &spec[0] {
+        serde_json::Value::Array(_) => names(&spec[0]),
+        serde_json::Value::String(kind) if kind == "COMBO" => names(&spec[1]["options"]),
+        _ => None,
+    }
+}
+
+struct Entry {
+    result: Result<Arc<ServerOptions>, String>,
+    fetched_at: Instant,
+}
+
+/// Discovered options, cached per server URL.
+#[derive(Default)]
+pub struct DiscoveryCache {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl DiscoveryCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cache the `comfy_*` filters validate against.
+    pub fn global() -> &'static DiscoveryCache {
+        static GLOBAL: OnceLock<DiscoveryCache> = OnceLock::new();
+        GLOBAL.get_or_init(DiscoveryCache::new)
+    }
+
+    /// The options of `base_url`, fetched on first use.
+    ///
+    /// A failed lookup is returned again without contacting the server
+    /// until [`RETRY_AFTER`] has passed.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the server cannot be reached.
+    pub fn options(&self, base_url: &str) -> Result<Arc<ServerOptions>, String> {
+        let key = base_url.trim_end_matches('/');
+        if let Some(entry) = self.entries.lock().unwrap().get(key) {
+            if entry.result.is_ok() || entry.fetched_at.elapsed() < RETRY_AFTER {
+                return entry.result.clone();
+            }
+        }
+        self.refresh(key)
+    }
+
+    /// Fetch the options of `base_url` again, replacing the cached ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the server cannot be reached.
+    pub fn refresh(&self, base_url: &str) -> Result<Arc<ServerOptions>, String> {
+        let key = base_url.trim_end_matches('/');
+        // Fetch without holding the lock; other servers stay available.
+        let result = ServerOptions::fetch(key).map(Arc::new);
+        self.entries.lock().unwrap().insert(
+            key.to_string(),
+            Entry {
+                result: result.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+        result
+    }
+
+    /// The cached options of `base_url`, without contacting it.
+    pub fn cached(&self, base_url: &str) -> Option<Arc<ServerOptions>> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .get(base_url.trim_end_matches('/'))
+            .and_then(|entry| entry.result.clone().ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::mock_server::MockComfy;
+    use super::*;
+
+    #[test]
+    fn lists_come_from_models_then_object_info() {
+        let server = MockComfy::start();
+        let options = ServerOptions::fetch(&server.url).unwrap();
+
+        // From /models.
+        assert_eq!(
+            options.get(OptionKind::Checkpoints).unwrap(),
+            ["sd15.safetensors", "sdxl.safetensors"]
+        );
+        // The mock has no /models/controlnet; the loader class lists them.
+        assert_eq!(options.get(OptionKind::ControlNets).unwrap(), ["canny.pth"]);
+        // Old-style and COMBO-style option lists.
+        assert_eq!(options.get(OptionKind::Samplers).unwrap(), ["euler", "dpmpp_2m"]);
+        assert_eq!(options.get(OptionKind::Schedulers).unwrap(), ["normal", "karras"]);
+        // Neither source knows upscale models: left unchecked.
+        assert_eq!(options.get(OptionKind::UpscaleModels), None);
+        // KSampler's object info is fetched once for both lists.
+        assert_eq!(server.requests("GET", "/object_info/KSampler").len(), 1);
+
+        let dropdowns = options.dropdowns();
+        assert_eq!(dropdowns.len(), 5);
+        assert_eq!(
+            dropdowns[0],
+            ParameterDropdown {
+                filter_id: "comfy_checkpoint_loader".to_string(),
+                parameter: "checkpoint_name".to_string(),
+                ui_hint: UiHint::Dropdown {
+                    options: vec!["sd15.safetensors".to_string(), "sdxl.safetensors".to_string()],
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn options_are_cached_per_url_until_refreshed() {
+        let server = MockComfy::start();
+        let cache = DiscoveryCache::new();
+        assert!(cache.cached(&server.url).is_none());
+
+        let first = cache.options(&server.url).unwrap();
+        let again = cache.options(&format!("{}/", server.url)).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        assert_eq!(server.requests("GET", "/models/checkpoints").len(), 1);
+
+        cache.refresh(&server.url).unwrap();
+        assert_eq!(server.requests("GET", "/models/checkpoints").len(), 2);
+        assert!(cache.cached(&server.url).is_some());
+    }
+
+    #[test]
+    fn unreachable_servers_fail_and_are_not_retried_immediately() {
+        // Bind and drop a listener to get a port nobody listens on.
+        let (_, url) = crate::filters::test_server::listen();
+        let cache = DiscoveryCache::new();
+        let error = cache.options(&url).unwrap_err();
+        assert_eq!(cache.options(&url).unwrap_err(), error);
+        assert!(cache.cached(&url).is_none());
+    }
+}
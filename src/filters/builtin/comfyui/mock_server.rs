@@ -0,0 +1,257 @@
+//! A minimal ComfyUI stand-in for tests.
+//!
+//! Answers `/prompt`, `/history/{id}`, `/view`, `/upload/image`,
+//! `/interrupt` and `/queue` the way ComfyUI does and records every request,
+//! so tests can assert on the exact prompts the `comfy_*` nodes submit.
+//! Clients that open `/ws` receive `executing` and `progress` events for
+//! their prompts.
+
+use super::websocket::server_frame;
+use crate::filters::test_server::{listen, read_request, write_response};
+
+use image::{DynamicImage, Rgba, RgbaImage};
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+
+/// How the server runs the prompts it receives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Finish every prompt immediately, streaming events over `/ws`.
+    Complete,
+    /// Like `Complete`, but refuse `/ws` so clients have to poll.
+    NoWebsocket,
+    /// Start every prompt but never finish it.
+    Hold,
+}
+
+/// One recorded HTTP request.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub body: Vec<u8>,
+}
+
+struct State {
+    mode: Mode,
+    requests: Vec<Request>,
+    prompts: Vec<serde_json::Value>,
+    /// Open `/ws` connections by client ID.
+    sockets: HashMap<String, TcpStream>,
+}
+
+/// A ComfyUI server on an ephemeral local port.
+pub struct MockComfy {
+    pub url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockComfy {
+    pub fn start() -> Self {
+        Self::start_with(Mode::Complete)
+    }
+
+    pub fn start_with(mode: Mode) -> Self {
+        let (listener, url) = listen();
+        let state = Arc::new(Mutex::new(State {
+            mode,
+            requests: Vec::new(),
+            prompts: Vec::new(),
+            sockets: HashMap::new(),
+        }));
+        let shared = Arc::clone(&state);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let _ = serve(stream, &shared);
+            }
+        });
+        Self { url, state }
+    }
+
+    /// Requests whose method is `method` and path starts with `prefix`.
+    pub fn requests(&self, method: &str, prefix: &str) -> Vec<Request> {
+        let state = self.state.lock().unwrap();
+        state
+            .requests
+            .iter()
+            .filter(|r| r.method == method && r.path.starts_with(prefix))
+            .cloned()
+            .collect()
+    }
+
+    /// The `prompt` objects submitted to `/prompt`, in order.
+    pub fn prompts(&self) -> Vec<serde_json::Value> {
+        self.state.lock().unwrap().prompts.clone()
+    }
+}
+
+fn serve(mut stream: TcpStream, state: &Mutex<State>) -> std::io::Result<()> {
+    let received = read_request(&stream)?;
+    let request = Request {
+        method: received.method().to_string(),
+        path: received.path().to_string(),
+        body: received.body,
+    };
+    if request.path.starts_with("/ws?clientId=") && state.lock().unwrap().mode != Mode::NoWebsocket
+    {
+        let client_id = request.path["/ws?clientId=".len()..].to_string();
+        stream.write_all(
+            b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n",
+        )?;
+        send_event(
+            &mut stream,
+            "status",
+            serde_json::json!({ "sid": client_id }),
+        );
+        let mut state = state.lock().unwrap();
+        state.sockets.insert(client_id, stream);
+        state.requests.push(request);
+        return Ok(());
+    }
+
+    let (status, content_type, response) = respond(&request, state);
+    state.lock().unwrap().requests.push(request);
+    write_response(&stream, status, &[("Content-Type", content_type)], &response)
+}
+
+fn respond(request: &Request, state: &Mutex<State>) -> (u16, &'static str, Vec<u8>) {
+    let json = |value: serde_json::Value| (200, "application/json", value.to_string().into_bytes());
+    let path = request.path.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("GET", "/system_stats") => json(serde_json::json!({ "system": {} })),
+        ("POST", "/prompt") => {
+            let mut state = state.lock().unwrap();
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            state.prompts.push(body["prompt"].clone());
+            let prompt_id = format!("prompt-{}", state.prompts.len());
+            let mode = state.mode;
+            if let Some(socket) = body["client_id"]
+                .as_str()
+                .and_then(|id| state.sockets.get_mut(id))
+            {
+                stream_events(socket, &prompt_id, &body["prompt"], mode);
+            }
+            json(serde_json::json!({
+                "prompt_id": prompt_id,
+                "number": state.prompts.len(),
+                "node_errors": {}
+            }))
+        }
+        ("POST", "/interrupt" | "/queue") => json(serde_json::json!({})),
+        ("GET", history) if history.starts_with("/history/") => {
+            let state = state.lock().unwrap();
+            let id = &history["/history/".len()..];
+            if state.mode == Mode::Hold {
+                return json(serde_json::json!({}));
+            }
+            let index: usize = id.trim_start_matches("prompt-").parse().unwrap();
+            let prompt = &state.prompts[index - 1];
+            let outputs: serde_json::Map<String, serde_json::Value> = prompt
+                .as_object()
+                .unwrap()
+                .iter()
+                .filter(|(_, node)| {
+                    matches!(node["class_type"].as_str(), Some("PreviewImage" | "SaveImage"))
+                })
+                .map(|(node_id, _)| {
+                    (
+                        node_id.clone(),
+                        serde_json::json!({ "images": [
+                            { "filename": format!("{node_id}.png"), "subfolder": "", "type": "output" }
+                        ]}),
+                    )
+                })
+                .collect();
+            json(serde_json::json!({
+                id: { "outputs": outputs, "status": { "status_str": "success", "completed": true } }
+            }))
+        }
+        ("GET", "/models/checkpoints") => {
+            json(serde_json::json!(["sd15.safetensors", "sdxl.safetensors"]))
+        }
+        ("GET", "/models/loras") => json(serde_json::json!(["detail.safetensors"])),
+        ("GET", "/object_info/KSampler") => json(serde_json::json!({ "KSampler": { "input": {
+            "required": {
+                "sampler_name": [["euler", "dpmpp_2m"], {}],
+                "scheduler": ["COMBO", { "options": ["normal", "karras"] }]
+            }
+        }}})),
+        ("GET", "/object_info/ControlNetLoader") => json(serde_json::json!({
+            "ControlNetLoader": { "input": { "required": { "control_net_name": [["canny.pth"]] } } }
+        })),
+        ("GET", "/view") => (200, "image/png", png(Rgba([200, 10, 10, 255]))),
+        ("POST", "/upload/image") => json(serde_json::json!({
+            "name": upload(&request.body).0, "subfolder": "", "type": "input"
+        })),
+        _ => (404, "text/plain", b"not found".to_vec()),
+    }
+}
+
+/// File name and bytes of the `image` field of a multipart upload.
+pub fn upload(body: &[u8]) -> (String, Vec<u8>) {
+    let text = String::from_utf8_lossy(body);
+    let name_at = text.find("filename=\"").unwrap() + "filename=\"".len();
+    let name = text[name_at..].split('"').next().unwrap().to_string();
+    let start = body.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+    let boundary = &body[..body.windows(2).position(|w| w == b"\r\n").unwrap()];
+    let end = start
+        + body[start..]
+            .windows(boundary.len() + 2)
+            .position(|w| &w[2..] == boundary)
+            .unwrap();
+    (name, body[start..end].to_vec())
+}
+
+fn send_event(socket: &mut TcpStream, kind: &str, data: serde_json::Value) {
+    let event = serde_json::json!({ "type": kind, "data": data }).to_string();
+    let _ = socket.write_all(&server_frame(0x1, true, event.as_bytes()));
+}
+
+/// Events for `prompt` as ComfyUI sends them: `executing` per node, two
+/// sampler steps per `KSampler`, then `executing` with no node once done.
+/// Held prompts stop after the first sampler step.
+fn stream_events(socket: &mut TcpStream, prompt_id: &str, prompt: &serde_json::Value, mode: Mode) {
+    let mut ids: Vec<&String> = prompt.as_object().unwrap().keys().collect();
+    ids.sort_by_key(|id| id.parse::<u64>().unwrap_or(u64::MAX));
+    for id in ids {
+        send_event(
+            socket,
+            "executing",
+            serde_json::json!({ "node": id, "prompt_id": prompt_id }),
+        );
+        if prompt[id]["class_type"] == "KSampler" {
+            for value in 1..=2 {
+                send_event(
+                    socket,
+                    "progress",
+                    serde_json::json!({ "value": value, "max": 2, "node": id, "prompt_id": prompt_id }),
+                );
+                if mode == Mode::Hold {
+                    return;
+                }
+            }
+        }
+    }
+    // Sampler previews arrive as binary messages.
+    let _ = socket.write_all(&server_frame(0x2, true, &[0, 0, 0, 1]));
+    send_event(
+        socket,
+        "executing",
+        serde_json::json!({ "node": null, "prompt_id": prompt_id }),
+    );
+}
+
+/// A 3×2 PNG filled with `color`; what `/view` serves.
+pub fn png(color: Rgba<u8>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(3, 2, color))
+        .write_to(
+            &mut std::io::Cursor::new(&mut bytes),
+            image::ImageFormat::Png,
+        )
+        .unwrap();
+    bytes
+}
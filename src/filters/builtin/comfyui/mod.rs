@@ -0,0 +1,2102 @@
+//! ComfyUI workflow nodes for Ambara.
+//!
+//! These filters mirror the core ComfyUI nodes (checkpoint loading, CLIP text
+//! encoding, KSampler, VAE decode, LoRA loading, ControlNet, upscaling) and
+//! run them on a ComfyUI server via its REST API.
+//!
+//! A chain of these nodes runs as one ComfyUI prompt: upstream nodes only
+//! build prompt fragments (see [`prompt`]), and the node that needs pixels
+//! (VAE Decode) submits the whole chain once. Sinks whose chains compile to
+//! the same prompt share that submission and read their images from its
+//! result. The server is the one given on the chain's Checkpoint Loader
+//! (default `http://127.0.0.1:8188`); prompts go through the `/prompt`,
+//! `/history` and `/view` endpoints.
+//!
+//! While a prompt runs, step progress from ComfyUI's `/ws` event stream (see
+//! [`websocket`]) is reported as node progress, falling back to polling
+//! `/history` when the stream is unavailable. Cancelling the Ambara run
+//! interrupts the prompt and removes it from the server's queue.
+//!
+//! Model, LoRA, sampler and scheduler names are checked against the lists
+//! the server offers (see [`discovery`]) during resource validation.
+
+pub mod discovery;
+mod prompt;
+mod websocket;
+#[cfg(test)]
+mod mock_server;
+
+use prompt::{ComfyRef, PromptBuilder};
+
+use crate::core::context::{ExecutionContext, ValidationContext};
+use crate::core::error::{ExecutionError, NodeId, ValidationError, ValidationWarning};
+use crate::core::node::{Category, FilterNode, NodeMetadata};
+use crate::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
+use crate::core::types::{ImageValue, PortType, Value};
+use crate::filters::http::HttpClient;
+use crate::filters::registry::FilterRegistry;
+use indexmap::IndexMap;
+use std::io::Cursor;
+use std::io::Read;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Register all ComfyUI workflow filters.
+pub fn register(registry: &mut FilterRegistry) {
+    registry.register(|| Box::new(ComfyCheckpointLoader));
+    registry.register(|| Box::new(ComfyClipTextEncode));
+    registry.register(|| Box::new(ComfyKSampler));
+    registry.register(|| Box::new(ComfyVaeDecode));
+    registry.register(|| Box::new(ComfyLoraLoader));
+    registry.register(|| Box::new(ComfyImageUpscale));
+    registry.register(|| Box::new(ComfyControlNetApply));
+    registry.register(|| Box::new(ComfyWorkflowRunner));
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// How often the event stream and the `/history` poll check for
+/// cancellation.
+const EVENT_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// How many submitted prompts [`Submissions`] remembers.
+const MAX_SUBMISSIONS: usize = 64;
+
+/// `/history` entries of submitted prompts, keyed by a hash of the server
+/// and the compiled prompt.
+///
+/// The first sink to compile a prompt submits it; sinks compiling the same
+/// prompt, such as two VAE Decode nodes on one latent, wait for that
+/// submission and read their outputs from its entry. Failed and cancelled
+/// submissions are not kept, so the next sink submits again.
+#[derive(Default)]
+struct Submissions {
+    entries: Mutex<IndexMap<u64, Arc<Mutex<Option<serde_json::Value>>>>>,
+}
+
+impl Submissions {
+    fn global() -> &'static Submissions {
+        static GLOBAL: OnceLock<Submissions> = OnceLock::new();
+        GLOBAL.get_or_init(Submissions::default)
+    }
+
+    /// The entry for `key`, running `submit` if no sink has submitted the
+    /// prompt yet.
+    fn history(
+        &self,
+        key: u64,
+        submit: impl FnOnce() -> Result<serde_json::Value, ExecutionError>,
+    ) -> Result<serde_json::Value, ExecutionError> {
+        let entry = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = Arc::clone(entries.entry(key).or_default());
+            if entries.len() > MAX_SUBMISSIONS {
+                entries.shift_remove_index(0);
+            }
+            entry
+        };
+        // Held while submitting, so concurrent sinks wait for the result
+        let mut history = entry.lock().unwrap();
+        if let Some(history) = history.as_ref() {
+            return Ok(history.clone());
+        }
+        let submitted = submit()?;
+        *history = Some(submitted.clone());
+        Ok(submitted)
+    }
+}
+
+/// Submit `workflow` and wait for it to finish, returning its `/history`
+/// entry.
+///
+/// Progress comes from the `/ws` event stream when the server offers one;
+/// otherwise `/history` is polled. If the Ambara run is cancelled, the
+/// prompt is interrupted and removed from the server's queue.
+fn execute_prompt(
+    ctx: &mut ExecutionContext,
+    base_url: &str,
+    workflow: &serde_json::Value,
+    timeout_secs: u64,
+) -> Result<serde_json::Value, ExecutionError> {
+    let client_id = uuid::Uuid::new_v4().to_string();
+    // Subscribe before submitting so no event of the prompt is missed.
+    let events = websocket::EventStream::connect(base_url, &client_id, EVENT_POLL_INTERVAL).ok();
+    let prompt_id = submit_prompt(base_url, workflow, &client_id, ctx.node_id)?;
+    let deadline =
+        std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+
+    let result = events
+        .map_or(Ok(()), |mut events| {
+            follow_events(ctx, &mut events, &prompt_id, workflow, deadline)
+        })
+        .and_then(|()| poll_comfyui_result(ctx, base_url, &prompt_id, deadline));
+    if matches!(result, Err(ExecutionError::Cancelled)) {
+        cancel_prompt(base_url, &prompt_id);
+    }
+    result
+}
+
+/// Report the prompt's progress from the event stream until it finishes.
+///
+/// Returns `Ok` once the prompt is done or the stream is lost; either way
+/// the caller collects the result from `/history`.
+fn follow_events(
+    ctx: &mut ExecutionContext,
+    events: &mut websocket::EventStream,
+    prompt_id: &str,
+    workflow: &serde_json::Value,
+    deadline: std::time::Instant,
+) -> Result<(), ExecutionError> {
+    loop {
+        ctx.check_cancelled()?;
+        check_deadline(deadline, ctx.node_id)?;
+        let event = match events.next_event() {
+            Ok(Some(event)) => event,
+            Ok(None) => continue,
+            Err(_) => return Ok(()),
+        };
+        let data = &event["data"];
+        if data.get("prompt_id").is_some_and(|id| id != prompt_id) {
+            continue;
+        }
+        match event["type"].as_str().unwrap_or_default() {
+            "executing" => match data["node"].as_str() {
+                Some(node) => {
+                    let class_type = workflow[node]["class_type"].as_str().unwrap_or(node);
+                    let progress = ctx.progress();
+                    ctx.report_progress(progress, &format!("running {}", class_type));
+                }
+                None => return Ok(()),
+            },
+            "progress" => {
+                let value = data["value"].as_u64().unwrap_or(0);
+                let max = data["max"].as_u64().unwrap_or(0).max(1);
+                ctx.report_progress(
+                    value as f32 / max as f32,
+                    &format!("step {}/{}", value, max),
+                );
+            }
+            "execution_success" => return Ok(()),
+            "execution_error" => {
+                return Err(ExecutionError::NodeExecution {
+                    node_id: ctx.node_id,
+                    error: format!(
+                        "ComfyUI node {} ({}) failed: {}",
+                        data["node_id"].as_str().unwrap_or("?"),
+                        data["node_type"].as_str().unwrap_or("?"),
+                        data["exception_message"].as_str().unwrap_or("unknown error")
+                    ),
+                });
+            }
+            "execution_interrupted" => {
+                return Err(ExecutionError::NodeExecution {
+                    node_id: ctx.node_id,
+                    error: "ComfyUI prompt was interrupted on the server".to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_deadline(deadline: std::time::Instant, node_id: NodeId) -> Result<(), ExecutionError> {
+    if std::time::Instant::now() > deadline {
+        return Err(ExecutionError::NodeExecution {
+            node_id,
+            error: "ComfyUI did not finish within the timeout".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Poll ComfyUI `/history/{prompt_id}` until the prompt is done.
+fn poll_comfyui_result(
+    ctx: &ExecutionContext,
+    base_url: &str,
+    prompt_id: &str,
+    deadline: std::time::Instant,
+) -> Result<serde_json::Value, ExecutionError> {
+    let node_id = ctx.node_id;
+    let url = format!("{}/history/{}", base_url, prompt_id);
+
+    loop {
+        ctx.check_cancelled()?;
+        check_deadline(deadline, node_id)?;
+
+        let resp = HttpClient::global()
+            .get(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .call()
+            .map_err(|e| ExecutionError::NodeExecution {
+                node_id,
+                error: format!("ComfyUI history poll failed: {}", e),
+            })?;
+
+        let history: serde_json::Value =
+            resp.into_json().map_err(|e| ExecutionError::NodeExecution {
+                node_id,
+                error: format!("Failed to parse history response: {}", e),
+            })?;
+
+        if let Some(entry) = history.get(prompt_id) {
+            return Ok(entry.clone());
+        }
+
+        // Sleep in short steps so cancellation is noticed promptly.
+        for _ in 0..(500 / EVENT_POLL_INTERVAL.as_millis()) {
+            ctx.check_cancelled()?;
+            std::thread::sleep(EVENT_POLL_INTERVAL);
+        }
+    }
+}
+
+/// Stop `prompt_id` on the server: interrupt it if it is running and drop it
+/// from the queue if it is still waiting. Best effort; the run is already
+/// cancelled on the Ambara side.
+fn cancel_prompt(base_url: &str, prompt_id: &str) {
+    let client = HttpClient::global();
+    // Servers that predate per-prompt interrupts ignore the body and
+    // interrupt whatever is running.
+    let _ = client
+        .post(&format!("{}/interrupt", base_url))
+        .timeout(std::time::Duration::from_secs(10))
+        .retries(0)
+        .send_json(&serde_json::json!({ "prompt_id": prompt_id }));
+    let _ = client
+        .post(&format!("{}/queue", base_url))
+        .timeout(std::time::Duration::from_secs(10))
+        .retries(0)
+        .send_json(&serde_json::json!({ "delete": [prompt_id] }));
+}
+
+/// Submit a ComfyUI workflow prompt and return the prompt_id.
+///
+/// Events for the prompt are sent to the event stream of `client_id`.
+fn submit_prompt(
+    base_url: &str,
+    workflow: &serde_json::Value,
+    client_id: &str,
+    node_id: NodeId,
+) -> Result<String, ExecutionError> {
+    let body = serde_json::json!({ "prompt": workflow, "client_id": client_id });
+    let resp = HttpClient::global()
+        .post(&format!("{}/prompt", base_url))
+        .timeout(std::time::Duration::from_secs(30))
+        .send_json(&body)
+        .map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("ComfyUI prompt submission failed: {}", e),
+        })?;
+
+    let resp_json: serde_json::Value =
+        resp.into_json().map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("Failed to parse prompt response: {}", e),
+        })?;
+
+    resp_json
+        .get("prompt_id")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| ExecutionError::NodeExecution {
+            node_id,
+            error: "ComfyUI response missing prompt_id".to_string(),
+        })
+}
+
+/// Download an image from ComfyUI's `/view` endpoint.
+fn download_comfyui_image(
+    base_url: &str,
+    filename: &str,
+    subfolder: &str,
+    folder_type: &str,
+    node_id: NodeId,
+) -> Result<image::DynamicImage, ExecutionError> {
+    let url = format!(
+        "{}/view?filename={}&subfolder={}&type={}",
+        base_url, filename, subfolder, folder_type,
+    );
+    let resp = HttpClient::global()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(30))
+        .call()
+        .map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("ComfyUI image download failed: {}", e),
+        })?;
+
+    let mut bytes = Vec::new();
+    resp.into_reader()
+        .take(100 * 1024 * 1024)
+        .read_to_end(&mut bytes)
+        .map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("Failed to read image bytes: {}", e),
+        })?;
+
+    image::load_from_memory(&bytes).map_err(|e| ExecutionError::NodeExecution {
+        node_id,
+        error: format!("Failed to decode ComfyUI image: {}", e),
+    })
+}
+
+/// Extract the first output image filename from a ComfyUI history entry.
+fn first_output_image(
+    history: &serde_json::Value,
+) -> Option<(String, String, String)> {
+    let outputs = history.get("outputs")?;
+    outputs.as_object()?.values().find_map(output_image)
+}
+
+/// The first image listed in one node's history `outputs` entry.
+fn output_image(node_output: &serde_json::Value) -> Option<(String, String, String)> {
+    let img = node_output.get("images")?.as_array()?.first()?;
+    let filename = img.get("filename")?.as_str()?.to_string();
+    let subfolder = img
+        .get("subfolder")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let folder_type = img
+        .get("type")
+        .and_then(|v| v.as_str())
+        .unwrap_or("output")
+        .to_string();
+    Some((filename, subfolder, folder_type))
+}
+
+fn validate_comfyui_url(ctx: &ValidationContext, param: &str) -> Result<(), ValidationError> {
+    let url = ctx.get_string(param).unwrap_or("");
+    if url.is_empty() {
+        return Err(ValidationError::ConstraintViolation {
+            node_id: ctx.node_id,
+            parameter: param.to_string(),
+            error: "ComfyUI server URL cannot be empty".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Check the node's server-side names (checkpoint, LoRA, sampler, ...)
+/// against the lists offered by the server at `comfyui_url`.
+///
+/// An unreachable server only produces a warning, so graphs can still be
+/// edited and validated offline.
+fn validate_server_names(
+    ctx: &ValidationContext,
+    filter_id: &str,
+) -> Result<Vec<ValidationWarning>, ValidationError> {
+    let url = ctx.get_string("comfyui_url").unwrap_or("");
+    let parameters: Vec<_> = discovery::parameters_of(filter_id).collect();
+    if url.is_empty() || parameters.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let options = match discovery::DiscoveryCache::global().options(url) {
+        Ok(options) => options,
+        Err(error) => {
+            let names: Vec<_> = parameters.iter().map(|(name, _)| *name).collect();
+            return Ok(vec![ValidationWarning {
+                message: format!(
+                    "Could not check {} against ComfyUI at {}: {}",
+                    names.join(", "),
+                    url,
+                    error
+                ),
+                node_id: Some(ctx.node_id),
+                suggestion: Some(
+                    "Start the ComfyUI server, or refresh its options once it is reachable"
+                        .to_string(),
+                ),
+            }]);
+        }
+    };
+    for (parameter, kind) in parameters {
+        let Some(available) = options.get(kind) else {
+            continue;
+        };
+        let value = ctx.get_string(parameter).unwrap_or("");
+        if !available.iter().any(|name| name == value) {
+            let listed = match available.len() {
+                0 => "none".to_string(),
+                n if n <= 5 => available.join(", "),
+                n => format!("{}, … ({} more)", available[..5].join(", "), n - 5),
+            };
+            return Err(ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: parameter.to_string(),
+                error: format!("'{}' is not available on {} (available: {})", value, url, listed),
+            });
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Read the [`ComfyRef`] on input `port`.
+fn comfy_ref(ctx: &ExecutionContext, port: &str) -> Result<ComfyRef, ExecutionError> {
+    let value = ctx.get_input(port)?;
+    ComfyRef::from_value(value).ok_or_else(|| ExecutionError::NodeExecution {
+        node_id: ctx.node_id,
+        error: format!("Input '{port}' must come from a ComfyUI node"),
+    })
+}
+
+/// Read the [`ComfyRef`] on optional input `port`, if connected.
+fn optional_comfy_ref(
+    ctx: &ExecutionContext,
+    port: &str,
+) -> Result<Option<ComfyRef>, ExecutionError> {
+    if ctx.inputs().contains_key(port) {
+        comfy_ref(ctx, port).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// Merge `input` into `builder` and return the link to it.
+fn link(
+    builder: &mut PromptBuilder,
+    input: &ComfyRef,
+    node_id: NodeId,
+) -> Result<serde_json::Value, ExecutionError> {
+    builder
+        .input(input)
+        .map_err(|error| ExecutionError::NodeExecution { node_id, error })
+}
+
+/// Link to `vae`, or to the VAE of the checkpoint the chain was built on.
+fn vae_link(
+    builder: &mut PromptBuilder,
+    vae: Option<&ComfyRef>,
+    node_id: NodeId,
+) -> Result<serde_json::Value, ExecutionError> {
+    if let Some(vae) = vae {
+        return link(builder, vae, node_id);
+    }
+    match builder.nodes_of_class("CheckpointLoaderSimple")[..] {
+        [checkpoint] => Ok(serde_json::json!([checkpoint, 2])),
+        _ => Err(ExecutionError::NodeExecution {
+            node_id,
+            error: "Connect vae_ref: the chain does not use exactly one checkpoint".to_string(),
+        }),
+    }
+}
+
+/// Upload `image` to ComfyUI's input folder and return the name a
+/// `LoadImage` node expects for it.
+fn upload_image(
+    base_url: &str,
+    image: &ImageValue,
+    node_id: NodeId,
+) -> Result<String, ExecutionError> {
+    let rgba = image
+        .get_image()
+        .ok_or_else(|| ExecutionError::NodeExecution {
+            node_id,
+            error: "Input image data not loaded".to_string(),
+        })?
+        .to_rgba8();
+    let mut png_bytes = Vec::new();
+    {
+        let encoder = image::codecs::png::PngEncoder::new(Cursor::new(&mut png_bytes));
+        use image::ImageEncoder;
+        encoder
+            .write_image(
+                rgba.as_raw(),
+                rgba.width(),
+                rgba.height(),
+                image::ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| ExecutionError::NodeExecution {
+                node_id,
+                error: format!("Failed to encode input image: {}", e),
+            })?;
+    }
+
+    // Name uploads by content: several images of one prompt (an init image
+    // and its mask) must not overwrite each other.
+    let filename = format!("ambara_{:016x}.png", prompt::fnv1a(&png_bytes));
+    // ComfyUI only accepts multipart uploads.
+    let boundary = format!("ambara-{}", uuid::Uuid::new_v4().simple());
+    let mut body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"image\"; \
+         filename=\"{filename}\"\r\nContent-Type: image/png\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(&png_bytes);
+    body.extend_from_slice(
+        format!(
+            "\r\n--{boundary}\r\nContent-Disposition: form-data; name=\"overwrite\"\r\n\r\n\
+             true\r\n--{boundary}--\r\n"
+        )
+        .as_bytes(),
+    );
+
+    let resp = HttpClient::global()
+        .post(&format!("{}/upload/image", base_url))
+        .timeout(std::time::Duration::from_secs(30))
+        // Content-named uploads overwrite themselves; resending is safe.
+        .idempotent(true)
+        .set(
+            "Content-Type",
+            &format!("multipart/form-data; boundary={boundary}"),
+        )
+        .send_bytes(&body)
+        .map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("Image upload to ComfyUI failed: {}", e),
+        })?;
+    let resp_json: serde_json::Value =
+        resp.into_json().map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("Failed to parse upload response: {}", e),
+        })?;
+
+    let name = resp_json
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ExecutionError::NodeExecution {
+            node_id,
+            error: "ComfyUI upload response missing name".to_string(),
+        })?;
+    Ok(match resp_json.get("subfolder").and_then(|v| v.as_str()) {
+        Some(subfolder) if !subfolder.is_empty() => format!("{}/{}", subfolder, name),
+        _ => name.to_string(),
+    })
+}
+
+/// Compile `builder`, run the prompt and return the first image saved by
+/// the fragment node `save_node`.
+///
+/// The prompt is submitted once for all sinks that compile it (see
+/// [`Submissions`]).
+fn run_prompt(
+    ctx: &mut ExecutionContext,
+    builder: PromptBuilder,
+    save_node: &str,
+    timeout_secs: u64,
+) -> Result<image::DynamicImage, ExecutionError> {
+    let node_id = ctx.node_id;
+    let base_url = builder.server().to_string();
+    let compiled = builder.compile();
+    let save_id = compiled.id_of(save_node).unwrap_or_default().to_string();
+
+    let key = prompt::fnv1a(format!("{base_url}\n{}", compiled.prompt).as_bytes());
+    let history = Submissions::global().history(key, || {
+        execute_prompt(ctx, &base_url, &compiled.prompt, timeout_secs)
+    })?;
+
+    let (filename, subfolder, folder_type) = history
+        .get("outputs")
+        .and_then(|outputs| outputs.get(&save_id))
+        .and_then(output_image)
+        .ok_or_else(|| ExecutionError::NodeExecution {
+            node_id,
+            error: "ComfyUI produced no output images".to_string(),
+        })?;
+    download_comfyui_image(&base_url, &filename, &subfolder, &folder_type, node_id)
+}
+
+fn image_output(img: image::DynamicImage) -> Value {
+    Value::Image(ImageValue::new(image::DynamicImage::ImageRgba8(
+        img.to_rgba8(),
+    )))
+}
+
+// ============================================================================
+// ComfyCheckpointLoader — load a Stable Diffusion checkpoint
+// ============================================================================
+
+/// Load a Stable Diffusion checkpoint model through ComfyUI.
+///
+/// This node sends a workflow to ComfyUI that loads a checkpoint and outputs
+/// the model name reference for downstream nodes to use.
+#[derive(Debug, Clone)]
+pub struct ComfyCheckpointLoader;
+
+impl FilterNode for ComfyCheckpointLoader {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_checkpoint_loader", "ComfyUI Checkpoint Loader")
+            .description(
+                "Load a Stable Diffusion checkpoint through ComfyUI. \
+                 Outputs model/CLIP/VAE references for downstream ComfyUI nodes.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("model")
+            .tag("checkpoint")
+            .output(
+                PortDefinition::output("model_ref", PortType::String)
+                    .with_description("Model reference name for downstream nodes"),
+            )
+            .output(
+                PortDefinition::output("clip_ref", PortType::String)
+                    .with_description("CLIP model reference"),
+            )
+            .output(
+                PortDefinition::output("vae_ref", PortType::String)
+                    .with_description("VAE model reference"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "comfyui_url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:8188".to_string()),
+                )
+                .with_description("ComfyUI server URL")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "checkpoint_name",
+                    PortType::String,
+                    Value::String("v1-5-pruned-emaonly.safetensors".to_string()),
+                )
+                .with_description(
+                    "Checkpoint filename as it appears in ComfyUI's models/checkpoints folder",
+                )
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        validate_comfyui_url(ctx, "comfyui_url")?;
+        let ckpt = ctx.get_string("checkpoint_name").unwrap_or("");
+        if ckpt.is_empty() {
+            return Err(ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: "checkpoint_name".to_string(),
+                error: "Checkpoint name cannot be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn validate_resources(
+        &self,
+        ctx: &ValidationContext,
+    ) -> Result<Vec<ValidationWarning>, ValidationError> {
+        validate_server_names(ctx, "comfy_checkpoint_loader")
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let comfyui_url = ctx.get_string("comfyui_url")?;
+        let checkpoint_name = ctx.get_string("checkpoint_name")?;
+
+        let mut builder = PromptBuilder::new(comfyui_url);
+        let loader = builder.add(
+            "CheckpointLoaderSimple",
+            serde_json::json!({ "ckpt_name": checkpoint_name }),
+        );
+
+        ctx.set_output("model_ref", builder.output(&loader, 0).to_value())?;
+        ctx.set_output("clip_ref", builder.output(&loader, 1).to_value())?;
+        ctx.set_output("vae_ref", builder.output(&loader, 2).to_value())?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyClipTextEncode — CLIP text encoding
+// ============================================================================
+
+/// Encode text prompts via CLIP through ComfyUI.
+#[derive(Debug, Clone)]
+pub struct ComfyClipTextEncode;
+
+impl FilterNode for ComfyClipTextEncode {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_clip_text_encode", "ComfyUI CLIP Text Encode")
+            .description(
+                "Encode a text prompt into CLIP conditioning using ComfyUI. \
+                 Connect the clip_ref from a Checkpoint Loader.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("clip")
+            .tag("prompt")
+            .input(
+                PortDefinition::input("clip_ref", PortType::String)
+                    .with_description("CLIP model reference from Checkpoint Loader"),
+            )
+            .output(
+                PortDefinition::output("conditioning", PortType::String)
+                    .with_description("Encoded conditioning reference"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "text",
+                    PortType::String,
+                    Value::String("a beautiful landscape, high quality".to_string()),
+                )
+                .with_description("Text prompt to encode")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        let text = ctx.get_string("text").unwrap_or("");
+        if text.is_empty() {
+            return Err(ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: "text".to_string(),
+                error: "Text prompt cannot be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let clip = comfy_ref(ctx, "clip_ref")?;
+        let text = ctx.get_string("text")?;
+
+        let mut builder = PromptBuilder::from_ref(&clip);
+        let clip = link(&mut builder, &clip, ctx.node_id)?;
+        let encode = builder.add(
+            "CLIPTextEncode",
+            serde_json::json!({ "text": text, "clip": clip }),
+        );
+
+        ctx.set_output("conditioning", builder.output(&encode, 0).to_value())?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyKSampler — denoise/sample latent image
+// ============================================================================
+
+/// KSampler node — the core sampling/denoising step in a Stable Diffusion
+/// workflow, orchestrated through ComfyUI.
+#[derive(Debug, Clone)]
+pub struct ComfyKSampler;
+
+impl FilterNode for ComfyKSampler {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_ksampler", "ComfyUI KSampler")
+            .description(
+                "Run the KSampler (denoising) step through ComfyUI with full control \
+                 over sampler, scheduler, steps, CFG scale, and seed. Starts from an \
+                 empty latent, or from an input image (img2img) with an optional \
+                 inpainting mask.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("sampler")
+            .tag("generation")
+            .input(
+                PortDefinition::input("model_ref", PortType::String)
+                    .with_description("Model reference from Checkpoint Loader"),
+            )
+            .input(
+                PortDefinition::input("positive", PortType::String)
+                    .with_description("Positive conditioning from CLIP Text Encode"),
+            )
+            .input(
+                PortDefinition::input("negative", PortType::String)
+                    .with_description("Negative conditioning from CLIP Text Encode")
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("image", PortType::Image)
+                    .with_description(
+                        "Image to start from (img2img); sampled at its own size",
+                    )
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("mask", PortType::Image)
+                    .with_description("Inpainting mask for the image; white areas are repainted")
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("vae_ref", PortType::String)
+                    .with_description(
+                        "VAE that encodes the image; defaults to the chain's checkpoint",
+                    )
+                    .optional(),
+            )
+            .output(
+                PortDefinition::output("latent_ref", PortType::String)
+                    .with_description("Latent image reference for VAE Decode"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "comfyui_url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:8188".to_string()),
+                )
+                .with_description(
+                    "Server to check names against; the prompt runs on the Checkpoint Loader's server",
+                )
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new("seed", PortType::Integer, Value::Integer(0))
+                    .with_description("Random seed (0 = random)")
+                    .with_constraint(Constraint::Range {
+                        min: 0.0,
+                        max: 2147483647.0,
+                    }),
+            )
+            .parameter(
+                ParameterDefinition::new("steps", PortType::Integer, Value::Integer(20))
+                    .with_description("Number of sampling steps")
+                    .with_constraint(Constraint::Range {
+                        min: 1.0,
+                        max: 150.0,
+                    })
+                    .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .parameter(
+                ParameterDefinition::new("cfg_scale", PortType::Float, Value::Float(7.0))
+                    .with_description("Classifier-free guidance scale")
+                    .with_constraint(Constraint::Range {
+                        min: 1.0,
+                        max: 30.0,
+                    })
+                    .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "sampler_name",
+                    PortType::String,
+                    Value::String("euler".to_string()),
+                )
+                .with_description("Sampler algorithm (euler, euler_ancestral, dpmpp_2m, etc.)")
+                .with_ui_hint(UiHint::Dropdown {
+                    options: vec![
+                        "euler".to_string(), "euler_ancestral".to_string(),
+                        "heun".to_string(), "dpm_2".to_string(),
+                        "dpm_2_ancestral".to_string(), "lms".to_string(),
+                        "dpmpp_2m".to_string(), "dpmpp_2m_sde".to_string(),
+                        "dpmpp_sde".to_string(), "ddim".to_string(),
+                        "uni_pc".to_string(),
+                    ],
+                }),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "scheduler",
+                    PortType::String,
+                    Value::String("normal".to_string()),
+                )
+                .with_description("Noise scheduler")
+                .with_ui_hint(UiHint::Dropdown {
+                    options: vec![
+                        "normal".to_string(), "karras".to_string(),
+                        "exponential".to_string(), "sgm_uniform".to_string(),
+                        "simple".to_string(), "ddim_uniform".to_string(),
+                    ],
+                }),
+            )
+            .parameter(
+                ParameterDefinition::new("denoise", PortType::Float, Value::Float(1.0))
+                    .with_description(
+                        "Denoise strength (1.0 = full denoise; lower keeps more of the input image)",
+                    )
+                    .with_constraint(Constraint::Range {
+                        min: 0.0,
+                        max: 1.0,
+                    })
+                    .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .parameter(
+                ParameterDefinition::new("width", PortType::Integer, Value::Integer(512))
+                    .with_description("Latent image width (ignored with an input image)")
+                    .with_constraint(Constraint::Range {
+                        min: 64.0,
+                        max: 2048.0,
+                    })
+                    .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .parameter(
+                ParameterDefinition::new("height", PortType::Integer, Value::Integer(512))
+                    .with_description("Latent image height (ignored with an input image)")
+                    .with_constraint(Constraint::Range {
+                        min: 64.0,
+                        max: 2048.0,
+                    })
+                    .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .parameter(
+                ParameterDefinition::new("batch_size", PortType::Integer, Value::Integer(1))
+                    .with_description(
+                        "Number of images to generate per batch (ignored with an input image)",
+                    )
+                    .with_constraint(Constraint::Range {
+                        min: 1.0,
+                        max: 16.0,
+                    }),
+            )
+            .parameter(
+                ParameterDefinition::new("grow_mask_by", PortType::Integer, Value::Integer(6))
+                    .with_description("Pixels to grow the inpainting mask by")
+                    .with_constraint(Constraint::Range {
+                        min: 0.0,
+                        max: 64.0,
+                    }),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "timeout_secs",
+                    PortType::Integer,
+                    Value::Integer(300),
+                )
+                .with_description("Unused: VAE Decode waits for the compiled prompt")
+                .with_constraint(Constraint::Range {
+                    min: 10.0,
+                    max: 3600.0,
+                }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        validate_comfyui_url(ctx, "comfyui_url")
+    }
+
+    fn validate_resources(
+        &self,
+        ctx: &ValidationContext,
+    ) -> Result<Vec<ValidationWarning>, ValidationError> {
+        validate_server_names(ctx, "comfy_ksampler")
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let model = comfy_ref(ctx, "model_ref")?;
+        let positive = comfy_ref(ctx, "positive")?;
+        let negative = optional_comfy_ref(ctx, "negative")?;
+
+        let seed = ctx.get_integer("seed").unwrap_or(0);
+        let steps = ctx.get_integer("steps").unwrap_or(20);
+        let cfg_scale = ctx.get_float("cfg_scale").unwrap_or(7.0);
+        let sampler_name = ctx.get_string("sampler_name").unwrap_or("euler");
+        let scheduler = ctx.get_string("scheduler").unwrap_or("normal");
+        let denoise = ctx.get_float("denoise").unwrap_or(1.0);
+        let width = ctx.get_integer("width").unwrap_or(512);
+        let height = ctx.get_integer("height").unwrap_or(512);
+        let batch_size = ctx.get_integer("batch_size").unwrap_or(1);
+        let grow_mask_by = ctx.get_integer("grow_mask_by").unwrap_or(6);
+
+        let mut builder = PromptBuilder::from_ref(&model);
+        let model = link(&mut builder, &model, ctx.node_id)?;
+        let positive = link(&mut builder, &positive, ctx.node_id)?;
+        let negative = match negative {
+            Some(negative) => link(&mut builder, &negative, ctx.node_id)?,
+            // Without a negative prompt, sample against empty conditioning.
+            None => {
+                let zero = builder.add(
+                    "ConditioningZeroOut",
+                    serde_json::json!({ "conditioning": positive }),
+                );
+                serde_json::json!([zero, 0])
+            }
+        };
+        let latent = match ctx.get_input_image_optional("image") {
+            Some(image) => {
+                let vae = optional_comfy_ref(ctx, "vae_ref")?;
+                let vae = vae_link(&mut builder, vae.as_ref(), ctx.node_id)?;
+                let uploaded = upload_image(builder.server(), image, ctx.node_id)?;
+                let pixels = builder.add("LoadImage", serde_json::json!({ "image": uploaded }));
+                match ctx.get_input_image_optional("mask") {
+                    Some(mask) => {
+                        let uploaded = upload_image(builder.server(), mask, ctx.node_id)?;
+                        let mask = builder.add(
+                            "LoadImageMask",
+                            serde_json::json!({ "image": uploaded, "channel": "red" }),
+                        );
+                        builder.add(
+                            "VAEEncodeForInpaint",
+                            serde_json::json!({
+                                "pixels": [pixels, 0],
+                                "vae": vae,
+                                "mask": [mask, 0],
+                                "grow_mask_by": grow_mask_by,
+                            }),
+                        )
+                    }
+                    None => builder.add(
+                        "VAEEncode",
+                        serde_json::json!({ "pixels": [pixels, 0], "vae": vae }),
+                    ),
+                }
+            }
+            None if ctx.has_input("mask") => {
+                return Err(ExecutionError::NodeExecution {
+                    node_id: ctx.node_id,
+                    error: "A mask needs an image to inpaint".to_string(),
+                })
+            }
+            None => builder.add(
+                "EmptyLatentImage",
+                serde_json::json!({ "width": width, "height": height, "batch_size": batch_size }),
+            ),
+        };
+        let sampler = builder.add(
+            "KSampler",
+            serde_json::json!({
+                "model": model,
+                "positive": positive,
+                "negative": negative,
+                "latent_image": [latent, 0],
+                "seed": seed,
+                "steps": steps,
+                "cfg": cfg_scale,
+                "sampler_name": sampler_name,
+                "scheduler": scheduler,
+                "denoise": denoise,
+            }),
+        );
+
+        ctx.set_output("latent_ref", builder.output(&sampler, 0).to_value())?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyVaeDecode — decode latent image to pixels
+// ============================================================================
+
+/// Decode a latent image to pixels through ComfyUI, producing an image output.
+#[derive(Debug, Clone)]
+pub struct ComfyVaeDecode;
+
+impl FilterNode for ComfyVaeDecode {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_vae_decode", "ComfyUI VAE Decode")
+            .description(
+                "Decode a latent image produced by KSampler into a pixel image \
+                 through ComfyUI. Submits the connected chain of ComfyUI nodes as \
+                 one prompt and retrieves the generated image from the server.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("vae")
+            .tag("decode")
+            .input(
+                PortDefinition::input("latent_ref", PortType::String)
+                    .with_description("Latent image reference from KSampler"),
+            )
+            .input(
+                PortDefinition::input("vae_ref", PortType::String)
+                    .with_description("VAE model reference from Checkpoint Loader")
+                    .optional(),
+            )
+            .output(
+                PortDefinition::output("image", PortType::Image)
+                    .with_description("Decoded pixel image"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "comfyui_url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:8188".to_string()),
+                )
+                .with_description("Unused: the prompt runs on the Checkpoint Loader's server")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "timeout_secs",
+                    PortType::Integer,
+                    Value::Integer(300),
+                )
+                .with_description("Maximum time to wait for result")
+                .with_constraint(Constraint::Range {
+                    min: 10.0,
+                    max: 3600.0,
+                }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        validate_comfyui_url(ctx, "comfyui_url")
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let timeout = ctx.get_integer("timeout_secs").unwrap_or(300) as u64;
+        let latent = comfy_ref(ctx, "latent_ref")?;
+        let vae = optional_comfy_ref(ctx, "vae_ref")?;
+
+        let mut builder = PromptBuilder::from_ref(&latent);
+        let samples = link(&mut builder, &latent, ctx.node_id)?;
+        let vae = vae_link(&mut builder, vae.as_ref(), ctx.node_id)?;
+        let decode = builder.add(
+            "VAEDecode",
+            serde_json::json!({ "samples": samples, "vae": vae }),
+        );
+        let save = builder.add(
+            "SaveImage",
+            serde_json::json!({ "images": [decode, 0], "filename_prefix": "ambara" }),
+        );
+
+        let img = run_prompt(ctx, builder, &save, timeout)?;
+        ctx.set_output("image", image_output(img))?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyLoraLoader — load a LoRA model
+// ============================================================================
+
+/// Load a LoRA (Low-Rank Adaptation) model through ComfyUI.
+#[derive(Debug, Clone)]
+pub struct ComfyLoraLoader;
+
+impl FilterNode for ComfyLoraLoader {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_lora_loader", "ComfyUI LoRA Loader")
+            .description(
+                "Load a LoRA (Low-Rank Adaptation) model to modify a base checkpoint. \
+                 Adjusts both the model and CLIP weights.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("lora")
+            .tag("model")
+            .input(
+                PortDefinition::input("model_ref", PortType::String)
+                    .with_description("Base model reference from Checkpoint Loader"),
+            )
+            .input(
+                PortDefinition::input("clip_ref", PortType::String)
+                    .with_description("CLIP model reference from Checkpoint Loader"),
+            )
+            .output(
+                PortDefinition::output("model_ref", PortType::String)
+                    .with_description("Model reference with LoRA applied"),
+            )
+            .output(
+                PortDefinition::output("clip_ref", PortType::String)
+                    .with_description("CLIP reference with LoRA applied"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "comfyui_url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:8188".to_string()),
+                )
+                .with_description(
+                    "Server to check names against; the prompt runs on the Checkpoint Loader's server",
+                )
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "lora_name",
+                    PortType::String,
+                    Value::String(String::new()),
+                )
+                .with_description(
+                    "LoRA filename in ComfyUI's models/loras folder (e.g. detail_tweaker.safetensors)",
+                )
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "model_strength",
+                    PortType::Float,
+                    Value::Float(1.0),
+                )
+                .with_description("LoRA strength for the model (UNet) weights")
+                .with_constraint(Constraint::Range {
+                    min: -2.0,
+                    max: 2.0,
+                })
+                .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "clip_strength",
+                    PortType::Float,
+                    Value::Float(1.0),
+                )
+                .with_description("LoRA strength for the CLIP weights")
+                .with_constraint(Constraint::Range {
+                    min: -2.0,
+                    max: 2.0,
+                })
+                .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        validate_comfyui_url(ctx, "comfyui_url")?;
+        let lora = ctx.get_string("lora_name").unwrap_or("");
+        if lora.is_empty() {
+            return Err(ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: "lora_name".to_string(),
+                error: "LoRA name cannot be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn validate_resources(
+        &self,
+        ctx: &ValidationContext,
+    ) -> Result<Vec<ValidationWarning>, ValidationError> {
+        validate_server_names(ctx, "comfy_lora_loader")
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let model = comfy_ref(ctx, "model_ref")?;
+        let clip = comfy_ref(ctx, "clip_ref")?;
+        let lora_name = ctx.get_string("lora_name")?;
+        let model_strength = ctx.get_float("model_strength").unwrap_or(1.0);
+        let clip_strength = ctx.get_float("clip_strength").unwrap_or(1.0);
+
+        let mut builder = PromptBuilder::from_ref(&model);
+        let model = link(&mut builder, &model, ctx.node_id)?;
+        let clip = link(&mut builder, &clip, ctx.node_id)?;
+        let lora = builder.add(
+            "LoraLoader",
+            serde_json::json!({
+                "lora_name": lora_name,
+                "strength_model": model_strength,
+                "strength_clip": clip_strength,
+                "model": model,
+                "clip": clip,
+            }),
+        );
+
+        ctx.set_output("model_ref", builder.output(&lora, 0).to_value())?;
+        ctx.set_output("clip_ref", builder.output(&lora, 1).to_value())?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyImageUpscale — upscale an image through ComfyUI
+// ============================================================================
+
+/// Upscale an image using an upscale model through ComfyUI.
+#[derive(Debug, Clone)]
+pub struct ComfyImageUpscale;
+
+impl FilterNode for ComfyImageUpscale {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_image_upscale", "ComfyUI Image Upscale")
+            .description(
+                "Upscale an image using a model-based upscaler (RealESRGAN, etc.) \
+                 through ComfyUI.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("upscale")
+            .tag("super-resolution")
+            .input(
+                PortDefinition::input("image", PortType::Image)
+                    .with_description("Input image to upscale"),
+            )
+            .output(
+                PortDefinition::output("image", PortType::Image)
+                    .with_description("Upscaled output image"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "comfyui_url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:8188".to_string()),
+                )
+                .with_description("ComfyUI server URL")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "upscale_model",
+                    PortType::String,
+                    Value::String("RealESRGAN_x4plus.pth".to_string()),
+                )
+                .with_description("Upscale model filename in ComfyUI's upscale_models folder")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "timeout_secs",
+                    PortType::Integer,
+                    Value::Integer(300),
+                )
+                .with_description("Maximum time to wait for result")
+                .with_constraint(Constraint::Range {
+                    min: 10.0,
+                    max: 3600.0,
+                }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        validate_comfyui_url(ctx, "comfyui_url")
+    }
+
+    fn validate_resources(
+        &self,
+        ctx: &ValidationContext,
+    ) -> Result<Vec<ValidationWarning>, ValidationError> {
+        validate_server_names(ctx, "comfy_image_upscale")
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let comfyui_url = ctx.get_string("comfyui_url")?;
+        let upscale_model = ctx.get_string("upscale_model")?;
+        let timeout = ctx.get_integer("timeout_secs").unwrap_or(300) as u64;
+        let input_image = ctx.get_input_image("image")?;
+
+        let mut builder = PromptBuilder::new(comfyui_url);
+        let uploaded = upload_image(builder.server(), input_image, ctx.node_id)?;
+        let load = builder.add("LoadImage", serde_json::json!({ "image": uploaded }));
+        let model = builder.add(
+            "UpscaleModelLoader",
+            serde_json::json!({ "model_name": upscale_model }),
+        );
+        let upscale = builder.add(
+            "ImageUpscaleWithModel",
+            serde_json::json!({ "upscale_model": [model, 0], "image": [load, 0] }),
+        );
+        let save = builder.add(
+            "SaveImage",
+            serde_json::json!({ "images": [upscale, 0], "filename_prefix": "ambara_upscale" }),
+        );
+
+        let img = run_prompt(ctx, builder, &save, timeout)?;
+        ctx.set_output("image", image_output(img))?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyControlNetApply — apply a ControlNet to conditioning
+// ============================================================================
+
+/// Apply a ControlNet model to conditioning through ComfyUI.
+#[derive(Debug, Clone)]
+pub struct ComfyControlNetApply;
+
+impl FilterNode for ComfyControlNetApply {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_controlnet_apply", "ComfyUI ControlNet Apply")
+            .description(
+                "Apply a ControlNet to guide image generation using a control image \
+                 (edges, depth, pose, etc.) through ComfyUI.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("controlnet")
+            .tag("guided-generation")
+            .input(
+                PortDefinition::input("conditioning", PortType::String)
+                    .with_description("Conditioning from CLIP Text Encode"),
+            )
+            .input(
+                PortDefinition::input("control_image", PortType::Image)
+                    .with_description("Control image (edge map, depth map, pose, etc.)"),
+            )
+            .output(
+                PortDefinition::output("conditioning", PortType::String)
+                    .with_description("Conditioning with ControlNet applied"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "controlnet_name",
+                    PortType::String,
+                    Value::String(String::new()),
+                )
+                .with_description(
+                    "ControlNet model filename (e.g. control_v11p_sd15_canny.pth)",
+                )
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new("strength", PortType::Float, Value::Float(1.0))
+                    .with_description("ControlNet influence strength")
+                    .with_constraint(Constraint::Range {
+                        min: 0.0,
+                        max: 2.0,
+                    })
+                    .with_ui_hint(UiHint::Slider { logarithmic: false }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        let cn = ctx.get_string("controlnet_name").unwrap_or("");
+        if cn.is_empty() {
+            return Err(ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: "controlnet_name".to_string(),
+                error: "ControlNet model name cannot be empty".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let conditioning = comfy_ref(ctx, "conditioning")?;
+        let control_image = ctx.get_input_image("control_image")?;
+        let controlnet_name = ctx.get_string("controlnet_name")?;
+        let strength = ctx.get_float("strength").unwrap_or(1.0);
+
+        let mut builder = PromptBuilder::from_ref(&conditioning);
+        let conditioning = link(&mut builder, &conditioning, ctx.node_id)?;
+        let uploaded = upload_image(builder.server(), control_image, ctx.node_id)?;
+        let image = builder.add("LoadImage", serde_json::json!({ "image": uploaded }));
+        let controlnet = builder.add(
+            "ControlNetLoader",
+            serde_json::json!({ "control_net_name": controlnet_name }),
+        );
+        let apply = builder.add(
+            "ControlNetApply",
+            serde_json::json!({
+                "conditioning": conditioning,
+                "control_net": [controlnet, 0],
+                "image": [image, 0],
+                "strength": strength,
+            }),
+        );
+
+        ctx.set_output("conditioning", builder.output(&apply, 0).to_value())?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// ComfyWorkflowRunner — run arbitrary ComfyUI workflows
+// ============================================================================
+
+/// Run an arbitrary ComfyUI workflow JSON and retrieve the output image.
+///
+/// This is the most flexible node — you paste an entire ComfyUI workflow
+/// (exported as API format JSON) and it runs it, returning the first output image.
+#[derive(Debug, Clone)]
+pub struct ComfyWorkflowRunner;
+
+impl FilterNode for ComfyWorkflowRunner {
+    fn metadata(&self) -> NodeMetadata {
+        NodeMetadata::builder("comfy_workflow_runner", "ComfyUI Workflow Runner")
+            .description(
+                "Run an arbitrary ComfyUI workflow (API-format JSON) and retrieve \
+                 the first output image. Paste the exported workflow JSON.",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .tag("comfyui")
+            .tag("workflow")
+            .tag("advanced")
+            .input(
+                PortDefinition::input("image", PortType::Image)
+                    .with_description("Optional input image (uploaded to ComfyUI for the workflow)")
+                    .optional(),
+            )
+            .output(
+                PortDefinition::output("image", PortType::Image)
+                    .with_description("First output image from the workflow"),
+            )
+            .output(
+                PortDefinition::output("raw_history", PortType::String)
+                    .with_description("Full ComfyUI history JSON for debugging"),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "comfyui_url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:8188".to_string()),
+                )
+                .with_description("ComfyUI server URL")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "workflow_json",
+                    PortType::String,
+                    Value::String(String::new()),
+                )
+                .with_description(
+                    "ComfyUI workflow in API format JSON (use 'Save (API format)' in ComfyUI)",
+                )
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "timeout_secs",
+                    PortType::Integer,
+                    Value::Integer(600),
+                )
+                .with_description("Maximum time to wait for workflow completion")
+                .with_constraint(Constraint::Range {
+                    min: 10.0,
+                    max: 7200.0,
+                }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        validate_comfyui_url(ctx, "comfyui_url")?;
+        let wf = ctx.get_string("workflow_json").unwrap_or("");
+        if wf.is_empty() {
+            return Err(ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: "workflow_json".to_string(),
+                error: "Workflow JSON cannot be empty".to_string(),
+            });
+        }
+        // Validate it's valid JSON
+        serde_json::from_str::<serde_json::Value>(wf).map_err(|e| {
+            ValidationError::ConstraintViolation {
+                node_id: ctx.node_id,
+                parameter: "workflow_json".to_string(),
+                error: format!("Invalid workflow JSON: {}", e),
+            }
+        })?;
+        Ok(())
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let comfyui_url = ctx.get_string("comfyui_url")?.to_string();
+        let workflow_str = ctx.get_string("workflow_json")?.to_string();
+        let timeout = ctx.get_integer("timeout_secs").unwrap_or(600) as u64;
+
+        let workflow: serde_json::Value =
+            serde_json::from_str(&workflow_str).map_err(|e| ExecutionError::NodeExecution {
+                node_id: ctx.node_id,
+                error: format!("Invalid workflow JSON: {}", e),
+            })?;
+
+        let history = execute_prompt(ctx, &comfyui_url, &workflow, timeout)?;
+
+        let raw = serde_json::to_string_pretty(&history).unwrap_or_default();
+        ctx.set_output("raw_history", Value::String(raw))?;
+
+        let (filename, subfolder, folder_type) =
+            first_output_image(&history).ok_or_else(|| ExecutionError::NodeExecution {
+                node_id: ctx.node_id,
+                error: "Workflow produced no output images".to_string(),
+            })?;
+
+        let img = download_comfyui_image(
+            &comfyui_url,
+            &filename,
+            &subfolder,
+            &folder_type,
+            ctx.node_id,
+        )?;
+
+        ctx.set_output("image", image_output(img))?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock_server::MockComfy;
+    use super::*;
+    use crate::execution::engine::ExecutionEngine;
+    use crate::graph::structure::{GraphNode, ProcessingGraph};
+
+    fn string(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    fn checkpoint(graph: &mut ProcessingGraph, server: &MockComfy) -> NodeId {
+        graph.add_node(
+            GraphNode::new(Box::new(ComfyCheckpointLoader))
+                .with_parameter("comfyui_url", string(&server.url))
+                .with_parameter("checkpoint_name", string("sd15.safetensors")),
+        )
+    }
+
+    fn encode(graph: &mut ProcessingGraph, clip: (NodeId, &str), text: &str) -> NodeId {
+        let node = graph.add_node(
+            GraphNode::new(Box::new(ComfyClipTextEncode)).with_parameter("text", string(text)),
+        );
+        graph.connect(clip.0, clip.1, node, "clip_ref").unwrap();
+        node
+    }
+
+    mod compilation {
+        use super::*;
+
+        #[test]
+        fn text_to_image_chain_runs_as_one_prompt() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let ckpt = checkpoint(&mut graph, &server);
+            let lora = graph.add_node(
+                GraphNode::new(Box::new(ComfyLoraLoader))
+                    .with_parameter("lora_name", string("detail.safetensors"))
+                    .with_parameter("model_strength", Value::Float(0.8))
+                    .with_parameter("clip_strength", Value::Float(0.5)),
+            );
+            graph.connect(ckpt, "model_ref", lora, "model_ref").unwrap();
+            graph.connect(ckpt, "clip_ref", lora, "clip_ref").unwrap();
+            let positive = encode(&mut graph, (lora, "clip_ref"), "a red fox");
+            let negative = encode(&mut graph, (lora, "clip_ref"), "blurry");
+            let sampler = graph.add_node(
+                GraphNode::new(Box::new(ComfyKSampler))
+                    .with_parameter("seed", Value::Integer(42))
+                    .with_parameter("steps", Value::Integer(25)),
+            );
+            graph.connect(lora, "model_ref", sampler, "model_ref").unwrap();
+            graph.connect(positive, "conditioning", sampler, "positive").unwrap();
+            graph.connect(negative, "conditioning", sampler, "negative").unwrap();
+            let decode = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(sampler, "latent_ref", decode, "latent_ref").unwrap();
+            graph.connect(ckpt, "vae_ref", decode, "vae_ref").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+            assert_eq!(server.prompts().len(), 1);
+            assert_eq!(
+                server.prompts()[0],
+                serde_json::json!({
+                    "1": {
+                        "class_type": "CheckpointLoaderSimple",
+                        "inputs": { "ckpt_name": "sd15.safetensors" }
+                    },
+                    "2": {
+                        "class_type": "EmptyLatentImage",
+                        "inputs": { "width": 512, "height": 512, "batch_size": 1 }
+                    },
+                    "3": {
+                        "class_type": "LoraLoader",
+                        "inputs": {
+                            "lora_name": "detail.safetensors",
+                            "strength_model": 0.8,
+                            "strength_clip": 0.5,
+                            "model": ["1", 0],
+                            "clip": ["1", 1]
+                        }
+                    },
+                    "4": {
+                        "class_type": "CLIPTextEncode",
+                        "inputs": { "text": "a red fox", "clip": ["3", 1] }
+                    },
+                    "5": {
+                        "class_type": "CLIPTextEncode",
+                        "inputs": { "text": "blurry", "clip": ["3", 1] }
+                    },
+                    "6": {
+                        "class_type": "KSampler",
+                        "inputs": {
+                            "model": ["3", 0],
+                            "positive": ["4", 0],
+                            "negative": ["5", 0],
+                            "latent_image": ["2", 0],
+                            "seed": 42,
+                            "steps": 25,
+                            "cfg": 7.0,
+                            "sampler_name": "euler",
+                            "scheduler": "normal",
+                            "denoise": 1.0
+                        }
+                    },
+                    "7": {
+                        "class_type": "VAEDecode",
+                        "inputs": { "samples": ["6", 0], "vae": ["1", 2] }
+                    },
+                    "8": {
+                        "class_type": "SaveImage",
+                        "inputs": { "images": ["7", 0], "filename_prefix": "ambara" }
+                    }
+                })
+            );
+            assert_eq!(server.requests("GET", "/view?filename=8.png").len(), 1);
+
+            let image = result.outputs[&decode]["image"].as_image().unwrap();
+            assert_eq!((image.metadata.width, image.metadata.height), (3, 2));
+        }
+
+        #[test]
+        fn sinks_of_one_prompt_share_its_submission() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let ckpt = checkpoint(&mut graph, &server);
+            let positive = encode(&mut graph, (ckpt, "clip_ref"), "a red fox");
+            let sampler = graph.add_node(GraphNode::new(Box::new(ComfyKSampler)));
+            graph.connect(ckpt, "model_ref", sampler, "model_ref").unwrap();
+            graph.connect(positive, "conditioning", sampler, "positive").unwrap();
+            let decodes: Vec<_> = (0..2)
+                .map(|_| {
+                    let decode = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+                    graph.connect(sampler, "latent_ref", decode, "latent_ref").unwrap();
+                    decode
+                })
+                .collect();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert!(result.errors.is_empty(), "{:?}", result.errors);
+            assert_eq!(server.prompts().len(), 1);
+            for decode in decodes {
+                assert!(result.outputs[&decode]["image"].as_image().is_some());
+            }
+        }
+
+        #[test]
+        fn missing_negative_and_vae_fall_back_to_the_chain() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let ckpt = checkpoint(&mut graph, &server);
+            let positive = encode(&mut graph, (ckpt, "clip_ref"), "a red fox");
+            let sampler = graph.add_node(GraphNode::new(Box::new(ComfyKSampler)));
+            graph.connect(ckpt, "model_ref", sampler, "model_ref").unwrap();
+            graph.connect(positive, "conditioning", sampler, "positive").unwrap();
+            let decode = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(sampler, "latent_ref", decode, "latent_ref").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+            let prompt = &server.prompts()[0];
+            let sampler = &prompt["5"]["inputs"];
+            assert_eq!(prompt["5"]["class_type"], "KSampler");
+            let zero = &prompt[sampler["negative"][0].as_str().unwrap()];
+            assert_eq!(zero["class_type"], "ConditioningZeroOut");
+            assert_eq!(zero["inputs"]["conditioning"], sampler["positive"]);
+            assert_eq!(prompt["6"]["inputs"]["vae"], serde_json::json!(["1", 2]));
+        }
+
+        #[test]
+        fn plain_strings_are_not_comfy_references() {
+            let mut graph = ProcessingGraph::new();
+            let text = graph.add_node(
+                GraphNode::new(Box::new(crate::filters::builtin::StringConstant))
+                    .with_parameter("value", string("clip:sd15.safetensors")),
+            );
+            let node = encode(&mut graph, (text, "value"), "a red fox");
+
+            let result = ExecutionEngine::new().execute(&graph, None);
+            let err = match result {
+                Ok(result) => {
+                    let (_, err) = result.errors.into_iter().find(|(id, _)| *id == node).unwrap();
+                    err.to_string()
+                }
+                Err(err) => err.to_string(),
+            };
+            assert!(err.to_string().contains("must come from a ComfyUI node"), "{err}");
+        }
+    }
+
+    mod queue {
+        use super::super::mock_server::Mode;
+        use super::*;
+        use crate::execution::engine::ExecutionOptions;
+        use crate::execution::progress::{CancellationToken, ProgressUpdate};
+        use std::sync::{Arc, Mutex};
+
+        /// Checkpoint → CLIP → KSampler → VAE Decode; returns the decoder.
+        fn chain(graph: &mut ProcessingGraph, server: &MockComfy) -> NodeId {
+            let ckpt = checkpoint(graph, server);
+            let positive = encode(graph, (ckpt, "clip_ref"), "a red fox");
+            let sampler = graph.add_node(GraphNode::new(Box::new(ComfyKSampler)));
+            graph.connect(ckpt, "model_ref", sampler, "model_ref").unwrap();
+            graph.connect(positive, "conditioning", sampler, "positive").unwrap();
+            let decode = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(sampler, "latent_ref", decode, "latent_ref").unwrap();
+            decode
+        }
+
+        /// Run the graph, recording the decoder's progress messages and
+        /// cancelling `token` once `cancel_at` is reported.
+        fn run(
+            graph: &ProcessingGraph,
+            decode: NodeId,
+            token: CancellationToken,
+            cancel_at: Option<&'static str>,
+        ) -> (Result<(), String>, Vec<String>) {
+            let messages = Arc::new(Mutex::new(Vec::new()));
+            let seen = Arc::clone(&messages);
+            let cancel = token.clone();
+            let options = ExecutionOptions::new()
+                .with_cancellation(token)
+                .with_progress(move |update| {
+                    if let ProgressUpdate::NodeProgress { node_id, message: Some(message), .. } = update {
+                        assert_eq!(node_id, decode);
+                        if cancel_at == Some(message.as_str()) {
+                            cancel.cancel();
+                        }
+                        seen.lock().unwrap().push(message);
+                    }
+                });
+            let result = ExecutionEngine::new()
+                .execute(graph, Some(options))
+                .map(|result| assert!(result.errors.is_empty(), "{:?}", result.errors))
+                .map_err(|e| e.to_string());
+            let messages = messages.lock().unwrap().clone();
+            (result, messages)
+        }
+
+        #[test]
+        fn websocket_events_become_node_progress() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let decode = chain(&mut graph, &server);
+
+            let (result, messages) = run(&graph, decode, CancellationToken::new(), None);
+            result.unwrap();
+            assert_eq!(server.requests("GET", "/ws?clientId=").len(), 1);
+            let steps: Vec<_> = messages.iter().skip_while(|m| *m != "running KSampler").collect();
+            assert_eq!(steps[..3], ["running KSampler", "step 1/2", "step 2/2"]);
+            assert_eq!(messages.last().unwrap(), "running SaveImage");
+            // The result is fetched once, after the stream reported completion.
+            assert_eq!(server.requests("GET", "/history/").len(), 1);
+        }
+
+        #[test]
+        fn cancellation_interrupts_and_dequeues_the_prompt() {
+            let server = MockComfy::start_with(Mode::Hold);
+            let mut graph = ProcessingGraph::new();
+            let decode = chain(&mut graph, &server);
+
+            let (result, messages) = run(&graph, decode, CancellationToken::new(), Some("step 1/2"));
+            let cancelled = crate::core::error::AmbaraError::Execution(ExecutionError::Cancelled);
+            assert_eq!(result, Err(cancelled.to_string()));
+            assert_eq!(messages.last().unwrap(), "step 1/2");
+
+            let interrupt = server.requests("POST", "/interrupt");
+            assert_eq!(interrupt.len(), 1);
+            let body: serde_json::Value = serde_json::from_slice(&interrupt[0].body).unwrap();
+            assert_eq!(body, serde_json::json!({ "prompt_id": "prompt-1" }));
+            let queue = server.requests("POST", "/queue");
+            assert_eq!(queue.len(), 1);
+            let body: serde_json::Value = serde_json::from_slice(&queue[0].body).unwrap();
+            assert_eq!(body, serde_json::json!({ "delete": ["prompt-1"] }));
+        }
+
+        #[test]
+        fn servers_without_websocket_are_polled() {
+            let server = MockComfy::start_with(Mode::NoWebsocket);
+            let mut graph = ProcessingGraph::new();
+            let decode = chain(&mut graph, &server);
+
+            let (result, messages) = run(&graph, decode, CancellationToken::new(), None);
+            result.unwrap();
+            assert!(messages.is_empty(), "{:?}", messages);
+            assert_eq!(server.requests("GET", "/history/").len(), 1);
+            assert!(server.requests("POST", "/interrupt").is_empty());
+        }
+    }
+
+    mod img2img {
+        use super::super::mock_server::upload;
+        use super::*;
+        use crate::filters::builtin::color::Invert;
+
+        /// The node of `class_type` in a submitted prompt.
+        fn node_of<'a>(prompt: &'a serde_json::Value, class_type: &str) -> (&'a String, &'a serde_json::Value) {
+            let mut nodes = prompt
+                .as_object()
+                .unwrap()
+                .iter()
+                .filter(|(_, node)| node["class_type"] == class_type);
+            let node = nodes.next().unwrap_or_else(|| panic!("no {class_type} in {prompt}"));
+            assert!(nodes.next().is_none(), "several {class_type} in {prompt}");
+            node
+        }
+
+        fn sampler(graph: &mut ProcessingGraph, ckpt: NodeId, positive: NodeId) -> NodeId {
+            let sampler = graph.add_node(
+                GraphNode::new(Box::new(ComfyKSampler)).with_parameter("denoise", Value::Float(0.5)),
+            );
+            graph.connect(ckpt, "model_ref", sampler, "model_ref").unwrap();
+            graph.connect(positive, "conditioning", sampler, "positive").unwrap();
+            sampler
+        }
+
+        #[test]
+        fn generated_image_is_inpainted_in_the_same_graph() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let ckpt = checkpoint(&mut graph, &server);
+            let positive = encode(&mut graph, (ckpt, "clip_ref"), "a red fox");
+            let first = sampler(&mut graph, ckpt, positive);
+            let generated = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(first, "latent_ref", generated, "latent_ref").unwrap();
+            let mask = graph.add_node(
+                GraphNode::new(Box::new(Invert)).with_parameter("use_gpu", Value::Boolean(false)),
+            );
+            graph.connect(generated, "image", mask, "image").unwrap();
+            let refine = sampler(&mut graph, ckpt, positive);
+            graph.connect(generated, "image", refine, "image").unwrap();
+            graph.connect(mask, "image", refine, "mask").unwrap();
+            let refined = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(refine, "latent_ref", refined, "latent_ref").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert!(result.errors.is_empty(), "{:?}", result.errors);
+            assert!(result.outputs[&refined]["image"].as_image().is_some());
+
+            // The generated image and its inverse were uploaded as PNGs.
+            let uploads: Vec<_> = server
+                .requests("POST", "/upload/image")
+                .iter()
+                .map(|r| upload(&r.body))
+                .collect();
+            assert_eq!(uploads.len(), 2);
+            for ((_, bytes), color) in uploads.iter().zip([[200, 10, 10, 255], [55, 245, 245, 255]]) {
+                let image = image::load_from_memory(bytes).unwrap().to_rgba8();
+                assert_eq!(image.dimensions(), (3, 2));
+                assert!(image.pixels().all(|p| p.0 == color));
+            }
+            assert_ne!(uploads[0].0, uploads[1].0);
+
+            let prompts = server.prompts();
+            assert_eq!(prompts.len(), 2);
+            assert!(prompts[0].as_object().unwrap().values().any(|n| n["class_type"] == "EmptyLatentImage"));
+            let prompt = &prompts[1];
+            let (pixels, load) = node_of(prompt, "LoadImage");
+            assert_eq!(load["inputs"]["image"], uploads[0].0.as_str());
+            let (mask, load_mask) = node_of(prompt, "LoadImageMask");
+            assert_eq!(
+                load_mask["inputs"],
+                serde_json::json!({ "image": uploads[1].0, "channel": "red" })
+            );
+            let (encoded, encode) = node_of(prompt, "VAEEncodeForInpaint");
+            assert_eq!(
+                encode["inputs"],
+                serde_json::json!({
+                    "pixels": [pixels, 0],
+                    "vae": ["1", 2],
+                    "mask": [mask, 0],
+                    "grow_mask_by": 6
+                })
+            );
+            let (_, ksampler) = node_of(prompt, "KSampler");
+            assert_eq!(ksampler["inputs"]["latent_image"], serde_json::json!([encoded, 0]));
+            assert_eq!(ksampler["inputs"]["denoise"], 0.5);
+            assert!(prompt.as_object().unwrap().values().all(|n| n["class_type"] != "EmptyLatentImage"));
+        }
+
+        #[test]
+        fn image_without_mask_is_vae_encoded() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let ckpt = checkpoint(&mut graph, &server);
+            let positive = encode(&mut graph, (ckpt, "clip_ref"), "a red fox");
+            let first = sampler(&mut graph, ckpt, positive);
+            let generated = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(first, "latent_ref", generated, "latent_ref").unwrap();
+            let refine = sampler(&mut graph, ckpt, positive);
+            graph.connect(generated, "image", refine, "image").unwrap();
+            graph.connect(ckpt, "vae_ref", refine, "vae_ref").unwrap();
+            let refined = graph.add_node(GraphNode::new(Box::new(ComfyVaeDecode)));
+            graph.connect(refine, "latent_ref", refined, "latent_ref").unwrap();
+
+            let result = ExecutionEngine::new().execute(&graph, None).unwrap();
+            assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+            let prompt = &server.prompts()[1];
+            let (pixels, _) = node_of(prompt, "LoadImage");
+            let (encoded, encode) = node_of(prompt, "VAEEncode");
+            assert_eq!(
+                encode["inputs"],
+                serde_json::json!({ "pixels": [pixels, 0], "vae": ["1", 2] })
+            );
+            let (_, ksampler) = node_of(prompt, "KSampler");
+            assert_eq!(ksampler["inputs"]["latent_image"], serde_json::json!([encoded, 0]));
+        }
+    }
+
+    mod server_names {
+        use super::*;
+        use crate::validation::pipeline::ValidationPipeline;
+
+        fn unreachable_url() -> String {
+            // Bind and drop a listener to get a port nobody listens on.
+            crate::filters::test_server::listen().1
+        }
+
+        #[test]
+        fn names_missing_on_the_server_fail_validation() {
+            let server = MockComfy::start();
+            let mut graph = ProcessingGraph::new();
+            let ckpt = checkpoint(&mut graph, &server);
+            let report = ValidationPipeline::default_pipeline().validate(&graph);
+            assert!(report.success, "{:?}", report.errors);
+
+            graph
+                .get_node_mut(ckpt)
+                .unwrap()
+                .parameters
+                .insert("checkpoint_name".to_string(), string("sd1.5.safetensors"));
+            let report = ValidationPipeline::default_pipeline().validate(&graph);
+            let errors: Vec<_> = report.errors.iter().map(ToString::to_string).collect();
+            assert_eq!(errors.len(), 1, "{errors:?}");
+            let expected = format!(
+                "'sd1.5.safetensors' is not available on {} (available: sd15.safetensors, sdxl.safetensors)",
+                server.url
+            );
+            assert!(errors[0].contains(&expected), "{errors:?}");
+        }
+
+        #[test]
+        fn unreachable_servers_only_warn() {
+            let url = unreachable_url();
+            let mut ctx = ValidationContext::new(NodeId::new());
+            ctx.add_parameter("comfyui_url", string(&url));
+            ctx.add_parameter("sampler_name", string("not_a_sampler"));
+            ctx.add_parameter("scheduler", string("normal"));
+
+            let warnings = ComfyKSampler.validate_resources(&ctx).unwrap();
+            assert_eq!(warnings.len(), 1);
+            let expected = format!("Could not check sampler_name, scheduler against ComfyUI at {url}");
+            assert!(warnings[0].message.starts_with(&expected), "{}", warnings[0].message);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Compilation of `comfy_*` node chains into a single ComfyUI prompt.
+//!
+//! ComfyUI executes whole prompts: a checkpoint loader, LoRAs, encoders, a
+//! sampler and a decoder only make sense together. The `comfy_*` nodes
+//! therefore do not talk to the server one by one. Each node lowers itself
+//! into one or more API-format prompt nodes and outputs a [`ComfyRef`]: the
+//! prompt fragment built so far plus the slot it refers to. A downstream
+//! node merges the fragments of its inputs with [`PromptBuilder::input`] and
+//! adds its own nodes. The node that needs pixels (VAE decode, upscale)
+//! [`compile`](PromptBuilder::compile)s the accumulated fragment, which
+//! holds exactly the connected region of `comfy_*` nodes upstream of it,
+//! and submits it once.
+//!
+//! Fragment node IDs are content hashes, so the same loader reached through
+//! several branches collapses into one node. [`PromptBuilder::compile`]
+//! renumbers the nodes `"1"`, `"2"`, ... in dependency order so the
+//! submitted prompt reads like one exported from ComfyUI.
+
+use crate::core::types::Value;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// An API-format prompt: node ID → `{"class_type", "inputs"}`.
+pub type PromptMap = serde_json::Map<String, serde_json::Value>;
+
+/// Output of a `comfy_*` node: one slot of a node in a prompt that has not
+/// been submitted yet.
+///
+/// Travels between Ambara nodes as a JSON string on the `*_ref` and
+/// `conditioning` ports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComfyRef {
+    /// Server the prompt will run on.
+    pub server: String,
+    /// The fragment producing the referenced slot, with all its ancestors.
+    pub prompt: PromptMap,
+    /// Fragment ID of the producing node.
+    pub node: String,
+    /// Output slot on the producing node.
+    pub slot: u32,
+}
+
+impl ComfyRef {
+    /// Wrap the reference for an Ambara port.
+    pub fn to_value(&self) -> Value {
+        Value::String(serde_json::to_string(self).unwrap_or_default())
+    }
+
+    /// Read a reference produced by [`ComfyRef::to_value`].
+    pub fn from_value(value: &Value) -> Option<Self> {
+        serde_json::from_str(value.as_string()?).ok()
+    }
+}
+
+/// Builds the prompt fragment of one `comfy_*` node.
+#[derive(Debug, Clone)]
+pub struct PromptBuilder {
+    server: String,
+    prompt: PromptMap,
+}
+
+impl PromptBuilder {
+    /// Start an empty fragment for `server`.
+    pub fn new(server: impl Into<String>) -> Self {
+        Self {
+            server: server.into().trim_end_matches('/').to_string(),
+            prompt: PromptMap::new(),
+        }
+    }
+
+    /// Start from the fragment behind `input`, adopting its server.
+    pub fn from_ref(input: &ComfyRef) -> Self {
+        Self {
+            server: input.server.clone(),
+            prompt: input.prompt.clone(),
+        }
+    }
+
+    /// The server this fragment targets.
+    pub fn server(&self) -> &str {
+        &self.server
+    }
+
+    /// Merge `input`'s fragment and return the `[node, slot]` link to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if `input` targets a different server.
+    pub fn input(&mut self, input: &ComfyRef) -> Result<serde_json::Value, String> {
+        if input.server.trim_end_matches('/') != self.server {
+            return Err(format!(
+                "cannot combine ComfyUI nodes for {} and {} in one prompt",
+                input.server, self.server
+            ));
+        }
+        for (id, node) in &input.prompt {
+            self.prompt.entry(id.clone()).or_insert_with(|| node.clone());
+        }
+        Ok(serde_json::json!([input.node, input.slot]))
+    }
+
+    /// Add a node and return its fragment ID.
+    pub fn add(&mut self, class_type: &str, inputs: serde_json::Value) -> String {
+        let node = serde_json::json!({ "class_type": class_type, "inputs": inputs });
+        let id = format!("{:016x}", fnv1a(node.to_string().as_bytes()));
+        self.prompt.insert(id.clone(), node);
+        id
+    }
+
+    /// A reference to `slot` of node `id` in this fragment.
+    pub fn output(&self, id: &str, slot: u32) -> ComfyRef {
+        ComfyRef {
+            server: self.server.clone(),
+            prompt: self.prompt.clone(),
+            node: id.to_string(),
+            slot,
+        }
+    }
+
+    /// Fragment IDs of the nodes of type `class_type`.
+    pub fn nodes_of_class(&self, class_type: &str) -> Vec<&str> {
+        self.prompt
+            .iter()
+            .filter(|(_, node)| node["class_type"] == class_type)
+            .map(|(id, _)| id.as_str())
+            .collect()
+    }
+
+    /// Renumber the fragment into the prompt to submit.
+    pub fn compile(self) -> CompiledPrompt {
+        // Kahn's algorithm; ties are broken by class type, then fragment ID,
+        // so the numbering only depends on the prompt's content.
+        let deps: HashMap<&str, Vec<&str>> = self
+            .prompt
+            .iter()
+            .map(|(id, node)| (id.as_str(), links(&node["inputs"], &self.prompt)))
+            .collect();
+        let key = |id: &'_ str| {
+            let class = self.prompt[id]["class_type"].as_str().unwrap_or_default();
+            (class.to_string(), id.to_string())
+        };
+        let mut remaining: HashMap<&str, usize> =
+            deps.iter().map(|(id, d)| (*id, d.len())).collect();
+        let mut ready: BTreeSet<(String, String)> = remaining
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(id, _)| key(id))
+            .collect();
+
+        let mut ids: HashMap<String, String> = HashMap::new();
+        while let Some((_, id)) = ready.pop_first() {
+            ids.insert(id.clone(), (ids.len() + 1).to_string());
+            for (dependent, d) in &deps {
+                let uses = d.iter().filter(|dep| **dep == id).count();
+                if uses > 0 {
+                    let count = remaining.get_mut(dependent).expect("known node");
+                    *count -= uses;
+                    if *count == 0 {
+                        ready.insert(key(dependent));
+                    }
+                }
+            }
+        }
+
+        let mut prompt = PromptMap::new();
+        for (id, node) in &self.prompt {
+            let Some(new_id) = ids.get(id) else {
+                // Unreachable for fragments built from hashes; a cycle would
+                // need a node to contain its own ID.
+                continue;
+            };
+            let mut node = node.clone();
+            if let Some(inputs) = node["inputs"].as_object_mut() {
+                for value in inputs.values_mut() {
+                    if let Some(target) = link_target(value, &self.prompt) {
+                        value[0] = serde_json::Value::String(ids[target].clone());
+                    }
+                }
+            }
+            prompt.insert(new_id.clone(), node);
+        }
+        CompiledPrompt {
+            prompt: serde_json::Value::Object(prompt),
+            ids,
+        }
+    }
+}
+
+/// A prompt ready for `/prompt`.
+#[derive(Debug, Clone)]
+pub struct CompiledPrompt {
+    /// The API-format prompt.
+    pub prompt: serde_json::Value,
+    ids: HashMap<String, String>,
+}
+
+impl CompiledPrompt {
+    /// The submitted ID of the node with fragment ID `fragment_id`.
+    pub fn id_of(&self, fragment_id: &str) -> Option<&str> {
+        self.ids.get(fragment_id).map(String::as_str)
+    }
+}
+
+/// The node a `[node, slot]` input value links to, if it is a link.
+fn link_target<'a>(value: &serde_json::Value, prompt: &'a PromptMap) -> Option<&'a str> {
+    let link = value.as_array().filter(|l| l.len() == 2 && l[1].is_u64())?;
+    prompt
+        .get_key_value(link[0].as_str()?)
+        .map(|(id, _)| id.as_str())
+}
+
+fn links<'a>(inputs: &serde_json::Value, prompt: &'a PromptMap) -> Vec<&'a str> {
+    inputs
+        .as_object()
+        .map(|inputs| {
+            inputs
+                .values()
+                .filter_map(|v| link_target(v, prompt))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 64-bit FNV-1a; stable across builds, unlike `DefaultHasher`.
+pub(super) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loader(server: &str) -> (PromptBuilder, String) {
+        let mut builder = PromptBuilder::new(server);
+        let id = builder.add(
+            "CheckpointLoaderSimple",
+            serde_json::json!({ "ckpt_name": "sd15.safetensors" }),
+        );
+        (builder, id)
+    }
+
+    #[test]
+    fn refs_round_trip_through_values() {
+        let (builder, id) = loader("http://comfy:8188/");
+        let clip = builder.output(&id, 1);
+        assert_eq!(clip.server, "http://comfy:8188");
+        assert_eq!(ComfyRef::from_value(&clip.to_value()), Some(clip));
+        assert_eq!(ComfyRef::from_value(&Value::String("ckpt:x".into())), None);
+    }
+
+    #[test]
+    fn shared_ancestors_collapse_and_compile_in_dependency_order() {
+        let (base, ckpt) = loader("http://comfy");
+        let clip = base.output(&ckpt, 1);
+
+        let mut positive = PromptBuilder::from_ref(&clip);
+        let link = positive.input(&clip).unwrap();
+        let pos = positive.add("CLIPTextEncode", serde_json::json!({ "text": "a", "clip": link }));
+        let mut negative = PromptBuilder::from_ref(&clip);
+        let link = negative.input(&clip).unwrap();
+        let neg = negative.add("CLIPTextEncode", serde_json::json!({ "text": "b", "clip": link }));
+
+        let mut sampler = PromptBuilder::new("http://comfy");
+        let p = sampler.input(&positive.output(&pos, 0)).unwrap();
+        let n = sampler.input(&negative.output(&neg, 0)).unwrap();
+        let ks = sampler.add("KSampler", serde_json::json!({ "positive": p, "negative": n }));
+        assert_eq!(sampler.nodes_of_class("CheckpointLoaderSimple").len(), 1);
+
+        let compiled = sampler.compile();
+        assert_eq!(compiled.id_of(&ckpt), Some("1"));
+        assert_eq!(compiled.id_of(&ks), Some("4"));
+        let prompt = &compiled.prompt;
+        assert_eq!(prompt.as_object().unwrap().len(), 4);
+        let ks = &prompt["4"]["inputs"];
+        for (port, text) in [("positive", "a"), ("negative", "b")] {
+            let encoder = &prompt[ks[port][0].as_str().unwrap()];
+            assert_eq!(encoder["inputs"]["text"], text);
+            assert_eq!(encoder["inputs"]["clip"], serde_json::json!(["1", 1]));
+        }
+    }
+
+    #[test]
+    fn fragments_for_different_servers_do_not_mix() {
+        let (a, id) = loader("http://a");
+        let mut b = PromptBuilder::new("http://b");
+        assert!(b.input(&a.output(&id, 0)).is_err());
+    }
+}
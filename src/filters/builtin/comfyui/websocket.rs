@@ -0,0 +1,388 @@
+//! A minimal client for ComfyUI's `/ws` event stream.
+//!
+//! ComfyUI pushes JSON text messages (`status`, `executing`, `progress`,
+//! `execution_error`, ...) to the client ID a prompt was submitted with, and
+//! binary messages carrying sampler previews. This client implements just
+//! enough of RFC 6455 to follow those events over plain `ws://`: masked
+//! client frames, fragmented messages and ping/pong. Binary messages are
+//! skipped.
+//!
+//! The upgrade response is accepted on its `101` status alone; checking
+//! `Sec-WebSocket-Accept` would need SHA-1, and a server that answers `101`
+//! to a websocket request on `/ws` is speaking the protocol anyway.
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Timeout for connecting and for the upgrade handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest message accepted; ComfyUI events are a few hundred bytes.
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+const OP_CLOSE: u8 = 0x8;
+const OP_PING: u8 = 0x9;
+const OP_PONG: u8 = 0xA;
+
+/// An open `/ws` connection.
+pub struct EventStream {
+    stream: TcpStream,
+    /// Bytes received but not yet parsed into frames.
+    buffer: Vec<u8>,
+    /// Opcode and data of a message whose final frame has not arrived.
+    fragment: Option<(u8, Vec<u8>)>,
+}
+
+struct Frame {
+    fin: bool,
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+impl EventStream {
+    /// Open the event stream of the server at `base_url` for `client_id`.
+    ///
+    /// [`next_event`](Self::next_event) waits at most `poll_interval` for an
+    /// event, so callers can check for cancellation between events.
+    ///
+    /// # Errors
+    ///
+    /// Fails for `https://` servers, if the server cannot be reached, or if
+    /// it does not upgrade the connection.
+    pub fn connect(base_url: &str, client_id: &str, poll_interval: Duration) -> io::Result<Self> {
+        let rest = base_url.strip_prefix("http://").ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the event stream is only supported for http:// servers",
+            )
+        })?;
+        let (authority, prefix) = match rest.split_once('/') {
+            Some((authority, path)) => (authority, path.trim_end_matches('/')),
+            None => (rest, ""),
+        };
+        let prefix = if prefix.is_empty() {
+            String::new()
+        } else {
+            format!("/{prefix}")
+        };
+        let address = if authority.contains(':') {
+            authority.to_string()
+        } else {
+            format!("{authority}:80")
+        };
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("cannot resolve {authority}"),
+            )
+        })?;
+
+        let mut stream = TcpStream::connect_timeout(&address, HANDSHAKE_TIMEOUT)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let key = BASE64.encode(uuid::Uuid::new_v4().as_bytes());
+        write!(
+            stream,
+            "GET {prefix}/ws?clientId={client_id} HTTP/1.1\r\nHost: {authority}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        )?;
+
+        let mut buffer = Vec::new();
+        let head_len = loop {
+            if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+            if buffer.len() > 16 * 1024 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "oversized upgrade response",
+                ));
+            }
+            let mut chunk = [0; 1024];
+            let n = stream.read(&mut chunk)?;
+            if n == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            buffer.extend_from_slice(&chunk[..n]);
+        };
+        let head = String::from_utf8_lossy(&buffer[..head_len]).into_owned();
+        let status_line = head.lines().next().unwrap_or_default();
+        if status_line.split_whitespace().nth(1) != Some("101") {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("server refused the websocket upgrade: {status_line}"),
+            ));
+        }
+        // Frames sent right after the upgrade may share a read with it.
+        buffer.drain(..head_len);
+
+        stream.set_read_timeout(Some(poll_interval))?;
+        Ok(Self {
+            stream,
+            buffer,
+            fragment: None,
+        })
+    }
+
+    /// The next JSON event, or `None` if none arrived within the poll
+    /// interval.
+    ///
+    /// # Errors
+    ///
+    /// Fails when the connection is closed or the server breaks the
+    /// protocol.
+    pub fn next_event(&mut self) -> io::Result<Option<serde_json::Value>> {
+        loop {
+            while let Some(frame) = self.take_frame()? {
+                if let Some(text) = self.handle(frame)? {
+                    // Anything that is not JSON is not a ComfyUI event.
+                    if let Ok(event) = serde_json::from_str(&text) {
+                        return Ok(Some(event));
+                    }
+                }
+            }
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Read what the socket has; `false` if nothing arrived in time.
+    fn fill(&mut self) -> io::Result<bool> {
+        let mut chunk = [0; 8192];
+        match self.stream.read(&mut chunk) {
+            Ok(0) => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "event stream closed",
+            )),
+            Ok(n) => {
+                self.buffer.extend_from_slice(&chunk[..n]);
+                Ok(true)
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Split one complete frame off the buffer.
+    fn take_frame(&mut self) -> io::Result<Option<Frame>> {
+        let b = &self.buffer;
+        if b.len() < 2 {
+            return Ok(None);
+        }
+        let fin = b[0] & 0x80 != 0;
+        let opcode = b[0] & 0x0f;
+        let masked = b[1] & 0x80 != 0;
+        let (len, mut offset) = match b[1] & 0x7f {
+            126 if b.len() >= 4 => (u64::from(u16::from_be_bytes([b[2], b[3]])), 4),
+            127 if b.len() >= 10 => {
+                let mut len = [0; 8];
+                len.copy_from_slice(&b[2..10]);
+                (u64::from_be_bytes(len), 10)
+            }
+            126 | 127 => return Ok(None),
+            n => (u64::from(n), 2),
+        };
+        if len > MAX_MESSAGE_BYTES as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame too large",
+            ));
+        }
+        let mask = if masked {
+            if b.len() < offset + 4 {
+                return Ok(None);
+            }
+            offset += 4;
+            Some([b[offset - 4], b[offset - 3], b[offset - 2], b[offset - 1]])
+        } else {
+            None
+        };
+        let end = offset + len as usize;
+        if b.len() < end {
+            return Ok(None);
+        }
+
+        let mut payload = b[offset..end].to_vec();
+        if let Some(mask) = mask {
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+        }
+        self.buffer.drain(..end);
+        Ok(Some(Frame {
+            fin,
+            opcode,
+            payload,
+        }))
+    }
+
+    /// Apply a frame; returns the text of a message it completes.
+    fn handle(&mut self, frame: Frame) -> io::Result<Option<String>> {
+        let (opcode, data) = match frame.opcode {
+            OP_TEXT | OP_BINARY if self.fragment.is_some() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "new message inside a fragmented one",
+                ));
+            }
+            OP_TEXT | OP_BINARY => (frame.opcode, frame.payload),
+            OP_CONTINUATION => {
+                let (opcode, mut data) = self.fragment.take().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "unexpected continuation frame")
+                })?;
+                data.extend_from_slice(&frame.payload);
+                if data.len() > MAX_MESSAGE_BYTES {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "message too large",
+                    ));
+                }
+                (opcode, data)
+            }
+            OP_PING => {
+                self.send(OP_PONG, &frame.payload)?;
+                return Ok(None);
+            }
+            OP_PONG => return Ok(None),
+            OP_CLOSE => {
+                let _ = self.send(OP_CLOSE, &frame.payload[..frame.payload.len().min(2)]);
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "event stream closed by the server",
+                ));
+            }
+            other => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown websocket opcode {other:#x}"),
+                ));
+            }
+        };
+
+        if !frame.fin {
+            self.fragment = Some((opcode, data));
+            return Ok(None);
+        }
+        if opcode != OP_TEXT {
+            // Sampler previews.
+            return Ok(None);
+        }
+        String::from_utf8(data)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Send a single masked frame.
+    fn send(&mut self, opcode: u8, payload: &[u8]) -> io::Result<()> {
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            n if n < 126 => frame.push(0x80 | n as u8),
+            n if n <= usize::from(u16::MAX) => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(n as u16).to_be_bytes());
+            }
+            n => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(n as u64).to_be_bytes());
+            }
+        }
+        let mut mask = [0; 4];
+        mask.copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..4]);
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        self.stream.write_all(&frame)
+    }
+}
+
+impl Drop for EventStream {
+    fn drop(&mut self) {
+        // Normal closure; the server may already be gone.
+        let _ = self.send(OP_CLOSE, &1000u16.to_be_bytes());
+    }
+}
+
+/// Encode a server frame (unmasked), for test servers.
+#[cfg(test)]
+pub fn server_frame(opcode: u8, fin: bool, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![if fin { 0x80 } else { 0 } | opcode];
+    match payload.len() {
+        n if n < 126 => frame.push(n as u8),
+        n if n <= usize::from(u16::MAX) => {
+            frame.push(126);
+            frame.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        n => {
+            frame.push(127);
+            frame.extend_from_slice(&(n as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::test_server::{listen, read_request};
+
+    /// Accept one upgrade and write `frames` after the 101 response.
+    fn serve(frames: Vec<u8>) -> String {
+        let (listener, url) = listen();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&stream).unwrap();
+            assert_eq!(request.path(), "/ws?clientId=c1");
+            stream
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n")
+                .unwrap();
+            stream.write_all(&frames).unwrap();
+            // Answer to the ping, then the close on drop.
+            let mut rest = Vec::new();
+            let _ = stream.read_to_end(&mut rest);
+        });
+        url
+    }
+
+    #[test]
+    fn reads_fragmented_text_and_skips_binary_and_control_frames() {
+        let mut frames = server_frame(OP_BINARY, true, &[1, 2, 3]);
+        frames.extend(server_frame(OP_PING, true, b"hi"));
+        frames.extend(server_frame(OP_TEXT, false, br#"{"type":"prog"#));
+        frames.extend(server_frame(OP_CONTINUATION, true, br#"ress"}"#));
+        let long = format!(r#"{{"type":"status","pad":"{}"}}"#, "x".repeat(300));
+        frames.extend(server_frame(OP_TEXT, true, long.as_bytes()));
+        let url = serve(frames);
+
+        let mut events = EventStream::connect(&url, "c1", Duration::from_millis(50)).unwrap();
+        assert_eq!(events.next_event().unwrap().unwrap()["type"], "progress");
+        assert_eq!(events.next_event().unwrap().unwrap()["type"], "status");
+        assert!(events.next_event().unwrap().is_none());
+    }
+
+    #[test]
+    fn close_frames_end_the_stream() {
+        let url = serve(server_frame(OP_CLOSE, true, &1000u16.to_be_bytes()));
+        let mut events = EventStream::connect(&url, "c1", Duration::from_millis(50)).unwrap();
+        assert!(events.next_event().is_err());
+    }
+
+    #[test]
+    fn https_servers_are_not_supported() {
+        let err = EventStream::connect("https://comfy", "c1", Duration::from_millis(50))
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
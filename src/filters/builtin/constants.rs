@@ -6,6 +6,7 @@ use crate::core::node::{Category, FilterNode, NodeMetadata};
 use crate::core::port::{ParameterDefinition, PortDefinition, UiHint};
 use crate::core::types::{Color, PortType, Value};
 use crate::filters::registry::FilterRegistry;
+use std::collections::HashMap;
 
 /// Register constant nodes.
 pub fn register(registry: &mut FilterRegistry) {
@@ -16,6 +17,13 @@ pub fn register(registry: &mut FilterRegistry) {
     registry.register(|| Box::new(ColorConstant));
 }
 
+/// A constant's `value` output is its parameter.
+fn constant_output(ctx: &ValidationContext) -> HashMap<String, Value> {
+    ctx.get_parameter("value")
+        .map(|value| HashMap::from([("value".to_string(), value.clone())]))
+        .unwrap_or_default()
+}
+
 /// Integer constant node.
 #[derive(Debug, Clone)]
 pub struct IntegerConstant;
@@ -42,6 +50,10 @@ impl FilterNode for IntegerConstant {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        constant_output(ctx)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.get_integer("value").unwrap_or(0);
         ctx.set_output("value", Value::Integer(value))?;
@@ -79,6 +91,10 @@ impl FilterNode for FloatConstant {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        constant_output(ctx)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.get_float("value").unwrap_or(0.0);
         ctx.set_output("value", Value::Float(value))?;
@@ -117,6 +133,10 @@ impl FilterNode for StringConstant {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        constant_output(ctx)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.get_string("value").unwrap_or("").to_string();
         ctx.set_output("value", Value::String(value))?;
@@ -154,6 +174,10 @@ impl FilterNode for BooleanConstant {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        constant_output(ctx)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.get_bool("value").unwrap_or(false);
         ctx.set_output("value", Value::Boolean(value))?;
@@ -195,6 +219,10 @@ impl FilterNode for ColorConstant {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        constant_output(ctx)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.get_color("value").unwrap_or(Color { r: 255, g: 255, b: 255, a: 255 });
         ctx.set_output("value", Value::Color(value))?;
@@ -0,0 +1,829 @@
+//! Generic REST/JSON request filter.
+//!
+//! `http_request` covers model servers that would otherwise need their own
+//! filter: the method, URL, headers and body are templates filled from the
+//! node's inputs, and typed outputs are picked out of the JSON response with
+//! JSONPath-like selectors (see [`Selector`]).
+
+use crate::core::context::{ExecutionContext, ValidationContext};
+use crate::core::error::{ExecutionError, NodeId, ValidationError};
+use crate::core::node::{Category, FilterNode, NodeMetadata};
+use crate::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
+use crate::core::types::{ImageValue, PortType, Value};
+use crate::filters::http::HttpClient;
+use crate::filters::registry::FilterRegistry;
+use base64::Engine;
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+/// Register the HTTP request filter.
+pub fn register(registry: &mut FilterRegistry) {
+    registry.register(|| Box::new(HttpRequest));
+}
+
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+const BODY_FORMATS: [&str; 3] = ["json", "multipart", "none"];
+
+/// Image inputs, embedded as base64 PNG or sent as multipart files.
+const IMAGE_INPUTS: [&str; 2] = ["image", "image2"];
+
+/// Largest response body read.
+const MAX_RESPONSE_BYTES: u64 = 100 * 1024 * 1024;
+
+// ============================================================================
+// HttpRequest — configurable REST/JSON call
+// ============================================================================
+
+/// Call a REST endpoint built from templates and extract typed outputs from
+/// its JSON response.
+///
+/// Templates reference `{image}`, `{image2}` (base64 PNG), `{text}`,
+/// `{number}` and the keys of the `variables` parameter. In the JSON body a
+/// string that is exactly one placeholder is replaced by the value itself,
+/// so `"{number}"` becomes a JSON number; other strings are interpolated.
+/// Values substituted into the URL are percent-encoded.
+#[derive(Debug, Clone)]
+pub struct HttpRequest;
+
+impl FilterNode for HttpRequest {
+    fn metadata(&self) -> NodeMetadata {
+        let dropdown = |options: &[&str]| UiHint::Dropdown {
+            options: options.iter().map(|o| o.to_string()).collect(),
+        };
+        let one_of = |options: &[&str]| {
+            Constraint::OneOf(options.iter().map(|o| Value::String(o.to_string())).collect())
+        };
+        let selector = |name: &str, default: &str, output: &str| {
+            ParameterDefinition::new(name, PortType::String, Value::String(default.to_string()))
+                .with_description(format!(
+                    "Selector for the '{}' output, e.g. $.result[0].{} (empty = not extracted)",
+                    output, output
+                ))
+        };
+
+        NodeMetadata::builder("http_request", "HTTP Request")
+            .description(
+                "Call a REST/JSON endpoint built from templates and extract image, number, \
+                 text and array outputs from the response with JSONPath-like selectors",
+            )
+            .category(Category::Api)
+            .author("Ambara")
+            .version("1.0.0")
+            .input(
+                PortDefinition::input("image", PortType::Image)
+                    .with_description("Image available as {image} (base64 PNG) or as a multipart file")
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("image2", PortType::Image)
+                    .with_description("Second image, available as {image2}")
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("text", PortType::String)
+                    .with_description("Text available as {text}")
+                    .optional(),
+            )
+            .input(
+                PortDefinition::input("number", PortType::Float)
+                    .with_description("Number available as {number}")
+                    .optional(),
+            )
+            .output(
+                PortDefinition::output("image", PortType::Image)
+                    .with_description("Image selected by image_path, or an image response body"),
+            )
+            .output(
+                PortDefinition::output("number", PortType::Float)
+                    .with_description("Number selected by number_path"),
+            )
+            .output(
+                PortDefinition::output("text", PortType::String)
+                    .with_description("Text selected by text_path (JSON for non-strings)"),
+            )
+            .output(
+                PortDefinition::output("array", PortType::Array(Box::new(PortType::Any)))
+                    .with_description("Values selected by array_path"),
+            )
+            .output(
+                PortDefinition::output("response", PortType::String)
+                    .with_description("Raw response body (empty for image responses)"),
+            )
+            .output(
+                PortDefinition::output("status", PortType::Integer)
+                    .with_description("HTTP status code"),
+            )
+            .parameter(
+                ParameterDefinition::new("method", PortType::String, Value::String("POST".to_string()))
+                    .with_description("HTTP method")
+                    .with_ui_hint(dropdown(&METHODS))
+                    .with_constraint(one_of(&METHODS)),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "url",
+                    PortType::String,
+                    Value::String("http://127.0.0.1:5000/predict".to_string()),
+                )
+                .with_description("URL template, e.g. http://host/models/{model}/predict")
+                .with_constraint(Constraint::NotEmpty),
+            )
+            .parameter(
+                ParameterDefinition::new("headers", PortType::String, Value::String(String::new()))
+                    .with_description(
+                        "Extra headers, one 'Name: value' template per line; API keys belong \
+                         in the credentials file instead",
+                    )
+                    .with_ui_hint(UiHint::TextInput {
+                        multiline: true,
+                        placeholder: Some("Accept: application/json".to_string()),
+                    }),
+            )
+            .parameter(
+                ParameterDefinition::new(
+                    "body",
+                    PortType::String,
+                    Value::String(r#"{"image": "{image}"}"#.to_string()),
+                )
+                .with_description(
+                    "JSON body template; with multipart, its top-level fields become form fields",
+                )
+                .with_ui_hint(UiHint::TextInput {
+                    multiline: true,
+                    placeholder: None,
+                }),
+            )
+            .parameter(
+                ParameterDefinition::new("body_format", PortType::String, Value::String("json".to_string()))
+                    .with_description("Send the body as JSON, as multipart form data with image files, or not at all")
+                    .with_ui_hint(dropdown(&BODY_FORMATS))
+                    .with_constraint(one_of(&BODY_FORMATS)),
+            )
+            .parameter(
+                ParameterDefinition::new("variables", PortType::String, Value::String(String::new()))
+                    .with_description("Extra template values as a JSON object, e.g. {\"model\": \"resnet50\"}"),
+            )
+            .parameter(selector("image_path", "$.image", "image"))
+            .parameter(selector("number_path", "", "number"))
+            .parameter(selector("text_path", "", "text"))
+            .parameter(selector("array_path", "", "array"))
+            .parameter(
+                ParameterDefinition::new("timeout_secs", PortType::Integer, Value::Integer(60))
+                    .with_description("Request timeout in seconds")
+                    .with_constraint(Constraint::Range { min: 1.0, max: 600.0 }),
+            )
+            .build()
+    }
+
+    fn validate(&self, ctx: &ValidationContext) -> Result<(), ValidationError> {
+        let violation = |parameter: &str, error: String| ValidationError::ConstraintViolation {
+            node_id: ctx.node_id,
+            parameter: parameter.to_string(),
+            error,
+        };
+
+        let url = ctx.get_string("url").unwrap_or("");
+        if !url.starts_with("http://") && !url.starts_with("https://") && !url.starts_with('{') {
+            return Err(violation("url", "URL must start with http:// or https://".to_string()));
+        }
+        if ctx.get_string("body_format").unwrap_or("json") != "none" {
+            let body = ctx.get_string("body").unwrap_or("");
+            serde_json::from_str::<serde_json::Value>(body)
+                .map_err(|e| violation("body", format!("Invalid JSON: {}", e)))?;
+        }
+        let variables = ctx.get_string("variables").unwrap_or("");
+        if !variables.is_empty() {
+            parse_variables(variables).map_err(|e| violation("variables", e))?;
+        }
+        for name in ["image_path", "number_path", "text_path", "array_path"] {
+            let path = ctx.get_string(name).unwrap_or("");
+            if !path.is_empty() {
+                Selector::parse(path).map_err(|e| violation(name, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+        let node_id = ctx.node_id;
+        let error = |error: String| ExecutionError::NodeExecution { node_id, error };
+
+        let method = ctx.get_string("method").unwrap_or("POST").to_string();
+        let body_format = ctx.get_string("body_format").unwrap_or("json").to_string();
+        let timeout = ctx.get_integer("timeout_secs").unwrap_or(60) as u64;
+        let variables = template_variables(ctx)?;
+
+        let url = fill_template(ctx.get_string("url")?, &variables, true).map_err(error)?;
+        let mut request = HttpClient::global()
+            .request(&method, &url)
+            .timeout(std::time::Duration::from_secs(timeout));
+        for line in ctx.get_string("headers").unwrap_or("").lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| error(format!("Header line '{}' has no ':'", line)))?;
+            let value = fill_template(value.trim(), &variables, false).map_err(error)?;
+            request = request.set(name.trim(), &value);
+        }
+
+        let result = match body_format.as_str() {
+            "none" => request.call(),
+            format => {
+                let template: serde_json::Value = serde_json::from_str(ctx.get_string("body")?)
+                    .map_err(|e| error(format!("Invalid body template: {}", e)))?;
+                let body = fill_json(template, &variables).map_err(error)?;
+                if format == "multipart" {
+                    let (content_type, bytes) = multipart(ctx, &body)?;
+                    request.set("Content-Type", &content_type).send_bytes(&bytes)
+                } else {
+                    request.send_json(&body)
+                }
+            }
+        };
+        let resp = result.map_err(|e| error(format!("HTTP request failed: {}", e)))?;
+
+        ctx.set_output("status", Value::Integer(i64::from(resp.status())))?;
+        let is_image = resp.content_type().starts_with("image/");
+        let mut bytes = Vec::new();
+        resp.into_reader()
+            .take(MAX_RESPONSE_BYTES)
+            .read_to_end(&mut bytes)
+            .map_err(|e| error(format!("Failed to read response body: {}", e)))?;
+
+        if is_image {
+            let image = image::load_from_memory(&bytes)
+                .map_err(|e| error(format!("Failed to decode image response: {}", e)))?;
+            ctx.set_output("image", image_value(image))?;
+            ctx.set_output("response", Value::String(String::new()))?;
+            return Ok(());
+        }
+
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        let selectors: Vec<(&str, Selector)> = ["image", "number", "text", "array"]
+            .into_iter()
+            .filter_map(|output| {
+                let path = ctx.get_string(&format!("{}_path", output)).unwrap_or("");
+                (!path.is_empty()).then(|| Selector::parse(path).map(|s| (output, s)))
+            })
+            .collect::<Result<_, _>>()
+            .map_err(error)?;
+        if !selectors.is_empty() {
+            let json: serde_json::Value = serde_json::from_str(&text)
+                .map_err(|e| error(format!("Response is not JSON: {}", e)))?;
+            for (output, selector) in selectors {
+                let value = extract(output, &selector, &json).map_err(error)?;
+                ctx.set_output(output, value)?;
+            }
+        }
+        ctx.set_output("response", Value::String(text))?;
+        Ok(())
+    }
+
+    fn clone_box(&self) -> Box<dyn FilterNode> {
+        Box::new(self.clone())
+    }
+}
+
+// ============================================================================
+// Templates
+// ============================================================================
+
+fn parse_variables(json: &str) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    match serde_json::from_str(json) {
+        Ok(serde_json::Value::Object(map)) => Ok(map),
+        Ok(_) => Err("Variables must be a JSON object".to_string()),
+        Err(e) => Err(format!("Invalid JSON: {}", e)),
+    }
+}
+
+/// The values templates can reference.
+fn template_variables(
+    ctx: &ExecutionContext,
+) -> Result<HashMap<String, serde_json::Value>, ExecutionError> {
+    let node_id = ctx.node_id;
+    let variables = ctx.get_string("variables").unwrap_or("");
+    let mut values: HashMap<String, serde_json::Value> = if variables.is_empty() {
+        HashMap::new()
+    } else {
+        parse_variables(variables)
+            .map_err(|error| ExecutionError::NodeExecution { node_id, error })?
+            .into_iter()
+            .collect()
+    };
+    for name in IMAGE_INPUTS {
+        if let Some(image) = ctx.get_input_image_optional(name) {
+            let png = encode_png(image, node_id)?;
+            values.insert(
+                name.to_string(),
+                base64::engine::general_purpose::STANDARD.encode(png).into(),
+            );
+        }
+    }
+    if let Ok(Value::String(text)) = ctx.get_input("text") {
+        values.insert("text".to_string(), text.clone().into());
+    }
+    if let Some(number) = ctx.get_input("number").ok().and_then(Value::as_float) {
+        values.insert("number".to_string(), number.into());
+    }
+    Ok(values)
+}
+
+/// Replace `{name}` placeholders in `template`. Braces not around a name are
+/// kept as they are.
+fn fill_template(
+    template: &str,
+    variables: &HashMap<String, serde_json::Value>,
+    url_encode: bool,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}').map(|end| &after[..end]).filter(|name| is_name(name)) {
+            Some(name) => {
+                let value = variables
+                    .get(name)
+                    .ok_or_else(|| format!("Unknown template variable {{{}}}", name))?;
+                let text = match value {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if url_encode {
+                    out.push_str(&percent_encode(&text));
+                } else {
+                    out.push_str(&text);
+                }
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Fill the placeholders in the strings of a JSON body template.
+fn fill_json(
+    template: serde_json::Value,
+    variables: &HashMap<String, serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    Ok(match template {
+        serde_json::Value::String(s) => {
+            let whole = s
+                .strip_prefix('{')
+                .and_then(|s| s.strip_suffix('}'))
+                .filter(|name| is_name(name));
+            match whole {
+                Some(name) => variables
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| format!("Unknown template variable {{{}}}", name))?,
+                None => serde_json::Value::String(fill_template(&s, variables, false)?),
+            }
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(
+            items
+                .into_iter()
+                .map(|item| fill_json(item, variables))
+                .collect::<Result<_, _>>()?,
+        ),
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.into_iter()
+                .map(|(k, v)| Ok((k, fill_json(v, variables)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other,
+    })
+}
+
+fn is_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// A multipart body: the fields of `body` plus one file per image input.
+fn multipart(
+    ctx: &ExecutionContext,
+    body: &serde_json::Value,
+) -> Result<(String, Vec<u8>), ExecutionError> {
+    let node_id = ctx.node_id;
+    let fields = body.as_object().ok_or_else(|| ExecutionError::NodeExecution {
+        node_id,
+        error: "A multipart body template must be a JSON object".to_string(),
+    })?;
+    let boundary = format!("ambara-{}", uuid::Uuid::new_v4().simple());
+    let mut bytes = Vec::new();
+    for (name, value) in fields {
+        let text = match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        bytes.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{text}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    for name in IMAGE_INPUTS {
+        if let Some(image) = ctx.get_input_image_optional(name) {
+            bytes.extend_from_slice(
+                format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"; \
+                     filename=\"{name}.png\"\r\nContent-Type: image/png\r\n\r\n"
+                )
+                .as_bytes(),
+            );
+            bytes.extend_from_slice(&encode_png(image, node_id)?);
+            bytes.extend_from_slice(b"\r\n");
+        }
+    }
+    bytes.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    Ok((format!("multipart/form-data; boundary={boundary}"), bytes))
+}
+
+fn encode_png(image: &ImageValue, node_id: NodeId) -> Result<Vec<u8>, ExecutionError> {
+    let rgba = image
+        .get_image()
+        .ok_or_else(|| ExecutionError::NodeExecution {
+            node_id,
+            error: "Input image data is not loaded".to_string(),
+        })?
+        .to_rgba8();
+    let mut png = Vec::new();
+    image::DynamicImage::ImageRgba8(rgba)
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| ExecutionError::NodeExecution {
+            node_id,
+            error: format!("Failed to encode image: {}", e),
+        })?;
+    Ok(png)
+}
+
+fn image_value(image: image::DynamicImage) -> Value {
+    Value::Image(ImageValue::new(image::DynamicImage::ImageRgba8(image.to_rgba8())))
+}
+
+// ============================================================================
+// Selectors
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+/// A JSONPath-like selector: `$` followed by `.key`, `['key']`, `[index]`
+/// (negative counts from the end), `[*]` or `.*`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    segments: Vec<Segment>,
+}
+
+impl Selector {
+    /// Parse a selector such as `$.predictions[0].label`.
+    pub fn parse(path: &str) -> Result<Self, String> {
+        let invalid = |reason: &str| format!("Invalid selector '{}': {}", path, reason);
+        let mut rest = path
+            .trim()
+            .strip_prefix('$')
+            .ok_or_else(|| invalid("must start with $"))?;
+        let mut segments = Vec::new();
+        while !rest.is_empty() {
+            if let Some(after) = rest.strip_prefix('.') {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                let key = &after[..end];
+                segments.push(match key {
+                    "" => return Err(invalid("empty key")),
+                    "*" => Segment::Wildcard,
+                    key => Segment::Key(key.to_string()),
+                });
+                rest = &after[end..];
+            } else if let Some(after) = rest.strip_prefix('[') {
+                let end = after.find(']').ok_or_else(|| invalid("unclosed ["))?;
+                let inner = after[..end].trim();
+                segments.push(if inner == "*" {
+                    Segment::Wildcard
+                } else if let Some(key) = inner
+                    .strip_prefix('\'')
+                    .and_then(|s| s.strip_suffix('\''))
+                    .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')))
+                {
+                    Segment::Key(key.to_string())
+                } else {
+                    Segment::Index(inner.parse().map_err(|_| invalid("bad index"))?)
+                });
+                rest = &after[end + 1..];
+            } else {
+                return Err(invalid("expected . or ["));
+            }
+        }
+        Ok(Self { segments })
+    }
+
+    /// All values the selector matches, in document order.
+    pub fn select<'a>(&self, root: &'a serde_json::Value) -> Vec<&'a serde_json::Value> {
+        let mut current = vec![root];
+        for segment in &self.segments {
+            current = current
+                .into_iter()
+                .flat_map(|value| -> Vec<&serde_json::Value> {
+                    match (segment, value) {
+                        (Segment::Key(key), value) => value.get(key).into_iter().collect(),
+                        (Segment::Index(index), serde_json::Value::Array(items)) => {
+                            let index = if *index < 0 {
+                                items.len() as i64 + index
+                            } else {
+                                *index
+                            };
+                            usize::try_from(index)
+                                .ok()
+                                .and_then(|i| items.get(i))
+                                .into_iter()
+                                .collect()
+                        }
+                        (Segment::Wildcard, serde_json::Value::Array(items)) => {
+                            items.iter().collect()
+                        }
+                        (Segment::Wildcard, serde_json::Value::Object(map)) => {
+                            map.values().collect()
+                        }
+                        _ => Vec::new(),
+                    }
+                })
+                .collect();
+        }
+        current
+    }
+}
+
+/// Convert what `selector` matches into the value of `output`.
+fn extract(output: &str, selector: &Selector, json: &serde_json::Value) -> Result<Value, String> {
+    let matches = selector.select(json);
+    if output == "array" {
+        return Ok(Value::Array(match matches.as_slice() {
+            [serde_json::Value::Array(items)] => items.iter().map(json_value).collect(),
+            matches => matches.iter().map(|m| json_value(m)).collect(),
+        }));
+    }
+    let found = matches
+        .first()
+        .ok_or_else(|| format!("Nothing in the response matches the {} selector", output))?;
+    match output {
+        "image" => {
+            let encoded = found
+                .as_str()
+                .ok_or_else(|| "The image selector must match a base64 string".to_string())?;
+            // Accept data URLs as well as bare base64.
+            let encoded = encoded
+                .split_once(";base64,")
+                .map_or(encoded, |(_, data)| data);
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| format!("Failed to decode base64 image: {}", e))?;
+            let image = image::load_from_memory(&bytes)
+                .map_err(|e| format!("Failed to decode image: {}", e))?;
+            Ok(image_value(image))
+        }
+        "number" => match found {
+            serde_json::Value::Number(n) => n.as_f64(),
+            serde_json::Value::String(s) => s.trim().parse().ok(),
+            serde_json::Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+        .map(Value::Float)
+        .ok_or_else(|| format!("The number selector matched {}, not a number", found)),
+        _ => Ok(Value::String(match found {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })),
+    }
+}
+
+fn json_value(json: &serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::None,
+        serde_json::Value::Bool(b) => Value::Boolean(*b),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => Value::String(s.clone()),
+        serde_json::Value::Array(items) => Value::Array(items.iter().map(json_value).collect()),
+        serde_json::Value::Object(map) => Value::Map(
+            map.iter()
+                .map(|(k, v)| (k.clone(), json_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::test_server::serve_once;
+    use image::{Rgba, RgbaImage};
+
+    fn png(color: [u8; 4]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(2, 2, Rgba(color)))
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    fn image(color: [u8; 4]) -> Value {
+        Value::Image(ImageValue::new(image::DynamicImage::ImageRgba8(
+            RgbaImage::from_pixel(2, 2, Rgba(color)),
+        )))
+    }
+
+    /// A context with the filter's defaults and `params` set.
+    fn context(params: &[(&str, &str)]) -> ExecutionContext {
+        let mut ctx = ExecutionContext::new(NodeId::new());
+        for param in HttpRequest.metadata().parameters {
+            ctx.add_parameter(param.name, param.default_value);
+        }
+        for (name, value) in params {
+            ctx.add_parameter(*name, Value::String(value.to_string()));
+        }
+        ctx
+    }
+
+    mod selectors {
+        use super::*;
+
+        #[test]
+        fn keys_indices_and_wildcards() {
+            let json = serde_json::json!({
+                "predictions": [
+                    { "label": "cat", "score": 0.9 },
+                    { "label": "dog", "score": 0.1 }
+                ],
+                "odd key": true
+            });
+            let select = |path: &str| Selector::parse(path).unwrap().select(&json);
+            assert_eq!(select("$.predictions[0].label"), [&serde_json::json!("cat")]);
+            assert_eq!(select("$['predictions'][-1]['score']"), [&serde_json::json!(0.1)]);
+            assert_eq!(select("$.predictions[*].label").len(), 2);
+            assert_eq!(select("$[\"odd key\"]"), [&serde_json::json!(true)]);
+            assert!(select("$.predictions[5]").is_empty());
+            assert_eq!(select("$"), [&json]);
+        }
+
+        #[test]
+        fn malformed_selectors_are_rejected() {
+            for path in ["predictions", "$.", "$[0", "$[x]", "$predictions"] {
+                assert!(Selector::parse(path).is_err(), "{path}");
+            }
+        }
+    }
+
+    mod templates {
+        use super::*;
+
+        #[test]
+        fn placeholders_keep_json_types_and_are_encoded_in_urls() {
+            let variables: HashMap<String, serde_json::Value> = [
+                ("model".to_string(), serde_json::json!("res net/50")),
+                ("k".to_string(), serde_json::json!(3)),
+            ]
+            .into_iter()
+            .collect();
+            let body = fill_json(
+                serde_json::json!({ "top_k": "{k}", "note": "model {model}", "raw": "{not a name}" }),
+                &variables,
+            )
+            .unwrap();
+            assert_eq!(
+                body,
+                serde_json::json!({ "top_k": 3, "note": "model res net/50", "raw": "{not a name}" })
+            );
+            assert_eq!(
+                fill_template("http://h/{model}?k={k}", &variables, true).unwrap(),
+                "http://h/res%20net%2F50?k=3"
+            );
+            let err = fill_template("{missing}", &variables, false).unwrap_err();
+            assert!(err.contains("{missing}"), "{err}");
+        }
+    }
+
+    mod requests {
+        use super::*;
+
+        #[test]
+        fn json_requests_embed_images_and_extract_typed_outputs() {
+            let response = serde_json::json!({
+                "result": {
+                    "image": format!(
+                        "data:image/png;base64,{}",
+                        base64::engine::general_purpose::STANDARD.encode(png([0, 0, 255, 255]))
+                    ),
+                    "score": "0.75",
+                    "labels": ["a", "b"],
+                    "meta": { "model": "m" }
+                }
+            });
+            let (url, requests) = serve_once("application/json", response.to_string().into_bytes());
+            let mut ctx = context(&[
+                ("url", &format!("{url}/models/{{model}}")),
+                ("body", r#"{"image": "{image}", "prompt": "{text}"}"#),
+                ("variables", r#"{"model": "seg"}"#),
+                ("image_path", "$.result.image"),
+                ("number_path", "$.result.score"),
+                ("text_path", "$.result.meta"),
+                ("array_path", "$.result.labels"),
+            ]);
+            ctx.add_input("image", image([200, 10, 10, 255]));
+            ctx.add_input("text", Value::String("hello".to_string()));
+            HttpRequest.execute(&mut ctx).unwrap();
+
+            let request = requests.recv().unwrap();
+            assert_eq!(request.line, "POST /models/seg HTTP/1.1");
+            let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(body["prompt"], "hello");
+            let sent = base64::engine::general_purpose::STANDARD
+                .decode(body["image"].as_str().unwrap())
+                .unwrap();
+            assert_eq!(image::load_from_memory(&sent).unwrap().to_rgba8().get_pixel(0, 0).0, [200, 10, 10, 255]);
+
+            let outputs = ctx.take_outputs();
+            assert_eq!(outputs["status"], Value::Integer(200));
+            assert_eq!(outputs["number"], Value::Float(0.75));
+            assert_eq!(outputs["text"], Value::String(r#"{"model":"m"}"#.to_string()));
+            assert_eq!(
+                outputs["array"],
+                Value::Array(vec![Value::String("a".to_string()), Value::String("b".to_string())])
+            );
+            let image = outputs["image"].as_image().unwrap().get_image().unwrap().to_rgba8();
+            assert_eq!(image.get_pixel(0, 0).0, [0, 0, 255, 255]);
+        }
+
+        #[test]
+        fn multipart_requests_send_images_as_files() {
+            let (url, requests) = serve_once("application/json", b"{}".to_vec());
+            let mut ctx = context(&[
+                ("url", &url),
+                ("body_format", "multipart"),
+                ("body", r#"{"threshold": 0.5}"#),
+                ("image_path", ""),
+            ]);
+            ctx.add_input("image", image([1, 2, 3, 255]));
+            HttpRequest.execute(&mut ctx).unwrap();
+
+            let request = requests.recv().unwrap();
+            assert!(request
+                .header("Content-Type")
+                .unwrap()
+                .starts_with("multipart/form-data; boundary="));
+            let body = String::from_utf8_lossy(&request.body);
+            assert!(body.contains("name=\"threshold\"\r\n\r\n0.5\r\n"), "{body}");
+            assert!(body.contains("name=\"image\"; filename=\"image.png\""), "{body}");
+            assert_eq!(ctx.take_outputs()["response"], Value::String("{}".to_string()));
+        }
+
+        #[test]
+        fn image_responses_become_the_image_output() {
+            let (url, requests) = serve_once("image/png", png([9, 9, 9, 255]));
+            let mut ctx = context(&[("url", &format!("{url}/render?q={{text}}")), ("method", "GET"), ("body_format", "none")]);
+            ctx.add_input("text", Value::String("a b".to_string()));
+            HttpRequest.execute(&mut ctx).unwrap();
+
+            assert_eq!(requests.recv().unwrap().line, "GET /render?q=a%20b HTTP/1.1");
+            let outputs = ctx.take_outputs();
+            let image = outputs["image"].as_image().unwrap().get_image().unwrap().to_rgba8();
+            assert_eq!(image.get_pixel(1, 1).0, [9, 9, 9, 255]);
+        }
+
+        #[test]
+        fn missing_matches_are_errors() {
+            let (url, _requests) = serve_once("application/json", br#"{"other": 1}"#.to_vec());
+            let mut ctx = context(&[("url", &url), ("body_format", "none"), ("method", "GET")]);
+            let err = HttpRequest.execute(&mut ctx).unwrap_err();
+            assert!(err.to_string().contains("image selector"), "{err}");
+        }
+    }
+}
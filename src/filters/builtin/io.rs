@@ -4,9 +4,11 @@ use crate::core::context::{ExecutionContext, ValidationContext};
 use crate::core::error::{ExecutionError, ValidationError};
 use crate::core::node::{Category, FilterNode, NodeMetadata};
 use crate::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
-use crate::core::types::{ImageValue, PortType, Value};
+use crate::core::types::{ImageFormat, ImageMetadata, ImageValue, PortType, Value};
 use crate::filters::registry::FilterRegistry;
-use std::path::Path;
+use image::ImageDecoder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 /// Register I/O filters.
 pub fn register(registry: &mut FilterRegistry) {
@@ -77,6 +79,29 @@ impl FilterNode for LoadImage {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        // Only the header is read; pixels are decoded at execution time
+        let path = ctx.get_string("path").unwrap_or("");
+        let header = image::ImageReader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .ok()
+            .and_then(|reader| reader.into_decoder().ok());
+        let Some(decoder) = header else {
+            return HashMap::new();
+        };
+        let (width, height) = decoder.dimensions();
+        let metadata = ImageMetadata::new(
+            width,
+            height,
+            ImageFormat::from_path(Path::new(path)),
+            decoder.color_type(),
+        );
+        HashMap::from([(
+            "image".to_string(),
+            Value::Image(ImageValue::from_metadata(metadata, PathBuf::from(path))),
+        )])
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let path = ctx.get_string("path").map_err(|_| ExecutionError::MissingParameter {
             node_id: ctx.node_id,
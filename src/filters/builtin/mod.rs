@@ -21,6 +21,7 @@ mod noise;
 mod draw;
 mod text;
 mod api;
+mod http_request;
 mod comfyui;
 
 use crate::filters::registry::FilterRegistry;
@@ -46,6 +47,7 @@ pub fn register_all(registry: &mut FilterRegistry) {
     draw::register(registry);
     text::register(registry);
     api::register(registry);
+    http_request::register(registry);
     comfyui::register(registry);
 }
 
@@ -65,3 +67,5 @@ pub use edge::{EdgeDetect, Emboss, CannyEdge, Laplacian};
 pub use noise::{AddNoise, Denoise};
 pub use draw::{DrawRectangle, DrawCircle, DrawLine};
 pub use text::TextOverlay;
+pub use http_request::{HttpRequest, Selector};
+pub use comfyui::discovery as comfyui_discovery;
@@ -4,10 +4,14 @@ use crate::core::context::{ExecutionContext, ValidationContext};
 use crate::core::error::{ExecutionError, ValidationError};
 use crate::core::node::{Category, FilterNode, NodeMetadata};
 use crate::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
-use crate::core::types::{ImageValue, PortType, Value};
+use crate::core::types::{ImageFormat, ImageMetadata, ImageValue, PortType, Value};
 use crate::filters::registry::FilterRegistry;
+use crate::graph::serialization::migration::FilterMigration;
 use image::imageops::FilterType;
-use image::GenericImageView;
+use image::{ColorType, GenericImageView};
+use semver::Version;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// Register transform filters.
 pub fn register(registry: &mut FilterRegistry) {
@@ -17,6 +21,35 @@ pub fn register(registry: &mut FilterRegistry) {
     registry.register(|| Box::new(Crop));
 }
 
+/// The RGBA8 `image` output of a transform, as seen by inference.
+fn inferred_rgba_output(width: u32, height: u32) -> HashMap<String, Value> {
+    let metadata = ImageMetadata::new(width, height, ImageFormat::Unknown, ColorType::Rgba8);
+    HashMap::from([(
+        "image".to_string(),
+        Value::Image(ImageValue::from_metadata(metadata, PathBuf::new())),
+    )])
+}
+
+/// Output size of [`Resize`] for a source of `src_width`x`src_height`.
+fn resized_dimensions(
+    (src_width, src_height): (u32, u32),
+    (target_width, target_height): (u32, u32),
+    preserve_aspect: bool,
+) -> (u32, u32) {
+    if preserve_aspect {
+        let aspect = src_width as f32 / src_height as f32;
+        let new_height = (target_width as f32 / aspect) as u32;
+        if new_height <= target_height {
+            (target_width, new_height)
+        } else {
+            let new_width = (target_height as f32 * aspect) as u32;
+            (new_width, target_height)
+        }
+    } else {
+        (target_width, target_height)
+    }
+}
+
 /// Resizes an image.
 #[derive(Debug, Clone)]
 pub struct Resize;
@@ -89,6 +122,21 @@ impl FilterNode for Resize {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        let Some(input) = ctx.inferred_image("image") else {
+            return HashMap::new();
+        };
+        let target_width = ctx.get_integer("width").unwrap_or(1920) as u32;
+        let target_height = ctx.get_integer("height").unwrap_or(1080) as u32;
+        let preserve_aspect = ctx.get_bool("preserve_aspect").unwrap_or(true);
+        let (width, height) = resized_dimensions(
+            (input.width, input.height),
+            (target_width, target_height),
+            preserve_aspect,
+        );
+        inferred_rgba_output(width, height)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let image = ctx.get_input_image("image")?;
 
@@ -102,21 +150,11 @@ impl FilterNode for Resize {
             error: "Image has no data".to_string(),
         })?;
 
-        let (src_width, src_height) = img_data.dimensions();
-
-        // Calculate dimensions
-        let (final_width, final_height) = if preserve_aspect {
-            let aspect = src_width as f32 / src_height as f32;
-            let new_height = (target_width as f32 / aspect) as u32;
-            if new_height <= target_height {
-                (target_width, new_height)
-            } else {
-                let new_width = (target_height as f32 * aspect) as u32;
-                (new_width, target_height)
-            }
-        } else {
-            (target_width, target_height)
-        };
+        let (final_width, final_height) = resized_dimensions(
+            img_data.dimensions(),
+            (target_width, target_height),
+            preserve_aspect,
+        );
 
         // Select filter
         let filter = match filter_name {
@@ -142,16 +180,32 @@ impl FilterNode for Resize {
 }
 
 /// Rotates an image.
+///
+/// Version 2.0.0 takes the angle as a number of degrees; 1.0.0 took it as a
+/// dropdown string, which [`Rotate::migrations`](FilterNode::migrations)
+/// converts.
 #[derive(Debug, Clone)]
 pub struct Rotate;
 
+/// Angles `rotate` accepts, in degrees.
+const ROTATE_ANGLES: [f64; 3] = [90.0, 180.0, 270.0];
+
+/// The rotation angle in degrees. Graphs that were not migrated may still
+/// hold the string Rotate 1.0.0 stored, such as `"90"`.
+fn rotate_angle(value: &Value) -> Option<f64> {
+    match value {
+        Value::String(angle) => angle.trim().parse().ok(),
+        other => other.as_float(),
+    }
+}
+
 impl FilterNode for Rotate {
     fn metadata(&self) -> NodeMetadata {
         NodeMetadata::builder("rotate", "Rotate")
             .description("Rotate an image by a specified angle")
             .category(Category::Transform)
             .author("Ambara")
-            .version("1.0.0")
+            .version("2.0.0")
             .input(
                 PortDefinition::input("image", PortType::Image)
                     .with_description("Input image")
@@ -161,20 +215,12 @@ impl FilterNode for Rotate {
                     .with_description("Rotated image")
             )
             .parameter(
-                ParameterDefinition::new("angle", PortType::String, Value::String("90".to_string()))
-                    .with_description("Rotation angle")
-                    .with_ui_hint(UiHint::Dropdown {
-                        options: vec![
-                            "90".to_string(),
-                            "180".to_string(),
-                            "270".to_string(),
-                        ]
-                    })
-                    .with_constraint(Constraint::OneOf(vec![
-                        Value::String("90".to_string()),
-                        Value::String("180".to_string()),
-                        Value::String("270".to_string()),
-                    ])),
+                ParameterDefinition::new("angle", PortType::Float, Value::Float(90.0))
+                    .with_description("Clockwise rotation angle in degrees (90, 180, 270)")
+                    .with_ui_hint(UiHint::Angle)
+                    .with_constraint(Constraint::OneOf(
+                        ROTATE_ANGLES.iter().map(|&angle| Value::Float(angle)).collect(),
+                    )),
             )
             .build()
     }
@@ -183,10 +229,39 @@ impl FilterNode for Rotate {
         Ok(())
     }
 
+    fn migrations(&self) -> Vec<FilterMigration> {
+        vec![FilterMigration::new(
+            Version::new(1, 0, 0),
+            Version::new(2, 0, 0),
+            |node| {
+                // 1.0.0 stored the angle as the dropdown's string
+                if let Some(Value::String(angle)) = node.parameter("angle") {
+                    let degrees = angle
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|e| format!("angle '{angle}' is not a number: {e}"))?;
+                    node.set_parameter("angle", Value::Float(degrees));
+                }
+                Ok(())
+            },
+        )]
+    }
+
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        let Some(input) = ctx.inferred_image("image") else {
+            return HashMap::new();
+        };
+        let angle = ctx.get_parameter("angle").ok().and_then(rotate_angle).unwrap_or(90.0);
+        match angle as i64 {
+            90 | 270 => inferred_rgba_output(input.height, input.width),
+            _ => inferred_rgba_output(input.width, input.height),
+        }
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let image = ctx.get_input_image("image")?;
 
-        let angle = ctx.get_string("angle").unwrap_or("90");
+        let angle = ctx.get_parameter("angle").ok().and_then(rotate_angle).unwrap_or(90.0);
 
         let img_data = image.get_image().ok_or_else(|| ExecutionError::NodeExecution {
             node_id: ctx.node_id,
@@ -194,10 +269,10 @@ impl FilterNode for Rotate {
         })?;
 
         let rgba = img_data.to_rgba8();
-        let rotated = match angle {
-            "90" => image::imageops::rotate90(&rgba),
-            "180" => image::imageops::rotate180(&rgba),
-            "270" => image::imageops::rotate270(&rgba),
+        let rotated = match angle as i64 {
+            90 => image::imageops::rotate90(&rgba),
+            180 => image::imageops::rotate180(&rgba),
+            270 => image::imageops::rotate270(&rgba),
             _ => rgba,
         };
 
@@ -246,6 +321,12 @@ impl FilterNode for Flip {
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        ctx.inferred_image("image")
+            .map(|input| inferred_rgba_output(input.width, input.height))
+            .unwrap_or_default()
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let image = ctx.get_input_image("image")?;
 
@@ -334,9 +415,46 @@ impl FilterNode for Crop {
             });
         }
 
+        if let Some(input) = ctx.inferred_image("image") {
+            let x = ctx.get_integer("x").unwrap_or(0);
+            let y = ctx.get_integer("y").unwrap_or(0);
+            if x >= i64::from(input.width) || y >= i64::from(input.height) {
+                return Err(ValidationError::CustomValidation {
+                    node_id: ctx.node_id,
+                    error: format!(
+                        "Crop rectangle at ({}, {}) is outside the {}x{} input",
+                        x, y, input.width, input.height
+                    ),
+                });
+            }
+            if x + width > i64::from(input.width) || y + height > i64::from(input.height) {
+                return Err(ValidationError::CustomValidation {
+                    node_id: ctx.node_id,
+                    error: format!(
+                        "Crop rectangle {}x{} at ({}, {}) extends past the {}x{} input",
+                        width, height, x, y, input.width, input.height
+                    ),
+                });
+            }
+        }
+
         Ok(())
     }
 
+    fn infer_outputs(&self, ctx: &ValidationContext) -> HashMap<String, Value> {
+        let Some(input) = ctx.inferred_image("image") else {
+            return HashMap::new();
+        };
+        let x = ctx.get_integer("x").unwrap_or(0) as u32;
+        let y = ctx.get_integer("y").unwrap_or(0) as u32;
+        if x >= input.width || y >= input.height {
+            return HashMap::new();
+        }
+        let width = (ctx.get_integer("width").unwrap_or(100) as u32).min(input.width - x);
+        let height = (ctx.get_integer("height").unwrap_or(100) as u32).min(input.height - y);
+        inferred_rgba_output(width, height)
+    }
+
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let image = ctx.get_input_image("image")?;
 
@@ -401,6 +519,23 @@ mod tests {
         assert_eq!(metadata.id, "rotate");
     }
 
+    #[test]
+    fn test_rotate_reads_legacy_string_angles() {
+        use crate::core::error::NodeId;
+
+        let mut ctx = ValidationContext::new(NodeId::new());
+        ctx.add_inferred_input("image", inferred_rgba_output(40, 20).remove("image").unwrap());
+        ctx.add_parameter("angle", Value::String("180".to_string()));
+        let outputs = Rotate.infer_outputs(&ctx);
+        let rotated = &outputs["image"].as_image().unwrap().metadata;
+        assert_eq!((rotated.width, rotated.height), (40, 20));
+
+        ctx.add_parameter("angle", Value::Float(270.0));
+        let outputs = Rotate.infer_outputs(&ctx);
+        let rotated = &outputs["image"].as_image().unwrap().metadata;
+        assert_eq!((rotated.width, rotated.height), (20, 40));
+    }
+
     #[test]
     fn test_flip_metadata() {
         let filter = Flip;
@@ -416,4 +551,34 @@ mod tests {
         assert_eq!(metadata.id, "crop");
         assert_eq!(metadata.parameters.len(), 4);
     }
+
+    #[test]
+    fn test_crop_checks_inferred_bounds() {
+        use crate::core::error::NodeId;
+
+        let mut ctx = ValidationContext::new(NodeId::new());
+        ctx.add_parameter("x", Value::Integer(50));
+        ctx.add_parameter("y", Value::Integer(0));
+        ctx.add_parameter("width", Value::Integer(100));
+        ctx.add_parameter("height", Value::Integer(100));
+
+        // Placeholders are not checked against
+        ctx.add_input("image", inferred_rgba_output(40, 40).remove("image").unwrap());
+        assert!(Crop.validate(&ctx).is_ok());
+
+        ctx.add_inferred_input("image", inferred_rgba_output(40, 40).remove("image").unwrap());
+        assert!(Crop.validate(&ctx).is_err());
+
+        // The origin is inside but the rectangle is not; inference still
+        // clamps it so downstream nodes can be checked
+        ctx.add_inferred_input("image", inferred_rgba_output(120, 80).remove("image").unwrap());
+        let error = Crop.validate(&ctx).unwrap_err();
+        assert!(error.to_string().contains("extends past the 120x80 input"), "{error}");
+        let outputs = Crop.infer_outputs(&ctx);
+        let cropped = &outputs["image"].as_image().unwrap().metadata;
+        assert_eq!((cropped.width, cropped.height), (70, 80));
+
+        ctx.add_inferred_input("image", inferred_rgba_output(150, 100).remove("image").unwrap());
+        assert!(Crop.validate(&ctx).is_ok());
+    }
 }
@@ -3,6 +3,7 @@
 use crate::core::context::{ExecutionContext, ValidationContext};
 use crate::core::error::{ExecutionError, ValidationError};
 use crate::core::node::{Category, FilterNode, NodeMetadata, PassthroughNode};
+use crate::core::conversion::ImageLayout;
 use crate::core::port::{ParameterDefinition, PortDefinition};
 use crate::core::types::{ImageValue, PortType, Value};
 use crate::filters::registry::FilterRegistry;
@@ -182,19 +183,23 @@ impl FilterNode for MergeChannels {
             .input(
                 PortDefinition::input("red", PortType::Image)
                     .with_description("Red channel")
+                    .with_image_layout(ImageLayout::Grayscale)
             )
             .input(
                 PortDefinition::input("green", PortType::Image)
                     .with_description("Green channel")
+                    .with_image_layout(ImageLayout::Grayscale)
             )
             .input(
                 PortDefinition::input("blue", PortType::Image)
                     .with_description("Blue channel")
+                    .with_image_layout(ImageLayout::Grayscale)
             )
             .input(
                 PortDefinition::input("alpha", PortType::Image)
                     .optional()
                     .with_description("Alpha channel (optional, defaults to opaque)")
+                    .with_image_layout(ImageLayout::Grayscale)
             )
             .output(
                 PortDefinition::output("image", PortType::Image)
@@ -599,7 +604,7 @@ impl FilterNode for ArrayLength {
             .author("Ambara")
             .version("1.0.0")
             .input(
-                PortDefinition::input("array", PortType::Any)
+                PortDefinition::input("array", PortType::Array(Box::new(PortType::Var("T".to_string()))))
                     .with_description("Array to measure")
             )
             .output(
@@ -647,11 +652,11 @@ impl FilterNode for ValueDisplay {
             .author("Ambara")
             .version("1.0.0")
             .input(
-                PortDefinition::input("value", PortType::Any)
+                PortDefinition::input("value", PortType::Var("T".to_string()))
                     .with_description("Value to display")
             )
             .output(
-                PortDefinition::output("value", PortType::Any)
+                PortDefinition::output("value", PortType::Var("T".to_string()))
                     .with_description("Passthrough of input value")
             )
             .output(
@@ -672,26 +677,34 @@ impl FilterNode for ValueDisplay {
     fn execute(&self, ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
         let value = ctx.get_input("value")?;
         
-        let (display, type_name) = match value {
-            Value::Integer(i) => (format!("{}", i), "Integer"),
-            Value::Float(f) => (format!("{:.4}", f), "Float"),
-            Value::Boolean(b) => (format!("{}", b), "Boolean"),
-            Value::String(s) => (s.clone(), "String"),
-            Value::Color(c) => (format!("rgba({}, {}, {}, {})", c.r, c.g, c.b, c.a), "Color"),
-            Value::Vector2(x, y) => (format!("({:.2}, {:.2})", x, y), "Vector2"),
-            Value::Vector3(x, y, z) => (format!("({:.2}, {:.2}, {:.2})", x, y, z), "Vector3"),
-            Value::Image(img) => (
-                format!("Image {}x{} {:?}", img.metadata.width, img.metadata.height, img.metadata.format),
-                "Image"
-            ),
-            Value::Array(arr) => (format!("Array[{}]", arr.len()), "Array"),
-            Value::Map(map) => (format!("Map{{}} with {} keys", map.len()), "Map"),
-            Value::None => ("None".to_string(), "None"),
+        let display = match value {
+            Value::Integer(i) => format!("{}", i),
+            Value::Float(f) => format!("{:.4}", f),
+            Value::Boolean(b) => format!("{}", b),
+            Value::String(s) => s.clone(),
+            Value::Color(c) => format!("rgba({}, {}, {}, {})", c.r, c.g, c.b, c.a),
+            Value::Vector2(x, y) => format!("({:.2}, {:.2})", x, y),
+            Value::Vector3(x, y, z) => format!("({:.2}, {:.2}, {:.2})", x, y, z),
+            Value::Image(img) => {
+                format!("Image {}x{} {:?}", img.metadata.width, img.metadata.height, img.metadata.format)
+            }
+            Value::Array(arr) => format!("Array[{}]", arr.len()),
+            Value::Map(map) => format!("Map{{}} with {} keys", map.len()),
+            Value::None => "None".to_string(),
         };
-        
-        ctx.set_output("value", value.clone())?;
+        // The bound type, e.g. `Array<Image>` even for an empty array
+        let type_name = match value {
+            Value::None => "None".to_string(),
+            _ => ctx
+                .get_input_type("value")
+                .map(|port_type| port_type.to_string())
+                .unwrap_or_default(),
+        };
+        let value = value.clone();
+
+        ctx.set_output("value", value)?;
         ctx.set_output("display", Value::String(display))?;
-        ctx.set_output("type", Value::String(type_name.to_string()))?;
+        ctx.set_output("type", Value::String(type_name))?;
         Ok(())
     }
 
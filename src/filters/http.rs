@@ -0,0 +1,785 @@
+//! Shared HTTP client for filters that call external services.
+//!
+//! [`HttpClient::global`] is used by the API and ComfyUI filters instead of
+//! a fresh `ureq` agent per request, so connections to a server are reused
+//! across nodes and runs. On top of `ureq` it adds:
+//!
+//! - retries with exponential backoff and jitter. Idempotent requests
+//!   (`GET`, `PUT`, `DELETE`, ... or any request marked
+//!   [`idempotent`](HttpRequest::idempotent)) are retried after transport
+//!   errors and `429`/`502`/`503`/`504`. Other requests are only retried
+//!   after `429` and `503`, where the server refused them unprocessed.
+//!   `Retry-After` is honoured up to [`HttpConfig::retry_max_delay`].
+//! - per-host rate limits ([`HttpConfig::with_rate_limit`]).
+//! - auth headers from a [`Credentials`] store, keyed by host, so API keys
+//!   never have to be graph parameters.
+//! - an optional proxy, bypassed for the hosts in [`HttpConfig::no_proxy`]
+//!   (local servers such as ComfyUI by default).
+//!
+//! The global client is configured from the environment: `AMBARA_HTTP_PROXY`,
+//! `AMBARA_NO_PROXY` (comma separated hosts) and `AMBARA_CREDENTIALS` (path
+//! of a credentials file, see [`Credentials::load`]).
+
+use rand_core::{OsRng, RngCore};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Longest error body quoted in [`HttpError::Status`].
+const MAX_ERROR_BODY: usize = 512;
+
+/// Errors from [`HttpClient`] requests.
+#[derive(Error, Debug)]
+pub enum HttpError {
+    /// The request could not be sent or the response not read.
+    #[error("Request to {url} failed: {message}")]
+    Transport { url: String, message: String },
+
+    /// The server answered with a non-success status.
+    #[error("{url} returned HTTP {status}: {body}")]
+    Status {
+        url: String,
+        status: u16,
+        body: String,
+    },
+
+    /// The client configuration is invalid (for example a malformed proxy).
+    #[error("Invalid HTTP configuration: {0}")]
+    Config(String),
+
+    /// A credentials file could not be read.
+    #[error("Invalid credentials file {path}: {message}")]
+    Credentials { path: String, message: String },
+}
+
+/// Settings for an [`HttpClient`].
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    /// Timeout for establishing a connection.
+    pub connect_timeout: Duration,
+    /// Timeout for a whole request, unless the request sets its own.
+    pub timeout: Duration,
+    /// Retries after the first attempt, unless the request sets its own.
+    pub max_retries: u32,
+    /// Backoff before the first retry; doubled for each further retry.
+    pub retry_base_delay: Duration,
+    /// Longest wait between attempts, including `Retry-After`.
+    pub retry_max_delay: Duration,
+    /// Proxy URL (`http://host:port`), if any.
+    pub proxy: Option<String>,
+    /// Hosts reached without the proxy.
+    pub no_proxy: Vec<String>,
+    /// Requests per second allowed per host (`host` or `host:port`).
+    pub rate_limits: HashMap<String, f64>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(60),
+            max_retries: 3,
+            retry_base_delay: Duration::from_millis(250),
+            retry_max_delay: Duration::from_secs(10),
+            proxy: None,
+            no_proxy: vec![
+                "localhost".to_string(),
+                "127.0.0.1".to_string(),
+                "[::1]".to_string(),
+            ],
+            rate_limits: HashMap::new(),
+        }
+    }
+}
+
+impl HttpConfig {
+    /// Create the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The default configuration with the proxy settings from
+    /// `AMBARA_HTTP_PROXY` and `AMBARA_NO_PROXY`.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(proxy) = std::env::var("AMBARA_HTTP_PROXY") {
+            if !proxy.is_empty() {
+                config.proxy = Some(proxy);
+            }
+        }
+        if let Ok(hosts) = std::env::var("AMBARA_NO_PROXY") {
+            config.no_proxy.extend(
+                hosts
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|h| !h.is_empty())
+                    .map(str::to_string),
+            );
+        }
+        config
+    }
+
+    /// Set the connection timeout.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Set the default request timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the retry count and backoff bounds.
+    pub fn with_retries(mut self, max_retries: u32, base_delay: Duration, max_delay: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.retry_base_delay = base_delay;
+        self.retry_max_delay = max_delay;
+        self
+    }
+
+    /// Send requests through `proxy`.
+    pub fn with_proxy(mut self, proxy: impl Into<String>) -> Self {
+        self.proxy = Some(proxy.into());
+        self
+    }
+
+    /// Reach `host` without the proxy.
+    pub fn with_no_proxy(mut self, host: impl Into<String>) -> Self {
+        self.no_proxy.push(host.into());
+        self
+    }
+
+    /// Allow at most `requests_per_second` requests to `host`.
+    pub fn with_rate_limit(mut self, host: impl Into<String>, requests_per_second: f64) -> Self {
+        self.rate_limits.insert(host.into(), requests_per_second);
+        self
+    }
+}
+
+/// Auth headers to send, keyed by host (`host` or `host:port`).
+///
+/// Values are never printed by `Debug`.
+#[derive(Clone, Default)]
+pub struct Credentials {
+    headers: HashMap<String, Vec<(String, String)>>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: HashMap<_, Vec<_>> = self
+            .headers
+            .iter()
+            .map(|(host, headers)| (host, headers.iter().map(|(name, _)| name).collect()))
+            .collect();
+        f.debug_struct("Credentials").field("headers", &names).finish()
+    }
+}
+
+impl Credentials {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a store from a JSON file mapping hosts to headers:
+    ///
+    /// ```json
+    /// { "api.example.com": { "Authorization": "Bearer sk-..." } }
+    /// ```
+    pub fn load(path: &Path) -> Result<Self, HttpError> {
+        let error = |message: String| HttpError::Credentials {
+            path: path.display().to_string(),
+            message,
+        };
+        let content = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+        let hosts: HashMap<String, HashMap<String, String>> =
+            serde_json::from_str(&content).map_err(|e| error(e.to_string()))?;
+        let mut credentials = Self::new();
+        for (host, headers) in hosts {
+            for (name, value) in headers {
+                credentials = credentials.with_header(host.clone(), name, value);
+            }
+        }
+        Ok(credentials)
+    }
+
+    /// Send header `name: value` to `host`.
+    pub fn with_header(
+        mut self,
+        host: impl Into<String>,
+        name: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.headers
+            .entry(host.into())
+            .or_default()
+            .push((name.into(), value.into()));
+        self
+    }
+
+    /// Send `Authorization: Bearer {token}` to `host`.
+    pub fn with_bearer_token(self, host: impl Into<String>, token: impl AsRef<str>) -> Self {
+        self.with_header(host, "Authorization", format!("Bearer {}", token.as_ref()))
+    }
+
+    /// Whether the store has no headers.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    fn headers_for(&self, host: &Host) -> &[(String, String)] {
+        host.keys()
+            .find_map(|key| self.headers.get(key))
+            .map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The authority of a URL, for per-host settings.
+struct Host {
+    /// `host:port` as written in the URL.
+    authority: String,
+    /// The host name alone.
+    name: String,
+}
+
+impl Host {
+    fn of(url: &str) -> Self {
+        let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        let authority = authority.rsplit('@').next().unwrap_or_default().to_ascii_lowercase();
+        let name = if authority.starts_with('[') {
+            authority.split_inclusive(']').next().unwrap_or_default()
+        } else {
+            authority.split(':').next().unwrap_or_default()
+        }
+        .to_string();
+        Self { authority, name }
+    }
+
+    /// Setting keys for this host, most specific first.
+    fn keys(&self) -> impl Iterator<Item = &str> {
+        [self.authority.as_str(), self.name.as_str()].into_iter()
+    }
+}
+
+/// HTTP client with connection reuse, retries, rate limits, credentials and
+/// proxy support.
+pub struct HttpClient {
+    config: HttpConfig,
+    direct: ureq::Agent,
+    proxied: Option<ureq::Agent>,
+    credentials: RwLock<Credentials>,
+    /// Earliest time of the next request, per rate-limited host.
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl std::fmt::Debug for HttpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpClient")
+            .field("config", &self.config)
+            .field("credentials", &self.credentials)
+            .finish_non_exhaustive()
+    }
+}
+
+impl HttpClient {
+    /// Create a client.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`HttpError::Config`] if the proxy URL is invalid.
+    pub fn new(config: HttpConfig) -> Result<Self, HttpError> {
+        let builder = || {
+            ureq::AgentBuilder::new()
+                .timeout_connect(config.connect_timeout)
+                .try_proxy_from_env(false)
+        };
+        let proxied = match &config.proxy {
+            Some(proxy) => {
+                let proxy = ureq::Proxy::new(proxy)
+                    .map_err(|e| HttpError::Config(format!("proxy {}: {}", proxy, e)))?;
+                Some(builder().proxy(proxy).build())
+            }
+            None => None,
+        };
+        Ok(Self {
+            direct: builder().build(),
+            proxied,
+            credentials: RwLock::new(Credentials::new()),
+            next_slot: Mutex::new(HashMap::new()),
+            config,
+        })
+    }
+
+    /// The client shared by the built-in filters, configured from the
+    /// environment on first use.
+    pub fn global() -> &'static HttpClient {
+        static GLOBAL: OnceLock<HttpClient> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            let client = HttpClient::new(HttpConfig::from_env()).unwrap_or_else(|e| {
+                log::warn!("{e}; sending requests without a proxy");
+                HttpClient::new(HttpConfig {
+                    proxy: None,
+                    ..HttpConfig::from_env()
+                })
+                .expect("client without proxy")
+            });
+            if let Ok(path) = std::env::var("AMBARA_CREDENTIALS") {
+                match Credentials::load(Path::new(&path)) {
+                    Ok(credentials) => client.set_credentials(credentials),
+                    Err(e) => log::warn!("{e}"),
+                }
+            }
+            client
+        })
+    }
+
+    /// The client's configuration.
+    pub fn config(&self) -> &HttpConfig {
+        &self.config
+    }
+
+    /// Replace the credentials store.
+    pub fn set_credentials(&self, credentials: Credentials) {
+        *self.credentials.write().unwrap() = credentials;
+    }
+
+    /// Start a `GET` request.
+    pub fn get(&self, url: &str) -> HttpRequest<'_> {
+        self.request("GET", url)
+    }
+
+    /// Start a `POST` request.
+    pub fn post(&self, url: &str) -> HttpRequest<'_> {
+        self.request("POST", url)
+    }
+
+    /// Start a request with any method.
+    pub fn request(&self, method: &str, url: &str) -> HttpRequest<'_> {
+        let method = method.to_ascii_uppercase();
+        HttpRequest {
+            client: self,
+            idempotent: matches!(
+                method.as_str(),
+                "GET" | "HEAD" | "PUT" | "DELETE" | "OPTIONS" | "TRACE"
+            ),
+            method,
+            url: url.to_string(),
+            headers: Vec::new(),
+            timeout: None,
+            max_retries: None,
+        }
+    }
+
+    fn agent_for(&self, host: &Host) -> &ureq::Agent {
+        match &self.proxied {
+            Some(proxied) if !host.keys().any(|key| self.config.no_proxy.iter().any(|h| h == key)) => {
+                proxied
+            }
+            _ => &self.direct,
+        }
+    }
+
+    /// Wait until `host`'s rate limit allows another request.
+    fn wait_for_slot(&self, host: &Host) {
+        let Some((key, rate)) = host
+            .keys()
+            .find_map(|key| self.config.rate_limits.get_key_value(key))
+            .filter(|(_, rate)| **rate > 0.0)
+        else {
+            return;
+        };
+        let now = Instant::now();
+        let slot = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let next = next_slot.entry(key.clone()).or_insert(now);
+            let slot = (*next).max(now);
+            *next = slot + Duration::from_secs_f64(1.0 / rate);
+            slot
+        };
+        std::thread::sleep(slot - now);
+    }
+
+    /// Backoff before retry number `attempt` (from 0): a random delay
+    /// between half and all of `base * 2^attempt`, capped.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .config
+            .retry_base_delay
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.config.retry_max_delay);
+        let jitter = OsRng.next_u64() as f64 / u64::MAX as f64;
+        ceiling.mul_f64(0.5 + jitter / 2.0)
+    }
+}
+
+/// A request being built; send it with [`call`](Self::call),
+/// [`send_json`](Self::send_json) or [`send_bytes`](Self::send_bytes).
+#[must_use = "requests are only sent by call, send_json or send_bytes"]
+pub struct HttpRequest<'a> {
+    client: &'a HttpClient,
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    max_retries: Option<u32>,
+    idempotent: bool,
+}
+
+impl HttpRequest<'_> {
+    /// Set a header.
+    pub fn set(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Override the client's request timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Override the client's retry count.
+    pub fn retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Mark the request as safe (or not) to send twice, which allows
+    /// retries after transport errors.
+    pub fn idempotent(mut self, idempotent: bool) -> Self {
+        self.idempotent = idempotent;
+        self
+    }
+
+    /// Send the request without a body.
+    pub fn call(self) -> Result<ureq::Response, HttpError> {
+        self.send(None)
+    }
+
+    /// Send `data` as a JSON body.
+    pub fn send_json(self, data: &impl Serialize) -> Result<ureq::Response, HttpError> {
+        let body = serde_json::to_vec(data).map_err(|e| HttpError::Transport {
+            url: self.url.clone(),
+            message: format!("cannot serialize body: {}", e),
+        })?;
+        self.set("Content-Type", "application/json").send(Some(&body))
+    }
+
+    /// Send `data` as the body.
+    pub fn send_bytes(self, data: &[u8]) -> Result<ureq::Response, HttpError> {
+        self.send(Some(data))
+    }
+
+    fn send(self, body: Option<&[u8]>) -> Result<ureq::Response, HttpError> {
+        let client = self.client;
+        let host = Host::of(&self.url);
+        let agent = client.agent_for(&host);
+        let max_retries = self.max_retries.unwrap_or(client.config.max_retries);
+        let timeout = self.timeout.unwrap_or(client.config.timeout);
+
+        let mut attempt = 0;
+        loop {
+            client.wait_for_slot(&host);
+            let mut request = agent.request(&self.method, &self.url).timeout(timeout);
+            for (name, value) in client.credentials.read().unwrap().headers_for(&host) {
+                request = request.set(name, value);
+            }
+            for (name, value) in &self.headers {
+                request = request.set(name, value);
+            }
+            let result = match body {
+                Some(body) => request.send_bytes(body),
+                None => request.call(),
+            };
+
+            let retry = attempt < max_retries;
+            let error = match result {
+                Ok(response) => return Ok(response),
+                Err(ureq::Error::Status(status, response)) => {
+                    let retryable = match status {
+                        429 | 503 => true,
+                        502 | 504 => self.idempotent,
+                        _ => false,
+                    };
+                    if retry && retryable {
+                        let delay = retry_after(&response)
+                            .map_or_else(|| client.backoff(attempt), |d| d.min(client.config.retry_max_delay));
+                        std::thread::sleep(delay);
+                        attempt += 1;
+                        continue;
+                    }
+                    HttpError::Status {
+                        url: self.url,
+                        status,
+                        body: error_body(response),
+                    }
+                }
+                Err(ureq::Error::Transport(transport)) => {
+                    if retry && self.idempotent {
+                        std::thread::sleep(client.backoff(attempt));
+                        attempt += 1;
+                        continue;
+                    }
+                    HttpError::Transport {
+                        url: self.url,
+                        message: transport.to_string(),
+                    }
+                }
+            };
+            return Err(error);
+        }
+    }
+}
+
+/// A `Retry-After` header given in seconds.
+fn retry_after(response: &ureq::Response) -> Option<Duration> {
+    let secs: f64 = response.header("Retry-After")?.trim().parse().ok()?;
+    (secs.is_finite() && secs >= 0.0).then(|| Duration::from_secs_f64(secs))
+}
+
+/// The start of an error response's body.
+fn error_body(response: ureq::Response) -> String {
+    let mut body = response.into_string().unwrap_or_default();
+    if body.len() > MAX_ERROR_BODY {
+        let mut end = MAX_ERROR_BODY;
+        while !body.is_char_boundary(end) {
+            end -= 1;
+        }
+        body.truncate(end);
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::test_server::{listen, read_request, write_response, Request};
+    use std::sync::Arc;
+
+    /// One canned response.
+    #[derive(Clone)]
+    struct Reply {
+        status: u16,
+        headers: Vec<(&'static str, &'static str)>,
+        body: &'static str,
+        delay: Duration,
+    }
+
+    fn reply(status: u16) -> Reply {
+        Reply {
+            status,
+            headers: Vec::new(),
+            body: "",
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// A received request and when it arrived.
+    #[derive(Debug, Clone)]
+    struct Received {
+        request: Request,
+        at: Instant,
+    }
+
+    /// Serves `replies` in order, one per connection; `200 ok` after they
+    /// run out.
+    struct Stub {
+        url: String,
+        received: Arc<Mutex<Vec<Received>>>,
+    }
+
+    impl Stub {
+        fn start(replies: Vec<Reply>) -> Self {
+            let (listener, url) = listen();
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let log = Arc::clone(&received);
+            std::thread::spawn(move || {
+                let mut replies = replies.into_iter();
+                for stream in listener.incoming() {
+                    let Ok(stream) = stream else { return };
+                    let reply = replies.next().unwrap_or(Reply { body: "ok", ..reply(200) });
+                    let log = Arc::clone(&log);
+                    // Slow replies must not hold up the retry's connection.
+                    std::thread::spawn(move || serve(stream, reply, &log));
+                }
+            });
+            Self { url, received }
+        }
+
+        fn received(&self) -> Vec<Received> {
+            self.received.lock().unwrap().clone()
+        }
+    }
+
+    fn serve(stream: std::net::TcpStream, reply: Reply, log: &Mutex<Vec<Received>>) {
+        let request = read_request(&stream).unwrap();
+        log.lock().unwrap().push(Received {
+            request,
+            at: Instant::now(),
+        });
+
+        std::thread::sleep(reply.delay);
+        // The client may have given up on slow replies.
+        let _ = write_response(&stream, reply.status, &reply.headers, reply.body.as_bytes());
+    }
+
+    /// A client that retries quickly.
+    fn config() -> HttpConfig {
+        HttpConfig::new().with_retries(3, Duration::from_millis(1), Duration::from_millis(50))
+    }
+
+    fn client(config: HttpConfig) -> HttpClient {
+        HttpClient::new(config).unwrap()
+    }
+
+    mod retries {
+        use super::*;
+
+        #[test]
+        fn throttled_and_unavailable_responses_are_retried() {
+            let stub = Stub::start(vec![
+                Reply { headers: vec![("Retry-After", "0")], ..reply(429) },
+                reply(503),
+            ]);
+            let response = client(config()).get(&format!("{}/data", stub.url)).call().unwrap();
+            assert_eq!(response.into_string().unwrap(), "ok");
+            assert_eq!(stub.received().len(), 3);
+        }
+
+        #[test]
+        fn retries_give_up_with_the_last_status() {
+            let stub = Stub::start(vec![
+                Reply { body: "busy", ..reply(503) };
+                5
+            ]);
+            let error = client(config())
+                .get(&stub.url)
+                .retries(2)
+                .call()
+                .unwrap_err();
+            assert!(matches!(error, HttpError::Status { status: 503, ref body, .. } if body == "busy"));
+            assert_eq!(stub.received().len(), 3);
+        }
+
+        #[test]
+        fn posts_are_only_retried_when_refused() {
+            let stub = Stub::start(vec![reply(429), reply(502)]);
+            let client = client(config());
+            let error = client.post(&stub.url).send_json(&serde_json::json!({})).unwrap_err();
+            assert!(matches!(error, HttpError::Status { status: 502, .. }), "{error}");
+            assert_eq!(stub.received().len(), 2);
+
+            // Marked idempotent, the 502 is retried too.
+            let stub = Stub::start(vec![reply(502)]);
+            client.post(&stub.url).idempotent(true).send_bytes(b"x").unwrap();
+            assert_eq!(stub.received().len(), 2);
+        }
+
+        #[test]
+        fn slow_responses_time_out_and_are_retried() {
+            let stub = Stub::start(vec![Reply { delay: Duration::from_millis(500), ..reply(200) }]);
+            let client = client(config());
+            let started = Instant::now();
+            let response = client
+                .get(&stub.url)
+                .timeout(Duration::from_millis(100))
+                .call()
+                .unwrap();
+            assert_eq!(response.into_string().unwrap(), "ok");
+            assert_eq!(stub.received().len(), 2);
+            assert!(started.elapsed() < Duration::from_millis(450));
+
+            let stub = Stub::start(vec![Reply { delay: Duration::from_millis(500), ..reply(200) }]);
+            let error = client
+                .post(&stub.url)
+                .timeout(Duration::from_millis(100))
+                .send_bytes(b"x")
+                .unwrap_err();
+            assert!(matches!(error, HttpError::Transport { .. }), "{error}");
+            assert_eq!(stub.received().len(), 1);
+        }
+
+        #[test]
+        fn backoff_grows_with_jitter_up_to_the_cap() {
+            let client = client(HttpConfig::new().with_retries(
+                5,
+                Duration::from_millis(100),
+                Duration::from_millis(300),
+            ));
+            for (attempt, ceiling) in [(0, 100), (1, 200), (2, 300), (5, 300)] {
+                let delay = client.backoff(attempt);
+                let ceiling = Duration::from_millis(ceiling);
+                assert!(delay >= ceiling / 2 && delay <= ceiling, "{attempt}: {delay:?}");
+            }
+        }
+    }
+
+    mod hosts {
+        use super::*;
+
+        #[test]
+        fn credentials_are_sent_to_their_host_only() {
+            let stub = Stub::start(Vec::new());
+            let other = Stub::start(Vec::new());
+            let client = client(config());
+            let authority = stub.url.trim_start_matches("http://");
+            client.set_credentials(Credentials::new().with_bearer_token(authority, "secret"));
+
+            client.get(&stub.url).call().unwrap();
+            client.get(&other.url).call().unwrap();
+            assert_eq!(stub.received()[0].request.header("Authorization"), Some("Bearer secret"));
+            assert_eq!(other.received()[0].request.header("Authorization"), None);
+            assert!(!format!("{client:?}").contains("secret"));
+        }
+
+        #[test]
+        fn credentials_load_from_json() {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("credentials.json");
+            std::fs::write(&path, r#"{ "api.example.com": { "X-Api-Key": "k" } }"#).unwrap();
+            let credentials = Credentials::load(&path).unwrap();
+            let host = Host::of("https://user@API.example.com:443/v1?q=1");
+            assert_eq!(host.authority, "api.example.com:443");
+            assert_eq!(credentials.headers_for(&host), [("X-Api-Key".to_string(), "k".to_string())]);
+
+            std::fs::write(&path, "[]").unwrap();
+            assert!(matches!(Credentials::load(&path), Err(HttpError::Credentials { .. })));
+        }
+
+        #[test]
+        fn rate_limits_space_out_requests() {
+            let stub = Stub::start(Vec::new());
+            let client = client(config().with_rate_limit("127.0.0.1", 20.0));
+            for _ in 0..3 {
+                client.get(&stub.url).call().unwrap();
+            }
+            let received = stub.received();
+            for pair in received.windows(2) {
+                assert!(pair[1].at - pair[0].at >= Duration::from_millis(40));
+            }
+        }
+
+        #[test]
+        fn proxy_is_bypassed_for_local_hosts() {
+            // The stub plays the proxy: it sees absolute request URLs.
+            let proxy = Stub::start(Vec::new());
+            let client = client(config().with_proxy(&proxy.url));
+            client.get("http://images.example/cat.png").call().unwrap();
+            assert_eq!(
+                proxy.received()[0].request.line,
+                "GET http://images.example/cat.png HTTP/1.1"
+            );
+
+            let local = Stub::start(Vec::new());
+            client.get(&format!("{}/direct", local.url)).call().unwrap();
+            assert_eq!(local.received()[0].request.line, "GET /direct HTTP/1.1");
+            assert_eq!(proxy.received().len(), 1);
+        }
+    }
+}
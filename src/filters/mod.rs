@@ -1,8 +1,12 @@
 //! Filter module.
 //!
-//! Contains the filter registry and built-in filter implementations.
+//! Contains the filter registry, built-in filter implementations and the
+//! HTTP client shared by filters that call external services.
 
 pub mod registry;
 pub mod builtin;
+pub mod http;
+#[cfg(test)]
+pub(crate) mod test_server;
 
 pub use registry::{FilterRegistry, FilterFactory};
@@ -1,6 +1,8 @@
 //! Filter registry for managing available filter types.
 
+use crate::core::conversion::{Conversion, ConversionRegistry};
 use crate::core::node::{FilterNode, NodeMetadata, Category};
+use crate::graph::serialization::migration::{FilterMigration, Migrations};
 use indexmap::IndexMap;
 use std::sync::Arc;
 use serde::{Deserialize, Serialize};
@@ -50,6 +52,10 @@ pub struct FilterRegistry {
     filters: IndexMap<String, RegistryEntry>,
     /// Filters grouped by category.
     categories: IndexMap<Category, Vec<String>>,
+    /// Upgrade steps for saved graphs, including the filters' own.
+    migrations: Migrations,
+    /// Implicit conversions given to the graphs built with this registry.
+    conversions: Arc<ConversionRegistry>,
 }
 
 impl FilterRegistry {
@@ -58,6 +64,8 @@ impl FilterRegistry {
         Self {
             filters: IndexMap::new(),
             categories: IndexMap::new(),
+            migrations: Migrations::builtin(),
+            conversions: ConversionRegistry::builtin(),
         }
     }
 
@@ -82,6 +90,8 @@ impl FilterRegistry {
         let id = metadata.id.clone();
         let category = metadata.category;
 
+        self.migrations.set_filter_migrations(id.clone(), instance.migrations());
+
         let entry = RegistryEntry {
             factory: Arc::new(factory),
             metadata,
@@ -109,6 +119,8 @@ impl FilterRegistry {
         let id = metadata.id.clone();
         let category = metadata.category;
 
+        self.migrations.set_filter_migrations(id.clone(), instance.migrations());
+
         let entry = RegistryEntry {
             factory: Arc::new(factory),
             metadata,
@@ -162,6 +174,9 @@ impl FilterRegistry {
             let id = metadata.id.clone();
             let category = metadata.category;
 
+            // Steps of a builtin this filter replaces do not apply to it
+            self.migrations.set_filter_migrations(id.clone(), Vec::new());
+
             let entry = RegistryEntry {
                 factory: Arc::new(factory),
                 metadata,
@@ -177,6 +192,17 @@ impl FilterRegistry {
                 .push(id);
         }
 
+        /// Insert a registry entry as is, e.g. one taken out earlier with
+        /// [`get_entry`](Self::get_entry) and [`unregister`](Self::unregister).
+        pub fn insert_entry(&mut self, id: String, entry: RegistryEntry) {
+            let category = entry.metadata.category;
+            self.filters.insert(id.clone(), entry);
+            self.categories
+                .entry(category)
+                .or_default()
+                .push(id);
+        }
+
         /// Return all filter IDs contributed by a specific plugin.
         ///
         /// Useful for removing a plugin's filters when it is unloaded.
@@ -276,6 +302,7 @@ impl FilterRegistry {
     /// Unregister a filter.
     pub fn unregister(&mut self, id: &str) -> bool {
         if let Some(entry) = self.filters.shift_remove(id) {
+            self.migrations.set_filter_migrations(id, Vec::new());
             // Remove from category index
             if let Some(ids) = self.categories.get_mut(&entry.metadata.category) {
                 ids.retain(|i| i != id);
@@ -286,6 +313,35 @@ impl FilterRegistry {
         }
     }
 
+    /// Add a step that upgrades saved nodes of a filter, for filters that
+    /// cannot declare it through [`FilterNode::migrations`], such as plugin
+    /// filters. Register the filter first; re-registering it replaces its
+    /// steps.
+    pub fn add_migration(&mut self, filter_id: impl Into<String>, migration: FilterMigration) {
+        self.migrations.add_filter_migration(filter_id, migration);
+    }
+
+    /// Steps that upgrade saved graphs: the format steps and every
+    /// registered filter's steps. Used by
+    /// [`SerializedGraph::load`](crate::graph::serialization::SerializedGraph::load).
+    pub fn migrations(&self) -> &Migrations {
+        &self.migrations
+    }
+
+    /// Allow values to flow between two port types through `conversion`.
+    ///
+    /// Graphs built from this registry afterwards connect, validate and
+    /// execute with it; give existing graphs the new conversions with
+    /// [`ProcessingGraph::set_conversions`](crate::graph::structure::ProcessingGraph::set_conversions).
+    pub fn register_conversion(&mut self, conversion: Conversion) {
+        Arc::make_mut(&mut self.conversions).register(conversion);
+    }
+
+    /// Implicit conversions: the built-in ones and any registered.
+    pub fn conversions(&self) -> Arc<ConversionRegistry> {
+        Arc::clone(&self.conversions)
+    }
+
     /// Get the total number of registered filters.
     pub fn len(&self) -> usize {
         self.filters.len()
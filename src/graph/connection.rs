@@ -1,6 +1,7 @@
 //! Connection types for the graph.
 
 use crate::core::error::{ConnectionId, NodeId};
+use crate::core::types::PortType;
 use serde::{Deserialize, Serialize};
 
 /// An endpoint of a connection (node + port).
@@ -31,6 +32,17 @@ pub struct Connection {
     pub from: Endpoint,
     /// Target endpoint (input port).
     pub to: Endpoint,
+    /// Type of the values on this connection: the source port's type with
+    /// its type variables resolved. Kept up to date by
+    /// [`ProcessingGraph`](crate::graph::structure::ProcessingGraph) as
+    /// connections change; variables nothing constrains remain
+    /// [`PortType::Var`].
+    #[serde(default = "unknown_type")]
+    pub port_type: PortType,
+}
+
+fn unknown_type() -> PortType {
+    PortType::Any
 }
 
 impl Connection {
@@ -40,6 +52,7 @@ impl Connection {
             id: ConnectionId::new(),
             from,
             to,
+            port_type: PortType::Any,
         }
     }
 
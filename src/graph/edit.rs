@@ -0,0 +1,877 @@
+//! Undoable, replayable edits of a [`ProcessingGraph`].
+//!
+//! Every change goes through a [`GraphEdit`]: a serializable operation that,
+//! when applied, returns the edits that undo it. [`GraphEditor`] owns a graph
+//! and keeps undo and redo stacks on top of that:
+//!
+//! - Consecutive [`GraphEdit::SetParameter`] edits of the same parameter, or
+//!   [`GraphEdit::MoveNode`] edits of the same node, within the coalescing
+//!   window become one undo step, so a slider drag undoes in one go. Call
+//!   [`GraphEditor::end_coalescing`] when the drag ends.
+//! - [`GraphEditor::transaction`] (or `begin_transaction`/`commit_transaction`)
+//!   groups edits into one step that is applied all-or-nothing.
+//! - [`GraphEditor::log`] records every applied edit, including undos and
+//!   redos, as an [`EditLog`]. Replaying it onto the starting graph rebuilds
+//!   the current one, which is enough for crash recovery or for shipping
+//!   edits to another client.
+//!
+//! Nodes are addressed by [`NodeId`] and connections by their endpoints, as
+//! in [`SerializedGraph`](crate::graph::serialization::SerializedGraph), so
+//! edits stay valid when replayed into a freshly loaded graph.
+//!
+//! ```rust,ignore
+//! let mut editor = GraphEditor::new(ProcessingGraph::new());
+//! editor.apply(GraphEdit::AddNode { node: blur.clone() }, &registry)?;
+//! editor.apply(GraphEdit::set_parameter(blur.id, "sigma", Value::Float(2.0)), &registry)?;
+//! editor.undo(&registry)?;
+//! ```
+
+use crate::core::error::{GraphError, NodeId};
+use crate::core::types::Value;
+use crate::filters::registry::FilterRegistry;
+use crate::graph::serialization::{GraphLoadError, SerializedConnection, SerializedNode};
+use crate::graph::structure::{GraphMetadata, GraphNode, Position, ProcessingGraph};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Default window in which parameter and position edits coalesce.
+pub const DEFAULT_COALESCE_WINDOW: Duration = Duration::from_millis(750);
+
+/// Errors from applying edits.
+#[derive(Error, Debug)]
+pub enum EditError {
+    /// The graph rejected the edit.
+    #[error(transparent)]
+    Graph(#[from] GraphError),
+
+    /// A node could not be instantiated.
+    #[error(transparent)]
+    Load(Box<GraphLoadError>),
+
+    /// A node with this ID is already in the graph.
+    #[error("Node {0} already exists")]
+    DuplicateNode(NodeId),
+
+    /// A disconnect named a connection that does not exist.
+    #[error("No connection {from_node}.{from_port} -> {to_node}.{to_port}")]
+    ConnectionNotFound {
+        from_node: NodeId,
+        from_port: String,
+        to_node: NodeId,
+        to_port: String,
+    },
+
+    /// A transaction is already open.
+    #[error("A transaction is already open")]
+    TransactionOpen,
+
+    /// Commit or rollback without an open transaction, or undo/redo inside one.
+    #[error("No transaction is open")]
+    NoTransaction,
+}
+
+impl From<GraphLoadError> for EditError {
+    fn from(err: GraphLoadError) -> Self {
+        EditError::Load(Box::new(err))
+    }
+}
+
+/// One invertible change to a graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum GraphEdit {
+    /// Add a node (instantiated through the registry).
+    AddNode { node: SerializedNode },
+    /// Remove a node and its connections.
+    RemoveNode { node_id: NodeId },
+    /// Connect two ports.
+    Connect { connection: SerializedConnection },
+    /// Remove a connection.
+    Disconnect { connection: SerializedConnection },
+    /// Store a parameter value; `None` reverts to the filter default.
+    SetParameter {
+        node_id: NodeId,
+        name: String,
+        value: Option<Value>,
+    },
+    /// Move a node in the editor.
+    MoveNode { node_id: NodeId, position: Position },
+    /// Set or clear a node's label.
+    SetLabel {
+        node_id: NodeId,
+        label: Option<String>,
+    },
+    /// Enable or disable a node.
+    SetDisabled { node_id: NodeId, disabled: bool },
+    /// Expose a parameter as an input socket.
+    PromoteParameter { node_id: NodeId, name: String },
+    /// Turn a promoted parameter back into a plain parameter, removing any
+    /// connection feeding it.
+    DemoteParameter { node_id: NodeId, name: String },
+    /// Replace the graph metadata.
+    SetMetadata { metadata: GraphMetadata },
+}
+
+impl GraphEdit {
+    /// Shorthand for [`GraphEdit::SetParameter`] with a value.
+    pub fn set_parameter(node_id: NodeId, name: impl Into<String>, value: Value) -> Self {
+        GraphEdit::SetParameter {
+            node_id,
+            name: name.into(),
+            value: Some(value),
+        }
+    }
+
+    /// Shorthand for [`GraphEdit::Connect`].
+    pub fn connect(
+        from_node: NodeId,
+        from_port: impl Into<String>,
+        to_node: NodeId,
+        to_port: impl Into<String>,
+    ) -> Self {
+        GraphEdit::Connect {
+            connection: SerializedConnection {
+                from_node,
+                from_port: from_port.into(),
+                to_node,
+                to_port: to_port.into(),
+            },
+        }
+    }
+
+    /// Short description for undo/redo menus.
+    pub fn description(&self) -> String {
+        match self {
+            GraphEdit::AddNode { node } => format!("Add {}", node.filter_id),
+            GraphEdit::RemoveNode { .. } => "Remove node".to_string(),
+            GraphEdit::Connect { .. } => "Connect".to_string(),
+            GraphEdit::Disconnect { .. } => "Disconnect".to_string(),
+            GraphEdit::SetParameter { name, .. } => format!("Set {name}"),
+            GraphEdit::MoveNode { .. } => "Move node".to_string(),
+            GraphEdit::SetLabel { .. } => "Rename node".to_string(),
+            GraphEdit::SetDisabled { disabled: true, .. } => "Disable node".to_string(),
+            GraphEdit::SetDisabled {
+                disabled: false, ..
+            } => "Enable node".to_string(),
+            GraphEdit::PromoteParameter { name, .. } => format!("Expose {name}"),
+            GraphEdit::DemoteParameter { name, .. } => format!("Unexpose {name}"),
+            GraphEdit::SetMetadata { .. } => "Edit graph properties".to_string(),
+        }
+    }
+
+    /// What repeated edits must share to coalesce into one undo step.
+    fn coalesce_key(&self) -> Option<(NodeId, Option<&str>)> {
+        match self {
+            GraphEdit::SetParameter { node_id, name, .. } => Some((*node_id, Some(name))),
+            GraphEdit::MoveNode { node_id, .. } => Some((*node_id, None)),
+            _ => None,
+        }
+    }
+
+    /// Apply the edit and return the edits that undo it, in order.
+    pub fn apply(
+        &self,
+        graph: &mut ProcessingGraph,
+        registry: &FilterRegistry,
+    ) -> Result<Vec<GraphEdit>, EditError> {
+        match self {
+            GraphEdit::AddNode { node } => {
+                if graph.has_node(node.id) {
+                    return Err(EditError::DuplicateNode(node.id));
+                }
+                graph.add_node(GraphNode::from_serialized(node, registry)?);
+                Ok(vec![GraphEdit::RemoveNode { node_id: node.id }])
+            }
+            GraphEdit::RemoveNode { node_id } => {
+                let connections: Vec<SerializedConnection> = graph
+                    .connections()
+                    .iter()
+                    .filter(|c| c.from.node_id == *node_id || c.to.node_id == *node_id)
+                    .map(SerializedConnection::from)
+                    .collect();
+                let removed = graph.remove_node(*node_id)?;
+                let mut inverse = vec![GraphEdit::AddNode {
+                    node: SerializedNode::from(&removed),
+                }];
+                inverse.extend(
+                    connections
+                        .into_iter()
+                        .map(|connection| GraphEdit::Connect { connection }),
+                );
+                Ok(inverse)
+            }
+            GraphEdit::Connect { connection: c } => {
+                graph.connect(c.from_node, &c.from_port, c.to_node, &c.to_port)?;
+                Ok(vec![GraphEdit::Disconnect {
+                    connection: c.clone(),
+                }])
+            }
+            GraphEdit::Disconnect { connection: c } => {
+                let id = graph
+                    .connections()
+                    .iter()
+                    .find(|conn| SerializedConnection::from(*conn) == *c)
+                    .map(|conn| conn.id)
+                    .ok_or_else(|| EditError::ConnectionNotFound {
+                        from_node: c.from_node,
+                        from_port: c.from_port.clone(),
+                        to_node: c.to_node,
+                        to_port: c.to_port.clone(),
+                    })?;
+                graph.disconnect(id)?;
+                Ok(vec![GraphEdit::Connect {
+                    connection: c.clone(),
+                }])
+            }
+            GraphEdit::SetParameter {
+                node_id,
+                name,
+                value,
+            } => {
+                let node = graph.get_node_mut(*node_id)?;
+                if node.filter.metadata().get_parameter(name).is_none() {
+                    return Err(GraphError::ParameterNotFound {
+                        node_id: *node_id,
+                        parameter: name.clone(),
+                    }
+                    .into());
+                }
+                let previous = match value {
+                    Some(value) => node.parameters.insert(name.clone(), value.clone()),
+                    None => node.parameters.remove(name),
+                };
+                Ok(vec![GraphEdit::SetParameter {
+                    node_id: *node_id,
+                    name: name.clone(),
+                    value: previous,
+                }])
+            }
+            GraphEdit::MoveNode { node_id, position } => {
+                let node = graph.get_node_mut(*node_id)?;
+                let previous = std::mem::replace(&mut node.position, *position);
+                Ok(vec![GraphEdit::MoveNode {
+                    node_id: *node_id,
+                    position: previous,
+                }])
+            }
+            GraphEdit::SetLabel { node_id, label } => {
+                let node = graph.get_node_mut(*node_id)?;
+                let previous = std::mem::replace(&mut node.label, label.clone());
+                Ok(vec![GraphEdit::SetLabel {
+                    node_id: *node_id,
+                    label: previous,
+                }])
+            }
+            GraphEdit::SetDisabled { node_id, disabled } => {
+                let node = graph.get_node_mut(*node_id)?;
+                let previous = std::mem::replace(&mut node.disabled, *disabled);
+                Ok(vec![GraphEdit::SetDisabled {
+                    node_id: *node_id,
+                    disabled: previous,
+                }])
+            }
+            GraphEdit::PromoteParameter { node_id, name } => {
+                let was_promoted = graph.get_node(*node_id)?.promoted_parameters.contains(name);
+                graph.promote_parameter(*node_id, name.clone())?;
+                Ok(if was_promoted {
+                    Vec::new()
+                } else {
+                    vec![GraphEdit::DemoteParameter {
+                        node_id: *node_id,
+                        name: name.clone(),
+                    }]
+                })
+            }
+            GraphEdit::DemoteParameter { node_id, name } => {
+                let feeding: Vec<SerializedConnection> = graph
+                    .connections_to(*node_id)
+                    .filter(|c| c.to.port_name == *name)
+                    .map(SerializedConnection::from)
+                    .collect();
+                if !graph.demote_parameter(*node_id, name)? {
+                    return Ok(Vec::new());
+                }
+                let mut inverse = vec![GraphEdit::PromoteParameter {
+                    node_id: *node_id,
+                    name: name.clone(),
+                }];
+                inverse.extend(
+                    feeding
+                        .into_iter()
+                        .map(|connection| GraphEdit::Connect { connection }),
+                );
+                Ok(inverse)
+            }
+            GraphEdit::SetMetadata { metadata } => {
+                let previous = std::mem::replace(&mut graph.metadata, metadata.clone());
+                Ok(vec![GraphEdit::SetMetadata { metadata: previous }])
+            }
+        }
+    }
+}
+
+/// Apply `edits` in order, all or nothing. Returns the undo edits in the
+/// order they must be applied.
+pub(crate) fn apply_all(
+    edits: &[GraphEdit],
+    graph: &mut ProcessingGraph,
+    registry: &FilterRegistry,
+) -> Result<Vec<GraphEdit>, EditError> {
+    let mut inverse: Vec<GraphEdit> = Vec::new();
+    for edit in edits {
+        match edit.apply(graph, registry) {
+            Ok(mut undo) => {
+                undo.append(&mut inverse);
+                inverse = undo;
+            }
+            Err(err) => {
+                // The inverses of edits that just succeeded cannot fail.
+                let _ = apply_all(&inverse, graph, registry);
+                return Err(err);
+            }
+        }
+    }
+    Ok(inverse)
+}
+
+/// A group of edits applied, undone and redone together.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Description, e.g. "Set sigma" or "Undo Add gaussian_blur".
+    pub label: String,
+    /// The edits applied, in order.
+    pub edits: Vec<GraphEdit>,
+}
+
+/// Every edit applied through a [`GraphEditor`], in order.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct EditLog {
+    /// Applied groups of edits.
+    pub entries: Vec<LogEntry>,
+}
+
+impl EditLog {
+    /// Apply every entry to `graph`, which must be the graph the editor
+    /// started from.
+    pub fn replay(
+        &self,
+        graph: &mut ProcessingGraph,
+        registry: &FilterRegistry,
+    ) -> Result<(), EditError> {
+        for entry in &self.entries {
+            apply_all(&entry.edits, graph, registry)?;
+        }
+        Ok(())
+    }
+}
+
+/// One undo or redo step.
+#[derive(Debug, Clone)]
+struct Step {
+    label: String,
+    forward: Vec<GraphEdit>,
+    inverse: Vec<GraphEdit>,
+}
+
+/// A step still accepting coalesced edits.
+#[derive(Debug, Clone, Copy)]
+struct Coalescing {
+    at: Instant,
+}
+
+/// A graph with undo/redo history.
+#[derive(Debug, Clone)]
+pub struct GraphEditor {
+    graph: ProcessingGraph,
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    transaction: Option<Step>,
+    coalescing: Option<Coalescing>,
+    coalesce_window: Duration,
+    log: EditLog,
+}
+
+impl GraphEditor {
+    /// Start editing `graph` with empty history.
+    pub fn new(graph: ProcessingGraph) -> Self {
+        Self {
+            graph,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            transaction: None,
+            coalescing: None,
+            coalesce_window: DEFAULT_COALESCE_WINDOW,
+            log: EditLog::default(),
+        }
+    }
+
+    /// Set the coalescing window; `Duration::ZERO` disables coalescing.
+    pub fn with_coalesce_window(mut self, window: Duration) -> Self {
+        self.coalesce_window = window;
+        self
+    }
+
+    /// The current graph.
+    pub fn graph(&self) -> &ProcessingGraph {
+        &self.graph
+    }
+
+    /// Stop editing and return the graph.
+    pub fn into_graph(self) -> ProcessingGraph {
+        self.graph
+    }
+
+    /// Everything applied so far.
+    pub fn log(&self) -> &EditLog {
+        &self.log
+    }
+
+    /// Apply one edit as its own undo step (or as part of the open
+    /// transaction).
+    pub fn apply(&mut self, edit: GraphEdit, registry: &FilterRegistry) -> Result<(), EditError> {
+        let inverse = edit.apply(&mut self.graph, registry)?;
+        let label = edit.description();
+
+        if let Some(transaction) = &mut self.transaction {
+            let mut inverse = inverse;
+            inverse.append(&mut transaction.inverse);
+            transaction.inverse = inverse;
+            transaction.forward.push(edit);
+            return Ok(());
+        }
+
+        self.redo.clear();
+        let now = Instant::now();
+        let coalescable = edit.coalesce_key().is_some();
+        if self.coalesces_with_last(&edit, now) {
+            // Keep the original inverse: undo returns to before the drag.
+            if let Some(step) = self.undo.last_mut() {
+                step.forward = vec![edit.clone()];
+            }
+            if let Some(entry) = self.log.entries.last_mut() {
+                entry.edits = vec![edit];
+            }
+        } else {
+            self.log.entries.push(LogEntry {
+                label: label.clone(),
+                edits: vec![edit.clone()],
+            });
+            self.undo.push(Step {
+                label,
+                forward: vec![edit.clone()],
+                inverse,
+            });
+        }
+        self.coalescing = coalescable.then_some(Coalescing { at: now });
+        Ok(())
+    }
+
+    fn coalesces_with_last(&self, edit: &GraphEdit, now: Instant) -> bool {
+        let (Some(coalescing), Some(key)) = (self.coalescing, edit.coalesce_key()) else {
+            return false;
+        };
+        let Some(last) = self.undo.last() else {
+            return false;
+        };
+        now.duration_since(coalescing.at) < self.coalesce_window
+            && matches!(last.forward.as_slice(), [previous] if previous.coalesce_key() == Some(key))
+    }
+
+    /// Make the next edit start a new undo step, e.g. when a slider is
+    /// released.
+    pub fn end_coalescing(&mut self) {
+        self.coalescing = None;
+    }
+
+    /// Start grouping edits into one undo step.
+    pub fn begin_transaction(&mut self, label: impl Into<String>) -> Result<(), EditError> {
+        if self.transaction.is_some() {
+            return Err(EditError::TransactionOpen);
+        }
+        self.coalescing = None;
+        self.transaction = Some(Step {
+            label: label.into(),
+            forward: Vec::new(),
+            inverse: Vec::new(),
+        });
+        Ok(())
+    }
+
+    /// Close the open transaction as one undo step.
+    pub fn commit_transaction(&mut self) -> Result<(), EditError> {
+        let step = self.transaction.take().ok_or(EditError::NoTransaction)?;
+        if step.forward.is_empty() {
+            return Ok(());
+        }
+        self.redo.clear();
+        self.log.entries.push(LogEntry {
+            label: step.label.clone(),
+            edits: step.forward.clone(),
+        });
+        self.undo.push(step);
+        Ok(())
+    }
+
+    /// Undo everything applied since the transaction began.
+    pub fn rollback_transaction(&mut self, registry: &FilterRegistry) -> Result<(), EditError> {
+        let step = self.transaction.take().ok_or(EditError::NoTransaction)?;
+        apply_all(&step.inverse, &mut self.graph, registry)?;
+        Ok(())
+    }
+
+    /// Run `f` inside a transaction: commit if it succeeds, roll back if it
+    /// fails.
+    pub fn transaction<F>(
+        &mut self,
+        label: impl Into<String>,
+        registry: &FilterRegistry,
+        f: F,
+    ) -> Result<(), EditError>
+    where
+        F: FnOnce(&mut Self) -> Result<(), EditError>,
+    {
+        self.begin_transaction(label)?;
+        match f(self) {
+            Ok(()) => self.commit_transaction(),
+            Err(err) => {
+                self.rollback_transaction(registry)?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Whether there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Label of the step [`GraphEditor::undo`] would revert.
+    pub fn undo_label(&self) -> Option<&str> {
+        self.undo.last().map(|s| s.label.as_str())
+    }
+
+    /// Label of the step [`GraphEditor::redo`] would reapply.
+    pub fn redo_label(&self) -> Option<&str> {
+        self.redo.last().map(|s| s.label.as_str())
+    }
+
+    /// Revert the last step. Returns `false` if there is nothing to undo.
+    pub fn undo(&mut self, registry: &FilterRegistry) -> Result<bool, EditError> {
+        if self.transaction.is_some() {
+            return Err(EditError::NoTransaction);
+        }
+        let Some(step) = self.undo.pop() else {
+            return Ok(false);
+        };
+        self.coalescing = None;
+        if let Err(err) = apply_all(&step.inverse, &mut self.graph, registry) {
+            self.undo.push(step);
+            return Err(err);
+        }
+        self.log.entries.push(LogEntry {
+            label: format!("Undo {}", step.label),
+            edits: step.inverse.clone(),
+        });
+        self.redo.push(step);
+        Ok(true)
+    }
+
+    /// Reapply the last undone step. Returns `false` if there is nothing to
+    /// redo.
+    pub fn redo(&mut self, registry: &FilterRegistry) -> Result<bool, EditError> {
+        if self.transaction.is_some() {
+            return Err(EditError::NoTransaction);
+        }
+        let Some(step) = self.redo.pop() else {
+            return Ok(false);
+        };
+        self.coalescing = None;
+        let inverse = match apply_all(&step.forward, &mut self.graph, registry) {
+            Ok(inverse) => inverse,
+            Err(err) => {
+                self.redo.push(step);
+                return Err(err);
+            }
+        };
+        self.log.entries.push(LogEntry {
+            label: format!("Redo {}", step.label),
+            edits: step.forward.clone(),
+        });
+        self.undo.push(Step { inverse, ..step });
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::serialization::SerializedGraph;
+
+    fn registry() -> FilterRegistry {
+        FilterRegistry::with_builtins()
+    }
+
+    fn node(filter: &str, registry: &FilterRegistry) -> SerializedNode {
+        SerializedNode::from(&GraphNode::new(registry.create(filter).unwrap()))
+    }
+
+    /// An editor holding load → blur.
+    fn editor(registry: &FilterRegistry) -> (GraphEditor, NodeId, NodeId) {
+        let (load, blur) = (
+            node("load_image", registry),
+            node("gaussian_blur", registry),
+        );
+        let (load_id, blur_id) = (load.id, blur.id);
+        let mut editor = GraphEditor::new(ProcessingGraph::new());
+        editor
+            .apply(GraphEdit::AddNode { node: load }, registry)
+            .unwrap();
+        editor
+            .apply(GraphEdit::AddNode { node: blur }, registry)
+            .unwrap();
+        editor
+            .apply(
+                GraphEdit::connect(load_id, "image", blur_id, "image"),
+                registry,
+            )
+            .unwrap();
+        (editor, load_id, blur_id)
+    }
+
+    fn snapshot(editor: &GraphEditor) -> SerializedGraph {
+        let mut graph = editor.graph().to_serialized();
+        // Undo re-adds removed nodes at the end; order is not part of the state.
+        graph.nodes.sort_by_key(|n| n.id.0);
+        graph
+    }
+
+    #[test]
+    fn every_edit_undoes_to_the_previous_state() {
+        let registry = registry();
+        let (mut editor, load, blur) = editor(&registry);
+        let metadata = GraphMetadata {
+            name: Some("renamed".to_string()),
+            ..Default::default()
+        };
+        let edits = vec![
+            GraphEdit::set_parameter(blur, "sigma", Value::Float(4.0)),
+            GraphEdit::MoveNode {
+                node_id: blur,
+                position: Position::new(10.0, 20.0),
+            },
+            GraphEdit::SetLabel {
+                node_id: blur,
+                label: Some("Soften".to_string()),
+            },
+            GraphEdit::SetDisabled {
+                node_id: blur,
+                disabled: true,
+            },
+            GraphEdit::PromoteParameter {
+                node_id: blur,
+                name: "sigma".to_string(),
+            },
+            GraphEdit::Disconnect {
+                connection: SerializedConnection {
+                    from_node: load,
+                    from_port: "image".to_string(),
+                    to_node: blur,
+                    to_port: "image".to_string(),
+                },
+            },
+            GraphEdit::RemoveNode { node_id: load },
+            GraphEdit::SetMetadata { metadata },
+        ];
+        for edit in edits {
+            let before = snapshot(&editor);
+            editor.end_coalescing();
+            editor.apply(edit.clone(), &registry).unwrap();
+            assert_ne!(snapshot(&editor), before, "{edit:?} changed nothing");
+            let after = snapshot(&editor);
+            assert!(editor.undo(&registry).unwrap());
+            assert_eq!(snapshot(&editor), before, "undo of {edit:?}");
+            assert!(editor.redo(&registry).unwrap());
+            assert_eq!(snapshot(&editor), after, "redo of {edit:?}");
+            editor.undo(&registry).unwrap();
+        }
+    }
+
+    #[test]
+    fn removing_a_node_restores_its_connections_on_undo() {
+        let registry = registry();
+        let (mut editor, load, blur) = editor(&registry);
+        editor
+            .apply(GraphEdit::RemoveNode { node_id: blur }, &registry)
+            .unwrap();
+        assert_eq!(editor.graph().connection_count(), 0);
+        editor.undo(&registry).unwrap();
+        assert!(editor.graph().is_input_connected(blur, "image"));
+        assert!(editor.graph().has_node(load));
+    }
+
+    #[test]
+    fn slider_drags_coalesce_into_one_step() {
+        let registry = registry();
+        let (editor, _, blur) = editor(&registry);
+        let mut editor = editor.with_coalesce_window(Duration::from_secs(60));
+        for sigma in [1.5, 2.0, 2.5, 3.0] {
+            editor
+                .apply(
+                    GraphEdit::set_parameter(blur, "sigma", Value::Float(sigma)),
+                    &registry,
+                )
+                .unwrap();
+        }
+        assert_eq!(editor.undo_label(), Some("Set sigma"));
+        assert_eq!(editor.log().entries.len(), 4);
+
+        editor.undo(&registry).unwrap();
+        let node = editor.graph().get_node(blur).unwrap();
+        assert!(!node.parameters.contains_key("sigma"));
+        assert_eq!(editor.undo_label(), Some("Connect"));
+
+        // A released slider starts a new step.
+        editor.redo(&registry).unwrap();
+        editor.end_coalescing();
+        editor
+            .apply(
+                GraphEdit::set_parameter(blur, "sigma", Value::Float(9.0)),
+                &registry,
+            )
+            .unwrap();
+        editor.undo(&registry).unwrap();
+        let node = editor.graph().get_node(blur).unwrap();
+        assert_eq!(node.parameters["sigma"], Value::Float(3.0));
+    }
+
+    #[test]
+    fn edits_of_different_parameters_do_not_coalesce() {
+        let registry = registry();
+        let (editor, _, blur) = editor(&registry);
+        let mut editor = editor.with_coalesce_window(Duration::from_secs(60));
+        editor
+            .apply(
+                GraphEdit::set_parameter(blur, "sigma", Value::Float(2.0)),
+                &registry,
+            )
+            .unwrap();
+        editor
+            .apply(
+                GraphEdit::set_parameter(blur, "use_gpu", Value::Boolean(false)),
+                &registry,
+            )
+            .unwrap();
+        editor.undo(&registry).unwrap();
+        let node = editor.graph().get_node(blur).unwrap();
+        assert_eq!(node.parameters["sigma"], Value::Float(2.0));
+    }
+
+    #[test]
+    fn transactions_undo_as_one_step() {
+        let registry = registry();
+        let (mut editor, _, blur) = editor(&registry);
+        let before = snapshot(&editor);
+        editor
+            .transaction("Soften", &registry, |editor| {
+                editor.apply(
+                    GraphEdit::set_parameter(blur, "sigma", Value::Float(6.0)),
+                    &registry,
+                )?;
+                editor.apply(
+                    GraphEdit::SetLabel {
+                        node_id: blur,
+                        label: Some("Soft".to_string()),
+                    },
+                    &registry,
+                )
+            })
+            .unwrap();
+        assert_eq!(editor.undo_label(), Some("Soften"));
+        editor.undo(&registry).unwrap();
+        assert_eq!(snapshot(&editor), before);
+    }
+
+    #[test]
+    fn failed_transactions_roll_back() {
+        let registry = registry();
+        let (mut editor, load, blur) = editor(&registry);
+        let before = snapshot(&editor);
+        let result = editor.transaction("Rewire", &registry, |editor| {
+            editor.apply(
+                GraphEdit::set_parameter(blur, "sigma", Value::Float(6.0)),
+                &registry,
+            )?;
+            // The input is already connected.
+            editor.apply(GraphEdit::connect(load, "image", blur, "image"), &registry)
+        });
+        assert!(matches!(
+            result,
+            Err(EditError::Graph(GraphError::PortAlreadyConnected { .. }))
+        ));
+        assert_eq!(snapshot(&editor), before);
+        assert_eq!(editor.undo_label(), Some("Connect"));
+    }
+
+    #[test]
+    fn new_edits_clear_the_redo_stack() {
+        let registry = registry();
+        let (mut editor, _, blur) = editor(&registry);
+        editor.undo(&registry).unwrap();
+        assert!(editor.can_redo());
+        editor
+            .apply(
+                GraphEdit::SetDisabled {
+                    node_id: blur,
+                    disabled: true,
+                },
+                &registry,
+            )
+            .unwrap();
+        assert!(!editor.can_redo());
+        assert!(!editor.redo(&registry).unwrap());
+    }
+
+    #[test]
+    fn replaying_the_log_rebuilds_the_graph() {
+        let registry = registry();
+        let (mut editor, load, blur) = editor(&registry);
+        editor
+            .apply(
+                GraphEdit::set_parameter(blur, "sigma", Value::Float(2.0)),
+                &registry,
+            )
+            .unwrap();
+        editor.undo(&registry).unwrap();
+        editor
+            .apply(GraphEdit::RemoveNode { node_id: load }, &registry)
+            .unwrap();
+        editor.undo(&registry).unwrap();
+        editor.redo(&registry).unwrap();
+
+        let json = serde_json::to_string(editor.log()).unwrap();
+        let log: EditLog = serde_json::from_str(&json).unwrap();
+        let mut replayed = ProcessingGraph::new();
+        log.replay(&mut replayed, &registry).unwrap();
+        assert_eq!(replayed.to_serialized(), editor.graph().to_serialized());
+    }
+
+    #[test]
+    fn unknown_parameters_are_rejected() {
+        let registry = registry();
+        let (mut editor, _, blur) = editor(&registry);
+        let result = editor.apply(
+            GraphEdit::set_parameter(blur, "radius", Value::Float(1.0)),
+            &registry,
+        );
+        assert!(matches!(
+            result,
+            Err(EditError::Graph(GraphError::ParameterNotFound { .. }))
+        ));
+    }
+}
@@ -6,9 +6,12 @@
 pub mod structure;
 pub mod connection;
 pub mod topology;
+pub mod typing;
 pub mod serialization;
+pub mod edit;
 
 // Re-export commonly used types
 pub use structure::{ProcessingGraph, GraphNode, Position};
 pub use connection::{Connection, Endpoint};
 pub use topology::TopologyAnalyzer;
+pub use edit::{GraphEdit, GraphEditor};
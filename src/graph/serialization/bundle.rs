@@ -0,0 +1,933 @@
+//! Self-contained graph bundles (`.ambara` files).
+//!
+//! A bundle is a gzip-compressed tar archive holding everything needed to run
+//! a graph on another machine:
+//!
+//! ```text
+//! bundle.json           BundleManifest: assets, outputs, plugins, digests
+//! graph.json            the SerializedGraph, asset paths relative to the bundle
+//! assets/0-photo.png    input files referenced by the graph
+//! assets/1-frames/...   input directories, copied recursively
+//! outputs/<node>/<port>.png   cached image outputs (optional)
+//! plugins/<id>.toml     manifests of plugins providing the graph's filters (optional)
+//! ```
+//!
+//! Input files are found through the filter metadata: every parameter with a
+//! [`UiHint::FileChooser`] hint on a filter in [`Category::Input`] names a file
+//! or directory to embed. Relative paths are resolved against
+//! [`PackOptions::base_dir`] (the working directory by default), just as they
+//! are when the graph runs.
+//!
+//! [`pack_bundle`] writes a bundle, [`unpack_bundle`] extracts one and rewrites
+//! the asset parameters to absolute paths inside the extraction directory, and
+//! [`verify_bundle`] re-runs the unpacked graph and compares its outputs with
+//! the digests recorded at pack time.
+//!
+//! ```rust,ignore
+//! let registry = FilterRegistry::with_builtins();
+//! let options = PackOptions { include_outputs: true, ..PackOptions::default() };
+//! pack_bundle(&graph, &registry, &options, Path::new("portrait.ambara"))?;
+//!
+//! let report = verify_bundle(Path::new("portrait.ambara"), &registry)?;
+//! assert!(report.is_identical());
+//! ```
+
+use super::migration::{MigrationError, MigrationReport};
+use super::{GraphLoadError, SerializedGraph};
+use crate::core::error::NodeId;
+use crate::core::node::Category;
+use crate::core::port::UiHint;
+use crate::core::types::Value;
+use crate::execution::engine::ExecutionEngine;
+use crate::filters::registry::FilterRegistry;
+use crate::graph::structure::ProcessingGraph;
+use crate::plugins::manifest::PluginManifest;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{Cursor, Read};
+use std::path::{Component, Path, PathBuf};
+use thiserror::Error;
+
+/// File extension of graph bundles.
+pub const BUNDLE_EXTENSION: &str = "ambara";
+
+/// Current bundle layout version.
+pub const BUNDLE_VERSION: &str = "1.0.0";
+
+const MANIFEST_ENTRY: &str = "bundle.json";
+const GRAPH_ENTRY: &str = "graph.json";
+const ASSETS_DIR: &str = "assets";
+const OUTPUTS_DIR: &str = "outputs";
+const PLUGINS_DIR: &str = "plugins";
+
+/// Errors raised while packing, unpacking or verifying a bundle.
+#[derive(Error, Debug)]
+pub enum BundleError {
+    /// Reading or writing a file failed.
+    #[error("Bundle I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// `bundle.json` or `graph.json` could not be (de)serialized.
+    #[error("Invalid bundle JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A file or directory referenced by the graph does not exist.
+    #[error("Input '{parameter}' of node {node_id} not found: {path}")]
+    MissingAsset {
+        node_id: NodeId,
+        parameter: String,
+        path: String,
+    },
+
+    /// An entry listed in `bundle.json` is not in the archive.
+    #[error("Bundle entry '{0}' is missing")]
+    MissingEntry(String),
+
+    /// The archive holds an entry `bundle.json` does not list, or one whose
+    /// path would escape the extraction directory.
+    #[error("Unexpected bundle entry '{0}'")]
+    UnexpectedEntry(String),
+
+    /// An entry's contents do not match the digest in `bundle.json`.
+    #[error("Bundle entry '{0}' does not match its recorded digest")]
+    DigestMismatch(String),
+
+    /// The bundle was written by a newer Ambara.
+    #[error("Bundle version {found} is not supported (expected {supported})")]
+    UnsupportedVersion { found: String, supported: String },
+
+    /// The bundled graph could not be upgraded.
+    #[error(transparent)]
+    Migration(Box<MigrationError>),
+
+    /// The bundled graph could not be built.
+    #[error(transparent)]
+    Load(Box<GraphLoadError>),
+
+    /// Running the graph failed.
+    #[error("Executing the bundled graph failed: {0}")]
+    Execution(String),
+
+    /// An image output could not be encoded.
+    #[error("Encoding output {node_id}.{port} failed: {message}")]
+    Encode {
+        node_id: NodeId,
+        port: String,
+        message: String,
+    },
+
+    /// A bundled plugin manifest could not be read or written.
+    #[error("Invalid plugin manifest '{path}': {message}")]
+    PluginManifest { path: String, message: String },
+
+    /// [`verify_bundle`] was given a bundle packed without outputs.
+    #[error("Bundle has no recorded outputs to verify against")]
+    NoRecordedOutputs,
+}
+
+impl From<MigrationError> for BundleError {
+    fn from(err: MigrationError) -> Self {
+        BundleError::Migration(Box::new(err))
+    }
+}
+
+impl From<GraphLoadError> for BundleError {
+    fn from(err: GraphLoadError) -> Self {
+        BundleError::Load(Box::new(err))
+    }
+}
+
+/// An input file or directory embedded in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledAsset {
+    /// Node whose parameter references the asset.
+    pub node_id: NodeId,
+    /// The parameter holding the path.
+    pub parameter: String,
+    /// The path as it was in the graph when packed.
+    pub original_path: String,
+    /// Path inside the bundle, `/`-separated.
+    pub path: String,
+    /// Whether the asset is a directory.
+    #[serde(default)]
+    pub directory: bool,
+}
+
+/// A terminal output recorded at pack time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledOutput {
+    /// Node that produced the output.
+    pub node_id: NodeId,
+    /// Output port.
+    pub port: String,
+    /// SHA-256 of the value (see [`value_digest`]).
+    pub digest: String,
+    /// Cached copy inside the bundle, for image outputs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// A plugin manifest embedded in a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundledPlugin {
+    /// Plugin ID.
+    pub id: String,
+    /// Plugin version when packed.
+    pub version: String,
+    /// Filters of the graph the plugin provides.
+    pub filters: Vec<String>,
+    /// Path of the manifest inside the bundle.
+    pub path: String,
+}
+
+/// Contents of `bundle.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Bundle layout version ([`BUNDLE_VERSION`]).
+    pub bundle_version: String,
+    /// Version of Ambara that wrote the bundle.
+    pub ambara_version: String,
+    /// Embedded input files and directories.
+    pub assets: Vec<BundledAsset>,
+    /// Recorded terminal outputs.
+    #[serde(default)]
+    pub outputs: Vec<BundledOutput>,
+    /// Embedded plugin manifests.
+    #[serde(default)]
+    pub plugins: Vec<BundledPlugin>,
+    /// SHA-256 of every other entry in the archive, by path.
+    pub files: BTreeMap<String, String>,
+}
+
+/// Options for [`pack_bundle`].
+#[derive(Debug, Clone, Default)]
+pub struct PackOptions {
+    /// Directory relative asset paths are resolved against; the working
+    /// directory when `None`.
+    pub base_dir: Option<PathBuf>,
+    /// Run the graph and store its terminal outputs, so the bundle can be
+    /// checked with [`verify_bundle`].
+    pub include_outputs: bool,
+    /// Manifests of installed plugins; those providing a filter used by the
+    /// graph are embedded.
+    pub plugins: Vec<PluginManifest>,
+}
+
+/// A bundle extracted by [`unpack_bundle`].
+#[derive(Debug, Clone)]
+pub struct UnpackedBundle {
+    /// The graph, asset parameters pointing into [`Self::directory`].
+    pub graph: SerializedGraph,
+    /// The bundle's manifest.
+    pub manifest: BundleManifest,
+    /// Embedded plugin manifests.
+    pub plugins: Vec<PluginManifest>,
+    /// Migrations applied to the bundled graph.
+    pub migration: MigrationReport,
+    /// Where the bundle was extracted.
+    pub directory: PathBuf,
+}
+
+/// A recorded output that differs after unpacking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputMismatch {
+    /// Node that produced the output.
+    pub node_id: NodeId,
+    /// Output port.
+    pub port: String,
+    /// Digest recorded at pack time.
+    pub expected: String,
+    /// Digest after unpacking, or `None` if the output is gone.
+    pub actual: Option<String>,
+}
+
+/// Result of [`verify_bundle`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// Number of recorded outputs compared.
+    pub checked: usize,
+    /// Outputs that differ.
+    pub mismatches: Vec<OutputMismatch>,
+}
+
+impl VerifyReport {
+    /// Whether every recorded output was reproduced exactly.
+    pub fn is_identical(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_identical() {
+            return writeln!(f, "All {} recorded outputs reproduced", self.checked);
+        }
+        writeln!(
+            f,
+            "{} of {} recorded outputs differ:",
+            self.mismatches.len(),
+            self.checked
+        )?;
+        for mismatch in &self.mismatches {
+            match &mismatch.actual {
+                Some(actual) => writeln!(
+                    f,
+                    "  {}.{}: expected {}, got {}",
+                    mismatch.node_id, mismatch.port, mismatch.expected, actual
+                )?,
+                None => writeln!(f, "  {}.{}: not produced", mismatch.node_id, mismatch.port)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write `graph` and everything it references to a bundle at `path`.
+///
+/// Returns the manifest written into the bundle.
+pub fn pack_bundle(
+    graph: &SerializedGraph,
+    registry: &FilterRegistry,
+    options: &PackOptions,
+    path: &Path,
+) -> Result<BundleManifest, BundleError> {
+    let base_dir = match &options.base_dir {
+        Some(dir) => dir.clone(),
+        None => std::env::current_dir()?,
+    };
+
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut bundled = graph.clone();
+    let mut assets = Vec::new();
+    // The same file referenced twice is stored once.
+    let mut stored: HashMap<PathBuf, (String, bool)> = HashMap::new();
+
+    for (node_id, parameter, original) in input_assets(graph, registry) {
+        let source = base_dir.join(&original);
+        if !source.exists() {
+            return Err(BundleError::MissingAsset {
+                node_id,
+                parameter,
+                path: original,
+            });
+        }
+        let key = source.canonicalize()?;
+        let (bundle_path, directory) = match stored.get(&key) {
+            Some(existing) => existing.clone(),
+            None => {
+                let name = key
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| "asset".to_string());
+                let bundle_path = format!("{ASSETS_DIR}/{}-{name}", stored.len());
+                let directory = key.is_dir();
+                if directory {
+                    for entry in walkdir::WalkDir::new(&key).sort_by_file_name() {
+                        let entry = entry.map_err(std::io::Error::from)?;
+                        if !entry.file_type().is_file() {
+                            continue;
+                        }
+                        let relative = entry.path().strip_prefix(&key).unwrap_or(entry.path());
+                        entries.push((
+                            format!("{bundle_path}/{}", slash_path(relative)),
+                            std::fs::read(entry.path())?,
+                        ));
+                    }
+                } else {
+                    entries.push((bundle_path.clone(), std::fs::read(&key)?));
+                }
+                stored.insert(key, (bundle_path.clone(), directory));
+                (bundle_path, directory)
+            }
+        };
+
+        if let Some(node) = bundled.nodes.iter_mut().find(|n| n.id == node_id) {
+            node.parameters
+                .insert(parameter.clone(), Value::String(bundle_path.clone()));
+        }
+        assets.push(BundledAsset {
+            node_id,
+            parameter,
+            original_path: original,
+            path: bundle_path,
+            directory,
+        });
+    }
+
+    let mut outputs = Vec::new();
+    if options.include_outputs {
+        for (node_id, port, value) in run_graph(graph, registry)? {
+            let mut output = BundledOutput {
+                node_id,
+                port,
+                digest: value_digest(&value),
+                path: None,
+            };
+            if let Some(image) = value.as_image().and_then(|i| i.get_image()) {
+                let mut png = Vec::new();
+                image
+                    .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+                    .map_err(|e| BundleError::Encode {
+                        node_id,
+                        port: output.port.clone(),
+                        message: e.to_string(),
+                    })?;
+                let entry = format!("{OUTPUTS_DIR}/{}/{}.png", node_id.0, output.port);
+                entries.push((entry.clone(), png));
+                output.path = Some(entry);
+            }
+            outputs.push(output);
+        }
+    }
+
+    let mut plugins = Vec::new();
+    for manifest in &options.plugins {
+        let filters: Vec<String> = manifest
+            .plugin
+            .filters
+            .ids
+            .iter()
+            .filter(|id| graph.nodes.iter().any(|n| &n.filter_id == *id))
+            .cloned()
+            .collect();
+        if filters.is_empty() {
+            continue;
+        }
+        let entry = format!("{PLUGINS_DIR}/{}.toml", manifest.plugin.id);
+        let toml = toml::to_string(manifest).map_err(|e| BundleError::PluginManifest {
+            path: entry.clone(),
+            message: e.to_string(),
+        })?;
+        entries.push((entry.clone(), toml.into_bytes()));
+        plugins.push(BundledPlugin {
+            id: manifest.plugin.id.clone(),
+            version: manifest.plugin.version.clone(),
+            filters,
+            path: entry,
+        });
+    }
+
+    entries.push((GRAPH_ENTRY.to_string(), bundled.to_json()?.into_bytes()));
+
+    let manifest = BundleManifest {
+        bundle_version: BUNDLE_VERSION.to_string(),
+        ambara_version: env!("CARGO_PKG_VERSION").to_string(),
+        assets,
+        outputs,
+        plugins,
+        files: entries
+            .iter()
+            .map(|(name, data)| (name.clone(), sha256_hex(data)))
+            .collect(),
+    };
+
+    let file = std::fs::File::create(path)?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    append(&mut archive, MANIFEST_ENTRY, &serde_json::to_vec_pretty(&manifest)?)?;
+    for (name, data) in &entries {
+        append(&mut archive, name, data)?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(manifest)
+}
+
+/// Extract the bundle at `path` into `destination`.
+///
+/// Every entry is checked against the digests in `bundle.json`. The graph is
+/// upgraded to the current format, its asset parameters are rewritten to
+/// absolute paths under `destination`, and it is written back to
+/// `destination/graph.json` so it can be loaded from anywhere.
+pub fn unpack_bundle(
+    path: &Path,
+    destination: &Path,
+    registry: &FilterRegistry,
+) -> Result<UnpackedBundle, BundleError> {
+    let entries = read_entries(path)?;
+    let manifest: BundleManifest = match entries.get(MANIFEST_ENTRY) {
+        Some(data) => serde_json::from_slice(data)?,
+        None => return Err(BundleError::MissingEntry(MANIFEST_ENTRY.to_string())),
+    };
+    if manifest.bundle_version != BUNDLE_VERSION {
+        return Err(BundleError::UnsupportedVersion {
+            found: manifest.bundle_version,
+            supported: BUNDLE_VERSION.to_string(),
+        });
+    }
+
+    for name in entries.keys().filter(|name| *name != MANIFEST_ENTRY) {
+        if !manifest.files.contains_key(name) {
+            return Err(BundleError::UnexpectedEntry(name.clone()));
+        }
+    }
+    for (name, digest) in &manifest.files {
+        let data = entries
+            .get(name)
+            .ok_or_else(|| BundleError::MissingEntry(name.clone()))?;
+        if sha256_hex(data) != *digest {
+            return Err(BundleError::DigestMismatch(name.clone()));
+        }
+    }
+
+    std::fs::create_dir_all(destination)?;
+    let destination = destination.canonicalize()?;
+    for (name, data) in &entries {
+        let target = destination.join(name);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(target, data)?;
+    }
+
+    let graph_json = std::str::from_utf8(&entries[GRAPH_ENTRY])
+        .map_err(|_| BundleError::UnexpectedEntry(GRAPH_ENTRY.to_string()))?;
+    let migrated = SerializedGraph::load(graph_json, registry)?;
+    let mut graph = migrated.graph;
+    for asset in &manifest.assets {
+        if let Some(node) = graph.nodes.iter_mut().find(|n| n.id == asset.node_id) {
+            let resolved = destination.join(&asset.path);
+            node.parameters.insert(
+                asset.parameter.clone(),
+                Value::String(resolved.to_string_lossy().into_owned()),
+            );
+        }
+    }
+    std::fs::write(destination.join(GRAPH_ENTRY), graph.to_json()?)?;
+
+    let plugins = manifest
+        .plugins
+        .iter()
+        .map(|plugin| {
+            let text = String::from_utf8_lossy(&entries[&plugin.path]);
+            PluginManifest::from_toml_str(&text).map_err(|e| BundleError::PluginManifest {
+                path: plugin.path.clone(),
+                message: e.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(UnpackedBundle {
+        graph,
+        manifest,
+        plugins,
+        migration: migrated.report,
+        directory: destination,
+    })
+}
+
+/// Unpack the bundle at `path` into a scratch directory, run it, and compare
+/// its terminal outputs with the ones recorded at pack time.
+pub fn verify_bundle(path: &Path, registry: &FilterRegistry) -> Result<VerifyReport, BundleError> {
+    let scratch = std::env::temp_dir().join(format!("ambara-bundle-{}", uuid::Uuid::new_v4()));
+    let result = unpack_bundle(path, &scratch, registry).and_then(|unpacked| {
+        if unpacked.manifest.outputs.is_empty() {
+            return Err(BundleError::NoRecordedOutputs);
+        }
+        let actual: HashMap<(NodeId, String), String> = run_graph(&unpacked.graph, registry)?
+            .into_iter()
+            .map(|(node_id, port, value)| ((node_id, port), value_digest(&value)))
+            .collect();
+
+        let mut report = VerifyReport {
+            checked: unpacked.manifest.outputs.len(),
+            mismatches: Vec::new(),
+        };
+        for expected in &unpacked.manifest.outputs {
+            let actual = actual.get(&(expected.node_id, expected.port.clone()));
+            if actual != Some(&expected.digest) {
+                report.mismatches.push(OutputMismatch {
+                    node_id: expected.node_id,
+                    port: expected.port.clone(),
+                    expected: expected.digest.clone(),
+                    actual: actual.cloned(),
+                });
+            }
+        }
+        Ok(report)
+    });
+    let _ = std::fs::remove_dir_all(&scratch);
+    result
+}
+
+/// Input files referenced by `graph`: `(node, parameter, path)` for every
+/// non-empty [`UiHint::FileChooser`] parameter of an [`Category::Input`]
+/// filter. Nodes whose filter `registry` does not know are skipped.
+pub fn input_assets(
+    graph: &SerializedGraph,
+    registry: &FilterRegistry,
+) -> Vec<(NodeId, String, String)> {
+    let mut assets = Vec::new();
+    for node in &graph.nodes {
+        let Some(metadata) = registry.get_metadata(&node.filter_id) else {
+            continue;
+        };
+        if metadata.category != Category::Input {
+            continue;
+        }
+        for param in &metadata.parameters {
+            if !matches!(param.ui_hint, UiHint::FileChooser { .. }) {
+                continue;
+            }
+            let value = node.parameters.get(&param.name).unwrap_or(&param.default_value);
+            if let Some(path) = value.as_string().filter(|p| !p.is_empty()) {
+                assets.push((node.id, param.name.clone(), path.to_string()));
+            }
+        }
+    }
+    assets
+}
+
+/// SHA-256 of a value, as lowercase hex.
+///
+/// Images are hashed by dimensions and RGBA8 pixels, so the encoding they
+/// were loaded from does not matter; map entries are hashed in key order.
+pub fn value_digest(value: &Value) -> String {
+    let mut hasher = Sha256::new();
+    hash_value(&mut hasher, value);
+    format!("{:x}", hasher.finalize())
+}
+
+fn hash_value(hasher: &mut Sha256, value: &Value) {
+    match value {
+        Value::Image(image) => {
+            hasher.update(b"image");
+            match image.get_image() {
+                Some(image) => {
+                    let rgba = image.to_rgba8();
+                    hasher.update(rgba.width().to_le_bytes());
+                    hasher.update(rgba.height().to_le_bytes());
+                    hasher.update(rgba.as_raw());
+                }
+                None => {
+                    hasher.update(image.metadata.width.to_le_bytes());
+                    hasher.update(image.metadata.height.to_le_bytes());
+                }
+            }
+        }
+        Value::Integer(v) => {
+            hasher.update(b"integer");
+            hasher.update(v.to_le_bytes());
+        }
+        Value::Float(v) => {
+            hasher.update(b"float");
+            hasher.update(v.to_bits().to_le_bytes());
+        }
+        Value::String(v) => {
+            hasher.update(b"string");
+            hasher.update((v.len() as u64).to_le_bytes());
+            hasher.update(v.as_bytes());
+        }
+        Value::Boolean(v) => {
+            hasher.update(b"boolean");
+            hasher.update([u8::from(*v)]);
+        }
+        Value::Color(c) => {
+            hasher.update(b"color");
+            hasher.update([c.r, c.g, c.b, c.a]);
+        }
+        Value::Vector2(x, y) => {
+            hasher.update(b"vector2");
+            for v in [x, y] {
+                hasher.update(v.to_bits().to_le_bytes());
+            }
+        }
+        Value::Vector3(x, y, z) => {
+            hasher.update(b"vector3");
+            for v in [x, y, z] {
+                hasher.update(v.to_bits().to_le_bytes());
+            }
+        }
+        Value::Array(items) => {
+            hasher.update(b"array");
+            hasher.update((items.len() as u64).to_le_bytes());
+            for item in items {
+                hash_value(hasher, item);
+            }
+        }
+        Value::Map(map) => {
+            hasher.update(b"map");
+            hasher.update((map.len() as u64).to_le_bytes());
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for key in keys {
+                hash_value(hasher, &Value::String(key.clone()));
+                hash_value(hasher, &map[key]);
+            }
+        }
+        Value::None => hasher.update(b"none"),
+    }
+}
+
+/// Execute `graph` and return its terminal outputs, ordered by node and port.
+fn run_graph(
+    graph: &SerializedGraph,
+    registry: &FilterRegistry,
+) -> Result<Vec<(NodeId, String, Value)>, BundleError> {
+    let processing_graph = ProcessingGraph::from_serialized(graph, registry)?;
+    let result = ExecutionEngine::new()
+        .execute(&processing_graph, None)
+        .map_err(|e| BundleError::Execution(e.to_string()))?;
+    let mut outputs: Vec<(NodeId, String, Value)> = result
+        .outputs
+        .into_iter()
+        .flat_map(|(node_id, values)| {
+            values
+                .into_iter()
+                .map(move |(port, value)| (node_id, port, value))
+        })
+        .collect();
+    outputs.sort_by(|a, b| (a.0 .0, &a.1).cmp(&(b.0 .0, &b.1)));
+    Ok(outputs)
+}
+
+/// Read every file entry of a bundle into memory, rejecting paths that
+/// would leave the extraction directory.
+fn read_entries(path: &Path) -> Result<BTreeMap<String, Vec<u8>>, BundleError> {
+    let file = std::fs::File::open(path)?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut entries = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_path = entry.path()?.into_owned();
+        let safe = entry_path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+        let name = slash_path(&entry_path);
+        if !safe {
+            return Err(BundleError::UnexpectedEntry(name));
+        }
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        entries.insert(name, data);
+    }
+    Ok(entries)
+}
+
+fn append<W: std::io::Write>(
+    archive: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> std::io::Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    archive.append_data(&mut header, name, data)
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::structure::GraphNode;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    /// Write a 4x3 test image and a folder with two more.
+    fn write_inputs(dir: &Path) -> (PathBuf, PathBuf) {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(4, 3, |x, y| {
+            Rgba([x as u8 * 40, y as u8 * 60, 128, 255])
+        }));
+        let photo = dir.join("photo.png");
+        image.save(&photo).unwrap();
+        let frames = dir.join("frames");
+        std::fs::create_dir_all(frames.join("nested")).unwrap();
+        image.save(frames.join("a.png")).unwrap();
+        image.grayscale().save(frames.join("nested/b.png")).unwrap();
+        (photo, frames)
+    }
+
+    /// load_image(photo) → grayscale, plus load_folder(frames).
+    fn graph(photo: &Path, frames: &Path, registry: &FilterRegistry) -> SerializedGraph {
+        let mut graph = ProcessingGraph::new();
+        let mut load = GraphNode::new(registry.create("load_image").unwrap());
+        load.set_parameter("path", Value::String(photo.to_string_lossy().into_owned()));
+        let load = graph.add_node(load);
+        let mut gray = GraphNode::new(registry.create("grayscale").unwrap());
+        gray.set_parameter("use_gpu", Value::Boolean(false));
+        let gray = graph.add_node(gray);
+        graph.connect(load, "image", gray, "image").unwrap();
+        let mut folder = GraphNode::new(registry.create("load_folder").unwrap());
+        folder.set_parameter("directory", Value::String(frames.to_string_lossy().into_owned()));
+        graph.add_node(folder);
+        graph.to_serialized()
+    }
+
+    #[test]
+    fn input_assets_come_from_file_parameters_of_input_filters() {
+        let registry = FilterRegistry::with_builtins();
+        let graph = graph(Path::new("a.png"), Path::new("frames"), &registry);
+        let mut found: Vec<_> = input_assets(&graph, &registry)
+            .into_iter()
+            .map(|(_, parameter, path)| (parameter, path))
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("directory".to_string(), "frames".to_string()),
+                ("path".to_string(), "a.png".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pack_and_unpack_rewrite_asset_paths() {
+        let registry = FilterRegistry::with_builtins();
+        let source = tempfile::tempdir().unwrap();
+        let (photo, frames) = write_inputs(source.path());
+        let graph = graph(&photo, &frames, &registry);
+
+        let bundle = source.path().join(format!("graph.{BUNDLE_EXTENSION}"));
+        let manifest = pack_bundle(&graph, &registry, &PackOptions::default(), &bundle).unwrap();
+        assert_eq!(manifest.assets.len(), 2);
+        assert!(manifest.files.contains_key("assets/1-frames/nested/b.png"));
+
+        // Delete the originals: the unpacked graph must not need them.
+        std::fs::remove_file(&photo).unwrap();
+        std::fs::remove_dir_all(&frames).unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let unpacked = unpack_bundle(&bundle, target.path(), &registry).unwrap();
+        for (_, _, path) in input_assets(&unpacked.graph, &registry) {
+            let path = Path::new(&path);
+            assert!(path.is_absolute() && path.exists(), "{}", path.display());
+        }
+        assert!(target.path().join(GRAPH_ENTRY).exists());
+        assert!(ProcessingGraph::from_serialized(&unpacked.graph, &registry).is_ok());
+    }
+
+    #[test]
+    fn missing_inputs_are_reported() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let graph = graph(&dir.path().join("gone.png"), dir.path(), &registry);
+        let err = pack_bundle(&graph, &registry, &PackOptions::default(), &dir.path().join("b.ambara"));
+        assert!(matches!(err, Err(BundleError::MissingAsset { parameter, .. }) if parameter == "path"));
+    }
+
+    #[test]
+    fn verify_reproduces_recorded_outputs() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let (photo, frames) = write_inputs(dir.path());
+        let graph = graph(&photo, &frames, &registry);
+        let bundle = dir.path().join("graph.ambara");
+        let options = PackOptions {
+            include_outputs: true,
+            ..PackOptions::default()
+        };
+
+        let manifest = pack_bundle(&graph, &registry, &options, &bundle).unwrap();
+        assert!(manifest.outputs.iter().any(|o| o.path.is_some()));
+        let report = verify_bundle(&bundle, &registry).unwrap();
+        assert_eq!(report.checked, manifest.outputs.len());
+        assert!(report.is_identical(), "{report}");
+    }
+
+    #[test]
+    fn verify_needs_recorded_outputs() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("empty.ambara");
+        pack_bundle(&SerializedGraph::new(), &registry, &PackOptions::default(), &bundle).unwrap();
+        assert!(matches!(
+            verify_bundle(&bundle, &registry),
+            Err(BundleError::NoRecordedOutputs)
+        ));
+    }
+
+    #[test]
+    fn tampered_entries_are_rejected() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let (photo, frames) = write_inputs(dir.path());
+        let bundle = dir.path().join("graph.ambara");
+        pack_bundle(&graph(&photo, &frames, &registry), &registry, &PackOptions::default(), &bundle)
+            .unwrap();
+
+        // Rebuild the archive with one asset replaced.
+        let mut entries = read_entries(&bundle).unwrap();
+        entries.insert("assets/0-photo.png".to_string(), b"not a png".to_vec());
+        let mut archive = tar::Builder::new(GzEncoder::new(
+            std::fs::File::create(&bundle).unwrap(),
+            Compression::default(),
+        ));
+        for (name, data) in &entries {
+            append(&mut archive, name, data).unwrap();
+        }
+        archive.into_inner().unwrap().finish().unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        assert!(matches!(
+            unpack_bundle(&bundle, target.path(), &registry),
+            Err(BundleError::DigestMismatch(name)) if name == "assets/0-photo.png"
+        ));
+    }
+
+    #[test]
+    fn plugin_manifests_for_used_filters_are_embedded() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = SerializedGraph::new();
+        let mut node: super::super::SerializedNode =
+            (&GraphNode::new(registry.create("grayscale").unwrap())).into();
+        node.filter_id = "example.invert".to_string();
+        graph.nodes.push(node);
+
+        let manifest = |id: &str, filter: &str| {
+            PluginManifest::from_toml_str(&format!(
+                r#"
+                [plugin]
+                id = "{id}"
+                name = "Example"
+                version = "0.1.0"
+                description = "Example"
+                author = "Ambara"
+                license = "MIT"
+                ambara_abi_version = 1
+                min_ambara_version = "0.1.0"
+                max_ambara_version = "99.0.0"
+                [plugin.capabilities]
+                [plugin.filters]
+                ids = ["{filter}"]
+                "#
+            ))
+            .unwrap()
+        };
+        let options = PackOptions {
+            plugins: vec![
+                manifest("com.example.used", "example.invert"),
+                manifest("com.example.unused", "example.other"),
+            ],
+            ..PackOptions::default()
+        };
+        let bundle = dir.path().join("plugins.ambara");
+        let packed = pack_bundle(&graph, &registry, &options, &bundle).unwrap();
+        assert_eq!(packed.plugins.len(), 1);
+        assert_eq!(packed.plugins[0].filters, vec!["example.invert".to_string()]);
+
+        let entries = read_entries(&bundle).unwrap();
+        let toml = String::from_utf8_lossy(&entries["plugins/com.example.used.toml"]);
+        assert_eq!(
+            PluginManifest::from_toml_str(&toml).unwrap().plugin.id,
+            "com.example.used"
+        );
+    }
+}
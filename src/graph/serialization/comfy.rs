@@ -0,0 +1,1109 @@
+//! Import of ComfyUI workflows.
+//!
+//! ComfyUI saves workflows in two formats. The UI format ("Save") has
+//! `nodes` and `links` arrays, node positions, and positional
+//! `widgets_values`. The API format ("Save (API format)") maps node IDs to a
+//! `class_type` and named `inputs`, with links written as `[node, slot]`.
+//! [`import_comfy_workflow`] accepts either and builds a [`SerializedGraph`]:
+//!
+//! - Classes with a builtin counterpart become that filter (`comfy_*`,
+//!   `load_image`, `save_image`, `preview`), with widget values as
+//!   parameters. Loader nodes that Ambara models as parameters
+//!   (`EmptyLatentImage`, `ControlNetLoader`, `UpscaleModelLoader`) are
+//!   folded into the filter they feed.
+//! - `Reroute` nodes are resolved to the link they forward.
+//! - Any other class becomes an opaque `passthrough` node that keeps its
+//!   class type and widget values in the `comfy_class_type` and
+//!   `comfy_inputs` parameters, so nothing in the workflow is lost.
+//!
+//! Whatever could not be carried over is listed in the
+//! [`ComfyImportReport`].
+
+use super::{SerializedConnection, SerializedGraph, SerializedNode};
+use crate::core::error::NodeId;
+use crate::core::types::Value;
+use crate::graph::structure::Position;
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use thiserror::Error;
+
+/// Errors that prevent a workflow from being imported at all.
+#[derive(Error, Debug)]
+pub enum ComfyImportError {
+    /// The file is not JSON.
+    #[error("Invalid workflow JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The JSON is neither a UI-format nor an API-format workflow.
+    #[error("Not a ComfyUI workflow: expected a `nodes` array or nodes with a `class_type`")]
+    UnrecognizedFormat,
+
+    /// A node is missing its ID or class.
+    #[error("Invalid ComfyUI node {id}: {reason}")]
+    InvalidNode { id: String, reason: String },
+}
+
+/// The format a workflow was saved in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComfyFormat {
+    /// Written by ComfyUI's "Save".
+    Ui,
+    /// Written by ComfyUI's "Save (API format)".
+    Api,
+}
+
+/// A ComfyUI node imported as an opaque `passthrough` node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnmappedNode {
+    /// ID of the node in the workflow.
+    pub comfy_id: String,
+    /// Its ComfyUI class.
+    pub class_type: String,
+}
+
+/// What an import carried over and what it could not.
+#[derive(Debug, Clone, Serialize)]
+pub struct ComfyImportReport {
+    /// The format the workflow was saved in.
+    pub format: ComfyFormat,
+    /// Graph node created for each imported ComfyUI node, by ComfyUI ID.
+    pub node_ids: BTreeMap<String, NodeId>,
+    /// ComfyUI nodes folded into the parameters of the filter they feed.
+    pub folded: Vec<String>,
+    /// ComfyUI nodes with no builtin counterpart.
+    pub unmapped: Vec<UnmappedNode>,
+    /// Links, outputs and values that were dropped.
+    pub warnings: Vec<String>,
+}
+
+impl ComfyImportReport {
+    /// Whether everything in the workflow mapped onto builtin filters.
+    pub fn is_complete(&self) -> bool {
+        self.unmapped.is_empty() && self.warnings.is_empty()
+    }
+}
+
+impl fmt::Display for ComfyImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let format = match self.format {
+            ComfyFormat::Ui => "UI",
+            ComfyFormat::Api => "API",
+        };
+        writeln!(
+            f,
+            "Imported {} nodes from a {format}-format workflow ({} folded into parameters)",
+            self.node_ids.len(),
+            self.folded.len()
+        )?;
+        if !self.unmapped.is_empty() {
+            writeln!(f, "Kept as opaque passthrough nodes:")?;
+            for node in &self.unmapped {
+                writeln!(f, "  #{} {}", node.comfy_id, node.class_type)?;
+            }
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f, "Warnings:")?;
+            for warning in &self.warnings {
+                writeln!(f, "  {warning}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An imported workflow.
+#[derive(Debug, Clone)]
+pub struct ComfyImport {
+    /// The workflow as an Ambara graph.
+    pub graph: SerializedGraph,
+    /// What was and was not carried over.
+    pub report: ComfyImportReport,
+}
+
+/// Import a UI-format or API-format ComfyUI workflow.
+///
+/// # Errors
+///
+/// Returns a [`ComfyImportError`] if `json` is not a ComfyUI workflow.
+/// Nodes, links and values that cannot be mapped do not fail the import;
+/// they are listed in the report.
+pub fn import_comfy_workflow(json: &str) -> Result<ComfyImport, ComfyImportError> {
+    let raw: serde_json::Value = serde_json::from_str(json)?;
+    let (format, nodes) = if raw.get("nodes").is_some_and(serde_json::Value::is_array) {
+        (ComfyFormat::Ui, parse_ui(&raw)?)
+    } else {
+        (ComfyFormat::Api, parse_api(&raw)?)
+    };
+    Ok(Importer::new(format).run(nodes))
+}
+
+// ---------------------------------------------------------------------------
+// Class mappings
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Int,
+    Float,
+    Text,
+}
+
+/// A ComfyUI widget carried over as a parameter.
+struct Param {
+    widget: &'static str,
+    param: &'static str,
+    kind: Kind,
+}
+
+/// An input fed by a loader node whose widgets become parameters.
+struct Fold {
+    input: &'static str,
+    class_type: &'static str,
+    params: &'static [Param],
+}
+
+/// How a ComfyUI class maps onto a builtin filter.
+struct Mapping {
+    class_type: &'static str,
+    filter_id: &'static str,
+    params: &'static [Param],
+    /// ComfyUI input name → filter input port.
+    inputs: &'static [(&'static str, &'static str)],
+    folds: &'static [Fold],
+    /// Filter output port for each ComfyUI output slot.
+    outputs: &'static [&'static str],
+}
+
+const fn param(widget: &'static str, param: &'static str, kind: Kind) -> Param {
+    Param {
+        widget,
+        param,
+        kind,
+    }
+}
+
+const MAPPINGS: &[Mapping] = &[
+    Mapping {
+        class_type: "CheckpointLoaderSimple",
+        filter_id: "comfy_checkpoint_loader",
+        params: &[param("ckpt_name", "checkpoint_name", Kind::Text)],
+        inputs: &[],
+        folds: &[],
+        outputs: &["model_ref", "clip_ref", "vae_ref"],
+    },
+    Mapping {
+        class_type: "CLIPTextEncode",
+        filter_id: "comfy_clip_text_encode",
+        params: &[param("text", "text", Kind::Text)],
+        inputs: &[("clip", "clip_ref")],
+        folds: &[],
+        outputs: &["conditioning"],
+    },
+    Mapping {
+        class_type: "LoraLoader",
+        filter_id: "comfy_lora_loader",
+        params: &[
+            param("lora_name", "lora_name", Kind::Text),
+            param("strength_model", "model_strength", Kind::Float),
+            param("strength_clip", "clip_strength", Kind::Float),
+        ],
+        inputs: &[("model", "model_ref"), ("clip", "clip_ref")],
+        folds: &[],
+        outputs: &["model_ref", "clip_ref"],
+    },
+    Mapping {
+        class_type: "KSampler",
+        filter_id: "comfy_ksampler",
+        params: &[
+            param("seed", "seed", Kind::Int),
+            param("steps", "steps", Kind::Int),
+            param("cfg", "cfg_scale", Kind::Float),
+            param("sampler_name", "sampler_name", Kind::Text),
+            param("scheduler", "scheduler", Kind::Text),
+            param("denoise", "denoise", Kind::Float),
+        ],
+        inputs: &[
+            ("model", "model_ref"),
+            ("positive", "positive"),
+            ("negative", "negative"),
+        ],
+        folds: &[Fold {
+            input: "latent_image",
+            class_type: "EmptyLatentImage",
+            params: &[
+                param("width", "width", Kind::Int),
+                param("height", "height", Kind::Int),
+                param("batch_size", "batch_size", Kind::Int),
+            ],
+        }],
+        outputs: &["latent_ref"],
+    },
+    Mapping {
+        class_type: "VAEDecode",
+        filter_id: "comfy_vae_decode",
+        params: &[],
+        inputs: &[("samples", "latent_ref"), ("vae", "vae_ref")],
+        folds: &[],
+        outputs: &["image"],
+    },
+    Mapping {
+        class_type: "ControlNetApply",
+        filter_id: "comfy_controlnet_apply",
+        params: &[param("strength", "strength", Kind::Float)],
+        inputs: &[("conditioning", "conditioning"), ("image", "control_image")],
+        folds: &[Fold {
+            input: "control_net",
+            class_type: "ControlNetLoader",
+            params: &[param("control_net_name", "controlnet_name", Kind::Text)],
+        }],
+        outputs: &["conditioning"],
+    },
+    Mapping {
+        class_type: "ImageUpscaleWithModel",
+        filter_id: "comfy_image_upscale",
+        params: &[],
+        inputs: &[("image", "image")],
+        folds: &[Fold {
+            input: "upscale_model",
+            class_type: "UpscaleModelLoader",
+            params: &[param("model_name", "upscale_model", Kind::Text)],
+        }],
+        outputs: &["image"],
+    },
+    Mapping {
+        class_type: "LoadImage",
+        filter_id: "load_image",
+        params: &[param("image", "path", Kind::Text)],
+        inputs: &[],
+        folds: &[],
+        outputs: &["image"],
+    },
+    Mapping {
+        class_type: "SaveImage",
+        filter_id: "save_image",
+        params: &[param("filename_prefix", "filename", Kind::Text)],
+        inputs: &[("images", "image")],
+        folds: &[],
+        outputs: &[],
+    },
+    Mapping {
+        class_type: "PreviewImage",
+        filter_id: "preview",
+        params: &[],
+        inputs: &[("images", "image")],
+        folds: &[],
+        outputs: &[],
+    },
+];
+
+/// Widget order of UI-format `widgets_values` for the classes above.
+/// Empty names are UI-only widgets such as `control_after_generate`.
+fn widget_order(class_type: &str) -> Option<&'static [&'static str]> {
+    Some(match class_type {
+        "CheckpointLoaderSimple" => &["ckpt_name"],
+        "CLIPTextEncode" => &["text"],
+        "LoraLoader" => &["lora_name", "strength_model", "strength_clip"],
+        "KSampler" => &[
+            "seed",
+            "",
+            "steps",
+            "cfg",
+            "sampler_name",
+            "scheduler",
+            "denoise",
+        ],
+        "VAEDecode" | "ImageUpscaleWithModel" | "PreviewImage" => &[],
+        "ControlNetApply" => &["strength"],
+        "LoadImage" => &["image", ""],
+        "SaveImage" => &["filename_prefix"],
+        "EmptyLatentImage" => &["width", "height", "batch_size"],
+        "ControlNetLoader" => &["control_net_name"],
+        "UpscaleModelLoader" => &["model_name"],
+        _ => return None,
+    })
+}
+
+fn mapping(class_type: &str) -> Option<&'static Mapping> {
+    MAPPINGS.iter().find(|m| m.class_type == class_type)
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// A workflow node in a format-independent shape.
+#[derive(Debug, Clone)]
+struct ComfyNode {
+    id: String,
+    class_type: String,
+    title: Option<String>,
+    position: Option<Position>,
+    disabled: bool,
+    /// Widget values by name. UI-format nodes of unknown classes keep their
+    /// positional values under `widgets_values`.
+    widgets: serde_json::Map<String, serde_json::Value>,
+    /// Linked inputs in declaration order: name → (source node, slot).
+    links: Vec<(String, (String, u64))>,
+}
+
+fn parse_api(raw: &serde_json::Value) -> Result<Vec<ComfyNode>, ComfyImportError> {
+    let entries = raw
+        .as_object()
+        .filter(|o| !o.is_empty() && o.values().all(|n| n.get("class_type").is_some()))
+        .ok_or(ComfyImportError::UnrecognizedFormat)?;
+
+    let mut nodes = Vec::new();
+    for (id, node) in entries {
+        let class_type = node["class_type"]
+            .as_str()
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| invalid(id, "`class_type` is not a string"))?;
+        let mut widgets = serde_json::Map::new();
+        let mut links = Vec::new();
+        if let Some(inputs) = node.get("inputs").and_then(serde_json::Value::as_object) {
+            for (name, value) in inputs {
+                match api_link(value) {
+                    Some(link) => links.push((name.clone(), link)),
+                    None => {
+                        widgets.insert(name.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        nodes.push(ComfyNode {
+            id: id.clone(),
+            class_type: class_type.to_string(),
+            title: node
+                .pointer("/_meta/title")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string),
+            position: None,
+            disabled: false,
+            widgets,
+            links,
+        });
+    }
+    Ok(nodes)
+}
+
+/// An API-format `[node, slot]` link.
+fn api_link(value: &serde_json::Value) -> Option<(String, u64)> {
+    match value.as_array()?.as_slice() {
+        [node, slot] => {
+            let node = match node {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => return None,
+            };
+            Some((node, slot.as_u64()?))
+        }
+        _ => None,
+    }
+}
+
+fn parse_ui(raw: &serde_json::Value) -> Result<Vec<ComfyNode>, ComfyImportError> {
+    // link ID → (source node, source slot)
+    let mut link_sources: HashMap<String, (String, u64)> = HashMap::new();
+    for link in raw
+        .get("links")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten()
+    {
+        let (id, from, slot) = match link {
+            serde_json::Value::Array(l) if l.len() >= 3 => (&l[0], &l[1], &l[2]),
+            serde_json::Value::Object(l) => {
+                match (l.get("id"), l.get("origin_id"), l.get("origin_slot")) {
+                    (Some(id), Some(from), Some(slot)) => (id, from, slot),
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+        if let Some(slot) = slot.as_u64() {
+            link_sources.insert(id_text(id), (id_text(from), slot));
+        }
+    }
+
+    let mut nodes = Vec::new();
+    for node in raw["nodes"].as_array().into_iter().flatten() {
+        let id = node.get("id").map(id_text).unwrap_or_default();
+        if id.is_empty() {
+            return Err(invalid("?", "missing `id`"));
+        }
+        let class_type = node
+            .get("type")
+            .and_then(serde_json::Value::as_str)
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| invalid(&id, "missing `type`"))?
+            .to_string();
+
+        let links = node
+            .get("inputs")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|input| {
+                let name = input.get("name")?.as_str()?.to_string();
+                let source = link_sources.get(&id_text(input.get("link")?))?;
+                Some((name, source.clone()))
+            })
+            .collect();
+
+        let mut widgets = serde_json::Map::new();
+        match node.get("widgets_values") {
+            Some(serde_json::Value::Object(named)) => widgets.clone_from(named),
+            Some(serde_json::Value::Array(values)) => match widget_order(&class_type) {
+                Some(order) => {
+                    for (name, value) in order.iter().zip(values) {
+                        if !name.is_empty() {
+                            widgets.insert((*name).to_string(), value.clone());
+                        }
+                    }
+                }
+                None if !values.is_empty() => {
+                    widgets.insert("widgets_values".to_string(), values.clone().into());
+                }
+                None => {}
+            },
+            _ => {}
+        }
+
+        let position = match node.get("pos") {
+            Some(serde_json::Value::Array(p)) if p.len() >= 2 => {
+                Some((p[0].as_f64(), p[1].as_f64()))
+            }
+            Some(serde_json::Value::Object(p)) => Some((
+                p.get("0").and_then(serde_json::Value::as_f64),
+                p.get("1").and_then(serde_json::Value::as_f64),
+            )),
+            _ => None,
+        }
+        .and_then(|(x, y)| Some(Position::new(x?, y?)));
+
+        nodes.push(ComfyNode {
+            id,
+            class_type,
+            title: node
+                .get("title")
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string),
+            position,
+            // Mode 2 is "never", 4 is "bypass".
+            disabled: matches!(
+                node.get("mode").and_then(serde_json::Value::as_u64),
+                Some(2 | 4)
+            ),
+            widgets,
+            links,
+        });
+    }
+    resolve_reroutes(&mut nodes);
+    Ok(nodes)
+}
+
+/// Point links through `Reroute` nodes at their source and drop the reroutes.
+fn resolve_reroutes(nodes: &mut Vec<ComfyNode>) {
+    let reroutes: HashMap<String, Option<(String, u64)>> = nodes
+        .iter()
+        .filter(|n| n.class_type == "Reroute")
+        .map(|n| (n.id.clone(), n.links.first().map(|(_, l)| l.clone())))
+        .collect();
+    nodes.retain(|n| n.class_type != "Reroute");
+    for node in nodes.iter_mut() {
+        node.links.retain_mut(|(_, source)| {
+            // Bounded so a cycle of reroutes cannot hang the import.
+            for _ in 0..=reroutes.len() {
+                match reroutes.get(&source.0) {
+                    Some(Some(next)) => *source = next.clone(),
+                    Some(None) => return false,
+                    None => return true,
+                }
+            }
+            false
+        });
+    }
+}
+
+fn id_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn invalid(id: &str, reason: &str) -> ComfyImportError {
+    ComfyImportError::InvalidNode {
+        id: id.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Graph construction
+// ---------------------------------------------------------------------------
+
+struct Importer {
+    graph: SerializedGraph,
+    report: ComfyImportReport,
+}
+
+impl Importer {
+    fn new(format: ComfyFormat) -> Self {
+        Self {
+            graph: SerializedGraph::new(),
+            report: ComfyImportReport {
+                format,
+                node_ids: BTreeMap::new(),
+                folded: Vec::new(),
+                unmapped: Vec::new(),
+                warnings: Vec::new(),
+            },
+        }
+    }
+
+    fn run(mut self, mut nodes: Vec<ComfyNode>) -> ComfyImport {
+        nodes.sort_by(|a, b| id_order(&a.id).cmp(&id_order(&b.id)));
+        let by_id: HashMap<&str, &ComfyNode> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+        let folded = folded_nodes(&nodes, &by_id);
+        let layout = layout(&nodes);
+
+        for node in &nodes {
+            if folded.contains(node.id.as_str()) {
+                self.report.folded.push(node.id.clone());
+                continue;
+            }
+            let position = node
+                .position
+                .or_else(|| layout.get(node.id.as_str()).copied())
+                .unwrap_or_default();
+            let id = NodeId::new();
+            let serialized = match mapping(&node.class_type) {
+                Some(mapping) => self.mapped_node(id, node, mapping, &by_id, position),
+                None => self.opaque_node(id, node, position),
+            };
+            self.graph.nodes.push(serialized);
+            self.report.node_ids.insert(node.id.clone(), id);
+        }
+
+        for node in &nodes {
+            if !folded.contains(node.id.as_str()) {
+                self.connect_inputs(node, &by_id);
+            }
+        }
+        ComfyImport {
+            graph: self.graph,
+            report: self.report,
+        }
+    }
+
+    fn mapped_node(
+        &mut self,
+        id: NodeId,
+        node: &ComfyNode,
+        mapping: &Mapping,
+        by_id: &HashMap<&str, &ComfyNode>,
+        position: Position,
+    ) -> SerializedNode {
+        let mut parameters = HashMap::new();
+        self.copy_params(node, mapping.params, &mut parameters);
+        for fold in mapping.folds {
+            if let Some(source) =
+                linked(node, fold.input).and_then(|(src, _)| by_id.get(src.as_str()))
+            {
+                if source.class_type == fold.class_type {
+                    self.copy_params(source, fold.params, &mut parameters);
+                }
+            }
+        }
+        SerializedNode {
+            id,
+            filter_id: mapping.filter_id.to_string(),
+            position,
+            parameters,
+            label: node.title.clone().filter(|t| *t != node.class_type),
+            disabled: node.disabled,
+            promoted_parameters: Vec::new(),
+            filter_version: None,
+        }
+    }
+
+    fn opaque_node(&mut self, id: NodeId, node: &ComfyNode, position: Position) -> SerializedNode {
+        self.report.unmapped.push(UnmappedNode {
+            comfy_id: node.id.clone(),
+            class_type: node.class_type.clone(),
+        });
+        let mut parameters = HashMap::new();
+        parameters.insert(
+            "comfy_class_type".to_string(),
+            Value::String(node.class_type.clone()),
+        );
+        parameters.insert(
+            "comfy_inputs".to_string(),
+            Value::String(serde_json::Value::Object(node.widgets.clone()).to_string()),
+        );
+        SerializedNode {
+            id,
+            filter_id: "passthrough".to_string(),
+            position,
+            parameters,
+            label: Some(
+                node.title
+                    .clone()
+                    .unwrap_or_else(|| node.class_type.clone()),
+            ),
+            disabled: node.disabled,
+            promoted_parameters: Vec::new(),
+            filter_version: None,
+        }
+    }
+
+    fn copy_params(
+        &mut self,
+        node: &ComfyNode,
+        params: &[Param],
+        parameters: &mut HashMap<String, Value>,
+    ) {
+        for p in params {
+            let Some(raw) = node.widgets.get(p.widget) else {
+                continue;
+            };
+            let value = match p.kind {
+                Kind::Int => raw.as_i64().map(Value::Integer),
+                Kind::Float => raw.as_f64().map(Value::Float),
+                Kind::Text => raw.as_str().map(|s| Value::String(s.to_string())),
+            };
+            match value {
+                Some(value) => {
+                    parameters.insert(p.param.to_string(), value);
+                }
+                None => self.warn(node, format!("could not import `{}` = {raw}", p.widget)),
+            }
+        }
+    }
+
+    fn connect_inputs(&mut self, node: &ComfyNode, by_id: &HashMap<&str, &ComfyNode>) {
+        let to_node = self.report.node_ids[&node.id];
+        let target = mapping(&node.class_type);
+        for (index, (input, (source_id, slot))) in node.links.iter().enumerate() {
+            let to_port = match target {
+                Some(m) => match m.inputs.iter().find(|(name, _)| name == input) {
+                    Some((_, port)) => *port,
+                    None if m.folds.iter().any(|f| f.input == input) => {
+                        if by_id.get(source_id.as_str()).is_some_and(|s| {
+                            m.folds
+                                .iter()
+                                .any(|f| f.input == input && f.class_type == s.class_type)
+                        }) {
+                            continue;
+                        }
+                        self.warn(
+                            node,
+                            format!("input `{input}` must come from a loader node; link dropped"),
+                        );
+                        continue;
+                    }
+                    None => {
+                        self.warn(
+                            node,
+                            format!("input `{input}` has no Ambara port; link dropped"),
+                        );
+                        continue;
+                    }
+                },
+                None if index == 0 => "input",
+                None => {
+                    self.warn(
+                        node,
+                        format!("opaque node keeps only its first input; `{input}` dropped"),
+                    );
+                    continue;
+                }
+            };
+
+            let Some(from_node) = self.report.node_ids.get(source_id).copied() else {
+                self.warn(
+                    node,
+                    format!("input `{input}` links to missing node #{source_id}"),
+                );
+                continue;
+            };
+            let source_class = by_id.get(source_id.as_str()).map(|s| s.class_type.as_str());
+            let from_port = match source_class.and_then(mapping) {
+                Some(m) => m.outputs.get(*slot as usize).copied(),
+                None => (*slot == 0).then_some("output"),
+            };
+            let Some(from_port) = from_port else {
+                self.warn(
+                    node,
+                    format!(
+                        "input `{input}` uses output {slot} of #{source_id} ({}), which has no Ambara port",
+                        source_class.unwrap_or("?")
+                    ),
+                );
+                continue;
+            };
+            self.graph.connections.push(SerializedConnection {
+                from_node,
+                from_port: from_port.to_string(),
+                to_node,
+                to_port: to_port.to_string(),
+            });
+        }
+    }
+
+    fn warn(&mut self, node: &ComfyNode, message: String) {
+        self.report
+            .warnings
+            .push(format!("#{} {}: {message}", node.id, node.class_type));
+    }
+}
+
+/// Loader nodes whose every consumer folds them into its parameters.
+fn folded_nodes<'a>(nodes: &'a [ComfyNode], by_id: &HashMap<&str, &ComfyNode>) -> HashSet<&'a str> {
+    let mut consumers: HashMap<&str, Vec<bool>> = HashMap::new();
+    for node in nodes {
+        let folds = mapping(&node.class_type)
+            .map(|m| m.folds)
+            .unwrap_or_default();
+        for (input, (source, _)) in &node.links {
+            let folds_source = by_id.get(source.as_str()).is_some_and(|s| {
+                folds
+                    .iter()
+                    .any(|f| f.input == input && f.class_type == s.class_type)
+            });
+            consumers
+                .entry(source.as_str())
+                .or_default()
+                .push(folds_source);
+        }
+    }
+    nodes
+        .iter()
+        .filter(|n| {
+            consumers
+                .get(n.id.as_str())
+                .is_some_and(|c| c.iter().all(|folds| *folds))
+        })
+        .map(|n| n.id.as_str())
+        .collect()
+}
+
+fn linked<'a>(node: &'a ComfyNode, input: &str) -> Option<&'a (String, u64)> {
+    node.links
+        .iter()
+        .find(|(name, _)| name == input)
+        .map(|(_, l)| l)
+}
+
+/// Numeric IDs in numeric order, then everything else.
+fn id_order(id: &str) -> (u64, &str) {
+    (id.parse().unwrap_or(u64::MAX), id)
+}
+
+/// Column-by-depth positions for workflows saved without a layout.
+fn layout(nodes: &[ComfyNode]) -> HashMap<&str, Position> {
+    fn depth<'a>(
+        id: &'a str,
+        nodes: &HashMap<&'a str, &'a ComfyNode>,
+        memo: &mut HashMap<&'a str, usize>,
+        visiting: &mut HashSet<&'a str>,
+    ) -> usize {
+        if let Some(d) = memo.get(id) {
+            return *d;
+        }
+        if !visiting.insert(id) {
+            return 0;
+        }
+        let d = nodes.get(id).map_or(0, |node| {
+            node.links
+                .iter()
+                .map(|(_, (source, _))| depth(source, nodes, memo, visiting) + 1)
+                .max()
+                .unwrap_or(0)
+        });
+        visiting.remove(id);
+        memo.insert(id, d);
+        d
+    }
+
+    let by_id: HashMap<&str, &ComfyNode> = nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+    let mut memo = HashMap::new();
+    let mut rows: HashMap<usize, usize> = HashMap::new();
+    nodes
+        .iter()
+        .map(|node| {
+            let column = depth(&node.id, &by_id, &mut memo, &mut HashSet::new());
+            let row = rows.entry(column).or_default();
+            *row += 1;
+            (
+                node.id.as_str(),
+                Position::new(column as f64 * 300.0, (*row - 1) as f64 * 200.0),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const API_WORKFLOW: &str = r#"{
+        "3": {"class_type": "KSampler", "inputs": {
+            "seed": 42, "steps": 20, "cfg": 8, "sampler_name": "euler",
+            "scheduler": "normal", "denoise": 1,
+            "model": ["4", 0], "positive": ["6", 0], "negative": ["7", 0],
+            "latent_image": ["5", 0]}},
+        "4": {"class_type": "CheckpointLoaderSimple",
+              "inputs": {"ckpt_name": "sd15.safetensors"}},
+        "5": {"class_type": "EmptyLatentImage",
+              "inputs": {"width": 768, "height": 512, "batch_size": 1}},
+        "6": {"class_type": "CLIPTextEncode", "inputs": {"text": "a red fox", "clip": ["4", 1]},
+              "_meta": {"title": "Positive"}},
+        "7": {"class_type": "CLIPTextEncode", "inputs": {"text": "blurry", "clip": ["4", 1]}},
+        "8": {"class_type": "VAEDecode", "inputs": {"samples": ["3", 0], "vae": ["4", 2]}},
+        "9": {"class_type": "FaceRestore", "inputs": {"strength": 0.5, "image": ["8", 0]}},
+        "10": {"class_type": "SaveImage", "inputs": {"filename_prefix": "fox", "images": ["9", 0]}}
+    }"#;
+
+    /// The same graph without the face restore, as ComfyUI's "Save" writes it.
+    const UI_WORKFLOW: &str = r#"{
+        "last_node_id": 11, "last_link_id": 10,
+        "nodes": [
+            {"id": 3, "type": "KSampler", "pos": [863, 186], "mode": 0,
+             "inputs": [
+                {"name": "model", "type": "MODEL", "link": 1},
+                {"name": "positive", "type": "CONDITIONING", "link": 4},
+                {"name": "negative", "type": "CONDITIONING", "link": 6},
+                {"name": "latent_image", "type": "LATENT", "link": 2}],
+             "widgets_values": [156680208, "randomize", 20, 8, "euler", "normal", 1]},
+            {"id": 4, "type": "CheckpointLoaderSimple", "pos": [26, 474], "mode": 0,
+             "widgets_values": ["sd15.safetensors"]},
+            {"id": 5, "type": "EmptyLatentImage", "pos": [473, 609], "mode": 0,
+             "widgets_values": [512, 512, 1]},
+            {"id": 6, "type": "CLIPTextEncode", "pos": [415, 186], "mode": 0,
+             "inputs": [{"name": "clip", "type": "CLIP", "link": 3}],
+             "widgets_values": ["a red fox"]},
+            {"id": 7, "type": "CLIPTextEncode", "pos": [413, 389], "mode": 4,
+             "inputs": [{"name": "clip", "type": "CLIP", "link": 5}],
+             "widgets_values": ["blurry"]},
+            {"id": 8, "type": "VAEDecode", "pos": {"0": 1209, "1": 188}, "mode": 0,
+             "inputs": [
+                {"name": "samples", "type": "LATENT", "link": 7},
+                {"name": "vae", "type": "VAE", "link": 10}]},
+            {"id": 9, "type": "SaveImage", "pos": [1451, 189], "mode": 0,
+             "inputs": [{"name": "images", "type": "IMAGE", "link": 9}],
+             "widgets_values": ["ComfyUI"]},
+            {"id": 11, "type": "Reroute", "pos": [1000, 400], "mode": 0,
+             "inputs": [{"name": "", "type": "*", "link": 8}]}
+        ],
+        "links": [
+            [1, 4, 0, 3, 0, "MODEL"],
+            [2, 5, 0, 3, 3, "LATENT"],
+            [3, 4, 1, 6, 0, "CLIP"],
+            [4, 6, 0, 3, 1, "CONDITIONING"],
+            [5, 4, 1, 7, 0, "CLIP"],
+            [6, 7, 0, 3, 2, "CONDITIONING"],
+            [7, 3, 0, 8, 0, "LATENT"],
+            [8, 4, 2, 11, 0, "VAE"],
+            [9, 8, 0, 9, 0, "IMAGE"],
+            [10, 11, 0, 8, 1, "VAE"]
+        ],
+        "groups": [], "config": {}, "extra": {}, "version": 0.4
+    }"#;
+
+    fn node<'a>(import: &'a ComfyImport, comfy_id: &str) -> &'a SerializedNode {
+        let id = import.report.node_ids[comfy_id];
+        import.graph.nodes.iter().find(|n| n.id == id).unwrap()
+    }
+
+    /// Connections as `(comfy from, port, comfy to, port)`.
+    fn links(import: &ComfyImport) -> Vec<(String, String, String, String)> {
+        let comfy_id = |id: NodeId| {
+            import
+                .report
+                .node_ids
+                .iter()
+                .find(|(_, n)| **n == id)
+                .map(|(c, _)| c.clone())
+                .unwrap()
+        };
+        let mut links: Vec<_> = import
+            .graph
+            .connections
+            .iter()
+            .map(|c| {
+                (
+                    comfy_id(c.from_node),
+                    c.from_port.clone(),
+                    comfy_id(c.to_node),
+                    c.to_port.clone(),
+                )
+            })
+            .collect();
+        links.sort();
+        links
+    }
+
+    fn link(
+        from: &str,
+        from_port: &str,
+        to: &str,
+        to_port: &str,
+    ) -> (String, String, String, String) {
+        (from.into(), from_port.into(), to.into(), to_port.into())
+    }
+
+    mod api_format {
+        use super::*;
+
+        #[test]
+        fn maps_known_classes_and_folds_loaders() {
+            let import = import_comfy_workflow(API_WORKFLOW).unwrap();
+            assert_eq!(import.report.format, ComfyFormat::Api);
+            assert_eq!(import.report.folded, ["5"]);
+            assert_eq!(import.graph.nodes.len(), 7);
+
+            let sampler = node(&import, "3");
+            assert_eq!(sampler.filter_id, "comfy_ksampler");
+            assert_eq!(sampler.parameters["seed"], Value::Integer(42));
+            assert_eq!(sampler.parameters["cfg_scale"], Value::Float(8.0));
+            assert_eq!(sampler.parameters["width"], Value::Integer(768));
+            assert_eq!(
+                sampler.parameters["sampler_name"],
+                Value::String("euler".into())
+            );
+            assert_eq!(node(&import, "6").label.as_deref(), Some("Positive"));
+            assert_eq!(
+                node(&import, "4").parameters["checkpoint_name"],
+                Value::String("sd15.safetensors".into())
+            );
+
+            assert_eq!(
+                links(&import),
+                [
+                    link("3", "latent_ref", "8", "latent_ref"),
+                    link("4", "clip_ref", "6", "clip_ref"),
+                    link("4", "clip_ref", "7", "clip_ref"),
+                    link("4", "model_ref", "3", "model_ref"),
+                    link("4", "vae_ref", "8", "vae_ref"),
+                    link("6", "conditioning", "3", "positive"),
+                    link("7", "conditioning", "3", "negative"),
+                    link("8", "image", "9", "input"),
+                    link("9", "output", "10", "image"),
+                ]
+            );
+        }
+
+        #[test]
+        fn unknown_classes_become_opaque_passthrough_nodes() {
+            let import = import_comfy_workflow(API_WORKFLOW).unwrap();
+            assert_eq!(
+                import.report.unmapped,
+                [UnmappedNode {
+                    comfy_id: "9".into(),
+                    class_type: "FaceRestore".into()
+                }]
+            );
+            let opaque = node(&import, "9");
+            assert_eq!(opaque.filter_id, "passthrough");
+            assert_eq!(opaque.label.as_deref(), Some("FaceRestore"));
+            assert_eq!(
+                opaque.parameters["comfy_inputs"],
+                Value::String(r#"{"strength":0.5}"#.into())
+            );
+            assert!(!import.report.is_complete());
+            assert!(import.report.to_string().contains("#9 FaceRestore"));
+        }
+
+        #[test]
+        fn nodes_are_laid_out_by_depth() {
+            let import = import_comfy_workflow(API_WORKFLOW).unwrap();
+            assert_eq!(node(&import, "4").position, Position::new(0.0, 0.0));
+            assert_eq!(node(&import, "6").position, Position::new(300.0, 0.0));
+            assert_eq!(node(&import, "7").position, Position::new(300.0, 200.0));
+            assert_eq!(node(&import, "10").position.x, 1500.0);
+        }
+    }
+
+    mod ui_format {
+        use super::*;
+
+        #[test]
+        fn keeps_positions_widgets_and_modes() {
+            let import = import_comfy_workflow(UI_WORKFLOW).unwrap();
+            assert_eq!(import.report.format, ComfyFormat::Ui);
+            assert!(import.report.is_complete(), "{}", import.report);
+            assert_eq!(import.report.folded, ["5"]);
+
+            let sampler = node(&import, "3");
+            assert_eq!(sampler.position, Position::new(863.0, 186.0));
+            assert_eq!(sampler.parameters["seed"], Value::Integer(156680208));
+            assert_eq!(sampler.parameters["steps"], Value::Integer(20));
+            assert_eq!(
+                sampler.parameters["scheduler"],
+                Value::String("normal".into())
+            );
+            assert_eq!(sampler.parameters["height"], Value::Integer(512));
+            assert!(node(&import, "7").disabled);
+            assert_eq!(node(&import, "8").position, Position::new(1209.0, 188.0));
+            assert_eq!(
+                node(&import, "9").parameters["filename"],
+                Value::String("ComfyUI".into())
+            );
+        }
+
+        #[test]
+        fn reroutes_are_resolved() {
+            let import = import_comfy_workflow(UI_WORKFLOW).unwrap();
+            assert!(!import.report.node_ids.contains_key("11"));
+            assert!(links(&import).contains(&link("4", "vae_ref", "8", "vae_ref")));
+            assert_eq!(import.graph.connections.len(), 8);
+        }
+    }
+
+    mod report {
+        use super::*;
+
+        #[test]
+        fn unmappable_links_and_values_are_reported() {
+            let workflow = r#"{
+                "1": {"class_type": "LoadImage", "inputs": {"image": "fox.png"}},
+                "2": {"class_type": "VAEEncode", "inputs": {"pixels": ["1", 1]}},
+                "3": {"class_type": "KSampler", "inputs": {"steps": "many", "latent_image": ["2", 0]}}
+            }"#;
+            let import = import_comfy_workflow(workflow).unwrap();
+            assert_eq!(
+                import.report.warnings,
+                [
+                    "#3 KSampler: could not import `steps` = \"many\"",
+                    "#2 VAEEncode: input `pixels` uses output 1 of #1 (LoadImage), which has no Ambara port",
+                    "#3 KSampler: input `latent_image` must come from a loader node; link dropped",
+                ]
+            );
+            assert!(import.graph.connections.is_empty());
+        }
+
+        #[test]
+        fn rejects_documents_that_are_not_workflows() {
+            for json in [r#"{"version": "1.0.0"}"#, "[]", r#"{"1": {"inputs": {}}}"#] {
+                assert!(matches!(
+                    import_comfy_workflow(json),
+                    Err(ComfyImportError::UnrecognizedFormat)
+                ));
+            }
+            assert!(matches!(
+                import_comfy_workflow(r#"{"nodes": [{"id": 1}]}"#),
+                Err(ComfyImportError::InvalidNode { .. })
+            ));
+            assert!(matches!(
+                import_comfy_workflow("not json"),
+                Err(ComfyImportError::Json(_))
+            ));
+        }
+    }
+}
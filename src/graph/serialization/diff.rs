@@ -0,0 +1,946 @@
+//! Semantic diff and three-way merge of saved graphs.
+//!
+//! Both work on [`SerializedGraph`]s and match nodes by [`NodeId`], so a node
+//! keeps its identity when it is moved, relabelled or re-parameterised.
+//! Connections are matched by their endpoints.
+//!
+//! [`diff_graphs`] lists added and removed nodes, per-node changes (filter,
+//! label, disabled flag, position, parameters, promoted parameters) and added
+//! and removed connections. [`GraphDiff`] renders as text through `Display`
+//! and as JSON through `Serialize`.
+//!
+//! [`merge_graphs`] combines two graphs edited from a common base. Edits to
+//! different nodes, different parameters of one node, or different
+//! connections merge cleanly. Both sides changing the same value differently,
+//! deleting a node the other side changed, wiring a deleted node, or
+//! connecting different sources to the same input are reported as
+//! [`MergeConflict`]s; the merged graph then keeps "ours". Node positions
+//! never conflict: if both sides moved a node, ours wins.
+//!
+//! `ambara graph-merge %O %A %B --output %A` works as a git merge driver.
+
+use super::{SerializedConnection, SerializedGraph, SerializedNode};
+use crate::core::error::NodeId;
+use crate::core::types::Value;
+use crate::graph::structure::{GraphMetadata, Position};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A value that differs between two graphs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change<T> {
+    /// Value in the old graph.
+    pub before: T,
+    /// Value in the new graph.
+    pub after: T,
+}
+
+impl<T: PartialEq + Clone> Change<T> {
+    fn of(before: &T, after: &T) -> Option<Self> {
+        (before != after).then(|| Change {
+            before: before.clone(),
+            after: after.clone(),
+        })
+    }
+}
+
+/// A parameter that was set, cleared or changed.
+///
+/// `None` means the parameter is not stored (the filter default applies).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParameterChange {
+    /// Parameter name.
+    pub name: String,
+    /// Stored value in the old graph.
+    pub before: Option<Value>,
+    /// Stored value in the new graph.
+    pub after: Option<Value>,
+}
+
+/// Changes to a node present in both graphs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NodeChange {
+    /// The node.
+    pub node_id: NodeId,
+    /// Its filter in the new graph.
+    pub filter_id: String,
+    /// Filter replaced.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Change<String>>,
+    /// Filter version changed (e.g. by a migration).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_version: Option<Change<Option<String>>>,
+    /// Label changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<Change<Option<String>>>,
+    /// Enabled or disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<Change<bool>>,
+    /// Moved in the editor.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<Change<Position>>,
+    /// Promoted parameters changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub promoted_parameters: Option<Change<Vec<String>>>,
+    /// Parameter changes, by name.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<ParameterChange>,
+}
+
+/// Differences between two graphs.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct GraphDiff {
+    /// Graph metadata changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Change<GraphMetadata>>,
+    /// Nodes only in the new graph.
+    pub added_nodes: Vec<SerializedNode>,
+    /// Nodes only in the old graph.
+    pub removed_nodes: Vec<SerializedNode>,
+    /// Nodes in both graphs that differ.
+    pub changed_nodes: Vec<NodeChange>,
+    /// Connections only in the new graph.
+    pub added_connections: Vec<SerializedConnection>,
+    /// Connections only in the old graph.
+    pub removed_connections: Vec<SerializedConnection>,
+}
+
+impl GraphDiff {
+    /// Whether the graphs are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_none()
+            && self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_connections.is_empty()
+            && self.removed_connections.is_empty()
+    }
+}
+
+/// Compare two graphs node by node.
+pub fn diff_graphs(before: &SerializedGraph, after: &SerializedGraph) -> GraphDiff {
+    let old: HashMap<NodeId, &SerializedNode> = before.nodes.iter().map(|n| (n.id, n)).collect();
+    let new: HashMap<NodeId, &SerializedNode> = after.nodes.iter().map(|n| (n.id, n)).collect();
+
+    let mut diff = GraphDiff {
+        metadata: Change::of(&before.metadata, &after.metadata),
+        ..GraphDiff::default()
+    };
+    for node in &after.nodes {
+        match old.get(&node.id) {
+            None => diff.added_nodes.push(node.clone()),
+            Some(previous) => {
+                if let Some(change) = diff_node(previous, node) {
+                    diff.changed_nodes.push(change);
+                }
+            }
+        }
+    }
+    diff.removed_nodes = before
+        .nodes
+        .iter()
+        .filter(|n| !new.contains_key(&n.id))
+        .cloned()
+        .collect();
+
+    let old_connections: HashSet<&SerializedConnection> = before.connections.iter().collect();
+    let new_connections: HashSet<&SerializedConnection> = after.connections.iter().collect();
+    diff.added_connections = after
+        .connections
+        .iter()
+        .filter(|c| !old_connections.contains(c))
+        .cloned()
+        .collect();
+    diff.removed_connections = before
+        .connections
+        .iter()
+        .filter(|c| !new_connections.contains(c))
+        .cloned()
+        .collect();
+    diff
+}
+
+fn diff_node(before: &SerializedNode, after: &SerializedNode) -> Option<NodeChange> {
+    let mut names: Vec<&String> = before
+        .parameters
+        .keys()
+        .chain(after.parameters.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    names.sort();
+    let parameters = names
+        .into_iter()
+        .filter_map(|name| {
+            let (old, new) = (before.parameters.get(name), after.parameters.get(name));
+            (old != new).then(|| ParameterChange {
+                name: name.clone(),
+                before: old.cloned(),
+                after: new.cloned(),
+            })
+        })
+        .collect();
+
+    let change = NodeChange {
+        node_id: after.id,
+        filter_id: after.filter_id.clone(),
+        filter: Change::of(&before.filter_id, &after.filter_id),
+        filter_version: Change::of(&before.filter_version, &after.filter_version),
+        label: Change::of(&before.label, &after.label),
+        disabled: Change::of(&before.disabled, &after.disabled),
+        position: Change::of(&before.position, &after.position),
+        promoted_parameters: Change::of(&before.promoted_parameters, &after.promoted_parameters),
+        parameters,
+    };
+    let unchanged = change.filter.is_none()
+        && change.filter_version.is_none()
+        && change.label.is_none()
+        && change.disabled.is_none()
+        && change.position.is_none()
+        && change.promoted_parameters.is_none()
+        && change.parameters.is_empty();
+    (!unchanged).then_some(change)
+}
+
+fn describe_node(node: &SerializedNode) -> String {
+    match &node.label {
+        Some(label) => format!("{} {} \"{label}\"", node.id, node.filter_id),
+        None => format!("{} {}", node.id, node.filter_id),
+    }
+}
+
+fn describe_connection(conn: &SerializedConnection) -> String {
+    format!(
+        "{}.{} -> {}.{}",
+        conn.from_node, conn.from_port, conn.to_node, conn.to_port
+    )
+}
+
+fn describe_option<T: fmt::Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => "(unset)".to_string(),
+    }
+}
+
+impl fmt::Display for GraphDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No changes");
+        }
+        if let Some(metadata) = &self.metadata {
+            let (before, after) = (&metadata.before, &metadata.after);
+            writeln!(f, "~ metadata")?;
+            let fields = [
+                ("name", &before.name, &after.name),
+                ("description", &before.description, &after.description),
+                ("author", &before.author, &after.author),
+                ("version", &before.version, &after.version),
+                ("created_at", &before.created_at, &after.created_at),
+                ("modified_at", &before.modified_at, &after.modified_at),
+            ];
+            for (field, old, new) in fields {
+                if old != new {
+                    writeln!(f, "    {field}: {} -> {}", describe_option(old), describe_option(new))?;
+                }
+            }
+            if before.tags != after.tags {
+                writeln!(f, "    tags: {:?} -> {:?}", before.tags, after.tags)?;
+            }
+        }
+        for node in &self.added_nodes {
+            writeln!(f, "+ node {}", describe_node(node))?;
+        }
+        for node in &self.removed_nodes {
+            writeln!(f, "- node {}", describe_node(node))?;
+        }
+        for node in &self.changed_nodes {
+            writeln!(f, "~ node {} {}", node.node_id, node.filter_id)?;
+            if let Some(c) = &node.filter {
+                writeln!(f, "    filter: {} -> {}", c.before, c.after)?;
+            }
+            if let Some(c) = &node.filter_version {
+                writeln!(
+                    f,
+                    "    filter version: {} -> {}",
+                    describe_option(&c.before),
+                    describe_option(&c.after)
+                )?;
+            }
+            if let Some(c) = &node.label {
+                writeln!(
+                    f,
+                    "    label: {} -> {}",
+                    describe_option(&c.before),
+                    describe_option(&c.after)
+                )?;
+            }
+            if let Some(c) = &node.disabled {
+                writeln!(f, "    disabled: {} -> {}", c.before, c.after)?;
+            }
+            if let Some(c) = &node.position {
+                writeln!(
+                    f,
+                    "    moved: ({}, {}) -> ({}, {})",
+                    c.before.x, c.before.y, c.after.x, c.after.y
+                )?;
+            }
+            if let Some(c) = &node.promoted_parameters {
+                writeln!(f, "    promoted: {:?} -> {:?}", c.before, c.after)?;
+            }
+            for p in &node.parameters {
+                writeln!(
+                    f,
+                    "    {}: {} -> {}",
+                    p.name,
+                    describe_option(&p.before),
+                    describe_option(&p.after)
+                )?;
+            }
+        }
+        for conn in &self.added_connections {
+            writeln!(f, "+ connection {}", describe_connection(conn))?;
+        }
+        for conn in &self.removed_connections {
+            writeln!(f, "- connection {}", describe_connection(conn))?;
+        }
+        Ok(())
+    }
+}
+
+/// Which side of a merge did something.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Ours,
+    Theirs,
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Ours => write!(f, "ours"),
+            Side::Theirs => write!(f, "theirs"),
+        }
+    }
+}
+
+/// An edit [`merge_graphs`] could not reconcile.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MergeConflict {
+    /// Both sides changed a node field (filter, label, disabled flag, filter
+    /// version) differently. Values are rendered as text.
+    Field {
+        node_id: NodeId,
+        field: String,
+        ours: String,
+        theirs: String,
+    },
+    /// Both sides changed a parameter differently.
+    Parameter {
+        node_id: NodeId,
+        name: String,
+        base: Option<Value>,
+        ours: Option<Value>,
+        theirs: Option<Value>,
+    },
+    /// One side deleted a node the other changed. The node is kept.
+    DeletedNode { node_id: NodeId, deleted_by: Side },
+    /// One side connected a node the other deleted. The connection is
+    /// dropped.
+    ConnectionToDeletedNode { connection: SerializedConnection, added_by: Side },
+    /// The sides connected different sources to one input. Ours is kept.
+    Input {
+        node_id: NodeId,
+        port: String,
+        ours: String,
+        theirs: String,
+    },
+    /// Both sides changed a metadata field differently.
+    Metadata {
+        field: String,
+        ours: String,
+        theirs: String,
+    },
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeConflict::Field {
+                node_id,
+                field,
+                ours,
+                theirs,
+            } => write!(f, "node {node_id}: {field} changed to {ours} (ours) and {theirs} (theirs)"),
+            MergeConflict::Parameter {
+                node_id,
+                name,
+                ours,
+                theirs,
+                ..
+            } => write!(
+                f,
+                "node {node_id}: parameter '{name}' changed to {} (ours) and {} (theirs)",
+                describe_option(ours),
+                describe_option(theirs)
+            ),
+            MergeConflict::DeletedNode {
+                node_id,
+                deleted_by,
+            } => write!(f, "node {node_id}: deleted by {deleted_by} but changed by the other side"),
+            MergeConflict::ConnectionToDeletedNode {
+                connection,
+                added_by,
+            } => write!(
+                f,
+                "connection {} added by {added_by} to a node the other side deleted",
+                describe_connection(connection)
+            ),
+            MergeConflict::Input {
+                node_id,
+                port,
+                ours,
+                theirs,
+            } => write!(
+                f,
+                "node {node_id}: input '{port}' connected to {ours} (ours) and {theirs} (theirs)"
+            ),
+            MergeConflict::Metadata {
+                field,
+                ours,
+                theirs,
+            } => write!(f, "metadata: {field} changed to {ours} (ours) and {theirs} (theirs)"),
+        }
+    }
+}
+
+/// Result of [`merge_graphs`].
+#[derive(Debug, Clone)]
+pub struct MergeResult {
+    /// The merged graph; conflicting values are taken from "ours".
+    pub graph: SerializedGraph,
+    /// Edits that could not be reconciled.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    /// Whether the merge needs no manual resolution.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Outcome of merging one value.
+enum Merge3<T> {
+    Resolved(T),
+    Conflict,
+}
+
+/// Three-way merge of one value: a side that did not change it yields to
+/// the side that did.
+fn merge3<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> Merge3<T> {
+    if ours == theirs || theirs == base {
+        Merge3::Resolved(ours.clone())
+    } else if ours == base {
+        Merge3::Resolved(theirs.clone())
+    } else {
+        Merge3::Conflict
+    }
+}
+
+/// Three-way merge of a list treated as a set: items either side removed
+/// are dropped, items either side added are kept.
+fn merge_set<T: PartialEq + Clone>(base: &[T], ours: &[T], theirs: &[T]) -> Vec<T> {
+    let mut merged: Vec<T> = ours
+        .iter()
+        .filter(|item| !base.contains(item) || theirs.contains(item))
+        .cloned()
+        .collect();
+    for item in theirs {
+        if !base.contains(item) && !merged.contains(item) {
+            merged.push(item.clone());
+        }
+    }
+    merged
+}
+
+/// Merge `ours` and `theirs`, both edited from `base`.
+pub fn merge_graphs(
+    base: &SerializedGraph,
+    ours: &SerializedGraph,
+    theirs: &SerializedGraph,
+) -> MergeResult {
+    let mut conflicts = Vec::new();
+    let base_nodes: HashMap<NodeId, &SerializedNode> = base.nodes.iter().map(|n| (n.id, n)).collect();
+    let our_nodes: HashMap<NodeId, &SerializedNode> = ours.nodes.iter().map(|n| (n.id, n)).collect();
+    let their_nodes: HashMap<NodeId, &SerializedNode> =
+        theirs.nodes.iter().map(|n| (n.id, n)).collect();
+
+    // Ours first, then nodes only theirs has, then nodes only base has.
+    let mut order: Vec<NodeId> = Vec::new();
+    let mut seen = HashSet::new();
+    for node in ours.nodes.iter().chain(&theirs.nodes).chain(&base.nodes) {
+        if seen.insert(node.id) {
+            order.push(node.id);
+        }
+    }
+
+    let mut nodes = Vec::new();
+    for id in order {
+        let (b, o, t) = (
+            base_nodes.get(&id).copied(),
+            our_nodes.get(&id).copied(),
+            their_nodes.get(&id).copied(),
+        );
+        match (b, o, t) {
+            (_, Some(o), Some(t)) => nodes.push(merge_node(b, o, t, &mut conflicts)),
+            (Some(b), Some(o), None) => {
+                if !same_content(b, o) {
+                    conflicts.push(MergeConflict::DeletedNode {
+                        node_id: id,
+                        deleted_by: Side::Theirs,
+                    });
+                    nodes.push(o.clone());
+                }
+            }
+            (Some(b), None, Some(t)) => {
+                if !same_content(b, t) {
+                    conflicts.push(MergeConflict::DeletedNode {
+                        node_id: id,
+                        deleted_by: Side::Ours,
+                    });
+                    nodes.push(t.clone());
+                }
+            }
+            (None, Some(node), None) | (None, None, Some(node)) => nodes.push(node.clone()),
+            (_, None, None) => {}
+        }
+    }
+
+    let present: HashSet<NodeId> = nodes.iter().map(|n| n.id).collect();
+    let merged = merge_set(&base.connections, &ours.connections, &theirs.connections);
+    let mut connections: Vec<SerializedConnection> = Vec::new();
+    for conn in merged {
+        if !present.contains(&conn.from_node) || !present.contains(&conn.to_node) {
+            let added_by = if ours.connections.contains(&conn) {
+                Side::Ours
+            } else {
+                Side::Theirs
+            };
+            conflicts.push(MergeConflict::ConnectionToDeletedNode {
+                connection: conn,
+                added_by,
+            });
+            continue;
+        }
+        // An input takes one connection; ours come first, so a later
+        // connection into the same input is theirs.
+        if let Some(existing) = connections
+            .iter()
+            .find(|c| c.to_node == conn.to_node && c.to_port == conn.to_port)
+        {
+            conflicts.push(MergeConflict::Input {
+                node_id: conn.to_node,
+                port: conn.to_port.clone(),
+                ours: format!("{}.{}", existing.from_node, existing.from_port),
+                theirs: format!("{}.{}", conn.from_node, conn.from_port),
+            });
+            continue;
+        }
+        connections.push(conn);
+    }
+
+    let metadata = merge_metadata(&base.metadata, &ours.metadata, &theirs.metadata, &mut conflicts);
+    MergeResult {
+        graph: SerializedGraph {
+            version: SerializedGraph::VERSION.to_string(),
+            metadata,
+            nodes,
+            connections,
+        },
+        conflicts,
+    }
+}
+
+/// Whether two versions of a node differ only in position.
+fn same_content(a: &SerializedNode, b: &SerializedNode) -> bool {
+    let mut b = b.clone();
+    b.position = a.position;
+    *a == b
+}
+
+fn merge_node(
+    base: Option<&SerializedNode>,
+    ours: &SerializedNode,
+    theirs: &SerializedNode,
+    conflicts: &mut Vec<MergeConflict>,
+) -> SerializedNode {
+    // A node both sides added is merged as if the base had their common
+    // values only; the field comparisons below then see no base change.
+    let base = base.unwrap_or(ours);
+    let mut merged = ours.clone();
+
+    let node_id = ours.id;
+    let mut field = |name: &str, ours: String, theirs: String| {
+        conflicts.push(MergeConflict::Field {
+            node_id,
+            field: name.to_string(),
+            ours,
+            theirs,
+        });
+    };
+    match merge3(&base.filter_id, &ours.filter_id, &theirs.filter_id) {
+        Merge3::Resolved(v) => merged.filter_id = v,
+        Merge3::Conflict => field("filter", ours.filter_id.clone(), theirs.filter_id.clone()),
+    }
+    match merge3(&base.filter_version, &ours.filter_version, &theirs.filter_version) {
+        Merge3::Resolved(v) => merged.filter_version = v,
+        Merge3::Conflict => field(
+            "filter version",
+            describe_option(&ours.filter_version),
+            describe_option(&theirs.filter_version),
+        ),
+    }
+    match merge3(&base.label, &ours.label, &theirs.label) {
+        Merge3::Resolved(v) => merged.label = v,
+        Merge3::Conflict => field(
+            "label",
+            describe_option(&ours.label),
+            describe_option(&theirs.label),
+        ),
+    }
+    match merge3(&base.disabled, &ours.disabled, &theirs.disabled) {
+        Merge3::Resolved(v) => merged.disabled = v,
+        Merge3::Conflict => field("disabled", ours.disabled.to_string(), theirs.disabled.to_string()),
+    }
+    if let Merge3::Resolved(v) = merge3(&base.position, &ours.position, &theirs.position) {
+        merged.position = v;
+    }
+    merged.promoted_parameters = merge_set(
+        &base.promoted_parameters,
+        &ours.promoted_parameters,
+        &theirs.promoted_parameters,
+    );
+
+    let mut names: Vec<&String> = base
+        .parameters
+        .keys()
+        .chain(ours.parameters.keys())
+        .chain(theirs.parameters.keys())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    names.sort();
+    for name in names {
+        let (b, o, t) = (
+            base.parameters.get(name).cloned(),
+            ours.parameters.get(name).cloned(),
+            theirs.parameters.get(name).cloned(),
+        );
+        match merge3(&b, &o, &t) {
+            Merge3::Resolved(Some(value)) => {
+                merged.parameters.insert(name.clone(), value);
+            }
+            Merge3::Resolved(None) => {
+                merged.parameters.remove(name);
+            }
+            Merge3::Conflict => conflicts.push(MergeConflict::Parameter {
+                node_id: merged.id,
+                name: name.clone(),
+                base: b,
+                ours: o,
+                theirs: t,
+            }),
+        }
+    }
+    merged
+}
+
+fn merge_metadata(
+    base: &GraphMetadata,
+    ours: &GraphMetadata,
+    theirs: &GraphMetadata,
+    conflicts: &mut Vec<MergeConflict>,
+) -> GraphMetadata {
+    let mut merged = ours.clone();
+    let fields = [
+        ("name", &base.name, &ours.name, &theirs.name, &mut merged.name),
+        ("description", &base.description, &ours.description, &theirs.description, &mut merged.description),
+        ("author", &base.author, &ours.author, &theirs.author, &mut merged.author),
+        ("version", &base.version, &ours.version, &theirs.version, &mut merged.version),
+        ("created_at", &base.created_at, &ours.created_at, &theirs.created_at, &mut merged.created_at),
+    ];
+    for (name, b, o, t, slot) in fields {
+        match merge3(b, o, t) {
+            Merge3::Resolved(v) => *slot = v,
+            Merge3::Conflict => conflicts.push(MergeConflict::Metadata {
+                field: name.to_string(),
+                ours: describe_option(o),
+                theirs: describe_option(t),
+            }),
+        }
+    }
+    merged.tags = merge_set(&base.tags, &ours.tags, &theirs.tags);
+    // Timestamps of concurrent edits always differ; keep the later one.
+    merged.modified_at = ours.modified_at.clone().max(theirs.modified_at.clone());
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(filter: &str, parameters: &[(&str, Value)]) -> SerializedNode {
+        SerializedNode {
+            id: NodeId::new(),
+            filter_id: filter.to_string(),
+            position: Position::new(0.0, 0.0),
+            parameters: parameters
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+            label: None,
+            disabled: false,
+            promoted_parameters: Vec::new(),
+            filter_version: Some("1.0.0".to_string()),
+        }
+    }
+
+    fn connect(from: &SerializedNode, to: &SerializedNode) -> SerializedConnection {
+        SerializedConnection {
+            from_node: from.id,
+            from_port: "image".to_string(),
+            to_node: to.id,
+            to_port: "image".to_string(),
+        }
+    }
+
+    /// load → blur → save
+    fn base() -> SerializedGraph {
+        let load = node("load_image", &[("path", Value::String("in.png".to_string()))]);
+        let blur = node("gaussian_blur", &[("sigma", Value::Float(1.0))]);
+        let save = node("save_image", &[]);
+        let mut graph = SerializedGraph::new();
+        graph.connections = vec![connect(&load, &blur), connect(&blur, &save)];
+        graph.nodes = vec![load, blur, save];
+        graph
+    }
+
+    fn node_mut<'a>(graph: &'a mut SerializedGraph, filter: &str) -> &'a mut SerializedNode {
+        graph.nodes.iter_mut().find(|n| n.filter_id == filter).unwrap()
+    }
+
+    mod diff {
+        use super::*;
+
+        #[test]
+        fn identical_graphs_have_no_diff() {
+            let graph = base();
+            let diff = diff_graphs(&graph, &graph);
+            assert!(diff.is_empty());
+            assert_eq!(diff.to_string(), "No changes\n");
+        }
+
+        #[test]
+        fn reports_node_and_parameter_changes() {
+            let before = base();
+            let mut after = before.clone();
+            let blur = node_mut(&mut after, "gaussian_blur");
+            blur.parameters.insert("sigma".to_string(), Value::Float(2.5));
+            blur.label = Some("Soften".to_string());
+            let blur_id = blur.id;
+
+            let diff = diff_graphs(&before, &after);
+            assert_eq!(diff.changed_nodes.len(), 1);
+            let change = &diff.changed_nodes[0];
+            assert_eq!(change.node_id, blur_id);
+            assert_eq!(change.label.as_ref().unwrap().after.as_deref(), Some("Soften"));
+            assert_eq!(
+                change.parameters,
+                vec![ParameterChange {
+                    name: "sigma".to_string(),
+                    before: Some(Value::Float(1.0)),
+                    after: Some(Value::Float(2.5)),
+                }]
+            );
+            let text = diff.to_string();
+            assert!(text.contains("label: (unset) -> Soften"), "{text}");
+            assert!(text.contains("sigma: 1.0000 -> 2.5000"), "{text}");
+        }
+
+        #[test]
+        fn reports_added_and_removed_nodes_and_connections() {
+            let before = base();
+            let mut after = before.clone();
+            let blur = after.nodes.remove(1);
+            after.connections.clear();
+            let sharpen = node("sharpen", &[]);
+            after.connections.push(connect(&after.nodes[0], &sharpen));
+            after.connections.push(connect(&sharpen, &after.nodes[1]));
+            after.nodes.push(sharpen.clone());
+
+            let diff = diff_graphs(&before, &after);
+            assert_eq!(diff.added_nodes, vec![sharpen]);
+            assert_eq!(diff.removed_nodes, vec![blur]);
+            assert_eq!(diff.added_connections.len(), 2);
+            assert_eq!(diff.removed_connections.len(), 2);
+
+            let json = serde_json::to_value(&diff).unwrap();
+            assert_eq!(json["added_nodes"][0]["filter_id"], "sharpen");
+            assert!(json.get("metadata").is_none());
+        }
+    }
+
+    mod merge {
+        use super::*;
+
+        #[test]
+        fn non_overlapping_edits_merge_cleanly() {
+            let base = base();
+            let mut ours = base.clone();
+            node_mut(&mut ours, "gaussian_blur")
+                .parameters
+                .insert("sigma".to_string(), Value::Float(3.0));
+            let mut theirs = base.clone();
+            node_mut(&mut theirs, "gaussian_blur").label = Some("Soften".to_string());
+            node_mut(&mut theirs, "load_image")
+                .parameters
+                .insert("path".to_string(), Value::String("other.png".to_string()));
+            theirs.metadata.tags.push("portrait".to_string());
+
+            let merged = merge_graphs(&base, &ours, &theirs);
+            assert!(merged.is_clean(), "{:?}", merged.conflicts);
+            let mut graph = merged.graph;
+            let blur = node_mut(&mut graph, "gaussian_blur").clone();
+            assert_eq!(blur.parameters["sigma"], Value::Float(3.0));
+            assert_eq!(blur.label.as_deref(), Some("Soften"));
+            assert_eq!(
+                node_mut(&mut graph, "load_image").parameters["path"],
+                Value::String("other.png".to_string())
+            );
+            assert_eq!(graph.metadata.tags, vec!["portrait".to_string()]);
+            assert_eq!(graph.connections, base.connections);
+        }
+
+        #[test]
+        fn conflicting_parameter_edits_keep_ours() {
+            let base = base();
+            let mut ours = base.clone();
+            node_mut(&mut ours, "gaussian_blur")
+                .parameters
+                .insert("sigma".to_string(), Value::Float(3.0));
+            let mut theirs = base.clone();
+            node_mut(&mut theirs, "gaussian_blur")
+                .parameters
+                .insert("sigma".to_string(), Value::Float(5.0));
+
+            let mut merged = merge_graphs(&base, &ours, &theirs);
+            assert!(matches!(
+                merged.conflicts.as_slice(),
+                [MergeConflict::Parameter { name, .. }] if name == "sigma"
+            ));
+            assert_eq!(
+                node_mut(&mut merged.graph, "gaussian_blur").parameters["sigma"],
+                Value::Float(3.0)
+            );
+        }
+
+        #[test]
+        fn additions_on_both_sides_are_kept() {
+            let base = base();
+            let mut ours = base.clone();
+            let ours_node = node("invert", &[]);
+            ours.nodes.push(ours_node.clone());
+            let mut theirs = base.clone();
+            let their_node = node("sharpen", &[]);
+            theirs.connections.push(connect(&base.nodes[0], &their_node));
+            theirs.nodes.push(their_node.clone());
+
+            let merged = merge_graphs(&base, &ours, &theirs);
+            assert!(merged.is_clean(), "{:?}", merged.conflicts);
+            assert_eq!(merged.graph.nodes.len(), 5);
+            assert!(merged.graph.connections.contains(&connect(&base.nodes[0], &their_node)));
+        }
+
+        #[test]
+        fn deleting_a_changed_node_conflicts() {
+            let base = base();
+            let mut ours = base.clone();
+            ours.nodes.retain(|n| n.filter_id != "gaussian_blur");
+            ours.connections.clear();
+            let mut theirs = base.clone();
+            node_mut(&mut theirs, "gaussian_blur")
+                .parameters
+                .insert("sigma".to_string(), Value::Float(4.0));
+
+            let merged = merge_graphs(&base, &ours, &theirs);
+            assert!(merged.conflicts.contains(&MergeConflict::DeletedNode {
+                node_id: base.nodes[1].id,
+                deleted_by: Side::Ours,
+            }));
+            assert_eq!(merged.graph.nodes.len(), 3);
+            // Ours removed the connections and theirs did not touch them.
+            assert!(merged.graph.connections.is_empty());
+        }
+
+        #[test]
+        fn deleting_an_unchanged_node_merges() {
+            let base = base();
+            let mut ours = base.clone();
+            ours.nodes.retain(|n| n.filter_id != "save_image");
+            ours.connections.truncate(1);
+            let mut theirs = base.clone();
+            node_mut(&mut theirs, "save_image").position = Position::new(50.0, 10.0);
+
+            let merged = merge_graphs(&base, &ours, &theirs);
+            assert!(merged.is_clean(), "{:?}", merged.conflicts);
+            assert_eq!(merged.graph.nodes.len(), 2);
+            assert_eq!(merged.graph.connections.len(), 1);
+        }
+
+        #[test]
+        fn wiring_a_deleted_node_conflicts() {
+            let base = base();
+            let mut ours = base.clone();
+            ours.nodes.retain(|n| n.filter_id != "save_image");
+            ours.connections.truncate(1);
+            let mut theirs = base.clone();
+            let extra = connect(&base.nodes[0], &base.nodes[2]);
+            theirs.connections.push(SerializedConnection {
+                to_port: "mask".to_string(),
+                ..extra
+            });
+
+            let merged = merge_graphs(&base, &ours, &theirs);
+            assert!(matches!(
+                merged.conflicts.as_slice(),
+                [MergeConflict::ConnectionToDeletedNode { added_by: Side::Theirs, .. }]
+            ));
+            assert_eq!(merged.graph.connections.len(), 1);
+        }
+
+        #[test]
+        fn rewiring_one_input_twice_conflicts() {
+            let base = base();
+            let invert = node("invert", &[]);
+            let sharpen = node("sharpen", &[]);
+            let save = base.nodes[2].clone();
+            let mut ours = base.clone();
+            ours.connections[1] = connect(&invert, &save);
+            ours.nodes.push(invert);
+            let mut theirs = base.clone();
+            theirs.connections[1] = connect(&sharpen, &save);
+            theirs.nodes.push(sharpen);
+
+            let merged = merge_graphs(&base, &ours, &theirs);
+            assert!(matches!(
+                merged.conflicts.as_slice(),
+                [MergeConflict::Input { port, .. }] if port == "image"
+            ));
+            assert_eq!(merged.graph.connections, ours.connections);
+        }
+    }
+}
@@ -0,0 +1,808 @@
+//! Upgrading saved graphs to the current format.
+//!
+//! Two kinds of steps are registered on [`Migrations`]:
+//!
+//! - **Format steps** rewrite the raw JSON of a document from one
+//!   [`SerializedGraph::VERSION`] to the next. They run first, before the
+//!   document is deserialized, so they can handle layouts the current
+//!   structs no longer accept.
+//! - **Filter steps** rewrite a single node when the filter version stored in
+//!   the file (`SerializedNode::filter_version`) is older than the installed
+//!   filter's [`NodeMetadata::version`](crate::core::node::NodeMetadata).
+//!   They edit parameters and rename ports through [`NodeEdit`], which keeps
+//!   connections and promoted parameters in step. Filters ship their own
+//!   steps from [`FilterNode::migrations`](crate::core::node::FilterNode::migrations);
+//!   the [`FilterRegistry`] collects them, and
+//!   [`FilterRegistry::add_migration`] adds steps for filters that cannot
+//!   declare them, such as plugin filters.
+//!
+//! Steps chain: a document at 1.0.0 goes through `1.0.0 → 1.1.0`, then
+//! `1.1.0 → 1.2.0`, and so on. [`Migrations::migrate`] returns the upgraded
+//! graph together with a [`MigrationReport`] of everything it changed.
+//! [`SerializedGraph::load`] runs the registry's migrations.
+//!
+//! ```rust,ignore
+//! impl FilterNode for Rotate {
+//!     fn migrations(&self) -> Vec<FilterMigration> {
+//!         vec![FilterMigration::new(Version::new(1, 0, 0), Version::new(2, 0, 0), |node| {
+//!             if let Some(Value::String(angle)) = node.remove_parameter("angle") {
+//!                 let degrees = angle.parse::<f64>().map_err(|e| e.to_string())?;
+//!                 node.set_parameter("angle", Value::Float(degrees));
+//!             }
+//!             Ok(())
+//!         })]
+//!     }
+//!     // ...
+//! }
+//!
+//! let migrated = SerializedGraph::load(&text, &registry)?;
+//! ```
+
+use super::{SerializedConnection, SerializedGraph, SerializedNode};
+use crate::core::error::NodeId;
+use crate::core::types::Value;
+use crate::filters::registry::FilterRegistry;
+use semver::Version;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Version assumed for documents without a `version` field.
+const UNVERSIONED: Version = Version::new(0, 0, 0);
+
+/// Filter version assumed for nodes saved before filter versions were
+/// recorded (format 1.0.0). Every builtin filter was at 1.0.0 then.
+const UNRECORDED_FILTER_VERSION: &str = "1.0.0";
+
+/// Errors that prevent a saved graph from being upgraded.
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    /// The document is not JSON, or does not match the current format after
+    /// all format steps ran.
+    #[error("Invalid graph JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The document is not a JSON object.
+    #[error("Invalid graph JSON: expected an object")]
+    NotAnObject,
+
+    /// A version string is not valid SemVer.
+    #[error("Invalid version '{version}': {reason}")]
+    InvalidVersion { version: String, reason: String },
+
+    /// The document was written by a newer Ambara.
+    #[error("Graph format {found} is newer than the supported {supported}")]
+    UnsupportedVersion { found: String, supported: String },
+
+    /// No format step starts at this version.
+    #[error("No migration from graph format {from} to {to}")]
+    NoFormatPath { from: String, to: String },
+
+    /// A format step failed.
+    #[error("Migrating graph format {from} to {to} failed: {message}")]
+    FormatStep { from: String, to: String, message: String },
+
+    /// A filter step failed.
+    #[error("Migrating node {node_id} ({filter_id}) from {from} to {to} failed: {message}")]
+    FilterStep {
+        node_id: NodeId,
+        filter_id: String,
+        from: String,
+        to: String,
+        message: String,
+    },
+}
+
+type FormatFn = dyn Fn(&mut serde_json::Value) -> Result<(), String> + Send + Sync;
+type NodeFn = dyn Fn(&mut NodeEdit<'_>) -> Result<(), String> + Send + Sync;
+
+#[derive(Clone)]
+struct FormatStep {
+    from: Version,
+    to: Version,
+    description: String,
+    apply: Arc<FormatFn>,
+}
+
+/// A step that upgrades nodes of one filter from version `from` to `to`.
+#[derive(Clone)]
+pub struct FilterMigration {
+    from: Version,
+    to: Version,
+    apply: Arc<NodeFn>,
+}
+
+impl FilterMigration {
+    /// Create a step that upgrades a node from filter version `from` to `to`.
+    pub fn new<F>(from: Version, to: Version, apply: F) -> Self
+    where
+        F: Fn(&mut NodeEdit<'_>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            from,
+            to,
+            apply: Arc::new(apply),
+        }
+    }
+
+    /// Filter version the step upgrades from.
+    pub fn from(&self) -> &Version {
+        &self.from
+    }
+
+    /// Filter version the step upgrades to.
+    pub fn to(&self) -> &Version {
+        &self.to
+    }
+}
+
+impl fmt::Debug for FilterMigration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FilterMigration({} -> {})", self.from, self.to)
+    }
+}
+
+/// Mutable view of one node for filter steps.
+///
+/// Renames carry over to the node's connections and promoted parameters.
+pub struct NodeEdit<'a> {
+    node: &'a mut SerializedNode,
+    connections: &'a mut [SerializedConnection],
+}
+
+impl NodeEdit<'_> {
+    /// The node being migrated.
+    pub fn node(&self) -> &SerializedNode {
+        self.node
+    }
+
+    /// Get a stored parameter value.
+    pub fn parameter(&self, name: &str) -> Option<&Value> {
+        self.node.parameters.get(name)
+    }
+
+    /// Set a parameter value.
+    pub fn set_parameter(&mut self, name: impl Into<String>, value: Value) {
+        self.node.parameters.insert(name.into(), value);
+    }
+
+    /// Remove a stored parameter value.
+    pub fn remove_parameter(&mut self, name: &str) -> Option<Value> {
+        self.node.parameters.remove(name)
+    }
+
+    /// Rename a parameter, including its promoted socket and connection.
+    pub fn rename_parameter(&mut self, from: &str, to: &str) {
+        if let Some(value) = self.node.parameters.remove(from) {
+            self.node.parameters.insert(to.to_string(), value);
+        }
+        for name in &mut self.node.promoted_parameters {
+            if name == from {
+                *name = to.to_string();
+            }
+        }
+        self.rename_input(from, to);
+    }
+
+    /// Rename an input port on every connection into this node.
+    pub fn rename_input(&mut self, from: &str, to: &str) {
+        let id = self.node.id;
+        for conn in self.connections.iter_mut() {
+            if conn.to_node == id && conn.to_port == from {
+                conn.to_port = to.to_string();
+            }
+        }
+    }
+
+    /// Rename an output port on every connection out of this node.
+    pub fn rename_output(&mut self, from: &str, to: &str) {
+        let id = self.node.id;
+        for conn in self.connections.iter_mut() {
+            if conn.from_node == id && conn.from_port == from {
+                conn.from_port = to.to_string();
+            }
+        }
+    }
+}
+
+/// A node whose filter steps ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeMigration {
+    /// The migrated node.
+    pub node_id: NodeId,
+    /// Its filter.
+    pub filter_id: String,
+    /// Filter version stored in the file.
+    pub from_version: String,
+    /// Filter version after migration.
+    pub to_version: String,
+}
+
+/// What [`Migrations::migrate`] changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Format version of the document as read.
+    pub from_version: String,
+    /// Format version after migration (always [`SerializedGraph::VERSION`]).
+    pub to_version: String,
+    /// Descriptions of the format steps that ran, in order.
+    pub format_steps: Vec<String>,
+    /// Nodes whose filter steps ran.
+    pub nodes: Vec<NodeMigration>,
+    /// Things that could not be migrated; the graph is still returned.
+    pub warnings: Vec<String>,
+}
+
+impl MigrationReport {
+    /// Whether the document was already current.
+    pub fn is_empty(&self) -> bool {
+        self.format_steps.is_empty() && self.nodes.is_empty() && self.warnings.is_empty()
+    }
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Graph format {} is current", self.to_version);
+        }
+        if self.from_version != self.to_version {
+            writeln!(
+                f,
+                "Migrated graph format {} to {}",
+                self.from_version, self.to_version
+            )?;
+            for step in &self.format_steps {
+                writeln!(f, "  {step}")?;
+            }
+        }
+        if !self.nodes.is_empty() {
+            writeln!(f, "Migrated nodes:")?;
+            for node in &self.nodes {
+                writeln!(
+                    f,
+                    "  {} {} {} -> {}",
+                    node.node_id, node.filter_id, node.from_version, node.to_version
+                )?;
+            }
+        }
+        if !self.warnings.is_empty() {
+            writeln!(f, "Warnings:")?;
+            for warning in &self.warnings {
+                writeln!(f, "  {warning}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A graph upgraded to the current format.
+#[derive(Debug, Clone)]
+pub struct MigratedGraph {
+    /// The graph at [`SerializedGraph::VERSION`].
+    pub graph: SerializedGraph,
+    /// What was changed.
+    pub report: MigrationReport,
+}
+
+/// Registered format and filter upgrade steps.
+#[derive(Clone, Default)]
+pub struct Migrations {
+    format: Vec<FormatStep>,
+    filters: HashMap<String, Vec<FilterMigration>>,
+}
+
+impl fmt::Debug for Migrations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migrations")
+            .field(
+                "format",
+                &self
+                    .format
+                    .iter()
+                    .map(|s| format!("{} -> {}", s.from, s.to))
+                    .collect::<Vec<_>>(),
+            )
+            .field("filters", &self.filters.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Migrations {
+    /// Create an empty set of migrations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Steps for every format version Ambara has written.
+    pub fn builtin() -> Self {
+        Self::new()
+            .with_format_step(
+                UNVERSIONED,
+                Version::new(1, 0, 0),
+                "stamp format version on unversioned document",
+                |_| Ok(()),
+            )
+            .with_format_step(
+                Version::new(1, 0, 0),
+                Version::new(1, 1, 0),
+                "record filter versions (assumed 1.0.0)",
+                |raw| {
+                    let nodes = raw
+                        .get_mut("nodes")
+                        .and_then(serde_json::Value::as_array_mut)
+                        .into_iter()
+                        .flatten();
+                    for node in nodes.filter_map(serde_json::Value::as_object_mut) {
+                        node.entry("filter_version")
+                            .or_insert_with(|| UNRECORDED_FILTER_VERSION.into());
+                    }
+                    Ok(())
+                },
+            )
+    }
+
+    /// Register a step that rewrites the raw JSON of format `from` into
+    /// format `to`.
+    pub fn with_format_step<F>(
+        mut self,
+        from: Version,
+        to: Version,
+        description: impl Into<String>,
+        apply: F,
+    ) -> Self
+    where
+        F: Fn(&mut serde_json::Value) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.format.push(FormatStep {
+            from,
+            to,
+            description: description.into(),
+            apply: Arc::new(apply),
+        });
+        self
+    }
+
+    /// Register a step that upgrades nodes of `filter_id` from filter
+    /// version `from` to `to`.
+    pub fn with_filter_step<F>(
+        mut self,
+        filter_id: impl Into<String>,
+        from: Version,
+        to: Version,
+        apply: F,
+    ) -> Self
+    where
+        F: Fn(&mut NodeEdit<'_>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.add_filter_migration(filter_id, FilterMigration::new(from, to, apply));
+        self
+    }
+
+    /// Add a step for nodes of `filter_id`.
+    pub fn add_filter_migration(&mut self, filter_id: impl Into<String>, migration: FilterMigration) {
+        self.filters.entry(filter_id.into()).or_default().push(migration);
+    }
+
+    /// Replace every step for nodes of `filter_id`.
+    pub fn set_filter_migrations(
+        &mut self,
+        filter_id: impl Into<String>,
+        migrations: Vec<FilterMigration>,
+    ) {
+        let filter_id = filter_id.into();
+        if migrations.is_empty() {
+            self.filters.remove(&filter_id);
+        } else {
+            self.filters.insert(filter_id, migrations);
+        }
+    }
+
+    /// Steps registered for nodes of `filter_id`.
+    pub fn filter_migrations(&self, filter_id: &str) -> &[FilterMigration] {
+        self.filters.get(filter_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Parse and upgrade a saved graph.
+    pub fn migrate_json(
+        &self,
+        json: &str,
+        registry: &FilterRegistry,
+    ) -> Result<MigratedGraph, MigrationError> {
+        self.migrate(serde_json::from_str(json)?, registry)
+    }
+
+    /// Upgrade a saved graph to the current format and filter versions.
+    ///
+    /// Nodes whose filter is unknown to `registry` are left alone; loading
+    /// the graph reports them. Filter versions with no registered path to
+    /// the installed version are stamped current with a warning.
+    pub fn migrate(
+        &self,
+        mut raw: serde_json::Value,
+        registry: &FilterRegistry,
+    ) -> Result<MigratedGraph, MigrationError> {
+        if !raw.is_object() {
+            return Err(MigrationError::NotAnObject);
+        }
+        let current = parse_version(SerializedGraph::VERSION)?;
+        let found = match raw.get("version").and_then(serde_json::Value::as_str) {
+            Some(version) => parse_version(version)?,
+            None => UNVERSIONED,
+        };
+        if found > current {
+            return Err(MigrationError::UnsupportedVersion {
+                found: found.to_string(),
+                supported: current.to_string(),
+            });
+        }
+
+        let mut report = MigrationReport {
+            from_version: found.to_string(),
+            to_version: current.to_string(),
+            format_steps: Vec::new(),
+            nodes: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        let mut version = found;
+        while version < current {
+            let step = self
+                .format
+                .iter()
+                .find(|s| s.from == version)
+                .ok_or_else(|| MigrationError::NoFormatPath {
+                    from: version.to_string(),
+                    to: current.to_string(),
+                })?;
+            if step.to <= step.from {
+                return Err(MigrationError::FormatStep {
+                    from: step.from.to_string(),
+                    to: step.to.to_string(),
+                    message: "step does not advance the version".to_string(),
+                });
+            }
+            (step.apply)(&mut raw).map_err(|message| MigrationError::FormatStep {
+                from: step.from.to_string(),
+                to: step.to.to_string(),
+                message,
+            })?;
+            report.format_steps.push(format!("{} -> {}: {}", step.from, step.to, step.description));
+            version = step.to.clone();
+        }
+        raw["version"] = current.to_string().into();
+
+        let mut graph: SerializedGraph = serde_json::from_value(raw)?;
+        let SerializedGraph {
+            nodes, connections, ..
+        } = &mut graph;
+        for node in nodes.iter_mut() {
+            self.migrate_node(node, connections, registry, &mut report)?;
+        }
+
+        Ok(MigratedGraph { graph, report })
+    }
+
+    fn migrate_node(
+        &self,
+        node: &mut SerializedNode,
+        connections: &mut [SerializedConnection],
+        registry: &FilterRegistry,
+        report: &mut MigrationReport,
+    ) -> Result<(), MigrationError> {
+        let Some(metadata) = registry.get_metadata(&node.filter_id) else {
+            return Ok(());
+        };
+        let installed = parse_version(&metadata.version)?;
+        let Some(stored) = node.filter_version.as_deref() else {
+            node.filter_version = Some(installed.to_string());
+            return Ok(());
+        };
+        let stored = parse_version(stored)?;
+        if stored > installed {
+            report.warnings.push(format!(
+                "Node {} uses {} {stored}, newer than the installed {installed}",
+                node.id, node.filter_id
+            ));
+            return Ok(());
+        }
+
+        let steps = self.filter_migrations(&node.filter_id);
+        let mut version = stored.clone();
+        while version < installed {
+            let Some(step) = steps.iter().find(|s| s.from == version) else {
+                report.warnings.push(format!(
+                    "No migration for node {} ({}) from {version} to {installed}; parameters kept as saved",
+                    node.id, node.filter_id
+                ));
+                break;
+            };
+            if step.to <= step.from {
+                return Err(MigrationError::FilterStep {
+                    node_id: node.id,
+                    filter_id: node.filter_id.clone(),
+                    from: step.from.to_string(),
+                    to: step.to.to_string(),
+                    message: "step does not advance the version".to_string(),
+                });
+            }
+            let mut edit = NodeEdit {
+                node: &mut *node,
+                connections: &mut *connections,
+            };
+            (step.apply)(&mut edit).map_err(|message| MigrationError::FilterStep {
+                node_id: node.id,
+                filter_id: node.filter_id.clone(),
+                from: step.from.to_string(),
+                to: step.to.to_string(),
+                message,
+            })?;
+            version = step.to.clone();
+        }
+
+        if version != stored {
+            report.nodes.push(NodeMigration {
+                node_id: node.id,
+                filter_id: node.filter_id.clone(),
+                from_version: stored.to_string(),
+                to_version: installed.to_string(),
+            });
+        }
+        node.filter_version = Some(installed.to_string());
+        Ok(())
+    }
+}
+
+fn parse_version(version: &str) -> Result<Version, MigrationError> {
+    Version::parse(version).map_err(|e| MigrationError::InvalidVersion {
+        version: version.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::{ExecutionContext, ValidationContext};
+    use crate::core::error::{ExecutionError, ValidationError};
+    use crate::core::node::{FilterNode, NodeMetadata};
+    use crate::core::port::{ParameterDefinition, PortDefinition};
+    use crate::core::types::PortType;
+    use serde_json::json;
+
+    /// `rotate` as it might look after `angle` became a number and the
+    /// output was renamed.
+    #[derive(Debug, Clone)]
+    struct RotateV2;
+
+    impl FilterNode for RotateV2 {
+        fn metadata(&self) -> NodeMetadata {
+            NodeMetadata::builder("rotate", "Rotate")
+                .version("2.0.0")
+                .input(PortDefinition::input("image", PortType::Image))
+                .output(PortDefinition::output("rotated", PortType::Image))
+                .parameter(ParameterDefinition::new("angle", PortType::Float, Value::Float(90.0)))
+                .build()
+        }
+
+        fn validate(&self, _ctx: &ValidationContext) -> Result<(), ValidationError> {
+            Ok(())
+        }
+
+        fn execute(&self, _ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+            Ok(())
+        }
+
+        fn clone_box(&self) -> Box<dyn FilterNode> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn registry() -> FilterRegistry {
+        let mut registry = FilterRegistry::new();
+        registry.register(|| Box::new(RotateV2));
+        registry
+    }
+
+    fn rotate_migration() -> Migrations {
+        Migrations::builtin().with_filter_step(
+            "rotate",
+            Version::new(1, 0, 0),
+            Version::new(2, 0, 0),
+            |node| {
+                if let Some(Value::String(angle)) = node.remove_parameter("angle") {
+                    let degrees = angle.parse::<f64>().map_err(|e| e.to_string())?;
+                    node.set_parameter("angle", Value::Float(degrees));
+                }
+                node.rename_output("image", "rotated");
+                Ok(())
+            },
+        )
+    }
+
+    /// A format 1.0.0 document: load → rotate → save.
+    fn legacy_document(angle: &str) -> (serde_json::Value, NodeId) {
+        let (load, rotate, save) = (NodeId::new(), NodeId::new(), NodeId::new());
+        let node = |id: NodeId, filter: &str, parameters: serde_json::Value| {
+            json!({
+                "id": id, "filter_id": filter, "position": {"x": 0.0, "y": 0.0},
+                "parameters": parameters, "label": null, "disabled": false
+            })
+        };
+        let doc = json!({
+            "version": "1.0.0",
+            "metadata": {"name": "legacy", "tags": []},
+            "nodes": [
+                node(load, "load_image", json!({})),
+                node(rotate, "rotate", json!({"angle": Value::String(angle.to_string())})),
+                node(save, "save_image", json!({})),
+            ],
+            "connections": [
+                {"from_node": load, "from_port": "image", "to_node": rotate, "to_port": "image"},
+                {"from_node": rotate, "from_port": "image", "to_node": save, "to_port": "image"},
+            ]
+        });
+        (doc, rotate)
+    }
+
+    mod format {
+        use super::*;
+
+        #[test]
+        fn unversioned_documents_are_upgraded() {
+            let migrated = Migrations::builtin()
+                .migrate_json(r#"{ "nodes": [], "connections": [] }"#, &registry())
+                .unwrap();
+            assert_eq!(migrated.graph.version, SerializedGraph::VERSION);
+            assert_eq!(migrated.report.from_version, "0.0.0");
+            assert_eq!(migrated.report.format_steps.len(), 2);
+        }
+
+        #[test]
+        fn current_documents_report_nothing() {
+            let json = SerializedGraph::new().to_json().unwrap();
+            let migrated = Migrations::builtin().migrate_json(&json, &registry()).unwrap();
+            assert!(migrated.report.is_empty());
+        }
+
+        #[test]
+        fn newer_and_malformed_versions_are_rejected() {
+            let load = |version: &str| {
+                let doc = json!({"version": version, "nodes": [], "connections": []});
+                Migrations::builtin().migrate(doc, &registry())
+            };
+            assert!(matches!(load("9.0.0"), Err(MigrationError::UnsupportedVersion { .. })));
+            assert!(matches!(load("one"), Err(MigrationError::InvalidVersion { .. })));
+            assert!(matches!(load("0.5.0"), Err(MigrationError::NoFormatPath { .. })));
+            assert!(matches!(
+                Migrations::builtin().migrate(json!([1, 2]), &registry()),
+                Err(MigrationError::NotAnObject)
+            ));
+        }
+    }
+
+    mod filters {
+        use super::*;
+
+        #[test]
+        fn filter_steps_rewrite_parameters_and_ports() {
+            let (doc, rotate) = legacy_document("180");
+            let migrated = rotate_migration().migrate(doc, &registry()).unwrap();
+
+            let node = migrated.graph.nodes.iter().find(|n| n.id == rotate).unwrap();
+            assert_eq!(node.parameters["angle"], Value::Float(180.0));
+            assert_eq!(node.filter_version.as_deref(), Some("2.0.0"));
+            let outgoing = migrated
+                .graph
+                .connections
+                .iter()
+                .find(|c| c.from_node == rotate)
+                .unwrap();
+            assert_eq!(outgoing.from_port, "rotated");
+
+            assert_eq!(
+                migrated.report.nodes,
+                vec![NodeMigration {
+                    node_id: rotate,
+                    filter_id: "rotate".to_string(),
+                    from_version: "1.0.0".to_string(),
+                    to_version: "2.0.0".to_string(),
+                }]
+            );
+            // Filters the registry does not know are left for loading to report
+            assert!(migrated.report.warnings.is_empty());
+        }
+
+        #[test]
+        fn missing_steps_warn_and_keep_parameters() {
+            let (doc, rotate) = legacy_document("90");
+            let migrated = Migrations::builtin().migrate(doc, &registry()).unwrap();
+
+            let node = migrated.graph.nodes.iter().find(|n| n.id == rotate).unwrap();
+            assert_eq!(node.parameters["angle"], Value::String("90".to_string()));
+            assert_eq!(migrated.report.warnings.len(), 1);
+            assert!(migrated.report.warnings[0].contains("No migration"));
+        }
+
+        #[test]
+        fn failing_steps_name_the_node() {
+            let (doc, rotate) = legacy_document("sideways");
+            match rotate_migration().migrate(doc, &registry()) {
+                Err(MigrationError::FilterStep { node_id, filter_id, .. }) => {
+                    assert_eq!((node_id, filter_id.as_str()), (rotate, "rotate"));
+                }
+                other => panic!("expected a filter step error, got {other:?}"),
+            }
+        }
+
+        #[test]
+        fn load_runs_the_steps_filters_declare() {
+            let (doc, rotate) = legacy_document("270");
+            let registry = FilterRegistry::with_builtins();
+            let migrated = SerializedGraph::load(&doc.to_string(), &registry).unwrap();
+
+            let node = migrated.graph.nodes.iter().find(|n| n.id == rotate).unwrap();
+            assert_eq!(node.parameters["angle"], Value::Float(270.0));
+            assert_eq!(node.filter_version.as_deref(), Some("2.0.0"));
+            assert!(migrated.report.nodes.iter().any(|n| n.node_id == rotate));
+            assert!(!migrated.report.warnings.iter().any(|w| w.contains("(rotate)")));
+        }
+
+        #[test]
+        fn registry_steps_are_replaced_on_reregistration() {
+            let mut registry = FilterRegistry::new();
+            registry.register(|| Box::new(RotateV2));
+            registry.add_migration(
+                "rotate",
+                FilterMigration::new(Version::new(1, 0, 0), Version::new(2, 0, 0), |node| {
+                    node.rename_output("image", "rotated");
+                    Ok(())
+                }),
+            );
+            assert_eq!(registry.migrations().filter_migrations("rotate").len(), 1);
+
+            let (doc, rotate) = legacy_document("90");
+            let migrated = SerializedGraph::load(&doc.to_string(), &registry).unwrap();
+            let outgoing = migrated
+                .graph
+                .connections
+                .iter()
+                .find(|c| c.from_node == rotate)
+                .unwrap();
+            assert_eq!(outgoing.from_port, "rotated");
+
+            registry.register(|| Box::new(RotateV2));
+            assert!(registry.migrations().filter_migrations("rotate").is_empty());
+        }
+
+        #[test]
+        fn renamed_parameters_follow_promotion_and_connections() {
+            let (source, target) = (NodeId::new(), NodeId::new());
+            let mut node = SerializedNode {
+                id: target,
+                filter_id: "rotate".to_string(),
+                position: Default::default(),
+                parameters: HashMap::from([("angle".to_string(), Value::Float(1.0))]),
+                label: None,
+                disabled: false,
+                promoted_parameters: vec!["angle".to_string()],
+                filter_version: Some("1.0.0".to_string()),
+            };
+            let mut connections = vec![SerializedConnection {
+                from_node: source,
+                from_port: "value".to_string(),
+                to_node: target,
+                to_port: "angle".to_string(),
+            }];
+            NodeEdit {
+                node: &mut node,
+                connections: &mut connections,
+            }
+            .rename_parameter("angle", "degrees");
+
+            assert!(node.parameters.contains_key("degrees"));
+            assert_eq!(node.promoted_parameters, vec!["degrees".to_string()]);
+            assert_eq!(connections[0].to_port, "degrees");
+        }
+    }
+}
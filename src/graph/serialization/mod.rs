@@ -0,0 +1,537 @@
+//! Graph serialization for saving and loading.
+//!
+//! [`SerializedGraph`] is the on-disk form of a [`ProcessingGraph`].
+//! [`ProcessingGraph::to_serialized`] and [`ProcessingGraph::from_serialized`]
+//! convert between the two; every front end loads graphs through them.
+//!
+//! [`migration`] upgrades documents written by older versions; load saved
+//! graphs with [`SerializedGraph::load`] rather than
+//! [`SerializedGraph::from_json`] so they are upgraded first.
+//!
+//! [`bundle`] packs a graph and the files it reads into a single `.ambara`
+//! archive.
+//!
+//! [`diff`] compares saved graphs and merges concurrent edits to them.
+//!
+//! [`comfy`] imports ComfyUI workflows into the same representation.
+
+pub mod bundle;
+pub mod comfy;
+pub mod diff;
+pub mod migration;
+
+use crate::core::error::{GraphError, NodeId};
+use crate::core::types::Value;
+use crate::filters::registry::FilterRegistry;
+use crate::graph::connection::Connection;
+use crate::graph::structure::{GraphMetadata, GraphNode, Position, ProcessingGraph};
+use migration::{MigratedGraph, MigrationError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+/// Errors that prevent a [`SerializedGraph`] from being loaded.
+#[derive(Error, Debug)]
+pub enum GraphLoadError {
+    /// Two nodes share an ID.
+    #[error("Duplicate node id: {0}")]
+    DuplicateNode(NodeId),
+
+    /// A node names a filter the registry does not know.
+    #[error("Unknown filter id: {filter_id} (node {node_id})")]
+    UnknownFilter { node_id: NodeId, filter_id: String },
+
+    /// A connection references a node that is not in the graph.
+    #[error("Connection references unknown node {0}")]
+    UnknownNode(NodeId),
+
+    /// A connection references a port the node does not have.
+    #[error("Port '{port}' not found on node {node_id}")]
+    MissingPort { node_id: NodeId, port: String },
+
+    /// A promoted parameter does not exist on the node's filter.
+    #[error("Parameter '{parameter}' not found on node {node_id}")]
+    MissingParameter { node_id: NodeId, parameter: String },
+
+    /// A connection was rejected by [`ProcessingGraph::connect`].
+    #[error("Cannot connect {from_node}.{from_port} to {to_node}.{to_port}: {source}")]
+    Connection {
+        from_node: NodeId,
+        from_port: String,
+        to_node: NodeId,
+        to_port: String,
+        source: Box<GraphError>,
+    },
+}
+
+impl GraphLoadError {
+    /// Get the node this error is about, if any.
+    pub fn node_id(&self) -> Option<NodeId> {
+        match self {
+            GraphLoadError::DuplicateNode(node_id)
+            | GraphLoadError::UnknownNode(node_id)
+            | GraphLoadError::UnknownFilter { node_id, .. }
+            | GraphLoadError::MissingPort { node_id, .. }
+            | GraphLoadError::MissingParameter { node_id, .. } => Some(*node_id),
+            GraphLoadError::Connection { to_node, .. } => Some(*to_node),
+        }
+    }
+}
+
+/// Serializable representation of a graph node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedNode {
+    /// Node ID
+    pub id: NodeId,
+    /// Filter type ID (to look up in registry)
+    pub filter_id: String,
+    /// Position in UI
+    pub position: Position,
+    /// Parameter values (override defaults)
+    pub parameters: HashMap<String, Value>,
+    /// Optional display label
+    pub label: Option<String>,
+    /// Whether the node is disabled
+    pub disabled: bool,
+    /// Parameters exposed as input sockets
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promoted_parameters: Vec<String>,
+    /// Version of the filter the node was saved with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_version: Option<String>,
+}
+
+/// Serializable representation of a connection.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SerializedConnection {
+    /// From node ID
+    pub from_node: NodeId,
+    /// From port name
+    pub from_port: String,
+    /// To node ID
+    pub to_node: NodeId,
+    /// To port name
+    pub to_port: String,
+}
+
+impl From<&Connection> for SerializedConnection {
+    fn from(conn: &Connection) -> Self {
+        Self {
+            from_node: conn.from.node_id,
+            from_port: conn.from.port_name.clone(),
+            to_node: conn.to.node_id,
+            to_port: conn.to.port_name.clone(),
+        }
+    }
+}
+
+/// Serializable representation of a complete graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedGraph {
+    /// Graph format version
+    pub version: String,
+    /// Graph metadata
+    #[serde(default)]
+    pub metadata: GraphMetadata,
+    /// All nodes
+    pub nodes: Vec<SerializedNode>,
+    /// All connections
+    pub connections: Vec<SerializedConnection>,
+}
+
+impl SerializedGraph {
+    /// Current format version.
+    ///
+    /// 1.1.0 added `SerializedNode::filter_version`.
+    pub const VERSION: &'static str = "1.1.0";
+
+    /// Create a new serialized graph.
+    pub fn new() -> Self {
+        Self {
+            version: Self::VERSION.to_string(),
+            metadata: GraphMetadata::default(),
+            nodes: Vec::new(),
+            connections: Vec::new(),
+        }
+    }
+
+    /// Serialize to JSON string.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Deserialize from JSON string.
+    ///
+    /// The document must already be in the current format; use
+    /// [`SerializedGraph::load`] for files that may be older.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Parse a saved graph and upgrade it with the registry's
+    /// [`Migrations`](migration::Migrations): the format steps and the steps
+    /// of its filters.
+    pub fn load(json: &str, registry: &FilterRegistry) -> Result<MigratedGraph, MigrationError> {
+        registry.migrations().migrate_json(json, registry)
+    }
+
+    /// Serialize to compact JSON (no whitespace).
+    pub fn to_json_compact(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+}
+
+impl Default for SerializedGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<&GraphNode> for SerializedNode {
+    fn from(node: &GraphNode) -> Self {
+        Self {
+            id: node.id,
+            filter_id: node.filter.metadata().id,
+            position: node.position,
+            parameters: node.parameters.clone(),
+            label: node.label.clone(),
+            disabled: node.disabled,
+            promoted_parameters: node.promoted_parameters.iter().cloned().collect(),
+            filter_version: Some(node.filter.metadata().version),
+        }
+    }
+}
+
+impl From<&ProcessingGraph> for SerializedGraph {
+    fn from(graph: &ProcessingGraph) -> Self {
+        Self {
+            version: Self::VERSION.to_string(),
+            metadata: graph.metadata.clone(),
+            nodes: graph.nodes().map(SerializedNode::from).collect(),
+            connections: graph
+                .connections()
+                .iter()
+                .map(SerializedConnection::from)
+                .collect(),
+        }
+    }
+}
+
+impl GraphNode {
+    /// Instantiate a node from its serializable form.
+    pub fn from_serialized(
+        node: &SerializedNode,
+        registry: &FilterRegistry,
+    ) -> Result<Self, GraphLoadError> {
+        let filter = registry
+            .create(&node.filter_id)
+            .ok_or_else(|| GraphLoadError::UnknownFilter {
+                node_id: node.id,
+                filter_id: node.filter_id.clone(),
+            })?;
+
+        let mut graph_node = GraphNode::new(filter)
+            .with_id(node.id)
+            .with_position(node.position.x, node.position.y);
+        graph_node.parameters = node.parameters.clone();
+        graph_node.label = node.label.clone();
+        graph_node.disabled = node.disabled;
+        for name in &node.promoted_parameters {
+            graph_node
+                .promote_parameter(name)
+                .map_err(|_| GraphLoadError::MissingParameter {
+                    node_id: node.id,
+                    parameter: name.clone(),
+                })?;
+        }
+        Ok(graph_node)
+    }
+}
+
+impl ProcessingGraph {
+    /// Convert to the serializable form.
+    ///
+    /// Node IDs, positions, labels, disabled flags, parameter values and
+    /// graph metadata are kept. Connection IDs are not: they are
+    /// regenerated on load.
+    pub fn to_serialized(&self) -> SerializedGraph {
+        SerializedGraph::from(self)
+    }
+
+    /// Build a graph from its serializable form.
+    ///
+    /// Filters are instantiated from `registry`. Connections go through
+    /// [`ProcessingGraph::connect`], so ports and types are checked.
+    pub fn from_serialized(
+        graph: &SerializedGraph,
+        registry: &FilterRegistry,
+    ) -> Result<Self, GraphLoadError> {
+        let mut result = ProcessingGraph::new().with_conversions(registry.conversions());
+        result.metadata = graph.metadata.clone();
+
+        let mut seen = HashSet::new();
+        for node in &graph.nodes {
+            if !seen.insert(node.id) {
+                return Err(GraphLoadError::DuplicateNode(node.id));
+            }
+            result.add_node(GraphNode::from_serialized(node, registry)?);
+        }
+
+        for conn in &graph.connections {
+            for id in [conn.from_node, conn.to_node] {
+                if !result.has_node(id) {
+                    return Err(GraphLoadError::UnknownNode(id));
+                }
+            }
+            result
+                .connect(conn.from_node, &conn.from_port, conn.to_node, &conn.to_port)
+                .map_err(|source| match source {
+                    GraphError::PortNotFound { node_id, port } => {
+                        GraphLoadError::MissingPort { node_id, port }
+                    }
+                    GraphError::ParameterNotPromoted { node_id, parameter } => {
+                        GraphLoadError::MissingPort {
+                            node_id,
+                            port: parameter,
+                        }
+                    }
+                    source => GraphLoadError::Connection {
+                        from_node: conn.from_node,
+                        from_port: conn.from_port.clone(),
+                        to_node: conn.to_node,
+                        to_port: conn.to_port.clone(),
+                        source: Box::new(source),
+                    },
+                })?;
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_graph() {
+        let mut graph = SerializedGraph::new();
+        graph.metadata.name = Some("Test Graph".to_string());
+        graph.nodes.push(SerializedNode {
+            id: NodeId::new(),
+            filter_id: "load_image".to_string(),
+            position: Position::new(100.0, 100.0),
+            parameters: HashMap::new(),
+            label: None,
+            disabled: false,
+            promoted_parameters: Vec::new(),
+            filter_version: None,
+        });
+
+        let json = graph.to_json().unwrap();
+        assert!(json.contains("Test Graph"));
+        assert!(json.contains("load_image"));
+
+        let deserialized = SerializedGraph::from_json(&json).unwrap();
+        assert_eq!(deserialized.metadata.name, Some("Test Graph".to_string()));
+        assert_eq!(deserialized.nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_promoted_parameters_are_optional_in_json() {
+        let mut node = SerializedNode {
+            id: NodeId::new(),
+            filter_id: "gaussian_blur".to_string(),
+            position: Position::default(),
+            parameters: HashMap::new(),
+            label: None,
+            disabled: false,
+            promoted_parameters: Vec::new(),
+            filter_version: None,
+        };
+        let json = serde_json::to_value(&node).unwrap();
+        assert!(json.get("promoted_parameters").is_none());
+
+        node.promoted_parameters.push("sigma".to_string());
+        let json = serde_json::to_string(&node).unwrap();
+        let back: SerializedNode = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.promoted_parameters, vec!["sigma".to_string()]);
+    }
+
+    mod conversion {
+        use super::*;
+
+        fn node(filter_id: &str) -> SerializedNode {
+            SerializedNode {
+                id: NodeId::new(),
+                filter_id: filter_id.to_string(),
+                position: Position::default(),
+                parameters: HashMap::new(),
+                label: None,
+                disabled: false,
+                promoted_parameters: Vec::new(),
+                filter_version: Some("1.0.0".to_string()),
+            }
+        }
+
+        fn connection(from: &SerializedNode, from_port: &str, to: &SerializedNode, to_port: &str) -> SerializedConnection {
+            SerializedConnection {
+                from_node: from.id,
+                from_port: from_port.to_string(),
+                to_node: to.id,
+                to_port: to_port.to_string(),
+            }
+        }
+
+        #[test]
+        fn preserves_ids_and_node_state() {
+            let registry = FilterRegistry::with_builtins();
+            let mut source = node("float_constant");
+            source.label = Some("Radius".to_string());
+            source.position = Position::new(10.0, -4.5);
+            let mut blur = node("gaussian_blur");
+            blur.disabled = true;
+            blur.parameters.insert("sigma".to_string(), Value::Float(2.5));
+            blur.promoted_parameters.push("sigma".to_string());
+
+            let mut graph = SerializedGraph::new();
+            graph.metadata.name = Some("Blur".to_string());
+            graph.connections.push(connection(&source, "value", &blur, "sigma"));
+            graph.nodes = vec![source, blur];
+
+            let loaded = ProcessingGraph::from_serialized(&graph, &registry).unwrap();
+            assert_eq!(loaded.to_serialized(), graph);
+        }
+
+        #[test]
+        fn reports_unknown_filters_nodes_and_ports() {
+            let registry = FilterRegistry::with_builtins();
+            let load = |graph: &SerializedGraph| ProcessingGraph::from_serialized(graph, &registry);
+
+            let mut graph = SerializedGraph::new();
+            graph.nodes.push(node("no_such_filter"));
+            assert!(matches!(load(&graph), Err(GraphLoadError::UnknownFilter { .. })));
+
+            let a = node("float_constant");
+            let b = node("gaussian_blur");
+            let mut graph = SerializedGraph::new();
+            graph.nodes = vec![a.clone(), a.clone()];
+            assert!(matches!(load(&graph), Err(GraphLoadError::DuplicateNode(id)) if id == a.id));
+
+            let ghost = node("invert");
+            let mut graph = SerializedGraph::new();
+            graph.nodes = vec![a.clone(), b.clone()];
+            graph.connections.push(connection(&ghost, "image", &b, "image"));
+            assert!(matches!(load(&graph), Err(GraphLoadError::UnknownNode(id)) if id == ghost.id));
+
+            graph.connections = vec![connection(&a, "value", &b, "radius")];
+            assert!(matches!(
+                load(&graph),
+                Err(GraphLoadError::MissingPort { node_id, port }) if node_id == b.id && port == "radius"
+            ));
+
+            graph.connections = vec![connection(&a, "value", &b, "image")];
+            assert!(matches!(
+                load(&graph),
+                Err(GraphLoadError::Connection { source, .. }) if matches!(*source, GraphError::TypeMismatch { .. })
+            ));
+        }
+    }
+
+    mod round_trip {
+        use super::*;
+        use crate::core::types::PortType;
+        use proptest::prelude::*;
+
+        const FILTERS: &[&str] = &[
+            "integer_constant",
+            "float_constant",
+            "string_constant",
+            "gaussian_blur",
+            "brightness",
+            "invert",
+            "add",
+            "load_image",
+        ];
+
+        /// filter index, position, label, disabled, promote first parameter, parameter seed
+        type NodeSpec = (usize, (f64, f64), Option<String>, bool, bool, i64);
+        /// source node, target node, output index, input index
+        type EdgeSpec = (usize, usize, usize, usize);
+
+        fn node_spec() -> impl Strategy<Value = NodeSpec> {
+            (
+                0..FILTERS.len(),
+                (-1e6..1e6f64, -1e6..1e6f64),
+                proptest::option::of("[a-zA-Z ]{1,12}"),
+                any::<bool>(),
+                any::<bool>(),
+                -1000..1000i64,
+            )
+        }
+
+        fn build(nodes: &[NodeSpec], edges: &[EdgeSpec]) -> ProcessingGraph {
+            let registry = FilterRegistry::with_builtins();
+            let mut graph = ProcessingGraph::new().with_name("generated");
+            let mut ids = Vec::new();
+            for (filter, (x, y), label, disabled, promote, seed) in nodes {
+                let mut node = GraphNode::new(registry.create(FILTERS[*filter]).unwrap())
+                    .with_position(*x, *y);
+                node.label = label.clone();
+                node.disabled = *disabled;
+                if let Some(param) = node.filter.metadata().parameters.first().cloned() {
+                    let value = match param.param_type {
+                        PortType::Integer => Some(Value::Integer(*seed)),
+                        PortType::Float => Some(Value::Float(*seed as f64 / 8.0)),
+                        PortType::String => Some(Value::String(seed.to_string())),
+                        PortType::Boolean => Some(Value::Boolean(seed % 2 == 0)),
+                        _ => None,
+                    };
+                    if let Some(value) = value {
+                        node.set_parameter(param.name.clone(), value);
+                    }
+                    if *promote {
+                        node.promote_parameter(param.name).unwrap();
+                    }
+                }
+                ids.push(graph.add_node(node));
+            }
+            for &(from, to, output, input) in edges {
+                let (from, to) = (ids[from % ids.len()], ids[to % ids.len()]);
+                let outputs = graph.get_node(from).unwrap().filter.metadata().outputs;
+                let inputs = graph.get_node(to).unwrap().input_ports();
+                if outputs.is_empty() || inputs.is_empty() {
+                    continue;
+                }
+                // Rejected edges (cycles, type mismatches, taken inputs) are fine
+                let _ = graph.connect(
+                    from,
+                    outputs[output % outputs.len()].name.clone(),
+                    to,
+                    inputs[input % inputs.len()].name.clone(),
+                );
+            }
+            graph
+        }
+
+        proptest! {
+            #[test]
+            fn serialized_form_is_stable(
+                nodes in prop::collection::vec(node_spec(), 1..8),
+                edges in prop::collection::vec((0..8usize, 0..8usize, 0..4usize, 0..4usize), 0..16),
+            ) {
+                let registry = FilterRegistry::with_builtins();
+                let graph = build(&nodes, &edges);
+                let serialized = graph.to_serialized();
+
+                let loaded = ProcessingGraph::from_serialized(&serialized, &registry).unwrap();
+                prop_assert_eq!(loaded.node_count(), graph.node_count());
+                prop_assert_eq!(loaded.connection_count(), graph.connection_count());
+                prop_assert_eq!(&loaded.to_serialized(), &serialized);
+
+                let json = serialized.to_json().unwrap();
+                prop_assert_eq!(&SerializedGraph::from_json(&json).unwrap(), &serialized);
+            }
+        }
+    }
+}
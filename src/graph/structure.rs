@@ -6,13 +6,17 @@
 //! - Graph-wide validation
 //! - Execution planning
 
+use crate::core::conversion::ConversionRegistry;
 use crate::core::error::{ConnectionId, GraphError, GraphResult, NodeId};
 use crate::core::node::FilterNode;
-use crate::core::types::Value;
+use crate::core::port::{ParameterDefinition, PortDefinition};
+use crate::core::types::{PortType, Value};
 use crate::graph::connection::{Connection, Endpoint};
+use crate::graph::typing::{declares_type_variables, TypeResolver};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
 
 /// Position of a node in the UI (for serialization).
 #[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
@@ -44,6 +48,8 @@ pub struct GraphNode {
     pub label: Option<String>,
     /// Whether this node is disabled
     pub disabled: bool,
+    /// Parameters exposed as input sockets that upstream nodes can drive
+    pub promoted_parameters: BTreeSet<String>,
 }
 
 impl std::fmt::Debug for GraphNode {
@@ -55,6 +61,7 @@ impl std::fmt::Debug for GraphNode {
             .field("parameters", &self.parameters)
             .field("label", &self.label)
             .field("disabled", &self.disabled)
+            .field("promoted_parameters", &self.promoted_parameters)
             .finish()
     }
 }
@@ -69,6 +76,7 @@ impl GraphNode {
             parameters: HashMap::new(),
             label: None,
             disabled: false,
+            promoted_parameters: BTreeSet::new(),
         }
     }
 
@@ -120,10 +128,97 @@ impl GraphNode {
     pub fn set_parameter(&mut self, name: impl Into<String>, value: Value) {
         self.parameters.insert(name.into(), value);
     }
+
+    /// Expose a parameter as an input socket (builder form).
+    ///
+    /// Unknown names are ignored; use [`GraphNode::promote_parameter`] to
+    /// get an error instead.
+    pub fn with_promoted_parameter(mut self, name: impl Into<String>) -> Self {
+        let _ = self.promote_parameter(name);
+        self
+    }
+
+    /// Expose a parameter as an input socket.
+    ///
+    /// Once promoted, the parameter can be the target of a connection. A
+    /// connected value replaces the stored one at execution time; when the
+    /// socket is left unconnected the stored value (or default) is used.
+    pub fn promote_parameter(&mut self, name: impl Into<String>) -> GraphResult<()> {
+        let name = name.into();
+        let metadata = self.filter.metadata();
+        if metadata.get_parameter(&name).is_none() {
+            return Err(GraphError::ParameterNotFound {
+                node_id: self.id,
+                parameter: name,
+            });
+        }
+        if metadata.get_input(&name).is_some() {
+            return Err(GraphError::InvalidConnection {
+                reason: format!(
+                    "parameter '{}' shares its name with an input port and cannot be promoted",
+                    name
+                ),
+            });
+        }
+        self.promoted_parameters.insert(name);
+        Ok(())
+    }
+
+    /// Turn a promoted parameter back into a plain parameter.
+    ///
+    /// Returns whether the parameter was promoted. Callers holding the node
+    /// inside a graph should use [`ProcessingGraph::demote_parameter`] so the
+    /// connection feeding it is removed as well.
+    pub fn demote_parameter(&mut self, name: &str) -> bool {
+        self.promoted_parameters.remove(name)
+    }
+
+    /// Check whether a parameter is exposed as an input socket.
+    pub fn is_promoted(&self, name: &str) -> bool {
+        self.promoted_parameters.contains(name)
+    }
+
+    /// Look up an input socket by name.
+    ///
+    /// Covers both the filter's declared inputs and promoted parameters.
+    /// Promoted parameters appear as optional inputs of the parameter's type.
+    pub fn input_port(&self, name: &str) -> Option<PortDefinition> {
+        let metadata = self.filter.metadata();
+        if let Some(port) = metadata.get_input(name) {
+            return Some(port.clone());
+        }
+        if !self.is_promoted(name) {
+            return None;
+        }
+        metadata
+            .get_parameter(name)
+            .map(Self::promoted_port)
+    }
+
+    /// All input sockets: declared inputs followed by promoted parameters.
+    pub fn input_ports(&self) -> Vec<PortDefinition> {
+        let metadata = self.filter.metadata();
+        let mut ports = metadata.inputs.clone();
+        ports.extend(
+            metadata
+                .parameters
+                .iter()
+                .filter(|param| self.is_promoted(&param.name))
+                .map(Self::promoted_port),
+        );
+        ports
+    }
+
+    fn promoted_port(param: &ParameterDefinition) -> PortDefinition {
+        PortDefinition::input(param.name.clone(), param.param_type.clone())
+            .with_display_name(param.display_name.clone())
+            .with_description(param.description.clone())
+            .optional()
+    }
 }
 
 /// Metadata about the graph itself.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct GraphMetadata {
     /// Optional name for this graph.
     pub name: Option<String>,
@@ -134,6 +229,7 @@ pub struct GraphMetadata {
     /// Version string.
     pub version: Option<String>,
     /// Additional tags.
+    #[serde(default)]
     pub tags: Vec<String>,
     /// Creation timestamp.
     pub created_at: Option<String>,
@@ -152,15 +248,18 @@ pub struct ProcessingGraph {
     connections: Vec<Connection>,
     /// Graph metadata.
     pub metadata: GraphMetadata,
+    /// Implicit conversions allowed between connected ports.
+    conversions: Arc<ConversionRegistry>,
 }
 
 impl ProcessingGraph {
-    /// Create a new empty graph.
+    /// Create a new empty graph using the built-in conversions.
     pub fn new() -> Self {
         Self {
             nodes: IndexMap::new(),
             connections: Vec::new(),
             metadata: GraphMetadata::default(),
+            conversions: ConversionRegistry::builtin(),
         }
     }
 
@@ -170,6 +269,26 @@ impl ProcessingGraph {
         self
     }
 
+    /// Create with the conversions of a filter registry.
+    pub fn with_conversions(mut self, conversions: Arc<ConversionRegistry>) -> Self {
+        self.set_conversions(conversions);
+        self
+    }
+
+    /// Implicit conversions used to connect, validate and execute this graph.
+    pub fn conversions(&self) -> &ConversionRegistry {
+        &self.conversions
+    }
+
+    /// Replace the implicit conversions and re-resolve connection types.
+    ///
+    /// Existing connections are kept; type validation reports any that the
+    /// new conversions no longer allow.
+    pub fn set_conversions(&mut self, conversions: Arc<ConversionRegistry>) {
+        self.conversions = conversions;
+        self.refresh_connection_types();
+    }
+
     // ========================================================================
     // Node Management
     // ========================================================================
@@ -191,13 +310,21 @@ impl ProcessingGraph {
     /// Also removes all connections involving this node.
     pub fn remove_node(&mut self, id: NodeId) -> GraphResult<GraphNode> {
         // Remove all connections involving this node
+        let mut neighbours = Vec::new();
         self.connections.retain(|conn| {
-            conn.from.node_id != id && conn.to.node_id != id
+            let involved = conn.from.node_id == id || conn.to.node_id == id;
+            if involved {
+                neighbours.extend([conn.from.node_id, conn.to.node_id]);
+            }
+            !involved
         });
 
-        self.nodes
+        let node = self
+            .nodes
             .shift_remove(&id)
-            .ok_or(GraphError::NodeNotFound(id))
+            .ok_or(GraphError::NodeNotFound(id))?;
+        self.refresh_types_downstream(neighbours);
+        Ok(node)
     }
 
     /// Get a reference to a node.
@@ -265,18 +392,33 @@ impl ProcessingGraph {
                 port: from_port.clone(),
             })?;
 
-        let to_port_def = to_metadata
-            .get_input(&to_port)
-            .ok_or_else(|| GraphError::PortNotFound {
-                node_id: to_node,
-                port: to_port.clone(),
-            })?;
+        let to_port_def = match to_node_data.input_port(&to_port) {
+            Some(port) => port,
+            None if to_metadata.get_parameter(&to_port).is_some() => {
+                return Err(GraphError::ParameterNotPromoted {
+                    node_id: to_node,
+                    parameter: to_port,
+                });
+            }
+            None => {
+                return Err(GraphError::PortNotFound {
+                    node_id: to_node,
+                    port: to_port,
+                });
+            }
+        };
 
-        // Check type compatibility
-        if !from_port_def.port_type.compatible_with(&to_port_def.port_type) {
+        // Check type compatibility, resolving type variables of both nodes
+        // from their existing connections
+        let mut resolver = TypeResolver::new(self);
+        let from_type = resolver
+            .output_type(from_node, &from_port)
+            .unwrap_or_else(|| from_port_def.port_type.clone());
+        let mut to_bindings = resolver.bindings(to_node);
+        if !to_bindings.bind_input(&to_port_def.port_type, &from_type, &self.conversions) {
             return Err(GraphError::TypeMismatch {
-                from_type: from_port_def.port_type.clone(),
-                to_type: to_port_def.port_type.clone(),
+                from_type,
+                to_type: to_bindings.resolve(&to_port_def.port_type),
             });
         }
 
@@ -306,10 +448,12 @@ impl ProcessingGraph {
                 node_id: to_node,
                 port_name: to_port,
             },
+            port_type: from_type,
         };
 
         let id = connection.id;
         self.connections.push(connection);
+        self.refresh_types_downstream([from_node, to_node]);
         Ok(id)
     }
 
@@ -337,6 +481,7 @@ impl ProcessingGraph {
                 node_id: to_node,
                 port_name: to_port.into(),
             },
+            port_type: PortType::Any,
         };
         let id = connection.id;
         self.connections.push(connection);
@@ -351,14 +496,98 @@ impl ProcessingGraph {
             .position(|c| c.id == id)
             .ok_or(GraphError::ConnectionNotFound(id))?;
 
-        Ok(self.connections.remove(pos))
+        let connection = self.connections.remove(pos);
+        self.refresh_types_downstream([connection.from.node_id, connection.to.node_id]);
+        Ok(connection)
     }
 
     /// Remove all connections to a specific input port.
     pub fn disconnect_input(&mut self, node_id: NodeId, port: &str) {
+        let mut touched = vec![node_id];
         self.connections.retain(|conn| {
-            !(conn.to.node_id == node_id && conn.to.port_name == port)
+            let removed = conn.to.node_id == node_id && conn.to.port_name == port;
+            if removed {
+                touched.push(conn.from.node_id);
+            }
+            !removed
         });
+        self.refresh_types_downstream(touched);
+    }
+
+    /// Recompute each connection's [`Connection::port_type`].
+    ///
+    /// Called whenever connections change. Call it after replacing a node's
+    /// filter with one whose ports differ.
+    pub fn refresh_connection_types(&mut self) {
+        let types: Vec<PortType> = {
+            let mut resolver = TypeResolver::new(self);
+            self.connections
+                .iter()
+                .map(|conn| {
+                    resolver
+                        .output_type(conn.from.node_id, &conn.from.port_name)
+                        .unwrap_or(PortType::Any)
+                })
+                .collect()
+        };
+        for (conn, port_type) in self.connections.iter_mut().zip(types) {
+            conn.port_type = port_type;
+        }
+    }
+
+    /// Recompute [`Connection::port_type`] after connections of `touched`
+    /// changed.
+    ///
+    /// Only generic nodes resolve differently when their connections
+    /// change, and the change reaches the generic nodes downstream of them,
+    /// so only connections leaving those nodes are re-resolved.
+    fn refresh_types_downstream(&mut self, touched: impl IntoIterator<Item = NodeId>) {
+        let mut queue: std::collections::VecDeque<NodeId> = touched.into_iter().collect();
+        let mut seen = std::collections::HashSet::new();
+        let mut updates = Vec::new();
+        {
+            let mut resolver = TypeResolver::new(self);
+            while let Some(node_id) = queue.pop_front() {
+                if !seen.insert(node_id) {
+                    continue;
+                }
+                let generic = self
+                    .get_node(node_id)
+                    .is_ok_and(|node| declares_type_variables(&node.filter.metadata()));
+                if !generic {
+                    continue;
+                }
+                for (index, conn) in self.connections.iter().enumerate() {
+                    if conn.from.node_id != node_id {
+                        continue;
+                    }
+                    let port_type = resolver
+                        .output_type(node_id, &conn.from.port_name)
+                        .unwrap_or(PortType::Any);
+                    updates.push((index, port_type));
+                    queue.push_back(conn.to.node_id);
+                }
+            }
+        }
+        for (index, port_type) in updates {
+            self.connections[index].port_type = port_type;
+        }
+    }
+
+    /// Promote a node parameter to an input socket.
+    pub fn promote_parameter(&mut self, node_id: NodeId, name: impl Into<String>) -> GraphResult<()> {
+        self.get_node_mut(node_id)?.promote_parameter(name)
+    }
+
+    /// Demote a promoted parameter, removing any connection feeding it.
+    ///
+    /// Returns whether the parameter was promoted.
+    pub fn demote_parameter(&mut self, node_id: NodeId, name: &str) -> GraphResult<bool> {
+        let demoted = self.get_node_mut(node_id)?.demote_parameter(name);
+        if demoted {
+            self.disconnect_input(node_id, name);
+        }
+        Ok(demoted)
     }
 
     /// Get a connection by ID.
@@ -642,4 +871,63 @@ mod tests {
         assert!(upstream.contains(&node1));
         assert!(upstream.contains(&node2));
     }
+
+    mod promoted_parameters {
+        use super::*;
+        use crate::core::types::PortType;
+        use crate::filters::builtin::{GaussianBlur, IntegerConstant, StringConstant};
+
+        #[test]
+        fn promote_rejects_unknown_parameter() {
+            let mut node = GraphNode::new(Box::new(GaussianBlur));
+            assert!(matches!(
+                node.promote_parameter("nope"),
+                Err(GraphError::ParameterNotFound { .. })
+            ));
+            assert!(node.promoted_parameters.is_empty());
+        }
+
+        #[test]
+        fn promoted_parameter_becomes_optional_input() {
+            let node = GraphNode::new(Box::new(GaussianBlur)).with_promoted_parameter("sigma");
+            let port = node.input_port("sigma").unwrap();
+            assert_eq!(port.port_type, PortType::Float);
+            assert!(port.optional);
+            assert_eq!(node.input_ports().len(), node.filter.metadata().inputs.len() + 1);
+        }
+
+        #[test]
+        fn connect_requires_promotion_and_compatible_type() {
+            let mut graph = ProcessingGraph::new();
+            let int = graph.add_node(GraphNode::new(Box::new(IntegerConstant)));
+            let text = graph.add_node(GraphNode::new(Box::new(StringConstant)));
+            let blur = graph.add_node(GraphNode::new(Box::new(GaussianBlur)));
+
+            assert!(matches!(
+                graph.connect(int, "value", blur, "sigma"),
+                Err(GraphError::ParameterNotPromoted { .. })
+            ));
+
+            graph.promote_parameter(blur, "sigma").unwrap();
+            assert!(matches!(
+                graph.connect(text, "value", blur, "sigma"),
+                Err(GraphError::TypeMismatch { .. })
+            ));
+            // Integer widens to Float
+            graph.connect(int, "value", blur, "sigma").unwrap();
+        }
+
+        #[test]
+        fn demote_removes_feeding_connection() {
+            let mut graph = ProcessingGraph::new();
+            let int = graph.add_node(GraphNode::new(Box::new(IntegerConstant)));
+            let blur = graph.add_node(GraphNode::new(Box::new(GaussianBlur)));
+            graph.promote_parameter(blur, "sigma").unwrap();
+            graph.connect(int, "value", blur, "sigma").unwrap();
+
+            assert!(graph.demote_parameter(blur, "sigma").unwrap());
+            assert!(!graph.is_input_connected(blur, "sigma"));
+            assert!(!graph.demote_parameter(blur, "sigma").unwrap());
+        }
+    }
 }
@@ -0,0 +1,213 @@
+//! Resolution of generic port types.
+//!
+//! Ports may declare type variables ([`PortType::Var`]). A node's variables
+//! are bound from its connections: each input binds them from the resolved
+//! type of the output feeding it, and each output from the declared type of
+//! the input it feeds. Resolution walks upstream, so a chain of generic
+//! nodes carries the concrete type from its source.
+
+use crate::core::error::NodeId;
+use crate::core::node::NodeMetadata;
+use crate::core::types::{PortType, TypeBindings};
+use crate::graph::structure::ProcessingGraph;
+use std::collections::{HashMap, HashSet};
+
+/// Resolves type variables for one state of a graph, memoizing per node.
+pub(crate) struct TypeResolver<'a> {
+    graph: &'a ProcessingGraph,
+    bindings: HashMap<NodeId, TypeBindings>,
+    visiting: HashSet<NodeId>,
+}
+
+impl<'a> TypeResolver<'a> {
+    pub(crate) fn new(graph: &'a ProcessingGraph) -> Self {
+        Self {
+            graph,
+            bindings: HashMap::new(),
+            visiting: HashSet::new(),
+        }
+    }
+
+    /// Bindings of a node's type variables. Connections that do not unify
+    /// are skipped here; type validation reports them.
+    pub(crate) fn bindings(&mut self, node_id: NodeId) -> TypeBindings {
+        if let Some(bindings) = self.bindings.get(&node_id) {
+            return bindings.clone();
+        }
+        let Ok(node) = self.graph.get_node(node_id) else {
+            return TypeBindings::new();
+        };
+        let metadata = node.filter.metadata();
+        // A cycle (only possible through unchecked test helpers) stops here
+        if !declares_type_variables(&metadata) || !self.visiting.insert(node_id) {
+            return TypeBindings::new();
+        }
+
+        let mut bindings = TypeBindings::new();
+        let graph = self.graph;
+        for conn in graph.connections_to(node_id) {
+            let Some(declared) = node.input_port(&conn.to.port_name) else {
+                continue;
+            };
+            if let Some(incoming) = self.output_type(conn.from.node_id, &conn.from.port_name) {
+                bindings.bind_input(&declared.port_type, &incoming, graph.conversions());
+            }
+        }
+        for conn in graph.connections_from(node_id) {
+            let Some(declared) = metadata.get_output(&conn.from.port_name) else {
+                continue;
+            };
+            let target = graph
+                .get_node(conn.to.node_id)
+                .ok()
+                .and_then(|to| to.input_port(&conn.to.port_name));
+            if let Some(target) = target {
+                bindings.bind_output(&declared.port_type, &target.port_type, graph.conversions());
+            }
+        }
+
+        self.visiting.remove(&node_id);
+        self.bindings.insert(node_id, bindings.clone());
+        bindings
+    }
+
+    /// Resolved type of an output port.
+    pub(crate) fn output_type(&mut self, node_id: NodeId, port: &str) -> Option<PortType> {
+        let declared = self
+            .graph
+            .get_node(node_id)
+            .ok()?
+            .filter
+            .metadata()
+            .get_output(port)?
+            .port_type
+            .clone();
+        if !declared.is_generic() {
+            return Some(declared);
+        }
+        Some(self.bindings(node_id).resolve(&declared))
+    }
+
+    /// Resolved type of an input port (including promoted parameters).
+    pub(crate) fn input_type(&mut self, node_id: NodeId, port: &str) -> Option<PortType> {
+        let declared = self.graph.get_node(node_id).ok()?.input_port(port)?.port_type;
+        if !declared.is_generic() {
+            return Some(declared);
+        }
+        Some(self.bindings(node_id).resolve(&declared))
+    }
+}
+
+/// Whether any port of a filter declares a type variable.
+pub(crate) fn declares_type_variables(metadata: &NodeMetadata) -> bool {
+    metadata.inputs.iter().any(|p| p.port_type.is_generic())
+        || metadata.outputs.iter().any(|p| p.port_type.is_generic())
+}
+
+impl ProcessingGraph {
+    /// Bindings of a node's type variables implied by its connections.
+    pub fn type_bindings(&self, node_id: NodeId) -> TypeBindings {
+        TypeResolver::new(self).bindings(node_id)
+    }
+
+    /// Type of an output port with the node's type variables resolved.
+    pub fn resolved_output_type(&self, node_id: NodeId, port: &str) -> Option<PortType> {
+        TypeResolver::new(self).output_type(node_id, port)
+    }
+
+    /// Type of an input port with the node's type variables resolved.
+    pub fn resolved_input_type(&self, node_id: NodeId, port: &str) -> Option<PortType> {
+        TypeResolver::new(self).input_type(node_id, port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::error::GraphError;
+    use crate::core::node::PassthroughNode;
+    use crate::core::types::PortType;
+    use crate::filters::registry::FilterRegistry;
+    use crate::graph::structure::{GraphNode, ProcessingGraph};
+
+    fn add(graph: &mut ProcessingGraph, registry: &FilterRegistry, filter: &str) -> crate::core::error::NodeId {
+        graph.add_node(GraphNode::new(registry.create(filter).unwrap()))
+    }
+
+    #[test]
+    fn type_variables_follow_the_source() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let number = add(&mut graph, &registry, "integer_constant");
+        let first = graph.add_node(GraphNode::new(Box::new(PassthroughNode)));
+        let second = graph.add_node(GraphNode::new(Box::new(PassthroughNode)));
+        let a = graph.connect(first, "output", second, "input").unwrap();
+        assert_eq!(graph.get_connection(a).unwrap().port_type, PortType::Var("T".into()));
+
+        let b = graph.connect(number, "value", first, "input").unwrap();
+        assert_eq!(graph.get_connection(b).unwrap().port_type, PortType::Integer);
+        assert_eq!(graph.get_connection(a).unwrap().port_type, PortType::Integer);
+        assert_eq!(
+            graph.resolved_output_type(second, "output"),
+            Some(PortType::Integer)
+        );
+
+        graph.disconnect(b).unwrap();
+        assert_eq!(graph.get_connection(a).unwrap().port_type, PortType::Var("T".into()));
+    }
+
+    #[test]
+    fn edits_reach_the_whole_generic_chain() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let number = add(&mut graph, &registry, "float_constant");
+        let chain: Vec<_> = (0..3)
+            .map(|_| graph.add_node(GraphNode::new(Box::new(PassthroughNode))))
+            .collect();
+        let links: Vec<_> = chain
+            .windows(2)
+            .map(|pair| graph.connect(pair[0], "output", pair[1], "input").unwrap())
+            .collect();
+        graph.connect(number, "value", chain[0], "input").unwrap();
+        for link in &links {
+            assert_eq!(graph.get_connection(*link).unwrap().port_type, PortType::Float);
+        }
+
+        graph.remove_node(number).unwrap();
+        for link in &links {
+            assert_eq!(graph.get_connection(*link).unwrap().port_type, PortType::Var("T".into()));
+        }
+    }
+
+    #[test]
+    fn downstream_inputs_constrain_outputs() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let text = add(&mut graph, &registry, "string_constant");
+        let pass = graph.add_node(GraphNode::new(Box::new(PassthroughNode)));
+        let blur = add(&mut graph, &registry, "gaussian_blur");
+        graph.connect(pass, "output", blur, "image").unwrap();
+        assert_eq!(graph.resolved_input_type(pass, "input"), Some(PortType::Image));
+
+        let result = graph.connect(text, "value", pass, "input");
+        assert!(matches!(
+            result,
+            Err(GraphError::TypeMismatch { from_type: PortType::String, to_type: PortType::Image })
+        ));
+    }
+
+    #[test]
+    fn array_element_types_resolve() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let collect = add(&mut graph, &registry, "collect_images");
+        let length = add(&mut graph, &registry, "array_length");
+        let number = add(&mut graph, &registry, "integer_constant");
+        graph.connect(collect, "images", length, "array").unwrap();
+        assert_eq!(
+            graph.resolved_input_type(length, "array"),
+            Some(PortType::Array(Box::new(PortType::Image)))
+        );
+        let other = add(&mut graph, &registry, "array_length");
+        assert!(graph.connect(number, "value", other, "array").is_err());
+    }
+}
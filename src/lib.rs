@@ -130,8 +130,11 @@ pub mod prelude {
     // Port definitions
     pub use crate::core::port::{Constraint, ParameterDefinition, PortDefinition, UiHint};
 
+    // Implicit conversions
+    pub use crate::core::conversion::{ConversionRegistry, Fidelity, ImageLayout};
+
     // Contexts
-    pub use crate::core::context::{ExecutionContext, ValidationContext};
+    pub use crate::core::context::{ExecutionContext, ExecutionHooks, ValidationContext};
 
     // Errors
     pub use crate::core::error::{
@@ -143,9 +146,13 @@ pub mod prelude {
     pub use crate::graph::structure::{GraphNode, ProcessingGraph};
     pub use crate::graph::connection::{Connection, Endpoint};
     pub use crate::graph::topology::TopologyAnalyzer;
-    pub use crate::graph::serialization::{SerializedConnection, SerializedGraph, SerializedNode};
+    pub use crate::graph::edit::{EditError, GraphEdit, GraphEditor};
+    pub use crate::graph::serialization::{
+        GraphLoadError, SerializedConnection, SerializedGraph, SerializedNode,
+    };
 
     // Validation
+    pub use crate::validation::lint::{LintFinding, LintLevel, Linter};
     pub use crate::validation::pipeline::ValidationPipeline;
     pub use crate::validation::stages::{
         ConstraintValidation, CustomValidation, ResourceValidation, StructuralValidation,
@@ -155,7 +162,9 @@ pub mod prelude {
     // Execution
     pub use crate::execution::engine::{ExecutionEngine, ExecutionOptions, ExecutionResult, ExecutionStats};
     pub use crate::execution::cache::{CacheKey, CacheStats, ResultCache, SharedCache};
-    pub use crate::execution::progress::{ProgressCallback, ProgressTracker, ProgressUpdate, SkipReason};
+    pub use crate::execution::progress::{
+        CancellationToken, ProgressCallback, ProgressTracker, ProgressUpdate, SkipReason,
+    };
 
     // Chunked Processing
     pub use crate::core::chunked::{
@@ -3,7 +3,13 @@
 use std::collections::HashMap;
 use std::path::Path;
 
+use ambara::graph::serialization::bundle::{self, PackOptions};
+use ambara::graph::serialization::comfy::import_comfy_workflow;
+use ambara::graph::serialization::diff::{diff_graphs, merge_graphs};
+use ambara::graph::serialization::migration::{MigratedGraph, MigrationError};
 use ambara::graph::serialization::SerializedGraph;
+use ambara::plugins::resolver::{FilterConflictPolicy, Resolver, BUILTIN_OWNER};
+use ambara::plugins::signing::{self, PluginSignature, TrustStore};
 use ambara::prelude::*;
 use serde::Serialize;
 
@@ -93,6 +99,144 @@ fn main() {
                 std::process::exit(code);
             }
         }
+        "import-comfy" => {
+            if args.len() < 3 {
+                eprintln!("Usage: {} import-comfy <workflow.json> [--output <graph.json>]", args[0]);
+                std::process::exit(1);
+            }
+            let output = args
+                .iter()
+                .position(|a| a == "--output" || a == "-o")
+                .and_then(|i| args.get(i + 1));
+            std::process::exit(import_comfy_command(
+                Path::new(&args[2]),
+                output.map(Path::new),
+            ));
+        }
+        "bundle-pack" => {
+            if args.len() < 4 {
+                eprintln!(
+                    "Usage: {} bundle-pack <graph.json> <out.ambara> [--with-outputs] [--plugins <dir>]",
+                    args[0]
+                );
+                std::process::exit(1);
+            }
+            let plugin_dir = args
+                .iter()
+                .position(|a| a == "--plugins")
+                .and_then(|i| args.get(i + 1));
+            std::process::exit(bundle_pack_command(
+                Path::new(&args[2]),
+                Path::new(&args[3]),
+                args.iter().any(|a| a == "--with-outputs"),
+                plugin_dir.map(Path::new),
+            ));
+        }
+        "bundle-unpack" => {
+            if args.len() < 4 {
+                eprintln!("Usage: {} bundle-unpack <bundle.ambara> <dir>", args[0]);
+                std::process::exit(1);
+            }
+            std::process::exit(bundle_unpack_command(Path::new(&args[2]), Path::new(&args[3])));
+        }
+        "bundle-verify" => {
+            if args.len() < 3 {
+                eprintln!("Usage: {} bundle-verify <bundle.ambara>", args[0]);
+                std::process::exit(1);
+            }
+            std::process::exit(bundle_verify_command(Path::new(&args[2])));
+        }
+        "graph-diff" => {
+            if args.len() < 4 {
+                eprintln!("Usage: {} graph-diff <before.json> <after.json> [--json]", args[0]);
+                std::process::exit(1);
+            }
+            let as_json = args.iter().any(|a| a == "--json");
+            std::process::exit(graph_diff_command(
+                Path::new(&args[2]),
+                Path::new(&args[3]),
+                as_json,
+            ));
+        }
+        "graph-merge" => {
+            if args.len() < 5 {
+                eprintln!(
+                    "Usage: {} graph-merge <base.json> <ours.json> <theirs.json> [--output <merged.json>]",
+                    args[0]
+                );
+                std::process::exit(1);
+            }
+            let output = args
+                .iter()
+                .position(|a| a == "--output" || a == "-o")
+                .and_then(|i| args.get(i + 1));
+            std::process::exit(graph_merge_command(
+                Path::new(&args[2]),
+                Path::new(&args[3]),
+                Path::new(&args[4]),
+                output.map(Path::new),
+            ));
+        }
+        "lint" => {
+            if args.len() < 3 {
+                eprintln!(
+                    "Usage: {} lint <graph.json> [--fix] [-o <out>] [--allow|--warn|--deny <rule>]",
+                    args[0]
+                );
+                std::process::exit(1);
+            }
+            let fix = args.iter().any(|a| a == "--fix");
+            let output = args
+                .iter()
+                .position(|a| a == "--output" || a == "-o")
+                .and_then(|i| args.get(i + 1));
+            let levels: Vec<(String, LintLevel)> = args
+                .windows(2)
+                .filter_map(|pair| {
+                    let level = match pair[0].as_str() {
+                        "--allow" => LintLevel::Allow,
+                        "--warn" => LintLevel::Warn,
+                        "--deny" => LintLevel::Deny,
+                        _ => return None,
+                    };
+                    Some((pair[1].clone(), level))
+                })
+                .collect();
+            std::process::exit(lint_command(
+                Path::new(&args[2]),
+                fix,
+                output.map(Path::new),
+                &levels,
+            ));
+        }
+        "plugin-plan" => {
+            if args.len() < 3 {
+                eprintln!("Usage: {} plugin-plan <plugin-dir>", args[0]);
+                std::process::exit(1);
+            }
+            std::process::exit(plugin_plan_command(Path::new(&args[2])));
+        }
+        "plugin-keygen" => {
+            if args.len() < 3 {
+                eprintln!("Usage: {} plugin-keygen <secret-key-file>", args[0]);
+                std::process::exit(1);
+            }
+            std::process::exit(plugin_keygen_command(Path::new(&args[2])));
+        }
+        "plugin-sign" => {
+            if args.len() < 4 {
+                eprintln!("Usage: {} plugin-sign <library> <secret-key-file>", args[0]);
+                std::process::exit(1);
+            }
+            std::process::exit(plugin_sign_command(Path::new(&args[2]), Path::new(&args[3])));
+        }
+        "plugin-verify" => {
+            if args.len() < 4 {
+                eprintln!("Usage: {} plugin-verify <library> <trust-store.toml>", args[0]);
+                std::process::exit(1);
+            }
+            std::process::exit(plugin_verify_command(Path::new(&args[2]), Path::new(&args[3])));
+        }
         "help" | "--help" | "-h" => print_usage(&args[0]),
         _ => {
             eprintln!("Unknown command: {}", args[1]);
@@ -111,6 +255,21 @@ fn print_usage(program: &str) {
     println!("  process <in> <out> [options]      Process an image");
     println!("  load-graph <path> --dry-run       Validate serialized graph only");
     println!("  load-graph <path> --execute       Validate and execute serialized graph");
+    println!("  import-comfy <file> [-o <out>]    Convert a ComfyUI workflow to a graph");
+    println!("  bundle-pack <graph> <out>         Pack a graph and its input files into a .ambara bundle");
+    println!("        [--with-outputs]            Also record outputs for bundle-verify");
+    println!("        [--plugins <dir>]           Embed manifests of plugins the graph uses");
+    println!("  bundle-unpack <bundle> <dir>      Extract a bundle; writes <dir>/graph.json");
+    println!("  bundle-verify <bundle>            Check an unpacked bundle reproduces its outputs");
+    println!("  graph-diff <before> <after>       Show node, parameter and connection changes [--json]");
+    println!("  graph-merge <base> <ours> <theirs> [-o <out>]");
+    println!("                                    Three-way merge of graphs (git merge driver)");
+    println!("  lint <graph> [--fix] [-o <out>]   Report lint findings; --fix applies their fixes");
+    println!("        [--allow|--warn|--deny <rule>]  Change a rule's level");
+    println!("  plugin-plan <plugin-dir>          Show plugin load order and conflicts");
+    println!("  plugin-keygen <key-file>          Create a plugin signing key");
+    println!("  plugin-sign <library> <key-file>  Write ambara-plugin.sig for a plugin");
+    println!("  plugin-verify <library> <trust>   Verify a plugin against a trust store");
     println!("  help                              Show this help message");
     println!();
     println!("Process options:");
@@ -283,7 +442,7 @@ fn process_image(args: &[String]) {
 
     // Build and execute pipeline
     let registry = FilterRegistry::with_builtins();
-    let mut graph = ProcessingGraph::new();
+    let mut graph = ProcessingGraph::new().with_conversions(registry.conversions());
 
     // Load image
     let mut load_node = GraphNode::new(registry.create("load_image").unwrap());
@@ -368,6 +527,9 @@ fn process_image(args: &[String]) {
                 ProgressUpdate::NodeStarted { node_name, .. } => {
                     println!("   • Running: {}", node_name);
                 }
+                ProgressUpdate::NodeProgress { message: Some(message), .. } => {
+                    println!("     {}", message);
+                }
                 ProgressUpdate::Completed { total_duration_ms, nodes_processed, .. } => {
                     println!("✅ Complete in {}ms ({} nodes)", total_duration_ms, nodes_processed);
                 }
@@ -402,15 +564,20 @@ fn load_graph_command(path: &str, dry_run: bool, execute: bool) -> i32 {
         }
     };
 
-    let serialized = match parse_serialized_graph(&text) {
-        Ok(v) => v,
+    let registry = FilterRegistry::with_builtins();
+    let serialized = match parse_serialized_graph(&text, &registry) {
+        Ok(migrated) => {
+            if !migrated.report.is_empty() {
+                eprint!("{}", migrated.report);
+            }
+            migrated.graph
+        }
         Err(err) => {
-            eprintln!("Invalid graph JSON: {err}");
+            eprintln!("{err}");
             return 1;
         }
     };
 
-    let registry = FilterRegistry::with_builtins();
     let errors = validate_serialized_graph(&serialized, &registry);
     if !errors.is_empty() {
         for error in errors {
@@ -436,31 +603,374 @@ fn load_graph_command(path: &str, dry_run: bool, execute: bool) -> i32 {
     0
 }
 
-fn parse_serialized_graph(text: &str) -> Result<SerializedGraph, serde_json::Error> {
-    if let Ok(graph) = SerializedGraph::from_json(text) {
-        return Ok(graph);
+fn import_comfy_command(path: &Path, output: Option<&Path>) -> i32 {
+    let text = match std::fs::read_to_string(path) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("Failed to read workflow file: {err}");
+            return 1;
+        }
+    };
+    let mut import = match import_comfy_workflow(&text) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("{err}");
+            return 1;
+        }
+    };
+    import.graph.metadata.name = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+
+    let json = match import.graph.to_json() {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("Failed to serialize graph: {err}");
+            return 1;
+        }
+    };
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(output, json) {
+                eprintln!("Failed to write graph file: {err}");
+                return 1;
+            }
+        }
+        None => println!("{json}"),
+    }
+    // The report goes to stderr so stdout stays a loadable graph.
+    eprint!("{}", import.report);
+    0
+}
+
+fn bundle_pack_command(
+    graph_path: &Path,
+    output: &Path,
+    with_outputs: bool,
+    plugin_dir: Option<&Path>,
+) -> i32 {
+    let text = match std::fs::read_to_string(graph_path) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("Failed to read graph file: {err}");
+            return 1;
+        }
+    };
+    let registry = FilterRegistry::with_builtins();
+    let graph = match parse_serialized_graph(&text, &registry) {
+        Ok(migrated) => migrated.graph,
+        Err(err) => {
+            eprintln!("{err}");
+            return 1;
+        }
+    };
+    let plugins = match plugin_dir {
+        Some(dir) => match PluginRegistry::new(dir, PluginSystemConfig::default()).discover() {
+            Ok(found) => found.into_iter().map(|(_, manifest)| manifest).collect(),
+            Err(err) => {
+                eprintln!("{err}");
+                return 1;
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let options = PackOptions {
+        base_dir: None,
+        include_outputs: with_outputs,
+        plugins,
+    };
+    match bundle::pack_bundle(&graph, &registry, &options, output) {
+        Ok(manifest) => {
+            println!(
+                "Bundle written to {} ({} inputs, {} outputs, {} plugin manifests)",
+                output.display(),
+                manifest.assets.len(),
+                manifest.outputs.len(),
+                manifest.plugins.len()
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+fn bundle_unpack_command(path: &Path, destination: &Path) -> i32 {
+    let registry = FilterRegistry::with_builtins();
+    match bundle::unpack_bundle(path, destination, &registry) {
+        Ok(unpacked) => {
+            if !unpacked.migration.is_empty() {
+                eprint!("{}", unpacked.migration);
+            }
+            for plugin in &unpacked.manifest.plugins {
+                eprintln!(
+                    "Requires plugin {} {} ({})",
+                    plugin.id,
+                    plugin.version,
+                    plugin.filters.join(", ")
+                );
+            }
+            println!(
+                "Graph written to {}",
+                unpacked.directory.join("graph.json").display()
+            );
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+fn bundle_verify_command(path: &Path) -> i32 {
+    let registry = FilterRegistry::with_builtins();
+    match bundle::verify_bundle(path, &registry) {
+        Ok(report) => {
+            print!("{report}");
+            i32::from(!report.is_identical())
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+/// Read a graph file, upgrading older formats.
+fn read_graph_file(path: &Path, registry: &FilterRegistry) -> Result<SerializedGraph, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read graph file {}: {err}", path.display()))?;
+    parse_serialized_graph(&text, registry)
+        .map(|migrated| migrated.graph)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+fn graph_diff_command(before: &Path, after: &Path, as_json: bool) -> i32 {
+    let registry = FilterRegistry::with_builtins();
+    let graphs = read_graph_file(before, &registry)
+        .and_then(|b| read_graph_file(after, &registry).map(|a| (b, a)));
+    let (before, after) = match graphs {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("{err}");
+            return 1;
+        }
+    };
+
+    let diff = diff_graphs(&before, &after);
+    if as_json {
+        match serde_json::to_string_pretty(&diff) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("Failed to serialize diff: {err}");
+                return 1;
+            }
+        }
+    } else {
+        print!("{diff}");
+    }
+    0
+}
+
+fn graph_merge_command(base: &Path, ours: &Path, theirs: &Path, output: Option<&Path>) -> i32 {
+    let registry = FilterRegistry::with_builtins();
+    let graphs = [base, ours, theirs]
+        .into_iter()
+        .map(|path| read_graph_file(path, &registry))
+        .collect::<Result<Vec<_>, _>>();
+    let graphs = match graphs {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("{err}");
+            return 1;
+        }
+    };
+
+    let merged = merge_graphs(&graphs[0], &graphs[1], &graphs[2]);
+    let json = match merged.graph.to_json() {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("Failed to serialize graph: {err}");
+            return 1;
+        }
+    };
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(output, json) {
+                eprintln!("Failed to write graph file: {err}");
+                return 1;
+            }
+        }
+        None => println!("{json}"),
+    }
+    // Conflicts go to stderr; a non-zero exit tells git to leave the file
+    // marked as conflicted.
+    for conflict in &merged.conflicts {
+        eprintln!("CONFLICT: {conflict}");
+    }
+    i32::from(!merged.is_clean())
+}
+
+fn lint_command(
+    path: &Path,
+    fix: bool,
+    output: Option<&Path>,
+    levels: &[(String, LintLevel)],
+) -> i32 {
+    let registry = FilterRegistry::with_builtins();
+    let mut linter = Linter::with_builtin_rules();
+    for (rule, level) in levels {
+        if linter.level(rule).is_none() {
+            let known: Vec<&str> = linter.rules().map(|r| r.name()).collect();
+            eprintln!("Unknown lint rule '{rule}'. Rules: {}", known.join(", "));
+            return 1;
+        }
+        linter.set_level(rule.clone(), *level);
     }
 
-    // Fallback: tolerate documents that are missing optional top-level
-    // fields (`version`, `metadata`, `metadata.tags`) by injecting
-    // defaults before a second deserialization attempt. Only meaningful
-    // for JSON objects — bare scalars, arrays, or null cannot represent
-    // a graph and would panic on Value::IndexMut. Re-parse them so the
-    // caller gets the original deserialization error instead.
-    let mut raw: serde_json::Value = serde_json::from_str(text)?;
-    if !raw.is_object() {
-        return serde_json::from_value(raw);
+    let serialized = match read_graph_file(path, &registry) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("{err}");
+            return 1;
+        }
+    };
+    let mut graph = match ProcessingGraph::from_serialized(&serialized, &registry) {
+        Ok(v) => v,
+        Err(err) => {
+            eprintln!("{}: {err}", path.display());
+            return 1;
+        }
+    };
+
+    if fix {
+        let fixed = match linter.fix(&mut graph, &registry) {
+            Ok(v) => v,
+            Err(err) => {
+                eprintln!("Failed to apply a lint fix: {err}");
+                return 1;
+            }
+        };
+        for finding in &fixed {
+            if let Some(fix) = &finding.fix {
+                println!("fixed [{}]: {}", finding.rule, fix.description);
+            }
+        }
+        // Rewrite the input in place only when something changed
+        if !fixed.is_empty() || output.is_some() {
+            let json = match SerializedGraph::from(&graph).to_json() {
+                Ok(v) => v,
+                Err(err) => {
+                    eprintln!("Failed to serialize graph: {err}");
+                    return 1;
+                }
+            };
+            if let Err(err) = std::fs::write(output.unwrap_or(path), json) {
+                eprintln!("Failed to write graph file: {err}");
+                return 1;
+            }
+        }
     }
-    if raw.get("version").is_none() {
-        raw["version"] = serde_json::Value::String(SerializedGraph::VERSION.to_string());
+
+    let findings = linter.lint(&graph);
+    for finding in &findings {
+        println!("{finding}");
     }
-    if raw.get("metadata").is_none() || !raw["metadata"].is_object() {
-        raw["metadata"] = serde_json::json!({});
+    i32::from(findings.iter().any(|f| f.level == LintLevel::Deny))
+}
+
+fn plugin_plan_command(plugin_dir: &Path) -> i32 {
+    let registry = PluginRegistry::new(plugin_dir, PluginSystemConfig::default());
+    let discovered = match registry.discover() {
+        Ok(found) => found,
+        Err(err) => {
+            eprintln!("{err}");
+            return 1;
+        }
+    };
+    let builtins = FilterRegistry::with_builtins();
+    let plan = Resolver::new(FilterConflictPolicy::default())
+        .with_reserved_filters(BUILTIN_OWNER, builtins.filter_ids())
+        .resolve(discovered);
+    print!("{plan}");
+    i32::from(!plan.skipped.is_empty())
+}
+
+fn plugin_keygen_command(key_path: &Path) -> i32 {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    // Never replace an existing key, and keep the new one private
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
     }
-    if raw["metadata"].get("tags").is_none() {
-        raw["metadata"]["tags"] = serde_json::json!([]);
+    let mut file = match options.open(key_path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+            eprintln!("Refusing to overwrite existing key file: {}", key_path.display());
+            return 1;
+        }
+        Err(err) => {
+            eprintln!("Failed to write key file: {err}");
+            return 1;
+        }
+    };
+    let key = signing::generate_signing_key();
+    if let Err(err) = file.write_all(signing::encode_signing_key(&key).as_bytes()) {
+        eprintln!("Failed to write key file: {err}");
+        return 1;
     }
-    serde_json::from_value(raw)
+    println!("Secret key written to {}", key_path.display());
+    println!(
+        "Public key: {}",
+        signing::encode_public_key(&key.verifying_key())
+    );
+    0
+}
+
+fn plugin_sign_command(library: &Path, key_path: &Path) -> i32 {
+    let result = signing::load_signing_key(key_path)
+        .and_then(|key| PluginSignature::sign(library, &key))
+        .and_then(|signature| signature.write_for(library));
+    match result {
+        Ok(path) => {
+            println!("Signature written to {}", path.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+fn plugin_verify_command(library: &Path, trust_store: &Path) -> i32 {
+    let result = TrustStore::from_path(trust_store)
+        .and_then(|store| signing::verify_plugin(library, &store));
+    match result {
+        Ok(publisher) => {
+            println!("Signature valid (publisher: {publisher})");
+            0
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
+
+fn parse_serialized_graph(
+    text: &str,
+    registry: &FilterRegistry,
+) -> Result<MigratedGraph, MigrationError> {
+    // Older documents (including ones missing `version` or `metadata`) are
+    // upgraded by the builtin migrations; the report says what changed.
+    SerializedGraph::load(text, registry)
 }
 
 fn validate_serialized_graph(graph: &SerializedGraph, registry: &FilterRegistry) -> Vec<String> {
@@ -489,65 +999,20 @@ fn validate_serialized_graph(graph: &SerializedGraph, registry: &FilterRegistry)
 }
 
 fn execute_serialized_graph(graph: &SerializedGraph, registry: &FilterRegistry) -> LoadGraphResult {
-    let mut processing_graph = ProcessingGraph::new();
-    let mut node_map: HashMap<NodeId, NodeId> = HashMap::new();
-
-    for node in &graph.nodes {
-        // Defense-in-depth (loop 32): the call site at main()/load-graph
-        // always runs validate_serialized_graph first, which rejects
-        // duplicate node ids. But node_map.insert() below silently
-        // overwrites prior entries, which would silently reroute every
-        // edge targeting the duplicated id to the last-inserted node if
-        // a future caller ever bypassed validation. Mirror loop 27's
-        // Tauri executor: fail loud here too.
-        if node_map.contains_key(&node.id) {
-            return LoadGraphResult {
-                success: false,
-                errors: vec![format!("Duplicate node id: {}", node.id)],
-                outputs: HashMap::new(),
-            };
-        }
-
-        let Some(filter) = registry.create(&node.filter_id) else {
-            return LoadGraphResult {
-                success: false,
-                errors: vec![format!("Unknown filter id: {}", node.filter_id)],
-                outputs: HashMap::new(),
-            };
-        };
-
-        let mut graph_node = GraphNode::new(filter).with_position(node.position.x, node.position.y);
-        for (key, value) in &node.parameters {
-            graph_node = graph_node.with_parameter(key, value.clone());
-        }
-
-        let new_id = processing_graph.add_node(graph_node);
-        node_map.insert(node.id, new_id);
-    }
-
-    for conn in &graph.connections {
-        let Some(from) = node_map.get(&conn.from_node).copied() else {
-            return LoadGraphResult {
-                success: false,
-                errors: vec![format!("Unknown from_node {}", conn.from_node)],
-                outputs: HashMap::new(),
-            };
-        };
-        let Some(to) = node_map.get(&conn.to_node).copied() else {
-            return LoadGraphResult {
-                success: false,
-                errors: vec![format!("Unknown to_node {}", conn.to_node)],
-                outputs: HashMap::new(),
-            };
-        };
-        if let Err(err) = processing_graph.connect(from, &conn.from_port, to, &conn.to_port) {
+    // The call site at main()/load-graph runs validate_serialized_graph
+    // first, but from_serialized rejects duplicate ids, unknown filters and
+    // bad connections on its own, so callers that skip validation still
+    // fail loud.
+    let processing_graph = match ProcessingGraph::from_serialized(graph, registry) {
+        Ok(processing_graph) => processing_graph,
+        Err(err) => {
             return LoadGraphResult {
                 success: false,
-                errors: vec![format!("Connection error: {err}")],
+                errors: vec![err.to_string()],
                 outputs: HashMap::new(),
             };
         }
-    }
+    };
 
     let validation = ValidationPipeline::default().validate(&processing_graph);
     if !validation.errors.is_empty() {
@@ -638,16 +1103,18 @@ mod tests {
             "connections": []
         })
         .to_string();
-        let parsed = parse_serialized_graph(&json).expect("full payload must parse");
-        assert!(parsed.nodes.is_empty());
+        let registry = FilterRegistry::with_builtins();
+        let parsed = parse_serialized_graph(&json, &registry).expect("full payload must parse");
+        assert!(parsed.graph.nodes.is_empty());
     }
 
     #[test]
     fn parse_serialized_graph_injects_missing_top_level_fields() {
         let json = r#"{ "nodes": [], "connections": [] }"#;
-        let parsed = parse_serialized_graph(json).expect("partial payload must parse");
-        assert_eq!(parsed.version, SerializedGraph::VERSION);
-        assert!(parsed.metadata.tags.is_empty());
+        let registry = FilterRegistry::with_builtins();
+        let parsed = parse_serialized_graph(json, &registry).expect("partial payload must parse");
+        assert_eq!(parsed.graph.version, SerializedGraph::VERSION);
+        assert!(parsed.graph.metadata.tags.is_empty());
     }
 
     #[test]
@@ -655,8 +1122,9 @@ mod tests {
         // Regression: previously the fallback path called Value::IndexMut on
         // the parsed Value unconditionally. Bare strings/numbers/booleans
         // panicked instead of returning a serde_json::Error.
+        let registry = FilterRegistry::with_builtins();
         for payload in ["\"plain\"", "42", "true", "null", "[1, 2, 3]"] {
-            let err = parse_serialized_graph(payload)
+            let err = parse_serialized_graph(payload, &registry)
                 .expect_err(&format!("bare scalar payload {payload:?} must error, not parse"));
             // Sanity check: it produced an Err rather than panicking.
             let _ = err.to_string();
@@ -665,7 +1133,8 @@ mod tests {
 
     #[test]
     fn parse_serialized_graph_rejects_invalid_json() {
-        assert!(parse_serialized_graph("not json").is_err());
+        let registry = FilterRegistry::with_builtins();
+        assert!(parse_serialized_graph("not json", &registry).is_err());
     }
 
     #[test]
@@ -686,6 +1155,8 @@ mod tests {
                     parameters: HashMap::new(),
                     label: None,
                     disabled: false,
+                    promoted_parameters: Vec::new(),
+                    filter_version: None,
                 },
                 SerializedNode {
                     id: dup_id,
@@ -694,6 +1165,8 @@ mod tests {
                     parameters: HashMap::new(),
                     label: None,
                     disabled: false,
+                    promoted_parameters: Vec::new(),
+                    filter_version: None,
                 },
             ],
             connections: vec![],
@@ -729,6 +1202,8 @@ mod tests {
                     parameters: HashMap::new(),
                     label: None,
                     disabled: false,
+                    promoted_parameters: Vec::new(),
+                    filter_version: None,
                 },
                 SerializedNode {
                     id: dup_id,
@@ -737,6 +1212,8 @@ mod tests {
                     parameters: HashMap::new(),
                     label: None,
                     disabled: false,
+                    promoted_parameters: Vec::new(),
+                    filter_version: None,
                 },
             ],
             connections: vec![],
@@ -752,4 +1229,77 @@ mod tests {
             result.errors
         );
     }
+
+    #[test]
+    fn import_comfy_writes_a_loadable_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let workflow = dir.path().join("fox.json");
+        std::fs::write(
+            &workflow,
+            serde_json::json!({
+                "1": {"class_type": "CheckpointLoaderSimple", "inputs": {"ckpt_name": "sd15.safetensors"}},
+                "2": {"class_type": "CLIPTextEncode", "inputs": {"text": "a red fox", "clip": ["1", 1]}},
+                "3": {"class_type": "FaceRestore", "inputs": {"conditioning": ["2", 0]}}
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let output = dir.path().join("fox.graph.json");
+
+        assert_eq!(import_comfy_command(&workflow, Some(&output)), 0);
+        let registry = FilterRegistry::with_builtins();
+        let graph = parse_serialized_graph(&std::fs::read_to_string(&output).unwrap(), &registry)
+            .unwrap()
+            .graph;
+        assert_eq!(graph.metadata.name.as_deref(), Some("fox"));
+        assert_eq!((graph.nodes.len(), graph.connections.len()), (3, 2));
+        let errors = validate_serialized_graph(&graph, &registry);
+        assert!(errors.is_empty(), "{errors:?}");
+
+        assert_eq!(import_comfy_command(&dir.path().join("missing.json"), None), 1);
+    }
+
+    #[test]
+    fn lint_fix_rewrites_the_graph() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let mut previous = None;
+        for filter in ["load_image", "brightness", "brightness", "save_image"] {
+            let id = graph.add_node(GraphNode::new(registry.create(filter).unwrap()));
+            if let Some(previous) = previous {
+                graph.connect(previous, "image", id, "image").unwrap();
+            }
+            previous = Some(id);
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("graph.json");
+        std::fs::write(&path, SerializedGraph::from(&graph).to_json().unwrap()).unwrap();
+
+        let deny = [("mergeable_sequence".to_string(), LintLevel::Deny)];
+        assert_eq!(lint_command(&path, false, None, &deny), 1);
+        let unknown = [("no_such_rule".to_string(), LintLevel::Deny)];
+        assert_eq!(lint_command(&path, false, None, &unknown), 1);
+
+        assert_eq!(lint_command(&path, true, None, &deny), 0);
+        let fixed = read_graph_file(&path, &registry).unwrap();
+        assert_eq!((fixed.nodes.len(), fixed.connections.len()), (3, 2));
+    }
+
+    #[test]
+    fn keygen_keeps_existing_keys_and_private_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("publisher.key");
+        assert_eq!(plugin_keygen_command(&path), 0);
+        let key = std::fs::read_to_string(&path).unwrap();
+        assert!(signing::load_signing_key(&path).is_ok());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        assert_eq!(plugin_keygen_command(&path), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), key);
+    }
 }
@@ -241,8 +241,9 @@ pub struct PluginVTable {
     /// Periodic health check.
     ///
     /// Returns [`AbiResult::Ok`] if the plugin is functioning normally. If
-    /// this returns an error, the host will mark the plugin as unhealthy and
-    /// stop routing new work to it.
+    /// this returns an error, the host marks the plugin as unhealthy; after
+    /// repeated failures it quarantines the plugin and stops routing new
+    /// work to it (see [`crate::plugins::health`]).
     ///
     /// # Safety
     ///
@@ -0,0 +1,518 @@
+//! # Plugin Configuration Schema
+//!
+//! Typed settings a plugin declares in its manifest, validated by the host
+//! before `plugin_init` is called.
+//!
+//! ## Manifest Format
+//!
+//! ```toml
+//! [plugin.config_schema.comfyui_url]
+//! type = "string"
+//! default = "http://127.0.0.1:8188"
+//! description = "ComfyUI server URL"
+//! pattern = "http"
+//!
+//! [plugin.config_schema.timeout_secs]
+//! type = "integer"
+//! default = 30
+//! min = 1
+//! max = 600
+//!
+//! [plugin.config_schema.api_key]
+//! type = "string"
+//! secret = true
+//! required = true
+//! ```
+//!
+//! Each entry becomes a [`ParameterDefinition`] with matching
+//! [`Constraint`]s, so config values are checked by the same code that checks
+//! node parameters, and the UI can render the schema with the same widgets.
+//!
+//! Keys that are not in the schema (such as host-wide settings in
+//! [`crate::plugins::registry::PluginSystemConfig::host_config`]) are passed
+//! through unchanged. Secret values are never included in
+//! [`ConfigSchema::redact`] output, which is what inventory exports use.
+
+use crate::core::error::PluginError;
+use crate::core::port::{Constraint, ParameterDefinition, UiHint};
+use crate::core::types::{PortType, Value};
+use crate::plugins::manifest::PluginManifest;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Value type of a config entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigType {
+    /// UTF-8 string.
+    String,
+    /// 64-bit signed integer.
+    Integer,
+    /// 64-bit float.
+    Float,
+    /// Boolean flag.
+    Boolean,
+}
+
+impl ConfigType {
+    /// The [`PortType`] used for this entry's [`ParameterDefinition`].
+    #[must_use]
+    pub fn port_type(self) -> PortType {
+        match self {
+            Self::String => PortType::String,
+            Self::Integer => PortType::Integer,
+            Self::Float => PortType::Float,
+            Self::Boolean => PortType::Boolean,
+        }
+    }
+
+    /// Convert a JSON value to this type.
+    ///
+    /// Strings are parsed for non-string types, since legacy
+    /// `[plugin.config]` defaults are always strings.
+    fn coerce(self, json: &serde_json::Value) -> Option<Value> {
+        use serde_json::Value as J;
+        match (self, json) {
+            (Self::String, J::String(s)) => Some(Value::String(s.clone())),
+            (Self::Integer, J::Number(n)) => n.as_i64().map(Value::Integer),
+            (Self::Integer, J::String(s)) => s.trim().parse().ok().map(Value::Integer),
+            (Self::Float, J::Number(n)) => n.as_f64().map(Value::Float),
+            (Self::Float, J::String(s)) => s.trim().parse().ok().map(Value::Float),
+            (Self::Boolean, J::Bool(b)) => Some(Value::Boolean(*b)),
+            (Self::Boolean, J::String(s)) => s.trim().parse().ok().map(Value::Boolean),
+            _ => None,
+        }
+    }
+
+    fn zero(self) -> Value {
+        match self {
+            Self::String => Value::String(String::new()),
+            Self::Integer => Value::Integer(0),
+            Self::Float => Value::Float(0.0),
+            Self::Boolean => Value::Boolean(false),
+        }
+    }
+}
+
+/// One `[plugin.config_schema.<name>]` table, as written in the manifest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigFieldSpec {
+    /// Value type.
+    #[serde(rename = "type")]
+    pub kind: ConfigType,
+    /// Default value; required entries may omit it.
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
+    /// Label shown in the settings form.
+    #[serde(default)]
+    pub display_name: Option<String>,
+    /// Help text shown in the settings form.
+    #[serde(default)]
+    pub description: String,
+    /// Settings form section.
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Minimum numeric value.
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// Maximum numeric value.
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Numeric step.
+    #[serde(default)]
+    pub step: Option<f64>,
+    /// Allowed values.
+    #[serde(default)]
+    pub one_of: Vec<serde_json::Value>,
+    /// Text a string value must contain (see [`Constraint::Pattern`]).
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Minimum string length.
+    #[serde(default)]
+    pub min_length: Option<usize>,
+    /// Maximum string length.
+    #[serde(default)]
+    pub max_length: Option<usize>,
+    /// Whether the user must supply a value.
+    #[serde(default)]
+    pub required: bool,
+    /// Whether the value is sensitive (API keys, tokens).
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// A validated config entry.
+#[derive(Debug, Clone)]
+pub struct ConfigField {
+    /// Type, default, constraints and UI hints.
+    pub definition: ParameterDefinition,
+    /// Value type.
+    pub kind: ConfigType,
+    /// Whether the user must supply a value.
+    pub required: bool,
+    /// Whether the value is sensitive.
+    pub secret: bool,
+}
+
+/// The typed configuration a plugin accepts.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSchema {
+    fields: Vec<ConfigField>,
+}
+
+impl ConfigSchema {
+    /// Build the schema declared by `manifest`.
+    ///
+    /// Legacy `[plugin.config]` string values serve as defaults for schema
+    /// entries without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::ManifestParseError`] if a default does not
+    /// have the declared type or violates the entry's own constraints.
+    pub fn from_manifest(manifest: &PluginManifest) -> Result<Self, PluginError> {
+        let plugin = &manifest.plugin;
+        let fields = plugin
+            .config_schema
+            .iter()
+            .map(|(name, spec)| {
+                let legacy = plugin
+                    .config
+                    .get(name)
+                    .map(|s| serde_json::Value::String(s.clone()));
+                ConfigField::from_spec(name, spec, legacy.as_ref()).map_err(|reason| {
+                    PluginError::ManifestParseError {
+                        path: std::path::PathBuf::from("<manifest>"),
+                        reason: format!("plugin.config_schema.{name}: {reason}"),
+                    }
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { fields })
+    }
+
+    /// The declared entries, in manifest order.
+    #[must_use]
+    pub fn fields(&self) -> &[ConfigField] {
+        &self.fields
+    }
+
+    /// Whether the plugin declares no schema.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Look up an entry by name.
+    #[must_use]
+    pub fn field(&self, name: &str) -> Option<&ConfigField> {
+        self.fields.iter().find(|f| f.definition.name == name)
+    }
+
+    /// Validate `config` and fill in defaults.
+    ///
+    /// Returns `config` with every schema entry present and converted to its
+    /// declared type; other keys are left as they are.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::InvalidConfig`] for the first entry that is
+    /// missing while required, has the wrong type, or violates a constraint.
+    pub fn resolve(
+        &self,
+        plugin_id: &str,
+        config: &serde_json::Value,
+    ) -> Result<serde_json::Value, PluginError> {
+        let mut resolved = match config {
+            serde_json::Value::Object(map) => map.clone(),
+            _ => serde_json::Map::new(),
+        };
+        for field in &self.fields {
+            let name = &field.definition.name;
+            let invalid = |reason: String| PluginError::InvalidConfig {
+                plugin_id: plugin_id.to_string(),
+                key: name.clone(),
+                reason,
+            };
+            let value = match resolved.get(name).filter(|v| !v.is_null()) {
+                Some(json) => field.kind.coerce(json).ok_or_else(|| {
+                    invalid(format!("expected {}, got {json}", field.definition.param_type))
+                })?,
+                None if field.required => return Err(invalid("a value is required".to_string())),
+                None => field.definition.default_value.clone(),
+            };
+            field.definition.validate(&value).map_err(invalid)?;
+            resolved.insert(name.clone(), config_value_to_json(&value));
+        }
+        Ok(serde_json::Value::Object(resolved))
+    }
+
+    /// `config` with every secret entry removed, for display and export.
+    #[must_use]
+    pub fn redact(&self, config: &serde_json::Value) -> serde_json::Value {
+        let mut redacted = config.clone();
+        if let Some(map) = redacted.as_object_mut() {
+            for field in self.fields.iter().filter(|f| f.secret) {
+                map.remove(&field.definition.name);
+            }
+        }
+        redacted
+    }
+}
+
+impl ConfigField {
+    fn from_spec(
+        name: &str,
+        spec: &ConfigFieldSpec,
+        legacy_default: Option<&serde_json::Value>,
+    ) -> Result<Self, String> {
+        let default = match spec.default.as_ref().or(legacy_default) {
+            Some(json) => spec
+                .kind
+                .coerce(json)
+                .ok_or_else(|| format!("default {json} is not a {}", spec.kind.port_type()))?,
+            None => spec.kind.zero(),
+        };
+
+        let mut definition = ParameterDefinition::new(name, spec.kind.port_type(), default)
+            .with_description(spec.description.clone());
+        if let Some(display_name) = &spec.display_name {
+            definition = definition.with_display_name(display_name.clone());
+        }
+        if let Some(group) = &spec.group {
+            definition = definition.with_group(group.clone());
+        }
+        definition = match (spec.min, spec.max) {
+            (Some(min), Some(max)) => definition.with_range(min, max),
+            (Some(min), None) => definition.with_constraint(Constraint::MinValue(min)),
+            (None, Some(max)) => definition.with_constraint(Constraint::MaxValue(max)),
+            (None, None) => definition,
+        };
+        if let Some(step) = spec.step {
+            definition = definition.with_constraint(Constraint::Step(step));
+        }
+        if let Some(pattern) = &spec.pattern {
+            definition = definition.with_constraint(Constraint::Pattern(pattern.clone()));
+        }
+        if let Some(min) = spec.min_length {
+            definition = definition.with_constraint(Constraint::MinLength(min));
+        }
+        if let Some(max) = spec.max_length {
+            definition = definition.with_constraint(Constraint::MaxLength(max));
+        }
+        if !spec.one_of.is_empty() {
+            let options = spec
+                .one_of
+                .iter()
+                .map(|json| {
+                    spec.kind
+                        .coerce(json)
+                        .ok_or_else(|| format!("option {json} is not a {}", spec.kind.port_type()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if spec.kind == ConfigType::String {
+                let labels = options
+                    .iter()
+                    .filter_map(|v| v.as_string().map(str::to_string))
+                    .collect();
+                definition = definition.with_ui_hint(UiHint::Dropdown { options: labels });
+            }
+            definition = definition.with_constraint(Constraint::OneOf(options));
+        }
+        if spec.secret {
+            definition = definition.with_ui_hint(UiHint::TextInput {
+                multiline: false,
+                placeholder: None,
+            });
+        }
+
+        // A required entry's placeholder default is never used.
+        let has_default = spec.default.is_some() || legacy_default.is_some();
+        if has_default {
+            definition.validate(&definition.default_value)?;
+        }
+
+        Ok(Self {
+            definition,
+            kind: spec.kind,
+            required: spec.required,
+            secret: spec.secret,
+        })
+    }
+}
+
+/// Plain JSON for a scalar config value (as plugins receive it).
+fn config_value_to_json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(s) => serde_json::Value::String(s.clone()),
+        Value::Integer(i) => serde_json::json!(i),
+        Value::Float(f) => serde_json::json!(f),
+        Value::Boolean(b) => serde_json::Value::Bool(*b),
+        _ => serde_json::Value::Null,
+    }
+}
+
+/// Per-plugin user configuration, keyed by plugin ID.
+pub type PluginConfigMap = IndexMap<String, serde_json::Value>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(schema: &str) -> PluginManifest {
+        PluginManifest::from_toml_str(&format!(
+            r#"
+[plugin]
+id = "com.test.config"
+name = "Config"
+version = "1.0.0"
+description = ""
+author = ""
+license = "MIT"
+ambara_abi_version = 1
+min_ambara_version = "0.1.0"
+max_ambara_version = "99.0.0"
+
+[plugin.capabilities]
+
+[plugin.filters]
+ids = []
+
+[plugin.config]
+legacy = "7"
+
+{schema}
+"#
+        ))
+        .unwrap()
+    }
+
+    fn schema(toml: &str) -> ConfigSchema {
+        ConfigSchema::from_manifest(&manifest(toml)).unwrap()
+    }
+
+    const SCHEMA: &str = r#"
+[plugin.config_schema.url]
+type = "string"
+default = "http://127.0.0.1:8188"
+pattern = "http"
+
+[plugin.config_schema.timeout]
+type = "integer"
+default = 30
+min = 1
+max = 600
+
+[plugin.config_schema.api_key]
+type = "string"
+secret = true
+required = true
+"#;
+
+    mod from_manifest {
+        use super::*;
+
+        #[test]
+        fn builds_parameter_definitions() {
+            let schema = schema(SCHEMA);
+            assert_eq!(schema.fields().len(), 3);
+            let timeout = &schema.field("timeout").unwrap().definition;
+            assert_eq!(timeout.param_type, PortType::Integer);
+            assert_eq!(timeout.default_value, Value::Integer(30));
+            assert!(matches!(timeout.ui_hint, UiHint::Slider { .. }));
+            assert!(schema.field("api_key").unwrap().secret);
+        }
+
+        #[test]
+        fn legacy_config_supplies_defaults() {
+            let schema = schema("[plugin.config_schema.legacy]\ntype = \"integer\"");
+            assert_eq!(
+                schema.field("legacy").unwrap().definition.default_value,
+                Value::Integer(7)
+            );
+        }
+
+        #[test]
+        fn rejects_mistyped_default() {
+            let m = manifest("[plugin.config_schema.n]\ntype = \"integer\"\ndefault = \"many\"");
+            assert!(matches!(
+                ConfigSchema::from_manifest(&m),
+                Err(PluginError::ManifestParseError { .. })
+            ));
+        }
+
+        #[test]
+        fn rejects_default_outside_range() {
+            let m = manifest("[plugin.config_schema.n]\ntype = \"float\"\ndefault = 5.0\nmax = 1.0");
+            assert!(ConfigSchema::from_manifest(&m).is_err());
+        }
+
+        #[test]
+        fn one_of_strings_become_dropdown() {
+            let schema =
+                schema("[plugin.config_schema.mode]\ntype = \"string\"\ndefault = \"a\"\none_of = [\"a\", \"b\"]");
+            let definition = &schema.field("mode").unwrap().definition;
+            assert!(matches!(&definition.ui_hint, UiHint::Dropdown { options } if options.len() == 2));
+        }
+    }
+
+    mod resolve {
+        use super::*;
+
+        #[test]
+        fn fills_defaults_and_keeps_other_keys() {
+            let resolved = schema(SCHEMA)
+                .resolve("p", &json!({ "api_key": "k", "host_setting": true }))
+                .unwrap();
+            assert_eq!(resolved["timeout"], json!(30));
+            assert_eq!(resolved["url"], json!("http://127.0.0.1:8188"));
+            assert_eq!(resolved["host_setting"], json!(true));
+        }
+
+        #[test]
+        fn coerces_strings() {
+            let resolved = schema(SCHEMA)
+                .resolve("p", &json!({ "api_key": "k", "timeout": "45" }))
+                .unwrap();
+            assert_eq!(resolved["timeout"], json!(45));
+        }
+
+        #[test]
+        fn reports_missing_required_value() {
+            let err = schema(SCHEMA).resolve("p", &json!({})).unwrap_err();
+            assert!(matches!(err, PluginError::InvalidConfig { key, .. } if key == "api_key"));
+        }
+
+        #[test]
+        fn reports_constraint_violations() {
+            let err = schema(SCHEMA)
+                .resolve("p", &json!({ "api_key": "k", "timeout": 0 }))
+                .unwrap_err();
+            assert!(matches!(err, PluginError::InvalidConfig { key, .. } if key == "timeout"));
+
+            let err = schema(SCHEMA)
+                .resolve("p", &json!({ "api_key": "k", "url": "ftp://x" }))
+                .unwrap_err();
+            assert!(matches!(err, PluginError::InvalidConfig { key, .. } if key == "url"));
+        }
+
+        #[test]
+        fn reports_wrong_type() {
+            let err = schema(SCHEMA)
+                .resolve("p", &json!({ "api_key": 5 }))
+                .unwrap_err();
+            assert!(matches!(err, PluginError::InvalidConfig { .. }));
+        }
+    }
+
+    #[test]
+    fn redact_removes_secrets() {
+        let schema = schema(SCHEMA);
+        let resolved = schema.resolve("p", &json!({ "api_key": "k" })).unwrap();
+        let redacted = schema.redact(&resolved);
+        assert!(redacted.get("api_key").is_none());
+        assert_eq!(redacted["timeout"], json!(30));
+    }
+}
@@ -10,9 +10,19 @@
 //! vtable's `plugin_health_check` function pointer. The result is captured
 //! in a [`HealthReport`] which records the timestamp and the verdict.
 //!
-//! The registry's `health_check_all()` iterates all loaded plugins, collects
-//! their reports, and returns the IDs of any unhealthy plugins for the caller
-//! to decide whether to unload them.
+//! Every report is recorded in the plugin's [`PluginHealth`], which keeps a
+//! [`HealthHistory`] of recent checks (latency, failure counts). A plugin
+//! that fails [`HealthPolicy::failure_threshold`] checks in a row is
+//! quarantined: its `PluginFilterNode`s fail fast instead of calling into the
+//! library, and graph validation warns about them. After
+//! [`HealthPolicy::recovery_threshold`] consecutive healthy checks the
+//! quarantine is lifted again.
+//!
+//! The registry's `health_check_all()` runs one round of checks on demand;
+//! `spawn_health_monitor()` starts a [`HealthMonitor`] thread that does so
+//! every [`HealthPolicy::interval`]. A plugin that is busy executing a filter
+//! when its check is due is skipped for that round rather than blocking the
+//! monitor.
 //!
 //! ## Examples
 //!
@@ -24,7 +34,17 @@
 //! assert!(report.is_healthy());
 //! ```
 
-use std::time::Instant;
+use crate::plugins::loader::LoadedPlugin;
+
+use crossbeam::channel::{Receiver, Sender};
+use indexmap::IndexMap;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// The result of a single health-check invocation.
 #[derive(Debug, Clone)]
@@ -37,6 +57,8 @@ pub struct HealthReport {
     pub reason: Option<String>,
     /// Timestamp of the check.
     pub checked_at: Instant,
+    /// How long the plugin took to answer.
+    pub latency: Duration,
 }
 
 impl HealthReport {
@@ -62,6 +84,7 @@ impl HealthReport {
             healthy: true,
             reason: None,
             checked_at,
+            latency: Duration::ZERO,
         }
     }
 
@@ -96,6 +119,7 @@ impl HealthReport {
             healthy: false,
             reason: Some(reason.into()),
             checked_at,
+            latency: Duration::ZERO,
         }
     }
 
@@ -104,6 +128,361 @@ impl HealthReport {
     pub fn is_healthy(&self) -> bool {
         self.healthy
     }
+
+    /// Set the measured check latency.
+    #[must_use]
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+}
+
+// ============================================================================
+// Health history and quarantine
+// ============================================================================
+
+/// Thresholds and timing for health monitoring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthPolicy {
+    /// Time between monitor rounds.
+    pub interval: Duration,
+    /// Consecutive failed checks before a plugin is quarantined.
+    pub failure_threshold: u32,
+    /// Consecutive healthy checks before a quarantine is lifted. `0` keeps
+    /// plugins quarantined until released explicitly.
+    pub recovery_threshold: u32,
+    /// Number of recent checks kept in [`HealthHistory::recent`].
+    pub history_len: usize,
+    /// How long the monitor waits for a busy plugin before skipping it.
+    pub busy_timeout: Duration,
+}
+
+impl Default for HealthPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(30),
+            failure_threshold: 3,
+            recovery_threshold: 2,
+            history_len: 20,
+            busy_timeout: Duration::from_millis(100),
+        }
+    }
+}
+
+/// One recorded health check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthSample {
+    /// Whether the check passed.
+    pub healthy: bool,
+    /// Check latency in milliseconds.
+    pub latency_ms: f64,
+    /// Failure reason, for unhealthy checks.
+    pub reason: Option<String>,
+    /// Wall-clock time of the check, in milliseconds since the Unix epoch.
+    pub checked_at_ms: u64,
+}
+
+/// Why and since when a plugin is quarantined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quarantine {
+    /// Human-readable reason, usually the last failed check.
+    pub reason: String,
+    /// Milliseconds since the Unix epoch when the quarantine started.
+    pub since_ms: u64,
+    /// Set by [`PluginHealth::quarantine`]; only an explicit release lifts it.
+    pub manual: bool,
+}
+
+/// Accumulated health-check results for one plugin.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthHistory {
+    /// Checks recorded since the plugin was loaded.
+    pub total_checks: u64,
+    /// Failed checks recorded since the plugin was loaded.
+    pub total_failures: u64,
+    /// Failed checks since the last healthy one.
+    pub consecutive_failures: u32,
+    /// Healthy checks since the last failed one.
+    pub consecutive_successes: u32,
+    /// Latency of the most recent check, in milliseconds.
+    pub last_latency_ms: Option<f64>,
+    /// Mean latency over [`HealthHistory::recent`], in milliseconds.
+    pub average_latency_ms: Option<f64>,
+    /// The most recent checks, oldest first.
+    pub recent: VecDeque<HealthSample>,
+    /// Set while the plugin is quarantined.
+    pub quarantine: Option<Quarantine>,
+}
+
+/// A change in quarantine state caused by a recorded check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthTransition {
+    /// The plugin crossed the failure threshold and is now quarantined.
+    Quarantined,
+    /// The plugin recovered and is no longer quarantined.
+    Released,
+}
+
+impl HealthHistory {
+    /// Whether the plugin is currently quarantined.
+    #[must_use]
+    pub fn is_quarantined(&self) -> bool {
+        self.quarantine.is_some()
+    }
+
+    /// Record a health check and apply the policy's thresholds.
+    ///
+    /// Returns the quarantine transition the check caused, if any.
+    pub fn record(&mut self, report: &HealthReport, policy: &HealthPolicy) -> Option<HealthTransition> {
+        let latency_ms = report.latency.as_secs_f64() * 1000.0;
+        self.total_checks += 1;
+        self.last_latency_ms = Some(latency_ms);
+        self.recent.push_back(HealthSample {
+            healthy: report.healthy,
+            latency_ms,
+            reason: report.reason.clone(),
+            checked_at_ms: unix_ms(),
+        });
+        while self.recent.len() > policy.history_len.max(1) {
+            self.recent.pop_front();
+        }
+        self.average_latency_ms = Some(
+            self.recent.iter().map(|s| s.latency_ms).sum::<f64>() / self.recent.len() as f64,
+        );
+
+        if report.healthy {
+            self.consecutive_failures = 0;
+            self.consecutive_successes += 1;
+            if self.quarantine.as_ref().is_some_and(|q| !q.manual)
+                && policy.recovery_threshold > 0
+                && self.consecutive_successes >= policy.recovery_threshold
+            {
+                self.quarantine = None;
+                return Some(HealthTransition::Released);
+            }
+        } else {
+            self.total_failures += 1;
+            self.consecutive_successes = 0;
+            self.consecutive_failures += 1;
+            if !self.is_quarantined() && self.consecutive_failures >= policy.failure_threshold.max(1) {
+                self.quarantine = Some(Quarantine {
+                    reason: format!(
+                        "{} consecutive failed health checks (last: {})",
+                        self.consecutive_failures,
+                        report.reason.as_deref().unwrap_or("unhealthy")
+                    ),
+                    since_ms: unix_ms(),
+                    manual: false,
+                });
+                return Some(HealthTransition::Quarantined);
+            }
+        }
+        None
+    }
+}
+
+fn unix_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
+
+/// Health state of one loaded plugin, shared between the registry, the
+/// monitor thread and the plugin's filter nodes.
+#[derive(Debug, Default)]
+pub struct PluginHealth {
+    history: Mutex<HealthHistory>,
+}
+
+impl PluginHealth {
+    /// Create an empty health record.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a check; see [`HealthHistory::record`].
+    pub fn record(&self, report: &HealthReport, policy: &HealthPolicy) -> Option<HealthTransition> {
+        self.history.lock().record(report, policy)
+    }
+
+    /// A copy of the current history.
+    #[must_use]
+    pub fn snapshot(&self) -> HealthHistory {
+        self.history.lock().clone()
+    }
+
+    /// The quarantine reason, if the plugin is quarantined.
+    #[must_use]
+    pub fn quarantine_reason(&self) -> Option<String> {
+        self.history.lock().quarantine.as_ref().map(|q| q.reason.clone())
+    }
+
+    /// Whether the plugin is currently quarantined.
+    #[must_use]
+    pub fn is_quarantined(&self) -> bool {
+        self.history.lock().is_quarantined()
+    }
+
+    /// Quarantine the plugin until [`PluginHealth::release`] is called,
+    /// regardless of later check results.
+    pub fn quarantine(&self, reason: impl Into<String>) {
+        self.history.lock().quarantine = Some(Quarantine {
+            reason: reason.into(),
+            since_ms: unix_ms(),
+            manual: true,
+        });
+    }
+
+    /// Lift the quarantine and reset the consecutive counters. Returns
+    /// whether the plugin was quarantined.
+    pub fn release(&self) -> bool {
+        let mut history = self.history.lock();
+        history.consecutive_failures = 0;
+        history.consecutive_successes = 0;
+        history.quarantine.take().is_some()
+    }
+}
+
+// ============================================================================
+// Background monitor
+// ============================================================================
+
+/// A loaded plugin together with its health record.
+#[derive(Clone)]
+pub struct MonitoredPlugin {
+    /// The plugin to check.
+    pub plugin: Arc<Mutex<LoadedPlugin>>,
+    /// Where results are recorded.
+    pub health: Arc<PluginHealth>,
+}
+
+/// The set of plugins a [`HealthMonitor`] checks, keyed by plugin ID.
+///
+/// The registry keeps it up to date as plugins are loaded, reloaded and
+/// unloaded, so a running monitor always sees the current set.
+pub type MonitoredPlugins = Arc<RwLock<IndexMap<String, MonitoredPlugin>>>;
+
+/// A recorded check, as delivered by a [`HealthMonitorHandle`].
+#[derive(Debug, Clone)]
+pub struct HealthEvent {
+    /// The check result.
+    pub report: HealthReport,
+    /// The quarantine change it caused, if any.
+    pub transition: Option<HealthTransition>,
+}
+
+/// Check one plugin and record the result.
+///
+/// Returns `None` without checking if the plugin stays locked (busy
+/// executing a filter) for longer than [`HealthPolicy::busy_timeout`].
+#[must_use]
+pub fn check_plugin(monitored: &MonitoredPlugin, policy: &HealthPolicy) -> Option<HealthEvent> {
+    let mut plugin = monitored.plugin.try_lock_for(policy.busy_timeout)?;
+    let started = Instant::now();
+    let report = plugin.health_check().with_latency(started.elapsed());
+    drop(plugin);
+    let transition = monitored.health.record(&report, policy);
+    match transition {
+        Some(HealthTransition::Quarantined) => log::warn!(
+            "Plugin '{}' quarantined: {}",
+            report.plugin_id,
+            monitored.health.quarantine_reason().unwrap_or_default()
+        ),
+        Some(HealthTransition::Released) => {
+            log::info!("Plugin '{}' recovered; quarantine lifted", report.plugin_id);
+        }
+        None => {}
+    }
+    Some(HealthEvent { report, transition })
+}
+
+/// Periodically health-checks a set of plugins.
+pub struct HealthMonitor {
+    plugins: MonitoredPlugins,
+    policy: HealthPolicy,
+}
+
+impl HealthMonitor {
+    /// Create a monitor over `plugins`.
+    #[must_use]
+    pub fn new(plugins: MonitoredPlugins, policy: HealthPolicy) -> Self {
+        Self { plugins, policy }
+    }
+
+    /// Run one round of checks over every plugin that is not busy.
+    pub fn poll(&self) -> Vec<HealthEvent> {
+        let targets: Vec<MonitoredPlugin> = self.plugins.read().values().cloned().collect();
+        targets
+            .iter()
+            .filter_map(|monitored| check_plugin(monitored, &self.policy))
+            .collect()
+    }
+
+    /// Run [`HealthMonitor::poll`] on a background thread every
+    /// [`HealthPolicy::interval`].
+    #[must_use]
+    pub fn spawn(self) -> HealthMonitorHandle {
+        let (sender, receiver): (Sender<HealthEvent>, Receiver<HealthEvent>) =
+            crossbeam::channel::unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let thread = std::thread::Builder::new()
+            .name("ambara-plugin-health".to_string())
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(self.policy.interval);
+                    for event in self.poll() {
+                        if sender.send(event).is_err() {
+                            return;
+                        }
+                    }
+                }
+            })
+            .ok();
+
+        HealthMonitorHandle {
+            receiver,
+            stop,
+            thread,
+        }
+    }
+}
+
+/// Handle to a background [`HealthMonitor`] thread.
+///
+/// Dropping the handle stops the thread (after its current sleep).
+pub struct HealthMonitorHandle {
+    receiver: Receiver<HealthEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl HealthMonitorHandle {
+    /// Channel on which every recorded check is delivered.
+    #[must_use]
+    pub fn receiver(&self) -> &Receiver<HealthEvent> {
+        &self.receiver
+    }
+
+    /// Return all events received so far without blocking.
+    pub fn drain(&self) -> Vec<HealthEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Whether the background thread is still running.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|t| !t.is_finished())
+    }
+}
+
+impl Drop for HealthMonitorHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
@@ -123,4 +502,87 @@ mod tests {
         assert!(!r.is_healthy());
         assert_eq!(r.reason.as_deref(), Some("panic detected"));
     }
+
+    mod history {
+        use super::*;
+
+        fn policy() -> HealthPolicy {
+            HealthPolicy {
+                failure_threshold: 2,
+                recovery_threshold: 2,
+                history_len: 3,
+                ..HealthPolicy::default()
+            }
+        }
+
+        fn fail() -> HealthReport {
+            HealthReport::unhealthy("com.test", "ErrUnknown", Instant::now())
+        }
+
+        fn pass() -> HealthReport {
+            HealthReport::healthy("com.test", Instant::now()).with_latency(Duration::from_millis(4))
+        }
+
+        #[test]
+        fn repeated_failures_quarantine() {
+            let mut history = HealthHistory::default();
+            assert_eq!(history.record(&fail(), &policy()), None);
+            assert_eq!(history.record(&pass(), &policy()), None);
+            assert_eq!(history.record(&fail(), &policy()), None);
+            assert_eq!(history.record(&fail(), &policy()), Some(HealthTransition::Quarantined));
+            assert!(history.quarantine.as_ref().unwrap().reason.contains("ErrUnknown"));
+            assert_eq!(history.record(&fail(), &policy()), None);
+            assert_eq!(history.total_failures, 4);
+            assert_eq!(history.total_checks, 5);
+            assert_eq!(history.recent.len(), 3);
+        }
+
+        #[test]
+        fn consecutive_successes_release() {
+            let mut history = HealthHistory::default();
+            history.record(&fail(), &policy());
+            history.record(&fail(), &policy());
+            assert_eq!(history.record(&pass(), &policy()), None);
+            assert!(history.is_quarantined());
+            assert_eq!(history.record(&pass(), &policy()), Some(HealthTransition::Released));
+            assert!(!history.is_quarantined());
+        }
+
+        #[test]
+        fn zero_recovery_threshold_requires_release() {
+            let policy = HealthPolicy {
+                recovery_threshold: 0,
+                ..policy()
+            };
+            let health = PluginHealth::new();
+            health.record(&fail(), &policy);
+            health.record(&fail(), &policy);
+            for _ in 0..5 {
+                assert_eq!(health.record(&pass(), &policy), None);
+            }
+            assert!(health.is_quarantined());
+            assert!(health.release());
+            assert!(!health.is_quarantined());
+            assert!(!health.release());
+        }
+
+        #[test]
+        fn manual_quarantine_ignores_passing_checks() {
+            let health = PluginHealth::new();
+            health.quarantine("investigating");
+            for _ in 0..5 {
+                assert_eq!(health.record(&pass(), &policy()), None);
+            }
+            assert_eq!(health.quarantine_reason().as_deref(), Some("investigating"));
+        }
+
+        #[test]
+        fn latency_is_averaged_over_recent_checks() {
+            let mut history = HealthHistory::default();
+            history.record(&pass().with_latency(Duration::from_millis(2)), &policy());
+            history.record(&pass().with_latency(Duration::from_millis(6)), &policy());
+            assert_eq!(history.last_latency_ms, Some(6.0));
+            assert_eq!(history.average_latency_ms, Some(4.0));
+        }
+    }
 }
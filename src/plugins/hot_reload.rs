@@ -0,0 +1,777 @@
+//! # Plugin Hot Reload
+//!
+//! Support for reloading plugins while the host keeps running, intended for
+//! plugin development where the unload → rebuild → load cycle would
+//! otherwise be driven by hand.
+//!
+//! ## Design
+//!
+//! Hot reload is split into three independent pieces:
+//!
+//! 1. [`PluginWatcher`] polls the plugin directory and reports which plugin
+//!    subdirectories had their library or manifest added, modified or
+//!    removed. It can run inline ([`PluginWatcher::poll`]) or on a background
+//!    thread ([`PluginWatcher::spawn`]) that sends [`PluginChange`] events
+//!    over a channel.
+//! 2. [`crate::plugins::registry::PluginRegistry::apply_changes`] consumes
+//!    those events: it re-registers the affected filters in the
+//!    [`FilterRegistry`] and returns a [`ReloadReport`] describing how each
+//!    filter's [`NodeMetadata`] changed.
+//! 3. [`rebind_graph_nodes`] swaps the filter instances held by an existing
+//!    [`ProcessingGraph`] over to the freshly loaded plugin. Nodes whose
+//!    filter changed incompatibly (or disappeared) are left untouched and
+//!    reported so the UI can flag them.
+//!
+//! Polling (file size + modification time) is used instead of OS change
+//! notifications: the plugin directory holds a handful of files, and polling
+//! behaves identically on every platform Ambara ships on.
+//!
+//! ## Examples
+//!
+//! ```rust,ignore
+//! use ambara::plugins::hot_reload::{rebind_graph_nodes, PluginWatcher};
+//! use std::time::Duration;
+//!
+//! let watcher = PluginWatcher::new(registry.plugin_dir()).spawn(Duration::from_secs(1));
+//! for change in watcher.receiver() {
+//!     for (path, result) in registry.apply_changes(&[change], &mut filter_registry) {
+//!         match result {
+//!             Ok(report) => {
+//!                 let outcomes = rebind_graph_nodes(&mut graph, &filter_registry, &report);
+//!                 println!("{}: {outcomes:?}", path.display());
+//!             }
+//!             Err(e) => eprintln!("reload of {} failed: {e}", path.display()),
+//!         }
+//!     }
+//! }
+//! ```
+
+use crate::core::error::NodeId;
+use crate::core::node::NodeMetadata;
+use crate::core::types::PortType;
+use crate::filters::registry::FilterRegistry;
+use crate::graph::structure::ProcessingGraph;
+use crate::plugins::registry::PluginRegistry;
+
+use crossbeam::channel::{Receiver, Sender};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+/// Name of the manifest file inside each plugin directory.
+const MANIFEST_FILE: &str = "ambara-plugin.toml";
+
+// ============================================================================
+// File watching
+// ============================================================================
+
+/// Size and modification time of a single file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some(Self {
+            len: meta.len(),
+            modified: meta.modified().ok(),
+        })
+    }
+}
+
+/// Snapshot of the files that make up one plugin directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PluginFingerprint {
+    manifest: Option<FileStamp>,
+    library: Option<(PathBuf, FileStamp)>,
+}
+
+impl PluginFingerprint {
+    fn of(dir: &Path) -> Self {
+        let library = PluginRegistry::find_library_in(dir)
+            .and_then(|lib| FileStamp::of(&lib).map(|stamp| (lib, stamp)));
+        Self {
+            manifest: FileStamp::of(&dir.join(MANIFEST_FILE)),
+            library,
+        }
+    }
+
+    /// A directory only counts as a plugin once both files exist.
+    fn is_complete(&self) -> bool {
+        self.manifest.is_some() && self.library.is_some()
+    }
+}
+
+/// What happened to a plugin directory between two polls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginChangeKind {
+    /// A new directory now contains both a manifest and a library.
+    Added,
+    /// The library or the manifest of a known plugin changed on disk.
+    Modified,
+    /// The manifest or library disappeared (or the directory was removed).
+    Removed,
+}
+
+/// A change detected by [`PluginWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginChange {
+    /// The plugin subdirectory that changed.
+    pub plugin_dir: PathBuf,
+    /// The library found in that directory, if any.
+    pub library_path: Option<PathBuf>,
+    /// Kind of change.
+    pub kind: PluginChangeKind,
+}
+
+/// Polling watcher over a plugin directory.
+///
+/// The watcher takes a snapshot on construction, so files that already exist
+/// are not reported as [`PluginChangeKind::Added`] on the first poll.
+#[derive(Debug)]
+pub struct PluginWatcher {
+    plugin_dir: PathBuf,
+    known: HashMap<PathBuf, PluginFingerprint>,
+}
+
+impl PluginWatcher {
+    /// Create a watcher over `plugin_dir` and record its current state.
+    #[must_use]
+    pub fn new(plugin_dir: impl Into<PathBuf>) -> Self {
+        let plugin_dir = plugin_dir.into();
+        let known = Self::scan(&plugin_dir);
+        Self { plugin_dir, known }
+    }
+
+    /// The directory being watched.
+    #[must_use]
+    pub fn plugin_dir(&self) -> &Path {
+        &self.plugin_dir
+    }
+
+    fn scan(plugin_dir: &Path) -> HashMap<PathBuf, PluginFingerprint> {
+        let Ok(read_dir) = std::fs::read_dir(plugin_dir) else {
+            return HashMap::new();
+        };
+        read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .map(|dir| {
+                let fingerprint = PluginFingerprint::of(&dir);
+                (dir, fingerprint)
+            })
+            .filter(|(_, fingerprint)| fingerprint.is_complete())
+            .collect()
+    }
+
+    /// Compare the directory against the last snapshot and return what changed.
+    ///
+    /// Changes are reported in a stable (path-sorted) order.
+    pub fn poll(&mut self) -> Vec<PluginChange> {
+        let current = Self::scan(&self.plugin_dir);
+        let mut changes = Vec::new();
+
+        for (dir, fingerprint) in &current {
+            let kind = match self.known.get(dir) {
+                None => PluginChangeKind::Added,
+                Some(previous) if previous != fingerprint => PluginChangeKind::Modified,
+                Some(_) => continue,
+            };
+            changes.push(PluginChange {
+                plugin_dir: dir.clone(),
+                library_path: fingerprint.library.as_ref().map(|(lib, _)| lib.clone()),
+                kind,
+            });
+        }
+
+        for (dir, fingerprint) in &self.known {
+            if !current.contains_key(dir) {
+                changes.push(PluginChange {
+                    plugin_dir: dir.clone(),
+                    library_path: fingerprint.library.as_ref().map(|(lib, _)| lib.clone()),
+                    kind: PluginChangeKind::Removed,
+                });
+            }
+        }
+
+        self.known = current;
+        changes.sort_by(|a, b| a.plugin_dir.cmp(&b.plugin_dir));
+        changes
+    }
+
+    /// Move the watcher onto a background thread polling every `interval`.
+    ///
+    /// Detected changes are delivered through [`WatcherHandle::receiver`].
+    /// The thread stops when the handle is dropped.
+    #[must_use]
+    pub fn spawn(mut self, interval: Duration) -> WatcherHandle {
+        let (sender, receiver): (Sender<PluginChange>, Receiver<PluginChange>) =
+            crossbeam::channel::unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let thread = std::thread::Builder::new()
+            .name("ambara-plugin-watcher".to_string())
+            .spawn(move || {
+                while !thread_stop.load(Ordering::Relaxed) {
+                    std::thread::sleep(interval);
+                    for change in self.poll() {
+                        if sender.send(change).is_err() {
+                            return;
+                        }
+                    }
+                }
+            })
+            .ok();
+
+        WatcherHandle {
+            receiver,
+            stop,
+            thread,
+        }
+    }
+}
+
+/// Handle to a background [`PluginWatcher`] thread.
+///
+/// Dropping the handle stops the thread (after its current sleep).
+pub struct WatcherHandle {
+    receiver: Receiver<PluginChange>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatcherHandle {
+    /// Channel on which detected changes are delivered.
+    #[must_use]
+    pub fn receiver(&self) -> &Receiver<PluginChange> {
+        &self.receiver
+    }
+
+    /// Return all changes received so far without blocking.
+    pub fn drain(&self) -> Vec<PluginChange> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Whether the background thread is still running.
+    #[must_use]
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|t| !t.is_finished())
+    }
+}
+
+impl Drop for WatcherHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// ============================================================================
+// Reload reports
+// ============================================================================
+
+/// How a filter's metadata changed across a reload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum MetadataCompatibility {
+    /// Ports and parameters are identical.
+    Unchanged,
+    /// The filter is new in the reloaded plugin.
+    Added,
+    /// Existing graph nodes keep working; `changes` lists what differs.
+    Compatible {
+        /// Human-readable description of each change.
+        changes: Vec<String>,
+    },
+    /// Existing graph nodes may no longer connect or validate.
+    Incompatible {
+        /// Human-readable description of each breaking change.
+        reasons: Vec<String>,
+    },
+    /// The reloaded plugin no longer provides this filter.
+    Removed,
+}
+
+impl MetadataCompatibility {
+    /// Whether graph nodes using this filter can be rebound automatically.
+    #[must_use]
+    pub fn can_rebind(&self) -> bool {
+        matches!(
+            self,
+            MetadataCompatibility::Unchanged
+                | MetadataCompatibility::Added
+                | MetadataCompatibility::Compatible { .. }
+        )
+    }
+}
+
+/// Compare the metadata of a filter before and after a reload.
+///
+/// Removing or retyping an input, output or parameter is a breaking change,
+/// as is adding a new required input (existing nodes have nothing connected
+/// to it). Adding outputs, optional inputs or parameters, and dropping
+/// parameters, are compatible: stored values for a dropped parameter are
+/// simply ignored.
+#[must_use]
+pub fn compare_metadata(old: &NodeMetadata, new: &NodeMetadata) -> MetadataCompatibility {
+    let mut changes = Vec::new();
+    let mut reasons = Vec::new();
+
+    let retyped = |old_ty: &PortType, new_ty: &PortType| old_ty != new_ty && *new_ty != PortType::Any;
+
+    for old_port in &old.inputs {
+        match new.get_input(&old_port.name) {
+            None => reasons.push(format!("input '{}' was removed", old_port.name)),
+            Some(p) if retyped(&old_port.port_type, &p.port_type) => reasons.push(format!(
+                "input '{}' changed type from {} to {}",
+                old_port.name, old_port.port_type, p.port_type
+            )),
+            Some(p) if old_port.optional && !p.optional => {
+                reasons.push(format!("input '{}' is now required", old_port.name))
+            }
+            Some(_) => {}
+        }
+    }
+    for new_port in &new.inputs {
+        if old.get_input(&new_port.name).is_none() {
+            if new_port.optional {
+                changes.push(format!("optional input '{}' was added", new_port.name));
+            } else {
+                reasons.push(format!("required input '{}' was added", new_port.name));
+            }
+        }
+    }
+
+    for old_port in &old.outputs {
+        match new.get_output(&old_port.name) {
+            None => reasons.push(format!("output '{}' was removed", old_port.name)),
+            Some(p) if old_port.port_type != p.port_type => reasons.push(format!(
+                "output '{}' changed type from {} to {}",
+                old_port.name, old_port.port_type, p.port_type
+            )),
+            Some(_) => {}
+        }
+    }
+    for new_port in &new.outputs {
+        if old.get_output(&new_port.name).is_none() {
+            changes.push(format!("output '{}' was added", new_port.name));
+        }
+    }
+
+    for old_param in &old.parameters {
+        match new.get_parameter(&old_param.name) {
+            None => changes.push(format!("parameter '{}' was removed", old_param.name)),
+            Some(p) if retyped(&old_param.param_type, &p.param_type) => reasons.push(format!(
+                "parameter '{}' changed type from {} to {}",
+                old_param.name, old_param.param_type, p.param_type
+            )),
+            Some(_) => {}
+        }
+    }
+    for new_param in &new.parameters {
+        if old.get_parameter(&new_param.name).is_none() {
+            changes.push(format!("parameter '{}' was added", new_param.name));
+        }
+    }
+
+    if !reasons.is_empty() {
+        MetadataCompatibility::Incompatible { reasons }
+    } else if !changes.is_empty() {
+        MetadataCompatibility::Compatible { changes }
+    } else {
+        MetadataCompatibility::Unchanged
+    }
+}
+
+/// Result of reloading a single plugin.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadReport {
+    /// ID of the reloaded plugin.
+    pub plugin_id: String,
+    /// Library the plugin was reloaded from.
+    pub library_path: PathBuf,
+    /// Version before the reload (`None` if the plugin was newly loaded).
+    pub previous_version: Option<String>,
+    /// Version after the reload (`None` if the plugin was removed).
+    pub current_version: Option<String>,
+    /// Per-filter comparison, keyed by filter ID.
+    pub filters: IndexMap<String, MetadataCompatibility>,
+}
+
+impl ReloadReport {
+    /// Build a report by comparing the filter metadata before and after.
+    #[must_use]
+    pub fn from_metadata(
+        plugin_id: impl Into<String>,
+        library_path: impl Into<PathBuf>,
+        previous_version: Option<String>,
+        current_version: Option<String>,
+        old: &IndexMap<String, NodeMetadata>,
+        new: &IndexMap<String, NodeMetadata>,
+    ) -> Self {
+        let mut filters = IndexMap::new();
+        for (id, old_meta) in old {
+            let status = match new.get(id) {
+                Some(new_meta) => compare_metadata(old_meta, new_meta),
+                None => MetadataCompatibility::Removed,
+            };
+            filters.insert(id.clone(), status);
+        }
+        for id in new.keys() {
+            if !old.contains_key(id) {
+                filters.insert(id.clone(), MetadataCompatibility::Added);
+            }
+        }
+        Self {
+            plugin_id: plugin_id.into(),
+            library_path: library_path.into(),
+            previous_version,
+            current_version,
+            filters,
+        }
+    }
+
+    /// Filter IDs whose metadata changed incompatibly or that were removed.
+    pub fn breaking_filters(&self) -> impl Iterator<Item = &str> {
+        self.filters
+            .iter()
+            .filter(|(_, status)| !status.can_rebind())
+            .map(|(id, _)| id.as_str())
+    }
+}
+
+// ============================================================================
+// Graph rebinding
+// ============================================================================
+
+/// Outcome of rebinding one graph node after a reload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "outcome")]
+pub enum RebindOutcome {
+    /// The node now runs the reloaded filter.
+    Rebound {
+        /// The rebound node.
+        node_id: NodeId,
+        /// Its filter ID.
+        filter_id: String,
+    },
+    /// The filter changed incompatibly; the node keeps the old instance until
+    /// the user reviews it.
+    Incompatible {
+        /// The affected node.
+        node_id: NodeId,
+        /// Its filter ID.
+        filter_id: String,
+        /// What changed.
+        reasons: Vec<String>,
+    },
+    /// The reloaded plugin no longer provides the filter.
+    FilterMissing {
+        /// The affected node.
+        node_id: NodeId,
+        /// Its filter ID.
+        filter_id: String,
+    },
+}
+
+impl RebindOutcome {
+    /// The node this outcome refers to.
+    #[must_use]
+    pub fn node_id(&self) -> NodeId {
+        match self {
+            RebindOutcome::Rebound { node_id, .. }
+            | RebindOutcome::Incompatible { node_id, .. }
+            | RebindOutcome::FilterMissing { node_id, .. } => *node_id,
+        }
+    }
+
+    /// Whether the node needs the user's attention.
+    #[must_use]
+    pub fn needs_attention(&self) -> bool {
+        !matches!(self, RebindOutcome::Rebound { .. })
+    }
+}
+
+/// Point graph nodes that use filters from a reloaded plugin at the new code.
+///
+/// Every node whose filter ID appears in `report` gets a fresh instance from
+/// `filter_registry` when the filter is still compatible. Parameters,
+/// position, label and connections are kept as-is. Nodes whose filter became
+/// incompatible or was removed are not modified; they are returned as
+/// [`RebindOutcome::Incompatible`] / [`RebindOutcome::FilterMissing`] instead.
+pub fn rebind_graph_nodes(
+    graph: &mut ProcessingGraph,
+    filter_registry: &FilterRegistry,
+    report: &ReloadReport,
+) -> Vec<RebindOutcome> {
+    let mut outcomes = Vec::new();
+
+    for node in graph.nodes_mut() {
+        let filter_id = node.filter.metadata().id;
+        let Some(status) = report.filters.get(&filter_id) else {
+            continue;
+        };
+
+        let outcome = match status {
+            MetadataCompatibility::Incompatible { reasons } => RebindOutcome::Incompatible {
+                node_id: node.id,
+                filter_id,
+                reasons: reasons.clone(),
+            },
+            MetadataCompatibility::Removed => RebindOutcome::FilterMissing {
+                node_id: node.id,
+                filter_id,
+            },
+            _ => match filter_registry.create(&filter_id) {
+                Some(filter) => {
+                    node.filter = filter;
+                    RebindOutcome::Rebound {
+                        node_id: node.id,
+                        filter_id,
+                    }
+                }
+                None => RebindOutcome::FilterMissing {
+                    node_id: node.id,
+                    filter_id,
+                },
+            },
+        };
+        outcomes.push(outcome);
+    }
+
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::port::{ParameterDefinition, PortDefinition};
+    use crate::core::types::Value;
+
+    fn meta() -> NodeMetadata {
+        NodeMetadata::builder("demo.filter", "Demo")
+            .input(PortDefinition::input("image", PortType::Image))
+            .output(PortDefinition::output("image", PortType::Image))
+            .parameter(ParameterDefinition::new("amount", PortType::Float, Value::Float(1.0)))
+            .build()
+    }
+
+    mod compare_metadata {
+        use super::*;
+
+        #[test]
+        fn identical_metadata_is_unchanged() {
+            assert_eq!(compare_metadata(&meta(), &meta()), MetadataCompatibility::Unchanged);
+        }
+
+        #[test]
+        fn added_parameter_is_compatible() {
+            let mut new = meta();
+            new.parameters
+                .push(ParameterDefinition::new("extra", PortType::Integer, Value::Integer(0)));
+            assert!(matches!(
+                compare_metadata(&meta(), &new),
+                MetadataCompatibility::Compatible { .. }
+            ));
+        }
+
+        #[test]
+        fn removed_output_is_incompatible() {
+            let mut new = meta();
+            new.outputs.clear();
+            let MetadataCompatibility::Incompatible { reasons } = compare_metadata(&meta(), &new)
+            else {
+                panic!("expected incompatible");
+            };
+            assert!(reasons[0].contains("output 'image'"));
+        }
+
+        #[test]
+        fn retyped_parameter_is_incompatible() {
+            let mut new = meta();
+            new.parameters[0].param_type = PortType::String;
+            assert!(!compare_metadata(&meta(), &new).can_rebind());
+        }
+
+        #[test]
+        fn new_required_input_is_incompatible() {
+            let mut new = meta();
+            new.inputs.push(PortDefinition::input("mask", PortType::Image));
+            assert!(!compare_metadata(&meta(), &new).can_rebind());
+        }
+    }
+
+    mod watcher {
+        use super::*;
+
+        fn make_plugin(dir: &Path, name: &str) -> PathBuf {
+            let plugin = dir.join(name);
+            std::fs::create_dir_all(&plugin).unwrap();
+            std::fs::write(plugin.join(MANIFEST_FILE), "[plugin]\n").unwrap();
+            std::fs::write(plugin.join(format!("lib{name}.so")), b"v1").unwrap();
+            plugin
+        }
+
+        #[test]
+        fn existing_plugins_are_not_reported() {
+            let tmp = tempfile::tempdir().unwrap();
+            make_plugin(tmp.path(), "one");
+            let mut watcher = PluginWatcher::new(tmp.path());
+            assert!(watcher.poll().is_empty());
+        }
+
+        #[test]
+        fn detects_added_modified_and_removed() {
+            let tmp = tempfile::tempdir().unwrap();
+            let one = make_plugin(tmp.path(), "one");
+            let mut watcher = PluginWatcher::new(tmp.path());
+
+            let two = make_plugin(tmp.path(), "two");
+            let changes = watcher.poll();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].kind, PluginChangeKind::Added);
+            assert_eq!(changes[0].plugin_dir, two);
+
+            // Size change is detected even when mtime granularity is coarse.
+            std::fs::write(one.join("libone.so"), b"version two").unwrap();
+            let changes = watcher.poll();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].kind, PluginChangeKind::Modified);
+            assert_eq!(changes[0].library_path, Some(one.join("libone.so")));
+
+            std::fs::remove_file(two.join(MANIFEST_FILE)).unwrap();
+            let changes = watcher.poll();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].kind, PluginChangeKind::Removed);
+
+            assert!(watcher.poll().is_empty());
+        }
+
+        #[test]
+        fn background_watcher_delivers_changes() {
+            let tmp = tempfile::tempdir().unwrap();
+            let handle = PluginWatcher::new(tmp.path()).spawn(Duration::from_millis(10));
+            make_plugin(tmp.path(), "late");
+            let change = handle
+                .receiver()
+                .recv_timeout(Duration::from_secs(5))
+                .expect("watcher should report the new plugin");
+            assert_eq!(change.kind, PluginChangeKind::Added);
+        }
+    }
+
+    mod rebind {
+        use super::*;
+        use crate::core::context::{ExecutionContext, ValidationContext};
+        use crate::core::error::{ExecutionError, ValidationError};
+        use crate::core::node::FilterNode;
+        use crate::filters::registry::FilterSource;
+        use crate::graph::structure::GraphNode;
+
+        #[derive(Clone)]
+        struct Versioned(&'static str);
+
+        impl FilterNode for Versioned {
+            fn metadata(&self) -> NodeMetadata {
+                let mut m = meta();
+                m.version = self.0.to_string();
+                m
+            }
+            fn validate(&self, _ctx: &ValidationContext) -> Result<(), ValidationError> {
+                Ok(())
+            }
+            fn execute(&self, _ctx: &mut ExecutionContext) -> Result<(), ExecutionError> {
+                Ok(())
+            }
+            fn clone_box(&self) -> Box<dyn FilterNode> {
+                Box::new(self.clone())
+            }
+        }
+
+        fn registry_with(version: &'static str) -> FilterRegistry {
+            let mut registry = FilterRegistry::new();
+            let mut m = meta();
+            m.version = version.to_string();
+            registry.register_plugin_filter(
+                move || Box::new(Versioned(version)),
+                m,
+                FilterSource::Plugin {
+                    plugin_id: "com.test".to_string(),
+                    plugin_version: version.to_string(),
+                },
+            );
+            registry
+        }
+
+        fn report(status: MetadataCompatibility) -> ReloadReport {
+            let mut filters = IndexMap::new();
+            filters.insert("demo.filter".to_string(), status);
+            ReloadReport {
+                plugin_id: "com.test".to_string(),
+                library_path: PathBuf::from("libtest.so"),
+                previous_version: Some("1".to_string()),
+                current_version: Some("2".to_string()),
+                filters,
+            }
+        }
+
+        #[test]
+        fn compatible_nodes_are_rebound() {
+            let mut graph = ProcessingGraph::new();
+            let id = graph.add_node(
+                GraphNode::new(Box::new(Versioned("1"))).with_parameter("amount", Value::Float(3.0)),
+            );
+            let registry = registry_with("2");
+
+            let outcomes =
+                rebind_graph_nodes(&mut graph, &registry, &report(MetadataCompatibility::Unchanged));
+
+            assert_eq!(outcomes.len(), 1);
+            assert!(!outcomes[0].needs_attention());
+            let node = graph.get_node(id).unwrap();
+            assert_eq!(node.filter.metadata().version, "2");
+            assert_eq!(node.parameters.get("amount"), Some(&Value::Float(3.0)));
+        }
+
+        #[test]
+        fn incompatible_nodes_are_flagged_and_left_alone() {
+            let mut graph = ProcessingGraph::new();
+            let id = graph.add_node(GraphNode::new(Box::new(Versioned("1"))));
+            let registry = registry_with("2");
+
+            let outcomes = rebind_graph_nodes(
+                &mut graph,
+                &registry,
+                &report(MetadataCompatibility::Incompatible {
+                    reasons: vec!["output 'image' was removed".to_string()],
+                }),
+            );
+
+            assert!(matches!(outcomes[0], RebindOutcome::Incompatible { .. }));
+            assert_eq!(outcomes[0].node_id(), id);
+            assert_eq!(graph.get_node(id).unwrap().filter.metadata().version, "1");
+        }
+
+        #[test]
+        fn report_lists_added_and_removed_filters() {
+            let mut old = IndexMap::new();
+            old.insert("a".to_string(), meta());
+            let mut new = IndexMap::new();
+            new.insert("b".to_string(), meta());
+            let report = ReloadReport::from_metadata("p", "lib.so", None, None, &old, &new);
+            assert_eq!(report.filters["a"], MetadataCompatibility::Removed);
+            assert_eq!(report.filters["b"], MetadataCompatibility::Added);
+            assert_eq!(report.breaking_filters().collect::<Vec<_>>(), vec!["a"]);
+        }
+    }
+}
@@ -4,19 +4,24 @@
 //! locating its [`PluginVTable`], initialising the plugin instance, and
 //! providing a safe Rust wrapper around the raw FFI calls.
 //!
+//! Manifests with `kind = "process"` are started as a child process instead
+//! (see [`crate::plugins::process`]); [`LoadedPlugin`] offers the same
+//! interface for both.
+//!
 //! ## Safety Model
 //!
 //! All interaction with the loaded library happens through raw C function
 //! pointers in the vtable. Several invariants must be maintained at all times:
 //!
-//! 1. **Drop order**: The `_library` field must be dropped **last**. Because
-//!    Rust drops struct fields in declaration order, and `_library` is declared
-//!    last, this is guaranteed.  The `Drop` implementation calls `plugin_destroy`
-//!    before the implicit field drops occur, which is the correct sequence.
+//! 1. **Drop order**: The library must be unloaded **last**. `NativeLibrary`
+//!    declares `_library` as its last field, and Rust drops struct fields in
+//!    declaration order, so this is guaranteed. Its `Drop` implementation
+//!    calls `plugin_destroy` before the implicit field drops occur, which is
+//!    the correct sequence.
 //!
 //! 2. **vtable lifetime**: The vtable pointer points into the loaded library's
 //!    memory. It is valid only while `_library` is not dropped. All vtable
-//!    accesses in methods on `LoadedPlugin` happen before `_library` is dropped.
+//!    accesses in methods on `NativeLibrary` happen before `_library` is dropped.
 //!
 //! 3. **panic safety**: The host wraps all vtable calls in error-result
 //!    conversions. If the plugin panics across the FFI boundary (which is UB),
@@ -41,10 +46,14 @@
 use crate::core::error::PluginError;
 use crate::core::node::NodeMetadata;
 use crate::core::types::Value;
+use crate::plugins::config::ConfigSchema;
 use crate::plugins::api::{AbiResult, PluginHandle, PluginVTable, HOST_ABI_VERSION};
 use crate::plugins::health::HealthReport;
-use crate::plugins::manifest::PluginManifest;
+use crate::plugins::manifest::{PluginKind, PluginManifest};
+use crate::plugins::marshal;
+use crate::plugins::process::ProcessPlugin;
 use crate::plugins::sandbox::CapabilitySet;
+use crate::plugins::signing::SignatureStatus;
 
 use std::ffi::CStr;
 use std::path::{Path, PathBuf};
@@ -57,15 +66,43 @@ use std::time::Instant;
 /// a base64 image, so 4 MiB is sufficient for typical workloads.
 const DEFAULT_BUF_SIZE: usize = 4 * 1024 * 1024;
 
-/// A loaded plugin instance with its vtable and lifecycle state.
-///
-/// Fields are ordered carefully: `_library` is last so that Rust's field-drop
-/// order ensures the library is unloaded only **after** all other cleanup.
+/// A loaded plugin instance with its backend and lifecycle state.
 pub struct LoadedPlugin {
     /// Deserialized manifest (read from `ambara-plugin.toml`).
     pub manifest: PluginManifest,
-    /// Path the library was loaded from.
+    /// Path the library was loaded from (the entry file for process plugins).
     pub library_path: PathBuf,
+    /// The code behind the plugin.
+    backend: PluginBackend,
+    /// When this plugin was loaded.
+    pub loaded_at: Instant,
+    /// Granted capability set for this plugin.
+    pub capabilities: CapabilitySet,
+    /// Whether the plugin has passed its last health check.
+    pub last_healthy: bool,
+    /// Result of signature verification, set by the registry at load time.
+    pub signature: SignatureStatus,
+    /// Typed configuration entries declared by the manifest.
+    pub config_schema: ConfigSchema,
+    /// Configuration passed to `plugin_init`, after schema validation.
+    ///
+    /// May contain secrets; use [`LoadedPlugin::redacted_config`] for display.
+    pub config: serde_json::Value,
+}
+
+/// Where a plugin's filters run.
+enum PluginBackend {
+    /// A shared library called through its vtable.
+    Native(NativeLibrary),
+    /// A child process spoken to over stdio.
+    Process(Box<ProcessPlugin>),
+}
+
+/// A loaded shared library and the plugin instance it created.
+///
+/// Fields are ordered carefully: `_library` is last so that Rust's field-drop
+/// order ensures the library is unloaded only **after** all other cleanup.
+struct NativeLibrary {
     /// Opaque plugin handle (owned by the plugin; freed via `plugin_destroy`).
     handle: *mut PluginHandle,
     /// Pointer to the vtable inside the loaded library.
@@ -76,25 +113,73 @@ pub struct LoadedPlugin {
     /// struct access `vtable_ptr` before `_library` can be dropped (which only
     /// happens in `Drop::drop` after `plugin_destroy` has been called).
     vtable_ptr: *const PluginVTable,
-    /// When this plugin was loaded.
-    pub loaded_at: Instant,
-    /// Granted capability set for this plugin.
-    pub capabilities: CapabilitySet,
-    /// Whether the plugin has passed its last health check.
-    pub last_healthy: bool,
     /// The loaded shared library. **Must be the last field** (dropped last).
     _library: libloading::Library,
 }
 
-// SAFETY: LoadedPlugin is Send because:
+// SAFETY: NativeLibrary is Send because:
 // - `handle` and `vtable_ptr` are raw pointers into memory owned by `_library`.
 // - We hold `_library` exclusively (no sharing).
 // - All access is protected by `Arc<Mutex<LoadedPlugin>>` at the registry level.
-unsafe impl Send for LoadedPlugin {}
+unsafe impl Send for NativeLibrary {}
 
 // SAFETY: Same as Send reasoning. The plugin is never accessed concurrently
 // from multiple threads without Mutex protection at the call site.
-unsafe impl Sync for LoadedPlugin {}
+unsafe impl Sync for NativeLibrary {}
+
+/// A private copy of a plugin library under the system temp directory.
+///
+/// The copy is removed on drop. Unix keeps a loaded mapping alive after
+/// unlink; elsewhere removing a loaded library fails and the file is left
+/// for the OS temp cleaner.
+#[derive(Debug)]
+pub struct LibraryCopy {
+    path: PathBuf,
+}
+
+impl LibraryCopy {
+    /// Copy `library_path` into a uniquely named file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Io`] if the copy cannot be written.
+    pub fn create(library_path: &Path) -> Result<Self, PluginError> {
+        let shadow_dir = std::env::temp_dir().join("ambara-plugin-shadow");
+        std::fs::create_dir_all(&shadow_dir).map_err(|e| PluginError::Io {
+            message: format!("Cannot create shadow dir {}: {e}", shadow_dir.display()),
+        })?;
+
+        let stem = library_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("plugin");
+        let ext = library_path
+            .extension()
+            .and_then(|s| s.to_str())
+            .unwrap_or("so");
+        let path = shadow_dir.join(format!("{stem}-{}.{ext}", uuid::Uuid::new_v4().simple()));
+        std::fs::copy(library_path, &path).map_err(|e| PluginError::Io {
+            message: format!(
+                "Cannot copy {} to {}: {e}",
+                library_path.display(),
+                path.display()
+            ),
+        })?;
+        Ok(Self { path })
+    }
+
+    /// Path of the copy.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for LibraryCopy {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
 
 impl LoadedPlugin {
     /// Load a plugin from a shared library path.
@@ -112,6 +197,8 @@ impl LoadedPlugin {
     ///
     /// Returns [`PluginError`] if:
     /// - The manifest file cannot be found or parsed.
+    /// - `config` does not satisfy the manifest's `config_schema`
+    ///   ([`PluginError::InvalidConfig`]); checked before the library is opened.
     /// - The library cannot be loaded (missing file, wrong architecture).
     /// - The vtable symbol is absent or the ABI version mismatches.
     /// - `plugin_create` or `plugin_init` fails.
@@ -126,13 +213,68 @@ impl LoadedPlugin {
     /// assert!(plugin.health_check());
     /// ```
     pub fn load(library_path: &Path, config: &serde_json::Value) -> Result<Self, PluginError> {
-        // --- Step 1: Read manifest ---
-        let manifest_path = library_path
-            .parent()
-            .unwrap_or(Path::new("."))
-            .join("ambara-plugin.toml");
+        Self::load_from(library_path, library_path, config)
+    }
+
+    /// Load a plugin from a private copy of its shared library.
+    ///
+    /// The library is copied into a uniquely named file under the system
+    /// temp directory before being opened. Dynamic loaders cache libraries by
+    /// path (and on some platforms lock the file), so reopening a rebuilt
+    /// library at its original path can hand back the stale mapping. The
+    /// manifest is still read from the original directory and
+    /// [`LoadedPlugin::library_path`] still records the original path.
+    ///
+    /// Process plugins are started from their entry file directly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Io`] if the shadow copy cannot be written, and
+    /// otherwise the same errors as [`LoadedPlugin::load`].
+    pub fn load_shadowed(
+        library_path: &Path,
+        config: &serde_json::Value,
+    ) -> Result<Self, PluginError> {
+        // Process plugins re-read their entry file on every start.
+        let manifest = PluginManifest::from_path(&manifest_path_for(library_path))?;
+        if manifest.plugin.kind == PluginKind::Process {
+            return Self::load(library_path, config);
+        }
+
+        let copy = LibraryCopy::create(library_path)?;
+        Self::load_copy(library_path, &copy, config)
+    }
+
+    /// Load a plugin from `copy`, a private copy of the library at
+    /// `library_path`.
+    ///
+    /// Use this to open exactly the file whose signature was checked. The
+    /// manifest is read from the original directory.
+    ///
+    /// # Errors
+    ///
+    /// The same errors as [`LoadedPlugin::load`].
+    pub fn load_copy(
+        library_path: &Path,
+        copy: &LibraryCopy,
+        config: &serde_json::Value,
+    ) -> Result<Self, PluginError> {
+        Self::load_from(library_path, copy.path(), config)
+    }
 
-        let manifest = PluginManifest::from_path(&manifest_path)?;
+    /// Shared implementation of [`LoadedPlugin::load`] and
+    /// [`LoadedPlugin::load_shadowed`].
+    ///
+    /// `library_path` is the path the plugin is known by (its manifest lives
+    /// next to it); `open_path` is the file actually handed to the dynamic
+    /// loader.
+    fn load_from(
+        library_path: &Path,
+        open_path: &Path,
+        config: &serde_json::Value,
+    ) -> Result<Self, PluginError> {
+        // --- Step 1: Read manifest ---
+        let manifest = PluginManifest::from_path(&manifest_path_for(library_path))?;
         manifest.validate()?;
 
         // Check Ambara version compatibility
@@ -145,11 +287,53 @@ impl LoadedPlugin {
             });
         }
 
+        // --- Step 1b: Validate configuration against the manifest schema ---
+        // Done before the library is opened so a misconfigured plugin never
+        // runs any code.
+        let config_schema = ConfigSchema::from_manifest(&manifest)?;
+        let capabilities = CapabilitySet::from_manifest(&manifest.plugin.capabilities);
+
+        // Merge manifest config defaults with host-provided config
+        let mut init_config = serde_json::json!({});
+        for (k, v) in &manifest.plugin.config {
+            init_config[k] = serde_json::Value::String(v.clone());
+        }
+        if let Some(obj) = config.as_object() {
+            for (k, v) in obj {
+                init_config[k] = v.clone();
+            }
+        }
+        let mut init_config = config_schema.resolve(&manifest.plugin.id, &init_config)?;
+        let resolved_config = init_config.clone();
+        init_config["granted_capabilities"] = serde_json::json!(
+            capabilities.granted().map(|c| c.id()).collect::<Vec<_>>()
+        );
+
+        // Process plugins are started and initialised by the handshake.
+        if manifest.plugin.kind == PluginKind::Process {
+            let spec = manifest.plugin.process.clone().ok_or_else(|| {
+                PluginError::ManifestMissingField {
+                    field: "plugin.process".to_string(),
+                    path: manifest_path_for(library_path),
+                }
+            })?;
+            let process =
+                ProcessPlugin::spawn(&manifest.plugin.id, &spec, open_path, &init_config)?;
+            return Ok(Self::new(
+                manifest,
+                library_path,
+                PluginBackend::Process(Box::new(process)),
+                capabilities,
+                config_schema,
+                resolved_config,
+            ));
+        }
+
         // --- Step 2: Load the library ---
         // SAFETY: Loading a shared library is inherently unsafe — the caller
         // is responsible for ensuring the library is trusted code.
         let library = unsafe {
-            libloading::Library::new(library_path).map_err(|e| PluginError::PluginLoadFailed {
+            libloading::Library::new(open_path).map_err(|e| PluginError::PluginLoadFailed {
                 path: library_path.to_path_buf(),
                 reason: e.to_string(),
             })?
@@ -194,23 +378,7 @@ impl LoadedPlugin {
             });
         }
 
-        // --- Step 6: Derive capabilities and build init config ---
-        let capabilities = CapabilitySet::from_manifest(&manifest.plugin.capabilities);
-
-        // Merge manifest config defaults with host-provided config
-        let mut init_config = serde_json::json!({});
-        for (k, v) in &manifest.plugin.config {
-            init_config[k] = serde_json::Value::String(v.clone());
-        }
-        if let Some(obj) = config.as_object() {
-            for (k, v) in obj {
-                init_config[k] = v.clone();
-            }
-        }
-        init_config["granted_capabilities"] = serde_json::json!(
-            capabilities.granted().map(|c| c.id()).collect::<Vec<_>>()
-        );
-
+        // --- Step 6: Serialise the init config built in step 1b ---
         let config_json = serde_json::to_string(&init_config)
             .unwrap_or_else(|_| "{}".to_string());
         let config_bytes = config_json.as_bytes();
@@ -231,16 +399,55 @@ impl LoadedPlugin {
             });
         }
 
-        Ok(Self {
-            manifest,
-            library_path: library_path.to_path_buf(),
+        let native = NativeLibrary {
             handle,
             vtable_ptr,
+            _library: library,
+        };
+        Ok(Self::new(
+            manifest,
+            library_path,
+            PluginBackend::Native(native),
+            capabilities,
+            config_schema,
+            resolved_config,
+        ))
+    }
+
+    fn new(
+        manifest: PluginManifest,
+        library_path: &Path,
+        backend: PluginBackend,
+        capabilities: CapabilitySet,
+        config_schema: ConfigSchema,
+        config: serde_json::Value,
+    ) -> Self {
+        Self {
+            manifest,
+            library_path: library_path.to_path_buf(),
+            backend,
             loaded_at: Instant::now(),
             capabilities,
             last_healthy: true,
-            _library: library,
-        })
+            signature: SignatureStatus::NotChecked,
+            config_schema,
+            config,
+        }
+    }
+
+    /// How this plugin is run.
+    #[must_use]
+    pub fn kind(&self) -> PluginKind {
+        match self.backend {
+            PluginBackend::Native(_) => PluginKind::Native,
+            PluginBackend::Process(_) => PluginKind::Process,
+        }
+    }
+
+    /// The init configuration with secret entries removed.
+    #[must_use]
+    pub fn redacted_config(&self) -> serde_json::Value {
+        self.config_schema.redact(&self.config)
     }
 
     /// Return the plugin's unique ID from its manifest.
@@ -256,20 +463,10 @@ impl LoadedPlugin {
     /// A `Vec<String>` of filter identifiers (e.g., `"comfy.ksampler"`).
     #[must_use]
     pub fn filter_ids(&self) -> Vec<String> {
-        // SAFETY: vtable_ptr is valid; handle is valid and initialised.
-        let count = unsafe { ((*self.vtable_ptr).filter_count)(self.handle) };
-        (0..count)
-            .filter_map(|i| {
-                // SAFETY: i < count; handle is valid.
-                let ptr = unsafe { ((*self.vtable_ptr).filter_id_at)(self.handle, i) };
-                if ptr.is_null() {
-                    return None;
-                }
-                // SAFETY: ptr is a valid null-terminated C string.
-                let cstr = unsafe { CStr::from_ptr(ptr) };
-                cstr.to_str().ok().map(ToString::to_string)
-            })
-            .collect()
+        match &self.backend {
+            PluginBackend::Native(native) => native.filter_ids(),
+            PluginBackend::Process(process) => process.filter_ids(),
+        }
     }
 
     /// Retrieve the serialised [`NodeMetadata`] for a named filter.
@@ -283,44 +480,21 @@ impl LoadedPlugin {
     /// Returns [`PluginError::PluginExecutionError`] if the plugin fails to
     /// serialise the metadata.
     pub fn filter_metadata(&self, filter_id: &str) -> Result<NodeMetadata, PluginError> {
-        let id_cstr = std::ffi::CString::new(filter_id).map_err(|e| {
-            PluginError::PluginExecutionError {
-                plugin_id: self.id().to_string(),
-                filter_id: filter_id.to_string(),
-                message: format!("invalid filter_id: {e}"),
-            }
-        })?;
-
-        let mut buf = vec![0u8; DEFAULT_BUF_SIZE];
-        // SAFETY: All pointer arguments are valid for their respective lengths.
-        let written = unsafe {
-            ((*self.vtable_ptr).filter_metadata_json)(
-                self.handle,
-                id_cstr.as_ptr(),
-                buf.as_mut_ptr(),
-                buf.len(),
-            )
+        let result = match &self.backend {
+            PluginBackend::Native(native) => native.filter_metadata(filter_id),
+            PluginBackend::Process(process) => process
+                .filter_metadata(filter_id)
+                .cloned()
+                .ok_or_else(|| "filter was not announced in the handshake".to_string()),
         };
-
-        if written == 0 {
-            return Err(PluginError::PluginExecutionError {
-                plugin_id: self.id().to_string(),
-                filter_id: filter_id.to_string(),
-                message: "filter_metadata_json returned 0 bytes".to_string(),
-            });
-        }
-
-        let json_slice = &buf[..written];
-        serde_json::from_slice::<NodeMetadata>(json_slice).map_err(|e| {
-            PluginError::PluginExecutionError {
-                plugin_id: self.id().to_string(),
-                filter_id: filter_id.to_string(),
-                message: format!("failed to deserialise NodeMetadata JSON: {e}"),
-            }
+        result.map_err(|message| PluginError::PluginExecutionError {
+            plugin_id: self.id().to_string(),
+            filter_id: filter_id.to_string(),
+            message,
         })
     }
 
-    /// Execute a filter node via the C ABI.
+    /// Execute a filter node via the C ABI (or the process protocol).
     ///
     /// # Arguments
     ///
@@ -342,26 +516,104 @@ impl LoadedPlugin {
         inputs: &[(&str, Value)],
         params: &[(&str, Value)],
     ) -> Result<std::collections::HashMap<String, Value>, PluginError> {
+        let result = match &self.backend {
+            PluginBackend::Native(native) => native.execute(filter_id, inputs, params),
+            PluginBackend::Process(process) => process.execute(filter_id, inputs, params),
+        };
+        result.map_err(|message| PluginError::PluginExecutionError {
+            plugin_id: self.id().to_string(),
+            filter_id: filter_id.to_string(),
+            message,
+        })
+    }
+
+    /// Validate a filter's inputs and parameters.
+    ///
+    /// Returns a `Vec<String>` of validation error messages. An empty vec
+    /// means validation passed.
+    #[must_use]
+    pub fn validate_filter(
+        &self,
+        filter_id: &str,
+        inputs: &[(&str, Value)],
+        params: &[(&str, Value)],
+    ) -> Vec<String> {
+        match &self.backend {
+            PluginBackend::Native(native) => native.validate(filter_id, inputs, params),
+            PluginBackend::Process(process) => process.validate(filter_id, inputs, params),
+        }
+    }
+
+    /// Run the plugin health check and return a [`HealthReport`].
+    ///
+    /// Also updates `self.last_healthy`.
+    pub fn health_check(&mut self) -> HealthReport {
+        let now = Instant::now();
+        let result = match &self.backend {
+            PluginBackend::Native(native) => native.health_check(),
+            PluginBackend::Process(process) => process.health_check(),
+        };
+        self.last_healthy = result.is_ok();
+        match result {
+            Ok(()) => HealthReport::healthy(self.id(), now),
+            Err(reason) => HealthReport::unhealthy(self.id(), reason, now),
+        }
+    }
+}
+
+impl NativeLibrary {
+    fn filter_ids(&self) -> Vec<String> {
+        // SAFETY: vtable_ptr is valid; handle is valid and initialised.
+        let count = unsafe { ((*self.vtable_ptr).filter_count)(self.handle) };
+        (0..count)
+            .filter_map(|i| {
+                // SAFETY: i < count; handle is valid.
+                let ptr = unsafe { ((*self.vtable_ptr).filter_id_at)(self.handle, i) };
+                if ptr.is_null() {
+                    return None;
+                }
+                // SAFETY: ptr is a valid null-terminated C string.
+                let cstr = unsafe { CStr::from_ptr(ptr) };
+                cstr.to_str().ok().map(ToString::to_string)
+            })
+            .collect()
+    }
+
+    fn filter_metadata(&self, filter_id: &str) -> Result<NodeMetadata, String> {
         let id_cstr =
-            std::ffi::CString::new(filter_id).map_err(|e| PluginError::PluginExecutionError {
-                plugin_id: self.id().to_string(),
-                filter_id: filter_id.to_string(),
-                message: format!("invalid filter_id: {e}"),
-            })?;
+            std::ffi::CString::new(filter_id).map_err(|e| format!("invalid filter_id: {e}"))?;
 
-        let inputs_map: serde_json::Value = inputs
-            .iter()
-            .map(|(k, v)| (k.to_string(), serde_json::to_value(v).unwrap_or(serde_json::Value::Null)))
-            .collect::<serde_json::Map<_, _>>()
-            .into();
-        let params_map: serde_json::Value = params
-            .iter()
-            .map(|(k, v)| (k.to_string(), serde_json::to_value(v).unwrap_or(serde_json::Value::Null)))
-            .collect::<serde_json::Map<_, _>>()
-            .into();
+        let mut buf = vec![0u8; DEFAULT_BUF_SIZE];
+        // SAFETY: All pointer arguments are valid for their respective lengths.
+        let written = unsafe {
+            ((*self.vtable_ptr).filter_metadata_json)(
+                self.handle,
+                id_cstr.as_ptr(),
+                buf.as_mut_ptr(),
+                buf.len(),
+            )
+        };
 
-        let inputs_json = serde_json::to_string(&inputs_map).unwrap_or_else(|_| "{}".to_string());
-        let params_json = serde_json::to_string(&params_map).unwrap_or_else(|_| "{}".to_string());
+        if written == 0 {
+            return Err("filter_metadata_json returned 0 bytes".to_string());
+        }
+
+        let json_slice = &buf[..written];
+        serde_json::from_slice::<NodeMetadata>(json_slice)
+            .map_err(|e| format!("failed to deserialise NodeMetadata JSON: {e}"))
+    }
+
+    fn execute(
+        &self,
+        filter_id: &str,
+        inputs: &[(&str, Value)],
+        params: &[(&str, Value)],
+    ) -> Result<std::collections::HashMap<String, Value>, String> {
+        let id_cstr =
+            std::ffi::CString::new(filter_id).map_err(|e| format!("invalid filter_id: {e}"))?;
+
+        let inputs_json = marshal::map_to_json(inputs.iter().map(|(k, v)| (*k, v)));
+        let params_json = marshal::map_to_json(params.iter().map(|(k, v)| (*k, v)));
 
         let inputs_bytes = inputs_json.as_bytes();
         let params_bytes = params_json.as_bytes();
@@ -385,26 +637,17 @@ impl LoadedPlugin {
         };
 
         if result != AbiResult::Ok {
-            return Err(PluginError::PluginExecutionError {
-                plugin_id: self.id().to_string(),
-                filter_id: filter_id.to_string(),
-                message: format!("filter_execute returned {:?}", result),
-            });
+            return Err(format!("filter_execute returned {:?}", result));
         }
 
         let out_slice = &out_buf[..out_written];
-        let output_json: serde_json::Value = serde_json::from_slice(out_slice).map_err(|e| {
-            PluginError::PluginExecutionError {
-                plugin_id: self.id().to_string(),
-                filter_id: filter_id.to_string(),
-                message: format!("failed to parse output JSON: {e}"),
-            }
-        })?;
+        let output_json: serde_json::Value = serde_json::from_slice(out_slice)
+            .map_err(|e| format!("failed to parse output JSON: {e}"))?;
 
         let mut outputs = std::collections::HashMap::new();
         if let serde_json::Value::Object(map) = output_json {
             for (k, v) in map {
-                if let Ok(value) = serde_json::from_value::<Value>(v) {
+                if let Ok(value) = marshal::value_from_json(v) {
                     outputs.insert(k, value);
                 }
             }
@@ -412,12 +655,7 @@ impl LoadedPlugin {
         Ok(outputs)
     }
 
-    /// Validate a filter's inputs and parameters.
-    ///
-    /// Returns a `Vec<String>` of validation error messages. An empty vec
-    /// means validation passed.
-    #[must_use]
-    pub fn validate_filter(
+    fn validate(
         &self,
         filter_id: &str,
         inputs: &[(&str, Value)],
@@ -427,19 +665,8 @@ impl LoadedPlugin {
             return vec![format!("invalid filter_id: {filter_id}")];
         };
 
-        let inputs_map: serde_json::Value = inputs
-            .iter()
-            .map(|(k, v)| (k.to_string(), serde_json::to_value(v).unwrap_or(serde_json::Value::Null)))
-            .collect::<serde_json::Map<_, _>>()
-            .into();
-        let params_map: serde_json::Value = params
-            .iter()
-            .map(|(k, v)| (k.to_string(), serde_json::to_value(v).unwrap_or(serde_json::Value::Null)))
-            .collect::<serde_json::Map<_, _>>()
-            .into();
-
-        let inputs_json = serde_json::to_string(&inputs_map).unwrap_or_else(|_| "{}".to_string());
-        let params_json = serde_json::to_string(&params_map).unwrap_or_else(|_| "{}".to_string());
+        let inputs_json = marshal::map_to_json(inputs.iter().map(|(k, v)| (*k, v)));
+        let params_json = marshal::map_to_json(params.iter().map(|(k, v)| (*k, v)));
         let inputs_bytes = inputs_json.as_bytes();
         let params_bytes = params_json.as_bytes();
 
@@ -469,28 +696,18 @@ impl LoadedPlugin {
         serde_json::from_slice::<Vec<String>>(out_slice).unwrap_or_default()
     }
 
-    /// Run the plugin health check and return a [`HealthReport`].
-    ///
-    /// Also updates `self.last_healthy`.
-    pub fn health_check(&mut self) -> HealthReport {
-        let now = Instant::now();
+    fn health_check(&self) -> Result<(), String> {
         // SAFETY: vtable_ptr and handle are valid.
         let result = unsafe { ((*self.vtable_ptr).plugin_health_check)(self.handle) };
-        let healthy = result == AbiResult::Ok;
-        self.last_healthy = healthy;
-        if healthy {
-            HealthReport::healthy(self.id(), now)
+        if result == AbiResult::Ok {
+            Ok(())
         } else {
-            HealthReport::unhealthy(
-                self.id(),
-                format!("plugin_health_check returned {:?}", result),
-                now,
-            )
+            Err(format!("plugin_health_check returned {:?}", result))
         }
     }
 }
 
-impl Drop for LoadedPlugin {
+impl Drop for NativeLibrary {
     fn drop(&mut self) {
         if !self.handle.is_null() {
             // SAFETY: vtable_ptr is valid (library not yet dropped since we are
@@ -503,7 +720,15 @@ impl Drop for LoadedPlugin {
             self.handle = std::ptr::null_mut();
         }
         // After this function returns, Rust drops fields in declaration order:
-        // manifest, library_path, handle (null, no-op), vtable_ptr (raw ptr, no-op),
-        // loaded_at, capabilities, last_healthy, and LAST: _library (unloads .so).
+        // handle (null, no-op), vtable_ptr (raw ptr, no-op), and LAST:
+        // _library (unloads .so).
     }
 }
+
+/// Path of the manifest that belongs to `library_path`.
+pub(crate) fn manifest_path_for(library_path: &Path) -> PathBuf {
+    library_path
+        .parent()
+        .unwrap_or(Path::new("."))
+        .join("ambara-plugin.toml")
+}
@@ -28,11 +28,30 @@
 //!
 //! [plugin.filters]
 //! ids = ["my.filter_one", "my.filter_two"]
+//! overrides = ["my.filter_two"]
+//!
+//! [plugin.dependencies]
+//! "com.example.base" = "^1.2"
 //!
 //! [plugin.config]
 //! endpoint = "http://127.0.0.1:8188"
+//!
+//! [plugin.config_schema.endpoint]
+//! type = "string"
+//! description = "Server URL"
 //! ```
 //!
+//! Plugins are native shared libraries unless `kind = "process"`, in which
+//! case a `[plugin.process]` table names an executable that speaks the
+//! JSON-lines protocol of [`crate::plugins::process`].
+//!
+//! `dependencies` maps plugin IDs to SemVer requirements; the registry loads
+//! dependencies first and skips plugins whose requirements cannot be met
+//! (see [`crate::plugins::resolver`]). `overrides` lists filter IDs the
+//! plugin deliberately replaces when another plugin also provides them.
+//! `config_schema` declares typed, validated settings (see
+//! [`crate::plugins::config`]); `[plugin.config]` values act as their defaults.
+//!
 //! ## Examples
 //!
 //! ```rust
@@ -66,8 +85,12 @@
 //! ```
 
 use crate::core::error::PluginError;
+use crate::plugins::config::ConfigFieldSpec;
+use crate::plugins::process::ProcessSpec;
+use indexmap::IndexMap;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Top-level structure of `ambara-plugin.toml`.
 ///
@@ -114,6 +137,29 @@ pub struct PluginMeta {
     /// Default configuration values (key→value string map).
     #[serde(default)]
     pub config: std::collections::HashMap<String, String>,
+    /// Other plugins this plugin requires, as plugin ID → SemVer requirement.
+    #[serde(default)]
+    pub dependencies: IndexMap<String, String>,
+    /// Typed configuration entries (see [`crate::plugins::config`]).
+    #[serde(default)]
+    pub config_schema: IndexMap<String, ConfigFieldSpec>,
+    /// How the plugin is run.
+    #[serde(default)]
+    pub kind: PluginKind,
+    /// Launch settings for `kind = "process"` plugins.
+    #[serde(default)]
+    pub process: Option<ProcessSpec>,
+}
+
+/// How a plugin's code is loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginKind {
+    /// A shared library exporting the C vtable.
+    #[default]
+    Native,
+    /// An executable speaking the JSON-lines protocol over stdio.
+    Process,
 }
 
 /// Requested capability flags for a plugin.
@@ -143,6 +189,9 @@ pub struct PluginFiltersBlock {
     /// Filter identifiers the plugin will register on init.
     #[serde(default)]
     pub ids: Vec<String>,
+    /// Filter identifiers this plugin replaces if another plugin provides them.
+    #[serde(default)]
+    pub overrides: Vec<String>,
 }
 
 impl PluginManifest {
@@ -199,7 +248,6 @@ impl PluginManifest {
     /// `false` otherwise.
     #[must_use]
     pub fn is_compatible_with(&self, ambara_version: &str) -> bool {
-        use semver::{Version, VersionReq};
         let Ok(current) = Version::parse(ambara_version) else {
             return false;
         };
@@ -214,6 +262,48 @@ impl PluginManifest {
         min.matches(&current) && max.matches(&current)
     }
 
+    /// The process plugin's entry file inside `plugin_dir`, or `None` for
+    /// native plugins.
+    #[must_use]
+    pub fn process_entry(&self, plugin_dir: &Path) -> Option<PathBuf> {
+        match (self.plugin.kind, &self.plugin.process) {
+            (PluginKind::Process, Some(spec)) => Some(plugin_dir.join(&spec.entry)),
+            _ => None,
+        }
+    }
+
+    /// Parse the plugin's own version.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::ManifestParseError`] if `version` is not SemVer.
+    pub fn parsed_version(&self) -> Result<Version, PluginError> {
+        Version::parse(&self.plugin.version).map_err(|e| PluginError::ManifestParseError {
+            path: std::path::PathBuf::from("<manifest>"),
+            reason: format!("plugin.version '{}': {e}", self.plugin.version),
+        })
+    }
+
+    /// Parse `[plugin.dependencies]` into SemVer requirements.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::ManifestParseError`] naming the first
+    /// requirement that is not a valid SemVer range.
+    pub fn dependency_requirements(&self) -> Result<Vec<(String, VersionReq)>, PluginError> {
+        self.plugin
+            .dependencies
+            .iter()
+            .map(|(id, req)| {
+                let parsed = VersionReq::parse(req).map_err(|e| PluginError::ManifestParseError {
+                    path: std::path::PathBuf::from("<manifest>"),
+                    reason: format!("plugin.dependencies.\"{id}\" = '{req}': {e}"),
+                })?;
+                Ok((id.clone(), parsed))
+            })
+            .collect()
+    }
+
     /// Validate that required fields have sensible values.
     ///
     /// # Errors
@@ -240,6 +330,16 @@ impl PluginManifest {
                 path,
             });
         }
+        if self.plugin.kind == PluginKind::Process {
+            let spec = self.plugin.process.as_ref().ok_or_else(|| {
+                PluginError::ManifestMissingField {
+                    field: "plugin.process".to_string(),
+                    path: path.clone(),
+                }
+            })?;
+            spec.validate()
+                .map_err(|reason| PluginError::ManifestParseError { path, reason })?;
+        }
         Ok(())
     }
 }
@@ -309,6 +409,37 @@ ids = ["test.filter_one"]
         }
     }
 
+    mod dependencies {
+        use super::*;
+
+        #[test]
+        fn defaults_to_none() {
+            let m = PluginManifest::from_toml_str(VALID_TOML).unwrap();
+            assert!(m.plugin.dependencies.is_empty());
+            assert!(m.plugin.filters.overrides.is_empty());
+        }
+
+        #[test]
+        fn parses_requirements() {
+            let toml = format!("{VALID_TOML}\n[plugin.dependencies]\n\"com.test.base\" = \"^1.2\"\n");
+            let m = PluginManifest::from_toml_str(&toml).unwrap();
+            let reqs = m.dependency_requirements().unwrap();
+            assert_eq!(reqs[0].0, "com.test.base");
+            assert!(reqs[0].1.matches(&Version::new(1, 4, 0)));
+            assert!(!reqs[0].1.matches(&Version::new(2, 0, 0)));
+        }
+
+        #[test]
+        fn rejects_invalid_requirement() {
+            let toml = format!("{VALID_TOML}\n[plugin.dependencies]\n\"com.test.base\" = \"not a range\"\n");
+            let m = PluginManifest::from_toml_str(&toml).unwrap();
+            assert!(matches!(
+                m.dependency_requirements(),
+                Err(PluginError::ManifestParseError { .. })
+            ));
+        }
+    }
+
     mod validate {
         use super::*;
 
@@ -316,6 +447,25 @@ ids = ["test.filter_one"]
         fn valid_manifest_passes() {
             let m = PluginManifest::from_toml_str(VALID_TOML).unwrap();
             assert!(m.validate().is_ok());
+            assert_eq!(m.plugin.kind, PluginKind::Native);
+            assert!(m.process_entry(Path::new("/plugins/x")).is_none());
+        }
+
+        #[test]
+        fn process_plugin_needs_process_table() {
+            let toml = VALID_TOML.replace("[plugin.capabilities]", "kind = \"process\"\n\n[plugin.capabilities]");
+            let m = PluginManifest::from_toml_str(&toml).unwrap();
+            assert!(matches!(m.validate(), Err(PluginError::ManifestMissingField { .. })));
+
+            let m = PluginManifest::from_toml_str(&format!(
+                "{toml}\n[plugin.process]\nentry = \"run.py\"\ninterpreter = \"python3\"\n"
+            ))
+            .unwrap();
+            assert!(m.validate().is_ok());
+            assert_eq!(
+                m.process_entry(Path::new("/plugins/x")),
+                Some(PathBuf::from("/plugins/x/run.py"))
+            );
         }
     }
 }
@@ -0,0 +1,190 @@
+//! # ABI Value Marshalling
+//!
+//! Conversion between [`Value`] and the JSON exchanged through
+//! [`crate::plugins::api::PluginVTable::filter_execute`] and
+//! [`crate::plugins::api::PluginVTable::filter_validate`].
+//!
+//! `Value` already implements `Serialize`, but [`ImageValue`] skips its pixel
+//! buffer, so a loaded image would arrive on the other side as metadata only.
+//! The helpers here re-encode loaded images as base64 PNG
+//! ([`ImageDataRef::Base64`]) on the way out and decode them again on the way
+//! in. Both the host ([`crate::plugins::loader::LoadedPlugin`]) and the plugin
+//! SDK use them, so the two sides always agree on the wire format.
+
+use crate::core::types::{ImageDataRef, ImageValue, Value};
+
+use base64::Engine;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+/// Serialise a value for the ABI, embedding loaded images as base64 PNG.
+#[must_use]
+pub fn value_to_json(value: &Value) -> serde_json::Value {
+    serde_json::to_value(encode_images(value)).unwrap_or(serde_json::Value::Null)
+}
+
+/// Deserialise a value received over the ABI, decoding embedded images.
+///
+/// # Errors
+///
+/// Returns a message if the JSON is not a valid [`Value`] or an embedded
+/// image cannot be decoded.
+pub fn value_from_json(json: serde_json::Value) -> Result<Value, String> {
+    let value: Value = serde_json::from_value(json).map_err(|e| e.to_string())?;
+    decode_images(value)
+}
+
+/// Serialise named values as a JSON object (the ABI's input/param/output map).
+#[must_use]
+pub fn map_to_json<'a, I>(values: I) -> String
+where
+    I: IntoIterator<Item = (&'a str, &'a Value)>,
+{
+    let map: serde_json::Map<String, serde_json::Value> = values
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), value_to_json(v)))
+        .collect();
+    serde_json::Value::Object(map).to_string()
+}
+
+/// Parse a JSON object produced by [`map_to_json`].
+///
+/// Empty input is treated as an empty map.
+///
+/// # Errors
+///
+/// Returns a message naming the offending key if any entry fails to decode.
+pub fn map_from_json(bytes: &[u8]) -> Result<HashMap<String, Value>, String> {
+    if bytes.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let json: serde_json::Value = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+    let serde_json::Value::Object(map) = json else {
+        return Err("expected a JSON object".to_string());
+    };
+    map.into_iter()
+        .map(|(k, v)| {
+            let value = value_from_json(v).map_err(|e| format!("'{k}': {e}"))?;
+            Ok((k, value))
+        })
+        .collect()
+}
+
+fn encode_images(value: &Value) -> Value {
+    match value {
+        Value::Image(image) => Value::Image(encode_image(image)),
+        Value::Array(items) => Value::Array(items.iter().map(encode_images).collect()),
+        Value::Map(map) => Value::Map(
+            map.iter()
+                .map(|(k, v)| (k.clone(), encode_images(v)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn encode_image(image: &ImageValue) -> ImageValue {
+    let Some(data) = image.get_image() else {
+        return image.clone();
+    };
+    let mut png = Vec::new();
+    if data
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .is_err()
+    {
+        return image.clone();
+    }
+    let mut encoded = ImageValue::default();
+    encoded.metadata = image.metadata;
+    encoded.data_ref =
+        ImageDataRef::Base64(base64::engine::general_purpose::STANDARD.encode(png));
+    encoded
+}
+
+fn decode_images(value: Value) -> Result<Value, String> {
+    Ok(match value {
+        Value::Image(image) => Value::Image(decode_image(image)?),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(decode_images)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(map) => Value::Map(
+            map.into_iter()
+                .map(|(k, v)| Ok((k, decode_images(v)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other,
+    })
+}
+
+fn decode_image(image: ImageValue) -> Result<ImageValue, String> {
+    match &image.data_ref {
+        ImageDataRef::Base64(data) => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("invalid base64 image: {e}"))?;
+            let decoded = image::load_from_memory(&bytes)
+                .map_err(|e| format!("invalid embedded image: {e}"))?;
+            let mut loaded = ImageValue::new(decoded);
+            loaded.metadata.format = image.metadata.format;
+            Ok(loaded)
+        }
+        ImageDataRef::FilePath(path) if !image.is_loaded() => {
+            ImageValue::from_path(path).map_err(|e| format!("{}: {e}", path.display()))
+        }
+        _ => Ok(image),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    fn sample_image() -> Value {
+        let mut img = RgbaImage::new(2, 1);
+        img.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([0, 0, 255, 128]));
+        Value::Image(ImageValue::new(DynamicImage::ImageRgba8(img)))
+    }
+
+    #[test]
+    fn scalars_round_trip() {
+        let json = value_to_json(&Value::Float(1.5));
+        assert_eq!(value_from_json(json).unwrap(), Value::Float(1.5));
+    }
+
+    #[test]
+    fn images_round_trip_with_pixels() {
+        let json = value_to_json(&sample_image());
+        assert!(json.to_string().contains("Base64"));
+
+        let Value::Image(decoded) = value_from_json(json).unwrap() else {
+            panic!("expected image");
+        };
+        let pixels = decoded.get_image().unwrap().to_rgba8();
+        assert_eq!(pixels.get_pixel(1, 0), &Rgba([0, 0, 255, 128]));
+    }
+
+    #[test]
+    fn nested_images_are_encoded() {
+        let value = Value::Array(vec![sample_image()]);
+        let decoded = value_from_json(value_to_json(&value)).unwrap();
+        let Value::Array(items) = decoded else {
+            panic!("expected array");
+        };
+        assert!(items[0].as_image().unwrap().is_loaded());
+    }
+
+    #[test]
+    fn map_round_trip() {
+        let a = Value::Integer(3);
+        let json = map_to_json([("a", &a)]);
+        let map = map_from_json(json.as_bytes()).unwrap();
+        assert_eq!(map["a"], Value::Integer(3));
+        assert!(map_from_json(b"").unwrap().is_empty());
+        assert!(map_from_json(b"[1]").is_err());
+    }
+}
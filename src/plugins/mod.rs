@@ -31,6 +31,17 @@
 //! 1. An `ambara-plugin.toml` manifest file
 //! 2. A `.so` / `.dll` / `.dylib` library file
 //!
+//! Plugins written in other languages ship an executable instead of a
+//! library and declare `kind = "process"`; the host talks to them over a
+//! JSON-lines protocol on stdio (see `process.rs`).
+//!
+//! `PluginRegistry::load_all` loads them in dependency order and settles
+//! filter ID conflicts as described in `resolver.rs`;
+//! `PluginRegistry::plan_load_all` reports that plan without loading anything.
+//!
+//! `PluginRegistry::spawn_health_monitor` checks loaded plugins in the
+//! background and quarantines ones that keep failing (see `health.rs`).
+//!
 //! ## Quick Start (Plugin Author)
 //!
 //! See `docs/writing-a-plugin.md` for a complete step-by-step guide.
@@ -41,21 +52,41 @@
 //! to running untrusted native code. Only load plugins from sources you trust.
 //! The capability system in `sandbox.rs` provides coarse-grained permission
 //! flags, but does not constitute a security boundary.
+//!
+//! Deployments can require plugins to be signed: set
+//! [`PluginSystemConfig::signature_policy`] and populate its
+//! [`TrustStore`] with publisher keys (see `signing.rs`).
 
 #![warn(missing_docs)]
 
 pub mod api;
+pub mod config;
 pub mod error;
 pub mod health;
+pub mod hot_reload;
 pub mod loader;
 pub mod manifest;
+pub mod marshal;
+pub mod process;
 pub mod registry;
+pub mod resolver;
 pub mod sandbox;
+pub mod signing;
 
 pub use api::{AbiResult, AbiStr, PluginHandle, PluginVTable, HOST_ABI_VERSION};
+pub use config::{ConfigField, ConfigSchema, ConfigType};
 pub use error::PluginError;
-pub use health::HealthReport;
+pub use health::{
+    HealthHistory, HealthMonitor, HealthMonitorHandle, HealthPolicy, HealthReport, PluginHealth,
+};
+pub use hot_reload::{
+    rebind_graph_nodes, MetadataCompatibility, PluginChange, PluginChangeKind, PluginWatcher,
+    RebindOutcome, ReloadReport, WatcherHandle,
+};
 pub use loader::LoadedPlugin;
-pub use manifest::{PluginCapabilities, PluginManifest};
+pub use manifest::{PluginCapabilities, PluginKind, PluginManifest};
+pub use process::{ImageTransport, ProcessPlugin, ProcessSpec};
 pub use registry::{PluginFilterNode, PluginRegistry, PluginSystemConfig};
+pub use resolver::{FilterConflictPolicy, ResolutionPlan, Resolver};
 pub use sandbox::CapabilitySet;
+pub use signing::{PluginSignature, SignaturePolicy, SignatureStatus, TrustStore};
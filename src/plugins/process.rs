@@ -0,0 +1,843 @@
+//! # Subprocess Plugins
+//!
+//! Filters written in Python, shell or any other language cannot export a
+//! [`crate::plugins::api::PluginVTable`]. They can instead ship as a
+//! *process plugin*: an executable the host launches and talks to over
+//! stdin/stdout using the JSON-lines protocol described below. Once loaded, a
+//! process plugin is a [`crate::plugins::loader::LoadedPlugin`] like any
+//! other, so its filters are registered as ordinary `PluginFilterNode`s and
+//! take part in signing, capability grants and health monitoring.
+//!
+//! ## Manifest
+//!
+//! ```toml
+//! [plugin]
+//! kind = "process"
+//! # ... the usual [plugin] fields ...
+//!
+//! [plugin.process]
+//! entry = "plugin.py"          # file in the plugin directory
+//! interpreter = "python3"      # optional; otherwise `entry` is executed
+//! args = ["--quiet"]           # optional, passed after `entry`
+//! image_transport = "file"     # or "shared_memory"
+//! timeout_ms = 30000           # per request
+//!
+//! [plugin.process.env]
+//! OMP_NUM_THREADS = "1"
+//! ```
+//!
+//! `entry` must sit directly in the plugin directory: it plays the role of
+//! the shared library, so it is what `ambara-plugin.sig` signs and what the
+//! hot-reload watcher fingerprints. The process is started with the plugin
+//! directory as its working directory and `AMBARA_PLUGIN_ID` /
+//! `AMBARA_SCRATCH_DIR` in its environment. `ambara_abi_version` is not
+//! checked for process plugins; the handshake negotiates
+//! [`PROCESS_PROTOCOL_VERSION`] instead.
+//!
+//! ## Protocol
+//!
+//! Every message is one line of JSON. The host sends requests on the
+//! plugin's stdin and reads responses from its stdout; stderr is forwarded to
+//! the host log. Requests carry an `id` that the response echoes:
+//!
+//! ```text
+//! -> {"id":1,"method":"handshake","params":{"protocol_version":1,"ambara_version":"0.9.1",
+//!      "config":{...},"image_transport":"file","scratch_dir":"/tmp/ambara-process-..."}}
+//! <- {"id":1,"result":{"protocol_version":1,"filters":[<NodeMetadata>, ...]}}
+//! -> {"id":2,"method":"validate","params":{"filter_id":"x.blur","inputs":{...},"params":{...}}}
+//! <- {"id":2,"result":{"errors":[]}}
+//! -> {"id":3,"method":"execute","params":{"filter_id":"x.blur","inputs":{...},"params":{...}}}
+//! <- {"id":3,"result":{"outputs":{"image":<Value>}}}
+//! -> {"id":4,"method":"health","params":{}}
+//! <- {"id":4,"result":{"healthy":true}}
+//! -> {"id":5,"method":"shutdown","params":{}}
+//! ```
+//!
+//! A failed request is answered with `{"id":n,"error":{"message":"..."}}`.
+//! `config` is the validated plugin configuration, including
+//! `granted_capabilities`, exactly as native plugins receive it in
+//! `plugin_init`. Inputs, parameters and outputs use the serde form of
+//! [`Value`] (`{"type":"Float","data":1.5}`); filter metadata uses the serde
+//! form of [`NodeMetadata`].
+//!
+//! ## Images
+//!
+//! Pixels never travel inline. With `image_transport = "file"`, input images
+//! are written as PNG files to the scratch directory and passed as
+//! `{"ref_type":"FilePath","value":"<path>"}`. With `"shared_memory"` they
+//! are written as raw RGBA8 rows (`width * height * 4` bytes, dimensions in
+//! the image `metadata`) to a file under `/dev/shm` where available, and
+//! passed as `{"ref_type":"Temporary","value":"<path>"}`; plugins can map
+//! the file directly. Output images may use either form (or `Base64`); the
+//! host reads them back and deletes any it finds in the scratch directory.
+
+use crate::core::error::PluginError;
+use crate::core::node::NodeMetadata;
+use crate::core::types::{ImageDataRef, ImageValue, Value};
+use crate::plugins::marshal;
+
+use crossbeam::channel::{Receiver, RecvTimeoutError};
+use indexmap::IndexMap;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Version of the JSON-lines protocol spoken by this host.
+pub const PROCESS_PROTOCOL_VERSION: u32 = 1;
+
+/// Upper bound on how long a health check waits for an answer.
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a plugin gets to exit after `shutdown` before it is killed.
+const SHUTDOWN_GRACE: Duration = Duration::from_millis(500);
+
+/// How images are handed to and received from a process plugin.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageTransport {
+    /// PNG files in the scratch directory, passed as `FilePath` references.
+    #[default]
+    File,
+    /// Raw RGBA8 files in shared memory, passed as `Temporary` references.
+    SharedMemory,
+}
+
+/// The `[plugin.process]` manifest table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessSpec {
+    /// Program file, directly inside the plugin directory.
+    pub entry: String,
+    /// Interpreter used to run `entry`; when absent `entry` is executed.
+    #[serde(default)]
+    pub interpreter: Option<String>,
+    /// Extra arguments passed after `entry`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables for the process.
+    #[serde(default)]
+    pub env: IndexMap<String, String>,
+    /// How images are exchanged.
+    #[serde(default)]
+    pub image_transport: ImageTransport,
+    /// How long a request may take before it fails, in milliseconds.
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+impl ProcessSpec {
+    /// Check that `entry` names a file directly inside the plugin directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description of the problem.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut components = Path::new(&self.entry).components();
+        match (components.next(), components.next()) {
+            (Some(std::path::Component::Normal(_)), None) => Ok(()),
+            _ => Err(format!(
+                "plugin.process.entry '{}' must be a file name in the plugin directory",
+                self.entry
+            )),
+        }
+    }
+
+    /// The command that starts the plugin in `plugin_dir`.
+    #[must_use]
+    pub fn command(&self, plugin_dir: &Path) -> Command {
+        let entry = plugin_dir.join(&self.entry);
+        let mut command = match &self.interpreter {
+            Some(interpreter) => {
+                let mut command = Command::new(interpreter);
+                command.arg(&entry);
+                command
+            }
+            None => Command::new(&entry),
+        };
+        command
+            .args(&self.args)
+            .envs(&self.env)
+            .current_dir(plugin_dir);
+        command
+    }
+}
+
+#[derive(Deserialize)]
+struct Response {
+    id: u64,
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Handshake {
+    protocol_version: u32,
+    #[serde(default)]
+    filters: Vec<NodeMetadata>,
+}
+
+#[derive(Deserialize)]
+struct HealthResult {
+    healthy: bool,
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// The pipe to a running plugin process.
+struct Connection {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Receiver<String>,
+    next_id: u64,
+    /// Set once the process died or broke the protocol; every later request
+    /// fails with this reason.
+    failure: Option<String>,
+}
+
+impl Connection {
+    fn request(
+        &mut self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value, String> {
+        if let Some(failure) = &self.failure {
+            return Err(failure.clone());
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        let line = serde_json::json!({ "id": id, "method": method, "params": params });
+        if let Err(e) = writeln!(self.stdin, "{line}").and_then(|()| self.stdin.flush()) {
+            return Err(self.fail(format!("cannot write to plugin process: {e}")));
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.stdout.recv_timeout(remaining) {
+                Ok(line) => {
+                    let Ok(response) = serde_json::from_str::<Response>(&line) else {
+                        log::warn!("Ignoring non-protocol output from plugin process: {line}");
+                        continue;
+                    };
+                    // Late answers to requests that already timed out.
+                    if response.id != id {
+                        continue;
+                    }
+                    return match (response.error, response.result) {
+                        (Some(error), _) => Err(error.message),
+                        (None, result) => Ok(result.unwrap_or(serde_json::Value::Null)),
+                    };
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(format!(
+                        "no response to '{method}' within {} ms",
+                        timeout.as_millis()
+                    ));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let status = match self.child.try_wait() {
+                        Ok(Some(status)) => status.to_string(),
+                        _ => "stdout closed".to_string(),
+                    };
+                    return Err(self.fail(format!("plugin process exited ({status})")));
+                }
+            }
+        }
+    }
+
+    fn fail(&mut self, reason: String) -> String {
+        self.failure = Some(reason.clone());
+        reason
+    }
+
+    /// Why the process is unusable, if it has exited or broken the protocol.
+    fn failure(&mut self) -> Option<String> {
+        if self.failure.is_none() {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                self.failure = Some(format!("plugin process exited ({status})"));
+            }
+        }
+        self.failure.clone()
+    }
+}
+
+/// A running process plugin.
+///
+/// Requests are serialised through an internal lock, so a `ProcessPlugin`
+/// can be used through `&self` like a native plugin.
+pub struct ProcessPlugin {
+    plugin_id: String,
+    connection: Mutex<Connection>,
+    filters: IndexMap<String, NodeMetadata>,
+    transport: ImageTransport,
+    scratch_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl ProcessPlugin {
+    /// Start the plugin process and perform the handshake.
+    ///
+    /// # Arguments
+    ///
+    /// * `plugin_id` - ID from the manifest.
+    /// * `spec` - The manifest's `[plugin.process]` table.
+    /// * `entry_path` - Path of `spec.entry`; its directory is the working
+    ///   directory of the process.
+    /// * `config` - Validated init configuration sent in the handshake.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::PluginLoadFailed`] if the process cannot be
+    /// started, [`PluginError::PluginInitFailed`] if the handshake fails, and
+    /// [`PluginError::AbiVersionMismatch`] if the plugin speaks a different
+    /// protocol version.
+    pub fn spawn(
+        plugin_id: &str,
+        spec: &ProcessSpec,
+        entry_path: &Path,
+        config: &serde_json::Value,
+    ) -> Result<Self, PluginError> {
+        let load_failed = |reason: String| PluginError::PluginLoadFailed {
+            path: entry_path.to_path_buf(),
+            reason,
+        };
+        spec.validate().map_err(load_failed)?;
+        let plugin_dir = entry_path.parent().unwrap_or_else(|| Path::new("."));
+
+        let shm = Path::new("/dev/shm");
+        let scratch_root = if spec.image_transport == ImageTransport::SharedMemory && shm.is_dir() {
+            shm.to_path_buf()
+        } else {
+            std::env::temp_dir()
+        };
+        let scratch_dir =
+            scratch_root.join(format!("ambara-process-{}", uuid::Uuid::new_v4().simple()));
+        std::fs::create_dir_all(&scratch_dir).map_err(|e| PluginError::Io {
+            message: format!("Cannot create scratch dir {}: {e}", scratch_dir.display()),
+        })?;
+
+        let mut child = spec
+            .command(plugin_dir)
+            .env("AMBARA_PLUGIN_ID", plugin_id)
+            .env("AMBARA_SCRATCH_DIR", &scratch_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| {
+                let _ = std::fs::remove_dir_all(&scratch_dir);
+                load_failed(format!("cannot start plugin process: {e}"))
+            })?;
+
+        let stdin = child.stdin.take().ok_or_else(|| load_failed("no stdin pipe".to_string()))?;
+        let stdout = child.stdout.take().ok_or_else(|| load_failed("no stdout pipe".to_string()))?;
+        let stderr = child.stderr.take();
+
+        let (sender, receiver) = crossbeam::channel::unbounded();
+        let _ = std::thread::Builder::new()
+            .name(format!("ambara-plugin-{plugin_id}-stdout"))
+            .spawn(move || {
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else { break };
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        if let Some(stderr) = stderr {
+            let id = plugin_id.to_string();
+            let _ = std::thread::Builder::new()
+                .name(format!("ambara-plugin-{plugin_id}-stderr"))
+                .spawn(move || {
+                    for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                        log::info!("[{id}] {line}");
+                    }
+                });
+        }
+
+        let mut plugin = Self {
+            plugin_id: plugin_id.to_string(),
+            connection: Mutex::new(Connection {
+                child,
+                stdin,
+                stdout: receiver,
+                next_id: 1,
+                failure: None,
+            }),
+            filters: IndexMap::new(),
+            transport: spec.image_transport,
+            scratch_dir,
+            timeout: Duration::from_millis(spec.timeout_ms),
+        };
+
+        let init_failed = |message: String| PluginError::PluginInitFailed {
+            plugin_id: plugin_id.to_string(),
+            message,
+        };
+        let result = plugin
+            .request(
+                "handshake",
+                serde_json::json!({
+                    "protocol_version": PROCESS_PROTOCOL_VERSION,
+                    "ambara_version": crate::VERSION,
+                    "config": config,
+                    "image_transport": spec.image_transport,
+                    "scratch_dir": plugin.scratch_dir,
+                }),
+                plugin.timeout,
+            )
+            .map_err(|e| init_failed(format!("handshake failed: {e}")))?;
+        let handshake: Handshake = serde_json::from_value(result)
+            .map_err(|e| init_failed(format!("invalid handshake response: {e}")))?;
+        if handshake.protocol_version != PROCESS_PROTOCOL_VERSION {
+            return Err(PluginError::AbiVersionMismatch {
+                plugin_id: plugin_id.to_string(),
+                plugin_abi: handshake.protocol_version,
+                host_abi: PROCESS_PROTOCOL_VERSION,
+            });
+        }
+        plugin.filters = handshake
+            .filters
+            .into_iter()
+            .map(|metadata| (metadata.id.clone(), metadata))
+            .collect();
+        Ok(plugin)
+    }
+
+    fn request(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value, String> {
+        self.connection.lock().request(method, params, timeout)
+    }
+
+    /// IDs of the filters announced in the handshake.
+    #[must_use]
+    pub fn filter_ids(&self) -> Vec<String> {
+        self.filters.keys().cloned().collect()
+    }
+
+    /// Metadata announced in the handshake for `filter_id`.
+    #[must_use]
+    pub fn filter_metadata(&self, filter_id: &str) -> Option<&NodeMetadata> {
+        self.filters.get(filter_id)
+    }
+
+    /// Run a filter in the plugin process.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if images cannot be staged, the request fails or
+    /// times out, or an output cannot be decoded.
+    pub fn execute(
+        &self,
+        filter_id: &str,
+        inputs: &[(&str, Value)],
+        params: &[(&str, Value)],
+    ) -> Result<HashMap<String, Value>, String> {
+        let mut staged = Vec::new();
+        let request = self.encode_map(inputs, &mut staged).and_then(|inputs| {
+            let params = self.encode_map(params, &mut staged)?;
+            Ok(serde_json::json!({ "filter_id": filter_id, "inputs": inputs, "params": params }))
+        });
+        let result = request.and_then(|request| self.request("execute", request, self.timeout));
+        for path in staged {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let outputs = match result?.get_mut("outputs").map(serde_json::Value::take) {
+            Some(serde_json::Value::Object(outputs)) => outputs,
+            Some(serde_json::Value::Null) | None => serde_json::Map::new(),
+            Some(other) => return Err(format!("'outputs' must be an object, got {other}")),
+        };
+        outputs
+            .into_iter()
+            .map(|(name, json)| {
+                let value = marshal::value_from_json(json)
+                    .and_then(|value| self.decode_shared(value))
+                    .map_err(|e| format!("output '{name}': {e}"))?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+
+    /// Ask the plugin to validate inputs and parameters.
+    ///
+    /// Returns the error messages; a failed request is reported as one.
+    #[must_use]
+    pub fn validate(
+        &self,
+        filter_id: &str,
+        inputs: &[(&str, Value)],
+        params: &[(&str, Value)],
+    ) -> Vec<String> {
+        // Validation sees metadata only; pixels are not staged.
+        let to_json = |values: &[(&str, Value)]| -> serde_json::Map<String, serde_json::Value> {
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), serde_json::to_value(v).unwrap_or_default()))
+                .collect()
+        };
+        let request = serde_json::json!({
+            "filter_id": filter_id,
+            "inputs": to_json(inputs),
+            "params": to_json(params),
+        });
+        match self.request("validate", request, self.timeout) {
+            Ok(result) => result
+                .get("errors")
+                .and_then(|errors| serde_json::from_value::<Vec<String>>(errors.clone()).ok())
+                .unwrap_or_default(),
+            Err(e) => vec![e],
+        }
+    }
+
+    /// Check that the process is alive and reports itself healthy.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the plugin is unhealthy.
+    pub fn health_check(&self) -> Result<(), String> {
+        let mut connection = self.connection.lock();
+        if let Some(failure) = connection.failure() {
+            return Err(failure);
+        }
+        let result = connection.request(
+            "health",
+            serde_json::json!({}),
+            self.timeout.min(HEALTH_TIMEOUT),
+        )?;
+        let health: HealthResult = serde_json::from_value(result)
+            .map_err(|e| format!("invalid health response: {e}"))?;
+        if health.healthy {
+            Ok(())
+        } else {
+            Err(health.reason.unwrap_or_else(|| "plugin reported unhealthy".to_string()))
+        }
+    }
+
+    /// Serialise named values, staging loaded images for the transport.
+    fn encode_map(
+        &self,
+        values: &[(&str, Value)],
+        staged: &mut Vec<PathBuf>,
+    ) -> Result<serde_json::Value, String> {
+        let map = values
+            .iter()
+            .map(|(name, value)| {
+                let value = self.stage_images(value, staged)?;
+                let json = serde_json::to_value(&value).map_err(|e| e.to_string())?;
+                Ok((name.to_string(), json))
+            })
+            .collect::<Result<serde_json::Map<_, _>, String>>()?;
+        Ok(serde_json::Value::Object(map))
+    }
+
+    fn stage_images(&self, value: &Value, staged: &mut Vec<PathBuf>) -> Result<Value, String> {
+        Ok(match value {
+            Value::Image(image) => Value::Image(self.stage_image(image, staged)?),
+            Value::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.stage_images(item, staged))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Map(map) => Value::Map(
+                map.iter()
+                    .map(|(k, v)| Ok((k.clone(), self.stage_images(v, staged)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    fn stage_image(&self, image: &ImageValue, staged: &mut Vec<PathBuf>) -> Result<ImageValue, String> {
+        // Images that were never loaded are passed by reference as they are.
+        let Some(data) = image.get_image() else {
+            return Ok(image.clone());
+        };
+        let name = uuid::Uuid::new_v4().simple().to_string();
+        let mut out = ImageValue::default();
+        out.metadata = image.metadata;
+
+        match self.transport {
+            ImageTransport::File => {
+                let path = self.scratch_dir.join(format!("{name}.png"));
+                data.save_with_format(&path, image::ImageFormat::Png)
+                    .map_err(|e| format!("cannot stage image {}: {e}", path.display()))?;
+                out.data_ref = ImageDataRef::FilePath(path.clone());
+                staged.push(path);
+            }
+            ImageTransport::SharedMemory => {
+                let rgba = data.to_rgba8();
+                let path = self.scratch_dir.join(format!("{name}.rgba"));
+                std::fs::write(&path, rgba.as_raw())
+                    .map_err(|e| format!("cannot stage image {}: {e}", path.display()))?;
+                out.metadata.width = rgba.width();
+                out.metadata.height = rgba.height();
+                out.metadata.has_alpha = true;
+                out.data_ref = ImageDataRef::Temporary(path.display().to_string());
+                staged.push(path);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Load images the plugin returned as shared-memory references.
+    fn decode_shared(&self, value: Value) -> Result<Value, String> {
+        Ok(match value {
+            Value::Image(image) => Value::Image(self.decode_shared_image(image)?),
+            Value::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(|item| self.decode_shared(item))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Map(map) => Value::Map(
+                map.into_iter()
+                    .map(|(k, v)| Ok((k, self.decode_shared(v)?)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            other => other,
+        })
+    }
+
+    fn decode_shared_image(&self, image: ImageValue) -> Result<ImageValue, String> {
+        let decoded = match &image.data_ref {
+            ImageDataRef::Temporary(path) => {
+                let path = PathBuf::from(path);
+                let bytes = std::fs::read(&path)
+                    .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+                self.discard_output(&path);
+                let (width, height) = (image.metadata.width, image.metadata.height);
+                let rgba = image::RgbaImage::from_raw(width, height, bytes).ok_or_else(|| {
+                    format!("{} is not a {width}x{height} RGBA8 buffer", path.display())
+                })?;
+                ImageValue::new(image::DynamicImage::ImageRgba8(rgba))
+            }
+            ImageDataRef::FilePath(path) => {
+                self.discard_output(path);
+                image
+            }
+            _ => image,
+        };
+        Ok(decoded)
+    }
+
+    /// Delete an output file once read, if the plugin wrote it to scratch.
+    fn discard_output(&self, path: &Path) {
+        if path.starts_with(&self.scratch_dir) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl Drop for ProcessPlugin {
+    fn drop(&mut self) {
+        let connection = self.connection.get_mut();
+        let line = serde_json::json!({ "id": 0, "method": "shutdown", "params": {} });
+        let _ = writeln!(connection.stdin, "{line}").and_then(|()| connection.stdin.flush());
+
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while Instant::now() < deadline {
+            if matches!(connection.child.try_wait(), Ok(Some(_))) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if matches!(connection.child.try_wait(), Ok(None)) {
+            log::warn!("Plugin process '{}' did not exit; killing it", self.plugin_id);
+            let _ = connection.child.kill();
+            let _ = connection.child.wait();
+        }
+        let _ = std::fs::remove_dir_all(&self.scratch_dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(entry: &str) -> ProcessSpec {
+        ProcessSpec {
+            entry: entry.to_string(),
+            interpreter: None,
+            args: Vec::new(),
+            env: IndexMap::new(),
+            image_transport: ImageTransport::File,
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+
+    #[test]
+    fn entry_must_be_in_plugin_dir() {
+        assert!(spec("plugin.py").validate().is_ok());
+        assert!(spec("bin/plugin").validate().is_err());
+        assert!(spec("../plugin").validate().is_err());
+        assert!(spec("/usr/bin/plugin").validate().is_err());
+        assert!(spec("").validate().is_err());
+    }
+
+    #[test]
+    fn spec_defaults() {
+        let spec: ProcessSpec = toml::from_str("entry = \"run.sh\"").unwrap();
+        assert_eq!(spec.image_transport, ImageTransport::File);
+        assert_eq!(spec.timeout_ms, 30_000);
+        let shm: ProcessSpec =
+            toml::from_str("entry = \"run.sh\"\nimage_transport = \"shared_memory\"").unwrap();
+        assert_eq!(shm.image_transport, ImageTransport::SharedMemory);
+    }
+
+    mod example_plugin {
+        use crate::core::types::{ImageValue, Value};
+        use crate::filters::registry::{FilterRegistry, FilterSource};
+        use crate::plugins::loader::LoadedPlugin;
+        use crate::plugins::manifest::PluginKind;
+        use crate::core::error::PluginError;
+        use crate::plugins::registry::{PluginRegistry, PluginSystemConfig};
+        use crate::plugins::resolver::FilterConflictPolicy;
+        use image::{DynamicImage, Rgba, RgbaImage};
+        use std::path::PathBuf;
+
+        fn entry() -> PathBuf {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("plugins/process_example/plugin.py")
+        }
+
+        /// The example is written in Python; skip where it is not installed.
+        fn load() -> Option<LoadedPlugin> {
+            let python = std::process::Command::new("python3").arg("--version").output();
+            if !python.is_ok_and(|out| out.status.success()) {
+                eprintln!("python3 not available; skipping");
+                return None;
+            }
+            Some(LoadedPlugin::load(&entry(), &serde_json::json!({})).unwrap())
+        }
+
+        #[test]
+        fn handshake_announces_filters() {
+            let Some(mut plugin) = load() else { return };
+            assert_eq!(plugin.kind(), PluginKind::Process);
+            assert_eq!(plugin.filter_ids(), ["process_example.invert", "process_example.scale"]);
+            let meta = plugin.filter_metadata("process_example.scale").unwrap();
+            assert_eq!(meta.parameters[0].name, "factor");
+            assert!(plugin.health_check().is_healthy());
+        }
+
+        #[test]
+        fn validates_and_executes() {
+            let Some(mut plugin) = load() else { return };
+            let errors = plugin.validate_filter(
+                "process_example.scale",
+                &[],
+                &[("factor", Value::Float(-1.0))],
+            );
+            assert_eq!(errors, ["factor must not be negative"]);
+
+            let out = plugin
+                .execute_filter(
+                    "process_example.scale",
+                    &[("value", Value::Float(2.0))],
+                    &[("factor", Value::Float(1.5))],
+                )
+                .unwrap();
+            assert_eq!(out["value"], Value::Float(3.0));
+
+            let err = plugin.execute_filter("process_example.missing", &[], &[]).unwrap_err();
+            assert!(err.to_string().contains("unknown filter"), "{err}");
+        }
+
+        #[test]
+        fn images_cross_shared_memory() {
+            let Some(mut plugin) = load() else { return };
+            let img = RgbaImage::from_pixel(2, 1, Rgba([10, 20, 30, 200]));
+            let input = Value::Image(ImageValue::new(DynamicImage::ImageRgba8(img)));
+
+            let out = plugin
+                .execute_filter("process_example.invert", &[("image", input)], &[])
+                .unwrap();
+            let image = out["image"].as_image().unwrap().get_image().unwrap().to_rgba8();
+            assert_eq!(image.dimensions(), (2, 1));
+            assert_eq!(image.get_pixel(1, 0), &Rgba([245, 235, 225, 200]));
+        }
+
+        #[test]
+        fn filters_register_as_plugin_nodes() {
+            if load().is_none() {
+                return;
+            }
+            let dir = entry().parent().unwrap().to_path_buf();
+            assert_eq!(PluginRegistry::find_library_in(&dir), Some(entry()));
+
+            let mut plugins = PluginRegistry::new(dir.parent().unwrap(), PluginSystemConfig::default());
+            let plugin_id = plugins.load_plugin(&entry()).unwrap();
+            let mut filters = FilterRegistry::new();
+            plugins
+                .register_plugin_in_filter_registry(&plugin_id, &mut filters)
+                .unwrap();
+            assert!(matches!(
+                filters.filter_source("process_example.scale"),
+                Some(FilterSource::Plugin { plugin_id, .. }) if plugin_id == "com.ambara.process_example"
+            ));
+            assert!(plugins.health_check_all()[0].is_healthy());
+        }
+
+        #[test]
+        fn rejected_conflicts_register_nothing() {
+            if load().is_none() {
+                return;
+            }
+            let dir = entry().parent().unwrap().to_path_buf();
+            let config = PluginSystemConfig {
+                filter_conflicts: FilterConflictPolicy::Reject,
+                ..PluginSystemConfig::default()
+            };
+            let mut plugins = PluginRegistry::new(dir.parent().unwrap(), config);
+            let plugin_id = plugins.load_plugin(&entry()).unwrap();
+
+            // Take the plugin's second filter ID, so the first would
+            // register before the conflict is found
+            let mut filters = FilterRegistry::with_builtins();
+            let mut taken = filters.get_entry("invert").unwrap().clone();
+            taken.metadata.id = "process_example.scale".to_string();
+            filters.insert_entry("process_example.scale".to_string(), taken);
+
+            let result = plugins.register_plugin_in_filter_registry(&plugin_id, &mut filters);
+            assert!(matches!(
+                result,
+                Err(PluginError::FilterIdConflict { filter_id, .. }) if filter_id == "process_example.scale"
+            ));
+            assert!(!filters.contains("process_example.invert"));
+            assert!(filters.plugin_filters_for(&plugin_id).is_empty());
+        }
+    }
+
+    #[test]
+    fn missing_executable_fails_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = ProcessPlugin::spawn(
+            "com.test.missing",
+            &spec("missing"),
+            &dir.path().join("missing"),
+            &serde_json::json!({}),
+        );
+        assert!(matches!(result, Err(PluginError::PluginLoadFailed { .. })));
+    }
+}
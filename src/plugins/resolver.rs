@@ -0,0 +1,722 @@
+//! # Plugin Dependency Resolution
+//!
+//! Works out, before any library is opened, which discovered plugins can be
+//! loaded, in what order, and under which filter IDs.
+//!
+//! ## Dependencies
+//!
+//! A manifest may require other plugins:
+//!
+//! ```toml
+//! [plugin.dependencies]
+//! "com.example.base" = "^1.2"
+//! ```
+//!
+//! [`Resolver::resolve`] drops every candidate whose requirements are not met
+//! by an already-loaded plugin or another loadable candidate, then orders the
+//! rest so dependencies load first. Plugins that depend on each other in a
+//! cycle are skipped.
+//!
+//! ## Filter ID Conflicts
+//!
+//! Two plugins declaring the same filter ID in `[plugin.filters] ids` would
+//! otherwise overwrite each other in the [`crate::filters::registry::FilterRegistry`].
+//! The first claimant in load order keeps the ID. A later claimant either:
+//!
+//! - replaces it, if it lists the ID in `[plugin.filters] overrides` (an
+//!   earlier *planned* plugin then keeps its filter under a namespaced ID);
+//! - registers its filter as `<plugin-id>/<filter-id>` under
+//!   [`FilterConflictPolicy::Namespace`] (the default); or
+//! - is not loaded at all under [`FilterConflictPolicy::Reject`].
+//!
+//! ## Examples
+//!
+//! ```rust,ignore
+//! use ambara::plugins::resolver::{FilterConflictPolicy, Resolver};
+//!
+//! let plan = Resolver::new(FilterConflictPolicy::Namespace).resolve(registry.discover()?);
+//! println!("{plan}");
+//! ```
+
+use crate::core::error::PluginError;
+use crate::plugins::manifest::PluginManifest;
+
+use indexmap::IndexMap;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Owner label used for filters that do not come from a plugin.
+pub const BUILTIN_OWNER: &str = "builtin";
+
+/// What to do when a plugin declares a filter ID that is already taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterConflictPolicy {
+    /// Register the later filter as `<plugin-id>/<filter-id>`.
+    #[default]
+    Namespace,
+    /// Refuse to load the later plugin.
+    Reject,
+}
+
+/// The ID a filter is registered under when namespaced by its plugin.
+#[must_use]
+pub fn namespaced_filter_id(plugin_id: &str, filter_id: &str) -> String {
+    format!("{plugin_id}/{filter_id}")
+}
+
+// ============================================================================
+// Plan
+// ============================================================================
+
+/// A plugin the plan will load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedLoad {
+    /// Manifest plugin ID.
+    pub plugin_id: String,
+    /// Manifest plugin version.
+    pub version: Version,
+    /// Library to open.
+    pub library_path: PathBuf,
+    /// IDs of the plugins this one requires.
+    pub dependencies: Vec<String>,
+    /// Filters registered under a different ID, as declared ID → registered ID.
+    pub filter_aliases: IndexMap<String, String>,
+}
+
+/// A plugin the plan will not load.
+#[derive(Debug, Clone)]
+pub struct SkippedPlugin {
+    /// Manifest plugin ID.
+    pub plugin_id: String,
+    /// Library that would have been opened.
+    pub library_path: PathBuf,
+    /// Why the plugin is skipped.
+    pub error: PluginError,
+}
+
+/// How a filter ID conflict was settled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// `winner` listed the ID in its `overrides` and takes it over.
+    Overridden {
+        /// Plugin that now owns the ID.
+        winner: String,
+    },
+    /// The later claimant's filter is registered under a namespaced ID.
+    Namespaced {
+        /// Plugin whose filter was renamed.
+        plugin_id: String,
+        /// ID the filter is registered under instead.
+        registered_id: String,
+    },
+    /// The later claimant is not loaded.
+    Rejected {
+        /// Plugin that was skipped.
+        plugin_id: String,
+    },
+}
+
+/// A filter ID declared by more than one plugin (or a plugin and the host).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterConflict {
+    /// The contested filter ID.
+    pub filter_id: String,
+    /// Owner of the ID before the conflicting plugin was considered.
+    pub owner: String,
+    /// The plugin that also declares the ID.
+    pub claimant: String,
+    /// How the conflict was settled.
+    pub resolution: ConflictResolution,
+}
+
+/// Outcome of [`Resolver::resolve`].
+#[derive(Debug, Clone, Default)]
+pub struct ResolutionPlan {
+    /// Plugins to load, dependencies first.
+    pub load_order: Vec<PlannedLoad>,
+    /// Plugins that will not be loaded, with the reason.
+    pub skipped: Vec<SkippedPlugin>,
+    /// Filter ID conflicts found among the declared filters.
+    pub conflicts: Vec<FilterConflict>,
+}
+
+impl ResolutionPlan {
+    /// Whether every candidate will load with its declared filter IDs.
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.skipped.is_empty() && self.conflicts.is_empty()
+    }
+
+    /// The planned load for `plugin_id`, if it will be loaded.
+    #[must_use]
+    pub fn planned(&self, plugin_id: &str) -> Option<&PlannedLoad> {
+        self.load_order.iter().find(|p| p.plugin_id == plugin_id)
+    }
+}
+
+impl fmt::Display for ResolutionPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Load order:")?;
+        if self.load_order.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for (i, planned) in self.load_order.iter().enumerate() {
+            writeln!(
+                f,
+                "  {}. {} {} ({})",
+                i + 1,
+                planned.plugin_id,
+                planned.version,
+                planned.library_path.display()
+            )?;
+            for (declared, registered) in &planned.filter_aliases {
+                writeln!(f, "       {declared} -> {registered}")?;
+            }
+        }
+        if !self.conflicts.is_empty() {
+            writeln!(f, "Filter conflicts:")?;
+            for conflict in &self.conflicts {
+                let outcome = match &conflict.resolution {
+                    ConflictResolution::Overridden { winner } => format!("overridden by {winner}"),
+                    ConflictResolution::Namespaced {
+                        plugin_id,
+                        registered_id,
+                    } => format!("{plugin_id} registers it as {registered_id}"),
+                    ConflictResolution::Rejected { plugin_id } => format!("{plugin_id} rejected"),
+                };
+                writeln!(
+                    f,
+                    "  {}: {} vs {} — {outcome}",
+                    conflict.filter_id, conflict.owner, conflict.claimant
+                )?;
+            }
+        }
+        if !self.skipped.is_empty() {
+            writeln!(f, "Skipped:")?;
+            for skipped in &self.skipped {
+                writeln!(f, "  {}: {}", skipped.plugin_id, skipped.error)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Resolver
+// ============================================================================
+
+/// A plugin that is already loaded and therefore fixed in place.
+#[derive(Debug, Clone)]
+struct FixedPlugin {
+    version: Version,
+    filter_ids: Vec<String>,
+}
+
+/// A candidate whose manifest parsed cleanly.
+#[derive(Debug, Clone)]
+struct Candidate {
+    id: String,
+    version: Version,
+    library_path: PathBuf,
+    dependencies: Vec<(String, VersionReq)>,
+    filters: Vec<String>,
+    overrides: Vec<String>,
+}
+
+/// Builds a [`ResolutionPlan`] for a set of discovered plugins.
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    policy: FilterConflictPolicy,
+    fixed: IndexMap<String, FixedPlugin>,
+    reserved: IndexMap<String, String>,
+}
+
+impl Resolver {
+    /// Create a resolver with the given conflict policy.
+    #[must_use]
+    pub fn new(policy: FilterConflictPolicy) -> Self {
+        Self {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    /// Treat `plugin_id` as already loaded: it satisfies dependencies and
+    /// owns `filter_ids`.
+    #[must_use]
+    pub fn with_loaded(
+        mut self,
+        plugin_id: impl Into<String>,
+        version: Version,
+        filter_ids: Vec<String>,
+    ) -> Self {
+        self.fixed.insert(
+            plugin_id.into(),
+            FixedPlugin {
+                version,
+                filter_ids,
+            },
+        );
+        self
+    }
+
+    /// Treat `filter_ids` as owned by `owner` (e.g. [`BUILTIN_OWNER`]).
+    #[must_use]
+    pub fn with_reserved_filters<I, S>(mut self, owner: &str, filter_ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        for id in filter_ids {
+            self.reserved.insert(id.into(), owner.to_string());
+        }
+        self
+    }
+
+    /// Plan how to load `candidates` (library path and manifest, as returned
+    /// by [`crate::plugins::registry::PluginRegistry::discover`]).
+    ///
+    /// Candidates are considered in library path order so the plan does not
+    /// depend on directory iteration order.
+    #[must_use]
+    pub fn resolve(&self, mut candidates: Vec<(PathBuf, PluginManifest)>) -> ResolutionPlan {
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut skipped = Vec::new();
+        let mut pending: IndexMap<String, Candidate> = IndexMap::new();
+        for (library_path, manifest) in candidates {
+            let id = manifest.plugin.id.clone();
+            let skip = |error| SkippedPlugin {
+                plugin_id: id.clone(),
+                library_path: library_path.clone(),
+                error,
+            };
+            if self.fixed.contains_key(&id) {
+                skipped.push(skip(PluginError::PluginAlreadyLoaded { plugin_id: id.clone() }));
+                continue;
+            }
+            if pending.contains_key(&id) {
+                skipped.push(skip(PluginError::DuplicatePlugin {
+                    plugin_id: id.clone(),
+                    path: library_path.clone(),
+                }));
+                continue;
+            }
+            let parsed = manifest
+                .parsed_version()
+                .and_then(|v| Ok((v, manifest.dependency_requirements()?)));
+            match parsed {
+                Ok((version, dependencies)) => {
+                    pending.insert(
+                        id.clone(),
+                        Candidate {
+                            id,
+                            version,
+                            library_path,
+                            dependencies,
+                            filters: manifest.plugin.filters.ids.clone(),
+                            overrides: manifest.plugin.filters.overrides.clone(),
+                        },
+                    );
+                }
+                Err(error) => skipped.push(skip(error)),
+            }
+        }
+
+        // Rejecting a plugin over a filter conflict can strand its
+        // dependents, so repeat until the conflict pass rejects nothing.
+        loop {
+            self.drop_unsatisfied(&mut pending, &mut skipped);
+            let order = Self::topological_order(&mut pending, &mut skipped);
+            let (load_order, conflicts, rejected) = self.assign_filters(&order);
+            if rejected.is_empty() {
+                return ResolutionPlan {
+                    load_order,
+                    skipped,
+                    conflicts,
+                };
+            }
+            for (plugin_id, error) in rejected {
+                if let Some(c) = pending.shift_remove(&plugin_id) {
+                    skipped.push(SkippedPlugin {
+                        plugin_id,
+                        library_path: c.library_path,
+                        error,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Remove candidates with a missing or mismatched dependency, repeating
+    /// until every remaining candidate's requirements are met.
+    fn drop_unsatisfied(
+        &self,
+        pending: &mut IndexMap<String, Candidate>,
+        skipped: &mut Vec<SkippedPlugin>,
+    ) {
+        loop {
+            let failure = pending.values().find_map(|c| {
+                c.dependencies.iter().find_map(|(dep, req)| {
+                    let found = self
+                        .fixed
+                        .get(dep)
+                        .map(|f| &f.version)
+                        .or_else(|| pending.get(dep).map(|d| &d.version));
+                    let error = match found {
+                        None => PluginError::MissingDependency {
+                            plugin_id: c.id.clone(),
+                            dependency: dep.clone(),
+                            requirement: req.to_string(),
+                        },
+                        Some(v) if !req.matches(v) => PluginError::DependencyVersionMismatch {
+                            plugin_id: c.id.clone(),
+                            dependency: dep.clone(),
+                            requirement: req.to_string(),
+                            found: v.to_string(),
+                        },
+                        Some(_) => return None,
+                    };
+                    Some((c.id.clone(), error))
+                })
+            });
+            let Some((plugin_id, error)) = failure else {
+                return;
+            };
+            if let Some(c) = pending.shift_remove(&plugin_id) {
+                skipped.push(SkippedPlugin {
+                    plugin_id,
+                    library_path: c.library_path,
+                    error,
+                });
+            }
+        }
+    }
+
+    /// Order candidates dependencies-first (ties broken by plugin ID). Any
+    /// candidate left over is part of, or depends on, a cycle and is skipped.
+    fn topological_order(
+        pending: &mut IndexMap<String, Candidate>,
+        skipped: &mut Vec<SkippedPlugin>,
+    ) -> Vec<Candidate> {
+        let mut remaining: IndexMap<&str, BTreeSet<&str>> = pending
+            .values()
+            .map(|c| {
+                let deps = c
+                    .dependencies
+                    .iter()
+                    .map(|(d, _)| d.as_str())
+                    .filter(|d| pending.contains_key(*d))
+                    .collect();
+                (c.id.as_str(), deps)
+            })
+            .collect();
+
+        let mut order = Vec::new();
+        loop {
+            let ready: BTreeSet<&str> = remaining
+                .iter()
+                .filter(|(_, deps)| deps.is_empty())
+                .map(|(id, _)| *id)
+                .collect();
+            let Some(next) = ready.first().copied() else {
+                break;
+            };
+            remaining.shift_remove(next);
+            for deps in remaining.values_mut() {
+                deps.remove(next);
+            }
+            order.push(next.to_string());
+        }
+
+        let mut cycle: Vec<String> = remaining.keys().map(|id| (*id).to_string()).collect();
+        cycle.sort();
+        for plugin_id in &cycle {
+            if let Some(c) = pending.shift_remove(plugin_id) {
+                skipped.push(SkippedPlugin {
+                    plugin_id: plugin_id.clone(),
+                    library_path: c.library_path,
+                    error: PluginError::DependencyCycle {
+                        plugins: cycle.clone(),
+                    },
+                });
+            }
+        }
+
+        order
+            .iter()
+            .filter_map(|id| pending.get(id).cloned())
+            .collect()
+    }
+
+    /// Walk `order` assigning declared filter IDs to owners.
+    #[allow(clippy::type_complexity)]
+    fn assign_filters(
+        &self,
+        order: &[Candidate],
+    ) -> (Vec<PlannedLoad>, Vec<FilterConflict>, Vec<(String, PluginError)>) {
+        let mut owners: IndexMap<String, String> = self.reserved.clone();
+        for (plugin_id, fixed) in &self.fixed {
+            for filter_id in &fixed.filter_ids {
+                owners.insert(filter_id.clone(), plugin_id.clone());
+            }
+        }
+
+        let mut planned: Vec<PlannedLoad> = Vec::new();
+        let mut conflicts = Vec::new();
+        let mut rejected = Vec::new();
+
+        for candidate in order {
+            let mut aliases = IndexMap::new();
+            let mut rejection = None;
+            for filter_id in &candidate.filters {
+                let Some(owner) = owners.get(filter_id).cloned() else {
+                    owners.insert(filter_id.clone(), candidate.id.clone());
+                    continue;
+                };
+                if owner == candidate.id {
+                    continue;
+                }
+
+                let resolution = if candidate.overrides.contains(filter_id) {
+                    // Keep the displaced filter reachable if its plugin has
+                    // not been registered yet.
+                    if let Some(previous) = planned.iter_mut().find(|p| p.plugin_id == owner) {
+                        previous
+                            .filter_aliases
+                            .insert(filter_id.clone(), namespaced_filter_id(&owner, filter_id));
+                    }
+                    owners.insert(filter_id.clone(), candidate.id.clone());
+                    ConflictResolution::Overridden {
+                        winner: candidate.id.clone(),
+                    }
+                } else {
+                    match self.policy {
+                        FilterConflictPolicy::Namespace => {
+                            let registered_id = namespaced_filter_id(&candidate.id, filter_id);
+                            aliases.insert(filter_id.clone(), registered_id.clone());
+                            ConflictResolution::Namespaced {
+                                plugin_id: candidate.id.clone(),
+                                registered_id,
+                            }
+                        }
+                        FilterConflictPolicy::Reject => {
+                            rejection.get_or_insert(PluginError::FilterIdConflict {
+                                plugin_id: candidate.id.clone(),
+                                filter_id: filter_id.clone(),
+                                owner: owner.clone(),
+                            });
+                            ConflictResolution::Rejected {
+                                plugin_id: candidate.id.clone(),
+                            }
+                        }
+                    }
+                };
+                conflicts.push(FilterConflict {
+                    filter_id: filter_id.clone(),
+                    owner,
+                    claimant: candidate.id.clone(),
+                    resolution,
+                });
+            }
+
+            if let Some(error) = rejection {
+                rejected.push((candidate.id.clone(), error));
+                continue;
+            }
+            planned.push(PlannedLoad {
+                plugin_id: candidate.id.clone(),
+                version: candidate.version.clone(),
+                library_path: candidate.library_path.clone(),
+                dependencies: candidate.dependencies.iter().map(|(d, _)| d.clone()).collect(),
+                filter_aliases: aliases,
+            });
+        }
+
+        (planned, conflicts, rejected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(id: &str, version: &str, filters: &[&str], extra: &str) -> PluginManifest {
+        let ids = filters
+            .iter()
+            .map(|f| format!("\"{f}\""))
+            .collect::<Vec<_>>()
+            .join(", ");
+        PluginManifest::from_toml_str(&format!(
+            r#"
+[plugin]
+id = "{id}"
+name = "{id}"
+version = "{version}"
+description = ""
+author = ""
+license = "MIT"
+ambara_abi_version = 1
+min_ambara_version = "0.1.0"
+max_ambara_version = "99.0.0"
+
+[plugin.capabilities]
+
+[plugin.filters]
+ids = [{ids}]
+{extra}
+"#
+        ))
+        .unwrap()
+    }
+
+    fn candidate(id: &str, version: &str, filters: &[&str], extra: &str) -> (PathBuf, PluginManifest) {
+        (
+            PathBuf::from(format!("/plugins/{id}/lib.so")),
+            manifest(id, version, filters, extra),
+        )
+    }
+
+    fn order(plan: &ResolutionPlan) -> Vec<&str> {
+        plan.load_order.iter().map(|p| p.plugin_id.as_str()).collect()
+    }
+
+    mod dependencies {
+        use super::*;
+
+        #[test]
+        fn dependencies_load_first() {
+            let plan = Resolver::default().resolve(vec![
+                candidate("a.app", "1.0.0", &[], "[plugin.dependencies]\n\"z.base\" = \"^1\""),
+                candidate("z.base", "1.3.0", &[], ""),
+            ]);
+            assert!(plan.is_clean());
+            assert_eq!(order(&plan), ["z.base", "a.app"]);
+        }
+
+        #[test]
+        fn missing_dependency_skips_plugin_and_dependents() {
+            let plan = Resolver::default().resolve(vec![
+                candidate("a", "1.0.0", &[], "[plugin.dependencies]\n\"b\" = \"*\""),
+                candidate("b", "1.0.0", &[], "[plugin.dependencies]\n\"missing\" = \"*\""),
+            ]);
+            assert!(plan.load_order.is_empty());
+            assert_eq!(plan.skipped.len(), 2);
+            assert!(plan
+                .skipped
+                .iter()
+                .all(|s| matches!(s.error, PluginError::MissingDependency { .. })));
+        }
+
+        #[test]
+        fn version_mismatch_is_reported() {
+            let plan = Resolver::default().resolve(vec![
+                candidate("a", "1.0.0", &[], "[plugin.dependencies]\n\"b\" = \"^2\""),
+                candidate("b", "1.5.0", &[], ""),
+            ]);
+            assert_eq!(order(&plan), ["b"]);
+            assert!(matches!(
+                &plan.skipped[0].error,
+                PluginError::DependencyVersionMismatch { found, .. } if found == "1.5.0"
+            ));
+        }
+
+        #[test]
+        fn loaded_plugins_satisfy_dependencies() {
+            let plan = Resolver::default()
+                .with_loaded("b", Version::new(1, 0, 0), Vec::new())
+                .resolve(vec![candidate("a", "1.0.0", &[], "[plugin.dependencies]\n\"b\" = \"1\"")]);
+            assert_eq!(order(&plan), ["a"]);
+        }
+
+        #[test]
+        fn cycles_are_skipped() {
+            let plan = Resolver::default().resolve(vec![
+                candidate("a", "1.0.0", &[], "[plugin.dependencies]\n\"b\" = \"*\""),
+                candidate("b", "1.0.0", &[], "[plugin.dependencies]\n\"a\" = \"*\""),
+                candidate("c", "1.0.0", &[], ""),
+            ]);
+            assert_eq!(order(&plan), ["c"]);
+            assert!(matches!(
+                &plan.skipped[0].error,
+                PluginError::DependencyCycle { plugins } if plugins == &["a", "b"]
+            ));
+        }
+
+        #[test]
+        fn duplicate_ids_keep_first_by_path() {
+            let mut second = candidate("a", "2.0.0", &[], "");
+            second.0 = PathBuf::from("/plugins/z/lib.so");
+            let plan = Resolver::default().resolve(vec![second, candidate("a", "1.0.0", &[], "")]);
+            assert_eq!(plan.load_order[0].version, Version::new(1, 0, 0));
+            assert!(matches!(plan.skipped[0].error, PluginError::DuplicatePlugin { .. }));
+        }
+    }
+
+    mod conflicts {
+        use super::*;
+
+        #[test]
+        fn later_claimant_is_namespaced() {
+            let plan = Resolver::default().resolve(vec![
+                candidate("a", "1.0.0", &["blur"], ""),
+                candidate("b", "1.0.0", &["blur"], ""),
+            ]);
+            assert_eq!(order(&plan), ["a", "b"]);
+            assert!(plan.planned("a").unwrap().filter_aliases.is_empty());
+            assert_eq!(plan.planned("b").unwrap().filter_aliases["blur"], "b/blur");
+            assert_eq!(plan.conflicts.len(), 1);
+        }
+
+        #[test]
+        fn reject_policy_skips_claimant_and_dependents() {
+            let plan = Resolver::new(FilterConflictPolicy::Reject).resolve(vec![
+                candidate("a", "1.0.0", &["blur"], ""),
+                candidate("b", "1.0.0", &["blur"], ""),
+                candidate("c", "1.0.0", &[], "[plugin.dependencies]\n\"b\" = \"*\""),
+            ]);
+            assert_eq!(order(&plan), ["a"]);
+            assert!(matches!(plan.skipped[0].error, PluginError::FilterIdConflict { .. }));
+            assert!(matches!(plan.skipped[1].error, PluginError::MissingDependency { .. }));
+        }
+
+        #[test]
+        fn override_takes_the_id_and_namespaces_the_loser() {
+            let plan = Resolver::new(FilterConflictPolicy::Reject).resolve(vec![
+                candidate("a", "1.0.0", &["blur"], ""),
+                candidate("b", "1.0.0", &["blur"], "overrides = [\"blur\"]"),
+            ]);
+            assert_eq!(order(&plan), ["a", "b"]);
+            assert_eq!(plan.planned("a").unwrap().filter_aliases["blur"], "a/blur");
+            assert!(plan.planned("b").unwrap().filter_aliases.is_empty());
+            assert_eq!(
+                plan.conflicts[0].resolution,
+                ConflictResolution::Overridden { winner: "b".into() }
+            );
+        }
+
+        #[test]
+        fn reserved_filters_count_as_owned() {
+            let plan = Resolver::default()
+                .with_reserved_filters(BUILTIN_OWNER, ["blur"])
+                .resolve(vec![candidate("a", "1.0.0", &["blur"], "")]);
+            assert_eq!(plan.conflicts[0].owner, BUILTIN_OWNER);
+            assert_eq!(plan.planned("a").unwrap().filter_aliases["blur"], "a/blur");
+        }
+
+        #[test]
+        fn plan_display_lists_everything() {
+            let plan = Resolver::default().resolve(vec![
+                candidate("a", "1.0.0", &["blur"], ""),
+                candidate("b", "1.0.0", &["blur"], "[plugin.dependencies]\n\"x\" = \"*\""),
+            ]);
+            let text = plan.to_string();
+            assert!(text.contains("1. a 1.0.0"));
+            assert!(text.contains("Skipped:"));
+        }
+    }
+}
@@ -0,0 +1,627 @@
+//! # Plugin Signing
+//!
+//! Optional Ed25519 signatures that let a deployment restrict which plugin
+//! libraries [`crate::plugins::registry::PluginRegistry`] will `dlopen`.
+//!
+//! ## Signature File
+//!
+//! A signed plugin directory contains an `ambara-plugin.sig` file next to the
+//! manifest and library:
+//!
+//! ```toml
+//! [signature]
+//! algorithm = "ed25519"
+//! public_key = "<base64 32-byte Ed25519 public key>"
+//! library_sha256 = "<hex>"
+//! manifest_sha256 = "<hex>"
+//! signature = "<base64 64-byte signature>"
+//! ```
+//!
+//! The signature covers both SHA-256 hashes (see [`signed_message`]), so any
+//! change to the library or the manifest — including its capability
+//! requests — invalidates it.
+//!
+//! ## Policy
+//!
+//! [`SignaturePolicy`] controls what happens when verification fails:
+//! `off` skips it, `warn` logs and loads anyway (recording the failure in
+//! [`SignatureStatus`]), and `enforce` refuses to load the plugin.
+//!
+//! ## Examples
+//!
+//! ```rust,ignore
+//! use ambara::plugins::signing::{SignaturePolicy, TrustStore};
+//! use ambara::plugins::{PluginRegistry, PluginSystemConfig};
+//!
+//! let trust_store = TrustStore::from_path("/etc/ambara/trusted-keys.toml")?;
+//! let config = PluginSystemConfig {
+//!     signature_policy: SignaturePolicy::Enforce,
+//!     trust_store,
+//!     ..PluginSystemConfig::default()
+//! };
+//! let mut registry = PluginRegistry::new("/opt/ambara/plugins", config);
+//! registry.load_all();
+//! ```
+
+use crate::core::error::PluginError;
+
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Name of the signature file inside a plugin directory.
+pub const SIGNATURE_FILE: &str = "ambara-plugin.sig";
+
+/// Name of the manifest file covered by the signature.
+const MANIFEST_FILE: &str = "ambara-plugin.toml";
+
+/// Domain separator prefixed to every signed message.
+const SIGNATURE_DOMAIN: &str = "ambara-plugin-signature-v1";
+
+const B64: base64::engine::GeneralPurpose = base64::engine::general_purpose::STANDARD;
+
+// ============================================================================
+// Policy and status
+// ============================================================================
+
+/// How strictly plugin signatures are checked before loading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignaturePolicy {
+    /// Do not check signatures.
+    #[default]
+    Off,
+    /// Check signatures, log failures, and load the plugin anyway.
+    Warn,
+    /// Refuse to load plugins that are unsigned or fail verification.
+    Enforce,
+}
+
+/// Outcome of signature verification for a loaded plugin.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum SignatureStatus {
+    /// Verification was not performed (policy `off`).
+    #[default]
+    NotChecked,
+    /// The plugin has no signature file (policy `warn`).
+    Unsigned,
+    /// The signature is valid and made by a trusted key.
+    Verified {
+        /// Trust-store name of the signing key.
+        publisher: String,
+    },
+    /// Verification failed but the plugin was loaded (policy `warn`).
+    Failed {
+        /// Why verification failed.
+        reason: String,
+    },
+}
+
+// ============================================================================
+// Trust store
+// ============================================================================
+
+/// Publisher keys trusted to sign plugins, keyed by a human-readable name.
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    keys: IndexMap<String, VerifyingKey>,
+}
+
+#[derive(Deserialize)]
+struct TrustStoreFile {
+    #[serde(default, rename = "key")]
+    keys: Vec<TrustStoreEntry>,
+}
+
+#[derive(Deserialize)]
+struct TrustStoreEntry {
+    name: String,
+    public_key: String,
+}
+
+impl TrustStore {
+    /// Create an empty trust store.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust `key` under `name`, replacing any key with the same name.
+    pub fn add_key(&mut self, name: impl Into<String>, key: VerifyingKey) {
+        self.keys.insert(name.into(), key);
+    }
+
+    /// Trust a base64-encoded public key under `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::SignatureInvalid`] if the key cannot be decoded.
+    pub fn add_key_base64(&mut self, name: impl Into<String>, key: &str) -> Result<(), PluginError> {
+        let key = decode_public_key(key).map_err(|reason| PluginError::SignatureInvalid {
+            path: PathBuf::new(),
+            reason,
+        })?;
+        self.add_key(name, key);
+        Ok(())
+    }
+
+    /// Parse a trust store from TOML:
+    ///
+    /// ```toml
+    /// [[key]]
+    /// name = "acme"
+    /// public_key = "<base64>"
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::ManifestParseError`] for malformed TOML and
+    /// [`PluginError::SignatureInvalid`] for an undecodable key.
+    pub fn from_toml_str(toml_str: &str) -> Result<Self, PluginError> {
+        let file: TrustStoreFile =
+            toml::from_str(toml_str).map_err(|e| PluginError::ManifestParseError {
+                path: PathBuf::from("<trust store>"),
+                reason: e.to_string(),
+            })?;
+        let mut store = Self::new();
+        for entry in file.keys {
+            store.add_key_base64(entry.name, &entry.public_key)?;
+        }
+        Ok(store)
+    }
+
+    /// Read a trust store file (see [`TrustStore::from_toml_str`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Io`] if the file cannot be read, or any parse error.
+    pub fn from_path(path: &Path) -> Result<Self, PluginError> {
+        let content = std::fs::read_to_string(path).map_err(|e| PluginError::Io {
+            message: format!("Cannot read trust store {}: {e}", path.display()),
+        })?;
+        Self::from_toml_str(&content)
+    }
+
+    /// Name under which `key` is trusted, if any.
+    #[must_use]
+    pub fn publisher_for(&self, key: &VerifyingKey) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|(_, trusted)| *trusted == key)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Number of trusted keys.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no keys are trusted.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+// ============================================================================
+// Signature file
+// ============================================================================
+
+/// Contents of an `ambara-plugin.sig` file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginSignature {
+    /// Signature algorithm; always `"ed25519"`.
+    pub algorithm: String,
+    /// Base64-encoded public key of the signer.
+    pub public_key: String,
+    /// Hex SHA-256 of the library file.
+    pub library_sha256: String,
+    /// Hex SHA-256 of `ambara-plugin.toml`.
+    pub manifest_sha256: String,
+    /// Base64-encoded Ed25519 signature over [`signed_message`].
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignatureFile {
+    signature: PluginSignature,
+}
+
+impl PluginSignature {
+    /// Sign the library at `library_path` and the manifest next to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Io`] if either file cannot be read.
+    pub fn sign(library_path: &Path, key: &SigningKey) -> Result<Self, PluginError> {
+        let library_sha256 = sha256_file(library_path)?;
+        let manifest_sha256 = sha256_file(&sibling(library_path, MANIFEST_FILE))?;
+        let signature = key.sign(&signed_message(&library_sha256, &manifest_sha256));
+        Ok(Self {
+            algorithm: "ed25519".to_string(),
+            public_key: encode_public_key(&key.verifying_key()),
+            library_sha256,
+            manifest_sha256,
+            signature: B64.encode(signature.to_bytes()),
+        })
+    }
+
+    /// Parse a signature file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::SignatureInvalid`] if the TOML is malformed.
+    pub fn from_toml_str(toml_str: &str, path: &Path) -> Result<Self, PluginError> {
+        toml::from_str::<SignatureFile>(toml_str)
+            .map(|f| f.signature)
+            .map_err(|e| PluginError::SignatureInvalid {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+    }
+
+    /// Serialise as the contents of an `ambara-plugin.sig` file.
+    #[must_use]
+    pub fn to_toml_string(&self) -> String {
+        toml::to_string(&SignatureFile {
+            signature: self.clone(),
+        })
+        .unwrap_or_default()
+    }
+
+    /// Write `ambara-plugin.sig` next to `library_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PluginError::Io`] if the file cannot be written.
+    pub fn write_for(&self, library_path: &Path) -> Result<PathBuf, PluginError> {
+        let path = sibling(library_path, SIGNATURE_FILE);
+        std::fs::write(&path, self.to_toml_string()).map_err(|e| PluginError::Io {
+            message: format!("Cannot write {}: {e}", path.display()),
+        })?;
+        Ok(path)
+    }
+}
+
+/// Generate a new publisher key from the operating system's RNG.
+#[must_use]
+pub fn generate_signing_key() -> SigningKey {
+    SigningKey::generate(&mut rand_core::OsRng)
+}
+
+/// Base64 encoding of a secret key's 32-byte seed, as stored in key files.
+#[must_use]
+pub fn encode_signing_key(key: &SigningKey) -> String {
+    B64.encode(key.to_bytes())
+}
+
+/// Base64 encoding of a public key, as used in trust stores.
+#[must_use]
+pub fn encode_public_key(key: &VerifyingKey) -> String {
+    B64.encode(key.as_bytes())
+}
+
+/// Read a secret key file written by [`encode_signing_key`].
+///
+/// # Errors
+///
+/// Returns [`PluginError::Io`] if the file cannot be read and
+/// [`PluginError::SignatureInvalid`] if it does not hold a 32-byte seed.
+pub fn load_signing_key(path: &Path) -> Result<SigningKey, PluginError> {
+    let content = std::fs::read_to_string(path).map_err(|e| PluginError::Io {
+        message: format!("Cannot read key file {}: {e}", path.display()),
+    })?;
+    let seed: [u8; 32] = B64
+        .decode(content.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| PluginError::SignatureInvalid {
+            path: path.to_path_buf(),
+            reason: "key file must contain a base64 32-byte Ed25519 seed".to_string(),
+        })?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// The exact bytes an Ed25519 signature covers.
+#[must_use]
+pub fn signed_message(library_sha256: &str, manifest_sha256: &str) -> Vec<u8> {
+    format!("{SIGNATURE_DOMAIN}\nlibrary:{library_sha256}\nmanifest:{manifest_sha256}\n")
+        .into_bytes()
+}
+
+// ============================================================================
+// Verification
+// ============================================================================
+
+/// Verify the plugin library at `library_path` against `trust_store`.
+///
+/// Returns the trust-store name of the signing key on success.
+///
+/// # Errors
+///
+/// - [`PluginError::SignatureMissing`] — no `ambara-plugin.sig`.
+/// - [`PluginError::SignatureInvalid`] — malformed file or bad signature.
+/// - [`PluginError::IntegrityMismatch`] — library or manifest changed.
+/// - [`PluginError::UntrustedPublisher`] — valid signature, unknown key.
+pub fn verify_plugin(library_path: &Path, trust_store: &TrustStore) -> Result<String, PluginError> {
+    verify_plugin_copy(library_path, library_path, trust_store)
+}
+
+/// Verify the plugin at `library_path`, hashing `library_copy` in place of
+/// the library.
+///
+/// The signature and manifest are read next to `library_path`. Hashing the
+/// private copy that is then opened means the library cannot be swapped
+/// between verification and loading.
+///
+/// # Errors
+///
+/// The same as [`verify_plugin`].
+pub fn verify_plugin_copy(
+    library_path: &Path,
+    library_copy: &Path,
+    trust_store: &TrustStore,
+) -> Result<String, PluginError> {
+    let sig_path = sibling(library_path, SIGNATURE_FILE);
+    let invalid = |reason: String| PluginError::SignatureInvalid {
+        path: library_path.to_path_buf(),
+        reason,
+    };
+
+    if !sig_path.exists() {
+        return Err(PluginError::SignatureMissing {
+            path: library_path.to_path_buf(),
+        });
+    }
+    let content = std::fs::read_to_string(&sig_path).map_err(|e| invalid(e.to_string()))?;
+    let sig = PluginSignature::from_toml_str(&content, library_path)?;
+    if sig.algorithm != "ed25519" {
+        return Err(invalid(format!("unsupported algorithm '{}'", sig.algorithm)));
+    }
+
+    for (file, path, expected) in [
+        ("library", library_copy.to_path_buf(), &sig.library_sha256),
+        ("manifest", sibling(library_path, MANIFEST_FILE), &sig.manifest_sha256),
+    ] {
+        if !sha256_file(&path)?.eq_ignore_ascii_case(expected) {
+            return Err(PluginError::IntegrityMismatch {
+                path: library_path.to_path_buf(),
+                file: file.to_string(),
+            });
+        }
+    }
+
+    let public_key = decode_public_key(&sig.public_key).map_err(invalid)?;
+    let signature_bytes: [u8; 64] = B64
+        .decode(&sig.signature)
+        .map_err(|e| invalid(format!("signature is not base64: {e}")))?
+        .try_into()
+        .map_err(|_| invalid("signature must be 64 bytes".to_string()))?;
+    public_key
+        .verify(
+            &signed_message(&sig.library_sha256, &sig.manifest_sha256),
+            &Signature::from_bytes(&signature_bytes),
+        )
+        .map_err(|_| invalid("Ed25519 signature does not verify".to_string()))?;
+
+    trust_store
+        .publisher_for(&public_key)
+        .map(ToString::to_string)
+        .ok_or_else(|| PluginError::UntrustedPublisher {
+            path: library_path.to_path_buf(),
+            public_key: sig.public_key.clone(),
+        })
+}
+
+/// Apply `policy` to the plugin at `library_path`.
+///
+/// # Errors
+///
+/// Under [`SignaturePolicy::Enforce`], returns the verification error.
+/// Under `warn`, failures are logged and reported through the returned
+/// [`SignatureStatus`] instead.
+pub fn check_plugin(
+    library_path: &Path,
+    policy: SignaturePolicy,
+    trust_store: &TrustStore,
+) -> Result<SignatureStatus, PluginError> {
+    check_plugin_copy(library_path, library_path, policy, trust_store)
+}
+
+/// Apply `policy` to the plugin at `library_path`, hashing `library_copy`
+/// in place of the library (see [`verify_plugin_copy`]).
+///
+/// # Errors
+///
+/// The same as [`check_plugin`].
+pub fn check_plugin_copy(
+    library_path: &Path,
+    library_copy: &Path,
+    policy: SignaturePolicy,
+    trust_store: &TrustStore,
+) -> Result<SignatureStatus, PluginError> {
+    if policy == SignaturePolicy::Off {
+        return Ok(SignatureStatus::NotChecked);
+    }
+    match verify_plugin_copy(library_path, library_copy, trust_store) {
+        Ok(publisher) => Ok(SignatureStatus::Verified { publisher }),
+        Err(e) if policy == SignaturePolicy::Enforce => Err(e),
+        Err(PluginError::SignatureMissing { .. }) => {
+            log::warn!("Loading unsigned plugin {}", library_path.display());
+            Ok(SignatureStatus::Unsigned)
+        }
+        Err(e) => {
+            log::warn!("Loading plugin despite failed verification: {e}");
+            Ok(SignatureStatus::Failed {
+                reason: e.to_string(),
+            })
+        }
+    }
+}
+
+fn sibling(library_path: &Path, name: &str) -> PathBuf {
+    library_path.parent().unwrap_or(Path::new(".")).join(name)
+}
+
+fn sha256_file(path: &Path) -> Result<String, PluginError> {
+    let bytes = std::fs::read(path).map_err(|e| PluginError::Io {
+        message: format!("Cannot read {}: {e}", path.display()),
+    })?;
+    Ok(format!("{:x}", Sha256::digest(bytes)))
+}
+
+fn decode_public_key(key: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = B64
+        .decode(key.trim())
+        .map_err(|e| format!("public key is not base64: {e}"))?
+        .try_into()
+        .map_err(|_| "public key must be 32 bytes".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("invalid public key: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u8) -> SigningKey {
+        SigningKey::from_bytes(&[seed; 32])
+    }
+
+    fn plugin_dir() -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("libdemo.so");
+        std::fs::write(&lib, b"\x7fELF demo").unwrap();
+        std::fs::write(dir.path().join(MANIFEST_FILE), "[plugin]\nid = \"demo\"\n").unwrap();
+        (dir, lib)
+    }
+
+    fn trusting(seed: u8) -> TrustStore {
+        let mut store = TrustStore::new();
+        store.add_key("publisher", key(seed).verifying_key());
+        store
+    }
+
+    #[test]
+    fn signed_plugin_verifies() {
+        let (_dir, lib) = plugin_dir();
+        PluginSignature::sign(&lib, &key(1)).unwrap().write_for(&lib).unwrap();
+        assert_eq!(verify_plugin(&lib, &trusting(1)).unwrap(), "publisher");
+    }
+
+    #[test]
+    fn unsigned_plugin_is_reported() {
+        let (_dir, lib) = plugin_dir();
+        assert!(matches!(
+            verify_plugin(&lib, &trusting(1)),
+            Err(PluginError::SignatureMissing { .. })
+        ));
+    }
+
+    #[test]
+    fn modified_library_fails_integrity() {
+        let (_dir, lib) = plugin_dir();
+        PluginSignature::sign(&lib, &key(1)).unwrap().write_for(&lib).unwrap();
+        std::fs::write(&lib, b"tampered").unwrap();
+        assert!(matches!(
+            verify_plugin(&lib, &trusting(1)),
+            Err(PluginError::IntegrityMismatch { file, .. }) if file == "library"
+        ));
+    }
+
+    #[test]
+    fn copies_are_verified_in_place_of_the_library() {
+        let (dir, lib) = plugin_dir();
+        PluginSignature::sign(&lib, &key(1)).unwrap().write_for(&lib).unwrap();
+        let copy = dir.path().join("copy.so");
+        std::fs::copy(&lib, &copy).unwrap();
+        // Swapping the original after the copy was taken does not matter
+        std::fs::write(&lib, b"tampered").unwrap();
+        assert_eq!(verify_plugin_copy(&lib, &copy, &trusting(1)).unwrap(), "publisher");
+
+        std::fs::write(&copy, b"tampered").unwrap();
+        assert!(matches!(
+            verify_plugin_copy(&lib, &copy, &trusting(1)),
+            Err(PluginError::IntegrityMismatch { file, .. }) if file == "library"
+        ));
+    }
+
+    #[test]
+    fn modified_manifest_fails_integrity() {
+        let (dir, lib) = plugin_dir();
+        PluginSignature::sign(&lib, &key(1)).unwrap().write_for(&lib).unwrap();
+        std::fs::write(dir.path().join(MANIFEST_FILE), "[plugin]\nid = \"evil\"\n").unwrap();
+        assert!(matches!(
+            verify_plugin(&lib, &trusting(1)),
+            Err(PluginError::IntegrityMismatch { file, .. }) if file == "manifest"
+        ));
+    }
+
+    #[test]
+    fn untrusted_key_is_rejected() {
+        let (_dir, lib) = plugin_dir();
+        PluginSignature::sign(&lib, &key(2)).unwrap().write_for(&lib).unwrap();
+        assert!(matches!(
+            verify_plugin(&lib, &trusting(1)),
+            Err(PluginError::UntrustedPublisher { .. })
+        ));
+    }
+
+    #[test]
+    fn forged_signature_is_invalid() {
+        let (_dir, lib) = plugin_dir();
+        let mut sig = PluginSignature::sign(&lib, &key(2)).unwrap();
+        // Claim to be the trusted key without holding it.
+        sig.public_key = B64.encode(key(1).verifying_key().as_bytes());
+        sig.write_for(&lib).unwrap();
+        assert!(matches!(
+            verify_plugin(&lib, &trusting(1)),
+            Err(PluginError::SignatureInvalid { .. })
+        ));
+    }
+
+    #[test]
+    fn policy_controls_outcome() {
+        let (_dir, lib) = plugin_dir();
+        let store = trusting(1);
+        assert_eq!(
+            check_plugin(&lib, SignaturePolicy::Off, &store).unwrap(),
+            SignatureStatus::NotChecked
+        );
+        assert_eq!(
+            check_plugin(&lib, SignaturePolicy::Warn, &store).unwrap(),
+            SignatureStatus::Unsigned
+        );
+        assert!(check_plugin(&lib, SignaturePolicy::Enforce, &store).is_err());
+
+        PluginSignature::sign(&lib, &key(2)).unwrap().write_for(&lib).unwrap();
+        assert!(matches!(
+            check_plugin(&lib, SignaturePolicy::Warn, &store).unwrap(),
+            SignatureStatus::Failed { .. }
+        ));
+    }
+
+    #[test]
+    fn key_files_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("publisher.key");
+        let key = generate_signing_key();
+        std::fs::write(&path, encode_signing_key(&key)).unwrap();
+        assert_eq!(load_signing_key(&path).unwrap().to_bytes(), key.to_bytes());
+    }
+
+    #[test]
+    fn trust_store_parses_toml() {
+        let public = B64.encode(key(3).verifying_key().as_bytes());
+        let store =
+            TrustStore::from_toml_str(&format!("[[key]]\nname = \"acme\"\npublic_key = \"{public}\"\n"))
+                .unwrap();
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.publisher_for(&key(3).verifying_key()), Some("acme"));
+        assert!(TrustStore::from_toml_str("[[key]]\nname = \"x\"\npublic_key = \"nope\"\n").is_err());
+    }
+}
@@ -0,0 +1,278 @@
+//! Static inference of node outputs.
+//!
+//! Walks the graph in topological order and asks each node to describe its
+//! outputs ([`FilterNode::infer_outputs`]) from what is known about its
+//! inputs. The result carries image dimensions, alpha, bit depth and array
+//! lengths along connections, so [`CustomValidation`] can hand each node's
+//! `validate` real metadata instead of placeholders, before anything is
+//! decoded.
+//!
+//! [`FilterNode::infer_outputs`]: crate::core::node::FilterNode::infer_outputs
+//! [`CustomValidation`]: crate::validation::stages::CustomValidation
+
+use crate::core::context::ValidationContext;
+use crate::core::error::NodeId;
+use crate::core::types::{
+    Color, ImageFormat, ImageMetadata, ImageValue, PortType, Value,
+};
+use crate::graph::structure::{GraphNode, ProcessingGraph};
+use crate::graph::topology::TopologyAnalyzer;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Outputs inferred for each node of a graph.
+#[derive(Debug, Clone, Default)]
+pub struct InferredOutputs {
+    outputs: HashMap<NodeId, HashMap<String, Value>>,
+}
+
+impl InferredOutputs {
+    /// Infer the outputs of every node in `graph`.
+    ///
+    /// Disabled nodes produce nothing. A graph with a cycle infers nothing;
+    /// structural validation reports the cycle.
+    pub fn infer(graph: &ProcessingGraph) -> Self {
+        let mut inferred = Self::default();
+        let Ok(order) = TopologyAnalyzer::new(graph).topological_sort() else {
+            return inferred;
+        };
+
+        for node_id in order {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            if node.disabled || node.filter.unavailable_reason().is_some() {
+                continue;
+            }
+            let Some(ctx) = inferred.context(graph, node) else {
+                continue;
+            };
+            let outputs = node.filter.infer_outputs(&ctx);
+            if !outputs.is_empty() {
+                inferred.outputs.insert(node_id, outputs);
+            }
+        }
+        inferred
+    }
+
+    /// The inferred value of an output, if known.
+    pub fn get(&self, node_id: NodeId, port: &str) -> Option<&Value> {
+        self.outputs.get(&node_id)?.get(port)
+    }
+
+    /// The inferred metadata of an image output, if known.
+    pub fn image(&self, node_id: NodeId, port: &str) -> Option<&ImageMetadata> {
+        self.get(node_id, port)
+            .and_then(Value::as_image)
+            .map(|image| &image.metadata)
+    }
+
+    /// All inferred outputs of a node.
+    pub fn node(&self, node_id: NodeId) -> Option<&HashMap<String, Value>> {
+        self.outputs.get(&node_id)
+    }
+
+    /// Build the context `node` is validated with.
+    ///
+    /// Connected inputs hold the inferred upstream value where one is known
+    /// and a placeholder for the connection's resolved type otherwise. A
+    /// connected promoted parameter takes the inferred upstream value, or
+    /// keeps its stored value if nothing is known; the connected value is
+    /// then checked when the node executes.
+    pub fn validation_context(&self, graph: &ProcessingGraph, node: &GraphNode) -> ValidationContext {
+        self.build_context(graph, node).0
+    }
+
+    /// The context for inference, or `None` if a connected promoted
+    /// parameter's value is unknown (the stored value would be misleading).
+    fn context(&self, graph: &ProcessingGraph, node: &GraphNode) -> Option<ValidationContext> {
+        let (ctx, complete) = self.build_context(graph, node);
+        complete.then_some(ctx)
+    }
+
+    fn build_context(&self, graph: &ProcessingGraph, node: &GraphNode) -> (ValidationContext, bool) {
+        let mut ctx = ValidationContext::new(node.id);
+        let mut complete = true;
+        let metadata = node.filter.metadata();
+        let mut connected_parameters = HashMap::new();
+        let conversions = graph.conversions();
+
+        for conn in graph.connections_to(node.id) {
+            let upstream = self.get(conn.from.node_id, &conn.from.port_name);
+            if node.is_promoted(&conn.to.port_name)
+                && metadata.get_input(&conn.to.port_name).is_none()
+            {
+                match upstream {
+                    Some(value) => {
+                        connected_parameters.insert(conn.to.port_name.clone(), value.clone());
+                    }
+                    None => complete = false,
+                }
+                continue;
+            }
+
+            if let Some(value) = upstream {
+                let value = match node.input_port(&conn.to.port_name) {
+                    Some(port) => conversions.convert_input(value.clone(), &port),
+                    None => value.clone(),
+                };
+                ctx.add_inferred_input(conn.to.port_name.clone(), value);
+                continue;
+            }
+            ctx.add_input(conn.to.port_name.clone(), create_placeholder(&conn.port_type));
+        }
+
+        for param_def in &metadata.parameters {
+            let value = match connected_parameters.remove(&param_def.name) {
+                Some(value) => conversions.convert(value, &param_def.param_type),
+                None => node
+                    .parameters
+                    .get(&param_def.name)
+                    .cloned()
+                    .unwrap_or_else(|| param_def.default_value.clone()),
+            };
+            ctx.add_parameter(param_def.name.clone(), value);
+        }
+
+        (ctx, complete)
+    }
+}
+
+/// Create a placeholder value for validation based on port type.
+fn create_placeholder(port_type: &PortType) -> Value {
+    match port_type {
+        PortType::Image => {
+            let metadata = ImageMetadata {
+                width: 1920,
+                height: 1080,
+                format: ImageFormat::Png,
+                has_alpha: true,
+                grayscale: false,
+                bit_depth: 8,
+            };
+            Value::Image(ImageValue::from_metadata(metadata, PathBuf::new()))
+        }
+        PortType::Integer => Value::Integer(0),
+        PortType::Float => Value::Float(0.0),
+        PortType::String => Value::String(String::new()),
+        PortType::Boolean => Value::Boolean(false),
+        PortType::Color => Value::Color(Color::BLACK),
+        PortType::Vector2 => Value::Vector2(0.0, 0.0),
+        PortType::Vector3 => Value::Vector3(0.0, 0.0, 0.0),
+        PortType::Array(inner) => Value::Array(vec![create_placeholder(inner)]),
+        PortType::Map(_) => Value::Map(HashMap::new()),
+        PortType::Custom(_) | PortType::Var(_) => Value::None,
+        PortType::Any => Value::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filters::registry::FilterRegistry;
+
+    fn add(graph: &mut ProcessingGraph, registry: &FilterRegistry, filter: &str) -> NodeId {
+        graph.add_node(GraphNode::new(registry.create(filter).unwrap()))
+    }
+
+    fn load_png(graph: &mut ProcessingGraph, registry: &FilterRegistry, dir: &tempfile::TempDir) -> NodeId {
+        let path = dir.path().join("in.png");
+        image::RgbImage::new(800, 600).save(&path).unwrap();
+        let load = add(graph, registry, "load_image");
+        graph.get_node_mut(load).unwrap().set_parameter(
+            "path",
+            Value::String(path.to_string_lossy().into_owned()),
+        );
+        load
+    }
+
+    #[test]
+    fn load_image_reads_the_header() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = ProcessingGraph::new();
+        let load = load_png(&mut graph, &registry, &dir);
+
+        let inferred = InferredOutputs::infer(&graph);
+        let image = inferred.image(load, "image").unwrap();
+        assert_eq!((image.width, image.height), (800, 600));
+        assert!(!image.has_alpha);
+        assert_eq!(image.bit_depth, 8);
+        assert_eq!(image.format, ImageFormat::Png);
+    }
+
+    #[test]
+    fn dimensions_propagate_through_transforms() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = ProcessingGraph::new();
+        let load = load_png(&mut graph, &registry, &dir);
+        let rotate = add(&mut graph, &registry, "rotate");
+        let resize = add(&mut graph, &registry, "resize");
+        graph.connect(load, "image", rotate, "image").unwrap();
+        graph.connect(rotate, "image", resize, "image").unwrap();
+        let node = graph.get_node_mut(resize).unwrap();
+        node.set_parameter("width", Value::Integer(300));
+        node.set_parameter("height", Value::Integer(300));
+
+        let inferred = InferredOutputs::infer(&graph);
+        let rotated = inferred.image(rotate, "image").unwrap();
+        assert_eq!((rotated.width, rotated.height), (600, 800));
+        let resized = inferred.image(resize, "image").unwrap();
+        assert_eq!((resized.width, resized.height), (225, 300));
+        assert!(resized.has_alpha);
+    }
+
+    #[test]
+    fn unknown_inputs_stop_inference() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let load = add(&mut graph, &registry, "load_image");
+        let crop = add(&mut graph, &registry, "crop");
+        graph.connect(load, "image", crop, "image").unwrap();
+
+        let inferred = InferredOutputs::infer(&graph);
+        assert!(inferred.node(load).is_none());
+        assert!(inferred.node(crop).is_none());
+
+        let ctx = inferred.validation_context(&graph, graph.get_node(crop).unwrap());
+        assert!(ctx.has_input("image"));
+        assert!(ctx.inferred_image("image").is_none());
+    }
+
+    #[test]
+    fn connected_promoted_parameters_use_upstream_values() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = ProcessingGraph::new();
+        let load = load_png(&mut graph, &registry, &dir);
+        let width = add(&mut graph, &registry, "integer_constant");
+        graph
+            .get_node_mut(width)
+            .unwrap()
+            .set_parameter("value", Value::Integer(400));
+        let resize = add(&mut graph, &registry, "resize");
+        graph.promote_parameter(resize, "width").unwrap();
+        graph.connect(load, "image", resize, "image").unwrap();
+        graph.connect(width, "value", resize, "width").unwrap();
+
+        let inferred = InferredOutputs::infer(&graph);
+        let resized = inferred.image(resize, "image").unwrap();
+        assert_eq!((resized.width, resized.height), (400, 300));
+    }
+
+    #[test]
+    fn disabled_nodes_infer_nothing() {
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let mut graph = ProcessingGraph::new();
+        let load = load_png(&mut graph, &registry, &dir);
+        let flip = add(&mut graph, &registry, "flip");
+        graph.connect(load, "image", flip, "image").unwrap();
+        graph.get_node_mut(flip).unwrap().disabled = true;
+
+        let inferred = InferredOutputs::infer(&graph);
+        assert!(inferred.node(load).is_some());
+        assert!(inferred.node(flip).is_none());
+    }
+}
@@ -0,0 +1,1115 @@
+//! Lint rules for graphs.
+//!
+//! Lints flag graphs that are valid but probably not what their author
+//! meant: work whose result is thrown away, work done twice, steps that
+//! could be one step. Each [`LintRule`] has a name and a default
+//! [`LintLevel`] that a [`Linter`] can override. A finding may carry a
+//! [`LintFix`], the [`GraphEdit`]s that resolve it, applied all or nothing.
+//! [`Linter::fix`] applies fixes until none are left; `ambara lint --fix`
+//! does the same for a graph file. Findings whose resolution depends on
+//! what the author meant carry a suggestion instead, which is reported but
+//! never applied.
+//!
+//! As a [`ValidationStage`], the linter reports `warn` findings as
+//! warnings and `deny` findings as [`ValidationError::Lint`].
+//!
+//! ```rust,ignore
+//! let linter = Linter::with_builtin_rules().with_level("dead_branch", LintLevel::Deny);
+//! for finding in linter.lint(&graph) {
+//!     println!("{finding}");
+//! }
+//! let fixed = linter.fix(&mut graph, &registry)?;
+//! ```
+
+use crate::core::error::{NodeId, ValidationError, ValidationWarning};
+use crate::core::node::Category;
+use crate::core::types::Value;
+use crate::filters::registry::FilterRegistry;
+use crate::graph::edit::{apply_all, EditError, GraphEdit};
+use crate::graph::serialization::SerializedConnection;
+use crate::graph::structure::{GraphNode, ProcessingGraph};
+use crate::graph::topology::TopologyAnalyzer;
+use crate::graph::typing::declares_type_variables;
+use crate::validation::stages::ValidationStage;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Fixes applied by [`Linter::fix`] before it gives up, in case fixes keep
+/// producing new findings.
+const MAX_FIXES: usize = 256;
+
+/// How a rule's findings are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintLevel {
+    /// The rule does not run.
+    Allow,
+    /// Findings are validation warnings.
+    Warn,
+    /// Findings are validation errors.
+    Deny,
+}
+
+impl fmt::Display for LintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintLevel::Allow => write!(f, "allow"),
+            LintLevel::Warn => write!(f, "warning"),
+            LintLevel::Deny => write!(f, "error"),
+        }
+    }
+}
+
+/// Edits that resolve a finding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintFix {
+    /// What the fix does.
+    pub description: String,
+    /// The edits, in the order they are applied.
+    pub edits: Vec<GraphEdit>,
+}
+
+impl LintFix {
+    /// Create a fix.
+    pub fn new(description: impl Into<String>, edits: Vec<GraphEdit>) -> Self {
+        Self {
+            description: description.into(),
+            edits,
+        }
+    }
+
+    /// Apply the edits, all or nothing. Returns the edits that undo the fix.
+    pub fn apply(
+        &self,
+        graph: &mut ProcessingGraph,
+        registry: &FilterRegistry,
+    ) -> Result<Vec<GraphEdit>, EditError> {
+        apply_all(&self.edits, graph, registry)
+    }
+}
+
+/// One problem found by a rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LintFinding {
+    /// Name of the rule that found it.
+    pub rule: String,
+    /// Level it is reported at.
+    pub level: LintLevel,
+    /// What is wrong.
+    pub message: String,
+    /// Nodes involved, most relevant first.
+    pub node_ids: Vec<NodeId>,
+    /// Edits that resolve it, if the rule knows them.
+    pub fix: Option<LintFix>,
+    /// What the author might do, for findings without a safe fix.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl LintFinding {
+    /// Create a finding. [`Linter::lint`] fills in the rule and level.
+    pub fn new(message: impl Into<String>, node_ids: Vec<NodeId>) -> Self {
+        Self {
+            rule: String::new(),
+            level: LintLevel::Warn,
+            message: message.into(),
+            node_ids,
+            fix: None,
+            suggestion: None,
+        }
+    }
+
+    /// Attach a fix.
+    pub fn with_fix(mut self, fix: LintFix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+
+    /// Attach a suggestion, which [`Linter::fix`] does not apply.
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]: {}", self.level, self.rule, self.message)?;
+        if let Some(fix) = &self.fix {
+            write!(f, " (fix: {})", fix.description)?;
+        } else if let Some(suggestion) = &self.suggestion {
+            write!(f, " (suggestion: {})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// A named check over a whole graph.
+pub trait LintRule: Send + Sync {
+    /// Name used to configure the rule, e.g. `unused_node`.
+    fn name(&self) -> &str;
+
+    /// One-line description of what the rule flags.
+    fn description(&self) -> &str;
+
+    /// Level used unless the linter overrides it.
+    fn default_level(&self) -> LintLevel {
+        LintLevel::Warn
+    }
+
+    /// Find problems in `graph`.
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding>;
+}
+
+/// Runs lint rules at configured levels.
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+    levels: HashMap<String, LintLevel>,
+}
+
+impl Linter {
+    /// Create a linter without rules.
+    pub fn new() -> Self {
+        Self {
+            rules: Vec::new(),
+            levels: HashMap::new(),
+        }
+    }
+
+    /// Create a linter with the built-in rules at their default levels.
+    pub fn with_builtin_rules() -> Self {
+        let mut linter = Self::new();
+        linter.add_rule(Box::new(UnusedNode));
+        linter.add_rule(Box::new(DeadBranch::default()));
+        linter.add_rule(Box::new(DuplicateSubchain));
+        linter.add_rule(Box::new(MergeableSequence::default()));
+        linter.add_rule(Box::new(LossyReencode));
+        linter.add_rule(Box::new(DisabledRequiredInput));
+        linter
+    }
+
+    /// Add a rule.
+    pub fn add_rule(&mut self, rule: Box<dyn LintRule>) {
+        self.rules.push(rule);
+    }
+
+    /// Override the level of a rule.
+    pub fn set_level(&mut self, rule: impl Into<String>, level: LintLevel) {
+        self.levels.insert(rule.into(), level);
+    }
+
+    /// Builder form of [`Linter::set_level`].
+    pub fn with_level(mut self, rule: impl Into<String>, level: LintLevel) -> Self {
+        self.set_level(rule, level);
+        self
+    }
+
+    /// The level a rule runs at, or `None` if there is no such rule.
+    pub fn level(&self, rule: &str) -> Option<LintLevel> {
+        let rule = self.rules.iter().find(|r| r.name() == rule)?;
+        Some(self.level_of(rule.as_ref()))
+    }
+
+    /// The rules, in the order they run.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn LintRule> {
+        self.rules.iter().map(|rule| rule.as_ref())
+    }
+
+    fn level_of(&self, rule: &dyn LintRule) -> LintLevel {
+        self.levels
+            .get(rule.name())
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+
+    /// Run every rule that is not allowed.
+    pub fn lint(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        for rule in &self.rules {
+            let level = self.level_of(rule.as_ref());
+            if level == LintLevel::Allow {
+                continue;
+            }
+            findings.extend(rule.check(graph).into_iter().map(|mut finding| {
+                finding.rule = rule.name().to_string();
+                finding.level = level;
+                finding
+            }));
+        }
+        findings
+    }
+
+    /// Apply fixes until no finding has one. Fixes are applied one at a
+    /// time and the graph is linted again after each, since one fix can
+    /// resolve or change other findings. Returns the findings that were
+    /// fixed.
+    pub fn fix(
+        &self,
+        graph: &mut ProcessingGraph,
+        registry: &FilterRegistry,
+    ) -> Result<Vec<LintFinding>, EditError> {
+        let mut fixed = Vec::new();
+        while fixed.len() < MAX_FIXES {
+            let Some(finding) = self
+                .lint(graph)
+                .into_iter()
+                .find(|finding| finding.fix.is_some())
+            else {
+                break;
+            };
+            if let Some(fix) = &finding.fix {
+                fix.apply(graph, registry)?;
+            }
+            fixed.push(finding);
+        }
+        Ok(fixed)
+    }
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::with_builtin_rules()
+    }
+}
+
+impl ValidationStage for Linter {
+    fn name(&self) -> &str {
+        "Lint"
+    }
+
+    fn validate(
+        &self,
+        graph: &ProcessingGraph,
+    ) -> Result<Vec<ValidationWarning>, Vec<ValidationError>> {
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+
+        for finding in self.lint(graph) {
+            match finding.level {
+                LintLevel::Deny => errors.push(ValidationError::Lint {
+                    rule: finding.rule,
+                    node_ids: finding.node_ids,
+                    message: finding.message,
+                }),
+                _ => warnings.push(ValidationWarning {
+                    message: format!("[{}] {}", finding.rule, finding.message),
+                    node_id: finding.node_ids.first().copied(),
+                    suggestion: finding.fix.map(|fix| fix.description).or(finding.suggestion),
+                }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(warnings)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+// ============================================================================
+// Built-in Rules
+// ============================================================================
+
+/// `unused_node`: nodes with no connections, in a graph with other nodes.
+///
+/// Such nodes are often still being wired up, so the rule only suggests
+/// removing them.
+pub struct UnusedNode;
+
+impl LintRule for UnusedNode {
+    fn name(&self) -> &str {
+        "unused_node"
+    }
+
+    fn description(&self) -> &str {
+        "Nodes that are not connected to anything"
+    }
+
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        if graph.node_count() < 2 {
+            return Vec::new();
+        }
+        graph
+            .nodes()
+            .filter(|node| !is_connected(graph, node.id))
+            .map(|node| {
+                LintFinding::new(
+                    format!("'{}' is not connected to anything", node.display_name()),
+                    vec![node.id],
+                )
+                .with_suggestion(format!("Connect or remove '{}'", node.display_name()))
+            })
+            .collect()
+    }
+}
+
+/// `dead_branch`: connected nodes whose outputs reach no sink, in a graph
+/// that has sinks.
+///
+/// Sinks are output nodes (such as `save_image`), nodes without outputs and
+/// the filters listed in `sinks`, by default the preview and display nodes.
+pub struct DeadBranch {
+    /// Filter IDs that count as sinks besides output nodes.
+    pub sinks: Vec<String>,
+}
+
+impl Default for DeadBranch {
+    fn default() -> Self {
+        Self {
+            sinks: ["preview", "image_preview", "value_display"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+        }
+    }
+}
+
+impl DeadBranch {
+    fn is_sink(&self, node: &GraphNode) -> bool {
+        let metadata = node.filter.metadata();
+        metadata.category == Category::Output
+            || metadata.outputs.is_empty()
+            || self.sinks.contains(&metadata.id)
+    }
+}
+
+impl LintRule for DeadBranch {
+    fn name(&self) -> &str {
+        "dead_branch"
+    }
+
+    fn description(&self) -> &str {
+        "Nodes whose outputs reach no output or preview node"
+    }
+
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        let sinks: Vec<NodeId> = graph
+            .nodes()
+            .filter(|node| self.is_sink(node))
+            .map(|node| node.id)
+            .collect();
+        if sinks.is_empty() {
+            return Vec::new();
+        }
+        let mut live: HashSet<NodeId> = sinks.iter().copied().collect();
+        for sink in &sinks {
+            live.extend(graph.get_upstream(*sink));
+        }
+        let dead: HashSet<NodeId> = graph
+            .node_ids()
+            .filter(|id| !live.contains(id) && is_connected(graph, *id))
+            .collect();
+
+        // One finding per branch end, covering the dead nodes feeding it
+        let mut findings = Vec::new();
+        for tail in graph.nodes().filter(|node| dead.contains(&node.id)) {
+            if graph.connections_from(tail.id).next().is_some() {
+                continue;
+            }
+            let mut branch = vec![tail.id];
+            branch.extend(
+                graph
+                    .get_upstream(tail.id)
+                    .into_iter()
+                    .filter(|id| dead.contains(id)),
+            );
+            let message = match branch.len() {
+                1 => format!("'{}' does not reach any output", tail.display_name()),
+                n => format!(
+                    "'{}' and {} node(s) feeding it do not reach any output",
+                    tail.display_name(),
+                    n - 1
+                ),
+            };
+            let edits = branch
+                .iter()
+                .map(|&node_id| GraphEdit::RemoveNode { node_id })
+                .collect();
+            findings.push(LintFinding::new(message, branch.clone()).with_fix(LintFix::new(
+                format!("Remove the branch ending at '{}'", tail.display_name()),
+                edits,
+            )));
+        }
+        findings
+    }
+}
+
+/// `duplicate_subchain`: nodes that compute exactly what another node
+/// computes: the same filter and parameters on the same inputs. Found
+/// bottom-up, so a whole repeated chain is one finding.
+///
+/// Output nodes are never merged, since they have side effects.
+pub struct DuplicateSubchain;
+
+impl DuplicateSubchain {
+    /// What a node computes, with inputs named by their canonical node.
+    fn key(graph: &ProcessingGraph, node: &GraphNode, canonical: &HashMap<NodeId, NodeId>) -> String {
+        let metadata = node.filter.metadata();
+        let parameters: Vec<(String, String)> = metadata
+            .parameters
+            .iter()
+            .map(|param| {
+                let value = node
+                    .parameters
+                    .get(&param.name)
+                    .unwrap_or(&param.default_value);
+                (
+                    param.name.clone(),
+                    serde_json::to_string(value).unwrap_or_default(),
+                )
+            })
+            .collect();
+        let mut inputs: Vec<(String, NodeId, String)> = graph
+            .connections_to(node.id)
+            .map(|conn| {
+                let from = canonical
+                    .get(&conn.from.node_id)
+                    .copied()
+                    .unwrap_or(conn.from.node_id);
+                (conn.to.port_name.clone(), from, conn.from.port_name.clone())
+            })
+            .collect();
+        inputs.sort_by(|a, b| a.0.cmp(&b.0));
+        format!(
+            "{}|{}|{:?}|{:?}|{:?}",
+            metadata.id, node.disabled, parameters, node.promoted_parameters, inputs
+        )
+    }
+}
+
+impl LintRule for DuplicateSubchain {
+    fn name(&self) -> &str {
+        "duplicate_subchain"
+    }
+
+    fn description(&self) -> &str {
+        "Nodes or chains that repeat the work of another"
+    }
+
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        let Ok(order) = TopologyAnalyzer::new(graph).topological_sort() else {
+            return Vec::new();
+        };
+
+        let mut first: HashMap<String, NodeId> = HashMap::new();
+        let mut canonical: HashMap<NodeId, NodeId> = HashMap::new();
+        for node_id in order {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            let metadata = node.filter.metadata();
+            if metadata.category == Category::Output || metadata.outputs.is_empty() {
+                canonical.insert(node_id, node_id);
+                continue;
+            }
+            let key = Self::key(graph, node, &canonical);
+            let original = *first.entry(key).or_insert(node_id);
+            canonical.insert(node_id, original);
+        }
+        let duplicates: HashSet<NodeId> = canonical
+            .iter()
+            .filter(|(id, original)| id != original)
+            .map(|(id, _)| *id)
+            .collect();
+
+        let mut findings = Vec::new();
+        for tail in graph.nodes().filter(|node| duplicates.contains(&node.id)) {
+            // A chain ends where its results leave the duplicated work
+            let feeds_duplicates_only = graph.connections_from(tail.id).next().is_some()
+                && graph
+                    .connections_from(tail.id)
+                    .all(|conn| duplicates.contains(&conn.to.node_id));
+            if feeds_duplicates_only {
+                continue;
+            }
+
+            let mut chain = vec![tail.id];
+            let mut index = 0;
+            while index < chain.len() {
+                for conn in graph.connections_to(chain[index]) {
+                    let from = conn.from.node_id;
+                    if duplicates.contains(&from) && !chain.contains(&from) {
+                        chain.push(from);
+                    }
+                }
+                index += 1;
+            }
+
+            // Feed everything outside the chain from the originals instead
+            let mut edits = Vec::new();
+            for &node_id in &chain {
+                for conn in graph.connections_from(node_id) {
+                    if chain.contains(&conn.to.node_id) {
+                        continue;
+                    }
+                    edits.push(GraphEdit::Disconnect {
+                        connection: SerializedConnection::from(conn),
+                    });
+                    edits.push(GraphEdit::connect(
+                        canonical[&node_id],
+                        conn.from.port_name.clone(),
+                        conn.to.node_id,
+                        conn.to.port_name.clone(),
+                    ));
+                }
+            }
+            edits.extend(chain.iter().map(|&node_id| GraphEdit::RemoveNode { node_id }));
+
+            let original = graph
+                .get_node(canonical[&tail.id])
+                .map(|node| node.display_name())
+                .unwrap_or_default();
+            let message = match chain.len() {
+                1 => format!("'{}' repeats the work of '{}'", tail.display_name(), original),
+                n => format!(
+                    "'{}' and {} node(s) feeding it repeat the chain ending at '{}'",
+                    tail.display_name(),
+                    n - 1,
+                    original
+                ),
+            };
+            findings.push(LintFinding::new(message, chain.clone()).with_fix(LintFix::new(
+                format!("Reuse '{}' and remove the copy", original),
+                edits,
+            )));
+        }
+        findings
+    }
+}
+
+/// How two consecutive nodes of one filter combine into one.
+#[derive(Debug, Clone)]
+pub struct ParameterMerge {
+    /// Filter both nodes use.
+    pub filter_id: String,
+    /// Parameter that differs; all others must be equal.
+    pub parameter: String,
+    /// Combined value of the first and second node's parameter, or `None`
+    /// when they cannot be combined exactly.
+    pub combine: fn(f64, f64) -> Option<f64>,
+    /// Whether the merged node reproduces the pair's output exactly.
+    /// Approximate merges are suggested rather than fixed.
+    pub exact: bool,
+}
+
+/// Pixel adjustment `brightness` applies for `amount`.
+fn brightness_adjustment(amount: f64) -> i32 {
+    (amount as f32 * 255.0) as i32
+}
+
+/// `mergeable_sequence`: two nodes of the same filter in a row that one
+/// node could do, such as two `brightness` adjustments.
+pub struct MergeableSequence {
+    /// Filters that merge, and how.
+    pub merges: Vec<ParameterMerge>,
+}
+
+impl Default for MergeableSequence {
+    fn default() -> Self {
+        Self {
+            merges: vec![
+                // Clamping makes opposite adjustments order-dependent, and
+                // the amounts truncate to whole pixel steps separately
+                ParameterMerge {
+                    filter_id: "brightness".to_string(),
+                    parameter: "amount".to_string(),
+                    combine: |a, b| {
+                        let sum = a + b;
+                        let steps = brightness_adjustment(a) + brightness_adjustment(b);
+                        (a * b >= 0.0 && sum.abs() <= 1.0 && brightness_adjustment(sum) == steps)
+                            .then_some(sum)
+                    },
+                    exact: true,
+                },
+                // Gaussian blurs compose by adding variances, but kernel
+                // truncation and rounding make the result approximate
+                ParameterMerge {
+                    filter_id: "gaussian_blur".to_string(),
+                    parameter: "sigma".to_string(),
+                    combine: |a, b| {
+                        let sigma = a.hypot(b);
+                        (sigma <= 100.0).then_some(sigma)
+                    },
+                    exact: false,
+                },
+            ],
+        }
+    }
+}
+
+impl MergeableSequence {
+    fn merged_value(
+        &self,
+        graph: &ProcessingGraph,
+        first: &GraphNode,
+        second: &GraphNode,
+    ) -> Option<(&ParameterMerge, f64)> {
+        let metadata = first.filter.metadata();
+        if metadata.id != second.filter.metadata().id || first.disabled || second.disabled {
+            return None;
+        }
+        let merge = self.merges.iter().find(|m| m.filter_id == metadata.id)?;
+        // Values that arrive through connections are unknown here
+        let driven = |node: &GraphNode| {
+            node.promoted_parameters
+                .iter()
+                .any(|name| graph.is_input_connected(node.id, name))
+        };
+        if driven(first) || driven(second) {
+            return None;
+        }
+        for param in &metadata.parameters {
+            if param.name != merge.parameter
+                && first.get_parameter(&param.name) != second.get_parameter(&param.name)
+            {
+                return None;
+            }
+        }
+        let a = first.get_parameter(&merge.parameter)?.as_float()?;
+        let b = second.get_parameter(&merge.parameter)?.as_float()?;
+        Some((merge, (merge.combine)(a, b)?))
+    }
+}
+
+impl LintRule for MergeableSequence {
+    fn name(&self) -> &str {
+        "mergeable_sequence"
+    }
+
+    fn description(&self) -> &str {
+        "Consecutive nodes of the same filter that one node could replace"
+    }
+
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        let mut merged: HashSet<NodeId> = HashSet::new();
+
+        for conn in graph.connections() {
+            let (Ok(first), Ok(second)) = (
+                graph.get_node(conn.from.node_id),
+                graph.get_node(conn.to.node_id),
+            ) else {
+                continue;
+            };
+            // The first node's result must only feed the second
+            if graph.connections_from(first.id).count() != 1
+                || merged.contains(&first.id)
+                || merged.contains(&second.id)
+            {
+                continue;
+            }
+            let Some((merge, value)) = self.merged_value(graph, first, second) else {
+                continue;
+            };
+            merged.extend([first.id, second.id]);
+
+            let mut edits = vec![
+                GraphEdit::set_parameter(first.id, merge.parameter.clone(), Value::Float(value)),
+                GraphEdit::RemoveNode { node_id: second.id },
+            ];
+            edits.extend(graph.connections_from(second.id).map(|out| {
+                GraphEdit::connect(
+                    first.id,
+                    out.from.port_name.clone(),
+                    out.to.node_id,
+                    out.to.port_name.clone(),
+                )
+            }));
+            let finding = LintFinding::new(
+                format!(
+                    "'{}' and '{}' can be one {} node",
+                    first.display_name(),
+                    second.display_name(),
+                    merge.filter_id
+                ),
+                vec![first.id, second.id],
+            );
+            let description =
+                format!("Merge into '{}' with {} = {}", first.display_name(), merge.parameter, value);
+            findings.push(if merge.exact {
+                finding.with_fix(LintFix::new(description, edits))
+            } else {
+                finding.with_suggestion(description)
+            });
+        }
+        findings
+    }
+}
+
+/// `lossy_reencode`: output nodes that save a JPEG which the graph then
+/// reads back, so the image is compressed again further down the chain.
+///
+/// The saved path must reach the path input of an input node, such as
+/// `load_image`, directly or through generic nodes that pass values on.
+/// Whether JPEG is wanted for the saved file is the author's call, so the
+/// rule only suggests saving as PNG.
+pub struct LossyReencode;
+
+impl LossyReencode {
+    /// Input nodes reading a path that `node` writes.
+    fn readers(graph: &ProcessingGraph, node: NodeId) -> Vec<NodeId> {
+        let mut readers = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = vec![node];
+        while let Some(current) = pending.pop() {
+            for conn in graph.connections_from(current) {
+                let Ok(target) = graph.get_node(conn.to.node_id) else {
+                    continue;
+                };
+                if !seen.insert(target.id) {
+                    continue;
+                }
+                let metadata = target.filter.metadata();
+                if metadata.category == Category::Input && conn.to.port_name == "path" {
+                    readers.push(target.id);
+                } else if declares_type_variables(&metadata) {
+                    pending.push(target.id);
+                }
+            }
+        }
+        readers
+    }
+}
+
+impl LintRule for LossyReencode {
+    fn name(&self) -> &str {
+        "lossy_reencode"
+    }
+
+    fn description(&self) -> &str {
+        "Intermediate JPEG saves that are read back by the graph"
+    }
+
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        graph
+            .nodes()
+            .filter(|node| node.filter.metadata().category == Category::Output)
+            .filter(|node| {
+                node.get_parameter("format")
+                    .and_then(|format| format.as_string().map(str::to_ascii_lowercase))
+                    .is_some_and(|format| format == "jpg" || format == "jpeg")
+            })
+            .filter_map(|node| {
+                let readers = Self::readers(graph, node.id);
+                let reader = graph.get_node(*readers.first()?).ok()?;
+                let mut node_ids = vec![node.id];
+                node_ids.extend(readers.iter().copied());
+                Some(
+                    LintFinding::new(
+                        format!(
+                            "'{}' saves a JPEG that '{}' reads back; each re-encode loses quality",
+                            node.display_name(),
+                            reader.display_name()
+                        ),
+                        node_ids,
+                    )
+                    .with_suggestion(format!("Save '{}' as PNG", node.display_name())),
+                )
+            })
+            .collect()
+    }
+}
+
+/// `disabled_required_input`: disabled nodes feeding a required input,
+/// which is then empty when the graph runs.
+///
+/// The fix bypasses the node, feeding each such input from the node's own
+/// input of the same type, or enables it when there is none.
+pub struct DisabledRequiredInput;
+
+impl LintRule for DisabledRequiredInput {
+    fn name(&self) -> &str {
+        "disabled_required_input"
+    }
+
+    fn description(&self) -> &str {
+        "Disabled nodes that leave a required input empty"
+    }
+
+    fn check(&self, graph: &ProcessingGraph) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        for node in graph.nodes().filter(|node| node.disabled) {
+            let metadata = node.filter.metadata();
+            let broken: Vec<_> = graph
+                .connections_from(node.id)
+                .filter(|conn| {
+                    graph
+                        .get_node(conn.to.node_id)
+                        .ok()
+                        .and_then(|target| target.input_port(&conn.to.port_name))
+                        .is_some_and(|port| !port.optional && port.default_value.is_none())
+                })
+                .collect();
+            let Some(example) = broken.first() else {
+                continue;
+            };
+
+            // Bypass: feed each broken input from an input of the same type
+            let mut edits = Vec::new();
+            for conn in &broken {
+                let Some(output) = metadata.get_output(&conn.from.port_name) else {
+                    continue;
+                };
+                let source = graph.connections_to(node.id).find(|input| {
+                    metadata
+                        .get_input(&input.to.port_name)
+                        .is_some_and(|port| port.port_type == output.port_type)
+                });
+                let Some(source) = source else {
+                    edits.clear();
+                    break;
+                };
+                edits.push(GraphEdit::Disconnect {
+                    connection: SerializedConnection::from(*conn),
+                });
+                edits.push(GraphEdit::connect(
+                    source.from.node_id,
+                    source.from.port_name.clone(),
+                    conn.to.node_id,
+                    conn.to.port_name.clone(),
+                ));
+            }
+            let fix = if edits.is_empty() {
+                LintFix::new(
+                    format!("Enable '{}'", node.display_name()),
+                    vec![GraphEdit::SetDisabled {
+                        node_id: node.id,
+                        disabled: false,
+                    }],
+                )
+            } else {
+                LintFix::new(format!("Bypass '{}'", node.display_name()), edits)
+            };
+
+            let target = graph
+                .get_node(example.to.node_id)
+                .map(|target| target.display_name())
+                .unwrap_or_default();
+            let mut node_ids = vec![node.id];
+            node_ids.extend(broken.iter().map(|conn| conn.to.node_id));
+            findings.push(
+                LintFinding::new(
+                    format!(
+                        "Disabled '{}' leaves required input '{}' of '{}' empty",
+                        node.display_name(),
+                        example.to.port_name,
+                        target
+                    ),
+                    node_ids,
+                )
+                .with_fix(fix),
+            );
+        }
+        findings
+    }
+}
+
+fn is_connected(graph: &ProcessingGraph, node_id: NodeId) -> bool {
+    graph
+        .connections()
+        .iter()
+        .any(|c| c.from.node_id == node_id || c.to.node_id == node_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::stages::ValidationStage;
+
+    fn add(graph: &mut ProcessingGraph, registry: &FilterRegistry, filter: &str) -> NodeId {
+        graph.add_node(GraphNode::new(registry.create(filter).unwrap()))
+    }
+
+    /// load_image → `filters`… → save_image
+    fn chain(
+        graph: &mut ProcessingGraph,
+        registry: &FilterRegistry,
+        filters: &[&str],
+    ) -> Vec<NodeId> {
+        let mut ids = vec![add(graph, registry, "load_image")];
+        for filter in filters.iter().copied().chain(["save_image"]) {
+            let id = add(graph, registry, filter);
+            graph.connect(*ids.last().unwrap(), "image", id, "image").unwrap();
+            ids.push(id);
+        }
+        ids
+    }
+
+    fn rules(findings: &[LintFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.rule.as_str()).collect()
+    }
+
+    #[test]
+    fn dead_branches_are_removed_and_unused_nodes_kept() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let ids = chain(&mut graph, &registry, &["grayscale"]);
+        let lonely = add(&mut graph, &registry, "invert");
+        let blur = add(&mut graph, &registry, "gaussian_blur");
+        let sharpen = add(&mut graph, &registry, "sharpen");
+        graph.connect(ids[0], "image", blur, "image").unwrap();
+        graph.connect(blur, "image", sharpen, "image").unwrap();
+
+        let linter = Linter::with_builtin_rules();
+        let findings = linter.lint(&graph);
+        assert_eq!(rules(&findings), vec!["unused_node", "dead_branch"]);
+        assert_eq!(findings[0].node_ids, vec![lonely]);
+        assert_eq!(findings[1].node_ids, vec![sharpen, blur]);
+
+        let fixed = linter.fix(&mut graph, &registry).unwrap();
+        assert_eq!(rules(&fixed), vec!["dead_branch"]);
+        assert_eq!(graph.node_count(), 4);
+        let remaining = linter.lint(&graph);
+        assert_eq!(rules(&remaining), vec!["unused_node"]);
+        assert_eq!(remaining[0].fix, None);
+        assert_eq!(remaining[0].suggestion.as_deref(), Some("Connect or remove 'Invert Colors'"));
+    }
+
+    #[test]
+    fn duplicate_chains_reuse_the_original() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let ids = chain(&mut graph, &registry, &["grayscale", "invert"]);
+        let copy_gray = add(&mut graph, &registry, "grayscale");
+        let copy_invert = add(&mut graph, &registry, "invert");
+        let save = add(&mut graph, &registry, "save_image");
+        graph.connect(ids[0], "image", copy_gray, "image").unwrap();
+        graph.connect(copy_gray, "image", copy_invert, "image").unwrap();
+        graph.connect(copy_invert, "image", save, "image").unwrap();
+
+        let linter = Linter::with_builtin_rules();
+        let findings = linter.lint(&graph);
+        assert_eq!(rules(&findings), vec!["duplicate_subchain"]);
+        assert_eq!(findings[0].node_ids, vec![copy_invert, copy_gray]);
+
+        linter.fix(&mut graph, &registry).unwrap();
+        assert_eq!(graph.node_count(), 5);
+        let feeding_save: Vec<_> = graph.connections_to(save).map(|c| c.from.node_id).collect();
+        assert_eq!(feeding_save, vec![ids[2]]);
+    }
+
+    #[test]
+    fn consecutive_adjustments_merge() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let ids = chain(&mut graph, &registry, &["brightness", "brightness", "brightness"]);
+        for (id, amount) in ids[1..4].iter().zip([0.2, 0.3, -0.1]) {
+            graph
+                .get_node_mut(*id)
+                .unwrap()
+                .set_parameter("amount", Value::Float(amount));
+        }
+
+        let linter = Linter::with_builtin_rules();
+        let findings = linter.lint(&graph);
+        assert_eq!(rules(&findings), vec!["mergeable_sequence"]);
+        assert_eq!(findings[0].node_ids, vec![ids[1], ids[2]]);
+
+        // Opposite adjustments do not merge, since clamping makes them
+        // order-dependent
+        linter.fix(&mut graph, &registry).unwrap();
+        assert_eq!(graph.node_count(), 4);
+        let amount = graph.get_node(ids[1]).unwrap().get_parameter("amount");
+        assert!((amount.and_then(|v| v.as_float()).unwrap() - 0.5).abs() < 1e-9);
+        assert!(graph.is_reachable(ids[1], ids[3]));
+    }
+
+    #[test]
+    fn inexact_merges_are_only_suggested() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let ids = chain(&mut graph, &registry, &["brightness", "brightness"]);
+        // 0.1 is 25 pixel steps, 0.2 is 51, so two nodes differ from one
+        for id in &ids[1..3] {
+            graph
+                .get_node_mut(*id)
+                .unwrap()
+                .set_parameter("amount", Value::Float(0.1));
+        }
+        let linter = Linter::with_builtin_rules();
+        assert!(linter.lint(&graph).is_empty());
+
+        let mut graph = ProcessingGraph::new();
+        let ids = chain(&mut graph, &registry, &["gaussian_blur", "gaussian_blur"]);
+        let findings = linter.lint(&graph);
+        assert_eq!(rules(&findings), vec!["mergeable_sequence"]);
+        assert_eq!(findings[0].fix, None);
+        assert!(findings[0].suggestion.is_some());
+        linter.fix(&mut graph, &registry).unwrap();
+        assert!(graph.get_node(ids[2]).is_ok());
+    }
+
+    #[test]
+    fn jpeg_reencoding_and_disabled_inputs() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        let ids = chain(&mut graph, &registry, &["grayscale", "invert"]);
+        let save = ids[3];
+        graph
+            .get_node_mut(save)
+            .unwrap()
+            .set_parameter("format", Value::String("JPG".to_string()));
+        let pass = graph.add_node(GraphNode::new(Box::new(crate::core::node::PassthroughNode)));
+        graph.connect(save, "path", pass, "input").unwrap();
+        let reload = add(&mut graph, &registry, "load_image");
+        graph.promote_parameter(reload, "path").unwrap();
+        graph.connect(pass, "output", reload, "path").unwrap();
+        let final_save = add(&mut graph, &registry, "save_image");
+        graph.connect(reload, "image", final_save, "image").unwrap();
+        graph.get_node_mut(ids[1]).unwrap().disabled = true;
+
+        // A JPEG whose path is only displayed is not read back
+        let other_save = add(&mut graph, &registry, "save_image");
+        graph
+            .get_node_mut(other_save)
+            .unwrap()
+            .set_parameter("format", Value::String("jpeg".to_string()));
+        graph.connect(ids[0], "image", other_save, "image").unwrap();
+        let display = add(&mut graph, &registry, "value_display");
+        graph.connect(other_save, "path", display, "value").unwrap();
+
+        let linter = Linter::with_builtin_rules();
+        let findings = linter.lint(&graph);
+        assert_eq!(rules(&findings), vec!["lossy_reencode", "disabled_required_input"]);
+        assert_eq!(findings[0].node_ids, vec![save, reload]);
+        assert_eq!(findings[0].fix, None);
+        assert_eq!(findings[0].suggestion.as_deref(), Some("Save 'Save Image' as PNG"));
+        assert_eq!(findings[1].node_ids, vec![ids[1], ids[2]]);
+        assert_eq!(findings[1].fix.as_ref().unwrap().description, "Bypass 'Grayscale'");
+
+        // The bypass leaves the disabled node dead; the JPEG is left to the
+        // author
+        let fixed = linter.fix(&mut graph, &registry).unwrap();
+        assert_eq!(rules(&fixed), vec!["disabled_required_input", "dead_branch"]);
+        assert_eq!(
+            graph.get_node(save).unwrap().get_parameter("format"),
+            Some(Value::String("JPG".to_string()))
+        );
+        let feeding_invert: Vec<_> = graph.connections_to(ids[2]).map(|c| c.from.node_id).collect();
+        assert_eq!(feeding_invert, vec![ids[0]]);
+    }
+
+    #[test]
+    fn levels_decide_how_findings_are_reported() {
+        let registry = FilterRegistry::with_builtins();
+        let mut graph = ProcessingGraph::new();
+        chain(&mut graph, &registry, &[]);
+        let lonely = add(&mut graph, &registry, "invert");
+
+        let warnings = Linter::with_builtin_rules().validate(&graph).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].node_id, Some(lonely));
+        assert_eq!(warnings[0].suggestion.as_deref(), Some("Connect or remove 'Invert Colors'"));
+
+        let linter = Linter::with_builtin_rules().with_level("unused_node", LintLevel::Deny);
+        assert_eq!(linter.level("unused_node"), Some(LintLevel::Deny));
+        let errors = linter.validate(&graph).unwrap_err();
+        assert!(matches!(&errors[0], ValidationError::Lint { rule, .. } if rule == "unused_node"));
+
+        let linter = linter.with_level("unused_node", LintLevel::Allow);
+        assert!(linter.validate(&graph).unwrap().is_empty());
+        assert_eq!(linter.level("no_such_rule"), None);
+    }
+}
@@ -2,9 +2,13 @@
 //!
 //! The validation pipeline runs before execution to catch errors early.
 
+pub mod inference;
+pub mod lint;
 pub mod pipeline;
 pub mod stages;
 
+pub use inference::InferredOutputs;
+pub use lint::{LintFinding, LintFix, LintLevel, LintRule, Linter};
 pub use pipeline::ValidationPipeline;
 pub use stages::{
     StructuralValidation, TypeValidation, ConstraintValidation,
@@ -3,10 +3,13 @@
 //! Each stage checks for a specific category of errors.
 
 use crate::core::context::ValidationContext;
+use crate::core::conversion::{Fidelity, ImageLayout};
 use crate::core::error::{ValidationError, ValidationWarning};
 use crate::core::types::Value;
-use crate::graph::structure::ProcessingGraph;
+use crate::graph::structure::{GraphNode, ProcessingGraph};
 use crate::graph::topology::TopologyAnalyzer;
+use crate::graph::typing::TypeResolver;
+use crate::validation::inference::InferredOutputs;
 use std::path::Path;
 
 /// Trait for validation stages.
@@ -118,7 +121,12 @@ impl ValidationStage for StructuralValidation {
 /// Type validation - checks type compatibility.
 ///
 /// Verifies:
-/// - All connection types are compatible
+/// - All connection types are compatible, after resolving type variables
+///   ([`PortType::Var`](crate::core::types::PortType::Var))
+///
+/// Warns about connections whose implicit conversion may lose information
+/// ([`ProcessingGraph::conversions`]), including images converted to the channel
+/// layout an input asks for when the upstream image is known to differ.
 pub struct TypeValidation;
 
 impl ValidationStage for TypeValidation {
@@ -131,48 +139,75 @@ impl ValidationStage for TypeValidation {
         graph: &ProcessingGraph,
     ) -> Result<Vec<ValidationWarning>, Vec<ValidationError>> {
         let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let mut resolver = TypeResolver::new(graph);
+        let conversions = graph.conversions();
+        // Only inferred when an input asks for an image layout
+        let mut inferred: Option<InferredOutputs> = None;
 
         for conn in graph.connections() {
-            // Get the nodes
-            let from_node = match graph.get_node(conn.from.node_id) {
-                Ok(n) => n,
-                Err(_) => continue, // Structural validation will catch this
-            };
-            let to_node = match graph.get_node(conn.to.node_id) {
-                Ok(n) => n,
-                Err(_) => continue,
+            // Resolve type variables of both nodes; ports that are not found
+            // are reported by structural validation
+            let from_type = resolver.output_type(conn.from.node_id, &conn.from.port_name);
+            let to_type = resolver.input_type(conn.to.node_id, &conn.to.port_name);
+            let Ok(to_node) = graph.get_node(conn.to.node_id) else {
+                continue;
             };
 
-            // Get the port definitions
-            let from_metadata = from_node.filter.metadata();
-            let to_metadata = to_node.filter.metadata();
-
-            let from_port = from_metadata.get_output(&conn.from.port_name);
-            let to_port = to_metadata.get_input(&conn.to.port_name);
-
-            match (from_port, to_port) {
-                (Some(from_def), Some(to_def)) => {
-                    if !from_def.port_type.compatible_with(&to_def.port_type) {
-                        errors.push(ValidationError::TypeMismatch {
-                            expected: to_def.port_type.clone(),
-                            got: from_def.port_type.clone(),
-                        });
+            if let (Some(got), Some(expected)) = (from_type, to_type) {
+                match conversions.fidelity(&got, &expected) {
+                    None => errors.push(ValidationError::TypeMismatch { expected, got }),
+                    Some(Fidelity::Lossy) => {
+                        let description = conversions
+                            .find(&got, &expected)
+                            .map(|conversion| conversion.description.clone())
+                            .unwrap_or_else(|| format!("{} to {}", got, expected));
+                        warnings.push(lossy_warning(to_node, &conn.to.port_name, &description));
                     }
+                    Some(Fidelity::Lossless) => {}
                 }
-                _ => {
-                    // Port not found - structural validation will catch this
-                }
+            }
+
+            let Some(layout) = to_node
+                .input_port(&conn.to.port_name)
+                .and_then(|port| port.image_layout)
+            else {
+                continue;
+            };
+            let inferred = inferred.get_or_insert_with(|| InferredOutputs::infer(graph));
+            let metadata = inferred.image(conn.from.node_id, &conn.from.port_name);
+            if metadata.and_then(|metadata| layout.conversion(metadata)) == Some(Fidelity::Lossy) {
+                let description = match layout {
+                    ImageLayout::Grayscale => "the image is converted to grayscale",
+                    ImageLayout::Rgba => "the image is converted to RGBA",
+                };
+                warnings.push(lossy_warning(to_node, &conn.to.port_name, description));
             }
         }
 
         if errors.is_empty() {
-            Ok(Vec::new())
+            Ok(warnings)
         } else {
             Err(errors)
         }
     }
 }
 
+fn lossy_warning(node: &GraphNode, port: &str, description: &str) -> ValidationWarning {
+    ValidationWarning {
+        message: format!(
+            "Input '{}' of '{}' is converted implicitly and may lose information: {}",
+            port,
+            node.display_name(),
+            description
+        ),
+        node_id: Some(node.id),
+        suggestion: Some(
+            "Convert the value explicitly upstream if the loss is not intended".to_string(),
+        ),
+    }
+}
+
 /// Constraint validation - checks parameter constraints.
 ///
 /// Verifies:
@@ -194,6 +229,13 @@ impl ValidationStage for ConstraintValidation {
             let metadata = node.filter.metadata();
 
             for param_def in &metadata.parameters {
+                // Connected promoted parameters are checked at execution time
+                if node.is_promoted(&param_def.name)
+                    && graph.is_input_connected(node.id, &param_def.name)
+                {
+                    continue;
+                }
+
                 // Get the value (custom or default)
                 let value = node
                     .parameters
@@ -222,7 +264,8 @@ impl ValidationStage for ConstraintValidation {
 
 /// Custom validation - runs each node's custom validation.
 ///
-/// Calls the `validate` method on each filter.
+/// Calls the `validate` method on each filter, with input metadata inferred
+/// along connections ([`InferredOutputs`]) where available.
 pub struct CustomValidation;
 
 impl ValidationStage for CustomValidation {
@@ -235,40 +278,26 @@ impl ValidationStage for CustomValidation {
         graph: &ProcessingGraph,
     ) -> Result<Vec<ValidationWarning>, Vec<ValidationError>> {
         let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let inferred = InferredOutputs::infer(graph);
 
         for node in graph.nodes() {
             if node.disabled {
                 continue;
             }
 
-            // Build validation context
-            let mut ctx = ValidationContext::new(node.id);
-
-            // Add input metadata (we don't have actual values yet)
-            for conn in graph.connections_to(node.id) {
-                // For validation, we use placeholder values based on expected types
-                let from_node = match graph.get_node(conn.from.node_id) {
-                    Ok(n) => n,
-                    Err(_) => continue,
-                };
-                let from_metadata = from_node.filter.metadata();
-                if let Some(port) = from_metadata.get_output(&conn.from.port_name) {
-                    // Create a placeholder value for validation
-                    let placeholder = create_placeholder(&port.port_type);
-                    ctx.add_input(conn.to.port_name.clone(), placeholder);
-                }
+            if let Some(reason) = node.filter.unavailable_reason() {
+                warnings.push(ValidationWarning {
+                    message: format!("Node '{}' cannot run: {reason}", node.display_name()),
+                    node_id: Some(node.id),
+                    suggestion: Some(
+                        "Fix or reload the plugin, or release its quarantine".to_string(),
+                    ),
+                });
+                continue;
             }
 
-            // Add parameters
-            let metadata = node.filter.metadata();
-            for param_def in &metadata.parameters {
-                let value = node
-                    .parameters
-                    .get(&param_def.name)
-                    .cloned()
-                    .unwrap_or_else(|| param_def.default_value.clone());
-                ctx.add_parameter(param_def.name.clone(), value);
-            }
+            let ctx = inferred.validation_context(graph, node);
 
             // Run custom validation
             if let Err(error) = node.filter.validate(&ctx) {
@@ -277,7 +306,7 @@ impl ValidationStage for CustomValidation {
         }
 
         if errors.is_empty() {
-            Ok(Vec::new())
+            Ok(warnings)
         } else {
             Err(errors)
         }
@@ -289,6 +318,9 @@ impl ValidationStage for CustomValidation {
 /// Verifies:
 /// - File paths exist
 /// - Memory requirements are reasonable
+/// - Filter-specific resources ([`FilterNode::validate_resources`])
+///
+/// [`FilterNode::validate_resources`]: crate::core::node::FilterNode::validate_resources
 pub struct ResourceValidation;
 
 impl ValidationStage for ResourceValidation {
@@ -363,6 +395,21 @@ impl ValidationStage for ResourceValidation {
                     }
                 }
             }
+
+            // Filter-specific checks (e.g. model names offered by a server)
+            let mut ctx = ValidationContext::new(node.id);
+            for param_def in &metadata.parameters {
+                let value = node
+                    .parameters
+                    .get(&param_def.name)
+                    .cloned()
+                    .unwrap_or_else(|| param_def.default_value.clone());
+                ctx.add_parameter(param_def.name.clone(), value);
+            }
+            match node.filter.validate_resources(&ctx) {
+                Ok(node_warnings) => warnings.extend(node_warnings),
+                Err(error) => errors.push(error),
+            }
         }
 
         if errors.is_empty() {
@@ -373,35 +420,6 @@ impl ValidationStage for ResourceValidation {
     }
 }
 
-/// Create a placeholder value for validation based on port type.
-fn create_placeholder(port_type: &crate::core::types::PortType) -> Value {
-    use crate::core::types::{ImageMetadata, ImageValue, ImageFormat, PortType, Color};
-    use std::path::PathBuf;
-
-    match port_type {
-        PortType::Image => {
-            let metadata = ImageMetadata {
-                width: 1920,
-                height: 1080,
-                format: ImageFormat::Png,
-                has_alpha: true,
-            };
-            Value::Image(ImageValue::from_metadata(metadata, PathBuf::new()))
-        }
-        PortType::Integer => Value::Integer(0),
-        PortType::Float => Value::Float(0.0),
-        PortType::String => Value::String(String::new()),
-        PortType::Boolean => Value::Boolean(false),
-        PortType::Color => Value::Color(Color::BLACK),
-        PortType::Vector2 => Value::Vector2(0.0, 0.0),
-        PortType::Vector3 => Value::Vector3(0.0, 0.0, 0.0),
-        PortType::Array(inner) => Value::Array(vec![create_placeholder(inner)]),
-        PortType::Map(_) => Value::Map(std::collections::HashMap::new()),
-        PortType::Custom(_) => Value::None,
-        PortType::Any => Value::None,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -434,7 +452,102 @@ mod tests {
         let stage = TypeValidation;
         let result = stage.validate(&graph);
 
-        // PassthroughNode uses Any type, so should be compatible
+        // PassthroughNode is generic (T in, T out), so should be compatible
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_custom_validation_uses_inferred_dimensions() {
+        use crate::filters::registry::FilterRegistry;
+
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("in.png");
+        image::RgbImage::new(1600, 1200).save(&path).unwrap();
+
+        let mut graph = ProcessingGraph::new();
+        let load = graph.add_node(
+            GraphNode::new(registry.create("load_image").unwrap())
+                .with_parameter("path", Value::String(path.to_string_lossy().into_owned())),
+        );
+        let resize = graph.add_node(
+            GraphNode::new(registry.create("resize").unwrap())
+                .with_parameter("width", Value::Integer(800))
+                .with_parameter("height", Value::Integer(600)),
+        );
+        let crop = graph.add_node(
+            GraphNode::new(registry.create("crop").unwrap())
+                .with_parameter("x", Value::Integer(900)),
+        );
+        graph.connect(load, "image", resize, "image").unwrap();
+        graph.connect(resize, "image", crop, "image").unwrap();
+
+        let errors = CustomValidation.validate(&graph).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_string().contains("outside the 800x600 input"), "{}", errors[0]);
+
+        graph
+            .get_node_mut(crop)
+            .unwrap()
+            .set_parameter("x", Value::Integer(700));
+        assert!(CustomValidation.validate(&graph).is_ok());
+    }
+
+    #[test]
+    fn test_type_validation_warns_about_lossy_conversions() {
+        use crate::filters::registry::FilterRegistry;
+
+        let registry = FilterRegistry::with_builtins();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("in.png");
+        image::RgbImage::new(8, 8).save(&path).unwrap();
+
+        let mut graph = ProcessingGraph::new();
+        let scale = graph.add_node(GraphNode::new(registry.create("float_constant").unwrap()));
+        let resize = graph.add_node(
+            GraphNode::new(registry.create("resize").unwrap()).with_promoted_parameter("width"),
+        );
+        graph.connect(scale, "value", resize, "width").unwrap();
+        let load = graph.add_node(
+            GraphNode::new(registry.create("load_image").unwrap())
+                .with_parameter("path", Value::String(path.to_string_lossy().into_owned())),
+        );
+        let merge = graph.add_node(GraphNode::new(registry.create("merge_channels").unwrap()));
+        graph.connect(load, "image", merge, "red").unwrap();
+
+        let warnings = TypeValidation.validate(&graph).unwrap();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert_eq!(warnings[0].node_id, Some(resize));
+        assert!(warnings[0].message.contains("Float to Integer"), "{}", warnings[0].message);
+        assert_eq!(warnings[1].node_id, Some(merge));
+        assert!(warnings[1].message.contains("grayscale"), "{}", warnings[1].message);
+    }
+
+    #[test]
+    fn test_type_validation_uses_the_graph_conversions() {
+        use crate::core::conversion::{Conversion, Fidelity};
+        use crate::core::types::PortType;
+        use crate::filters::registry::FilterRegistry;
+
+        let mut registry = FilterRegistry::with_builtins();
+        registry.register_conversion(Conversion::new(
+            PortType::String,
+            PortType::Float,
+            Fidelity::Lossy,
+            "text is parsed as a number",
+            |value| value,
+        ));
+        let mut graph = ProcessingGraph::new().with_conversions(registry.conversions());
+        let text = graph.add_node(GraphNode::new(registry.create("string_constant").unwrap()));
+        let equal = graph.add_node(GraphNode::new(registry.create("equal").unwrap()));
+        graph.connect(text, "value", equal, "a").unwrap();
+
+        let warnings = TypeValidation.validate(&graph).unwrap();
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].message.contains("parsed as a number"), "{}", warnings[0].message);
+
+        // Without the conversion the connection no longer type-checks
+        graph.set_conversions(FilterRegistry::with_builtins().conversions());
+        assert!(TypeValidation.validate(&graph).is_err());
+    }
 }
@@ -2,12 +2,13 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{Emitter, Manager, State};
 
 // Import the ambara library
 use ambara::prelude::*;
 use ambara::graph::structure::ProcessingGraph;
 use ambara::execution::engine::{ExecutionEngine, ExecutionOptions};
+use ambara::filters::builtin::comfyui_discovery::{DiscoveryCache, ParameterDropdown};
 
 struct AppState {
     filter_registry: Mutex<FilterRegistry>,
@@ -17,7 +18,13 @@ struct AppState {
 impl AppState {
     fn new() -> Self {
         let plugin_dir = default_plugin_dir();
-        let mut plugin_registry = PluginRegistry::new(plugin_dir, PluginSystemConfig::default());
+        let config = PluginSystemConfig {
+            // Dev builds load plugins from shadow copies so they can be rebuilt
+            // in place and picked up by the watcher started in `run`.
+            hot_reload: cfg!(debug_assertions),
+            ..PluginSystemConfig::default()
+        };
+        let mut plugin_registry = PluginRegistry::new(plugin_dir, config);
         let mut filter_registry = FilterRegistry::with_builtins();
 
         // Best-effort auto-discovery; failures are surfaced in explicit commands.
@@ -31,6 +38,95 @@ impl AppState {
     }
 }
 
+/// Payload of the `plugin-reloaded` event emitted by the dev-mode watcher.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginReloadEvent {
+    plugin_dir: String,
+    report: Option<ambara::plugins::ReloadReport>,
+    error: Option<String>,
+}
+
+/// Watch the plugin directory and hot-reload changed plugins (debug builds).
+///
+/// Each applied change is forwarded to the frontend as a `plugin-reloaded`
+/// event so it can refresh the palette and rebind or flag affected nodes.
+fn spawn_plugin_watcher(app: tauri::AppHandle) {
+    let watcher = app.state::<AppState>().plugin_registry.lock().map(|r| r.spawn_watcher());
+    let Ok(watcher) = watcher else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        for change in watcher.receiver() {
+            let state = app.state::<AppState>();
+            let results = {
+                let (Ok(mut plugins), Ok(mut filters)) =
+                    (state.plugin_registry.lock(), state.filter_registry.lock())
+                else {
+                    return;
+                };
+                plugins.apply_changes(&[change], &mut filters)
+            };
+
+            for (dir, result) in results {
+                let event = match result {
+                    Ok(report) => PluginReloadEvent {
+                        plugin_dir: dir.display().to_string(),
+                        report: Some(report),
+                        error: None,
+                    },
+                    Err(e) => PluginReloadEvent {
+                        plugin_dir: dir.display().to_string(),
+                        report: None,
+                        error: Some(e.to_string()),
+                    },
+                };
+                let _ = app.emit("plugin-reloaded", event);
+            }
+        }
+    });
+}
+
+/// Payload of the `plugin-health` event emitted when a plugin enters or
+/// leaves quarantine.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PluginHealthEvent {
+    plugin_id: String,
+    transition: ambara::plugins::health::HealthTransition,
+    reason: Option<String>,
+}
+
+/// Health-check loaded plugins in the background.
+///
+/// Quarantine changes are forwarded to the frontend as `plugin-health`
+/// events; the full history is available through `get_plugins`.
+fn spawn_health_monitor(app: tauri::AppHandle) {
+    let monitor = app
+        .state::<AppState>()
+        .plugin_registry
+        .lock()
+        .map(|r| r.spawn_health_monitor());
+    let Ok(monitor) = monitor else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        for event in monitor.receiver() {
+            let Some(transition) = event.transition else {
+                continue;
+            };
+            let payload = PluginHealthEvent {
+                plugin_id: event.report.plugin_id,
+                transition,
+                reason: event.report.reason,
+            };
+            let _ = app.emit("plugin-health", payload);
+        }
+    });
+}
+
 fn default_plugin_dir() -> PathBuf {
     // ui/src-tauri -> repo root is ../../
     Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -55,6 +151,8 @@ pub struct FilterInfo {
     pub name: String,
     pub description: String,
     pub category: String,
+    /// Filter version, recorded on nodes created from this filter.
+    pub version: String,
     pub inputs: Vec<PortDefinition>,
     pub outputs: Vec<PortDefinition>,
     pub parameters: Vec<ParameterInfo>,
@@ -72,6 +170,47 @@ pub struct PluginInfo {
     pub healthy: bool,
     pub filter_count: usize,
     pub loaded_for_ms: u64,
+    pub signature: ambara::plugins::SignatureStatus,
+    /// Init configuration with secret entries removed.
+    pub config: serde_json::Value,
+    /// Health-check history, including quarantine state.
+    pub health: ambara::plugins::HealthHistory,
+}
+
+/// Settings form generated from a plugin's `config_schema`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginSettingsForm {
+    pub plugin_id: String,
+    pub fields: Vec<PluginSettingField>,
+}
+
+/// One settings form entry. Secret values are never sent to the UI; `hasValue`
+/// says whether one is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginSettingField {
+    pub name: String,
+    pub display_name: String,
+    pub description: String,
+    pub group: Option<String>,
+    pub value_type: ambara::plugins::ConfigType,
+    pub default_value: serde_json::Value,
+    pub value: Option<serde_json::Value>,
+    pub has_value: bool,
+    pub required: bool,
+    pub secret: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub options: Vec<serde_json::Value>,
+    pub ui_hint: UiHint,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginVerificationFailure {
+    pub library_path: String,
+    pub error: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +240,12 @@ pub struct FilterNodeData {
     pub inputs: Vec<PortDefinition>,
     pub outputs: Vec<PortDefinition>,
     pub parameters: Vec<ParameterValue>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub promoted_parameters: Vec<String>,
+    /// Version of the filter the parameters were written for. Missing on
+    /// nodes saved before the UI recorded it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_version: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_valid: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -263,6 +408,15 @@ fn get_execution_settings() -> ExecutionSettings {
     ExecutionSettings::default()
 }
 
+// Dropdown options for ComfyUI name parameters, discovered from the server.
+// Runs off the main thread: discovery waits on the network.
+#[tauri::command(async)]
+fn get_comfyui_options(url: String, refresh: bool) -> Result<Vec<ParameterDropdown>, String> {
+    let cache = DiscoveryCache::global();
+    let options = if refresh { cache.refresh(&url) } else { cache.options(&url) }?;
+    Ok(options.dropdowns())
+}
+
 // Get all available filters - uses the actual ambara FilterRegistry
 #[tauri::command]
 fn get_filters(state: State<AppState>) -> Result<Vec<FilterInfo>, String> {
@@ -285,6 +439,7 @@ fn filter_info_from_metadata(metadata: &NodeMetadata) -> FilterInfo {
         name: metadata.name.clone(),
         description: metadata.description.clone(),
         category: metadata.category.display_name().to_string(),
+        version: metadata.version.clone(),
         inputs: metadata
             .inputs
             .iter()
@@ -329,9 +484,18 @@ fn plugin_info_from_loaded(plugin: &ambara::plugins::loader::LoadedPlugin) -> Pl
         healthy: plugin.last_healthy,
         filter_count: plugin.filter_ids().len(),
         loaded_for_ms: plugin.loaded_at.elapsed().as_millis() as u64,
+        signature: plugin.signature.clone(),
+        config: plugin.redacted_config(),
+        health: ambara::plugins::HealthHistory::default(),
     }
 }
 
+fn plugin_info(registry: &PluginRegistry, plugin_id: &str) -> Option<PluginInfo> {
+    let mut info = registry.with_plugin(plugin_id, plugin_info_from_loaded)?;
+    info.health = registry.health_history(plugin_id).unwrap_or_default();
+    Some(info)
+}
+
 #[tauri::command]
 fn get_plugins(state: State<AppState>) -> Result<Vec<PluginInfo>, String> {
     let registry = state
@@ -341,7 +505,7 @@ fn get_plugins(state: State<AppState>) -> Result<Vec<PluginInfo>, String> {
 
     let mut plugins = Vec::new();
     for plugin_id in registry.plugin_ids() {
-        if let Some(info) = registry.with_plugin(plugin_id, plugin_info_from_loaded) {
+        if let Some(info) = plugin_info(&registry, plugin_id) {
             plugins.push(info);
         }
     }
@@ -349,6 +513,20 @@ fn get_plugins(state: State<AppState>) -> Result<Vec<PluginInfo>, String> {
     Ok(plugins)
 }
 
+#[tauri::command]
+fn release_plugin_quarantine(plugin_id: String, state: State<AppState>) -> Result<PluginInfo, String> {
+    let registry = state
+        .plugin_registry
+        .lock()
+        .map_err(|_| "Plugin registry lock poisoned".to_string())?;
+
+    registry
+        .release_quarantine(&plugin_id)
+        .map_err(|e| e.to_string())?;
+    plugin_info(&registry, &plugin_id)
+        .ok_or_else(|| format!("Plugin '{plugin_id}' not found in registry"))
+}
+
 #[tauri::command]
 fn load_plugin(path: String, state: State<AppState>) -> Result<PluginInfo, String> {
     let mut plugin_registry = state
@@ -369,8 +547,7 @@ fn load_plugin(path: String, state: State<AppState>) -> Result<PluginInfo, Strin
         return Err(err.to_string());
     }
 
-    plugin_registry
-        .with_plugin(&plugin_id, plugin_info_from_loaded)
+    plugin_info(&plugin_registry, &plugin_id)
         .ok_or_else(|| format!("Loaded plugin '{plugin_id}' not found in registry"))
 }
 
@@ -385,10 +562,87 @@ fn unload_plugin(plugin_id: String, state: State<AppState>) -> Result<(), String
         .lock()
         .map_err(|_| "Filter registry lock poisoned".to_string())?;
 
-    filter_registry.unregister_plugin_filters(&plugin_id);
     plugin_registry
         .unload_plugin(&plugin_id)
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    filter_registry.unregister_plugin_filters(&plugin_id);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_plugin_settings(plugin_id: String, state: State<AppState>) -> Result<PluginSettingsForm, String> {
+    let registry = state
+        .plugin_registry
+        .lock()
+        .map_err(|_| "Plugin registry lock poisoned".to_string())?;
+
+    registry
+        .with_plugin(&plugin_id, |plugin| PluginSettingsForm {
+            plugin_id: plugin_id.clone(),
+            fields: plugin
+                .config_schema
+                .fields()
+                .iter()
+                .map(|field| plugin_setting_field(field, &plugin.config))
+                .collect(),
+        })
+        .ok_or_else(|| format!("Plugin '{plugin_id}' not found"))
+}
+
+#[tauri::command]
+fn update_plugin_settings(
+    plugin_id: String,
+    values: serde_json::Value,
+    state: State<AppState>,
+) -> Result<PluginSettingsForm, String> {
+    {
+        let mut plugin_registry = state
+            .plugin_registry
+            .lock()
+            .map_err(|_| "Plugin registry lock poisoned".to_string())?;
+        let mut filter_registry = state
+            .filter_registry
+            .lock()
+            .map_err(|_| "Filter registry lock poisoned".to_string())?;
+        plugin_registry
+            .set_plugin_config(&plugin_id, &values, &mut filter_registry)
+            .map_err(|e| e.to_string())?;
+    }
+    get_plugin_settings(plugin_id, state)
+}
+
+fn plugin_setting_field(
+    field: &ambara::plugins::ConfigField,
+    config: &serde_json::Value,
+) -> PluginSettingField {
+    let definition = &field.definition;
+    let current = config.get(&definition.name).filter(|v| !v.is_null());
+    let (mut min, mut max, mut options) = (None, None, Vec::new());
+    for constraint in &definition.constraints {
+        match constraint {
+            Constraint::Range { min: lo, max: hi } => (min, max) = (Some(*lo), Some(*hi)),
+            Constraint::MinValue(lo) => min = Some(*lo),
+            Constraint::MaxValue(hi) => max = Some(*hi),
+            Constraint::OneOf(values) => options = values.iter().map(value_to_json).collect(),
+            _ => {}
+        }
+    }
+    PluginSettingField {
+        name: definition.name.clone(),
+        display_name: definition.display_name.clone(),
+        description: definition.description.clone(),
+        group: definition.group.clone(),
+        value_type: field.kind,
+        default_value: value_to_json(&definition.default_value),
+        value: if field.secret { None } else { current.cloned() },
+        has_value: current.is_some(),
+        required: field.required,
+        secret: field.secret,
+        min,
+        max,
+        options,
+        ui_hint: definition.ui_hint.clone(),
+    }
 }
 
 #[tauri::command]
@@ -437,14 +691,24 @@ fn export_graph_json(graph: GraphState) -> Result<String, String> {
 }
 
 #[tauri::command]
-fn import_graph_json(content: String) -> Result<GraphState, String> {
-    // First try the exchange envelope format.
-    if let Ok(envelope) = serde_json::from_str::<GraphExchangeEnvelope>(&content) {
-        return Ok(envelope.graph);
-    }
+fn import_graph_json(content: String, state: State<AppState>) -> Result<GraphState, String> {
+    let registry = state
+        .filter_registry
+        .lock()
+        .map_err(|_| "Filter registry lock poisoned".to_string())?;
+    import_graph_with(&content, &registry)
+}
 
-    // Fallback to raw GraphState format for backward compatibility.
-    serde_json::from_str::<GraphState>(&content).map_err(|e| e.to_string())
+/// Parse an exported or raw graph and upgrade it to the installed filters.
+fn import_graph_with(content: &str, registry: &FilterRegistry) -> Result<GraphState, String> {
+    // First try the exchange envelope format, then fall back to raw
+    // GraphState format for backward compatibility.
+    let mut graph = match serde_json::from_str::<GraphExchangeEnvelope>(content) {
+        Ok(envelope) => envelope.graph,
+        Err(_) => serde_json::from_str::<GraphState>(content).map_err(|e| e.to_string())?,
+    };
+    migrate_graph_state(&mut graph, registry)?;
+    Ok(graph)
 }
 
 #[tauri::command]
@@ -523,24 +787,10 @@ fn import_plugins_from_directory(dir: String, state: State<AppState>) -> Result<
             continue;
         }
 
-        let lib_path = std::fs::read_dir(&child)
-            .ok()
-            .and_then(|iter| {
-                iter.flatten().find_map(|f| {
-                    let p = f.path();
-                    let ext = p.extension().and_then(|e| e.to_str()).unwrap_or_default();
-                    if ["so", "dll", "dylib"].contains(&ext) {
-                        Some(p)
-                    } else {
-                        None
-                    }
-                })
-            });
-
-        let Some(lib_path) = lib_path else {
+        let Some(lib_path) = PluginRegistry::find_library_in(&child) else {
             failed.push(PluginImportIssue {
                 path: child.display().to_string(),
-                error: "No plugin library (.so/.dll/.dylib) found".to_string(),
+                error: "No plugin library (.so/.dll/.dylib) or process entry found".to_string(),
             });
             continue;
         };
@@ -565,7 +815,7 @@ fn import_plugins_from_directory(dir: String, state: State<AppState>) -> Result<
             continue;
         }
 
-        if let Some(info) = plugin_registry.with_plugin(&loaded_id, plugin_info_from_loaded) {
+        if let Some(info) = plugin_info(&plugin_registry, &loaded_id) {
             loaded.push(info);
         }
     }
@@ -573,6 +823,24 @@ fn import_plugins_from_directory(dir: String, state: State<AppState>) -> Result<
     Ok(PluginImportSummary { loaded, failed })
 }
 
+#[tauri::command]
+fn get_plugin_verification_failures(
+    state: State<AppState>,
+) -> Result<Vec<PluginVerificationFailure>, String> {
+    let registry = state
+        .plugin_registry
+        .lock()
+        .map_err(|_| "Plugin registry lock poisoned".to_string())?;
+
+    Ok(registry
+        .verification_failures()
+        .map(|(path, error)| PluginVerificationFailure {
+            library_path: path.display().to_string(),
+            error: error.to_string(),
+        })
+        .collect())
+}
+
 #[tauri::command]
 fn export_plugin_inventory_json(state: State<AppState>) -> Result<String, String> {
     let plugins = get_plugins(state)?;
@@ -691,6 +959,134 @@ fn validate_graph(graph: GraphState) -> ValidationResult {
     }
 }
 
+/// Convert the UI graph into the library's serialized form.
+///
+/// UI node ids are arbitrary strings, so each node gets a fresh `NodeId`.
+/// Returns the map from UI id to `NodeId`.
+fn to_serialized_graph(graph: &GraphState) -> (SerializedGraph, HashMap<String, NodeId>) {
+    let node_id_map: HashMap<String, NodeId> = graph
+        .nodes
+        .iter()
+        .map(|n| (n.id.clone(), NodeId::new()))
+        .collect();
+
+    let mut serialized = SerializedGraph::new();
+    serialized.nodes = graph
+        .nodes
+        .iter()
+        .map(|ui_node| SerializedNode {
+            id: node_id_map[&ui_node.id],
+            filter_id: ui_node.data.filter_type.clone(),
+            position: ambara::graph::Position::new(ui_node.position.x, ui_node.position.y),
+            parameters: ui_node
+                .data
+                .parameters
+                .iter()
+                .filter_map(|p| json_to_value(&p.value).map(|v| (p.name.clone(), v)))
+                .collect(),
+            label: None,
+            disabled: false,
+            promoted_parameters: ui_node.data.promoted_parameters.clone(),
+            filter_version: ui_node.data.filter_version.clone(),
+        })
+        .collect();
+    // validate_graph has already rejected edges with unknown endpoints
+    serialized.connections = graph
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            Some(SerializedConnection {
+                from_node: *node_id_map.get(&edge.source)?,
+                from_port: edge.source_handle.clone().unwrap_or_else(|| "output".to_string()),
+                to_node: *node_id_map.get(&edge.target)?,
+                to_port: edge.target_handle.clone().unwrap_or_else(|| "input".to_string()),
+            })
+        })
+        .collect();
+    (serialized, node_id_map)
+}
+
+/// Convert the UI graph and upgrade it through the registry's migrations.
+///
+/// Nodes without a `filterVersion` were saved before the UI recorded it, when
+/// the graph format (1.0.0) did not record filter versions either, so the
+/// graph is migrated as a 1.0.0 document.
+fn migrated_serialized_graph(
+    graph: &GraphState,
+    registry: &FilterRegistry,
+) -> Result<(SerializedGraph, HashMap<String, NodeId>), String> {
+    let (mut serialized, node_id_map) = to_serialized_graph(graph);
+    serialized.version = "1.0.0".to_string();
+    let raw = serde_json::to_value(&serialized).map_err(|e| e.to_string())?;
+    let migrated = registry
+        .migrations()
+        .migrate(raw, registry)
+        .map_err(|e| e.to_string())?;
+    Ok((migrated.graph, node_id_map))
+}
+
+/// Upgrade a UI graph to the installed filters: parameters, filter versions,
+/// promoted parameters and renamed ports are written back.
+fn migrate_graph_state(graph: &mut GraphState, registry: &FilterRegistry) -> Result<(), String> {
+    let (migrated, node_id_map) = migrated_serialized_graph(graph, registry)?;
+
+    for ui_node in &mut graph.nodes {
+        let Some(node) = migrated
+            .nodes
+            .iter()
+            .find(|node| node.id == node_id_map[&ui_node.id])
+        else {
+            continue;
+        };
+        let metadata = registry.get_metadata(&node.filter_id);
+        let type_name = |name: &str, value: &Value| {
+            metadata
+                .and_then(|metadata| metadata.get_parameter(name))
+                .map(|param| format!("{:?}", param.param_type))
+                .unwrap_or_else(|| format!("{:?}", value.get_type()))
+        };
+
+        // Values the library cannot represent were not migrated; keep them
+        ui_node.data.parameters.retain_mut(|param| {
+            let saved = json_to_value(&param.value);
+            match node.parameters.get(&param.name) {
+                Some(value) if saved.as_ref() != Some(value) => {
+                    param.value = value_to_json(value);
+                    param.param_type = type_name(&param.name, value);
+                    true
+                }
+                Some(_) => true,
+                None => saved.is_none(),
+            }
+        });
+        for (name, value) in &node.parameters {
+            if !ui_node.data.parameters.iter().any(|param| &param.name == name) {
+                ui_node.data.parameters.push(ParameterValue {
+                    name: name.clone(),
+                    value: value_to_json(value),
+                    param_type: type_name(name, value),
+                });
+            }
+        }
+        ui_node.data.promoted_parameters = node.promoted_parameters.clone();
+        ui_node.data.filter_version = node.filter_version.clone();
+    }
+
+    // Connections keep the order of the edges they were built from
+    let mut connections = migrated.connections.iter();
+    for edge in &mut graph.edges {
+        if !node_id_map.contains_key(&edge.source) || !node_id_map.contains_key(&edge.target) {
+            continue;
+        }
+        let Some(conn) = connections.next() else {
+            break;
+        };
+        edge.source_handle = Some(conn.from_port.clone());
+        edge.target_handle = Some(conn.to_port.clone());
+    }
+    Ok(())
+}
+
 // Execute graph (placeholder - would connect to ambara library)
 #[tauri::command]
 fn execute_graph(graph: GraphState, settings: Option<ExecutionSettings>, state: State<AppState>) -> ExecutionResult {
@@ -726,93 +1122,39 @@ fn execute_graph(graph: GraphState, settings: Option<ExecutionSettings>, state:
             }
         }
     };
-    let mut processing_graph = ProcessingGraph::new();
-    let mut node_id_map: HashMap<String, NodeId> = HashMap::new();
-
-    // Add nodes
-    for ui_node in &graph.nodes {
-        // Find the filter in registry
-        let filter_type = &ui_node.data.filter_type;
-        
-        if let Some(filter) = registry.create(filter_type) {
-            // Build the graph node with parameters
-            let mut graph_node = ambara::graph::structure::GraphNode::new(filter)
-                .with_position(ui_node.position.x, ui_node.position.y);
-
-            // Add parameters
-            for param in &ui_node.data.parameters {
-                if let Some(value) = json_to_value(&param.value) {
-                    graph_node = graph_node.with_parameter(&param.name, value);
-                }
-            }
-
-            let added_node_id = processing_graph.add_node(graph_node);
-            node_id_map.insert(ui_node.id.clone(), added_node_id);
-        } else {
+    let (serialized, node_id_map) = match migrated_serialized_graph(&graph, &registry) {
+        Ok(migrated) => migrated,
+        Err(message) => {
             return ExecutionResult {
                 success: false,
                 errors: vec![ExecutionError {
-                    node_id: ui_node.id.clone(),
-                    message: format!("Unknown filter type: {}", filter_type),
+                    node_id: String::new(),
+                    message,
                 }],
                 outputs: HashMap::new(),
                 execution_time: start.elapsed().as_millis() as u64,
             };
         }
-    }
-
-    // Add connections
-    for edge in &graph.edges {
-        let source_id = match node_id_map.get(&edge.source) {
-            Some(id) => *id,
-            None => {
-                return ExecutionResult {
-                    success: false,
-                    errors: vec![ExecutionError {
-                        node_id: edge.id.clone(),
-                        message: format!(
-                            "Edge '{}' references unknown source node '{}'",
-                            edge.id, edge.source
-                        ),
-                    }],
-                    outputs: HashMap::new(),
-                    execution_time: start.elapsed().as_millis() as u64,
-                };
-            }
-        };
-        let target_id = match node_id_map.get(&edge.target) {
-            Some(id) => *id,
-            None => {
-                return ExecutionResult {
-                    success: false,
-                    errors: vec![ExecutionError {
-                        node_id: edge.id.clone(),
-                        message: format!(
-                            "Edge '{}' references unknown target node '{}'",
-                            edge.id, edge.target
-                        ),
-                    }],
-                    outputs: HashMap::new(),
-                    execution_time: start.elapsed().as_millis() as u64,
-                };
-            }
-        };
-
-        let source_port = edge.source_handle.as_deref().unwrap_or("output");
-        let target_port = edge.target_handle.as_deref().unwrap_or("input");
-
-        if let Err(e) = processing_graph.connect(source_id, source_port, target_id, target_port) {
+    };
+    let processing_graph = match ProcessingGraph::from_serialized(&serialized, &registry) {
+        Ok(processing_graph) => processing_graph,
+        Err(e) => {
+            let node_id = e
+                .node_id()
+                .and_then(|id| node_id_map.iter().find(|(_, v)| **v == id))
+                .map(|(k, _)| k.clone())
+                .unwrap_or_default();
             return ExecutionResult {
                 success: false,
                 errors: vec![ExecutionError {
-                    node_id: edge.id.clone(),
-                    message: format!("Failed to connect: {}", e),
+                    node_id,
+                    message: e.to_string(),
                 }],
                 outputs: HashMap::new(),
                 execution_time: start.elapsed().as_millis() as u64,
             };
         }
-    }
+    };
 
     // Execute the graph
     let engine = ExecutionEngine::new();
@@ -935,13 +1277,18 @@ fn save_graph(graph: GraphState, path: String) -> Result<(), String> {
     Ok(())
 }
 
-// Load graph from file
+// Load graph from file, upgraded to the installed filters
 #[tauri::command]
-fn load_graph(path: String) -> Result<GraphState, String> {
+fn load_graph(path: String, state: State<AppState>) -> Result<GraphState, String> {
     let content = std::fs::read_to_string(&path)
         .map_err(|e| e.to_string())?;
-    let graph: GraphState = serde_json::from_str(&content)
+    let mut graph: GraphState = serde_json::from_str(&content)
         .map_err(|e| e.to_string())?;
+    let registry = state
+        .filter_registry
+        .lock()
+        .map_err(|_| "Filter registry lock poisoned".to_string())?;
+    migrate_graph_state(&mut graph, &registry)?;
     Ok(graph)
 }
 
@@ -951,9 +1298,20 @@ pub fn run() {
         .manage(AppState::new())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_dialog::init())
+        .setup(|app| {
+            if cfg!(debug_assertions) {
+                spawn_plugin_watcher(app.handle().clone());
+            }
+            spawn_health_monitor(app.handle().clone());
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             get_filters,
             get_plugins,
+            get_plugin_verification_failures,
+            get_plugin_settings,
+            update_plugin_settings,
+            release_plugin_quarantine,
             load_plugin,
             unload_plugin,
             get_plugin_filters,
@@ -967,7 +1325,8 @@ pub fn run() {
             execute_graph,
             save_graph,
             load_graph,
-            get_execution_settings
+            get_execution_settings,
+            get_comfyui_options
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -1001,6 +1360,8 @@ mod tests {
                     inputs: Vec::new(),
                     outputs: Vec::new(),
                     parameters: Vec::new(),
+                    promoted_parameters: Vec::new(),
+                    filter_version: None,
                     is_valid: None,
                     error_message: None,
                 },
@@ -1013,7 +1374,8 @@ mod tests {
     fn export_then_import_envelope_preserves_graph() {
         let original = sample_graph();
         let json = export_graph_json(original.clone()).expect("export must succeed");
-        let restored = import_graph_json(json).expect("import must succeed");
+        let restored = import_graph_with(&json, &FilterRegistry::with_builtins())
+            .expect("import must succeed");
 
         // No PartialEq on GraphState; compare via serde_json::Value (strict).
         let a = serde_json::to_value(&original).unwrap();
@@ -1025,15 +1387,42 @@ mod tests {
     fn import_graph_json_accepts_raw_graphstate_for_back_compat() {
         // Older clients may send a bare GraphState (no envelope).
         let raw_json = serde_json::to_string(&sample_graph()).unwrap();
-        let restored = import_graph_json(raw_json).expect("raw GraphState must still import");
+        let restored = import_graph_with(&raw_json, &FilterRegistry::with_builtins())
+            .expect("raw GraphState must still import");
         assert_eq!(restored.nodes.len(), 1);
         assert_eq!(restored.nodes[0].id, "n1");
     }
 
     #[test]
     fn import_graph_json_rejects_invalid_payloads() {
-        assert!(import_graph_json("not json".to_string()).is_err());
-        assert!(import_graph_json("{\"nodes\": \"oops\"}".to_string()).is_err());
+        let registry = FilterRegistry::with_builtins();
+        assert!(import_graph_with("not json", &registry).is_err());
+        assert!(import_graph_with("{\"nodes\": \"oops\"}", &registry).is_err());
+    }
+
+    #[test]
+    fn imported_graphs_are_migrated_to_the_installed_filters() {
+        // Saved before the UI recorded filter versions, with Rotate 1.0.0's
+        // string angle
+        let mut graph = sample_graph();
+        graph.nodes[0].data.filter_type = "rotate".to_string();
+        graph.nodes[0].data.parameters = vec![ParameterValue {
+            name: "angle".to_string(),
+            value: serde_json::json!("180"),
+            param_type: "String".to_string(),
+        }];
+        let json = serde_json::to_string(&graph).unwrap();
+
+        let restored = import_graph_with(&json, &FilterRegistry::with_builtins()).unwrap();
+        let data = &restored.nodes[0].data;
+        assert_eq!(data.filter_version.as_deref(), Some("2.0.0"));
+        assert_eq!(data.parameters[0].value, serde_json::json!(180.0));
+        assert_eq!(data.parameters[0].param_type, "Float");
+
+        // Recorded versions are kept, so current nodes are left alone
+        let json = serde_json::to_string(&restored).unwrap();
+        let again = import_graph_with(&json, &FilterRegistry::with_builtins()).unwrap();
+        assert_eq!(again.nodes[0].data.parameters[0].value, serde_json::json!(180.0));
     }
 
     #[test]
@@ -10,7 +10,7 @@ import { Settings } from './components/sidebar/Settings';
 import { ToastContainer } from './components/Toast';
 import { ConfirmDialog } from './components/ConfirmDialog';
 import { useToast } from './hooks/useToast';
-import { FilterInfo, FilterNodeData, ParameterValue } from './types';
+import { FilterInfo, FilterNodeData, ParameterValue, PluginReloadEvent } from './types';
 import * as api from './api/commands';
 import './App.css';
 
@@ -74,6 +74,79 @@ function App() {
     }
   }, [refreshFilters, toast]);
 
+  // Refresh the palette when the dev-mode watcher reloads a plugin, and
+  // flag nodes whose filter changed incompatibly or went away
+  const handlePluginReloaded = useCallback(async (event: PluginReloadEvent) => {
+    if (event.error || !event.report) {
+      toast.error(`Plugin reload failed (${event.pluginDir}): ${event.error ?? 'unknown error'}`);
+      return;
+    }
+    const report = event.report;
+    let current: FilterInfo[] = [];
+    try {
+      current = await api.getFilters();
+      setFilters(current);
+    } catch (err) {
+      console.error('Failed to refresh filters after plugin reload:', err);
+    }
+
+    let flagged = 0;
+    for (const node of useGraphStore.getState().nodes) {
+      // Conflicting filters are registered as `<plugin-id>/<filter-id>`
+      const filterId = Object.keys(report.filters).find((id) =>
+        node.data.filterType === id || node.data.filterType === `${report.plugin_id}/${id}`);
+      if (!filterId) continue;
+
+      const compatibility = report.filters[filterId];
+      if (compatibility.status === 'incompatible') {
+        flagged++;
+        updateNodeData(node.id, {
+          isValid: false,
+          errorMessage: `Changed by plugin reload: ${compatibility.reasons.join('; ')}`,
+        });
+      } else if (compatibility.status === 'removed') {
+        flagged++;
+        updateNodeData(node.id, {
+          isValid: false,
+          errorMessage: `No longer provided by ${report.plugin_id}`,
+        });
+      } else {
+        const filter = current.find((f) => f.id === node.data.filterType);
+        updateNodeData(node.id, {
+          ...(filter ? { inputs: filter.inputs, outputs: filter.outputs } : {}),
+          isValid: true,
+          errorMessage: undefined,
+        });
+      }
+    }
+
+    const version = report.current_version ? ` ${report.current_version}` : '';
+    if (flagged > 0) {
+      toast.warning(`Reloaded ${report.plugin_id}${version}; ${flagged} node(s) need attention`);
+    } else {
+      toast.info(`Reloaded ${report.plugin_id}${version}`);
+    }
+    // The toast callbacks are stable; the object holding them is not
+  }, [updateNodeData, toast.error, toast.warning, toast.info]);
+
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let isMounted = true;
+    api.onPluginReloaded((event) => { void handlePluginReloaded(event); })
+      .then((stop) => {
+        if (isMounted) {
+          unlisten = stop;
+        } else {
+          stop();
+        }
+      })
+      .catch((err) => console.log('Plugin reload events unavailable:', err));
+    return () => {
+      isMounted = false;
+      unlisten?.();
+    };
+  }, [handlePluginReloaded]);
+
   const handleAddFilter = useCallback((filter: FilterInfo) => {
     const id = `node_${++nodeIdCounter}`;
 
@@ -91,6 +164,7 @@ function App() {
       inputs: filter.inputs,
       outputs: filter.outputs,
       parameters,
+      filterVersion: filter.version,
       isValid: true,
     };
 
@@ -250,6 +324,7 @@ function App() {
           value,
           type: 'Any',
         })),
+        filterVersion: typeof node.filter_version === 'string' ? node.filter_version : filter?.version,
         isValid: true,
       };
 
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import { open, save } from '@tauri-apps/plugin-dialog';
 import {
   FilterInfo,
@@ -7,6 +8,10 @@ import {
   GraphExchangeEnvelope,
   PluginManifestPreview,
   PluginImportSummary,
+  PluginVerificationFailure,
+  PluginReloadEvent,
+  PluginSettingsForm,
+  ComfyParameterDropdown,
   GraphState,
   ValidationResult,
   ExecutionResult
@@ -38,6 +43,13 @@ export async function unloadPlugin(pluginId: string): Promise<void> {
   return invoke('unload_plugin', { pluginId });
 }
 
+// Subscribe to hot reloads of the plugin directory (debug builds)
+export async function onPluginReloaded(
+  handler: (event: PluginReloadEvent) => void,
+): Promise<UnlistenFn> {
+  return listen<PluginReloadEvent>('plugin-reloaded', (event) => handler(event.payload));
+}
+
 export async function getPluginFilters(pluginId: string): Promise<FilterInfo[]> {
   return invoke<FilterInfo[]>('get_plugin_filters', { pluginId });
 }
@@ -62,6 +74,32 @@ export async function importPluginsFromDirectory(dir: string): Promise<PluginImp
   return invoke<PluginImportSummary>('import_plugins_from_directory', { dir });
 }
 
+export async function getPluginVerificationFailures(): Promise<PluginVerificationFailure[]> {
+  return invoke<PluginVerificationFailure[]>('get_plugin_verification_failures');
+}
+
+export async function getPluginSettings(pluginId: string): Promise<PluginSettingsForm> {
+  return invoke<PluginSettingsForm>('get_plugin_settings', { pluginId });
+}
+
+export async function getComfyuiOptions(
+  url: string,
+  refresh = false
+): Promise<ComfyParameterDropdown[]> {
+  return invoke<ComfyParameterDropdown[]>('get_comfyui_options', { url, refresh });
+}
+
+export async function updatePluginSettings(
+  pluginId: string,
+  values: Record<string, unknown>
+): Promise<PluginSettingsForm> {
+  return invoke<PluginSettingsForm>('update_plugin_settings', { pluginId, values });
+}
+
+export async function releasePluginQuarantine(pluginId: string): Promise<PluginInfo> {
+  return invoke<PluginInfo>('release_plugin_quarantine', { pluginId });
+}
+
 export async function exportPluginInventoryJson(): Promise<string> {
   return invoke<string>('export_plugin_inventory_json');
 }
@@ -61,6 +61,23 @@ function FilterNodeComponent({ data, selected }: FilterNodeProps) {
       </div>
     )), [data.inputs]);
 
+  const promotedHandles = useMemo(() =>
+    data.parameters
+      .filter((param) => data.promotedParameters?.includes(param.name))
+      .map((param) => (
+        <div key={`param-${param.name}`} className="handle-row input-row promoted-row">
+          <Handle
+            type="target"
+            position={Position.Left}
+            id={param.name}
+            className="node-handle"
+            style={{ background: portColors[param.type] ?? portColors.Any }}
+          />
+          <span className="handle-label">{param.name}</span>
+          <span className="handle-type">{param.type}</span>
+        </div>
+      )), [data.parameters, data.promotedParameters]);
+
   const outputHandles = useMemo(() =>
     data.outputs.map((output) => {
       // Get the output value if it exists
@@ -127,6 +144,7 @@ function FilterNodeComponent({ data, selected }: FilterNodeProps) {
         <div className="filter-node-ports">
           <div className="input-ports">
             {inputHandles}
+            {promotedHandles}
           </div>
           <div className="output-ports">
             {outputHandles}
@@ -1,5 +1,7 @@
 import { useState } from 'react';
 import { PluginInfo } from '../../types';
+import { releasePluginQuarantine } from '../../api/commands';
+import { PluginSettings } from './PluginSettings';
 import './PluginPanel.css';
 
 interface PluginPanelProps {
@@ -11,6 +13,7 @@ interface PluginPanelProps {
 
 export function PluginPanel({ plugins, onLoadPlugin, onUnloadPlugin, onRefresh }: PluginPanelProps) {
     const [expanded, setExpanded] = useState(true);
+    const [settingsFor, setSettingsFor] = useState<string | null>(null);
 
     return (
         <div className="plugin-panel">
@@ -35,15 +38,47 @@ export function PluginPanel({ plugins, onLoadPlugin, onUnloadPlugin, onRefresh }
                                 <div key={plugin.id} className="plugin-item">
                                     <div className="plugin-row">
                                         <span className="plugin-name">{plugin.name}</span>
-                                        <span className={plugin.healthy ? 'plugin-health ok' : 'plugin-health bad'}>
-                                            {plugin.healthy ? 'Healthy' : 'Unhealthy'}
-                                        </span>
+                                        {plugin.health.quarantine ? (
+                                            <span className="plugin-health bad" title={plugin.health.quarantine.reason}>
+                                                Quarantined
+                                            </span>
+                                        ) : (
+                                            <span className={plugin.healthy ? 'plugin-health ok' : 'plugin-health bad'}>
+                                                {plugin.healthy ? 'Healthy' : 'Unhealthy'}
+                                            </span>
+                                        )}
                                     </div>
                                     <div className="plugin-meta">{plugin.id} · v{plugin.version}</div>
                                     <div className="plugin-meta">{plugin.filterCount} filters</div>
-                                    <button className="plugin-btn danger" onClick={() => onUnloadPlugin(plugin.id)}>
-                                        Unload
-                                    </button>
+                                    {plugin.health.total_checks > 0 && (
+                                        <div className="plugin-meta">
+                                            {plugin.health.total_failures}/{plugin.health.total_checks} checks failed
+                                            {plugin.health.average_latency_ms !== null &&
+                                                ` · ${plugin.health.average_latency_ms.toFixed(1)} ms avg`}
+                                        </div>
+                                    )}
+                                    {settingsFor === plugin.id && (
+                                        <PluginSettings pluginId={plugin.id} onSaved={onRefresh} />
+                                    )}
+                                    <div className="plugin-actions">
+                                        <button
+                                            className="plugin-btn secondary"
+                                            onClick={() => setSettingsFor((id) => (id === plugin.id ? null : plugin.id))}
+                                        >
+                                            Settings
+                                        </button>
+                                        {plugin.health.quarantine && (
+                                            <button
+                                                className="plugin-btn secondary"
+                                                onClick={() => releasePluginQuarantine(plugin.id).then(onRefresh)}
+                                            >
+                                                Release
+                                            </button>
+                                        )}
+                                        <button className="plugin-btn danger" onClick={() => onUnloadPlugin(plugin.id)}>
+                                            Unload
+                                        </button>
+                                    </div>
                                 </div>
                             ))}
                         </div>
@@ -0,0 +1,87 @@
+import { useEffect, useState } from 'react';
+import { getPluginSettings, updatePluginSettings } from '../../api/commands';
+import { PluginSettingField, PluginSettingsForm } from '../../types';
+
+interface PluginSettingsProps {
+    pluginId: string;
+    onSaved?: () => void;
+}
+
+function parseInput(field: PluginSettingField, raw: string | boolean): unknown {
+    if (field.valueType === 'boolean') return Boolean(raw);
+    if (field.valueType === 'integer') return raw === '' ? null : parseInt(String(raw), 10);
+    if (field.valueType === 'float') return raw === '' ? null : parseFloat(String(raw));
+    return raw;
+}
+
+export function PluginSettings({ pluginId, onSaved }: PluginSettingsProps) {
+    const [form, setForm] = useState<PluginSettingsForm | null>(null);
+    const [edits, setEdits] = useState<Record<string, unknown>>({});
+    const [error, setError] = useState<string | null>(null);
+
+    useEffect(() => {
+        getPluginSettings(pluginId)
+            .then(setForm)
+            .catch((e) => setError(String(e)));
+    }, [pluginId]);
+
+    if (error && !form) return <p className="plugin-settings-error">{error}</p>;
+    if (!form) return null;
+    if (form.fields.length === 0) return <p className="plugin-empty">No settings</p>;
+
+    const save = async () => {
+        try {
+            setForm(await updatePluginSettings(pluginId, edits));
+            setEdits({});
+            setError(null);
+            onSaved?.();
+        } catch (e) {
+            setError(String(e));
+        }
+    };
+
+    const current = (field: PluginSettingField) =>
+        field.name in edits ? edits[field.name] : field.value ?? (field.secret ? '' : field.defaultValue);
+
+    return (
+        <div className="plugin-settings">
+            {form.fields.map((field) => {
+                const value = current(field);
+                const set = (raw: string | boolean) =>
+                    setEdits((prev) => ({ ...prev, [field.name]: parseInput(field, raw) }));
+                return (
+                    <label key={field.name} className="plugin-setting" title={field.description}>
+                        <span>
+                            {field.displayName}
+                            {field.required && ' *'}
+                        </span>
+                        {field.valueType === 'boolean' ? (
+                            <input type="checkbox" checked={Boolean(value)} onChange={(e) => set(e.target.checked)} />
+                        ) : field.options.length > 0 ? (
+                            <select value={String(value ?? '')} onChange={(e) => set(e.target.value)}>
+                                {field.options.map((option) => (
+                                    <option key={String(option)} value={String(option)}>
+                                        {String(option)}
+                                    </option>
+                                ))}
+                            </select>
+                        ) : (
+                            <input
+                                type={field.secret ? 'password' : field.valueType === 'string' ? 'text' : 'number'}
+                                value={String(value ?? '')}
+                                min={field.min ?? undefined}
+                                max={field.max ?? undefined}
+                                placeholder={field.secret && field.hasValue ? '(unchanged)' : undefined}
+                                onChange={(e) => set(e.target.value)}
+                            />
+                        )}
+                    </label>
+                );
+            })}
+            {error && <p className="plugin-settings-error">{error}</p>}
+            <button className="plugin-btn" disabled={Object.keys(edits).length === 0} onClick={save}>
+                Save &amp; Reload
+            </button>
+        </div>
+    );
+}
@@ -8,7 +8,7 @@ interface PropertiesPanelProps {
 }
 
 export function PropertiesPanel({ onParameterChange }: PropertiesPanelProps) {
-  const { nodes, selectedNode, removeNode } = useGraphStore();
+  const { nodes, selectedNode, removeNode, setParameterPromoted } = useGraphStore();
 
   const node = nodes.find((n) => n.id === selectedNode);
 
@@ -66,13 +66,25 @@ export function PropertiesPanel({ onParameterChange }: PropertiesPanelProps) {
         <div className="properties-section">
           <h4>Parameters</h4>
           <div className="parameters-list">
-            {data.parameters.map((param) => (
-              <ParameterInput
-                key={param.name}
-                param={param}
-                onChange={(value) => onParameterChange(node.id, param.name, value)}
-              />
-            ))}
+            {data.parameters.map((param) => {
+              const promoted = data.promotedParameters?.includes(param.name) ?? false;
+              return (
+                <div key={param.name} className="parameter-entry">
+                  <ParameterInput
+                    param={param}
+                    onChange={(value) => onParameterChange(node.id, param.name, value)}
+                  />
+                  <label className="param-promote" title="Drive this parameter from a connection">
+                    <input
+                      type="checkbox"
+                      checked={promoted}
+                      onChange={(e) => setParameterPromoted(node.id, param.name, e.target.checked)}
+                    />
+                    Expose as input
+                  </label>
+                </div>
+              );
+            })}
           </div>
         </div>
       )}
@@ -22,6 +22,7 @@ interface GraphStore {
   addNode: (node: FilterNode) => void;
   removeNode: (nodeId: string) => void;
   updateNodeData: (nodeId: string, data: Partial<FilterNodeData>) => void;
+  setParameterPromoted: (nodeId: string, paramName: string, promoted: boolean) => void;
   setSelectedNode: (nodeId: string | null) => void;
   loadGraph: (state: GraphState) => void;
   clearGraph: () => void;
@@ -95,6 +96,25 @@ export const useGraphStore = create<GraphStore>((set, get) => ({
     });
   },
 
+  setParameterPromoted: (nodeId, paramName, promoted) => {
+    set({
+      nodes: get().nodes.map((node) => {
+        if (node.id !== nodeId) return node;
+        const current = node.data.promotedParameters ?? [];
+        const promotedParameters = promoted
+          ? [...current.filter((name) => name !== paramName), paramName]
+          : current.filter((name) => name !== paramName);
+        return { ...node, data: { ...node.data, promotedParameters } };
+      }),
+      // Demoting drops the connection that fed the parameter
+      edges: promoted
+        ? get().edges
+        : get().edges.filter(
+            (e) => !(e.target === nodeId && e.targetHandle === paramName)
+          ),
+    });
+  },
+
   setSelectedNode: (nodeId) => {
     set({ selectedNode: nodeId });
   },
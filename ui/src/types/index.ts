@@ -53,6 +53,8 @@ export interface FilterInfo {
   name: string;
   description: string;
   category: FilterCategory;
+  /** Filter version; recorded on nodes so saved graphs can be migrated. */
+  version: string;
   inputs: PortDefinition[];
   outputs: PortDefinition[];
   parameters: ParameterInfo[];
@@ -68,6 +70,108 @@ export interface PluginInfo {
   healthy: boolean;
   filterCount: number;
   loadedForMs: number;
+  signature: PluginSignatureStatus;
+  /** Init configuration; secret entries are omitted. */
+  config: Record<string, unknown>;
+  health: PluginHealthHistory;
+}
+
+export interface PluginHealthSample {
+  healthy: boolean;
+  latency_ms: number;
+  reason: string | null;
+  checked_at_ms: number;
+}
+
+export interface PluginHealthHistory {
+  total_checks: number;
+  total_failures: number;
+  consecutive_failures: number;
+  consecutive_successes: number;
+  last_latency_ms: number | null;
+  average_latency_ms: number | null;
+  recent: PluginHealthSample[];
+  /** Set while the plugin is quarantined; its filters fail fast. */
+  quarantine: { reason: string; since_ms: number; manual: boolean } | null;
+}
+
+/** Payload of the `plugin-health` event. */
+export interface PluginHealthEvent {
+  pluginId: string;
+  transition: 'quarantined' | 'released';
+  reason: string | null;
+}
+
+/** How a filter's metadata changed across a plugin reload. */
+export type MetadataCompatibility =
+  | { status: 'unchanged' }
+  | { status: 'added' }
+  | { status: 'compatible'; changes: string[] }
+  | { status: 'incompatible'; reasons: string[] }
+  | { status: 'removed' };
+
+/** What a hot reload changed, keyed by the plugin's own filter IDs. */
+export interface PluginReloadReport {
+  plugin_id: string;
+  library_path: string;
+  previous_version: string | null;
+  current_version: string | null;
+  filters: Record<string, MetadataCompatibility>;
+}
+
+/** Payload of the `plugin-reloaded` event. */
+export interface PluginReloadEvent {
+  pluginDir: string;
+  report: PluginReloadReport | null;
+  error: string | null;
+}
+
+export type PluginSettingUiHint =
+  | { widget: 'Default' }
+  | { widget: 'Slider'; options: { logarithmic: boolean } }
+  | { widget: 'Dropdown'; options: { options: string[] } }
+  | { widget: 'TextInput'; options: { multiline: boolean; placeholder: string | null } }
+  | { widget: string; options?: unknown };
+
+export interface PluginSettingField {
+  name: string;
+  displayName: string;
+  description: string;
+  group: string | null;
+  valueType: 'string' | 'integer' | 'float' | 'boolean';
+  defaultValue: unknown;
+  /** Current value; always null for secrets. */
+  value: unknown | null;
+  hasValue: boolean;
+  required: boolean;
+  secret: boolean;
+  min: number | null;
+  max: number | null;
+  options: unknown[];
+  uiHint: PluginSettingUiHint;
+}
+
+/** Dropdown options a ComfyUI server offers for one filter parameter. */
+export interface ComfyParameterDropdown {
+  filterId: string;
+  parameter: string;
+  uiHint: PluginSettingUiHint;
+}
+
+export interface PluginSettingsForm {
+  pluginId: string;
+  fields: PluginSettingField[];
+}
+
+export type PluginSignatureStatus =
+  | { status: 'not_checked' }
+  | { status: 'unsigned' }
+  | { status: 'verified'; publisher: string }
+  | { status: 'failed'; reason: string };
+
+export interface PluginVerificationFailure {
+  libraryPath: string;
+  error: string;
 }
 
 export interface ExternalApiCapabilities {
@@ -123,6 +227,10 @@ export interface FilterNodeData {
   inputs: PortDefinition[];
   outputs: PortDefinition[];
   parameters: ParameterValue[];
+  // Parameters exposed as input sockets
+  promotedParameters?: string[];
+  // Version of the filter the parameters were written for
+  filterVersion?: string;
   isValid?: boolean;
   errorMessage?: string;
   // Preview data (for preview nodes)